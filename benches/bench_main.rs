@@ -1,27 +1,32 @@
 use divan::{black_box, Bencher};
 use kontor_crypto::{
-    api::{self, Challenge, FieldElement, PorSystem},
-    build_tree, config,
+    api::{self, FieldElement, PorSystem},
+    build_tree,
+    circuit::{gadgets::poseidon_hash_tagged_gadget, PorCircuit},
+    config,
     erasure::encode_file_symbols,
-    poseidon::{domain_tags, poseidon_hash_tagged},
-    FileLedger,
+    merkle::{
+        build_tree_from_leaves, build_tree_streaming_with_retention, get_padded_proof_for_leaf,
+        LayerRetention,
+    },
+    poseidon::{self, domain_tags, poseidon_hash_tagged},
 };
-use rand::{rngs::StdRng, RngCore, SeedableRng};
+use nova_snark::frontend::{
+    gadgets::num::AllocatedNum, util_cs::test_cs::TestConstraintSystem, ConstraintSystem,
+};
+use nova_snark::traits::circuit::StepCircuit;
+use tracing_subscriber::EnvFilter;
+
+mod common;
+mod epoch;
+
+use common::{generate_test_data, setup_proving_fixture, setup_verification_fixture};
 
 fn main() {
     // Run benchmarks
     divan::main();
 }
 
-// --- Helpers ---
-
-fn generate_test_data(size: usize, seed: u64) -> Vec<u8> {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let mut data = vec![0u8; size];
-    rng.fill_bytes(&mut data);
-    data
-}
-
 // --- Primitives ---
 
 mod primitives {
@@ -45,6 +50,70 @@ mod primitives {
         ));
     }
 
+    /// One tagged hash synthesized in-circuit, which reuses the cached circuit constants.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1)
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 10, sample_size = 10)
+    )]
+    fn poseidon_hash_gadget(bencher: Bencher) {
+        poseidon::warm_up();
+        bencher.bench(|| {
+            let mut cs = TestConstraintSystem::<FieldElement>::new();
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || {
+                Ok(FieldElement::from(config::TEST_RANDOM_SEED))
+            })
+            .unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(FieldElement::from(123u64)))
+                .unwrap();
+            black_box(
+                poseidon_hash_tagged_gadget(cs.namespace(|| "hash"), domain_tags::node(), &a, &b)
+                    .unwrap(),
+            );
+        });
+    }
+
+    /// Synthesis of a four-slot step (the per-file hashing loop) with no subscriber, a
+    /// subscriber filtering out debug events, and one recording them. The first two should
+    /// match: disabled events do not evaluate or format their fields.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1, args = ["none", "info"])
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 10, sample_size = 1, args = ["none", "info", "debug"])
+    )]
+    fn step_synthesis(bencher: Bencher, subscriber_level: &str) {
+        poseidon::warm_up();
+        let circuit = PorCircuit::<FieldElement>::new(config::Shape::new(4, 10, 2), None);
+        let synthesize = || {
+            let mut cs = TestConstraintSystem::<FieldElement>::new();
+            let z: Vec<_> = (0..circuit.arity())
+                .map(|i| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("z{i}")), || {
+                        Ok(FieldElement::from(0u64))
+                    })
+                    .unwrap()
+                })
+                .collect();
+            black_box(circuit.synthesize(&mut cs, &z).unwrap());
+        };
+        match subscriber_level {
+            "none" => bencher.bench(synthesize),
+            level => {
+                let subscriber = tracing_subscriber::fmt()
+                    .with_env_filter(EnvFilter::new(format!("kontor_crypto={level}")))
+                    .with_writer(std::io::sink)
+                    .finish();
+                tracing::subscriber::with_default(subscriber, || bencher.bench(synthesize));
+            }
+        }
+    }
+
     #[cfg_attr(
         feature = "bench-smoke",
         divan::bench(sample_count = 1, sample_size = 1, args = [16])
@@ -65,6 +134,64 @@ mod primitives {
             });
     }
 
+    /// Streaming construction keeping every layer, over 4M leaves (1024 in smoke runs).
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1, args = [1 << 10])
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 1, sample_size = 1, args = [1 << 22])
+    )]
+    fn merkle_build_streaming(bencher: Bencher, num_leaves: usize) {
+        bencher.bench(|| {
+            let leaves = (0..num_leaves as u64).map(FieldElement::from);
+            build_tree_streaming_with_retention(leaves, Some(num_leaves), LayerRetention::Full)
+                .unwrap()
+        });
+    }
+
+    /// Streaming construction keeping only the leaves and the root.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1, args = [1 << 10])
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 1, sample_size = 1, args = [1 << 22])
+    )]
+    fn merkle_build_streaming_leaves_and_root(bencher: Bencher, num_leaves: usize) {
+        bencher.bench(|| {
+            let leaves = (0..num_leaves as u64).map(FieldElement::from);
+            build_tree_streaming_with_retention(
+                leaves,
+                Some(num_leaves),
+                LayerRetention::LeavesAndRoot,
+            )
+            .unwrap()
+        });
+    }
+
+    /// Authentication paths from a 1024-leaf tree keeping `keep_top_levels` interior
+    /// layers; 10 keeps the whole tree.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1, args = [0])
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 10, sample_size = 10, args = [0, 4, 7, 10])
+    )]
+    fn merkle_proof_pruned(bencher: Bencher, keep_top_levels: usize) {
+        let leaves: Vec<FieldElement> = (0..1024u64).map(FieldElement::from).collect();
+        let mut tree = build_tree_from_leaves(&leaves).unwrap();
+        tree.prune(keep_top_levels);
+        let depth = tree.layers.len() - 1;
+
+        bencher
+            .bench(|| get_padded_proof_for_leaf(black_box(&tree), black_box(517), depth).unwrap());
+    }
+
     #[cfg_attr(
         feature = "bench-smoke",
         divan::bench(sample_count = 1, sample_size = 1, args = [10])
@@ -80,6 +207,62 @@ mod primitives {
                 encode_file_symbols(black_box(&data)).unwrap();
             });
     }
+
+    /// Encode and decode throughput of each Reed-Solomon field. GF(2^16) codewords hold
+    /// 56 KiB of data, so the input fills many of them.
+    mod erasure_fields {
+        use super::*;
+        use divan::counter::BytesCount;
+        use kontor_crypto::{
+            api::ErasureField,
+            erasure::{decode_file_symbols_with, encode_file_symbols_with, ErasureConfig},
+        };
+
+        #[cfg(feature = "bench-smoke")]
+        const SIZE: usize = 300 * 1024;
+        #[cfg(not(feature = "bench-smoke"))]
+        const SIZE: usize = 4 << 20;
+
+        #[divan::bench(
+            sample_count = 1,
+            sample_size = 1,
+            args = [ErasureField::Gf8, ErasureField::Gf16]
+        )]
+        fn encode(bencher: Bencher, field: ErasureField) {
+            let config = ErasureConfig::field(field);
+            bencher
+                .counter(BytesCount::new(SIZE))
+                .with_inputs(|| generate_test_data(SIZE, 42))
+                .bench_values(|data| {
+                    encode_file_symbols_with(black_box(&data), config).unwrap();
+                });
+        }
+
+        /// Decoding with the full parity budget of every codeword lost from its start.
+        #[divan::bench(
+            sample_count = 1,
+            sample_size = 1,
+            args = [ErasureField::Gf8, ErasureField::Gf16]
+        )]
+        fn decode(bencher: Bencher, field: ErasureField) {
+            let config = ErasureConfig::field(field);
+            let code = config.code();
+            let symbols = encode_file_symbols_with(&generate_test_data(SIZE, 42), config).unwrap();
+            let num_codewords = symbols.len() / code.total_symbols_per_codeword();
+            let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+            for codeword in damaged.chunks_mut(code.total_symbols_per_codeword()) {
+                codeword[..code.parity_symbols_per_codeword()].fill(None);
+            }
+
+            bencher
+                .counter(BytesCount::new(SIZE))
+                .with_inputs(|| damaged.clone())
+                .bench_values(|mut damaged| {
+                    decode_file_symbols_with(black_box(&mut damaged), num_codewords, SIZE, config)
+                        .unwrap();
+                });
+        }
+    }
 }
 
 // --- File Preparation ---
@@ -105,38 +288,6 @@ fn file_preparation(bencher: Bencher, size_kb: usize) {
 mod proving {
     use super::*;
 
-    /// Pre-generate ledger, files, and challenges for proving benchmark.
-    /// This avoids regenerating expensive fixtures on each iteration.
-    fn setup_proving_fixture(
-        size_kb: usize,
-        num_files: usize,
-        num_challenges: usize,
-    ) -> (FileLedger, Vec<api::PreparedFile>, Vec<Challenge>) {
-        let mut prepared_files = Vec::new();
-        let mut challenges = Vec::new();
-        let mut ledger = FileLedger::new();
-
-        for i in 0..num_files {
-            let data = generate_test_data(size_kb * 1024, 42 + i as u64);
-            let (prepared, metadata) = api::prepare_file(&data, &format!("f{}", i)).unwrap();
-
-            ledger.add_file(&metadata).unwrap();
-
-            let challenge = Challenge::new(
-                metadata,
-                1000,
-                num_challenges,
-                FieldElement::from(config::TEST_RANDOM_SEED),
-                String::from("bench"),
-            );
-
-            prepared_files.push(prepared);
-            challenges.push(challenge);
-        }
-
-        (ledger, prepared_files, challenges)
-    }
-
     #[cfg_attr(
         feature = "bench-smoke",
         divan::bench(sample_count = 1, sample_size = 1, args = [(10, 1, 2)]) // 10KB, 1 file, 2 challenges
@@ -175,32 +326,6 @@ mod proving {
 mod verification {
     use super::*;
 
-    /// Pre-generate proof and fixtures for verification benchmark.
-    /// This avoids regenerating expensive proofs on each iteration.
-    fn setup_verification_fixture(
-        num_challenges: usize,
-    ) -> (FileLedger, api::Proof, Vec<Challenge>) {
-        let data = generate_test_data(16 * 1024, 42);
-        let (prepared, metadata) = api::prepare_file(&data, "v.dat").unwrap();
-        let mut ledger = FileLedger::new();
-        ledger.add_file(&metadata).unwrap();
-
-        let challenge = Challenge::new(
-            metadata,
-            1000,
-            num_challenges,
-            FieldElement::from(config::TEST_RANDOM_SEED),
-            "v".into(),
-        );
-
-        let system = PorSystem::new(&ledger);
-        let proof = system
-            .prove(vec![&prepared], std::slice::from_ref(&challenge))
-            .unwrap();
-
-        (ledger, proof, vec![challenge])
-    }
-
     #[cfg_attr(
         feature = "bench-smoke",
         divan::bench(sample_count = 1, sample_size = 1, args = [2])
@@ -221,3 +346,113 @@ mod verification {
         });
     }
 }
+
+// --- Parameter cache ---
+
+mod params_cache {
+    use super::*;
+    use kontor_crypto::params::{self, Shape};
+    use kontor_crypto::reporting::{current_rss_bytes, RssSampler, RSS_SAMPLE_INTERVAL};
+
+    fn shape() -> Shape {
+        Shape::new(1, 4, 0)
+    }
+
+    /// Path of the cached parameters of [`shape`], generated on first use.
+    fn cached_shape() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("kontor_bench_params_{}", std::process::id()));
+        params::set_cache_dir(Some(dir));
+        params::load_or_generate_params_for_shape(&shape()).unwrap();
+        params::cache_file_path(&shape()).unwrap()
+    }
+
+    /// A full load from disk, which streams the file rather than buffering it. The peak RSS
+    /// growth of one load is printed next to the file size.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1)
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 10, sample_size = 1)
+    )]
+    fn load_from_disk(bencher: Bencher) {
+        let path = cached_shape();
+
+        params::clear_memory_cache();
+        let before = current_rss_bytes();
+        let sampler = RssSampler::start(RSS_SAMPLE_INTERVAL);
+        let loaded = params::load_or_generate_params_for_shape(&shape()).unwrap();
+        if let (Some(before), Some(peak)) = (before, sampler.finish()) {
+            println!(
+                "load_from_disk: file {} bytes, peak RSS growth {} bytes",
+                std::fs::metadata(&path).unwrap().len(),
+                peak.saturating_sub(before)
+            );
+        }
+        drop(loaded);
+
+        bencher.bench(|| {
+            params::clear_memory_cache();
+            params::load_or_generate_params_for_shape(black_box(&shape())).unwrap()
+        });
+    }
+
+    /// Reading just the shape of a cache file.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1)
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 100, sample_size = 10)
+    )]
+    fn peek_header(bencher: Bencher) {
+        let path = cached_shape();
+
+        bencher.bench(|| params::peek_header(black_box(&path)).unwrap());
+    }
+}
+
+// --- Ledger ---
+
+mod ledger {
+    use super::*;
+    use kontor_crypto::FileLedger;
+
+    /// A ledger of `num_files` metadata-only files and the rc of the one in the middle.
+    fn ledger_with_files(num_files: usize) -> (FileLedger, FieldElement) {
+        let metadatas: Vec<api::FileMetadata> = (0..num_files)
+            .map(|i| api::FileMetadata {
+                root: FieldElement::from(i as u64 + 1),
+                file_id: format!("file_{:06}", i),
+                padded_len: 1 << 10,
+                original_size: 1000,
+                annotations: Default::default(),
+                tree_arity: api::TreeArity::Binary,
+                erasure: Some(api::ErasureCode::ReedSolomon),
+                salt_commitment: None,
+                hash_profile: Default::default(),
+            })
+            .collect();
+        let mut ledger = FileLedger::new();
+        ledger.add_files(&metadatas).unwrap();
+        let (_, rc) = ledger.lookup(&metadatas[num_files / 2].file_id).unwrap();
+        (ledger, rc)
+    }
+
+    /// Resolving an rc to its file and index, which should not grow with the ledger.
+    #[cfg_attr(
+        feature = "bench-smoke",
+        divan::bench(sample_count = 1, sample_size = 1, args = [100])
+    )]
+    #[cfg_attr(
+        not(feature = "bench-smoke"),
+        divan::bench(sample_count = 100, sample_size = 100, args = [100, 1_000, 10_000])
+    )]
+    fn lookup_by_rc(bencher: Bencher, num_files: usize) {
+        let (ledger, rc) = ledger_with_files(num_files);
+
+        bencher.bench(|| ledger.lookup_by_rc(black_box(rc)).unwrap());
+    }
+}
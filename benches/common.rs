@@ -0,0 +1,91 @@
+//! Shared fixtures for the benchmark suite.
+//!
+//! Fixtures are generated once, outside the benchmark loop, so expensive file
+//! preparation and proving does not count towards the measured phase.
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    config, FileLedger,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+pub fn generate_test_data(size: usize, seed: u64) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = vec![0u8; size];
+    rng.fill_bytes(&mut data);
+    data
+}
+
+/// Pre-generate ledger, files, and challenges for proving benchmarks.
+/// Every file in the ledger is challenged.
+pub fn setup_proving_fixture(
+    size_kb: usize,
+    num_files: usize,
+    num_challenges: usize,
+) -> (FileLedger, Vec<api::PreparedFile>, Vec<Challenge>) {
+    setup_ledger_fixture(size_kb, num_files, num_files, num_challenges)
+}
+
+/// Pre-generate a ledger of `ledger_files` files of which the first `challenged_files`
+/// are challenged with `num_challenges` sectors each.
+///
+/// Only the prepared files of challenged files are returned.
+pub fn setup_ledger_fixture(
+    size_kb: usize,
+    ledger_files: usize,
+    challenged_files: usize,
+    num_challenges: usize,
+) -> (FileLedger, Vec<api::PreparedFile>, Vec<Challenge>) {
+    assert!(challenged_files <= ledger_files);
+
+    let mut prepared_files = Vec::new();
+    let mut challenges = Vec::new();
+    let mut ledger = FileLedger::new();
+
+    for i in 0..ledger_files {
+        let data = generate_test_data(size_kb * 1024, 42 + i as u64);
+        let (prepared, metadata) = api::prepare_file(&data, &format!("f{}", i)).unwrap();
+
+        ledger.add_file(&metadata).unwrap();
+
+        if i < challenged_files {
+            let challenge = Challenge::new(
+                metadata,
+                1000,
+                num_challenges,
+                FieldElement::from(config::TEST_RANDOM_SEED),
+                String::from("bench"),
+            );
+
+            prepared_files.push(prepared);
+            challenges.push(challenge);
+        }
+    }
+
+    (ledger, prepared_files, challenges)
+}
+
+/// Pre-generate proof and fixtures for verification benchmarks.
+pub fn setup_verification_fixture(
+    num_challenges: usize,
+) -> (FileLedger, api::Proof, Vec<Challenge>) {
+    let data = generate_test_data(16 * 1024, 42);
+    let (prepared, metadata) = api::prepare_file(&data, "v.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    let challenge = Challenge::new(
+        metadata,
+        1000,
+        num_challenges,
+        FieldElement::from(config::TEST_RANDOM_SEED),
+        "v".into(),
+    );
+
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+
+    (ledger, proof, vec![challenge])
+}
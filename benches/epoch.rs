@@ -0,0 +1,196 @@
+//! End-to-end epoch simulation.
+//!
+//! Each benchmark is parameterized over `(N, M, K)`: `N` files in the ledger, `M` of
+//! them challenged, `K` sectors per challenged file. The phases of an epoch (ledger
+//! update, parameter load, prove, serialize, verify against a historical root) are
+//! measured separately; sector-based phases report throughput in sectors/second.
+
+use super::*;
+use divan::counter::{BytesCount, ItemsCount};
+use kontor_crypto::{params, FileLedger};
+
+use crate::common::setup_ledger_fixture;
+
+/// Size of every file in the epoch fixtures.
+const FILE_SIZE_KB: usize = 10;
+
+/// Number of challenged sectors in an epoch.
+fn sectors(args: (usize, usize, usize)) -> usize {
+    let (_, challenged_files, num_challenges) = args;
+    challenged_files * num_challenges
+}
+
+/// Prepare the metadata of the file added at the start of the next epoch.
+fn next_epoch_file(ledger_files: usize) -> api::FileMetadata {
+    let data = generate_test_data(FILE_SIZE_KB * 1024, 10_000 + ledger_files as u64);
+    api::prepare_file(&data, "next_epoch.dat").unwrap().1
+}
+
+#[cfg_attr(
+    feature = "bench-smoke",
+    divan::bench(sample_count = 1, sample_size = 1, args = [(2, 2, 2)])
+)]
+#[cfg_attr(
+    not(feature = "bench-smoke"),
+    divan::bench(
+        sample_count = 10,
+        sample_size = 1,
+        args = [
+            (4, 2, 10),  // 4 files, 2 challenged, 10 sectors each
+            (16, 4, 10), // 16 files, 4 challenged, 10 sectors each
+            (64, 4, 10), // 64 files, 4 challenged, 10 sectors each
+        ]
+    )
+)]
+fn ledger_add(bencher: Bencher, args: (usize, usize, usize)) {
+    let (ledger_files, challenged_files, num_challenges) = args;
+    let (ledger, _, _) =
+        setup_ledger_fixture(FILE_SIZE_KB, ledger_files, challenged_files, num_challenges);
+    let metadata = next_epoch_file(ledger_files);
+
+    bencher
+        .counter(ItemsCount::new(1usize))
+        .with_inputs(|| ledger.clone())
+        .bench_local_values(|mut ledger: FileLedger| {
+            ledger.add_file(black_box(&metadata)).unwrap();
+            ledger
+        });
+}
+
+#[cfg_attr(
+    feature = "bench-smoke",
+    divan::bench(sample_count = 1, sample_size = 1, args = [(2, 2, 2)])
+)]
+#[cfg_attr(
+    not(feature = "bench-smoke"),
+    divan::bench(
+        sample_count = 10,
+        sample_size = 10,
+        args = [(4, 2, 10), (16, 4, 10), (64, 4, 10)]
+    )
+)]
+fn param_load(bencher: Bencher, args: (usize, usize, usize)) {
+    let (ledger_files, challenged_files, num_challenges) = args;
+    let (ledger, _, challenges) =
+        setup_ledger_fixture(FILE_SIZE_KB, ledger_files, challenged_files, num_challenges);
+
+    let max_file_depth = challenges
+        .iter()
+        .map(|c| api::tree_depth_from_metadata(&c.file_metadata))
+        .max()
+        .unwrap_or(1);
+    let (files_per_step, file_tree_depth) = config::derive_shape(challenges.len(), max_file_depth);
+    let aggregated_tree_depth = if files_per_step > 1 {
+        ledger.depth()
+    } else {
+        0
+    };
+
+    // Generate once so the benchmark measures a cached load, as seen by every epoch
+    // after the first
+    params::load_or_generate_params(config::Shape::new(
+        files_per_step,
+        file_tree_depth,
+        aggregated_tree_depth,
+    ))
+    .unwrap();
+
+    bencher.bench(|| {
+        params::load_or_generate_params(config::Shape::new(
+            black_box(files_per_step),
+            black_box(file_tree_depth),
+            black_box(aggregated_tree_depth),
+        ))
+        .unwrap()
+    });
+}
+
+#[cfg_attr(
+    feature = "bench-smoke",
+    divan::bench(sample_count = 1, sample_size = 1, args = [(2, 2, 2)])
+)]
+#[cfg_attr(
+    not(feature = "bench-smoke"),
+    divan::bench(
+        sample_count = 1,
+        sample_size = 1,
+        args = [(4, 2, 10), (16, 4, 10), (64, 4, 10)]
+    )
+)]
+fn prove(bencher: Bencher, args: (usize, usize, usize)) {
+    let (ledger_files, challenged_files, num_challenges) = args;
+    let (ledger, files, challenges) =
+        setup_ledger_fixture(FILE_SIZE_KB, ledger_files, challenged_files, num_challenges);
+    let system = PorSystem::new(&ledger);
+
+    // Warm the parameter cache and report the phase breakdown of a warm proof
+    let files_ref: Vec<&_> = files.iter().collect();
+    system.prove(files_ref.clone(), &challenges).unwrap();
+    let (_, timings) = system.prove_with_timings(files_ref, &challenges).unwrap();
+    println!("epoch {:?}: {:?}", args, timings);
+
+    bencher.counter(ItemsCount::new(sectors(args))).bench(|| {
+        let files_ref: Vec<&_> = files.iter().collect();
+        system
+            .prove(black_box(files_ref), black_box(&challenges))
+            .unwrap()
+    });
+}
+
+#[cfg_attr(
+    feature = "bench-smoke",
+    divan::bench(sample_count = 1, sample_size = 1, args = [(2, 2, 2)])
+)]
+#[cfg_attr(
+    not(feature = "bench-smoke"),
+    divan::bench(
+        sample_count = 10,
+        sample_size = 10,
+        args = [(4, 2, 10), (16, 4, 10), (64, 4, 10)]
+    )
+)]
+fn serialize(bencher: Bencher, args: (usize, usize, usize)) {
+    let (ledger_files, challenged_files, num_challenges) = args;
+    let (ledger, files, challenges) =
+        setup_ledger_fixture(FILE_SIZE_KB, ledger_files, challenged_files, num_challenges);
+    let proof = PorSystem::new(&ledger)
+        .prove(files.iter().collect(), &challenges)
+        .unwrap();
+    let proof_len = proof.to_bytes().unwrap().len();
+
+    bencher
+        .counter(BytesCount::new(proof_len))
+        .bench(|| black_box(&proof).to_bytes().unwrap());
+}
+
+#[cfg_attr(
+    feature = "bench-smoke",
+    divan::bench(sample_count = 1, sample_size = 1, args = [(2, 2, 2)])
+)]
+#[cfg_attr(
+    not(feature = "bench-smoke"),
+    divan::bench(
+        sample_count = 10,
+        sample_size = 10,
+        args = [(4, 2, 10), (16, 4, 10), (64, 4, 10)]
+    )
+)]
+fn verify_historical_root(bencher: Bencher, args: (usize, usize, usize)) {
+    let (ledger_files, challenged_files, num_challenges) = args;
+    let (mut ledger, files, challenges) =
+        setup_ledger_fixture(FILE_SIZE_KB, ledger_files, challenged_files, num_challenges);
+    let proof = PorSystem::new(&ledger)
+        .prove(files.iter().collect(), &challenges)
+        .unwrap();
+
+    // The next epoch's ledger update moves the proof's root into the historical set
+    ledger.add_file(&next_epoch_file(ledger_files)).unwrap();
+    assert_ne!(ledger.root(), proof.ledger_root);
+
+    let system = PorSystem::new(&ledger);
+    bencher.counter(ItemsCount::new(sectors(args))).bench(|| {
+        assert!(system
+            .verify(black_box(&proof), black_box(&challenges))
+            .unwrap());
+    });
+}
@@ -0,0 +1,256 @@
+//! Aggregation of independently generated proofs.
+//!
+//! Storage nodes prove their own files separately against the same ledger root. An
+//! [`AggregatedProof`] wraps those proofs into one object for the chain: it carries each
+//! inner proof unchanged plus a SHA-256 commitment over their canonical encodings.
+//! [`crate::api::PorSystem::verify_aggregated`] checks the commitment and then verifies
+//! every inner proof against its own challenge set.
+//!
+//! Inner proofs are not folded together: proofs with different shapes or seeds can be
+//! aggregated as long as all ledger-bound proofs share one ledger root.
+
+use super::types::{Challenge, FieldElement, Proof, ProofBinding};
+use crate::instrumentation::debug_span;
+use crate::{config, KontorPoRError, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// Domain separator for the aggregate commitment
+const AGGREGATE_DOMAIN: &[u8] = b"KONTOR_POR_AGGREGATE_V1";
+
+/// Constants for aggregated proof serialization format
+mod aggregate_format {
+    /// Magic bytes identifying an aggregated proof
+    pub const MAGIC: &[u8] = b"NPAG";
+
+    /// Current format version
+    pub const VERSION: u16 = 1;
+
+    /// Header size in bytes: magic(4) + version(2) + commitment(32) + count(4)
+    pub const HEADER_SIZE: usize = 42;
+}
+
+/// Several independently generated proofs bundled for a single verification.
+pub struct AggregatedProof {
+    /// Inner proofs, in the order their challenge sets must be supplied
+    pub proofs: Vec<Proof>,
+    /// SHA-256 commitment over the canonical encoding of every inner proof
+    pub commitment: [u8; 32],
+}
+
+impl AggregatedProof {
+    /// The ledger root shared by all ledger-bound inner proofs, if any.
+    ///
+    /// File-root-bound proofs carry their file root instead of a ledger root and do not
+    /// contribute.
+    pub fn ledger_root(&self) -> Option<FieldElement> {
+        self.proofs
+            .iter()
+            .find(|proof| proof.binding().is_ledger())
+            .map(|proof| proof.ledger_root)
+    }
+
+    /// Checks the aggregate's structure and commitment against the given challenge sets.
+    ///
+    /// Returns an error for structural mismatches (see [`aggregate_proofs`]) and
+    /// `Ok(false)` if the commitment does not match the inner proofs.
+    pub(crate) fn check_commitment(&self, challenge_sets: &[Vec<Challenge>]) -> Result<bool> {
+        check_aggregate_inputs(&self.proofs, challenge_sets)?;
+        Ok(aggregate_commitment(&self.proofs)? == self.commitment)
+    }
+
+    /// Serialize this aggregated proof to bytes.
+    ///
+    /// Format: magic, version (u16 LE), commitment, proof count (u32 LE), then each inner
+    /// proof as a u32 LE length followed by its [`Proof::to_bytes`] encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut result = Vec::new();
+        result.extend_from_slice(aggregate_format::MAGIC);
+        result.extend_from_slice(&aggregate_format::VERSION.to_le_bytes());
+        result.extend_from_slice(&self.commitment);
+        result.extend_from_slice(&(self.proofs.len() as u32).to_le_bytes());
+        for proof in &self.proofs {
+            let bytes = proof.to_bytes()?;
+            result.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            result.extend_from_slice(&bytes);
+        }
+        Ok(result)
+    }
+
+    /// Deserialize an aggregated proof produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < aggregate_format::HEADER_SIZE {
+            return Err(KontorPoRError::Serialization(
+                "Aggregated proof bytes too short for header".to_string(),
+            ));
+        }
+        if &bytes[0..4] != aggregate_format::MAGIC {
+            return Err(KontorPoRError::Serialization(
+                "Invalid magic bytes in aggregated proof".to_string(),
+            ));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != aggregate_format::VERSION {
+            return Err(KontorPoRError::Serialization(format!(
+                "Unsupported aggregated proof format version: {}",
+                version
+            )));
+        }
+
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&bytes[6..38]);
+        let count = u32::from_le_bytes([bytes[38], bytes[39], bytes[40], bytes[41]]) as usize;
+
+        let mut offset = aggregate_format::HEADER_SIZE;
+        let mut proofs = Vec::new();
+        for _ in 0..count {
+            let length_bytes = bytes.get(offset..offset + 4).ok_or_else(|| {
+                KontorPoRError::Serialization("Aggregated proof bytes truncated".to_string())
+            })?;
+            let length = u32::from_le_bytes(length_bytes.try_into().expect("4-byte slice"));
+            offset += 4;
+            let proof_bytes = bytes.get(offset..offset + length as usize).ok_or_else(|| {
+                KontorPoRError::Serialization("Aggregated proof bytes truncated".to_string())
+            })?;
+            proofs.push(Proof::from_bytes(proof_bytes)?);
+            offset += length as usize;
+        }
+
+        if offset != bytes.len() {
+            return Err(KontorPoRError::Serialization(
+                "Aggregated proof bytes contain trailing data".to_string(),
+            ));
+        }
+
+        Ok(Self { proofs, commitment })
+    }
+}
+
+/// Bundles independently generated proofs into a single [`AggregatedProof`].
+///
+/// `challenge_sets[i]` must be exactly the challenges `proofs[i]` answers, in order.
+/// Mismatches are detected eagerly, before any commitment is computed:
+/// - proof/challenge set counts differ, or a set is empty
+/// - a proof's challenge IDs do not match its set
+/// - a challenge is answered by more than one inner proof
+/// - a proof's shape (slot count, binding) does not fit its set
+/// - ledger-bound proofs disagree on ledger root or aggregated tree depth
+///
+/// Inner SNARKs are not verified here (that needs a ledger); use
+/// [`crate::api::PorSystem::verify_aggregated`].
+pub fn aggregate_proofs(
+    proofs: &[Proof],
+    challenge_sets: &[Vec<Challenge>],
+) -> Result<AggregatedProof> {
+    let _span = debug_span!("aggregate_proofs", num_proofs = proofs.len()).entered();
+
+    check_aggregate_inputs(proofs, challenge_sets)?;
+
+    // Re-encode each proof canonically; this is also what the commitment covers
+    let proofs = proofs
+        .iter()
+        .map(|proof| Proof::from_bytes(&proof.to_bytes()?))
+        .collect::<Result<Vec<_>>>()?;
+    let commitment = aggregate_commitment(&proofs)?;
+
+    Ok(AggregatedProof { proofs, commitment })
+}
+
+/// Structural checks shared by aggregation and verification.
+fn check_aggregate_inputs(proofs: &[Proof], challenge_sets: &[Vec<Challenge>]) -> Result<()> {
+    if proofs.is_empty() {
+        return Err(KontorPoRError::InvalidInput(
+            "Must provide at least one proof to aggregate".to_string(),
+        ));
+    }
+    if proofs.len() != challenge_sets.len() {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "Proof count {} does not match challenge set count {}",
+            proofs.len(),
+            challenge_sets.len()
+        )));
+    }
+
+    let mut seen_ids = HashSet::new();
+    let mut shared_root: Option<(FieldElement, usize)> = None;
+
+    for (i, (proof, challenges)) in proofs.iter().zip(challenge_sets).enumerate() {
+        if challenges.is_empty() {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Challenge set {} is empty",
+                i
+            )));
+        }
+
+        let ids: Vec<_> = challenges.iter().map(|c| c.id()).collect();
+        if proof.challenge_ids != ids {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Proof {} does not answer the challenges in set {}",
+                i, i
+            )));
+        }
+        for id in ids {
+            if !seen_ids.insert(id) {
+                return Err(KontorPoRError::InvalidInput(format!(
+                    "Proof {} answers a challenge already covered by another proof",
+                    i
+                )));
+            }
+        }
+
+        // Shape: slot count (over every pass) and binding must match the challenge set
+        let (files_per_step, num_passes) =
+            config::derive_partition(challenges.len(), challenges[0].max_files_per_step);
+        let is_ledger_bound = ProofBinding::for_challenges(challenges).is_ledger();
+        if proof.ledger_indices.len() != files_per_step * num_passes
+            || is_ledger_bound != proof.binding().is_ledger()
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Proof {} shape ({} slots, aggregated_tree_depth {}) does not match its {} challenges",
+                i,
+                proof.ledger_indices.len(),
+                proof.shape.aggregated_tree_depth,
+                challenges.len()
+            )));
+        }
+
+        // All ledger-bound proofs must be against the same ledger state
+        if is_ledger_bound {
+            match shared_root {
+                None => shared_root = Some((proof.ledger_root, proof.shape.aggregated_tree_depth)),
+                Some((root, depth)) => {
+                    if proof.ledger_root != root {
+                        return Err(KontorPoRError::InvalidLedgerRoot {
+                            proof_root: format!("{:?}", proof.ledger_root),
+                            reason: format!(
+                                "Proof {} uses a different ledger root than the other proofs",
+                                i
+                            ),
+                        });
+                    }
+                    if proof.shape.aggregated_tree_depth != depth {
+                        return Err(KontorPoRError::InvalidInput(format!(
+                            "Proof {} has aggregated_tree_depth {}, expected {}",
+                            i, proof.shape.aggregated_tree_depth, depth
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// SHA-256 over the domain separator, proof count and length-prefixed proof encodings.
+fn aggregate_commitment(proofs: &[Proof]) -> Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(AGGREGATE_DOMAIN);
+    hasher.update((proofs.len() as u32).to_le_bytes());
+    for proof in proofs {
+        let bytes = proof.to_bytes()?;
+        hasher.update((bytes.len() as u32).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(hasher.finalize().into())
+}
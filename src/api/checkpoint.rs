@@ -0,0 +1,293 @@
+//! On-disk checkpoints for long-running proofs.
+//!
+//! A checkpoint stores the in-progress `RecursiveSNARK` together with the chain state and
+//! the index of the next step to fold, so a prover that dies part-way through a proof can
+//! resume instead of starting over. Each checkpoint also records a digest of the plan
+//! (sorted challenge IDs, ledger root and indices, shape and step count) and a digest of
+//! the public parameters; resuming with different inputs fails with
+//! [`KontorPoRError::CheckpointMismatch`] rather than producing a proof that will not verify.
+//!
+//! The file layout follows the parameter cache: magic, version, a bincode body and a
+//! trailing SHA-256 checksum. Checkpoints are written to a temporary file and published
+//! with an atomic rename, so a crash while saving leaves the previous checkpoint intact.
+
+use super::{
+    plan::Plan,
+    types::{FieldElement, PorParams},
+};
+use crate::instrumentation::{debug, info};
+use crate::{
+    merkle::canonical::field_to_bytes,
+    params::{bincode_options, HashingReader, HashingWriter},
+    KontorPoRError, Result,
+};
+use nova_snark::{
+    nova::RecursiveSNARK,
+    provider::{PallasEngine, VestaEngine},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+type E1 = PallasEngine;
+type E2 = VestaEngine;
+type C = crate::circuit::PorCircuit<FieldElement>;
+type NovaProof = RecursiveSNARK<E1, E2, C>;
+
+/// Constants for the on-disk checkpoint format
+mod checkpoint_format {
+    /// Magic bytes identifying a prove checkpoint
+    pub const MAGIC: &[u8] = b"KCKP";
+
+    /// Current format version
+    pub const VERSION: u16 = 1;
+
+    /// Length of the trailing SHA-256 checksum over the serialized checkpoint
+    pub const CHECKSUM_LEN: usize = 32;
+}
+
+/// Digests binding a checkpoint to the plan and parameters it was taken with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CheckpointDigests {
+    plan: [u8; 32],
+    params: [u8; 32],
+}
+
+impl CheckpointDigests {
+    pub(crate) fn new(plan: &Plan, params: &PorParams) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(b"kontor-por-checkpoint-plan");
+        for challenge in &plan.sorted_challenges {
+            hasher.update(challenge.id().0);
+        }
+        hasher.update(field_to_bytes(&plan.aggregated_root));
+        for index in &plan.ledger_indices {
+            hasher.update((*index as u64).to_le_bytes());
+        }
+        hasher.update(field_to_bytes(&plan.initial_state));
+        for value in [
+            plan.shape.files_per_step,
+            plan.shape.file_tree_depth,
+            plan.shape.aggregated_tree_depth,
+            plan.num_steps(),
+        ] {
+            hasher.update((value as u64).to_le_bytes());
+        }
+
+        Self {
+            plan: hasher.finalize().into(),
+            params: field_to_bytes(&params.pp.digest()),
+        }
+    }
+}
+
+/// A checkpoint read back from disk.
+pub(crate) struct Checkpoint {
+    /// Interval the checkpoint was written with, reused when proving continues
+    pub(crate) every_n_steps: usize,
+    /// First step that has not been folded yet
+    pub(crate) next_step: usize,
+    /// Chain state after the last folded step
+    pub(crate) state: FieldElement,
+    pub(crate) recursive_snark: NovaProof,
+}
+
+/// Serialized form of a checkpoint (owned, for reading).
+#[derive(Deserialize)]
+struct StoredCheckpoint {
+    plan_digest: [u8; 32],
+    params_digest: [u8; 32],
+    total_steps: usize,
+    every_n_steps: usize,
+    next_step: usize,
+    #[serde(with = "crate::merkle::canonical::field")]
+    state: FieldElement,
+    recursive_snark: NovaProof,
+}
+
+/// Serialized form of a checkpoint (borrowed, for writing without cloning the SNARK).
+#[derive(Serialize)]
+struct StoredCheckpointRef<'a> {
+    plan_digest: [u8; 32],
+    params_digest: [u8; 32],
+    total_steps: usize,
+    every_n_steps: usize,
+    next_step: usize,
+    #[serde(with = "crate::merkle::canonical::field")]
+    state: FieldElement,
+    recursive_snark: &'a NovaProof,
+}
+
+/// Saves checkpoints for one proof every `every_n_steps` folded steps.
+pub(crate) struct CheckpointWriter<'p> {
+    pub(crate) path: &'p Path,
+    pub(crate) every_n_steps: usize,
+    pub(crate) digests: CheckpointDigests,
+}
+
+impl CheckpointWriter<'_> {
+    /// Called after each folded step with the number of steps folded so far.
+    ///
+    /// Saves a checkpoint when `completed` is a multiple of the interval. No checkpoint is
+    /// taken after the final step, since only compression remains.
+    pub(crate) fn after_step(
+        &self,
+        completed: usize,
+        total_steps: usize,
+        recursive_snark: &NovaProof,
+        state: FieldElement,
+    ) -> Result<()> {
+        if !completed.is_multiple_of(self.every_n_steps) || completed >= total_steps {
+            return Ok(());
+        }
+        write_checkpoint(
+            self.path,
+            &StoredCheckpointRef {
+                plan_digest: self.digests.plan,
+                params_digest: self.digests.params,
+                total_steps,
+                every_n_steps: self.every_n_steps,
+                next_step: completed,
+                state,
+                recursive_snark,
+            },
+        )?;
+        info!(
+            "Saved prove checkpoint after {} of {} steps to {}",
+            completed,
+            total_steps,
+            self.path.display()
+        );
+        Ok(())
+    }
+}
+
+/// Write a checkpoint and its checksum to a temporary file and atomically rename it into place.
+fn write_checkpoint(path: &Path, checkpoint: &StoredCheckpointRef<'_>) -> Result<()> {
+    use bincode::Options;
+
+    let io_err = |e: std::io::Error| {
+        KontorPoRError::IO(format!(
+            "Failed to write checkpoint {}: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&tmp_path).map_err(io_err)?);
+    writer.write_all(checkpoint_format::MAGIC).map_err(io_err)?;
+    writer
+        .write_all(&checkpoint_format::VERSION.to_le_bytes())
+        .map_err(io_err)?;
+
+    let mut hashing = HashingWriter {
+        inner: writer,
+        hasher: Sha256::new(),
+    };
+    bincode_options()
+        .serialize_into(&mut hashing, checkpoint)
+        .map_err(|e| {
+            KontorPoRError::Serialization(format!("Failed to serialize checkpoint: {}", e))
+        })?;
+    let mut writer = hashing.inner;
+    writer
+        .write_all(&hashing.hasher.finalize())
+        .map_err(io_err)?;
+
+    let file = writer.into_inner().map_err(|e| io_err(e.into_error()))?;
+    file.sync_all().map_err(io_err)?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).map_err(io_err)
+}
+
+/// Read the checkpoint at `path` and check that it was taken for the same plan,
+/// parameters and step count.
+pub(crate) fn read_checkpoint(
+    path: &Path,
+    digests: &CheckpointDigests,
+    total_steps: usize,
+) -> Result<Checkpoint> {
+    use bincode::Options;
+
+    let file = File::open(path).map_err(|e| {
+        KontorPoRError::IO(if e.kind() == ErrorKind::NotFound {
+            format!("Checkpoint {} does not exist", path.display())
+        } else {
+            format!("Failed to open checkpoint {}: {}", path.display(), e)
+        })
+    })?;
+    let mut reader = BufReader::new(file);
+    let corrupt = |reason: &str| {
+        KontorPoRError::Serialization(format!("Invalid checkpoint {}: {}", path.display(), reason))
+    };
+
+    let mut header = [0u8; 6];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| corrupt("truncated header"))?;
+    if &header[..4] != checkpoint_format::MAGIC {
+        return Err(corrupt("bad magic bytes"));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != checkpoint_format::VERSION {
+        return Err(corrupt(&format!(
+            "unsupported version {} (expected {})",
+            version,
+            checkpoint_format::VERSION
+        )));
+    }
+
+    let mut hashing = HashingReader {
+        inner: reader,
+        hasher: Sha256::new(),
+    };
+    let stored: StoredCheckpoint = bincode_options()
+        .deserialize_from(&mut hashing)
+        .map_err(|e| crate::merkle::canonical::deserialize_error(e, "checkpoint"))?;
+
+    let computed = hashing.hasher.finalize();
+    let mut reader = hashing.inner;
+    let mut checksum = [0u8; checkpoint_format::CHECKSUM_LEN];
+    let mut trailing = [0u8; 1];
+    if reader.read_exact(&mut checksum).is_err()
+        || checksum[..] != computed[..]
+        || !matches!(reader.read(&mut trailing), Ok(0))
+    {
+        return Err(corrupt("bad checksum"));
+    }
+
+    if stored.params_digest != digests.params {
+        return Err(KontorPoRError::CheckpointMismatch {
+            reason: "public parameters differ from those the checkpoint was taken with".to_string(),
+        });
+    }
+    if stored.plan_digest != digests.plan || stored.total_steps != total_steps {
+        return Err(KontorPoRError::CheckpointMismatch {
+            reason: "challenges or ledger differ from those the checkpoint was taken with"
+                .to_string(),
+        });
+    }
+    if stored.every_n_steps == 0 || stored.next_step == 0 || stored.next_step >= total_steps {
+        return Err(corrupt(&format!(
+            "next step {} is out of range for {} steps",
+            stored.next_step, total_steps
+        )));
+    }
+
+    debug!(
+        "Resuming from checkpoint {} at step {} of {}",
+        path.display(),
+        stored.next_step,
+        total_steps
+    );
+    Ok(Checkpoint {
+        every_n_steps: stored.every_n_steps,
+        next_step: stored.next_step,
+        state: stored.state,
+        recursive_snark: stored.recursive_snark,
+    })
+}
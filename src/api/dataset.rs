@@ -0,0 +1,372 @@
+//! Preparing several files as one logical dataset.
+//!
+//! A dataset is a single blob: a canonical manifest listing every file's path, offset and
+//! size, followed by the files' contents in manifest order. The blob goes through the
+//! normal prepare pipeline, so the ledger, challenges, proofs and verification see one
+//! [`FileMetadata`] with one root. The manifest is part of the committed data and is also
+//! kept in [`DatasetMetadata`], which lets a single file be pulled out of the symbols by
+//! decoding only the codewords it occupies.
+//!
+//! Manifest encoding (all integers little-endian):
+//!
+//! ```text
+//! "KDSM" | version: u16 | count: u32 | count × (path_len: u32 | path | offset: u64 | size: u64)
+//! ```
+//!
+//! Entries are sorted by path, paths are unique and non-empty, the first offset is the
+//! manifest length and each further file starts where the previous one ends.
+
+use super::types::{FileMetadata, PreparedFile};
+use crate::instrumentation::debug_span;
+use crate::{KontorPoRError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Leading bytes of an encoded manifest.
+const MANIFEST_MAGIC: &[u8; 4] = b"KDSM";
+
+/// Manifest encoding version.
+const MANIFEST_VERSION: u16 = 1;
+
+/// Bytes of the magic, version and entry count.
+const MANIFEST_HEADER_LEN: usize = 4 + 2 + 4;
+
+/// Bytes of an entry besides its path: path length, offset and size.
+const ENTRY_FIXED_LEN: usize = 4 + 8 + 8;
+
+/// One file of a dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DatasetEntry {
+    /// Path of the file within the dataset
+    pub path: String,
+    /// Byte offset of the file's contents in the dataset blob
+    pub offset: u64,
+    /// Size of the file in bytes
+    pub size: u64,
+}
+
+/// Canonical listing of a dataset's files, stored at the start of the dataset blob.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    /// Entries sorted by path
+    pub entries: Vec<DatasetEntry>,
+}
+
+impl DatasetManifest {
+    /// Builds the manifest for files of the given paths and sizes, in any order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::InvalidInput`] for an empty or duplicate path.
+    pub fn new<'a>(files: impl IntoIterator<Item = (&'a str, u64)>) -> Result<Self> {
+        let mut files: Vec<(&str, u64)> = files.into_iter().collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+        for pair in files.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(KontorPoRError::InvalidInput(format!(
+                    "dataset path {:?} appears more than once",
+                    pair[0].0
+                )));
+            }
+        }
+        if files.iter().any(|(path, _)| path.is_empty()) {
+            return Err(KontorPoRError::InvalidInput(
+                "dataset paths must not be empty".to_string(),
+            ));
+        }
+        if files
+            .iter()
+            .any(|(path, _)| u32::try_from(path.len()).is_err())
+        {
+            return Err(KontorPoRError::InvalidInput(
+                "dataset path is too long".to_string(),
+            ));
+        }
+
+        let encoded_len: usize = MANIFEST_HEADER_LEN
+            + files
+                .iter()
+                .map(|(path, _)| ENTRY_FIXED_LEN + path.len())
+                .sum::<usize>();
+        let mut offset = encoded_len as u64;
+        let mut entries = Vec::with_capacity(files.len());
+        for (path, size) in files {
+            entries.push(DatasetEntry {
+                path: path.to_string(),
+                offset,
+                size,
+            });
+            offset = offset.checked_add(size).ok_or_else(|| {
+                KontorPoRError::InvalidInput("dataset size overflows u64".to_string())
+            })?;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Length of the encoded manifest, which is where the first file starts.
+    pub fn encoded_len(&self) -> usize {
+        MANIFEST_HEADER_LEN
+            + self
+                .entries
+                .iter()
+                .map(|entry| ENTRY_FIXED_LEN + entry.path.len())
+                .sum::<usize>()
+    }
+
+    /// Total size of the dataset blob: the manifest followed by every file.
+    pub fn total_size(&self) -> u64 {
+        self.encoded_len() as u64 + self.entries.iter().map(|entry| entry.size).sum::<u64>()
+    }
+
+    /// The entry for `path`, if the dataset contains it.
+    pub fn entry(&self, path: &str) -> Option<&DatasetEntry> {
+        self.entries
+            .binary_search_by(|entry| entry.path.as_str().cmp(path))
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+
+    /// Encodes the manifest in its canonical form.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        out.extend_from_slice(MANIFEST_MAGIC);
+        out.extend_from_slice(&MANIFEST_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            out.extend_from_slice(&(entry.path.len() as u32).to_le_bytes());
+            out.extend_from_slice(entry.path.as_bytes());
+            out.extend_from_slice(&entry.offset.to_le_bytes());
+            out.extend_from_slice(&entry.size.to_le_bytes());
+        }
+        out
+    }
+
+    /// Decodes a manifest from the start of `bytes`; trailing bytes (the files) are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::Serialization`] if the bytes are truncated, carry another
+    /// magic or version, or describe a manifest that is not canonical.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ManifestReader { bytes, pos: 0 };
+        if reader.take(4)? != MANIFEST_MAGIC {
+            return Err(KontorPoRError::Serialization(
+                "not a dataset manifest".to_string(),
+            ));
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != MANIFEST_VERSION {
+            return Err(KontorPoRError::Serialization(format!(
+                "unsupported dataset manifest version {} (expected {})",
+                version, MANIFEST_VERSION
+            )));
+        }
+
+        let count = u32::from_le_bytes(reader.array()?) as usize;
+        let mut files = Vec::with_capacity(count.min(bytes.len() / ENTRY_FIXED_LEN));
+        for _ in 0..count {
+            let path_len = u32::from_le_bytes(reader.array()?) as usize;
+            let path = std::str::from_utf8(reader.take(path_len)?).map_err(|_| {
+                KontorPoRError::Serialization("dataset path is not UTF-8".to_string())
+            })?;
+            let offset = u64::from_le_bytes(reader.array()?);
+            let size = u64::from_le_bytes(reader.array()?);
+            files.push((path, offset, size));
+        }
+
+        // Rebuilding from paths and sizes yields the canonical manifest; anything else was not
+        let manifest = Self::new(files.iter().map(|&(path, _, size)| (path, size)))
+            .map_err(|e| KontorPoRError::Serialization(format!("invalid dataset manifest: {e}")))?;
+        let canonical = manifest
+            .entries
+            .iter()
+            .zip(&files)
+            .all(|(entry, &(path, offset, _))| entry.path == path && entry.offset == offset);
+        if !canonical {
+            return Err(KontorPoRError::Serialization(
+                "dataset manifest is not in canonical order".to_string(),
+            ));
+        }
+        Ok(manifest)
+    }
+}
+
+/// Cursor over manifest bytes that reports truncation as a serialization error.
+struct ManifestReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ManifestReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| {
+                KontorPoRError::Serialization("dataset manifest is truncated".to_string())
+            })?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+}
+
+/// Prover-side result of [`prepare_dataset`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedDataset {
+    /// The prepared dataset blob; pass it to `PorSystem::prove` like any prepared file
+    pub file: PreparedFile,
+    /// The dataset's files
+    pub manifest: DatasetManifest,
+}
+
+/// Public metadata of a dataset prepared with [`prepare_dataset`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DatasetMetadata {
+    /// Metadata of the dataset blob; add it to the ledger and challenge it like any file
+    pub file: FileMetadata,
+    /// The dataset's files, as committed at the start of the blob
+    pub manifest: DatasetManifest,
+}
+
+impl DatasetMetadata {
+    /// The dataset's name, recorded as the blob's filename.
+    pub fn name(&self) -> &str {
+        self.file.filename()
+    }
+
+    /// Reconstructs the file at `path` from the dataset's symbols.
+    ///
+    /// Only the codewords holding the manifest and that file are decoded, so the symbols
+    /// of all other codewords may be `None`. The decoded manifest must match
+    /// `self.manifest`.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::InvalidInput`] if the dataset has no file at `path` or the
+    ///   manifest does not match the symbols
+    /// - [`KontorPoRError::ReconstructionFailed`] if a needed codeword has too many missing
+    ///   symbols
+    pub fn extract_file(&self, symbols: &[Option<Vec<u8>>], path: &str) -> Result<Vec<u8>> {
+        let _span = debug_span!("extract_file", dataset = self.name(), path).entered();
+
+        let entry = self.manifest.entry(path).ok_or_else(|| {
+            KontorPoRError::InvalidInput(format!("dataset {} has no file {:?}", self.name(), path))
+        })?;
+        self.check_sizes()?;
+
+        let manifest_bytes =
+            super::reconstruct_range(symbols, &self.file, 0..self.manifest.encoded_len())?;
+        if manifest_bytes != self.manifest.to_bytes() {
+            return Err(self.manifest_mismatch());
+        }
+
+        let start = entry.offset as usize;
+        super::reconstruct_range(symbols, &self.file, start..start + entry.size as usize)
+    }
+
+    fn check_sizes(&self) -> Result<()> {
+        if self.manifest.total_size() != self.file.original_size as u64 {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "dataset manifest describes {} bytes, metadata records {}",
+                self.manifest.total_size(),
+                self.file.original_size
+            )));
+        }
+        Ok(())
+    }
+
+    fn manifest_mismatch(&self) -> KontorPoRError {
+        KontorPoRError::InvalidInput(format!(
+            "dataset {} manifest does not match its symbols",
+            self.name()
+        ))
+    }
+}
+
+/// Prepares several files as one dataset with a single root.
+///
+/// `entries` pairs each file's path within the dataset with its contents; the order does
+/// not matter, since the manifest sorts files by path. The manifest and the contents are
+/// concatenated into one blob, which is prepared like [`prepare_file`](super::prepare_file)
+/// under the filename `name`.
+///
+/// # Errors
+///
+/// - [`KontorPoRError::InvalidInput`] for an empty or duplicate path
+/// - [`KontorPoRError::FileTooLarge`] if the blob exceeds `config::MAX_FILE_SIZE`
+pub fn prepare_dataset(
+    entries: &[(String, &[u8])],
+    name: &str,
+) -> Result<(PreparedDataset, DatasetMetadata)> {
+    let _span = debug_span!("prepare_dataset", num_files = entries.len(), name).entered();
+
+    let manifest = DatasetManifest::new(
+        entries
+            .iter()
+            .map(|(path, data)| (path.as_str(), data.len() as u64)),
+    )?;
+    let total_size = manifest.total_size();
+    if total_size > crate::config::MAX_FILE_SIZE {
+        return Err(KontorPoRError::FileTooLarge {
+            size: total_size,
+            max: crate::config::MAX_FILE_SIZE,
+        });
+    }
+
+    // Contents follow the manifest in path order
+    let mut sorted: Vec<&(String, &[u8])> = entries.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut blob = manifest.to_bytes();
+    blob.reserve(total_size as usize - blob.len());
+    for (_, data) in sorted {
+        blob.extend_from_slice(data);
+    }
+
+    let (prepared, metadata) = super::prepare_file(&blob, name)?;
+    Ok((
+        PreparedDataset {
+            file: prepared,
+            manifest: manifest.clone(),
+        },
+        DatasetMetadata {
+            file: metadata,
+            manifest,
+        },
+    ))
+}
+
+/// Reconstructs every file of a dataset, as `(path, contents)` pairs sorted by path.
+///
+/// # Errors
+///
+/// Fails like [`reconstruct_file`](super::reconstruct_file), and with
+/// [`KontorPoRError::InvalidInput`] if the recovered manifest differs from
+/// `metadata.manifest`.
+pub fn reconstruct_dataset(
+    symbols: &[Option<Vec<u8>>],
+    metadata: &DatasetMetadata,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let _span = debug_span!("reconstruct_dataset", dataset = metadata.name()).entered();
+
+    metadata.check_sizes()?;
+    let blob = super::reconstruct_file(symbols, &metadata.file)?;
+    if DatasetManifest::from_bytes(&blob)? != metadata.manifest {
+        return Err(metadata.manifest_mismatch());
+    }
+
+    Ok(metadata
+        .manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let start = entry.offset as usize;
+            let contents = blob[start..start + entry.size as usize].to_vec();
+            (entry.path.clone(), contents)
+        })
+        .collect())
+}
@@ -0,0 +1,162 @@
+//! Heartbeat proofs: liveness proofs that challenge no files.
+//!
+//! A heartbeat runs one step of the single-slot circuit with its only slot inactive
+//! (public depth 0), reusing the padding and gating of regular proofs. Nothing is opened,
+//! so the proof only shows that the prover produced a SNARK over public inputs binding
+//! the ledger root it claims and `H(TAG_HEARTBEAT, seed, prover)` as the chain state,
+//! which the inactive slot carries through unchanged.
+
+use super::types::{FieldElement, Proof};
+#[cfg(feature = "prover")]
+use super::witness::create_padding_witness;
+use crate::instrumentation::{debug, info_span};
+#[cfg(feature = "prover")]
+use crate::merkle::TreeArity;
+use crate::{
+    config,
+    ledger::FileLedger,
+    params::{load_or_generate_params_for_shape, Shape},
+    poseidon::{calculate_heartbeat_commitment, HashProfile},
+    KontorPoRError, Result,
+};
+#[cfg(feature = "prover")]
+use nova_snark::{
+    nova::{CompressedSNARK, RecursiveSNARK},
+    provider::{PallasEngine, VestaEngine},
+};
+
+#[cfg(feature = "prover")]
+type E1 = PallasEngine;
+#[cfg(feature = "prover")]
+type E2 = VestaEngine;
+#[cfg(feature = "prover")]
+type C = crate::circuit::PorCircuit<FieldElement>;
+
+/// Circuit shape of every heartbeat: one slot over the minimal file tree, no aggregation.
+fn heartbeat_shape() -> Shape {
+    Shape::from(config::Shape::derive(1, 0, 0))
+}
+
+/// Public inputs of a heartbeat step: the ledger root, the heartbeat commitment as the
+/// chain state, and one inactive slot carrying `seed`.
+fn heartbeat_z0(
+    ledger_root: FieldElement,
+    commitment: FieldElement,
+    seed: FieldElement,
+) -> Vec<FieldElement> {
+    config::PublicIOLayout::new(1).build_z0_primary(ledger_root, commitment, &[0], &[0], &[seed])
+}
+
+/// Proves liveness against `ledger`'s current root for `seed` and `prover_id`.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_heartbeat(
+    ledger: &FileLedger,
+    seed: FieldElement,
+    prover_id: &str,
+) -> Result<Proof> {
+    let _span = info_span!("prove_heartbeat").entered();
+
+    let shape = heartbeat_shape();
+    let params = load_or_generate_params_for_shape(&shape)?;
+    let ledger_root = ledger.root();
+    let commitment = calculate_heartbeat_commitment(seed, prover_id);
+    let z0_primary = heartbeat_z0(ledger_root, commitment, seed);
+
+    let witness = create_padding_witness(shape.file_tree_depth, 0, TreeArity::Binary);
+    let circuit = C::new(shape.dims(), Some(vec![witness]));
+
+    // The first prove_step after new() is a no-op, so this folds exactly one step
+    let mut recursive_snark = RecursiveSNARK::<E1, E2, C>::new(&params.pp, &circuit, &z0_primary)
+        .map_err(|e| {
+        KontorPoRError::Snark(format!("Heartbeat SNARK creation failed: {e:?}"))
+    })?;
+    recursive_snark
+        .prove_step(&params.pp, &circuit)
+        .map_err(|e| KontorPoRError::Snark(format!("Heartbeat step failed: {e:?}")))?;
+    let compressed_snark = CompressedSNARK::prove(&params.pp, &params.keys.pk, &recursive_snark)
+        .map_err(|e| KontorPoRError::Snark(format!("Proof compression failed: {e:?}")))?;
+
+    debug!("Heartbeat proof generated against root {:?}", ledger_root);
+    Ok(Proof {
+        compressed_snark,
+        challenge_ids: Vec::new(),
+        ledger_root,
+        ledger_indices: vec![0],
+        file_depths: vec![0],
+        shape: shape.dims(),
+        challenge_nonce_commitment: commitment,
+        challenge_slots: Vec::new(),
+        public_inputs: z0_primary,
+        public_outputs: recursive_snark.outputs().to_vec(),
+        hash_profile: HashProfile::Standard,
+        schema_version: config::PUBLIC_IO_SCHEMA_VERSION,
+        embedded_challenges: None,
+    })
+}
+
+/// Verifies a heartbeat proof for `seed` and `prover_id` against `ledger`.
+///
+/// The proof's ledger root must still be accepted by the ledger (current or retained
+/// historical root); otherwise this fails with [`KontorPoRError::InvalidLedgerRoot`].
+/// Proofs that answer challenges are rejected with [`KontorPoRError::InvalidInput`], and
+/// proofs of another public IO layout with [`KontorPoRError::SchemaVersionMismatch`].
+pub(crate) fn verify_heartbeat(
+    ledger: &FileLedger,
+    proof: &Proof,
+    seed: FieldElement,
+    prover_id: &str,
+) -> Result<bool> {
+    let _span = info_span!("verify_heartbeat").entered();
+    proof.check_schema_version()?;
+
+    if !proof.challenge_ids.is_empty()
+        || !proof.challenge_slots.is_empty()
+        || proof.ledger_indices != [0]
+        || proof.file_depths != [0]
+        || proof.shape.aggregated_tree_depth != 0
+        || proof.hash_profile != HashProfile::Standard
+    {
+        return Err(KontorPoRError::InvalidInput(
+            "Proof answers challenges and is not a heartbeat".to_string(),
+        ));
+    }
+    let expected = heartbeat_shape().dims();
+    if proof.shape != expected {
+        return Err(KontorPoRError::ShapeMismatch {
+            proof: proof.shape,
+            expected,
+        });
+    }
+
+    if !ledger.is_valid_root(proof.ledger_root) {
+        return Err(KontorPoRError::InvalidLedgerRoot {
+            proof_root: format!("{:?}", proof.ledger_root),
+            reason: "Heartbeat's ledger_root is not in the set of valid historical roots"
+                .to_string(),
+        });
+    }
+
+    let commitment = calculate_heartbeat_commitment(seed, prover_id);
+    if proof.challenge_nonce_commitment != commitment {
+        debug!("Heartbeat commitment does not match the seed and prover");
+        return Ok(false);
+    }
+
+    let z0_primary = heartbeat_z0(proof.ledger_root, commitment, seed);
+    if proof.public_inputs != z0_primary {
+        debug!("Heartbeat public_inputs do not match the seed and ledger root");
+        return Ok(false);
+    }
+
+    let params = load_or_generate_params_for_shape(&heartbeat_shape())?;
+    match proof
+        .compressed_snark
+        .verify(&params.keys.vk, 1, &z0_primary)
+    {
+        Ok(zn) => Ok(zn == proof.public_outputs),
+        Err(nova_snark::errors::NovaError::ProofVerifyError { reason: _ }) => Ok(false),
+        Err(e) => Err(KontorPoRError::Snark(format!(
+            "An unexpected error occurred during verification: {e:?}"
+        ))),
+    }
+}
@@ -64,18 +64,54 @@
 //! ```
 
 // Declare sub-modules
-mod plan;
+mod aggregate;
+#[cfg(feature = "prover")]
+mod checkpoint;
+#[cfg(feature = "prover")]
+mod dataset;
+mod heartbeat;
+pub mod plan;
+#[cfg(feature = "prover")]
 mod prove;
+mod root_usage;
+pub mod sampling;
+mod slice;
+mod spot_check;
+mod statement;
 mod system;
+#[cfg(feature = "transcript")]
+pub(crate) mod transcript;
+mod transport;
 mod types;
 mod verify;
-mod witness;
+mod verify_cache;
+pub(crate) mod witness;
 
 // Re-export the public API
+pub use crate::circuit::CircuitMode;
+pub use crate::merkle::TreeArity;
+pub use crate::poseidon::HashProfile;
+pub use aggregate::{aggregate_proofs, AggregatedProof};
+#[cfg(feature = "prover")]
+pub use dataset::{
+    prepare_dataset, reconstruct_dataset, DatasetEntry, DatasetManifest, DatasetMetadata,
+    PreparedDataset,
+};
+pub use root_usage::{RootUsage, RootUsageTracker};
+pub use slice::{verify_slice, ProofSlice};
+pub use spot_check::{spot_check, SpotCheckIndex, SpotCheckResult, SymbolStore};
+pub use statement::{ProofStatement, StatementEntry};
 pub use system::PorSystem;
+#[cfg(feature = "transcript")]
+pub use transcript::{Transcript, TranscriptDivergence, TranscriptEntry, TranscriptLabel};
+pub use transport::SignedChallenge;
 pub use types::{
-    Challenge, ChallengeID, FieldElement, FileMetadata, KeyPair, PorParams, PreparedFile, Proof,
+    Challenge, ChallengeID, ChallengeKind, ChallengeMode, ErasureCode, ErasureField, FieldElement,
+    FileMetadata, KeyPair, LeafValidation, PorParams, PreparedFile, Proof, ProofBinding,
+    ProofSizeBreakdown, ProveOptions, SeedSchedule, VerifyOptions,
 };
+pub use verify::PreverifiedStatement;
+pub use verify_cache::VerificationCache;
 
 // Internal modules can access these for implementation
 // Export for testing - these are implementation details
@@ -86,11 +122,22 @@ pub use witness::generate_circuit_witness;
 
 // Re-export key external types for easier access.
 pub use crate::{KontorPoRError, Result};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+#[cfg(feature = "zeroize")]
+pub use zeroize::Zeroizing;
 
 // Local imports for utility functions
-use crate::build_tree;
+#[cfg(feature = "prover")]
+use crate::instrumentation::{debug_span, warn};
+#[cfg(feature = "prover")]
+use crate::merkle::{
+    build_file_tree, build_file_tree_from_leaves, build_tree_from_prehashed, get_leaf_hash,
+};
 use sha2::{Digest, Sha256};
-use tracing::debug_span;
+#[cfg(feature = "prover")]
+use std::path::Path;
+#[cfg(feature = "prover")]
+use std::sync::atomic::AtomicBool;
 
 /// Processes raw data into a `PreparedFile` (private) and `FileMetadata` (public).
 /// This function applies erasure coding, concatenates shards, chunks the result into
@@ -100,6 +147,10 @@ use tracing::debug_span;
 /// Note: This is also available as `PorSystem::prepare_file()` method. The free function
 /// is provided for cases where you need to prepare files before creating the ledger.
 ///
+/// Empty data is supported: it encodes to zero codewords and a depth-0 tree whose single
+/// leaf is the all-zero padding symbol (so the root is zero). Such files can be added to
+/// a ledger, challenged, proven, verified and reconstructed (to an empty `Vec`).
+///
 /// # Arguments
 ///
 /// * `data` - The raw data to be processed
@@ -111,33 +162,490 @@ use tracing::debug_span;
 /// Returns a tuple of `(PreparedFile, FileMetadata)` where:
 /// - `PreparedFile` contains the private Merkle tree for the prover
 /// - `FileMetadata` contains the public commitment and reconstruction information
+#[cfg(feature = "prover")]
 pub fn prepare_file(
     data: &[u8],
     filename: &str,
 ) -> Result<(types::PreparedFile, types::FileMetadata)> {
-    let _span = debug_span!("prepare_file", data_size = data.len(), filename).entered();
+    prepare_file_with_arity(data, filename, TreeArity::Binary)
+}
+
+/// Like [`prepare_file`], but builds the file's Merkle tree with the given arity.
+///
+/// With [`TreeArity::Arity4`] the symbols are padded to a power of four and the tree has
+/// half the depth of the binary tree, so each challenge costs fewer hashes in the circuit.
+/// The arity is recorded in the returned `FileMetadata` and bound into the file's root
+/// commitment; all challenges in one proof must use files of the same arity.
+#[cfg(feature = "prover")]
+pub fn prepare_file_with_arity(
+    data: &[u8],
+    filename: &str,
+    tree_arity: TreeArity,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        tree_arity,
+        HashProfile::Standard,
+        Some(ErasureCode::ReedSolomon),
+        None,
+        None,
+    )
+}
 
-    if data.is_empty() {
-        return Err(KontorPoRError::EmptyData {
-            operation: "prepare_file".to_string(),
+/// Like [`prepare_file_with_arity`], but hashes the file's Merkle tree and root commitment
+/// under the given [`HashProfile`].
+///
+/// [`HashProfile::Conservative`] uses the strengthened Poseidon instantiation for
+/// long-lived archival commitments: hashing is slower and proofs cost more constraints
+/// per tree level. The profile is recorded in the returned `FileMetadata` and bound into
+/// the root commitment, challenge IDs and circuit parameters; all challenges in one proof
+/// must use files of the same profile.
+#[cfg(feature = "prover")]
+pub fn prepare_file_with_profile(
+    data: &[u8],
+    filename: &str,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        tree_arity,
+        hash_profile,
+        Some(ErasureCode::ReedSolomon),
+        None,
+        None,
+    )
+}
+
+/// Like [`prepare_file`], but erasure codes the data with the Reed-Solomon backend chosen
+/// by `erasure`.
+///
+/// With `ErasureConfig::field(ErasureField::Gf16)` the file is coded in GF(2^16) codewords
+/// of 2040 symbols, tolerating a burst of up to 192 lost symbols per codeword. Symbols
+/// and trees are built exactly as for [`prepare_file`]; the code is recorded in
+/// `FileMetadata::erasure`, and [`reconstruct_file`] decodes with it.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::{self, ErasureCode, ErasureField};
+/// use kontor_crypto::erasure::ErasureConfig;
+///
+/// let config = ErasureConfig::field(ErasureField::Gf16);
+/// let (_, metadata) = api::prepare_file_with_erasure(b"archive", "archive.tar", config)?;
+/// assert_eq!(metadata.erasure, Some(ErasureCode::ReedSolomonGf16));
+/// assert_eq!(metadata.total_symbols(), 2040);
+/// # Ok::<(), kontor_crypto::KontorPoRError>(())
+/// ```
+#[cfg(feature = "prover")]
+pub fn prepare_file_with_erasure(
+    data: &[u8],
+    filename: &str,
+    erasure: crate::erasure::ErasureConfig,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        TreeArity::Binary,
+        HashProfile::Standard,
+        Some(erasure.code()),
+        None,
+        None,
+    )
+}
+
+/// Like [`prepare_file`], but skips erasure coding for data that is already redundant.
+///
+/// The raw data is chunked directly into 31-byte symbols (the last one zero-padded)
+/// before padding and tree construction, and the returned `FileMetadata` records
+/// `erasure: None`. Proving and verifying are unchanged, but such files cannot be
+/// reconstructed from partial symbols: use [`assemble_raw`] with every symbol instead
+/// of [`reconstruct_file`].
+#[cfg(feature = "prover")]
+pub fn prepare_file_raw(
+    data: &[u8],
+    filename: &str,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        TreeArity::Binary,
+        HashProfile::Standard,
+        None,
+        None,
+        None,
+    )
+}
+
+/// Like [`prepare_file_raw`], but takes the data as 31-byte symbols together with their
+/// precomputed leaves instead of encoding every symbol again.
+///
+/// `symbols` are the raw data in order: every symbol but the last is `CHUNK_SIZE_BYTES`
+/// long and the last holds the remaining 1 to 31 bytes. `leaf_hashes[i]` must be
+/// [`crate::merkle::get_leaf_hash`] of `symbols[i]`; the returned file and metadata are
+/// then identical to `prepare_file_raw` on the concatenated symbols. Before the leaves are
+/// trusted, a random sample of them is checked against their symbols as in
+/// [`types::LeafValidation::Sampled`]; use [`prepare_file_with_leaf_hashes_validated`] to
+/// check every leaf.
+///
+/// # Errors
+///
+/// - [`KontorPoRError::InvalidInput`] if the counts of symbols and leaf hashes differ or a
+///   symbol has the wrong length
+/// - [`KontorPoRError::LeafHashMismatch`] at the first checked leaf hash that differs from
+///   its symbol's leaf
+/// - [`KontorPoRError::InvalidLeafEncoding`] if an unchecked leaf hash is wider than 31
+///   bytes
+/// - [`KontorPoRError::FileTooLarge`] if the symbols exceed `config::MAX_FILE_SIZE`
+#[cfg(feature = "prover")]
+pub fn prepare_file_with_leaf_hashes(
+    symbols: &[Vec<u8>],
+    leaf_hashes: &[FieldElement],
+    filename: &str,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_with_leaf_hashes_validated(
+        symbols,
+        leaf_hashes,
+        filename,
+        types::LeafValidation::Sampled,
+    )
+}
+
+/// [`prepare_file_with_leaf_hashes`], checking the leaf hashes against their symbols as
+/// `validation` selects.
+#[cfg(feature = "prover")]
+pub fn prepare_file_with_leaf_hashes_validated(
+    symbols: &[Vec<u8>],
+    leaf_hashes: &[FieldElement],
+    filename: &str,
+    validation: types::LeafValidation,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    let _span = debug_span!(
+        "prepare_file_with_leaf_hashes",
+        num_symbols = symbols.len(),
+        filename
+    )
+    .entered();
+
+    if leaf_hashes.len() != symbols.len() {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "prepare_file_with_leaf_hashes: expected {} leaf hashes, got {}",
+            symbols.len(),
+            leaf_hashes.len()
+        )));
+    }
+    let last = symbols.len().saturating_sub(1);
+    if let Some(index) = symbols.iter().enumerate().position(|(i, symbol)| {
+        symbol.is_empty()
+            || symbol.len() > crate::config::CHUNK_SIZE_BYTES
+            || (i < last && symbol.len() != crate::config::CHUNK_SIZE_BYTES)
+    }) {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "prepare_file_with_leaf_hashes: symbol {} is {} bytes, expected {}",
+            index,
+            symbols[index].len(),
+            if index < last {
+                format!("{}", crate::config::CHUNK_SIZE_BYTES)
+            } else {
+                format!("1 to {}", crate::config::CHUNK_SIZE_BYTES)
+            }
+        )));
+    }
+    let original_size = symbols.iter().map(Vec::len).sum::<usize>();
+    if original_size as u64 > crate::config::MAX_FILE_SIZE {
+        return Err(KontorPoRError::FileTooLarge {
+            size: original_size as u64,
+            max: crate::config::MAX_FILE_SIZE,
         });
     }
 
-    // 1. Calculate file ID
+    // 1. Check the leaf hashes before trusting them
+    validation.try_for_each_index(symbols.len(), &mut rand::thread_rng(), |index| {
+        if get_leaf_hash(&symbols[index])? == leaf_hashes[index] {
+            Ok(())
+        } else {
+            Err(KontorPoRError::LeafHashMismatch { index })
+        }
+    })?;
+
+    // 2. Calculate file ID over the concatenated symbols
     let mut hasher = Sha256::new();
-    hasher.update(data);
+    for symbol in symbols {
+        hasher.update(symbol);
+    }
     let file_id = format!("{:x}", hasher.finalize());
 
-    // 2. Encode file into 31-byte symbols using multi-codeword RS
-    let all_symbols = crate::erasure::encode_file_symbols(data)?;
+    // 3. Pad the leaves to the next power of two and build the Merkle tree
+    let padded_len = TreeArity::Binary.checked_padded_len(symbols.len()).ok_or(
+        KontorPoRError::FileTooLarge {
+            size: original_size as u64,
+            max: crate::config::MAX_FILE_SIZE,
+        },
+    )?;
+    let mut leaves = Vec::with_capacity(padded_len);
+    leaves.extend_from_slice(leaf_hashes);
+    leaves.resize(
+        padded_len,
+        get_leaf_hash(&[0; crate::config::CHUNK_SIZE_BYTES])?,
+    );
+    let (tree, root) = build_tree_from_prehashed(&leaves)?;
+
+    // 4. Create metadata and prepared file
+    let metadata = types::FileMetadata {
+        root,
+        file_id: file_id.clone(),
+        padded_len,
+        original_size,
+        annotations: std::collections::BTreeMap::from([(
+            types::FileMetadata::FILENAME_ANNOTATION.to_string(),
+            filename.to_string(),
+        )]),
+        tree_arity: TreeArity::Binary,
+        erasure: None,
+        salt_commitment: None,
+        hash_profile: HashProfile::Standard,
+    };
+    let prepared_file = types::PreparedFile {
+        tree,
+        file_id,
+        root,
+        tree_arity: TreeArity::Binary,
+        hash_profile: HashProfile::Standard,
+    };
+
+    Ok((prepared_file, metadata))
+}
+
+/// Like [`prepare_file`], but derives the file ID from a secret salt and the data.
+///
+/// An unsalted file ID is `SHA-256(data)`, so anyone holding a plaintext can check
+/// whether it is stored. Here the ID is `SHA-256(salt || data)` instead, and the returned
+/// `FileMetadata` records only a commitment to the salt
+/// ([`FileMetadata::salt_commitment`]), never the salt itself. Proving, verifying and
+/// ledger lookups work on the file ID as usual; the salt is needed only to recompute the
+/// ID from reconstructed data with [`compute_file_id`]. Preparing the same data with
+/// different salts yields distinct files that can share one ledger.
+#[cfg(feature = "prover")]
+pub fn prepare_file_salted(
+    data: &[u8],
+    filename: &str,
+    salt: &[u8; 32],
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        TreeArity::Binary,
+        HashProfile::Standard,
+        Some(ErasureCode::ReedSolomon),
+        Some(salt),
+        None,
+    )
+}
+
+/// Like [`prepare_file`], but stops early once `cancel` is set.
+///
+/// The flag is checked while hashing the file ID, before each Reed-Solomon codeword is
+/// encoded and while the Merkle tree is built, so setting it from another thread ends a
+/// long preparation within a few thousand hashes. Nothing is written outside the call:
+/// a cancelled preparation drops everything it built.
+///
+/// # Errors
+///
+/// [`KontorPoRError::Cancelled`] if `cancel` is set before preparation completes, or any
+/// error of [`prepare_file`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::{api, KontorPoRError};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// let cancel = AtomicBool::new(false);
+/// cancel.store(true, Ordering::Relaxed); // e.g. from the thread handling the deal
+/// let result = api::prepare_file_cancellable(b"large data", "deal.dat", &cancel);
+/// assert!(matches!(result, Err(KontorPoRError::Cancelled)));
+/// ```
+#[cfg(feature = "prover")]
+pub fn prepare_file_cancellable(
+    data: &[u8],
+    filename: &str,
+    cancel: &AtomicBool,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_inner(
+        data,
+        filename,
+        TreeArity::Binary,
+        HashProfile::Standard,
+        Some(ErasureCode::ReedSolomon),
+        None,
+        Some(cancel),
+    )
+}
 
-    // 3. Pad to next power of two
-    let padded_len = all_symbols.len().next_power_of_two();
-    let mut padded_symbols = all_symbols;
-    padded_symbols.resize(padded_len, vec![0; crate::config::CHUNK_SIZE_BYTES]);
+/// Like [`prepare_file`], but reads the data through a read-only memory map of the file at
+/// `path` instead of a buffer.
+///
+/// The file is erasure coded one codeword at a time straight from the mapped pages, each
+/// symbol turned into its Merkle leaf as it is produced, so besides pages the OS can evict
+/// again only the leaves and the tree are held in memory. The result is identical to
+/// `prepare_file(&std::fs::read(path)?, filename)`. The file must not be modified or
+/// truncated while it is being prepared.
+///
+/// # Errors
+///
+/// [`KontorPoRError::IO`] if the file cannot be opened or mapped, or any error of
+/// [`prepare_file`].
+#[cfg(feature = "prover")]
+pub fn prepare_file_mmap(
+    path: &Path,
+    filename: &str,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_mmap_inner(path, filename, None)
+}
 
-    // 4. Build Merkle tree
-    let (tree, root) = build_tree(&padded_symbols)?;
+/// [`prepare_file_mmap`], stopping early once `cancel` is set like
+/// [`prepare_file_cancellable`].
+#[cfg(feature = "prover")]
+pub fn prepare_file_mmap_cancellable(
+    path: &Path,
+    filename: &str,
+    cancel: &AtomicBool,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    prepare_file_mmap_inner(path, filename, Some(cancel))
+}
+
+#[cfg(feature = "prover")]
+fn prepare_file_mmap_inner(
+    path: &Path,
+    filename: &str,
+    cancel: Option<&AtomicBool>,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    let io_error =
+        |e: std::io::Error| KontorPoRError::IO(format!("Failed to map {}: {}", path.display(), e));
+    let file = std::fs::File::open(path).map_err(io_error)?;
+    // SAFETY: the map is read-only and dropped before returning; as documented, the file
+    // must not change while mapped.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+    prepare_file_inner(
+        &map,
+        filename,
+        TreeArity::Binary,
+        HashProfile::Standard,
+        Some(ErasureCode::ReedSolomon),
+        None,
+        cancel,
+    )
+}
+
+/// Computes the file ID of `data`: `SHA-256(data)`, or `SHA-256(salt || data)` for files
+/// prepared with [`prepare_file_salted`].
+pub fn compute_file_id(data: &[u8], salt: Option<&[u8; 32]>) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(salt) = salt {
+        hasher.update(salt);
+    }
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+/// [`compute_file_id`], checking `cancel` before each mebibyte of data.
+#[cfg(feature = "prover")]
+fn compute_file_id_cancellable(
+    data: &[u8],
+    salt: Option<&[u8; 32]>,
+    cancel: Option<&AtomicBool>,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    if let Some(salt) = salt {
+        hasher.update(salt);
+    }
+    for block in data.chunks(1 << 20) {
+        crate::utils::check_cancelled(cancel)?;
+        hasher.update(block);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(feature = "prover")]
+fn prepare_file_inner(
+    data: &[u8],
+    filename: &str,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+    erasure: Option<ErasureCode>,
+    salt: Option<&[u8; 32]>,
+    cancel: Option<&AtomicBool>,
+) -> Result<(types::PreparedFile, types::FileMetadata)> {
+    let _span = debug_span!("prepare_file", data_size = data.len(), filename).entered();
+
+    if data.len() as u64 > crate::config::MAX_FILE_SIZE {
+        return Err(KontorPoRError::FileTooLarge {
+            size: data.len() as u64,
+            max: crate::config::MAX_FILE_SIZE,
+        });
+    }
+
+    // 1. Calculate file ID
+    let file_id = compute_file_id_cancellable(data, salt, cancel)?;
+
+    // 2. Size the leaves: one per 31-byte symbol, padded to the next power of the arity (an
+    //    empty file pads to a single zero leaf)
+    let num_symbols = match erasure {
+        Some(code) => {
+            let codeword_bytes = code.data_symbols_per_codeword() * crate::config::CHUNK_SIZE_BYTES;
+            data.len().div_ceil(codeword_bytes) * code.total_symbols_per_codeword()
+        }
+        None => data.len().div_ceil(crate::config::CHUNK_SIZE_BYTES),
+    };
+    let padded_len =
+        tree_arity
+            .checked_padded_len(num_symbols)
+            .ok_or(KontorPoRError::FileTooLarge {
+                size: data.len() as u64,
+                max: crate::config::MAX_FILE_SIZE,
+            })?;
+    let mut leaves = Vec::with_capacity(padded_len);
+
+    // 3. Encode file into 31-byte symbols using multi-codeword RS, or chunk it as is. Each
+    //    symbol becomes its Merkle leaf as soon as it is produced, so only one codeword of
+    //    symbols is held at a time. Padding symbols are all zero.
+    match erasure {
+        Some(code) => crate::erasure::for_each_codeword(
+            data,
+            crate::erasure::ErasureConfig::field(code.field()),
+            cancel,
+            |codeword| {
+                for symbol in codeword {
+                    leaves.push(get_leaf_hash(symbol)?);
+                }
+                Ok(())
+            },
+        )?,
+        None => {
+            for block in data.chunks(crate::config::CHUNK_SIZE_BYTES << 12) {
+                crate::utils::check_cancelled(cancel)?;
+                for chunk in block.chunks(crate::config::CHUNK_SIZE_BYTES) {
+                    let mut symbol = [0; crate::config::CHUNK_SIZE_BYTES];
+                    symbol[..chunk.len()].copy_from_slice(chunk);
+                    let leaf = get_leaf_hash(&symbol);
+                    crate::utils::scrub_bytes(&mut symbol);
+                    leaves.push(leaf?);
+                }
+            }
+        }
+    }
+    leaves.resize(
+        padded_len,
+        get_leaf_hash(&[0; crate::config::CHUNK_SIZE_BYTES])?,
+    );
+
+    // 4. Build Merkle tree. The leaves are the symbols themselves, so the copy the tree
+    //    does not keep is scrubbed
+    let built = build_file_tree_from_leaves(&leaves, tree_arity, hash_profile, cancel);
+    crate::utils::scrub_field_elements(&mut leaves);
+    let (tree, root) = built?;
 
     // 5. Create metadata (num_data_symbols, num_codewords, total_symbols are derived)
     let metadata = types::FileMetadata {
@@ -145,7 +653,14 @@ pub fn prepare_file(
         file_id: file_id.clone(),
         padded_len,
         original_size: data.len(),
-        filename: filename.to_string(),
+        annotations: std::collections::BTreeMap::from([(
+            types::FileMetadata::FILENAME_ANNOTATION.to_string(),
+            filename.to_string(),
+        )]),
+        tree_arity,
+        erasure,
+        salt_commitment: salt.map(types::FileMetadata::salt_commitment_for),
+        hash_profile,
     };
 
     // 6. Create prepared file
@@ -153,15 +668,118 @@ pub fn prepare_file(
         tree,
         file_id,
         root,
+        tree_arity,
+        hash_profile,
     };
 
     Ok((prepared_file, metadata))
 }
 
+/// Rebuilds a `PreparedFile` from its stored symbols and public metadata.
+///
+/// This lets a node that kept only the encoded symbols (not the saved tree or the
+/// original data) recover the prover-side tree, e.g. after a restart. `symbols` may
+/// hold exactly `metadata.total_symbols()` symbols, in which case they are padded with
+/// zero symbols to `metadata.padded_len`, or already be padded to `padded_len`.
+///
+/// # Errors
+///
+/// - [`KontorPoRError::InvalidInput`] if the symbol count matches neither length or a
+///   symbol is not `CHUNK_SIZE_BYTES` long
+/// - [`KontorPoRError::RootMismatch`] if the rebuilt root differs from `metadata.root`
+#[cfg(feature = "prover")]
+pub fn rebuild_prepared(
+    symbols: &[Vec<u8>],
+    metadata: &types::FileMetadata,
+) -> Result<types::PreparedFile> {
+    let _span = debug_span!(
+        "rebuild_prepared",
+        num_symbols = symbols.len(),
+        file_id = %metadata.file_id
+    )
+    .entered();
+
+    metadata.check_size_limits()?;
+    let total_symbols = metadata.total_symbols();
+    if symbols.len() != total_symbols && symbols.len() != metadata.padded_len {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "rebuild_prepared: expected {} or {} symbols, got {}",
+            total_symbols,
+            metadata.padded_len,
+            symbols.len()
+        )));
+    }
+    if let Some(index) = symbols
+        .iter()
+        .position(|symbol| symbol.len() != crate::config::CHUNK_SIZE_BYTES)
+    {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "rebuild_prepared: symbol {} is {} bytes, expected {}",
+            index,
+            symbols[index].len(),
+            crate::config::CHUNK_SIZE_BYTES
+        )));
+    }
+
+    let mut padded_symbols = symbols.to_vec();
+    padded_symbols.resize(
+        metadata.padded_len,
+        vec![0; crate::config::CHUNK_SIZE_BYTES],
+    );
+
+    let (tree, root) =
+        build_file_tree(&padded_symbols, metadata.tree_arity, metadata.hash_profile)?;
+    if root != metadata.root {
+        return Err(KontorPoRError::RootMismatch {
+            file_id: metadata.file_id.clone(),
+        });
+    }
+
+    Ok(types::PreparedFile {
+        tree,
+        file_id: metadata.file_id.clone(),
+        root,
+        tree_arity: metadata.tree_arity,
+        hash_profile: metadata.hash_profile,
+    })
+}
+
+/// Prepares `data` again with the tree arity, hash profile and erasure code recorded in
+/// `metadata`, e.g. to preview a challenge's leaf indices from the original file.
+///
+/// Neither the filename nor a salt enters the tree, so they are not needed.
+///
+/// # Errors
+///
+/// - [`KontorPoRError::FileTooLarge`] if `data` exceeds `config::MAX_FILE_SIZE`
+/// - [`KontorPoRError::RootMismatch`] if the prepared root differs from `metadata.root`
+#[cfg(feature = "prover")]
+pub fn prepare_file_matching(
+    data: &[u8],
+    metadata: &types::FileMetadata,
+) -> Result<types::PreparedFile> {
+    let (mut prepared, _) = prepare_file_inner(
+        data,
+        "",
+        metadata.tree_arity,
+        metadata.hash_profile,
+        metadata.erasure,
+        None,
+        None,
+    )?;
+    if prepared.root != metadata.root {
+        return Err(KontorPoRError::RootMismatch {
+            file_id: metadata.file_id.clone(),
+        });
+    }
+    prepared.file_id = metadata.file_id.clone();
+    Ok(prepared)
+}
+
 /// Computes the Merkle tree depth implied by `FileMetadata`.
 ///
 /// Depth is defined as the number of sibling steps from a leaf to the root.
-/// For `padded_len` leaves, `depth = log2(padded_len)`.
+/// For `padded_len` leaves, `depth = log2(padded_len)` (`log4` for arity-4 trees).
 pub fn tree_depth_from_metadata(metadata: &types::FileMetadata) -> usize {
     metadata.depth()
 }
@@ -176,7 +794,8 @@ pub fn tree_depth_from_metadata(metadata: &types::FileMetadata) -> usize {
 /// # Returns
 ///
 /// Returns `Ok(Vec<u8>)` containing the original file data, or an error if:
-/// - Too many shards are missing for reconstruction
+/// - Too many symbols of a codeword are missing: [`KontorPoRError::ReconstructionFailed`]
+///   names every such codeword and the missing count of each
 /// - The reconstructed data is invalid
 /// - The metadata is inconsistent
 ///
@@ -200,15 +819,187 @@ pub fn tree_depth_from_metadata(metadata: &types::FileMetadata) -> usize {
 /// let reconstructed = reconstruct_file(&symbols, &metadata).unwrap();
 /// // Note: This example uses placeholder data, so reconstructed won't match original
 /// ```
+#[cfg(feature = "prover")]
 pub fn reconstruct_file(
     symbols: &[Option<Vec<u8>>],
     metadata: &types::FileMetadata,
 ) -> Result<Vec<u8>> {
+    if metadata.erasure.is_none() {
+        return Err(KontorPoRError::ErasureCoding {
+            details: "file was prepared without erasure coding and cannot be reconstructed \
+                      from partial symbols; use assemble_raw"
+                .to_string(),
+        });
+    }
+
+    metadata.check_size_limits()?;
+    let config = crate::erasure::ErasureConfig::for_metadata(metadata)?;
     let mut mutable_symbols = symbols.to_vec();
 
-    crate::erasure::decode_file_symbols(
+    let reconstructed = crate::erasure::decode_file_symbols_with(
         &mut mutable_symbols,
         metadata.num_codewords(),
         metadata.original_size,
+        config,
+    );
+    crate::utils::scrub_symbols(&mut mutable_symbols);
+    reconstructed
+}
+
+/// [`reconstruct_file`], returning the plaintext in a buffer that is zeroed when dropped.
+///
+/// With the `zeroize` feature every intermediate copy made while decoding is zeroed
+/// before it is freed; this variant extends that to the result. Copies the caller makes
+/// of the data (and `symbols` itself) are not covered.
+///
+/// # Errors
+///
+/// Fails like [`reconstruct_file`].
+#[cfg(feature = "zeroize")]
+pub fn reconstruct_file_zeroizing(
+    symbols: &[Option<Vec<u8>>],
+    metadata: &types::FileMetadata,
+) -> Result<Zeroizing<Vec<u8>>> {
+    reconstruct_file(symbols, metadata).map(Zeroizing::new)
+}
+
+/// Reconstructs only the bytes `range` of an erasure-coded file.
+///
+/// Only the codewords covering `range` are decoded, so reading a small part of a large
+/// file needs just those codewords' symbols; symbols of other codewords may be `None`.
+///
+/// # Errors
+///
+/// Fails like [`reconstruct_file`], and with [`KontorPoRError::InvalidInput`] if `range`
+/// extends past `metadata.original_size`.
+#[cfg(feature = "prover")]
+pub fn reconstruct_range(
+    symbols: &[Option<Vec<u8>>],
+    metadata: &types::FileMetadata,
+    range: std::ops::Range<usize>,
+) -> Result<Vec<u8>> {
+    if metadata.erasure.is_none() {
+        return Err(KontorPoRError::ErasureCoding {
+            details: "file was prepared without erasure coding and cannot be reconstructed \
+                      from partial symbols; use assemble_raw"
+                .to_string(),
+        });
+    }
+
+    metadata.check_size_limits()?;
+    crate::erasure::decode_byte_range_with(
+        symbols,
+        metadata.num_codewords(),
+        metadata.original_size,
+        range,
+        crate::erasure::ErasureConfig::for_metadata(metadata)?,
     )
 }
+
+/// Reconstructs a file from a [`DirSymbolStore`](crate::erasure::DirSymbolStore) directory.
+///
+/// Missing symbol files are erasures. Files that are not exactly 31 bytes are treated as
+/// missing and listed in the report's `warnings`. Files prepared without erasure coding are
+/// reassembled with [`assemble_raw`] and need every symbol.
+///
+/// # Errors
+///
+/// Returns `ErasureCoding` naming the unrecoverable codewords if any codeword has more
+/// than 24 missing symbols, and `IO` if the directory cannot be read.
+#[cfg(feature = "prover")]
+pub fn reconstruct_from_dir(
+    dir: &std::path::Path,
+    metadata: &types::FileMetadata,
+) -> Result<crate::erasure::DirReconstruction> {
+    let store = crate::erasure::DirSymbolStore::open(dir, metadata)?;
+    let loaded = store.load()?;
+
+    let warnings: Vec<String> = loaded
+        .corrupt
+        .iter()
+        .map(|symbol| {
+            format!(
+                "symbol {} has {} bytes (expected {}); treated as missing",
+                symbol.index,
+                symbol.len,
+                crate::config::CHUNK_SIZE_BYTES
+            )
+        })
+        .collect();
+    for warning in &warnings {
+        warn!(file_id = %metadata.file_id, "{}", warning);
+    }
+
+    let missing: Vec<usize> = loaded
+        .symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, symbol)| symbol.is_none())
+        .map(|(index, _)| index)
+        .collect();
+
+    let data = if metadata.erasure.is_some() {
+        let report = crate::erasure::recoverability(&loaded.symbols, metadata);
+        if !report.is_recoverable() {
+            return Err(KontorPoRError::ErasureCoding {
+                details: format!(
+                    "{} symbols missing from {}; codewords {:?} lost more than {} shards",
+                    missing.len(),
+                    dir.display(),
+                    report.unrecoverable_codewords(),
+                    metadata
+                        .erasure
+                        .unwrap_or_default()
+                        .parity_symbols_per_codeword()
+                        / metadata.erasure.unwrap_or_default().symbols_per_shard()
+                ),
+            });
+        }
+        reconstruct_file(&loaded.symbols, metadata)?
+    } else {
+        assemble_raw(&loaded.symbols, metadata)?
+    };
+
+    Ok(crate::erasure::DirReconstruction {
+        data,
+        missing,
+        warnings,
+    })
+}
+
+/// Reassembles a file prepared with [`prepare_file_raw`] from all of its symbols.
+///
+/// Without erasure coding there is no redundancy, so every one of
+/// `metadata.total_symbols()` symbols must be present; any missing symbol is an error.
+/// Symbols beyond that count (tree padding) are ignored.
+#[cfg(feature = "prover")]
+pub fn assemble_raw(
+    symbols: &[Option<Vec<u8>>],
+    metadata: &types::FileMetadata,
+) -> Result<Vec<u8>> {
+    if metadata.erasure.is_some() {
+        return Err(KontorPoRError::ErasureCoding {
+            details: "file is erasure coded; use reconstruct_file".to_string(),
+        });
+    }
+
+    metadata.check_size_limits()?;
+    let total = metadata.total_symbols();
+    let present = symbols.iter().take(total).flatten().count();
+    if present < total {
+        return Err(KontorPoRError::ErasureCoding {
+            details: format!(
+                "raw file needs all {} symbols, {} missing",
+                total,
+                total - present
+            ),
+        });
+    }
+
+    let mut data = Vec::with_capacity(total * crate::config::CHUNK_SIZE_BYTES);
+    for symbol in symbols.iter().take(total).flatten() {
+        data.extend_from_slice(symbol);
+    }
+    data.truncate(metadata.original_size);
+    Ok(data)
+}
@@ -1,56 +1,447 @@
 //! Preprocessing plan that consolidates logic shared between prove() and verify().
 //!
 //! The Plan struct eliminates duplication between proving and verification
-//! by handling all the common preprocessing steps in one place.
+//! by handling all the common preprocessing steps in one place. [`build_plan`]
+//! exposes the same plan to callers as a [`ProofPlan`], so the batching of a
+//! challenge set can be inspected before committing to a proof.
 
-use super::types::{Challenge, FieldElement};
-use crate::{config, ledger::FileLedger, KontorPoRError, Result};
+use super::types::{
+    Challenge, ChallengeID, ChallengeKind, ChallengeMode, FieldElement, ProofBinding, SeedSchedule,
+};
+use crate::{
+    circuit::CircuitMode, config, instrumentation::debug_span, ledger::FileLedger,
+    merkle::TreeArity, poseidon::HashProfile, KontorPoRError, Result,
+};
 use ff::Field;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+/// How a batch of challenges will be laid out across circuit steps and file slots.
+///
+/// Returned by [`build_plan`], which `prove` uses internally, so the step count,
+/// slot order and seeds here are exactly those of the resulting proof.
+///
+/// A batch of more files than [`Challenge::max_files_per_step`] allows is partitioned
+/// into `num_passes` passes of `files_per_step` slots: step `s` answers pass
+/// `s % num_passes` at challenge step `s / num_passes`, so every file is opened
+/// `num_challenges` times along one chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofPlan {
+    /// Number of file slots in the circuit (power of 2)
+    pub files_per_step: usize,
+    /// Number of passes over the slots the challenged files are partitioned into
+    pub num_passes: usize,
+    /// Maximum file tree depth for uniform structure
+    pub file_tree_depth: usize,
+    /// Aggregated tree depth (0 for file-root-bound proofs)
+    pub aggregated_tree_depth: usize,
+    /// Root the proof binds its files to
+    pub binding: ProofBinding,
+    /// File tree arity shared by all challenged files
+    pub tree_arity: TreeArity,
+    /// File tree hash profile shared by all challenged files
+    pub hash_profile: HashProfile,
+    /// Leaf exposure mode shared by all challenges
+    pub mode: ChallengeMode,
+    /// Ledger index checks enforced by the circuit, shared by all challenges
+    pub circuit_mode: CircuitMode,
+    /// Number of folded circuit steps (the shared `num_challenges` times `num_passes`)
+    pub total_steps: usize,
+    /// Slot assignments for every step, in step order
+    pub steps: Vec<StepPlan>,
+    /// Estimated R1CS constraints per step (see [`estimate_step_constraints`]), including
+    /// the strict-mode checks from [`CircuitMode::extra_constraints`]
+    pub estimated_constraints_per_step: usize,
+}
+
+impl ProofPlan {
+    /// The circuit shape of the planned proof, as recorded in [`super::Proof::shape`].
+    pub fn shape(&self) -> config::Shape {
+        config::Shape::new(
+            self.files_per_step,
+            self.file_tree_depth,
+            self.aggregated_tree_depth,
+        )
+    }
+
+    /// Estimated constraints across all steps.
+    pub fn estimated_constraints(&self) -> usize {
+        self.estimated_constraints_per_step * self.total_steps
+    }
+
+    /// File IDs in slot order, pass by pass (padding slots excluded).
+    pub fn slot_file_ids(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .take(self.num_passes)
+            .flat_map(|step| &step.slots)
+            .filter_map(|slot| slot.file_id.as_deref())
+            .collect()
+    }
+}
+
+/// The slot assignments of one circuit step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepPlan {
+    /// Step number (0-based; step 0 is folded by `RecursiveSNARK::new`)
+    pub step: usize,
+    /// Pass whose files fill the slots in this step (always 0 for unpartitioned batches)
+    pub pass: usize,
+    /// One assignment per file slot, `files_per_step` in total
+    pub slots: Vec<SlotAssignment>,
+}
+
+/// The challenge occupying one file slot in one step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotAssignment {
+    /// Slot position within the step
+    pub slot: usize,
+    /// Challenged file (None for padding slots)
+    pub file_id: Option<String>,
+    /// Challenge answered in this slot (None for padding slots)
+    pub challenge_id: Option<ChallengeID>,
+    /// Public ledger index bound to the slot (0 for padding slots)
+    pub ledger_index: usize,
+    /// Actual tree depth of the challenged file (0 for padding slots)
+    pub depth: usize,
+    /// Seed used to derive the slot's leaf index in this step (zero for padding slots)
+    pub seed: FieldElement,
+    /// Leaf index opened in this step for explicit-index challenges (None for seeded
+    /// challenges and padding slots)
+    pub explicit_index: Option<usize>,
+}
+
+/// Rough R1CS constraint count of one step circuit.
+///
+/// Uses the protocol's cost model (`C_IVC = CIRCUIT_COST_PER_DEPTH × depth`) for each
+/// slot's file path plus its aggregation path. This is an estimate for sizing
+/// batches; the exact count is only known once parameters are generated.
+pub fn estimate_step_constraints(
+    files_per_step: usize,
+    file_tree_depth: usize,
+    aggregated_tree_depth: usize,
+) -> usize {
+    config::CIRCUIT_COST_PER_DEPTH * files_per_step * (file_tree_depth + aggregated_tree_depth)
+}
+
+/// Plans how `challenges` will be batched into a proof against `ledger`.
+///
+/// Performs the same validation and planning as `prove` (which calls this internally)
+/// without touching prepared files or parameters, so it is cheap enough to run before
+/// deciding whether to prove.
+///
+/// # Errors
+///
+/// - [`KontorPoRError::InvalidInput`] if `challenges` is empty, or several challenges
+///   request [`ProofBinding::FileRoot`]
+/// - [`KontorPoRError::TooManyFiles`] above `config::PRACTICAL_MAX_FILES` challenges
+/// - [`KontorPoRError::InvalidChallengeCount`] if `num_challenges` is 0 or too large
+/// - [`KontorPoRError::ChallengeMismatch`] if challenges disagree on `num_challenges`,
+///   mode, seed schedule, tree arity, binding or `max_files_per_step`
+/// - [`KontorPoRError::InvalidInput`] if a batch partitioned by `max_files_per_step`
+///   uses per-step seeds or explicit indices
+/// - [`KontorPoRError::InvalidInput`] if a private-mode challenge targets a file with a
+///   single leaf
+/// - [`KontorPoRError::FileNotInLedger`] if a challenged file is missing from `ledger`
+pub fn build_plan(challenges: &[Challenge], ledger: &FileLedger) -> Result<ProofPlan> {
+    Ok(plan_batch(challenges, ledger)?.describe())
+}
+
+/// Validates a proving batch and builds its [`Plan`].
+pub(crate) fn plan_batch(challenges: &[Challenge], ledger: &FileLedger) -> Result<Plan> {
+    if challenges.is_empty() {
+        return Err(KontorPoRError::InvalidInput(
+            "Must provide at least one challenge to generate a proof".to_string(),
+        ));
+    }
+
+    // Practical limit for number of files (can be adjusted based on needs)
+    if challenges.len() > config::PRACTICAL_MAX_FILES {
+        return Err(KontorPoRError::TooManyFiles {
+            got: challenges.len(),
+            max: config::PRACTICAL_MAX_FILES,
+        });
+    }
+
+    // Verify all challenges use the same num_challenges (Nova requirement)
+    let num_challenges = challenges[0].num_challenges;
+    if num_challenges == 0 || num_challenges > config::MAX_NUM_CHALLENGES {
+        return Err(KontorPoRError::InvalidChallengeCount {
+            count: num_challenges,
+        });
+    }
+    if challenges
+        .iter()
+        .any(|c| c.num_challenges != num_challenges)
+    {
+        return Err(KontorPoRError::ChallengeMismatch {
+            field: "num_challenges".to_string(),
+        });
+    }
+
+    let _span = debug_span!("make_plan", num_challenges = challenges.len()).entered();
+    Plan::make_plan(challenges, ledger)
+}
+
+/// `challenges` with every repeated challenge ID dropped after its first occurrence, or
+/// `None` if the IDs are already distinct.
+///
+/// Challenges for the same file with another seed, count or binding have other IDs and
+/// are kept.
+pub(crate) fn collapse_duplicates(challenges: &[Challenge]) -> Option<Vec<Challenge>> {
+    let mut seen = BTreeSet::new();
+    let unique: Vec<Challenge> = challenges
+        .iter()
+        .filter(|challenge| seen.insert(challenge.id()))
+        .cloned()
+        .collect();
+    (unique.len() < challenges.len()).then_some(unique)
+}
 
 /// Internal preprocessing plan that consolidates logic shared between prove() and verify().
 /// This eliminates duplication and reduces the chance of drift between the two functions.
 #[derive(Debug, Clone)]
 pub(crate) struct Plan {
-    /// Number of file slots in the circuit (power of 2)
-    pub(crate) files_per_step: usize,
-    /// Maximum file tree depth for uniform structure
-    pub(crate) file_tree_depth: usize,
-    /// Aggregated tree depth (0 for single-file)
-    pub(crate) aggregated_tree_depth: usize,
+    /// Circuit shape: file slots per step (power of 2), maximum file tree depth for
+    /// uniform structure, and aggregated tree depth (0 for file-root-bound proofs)
+    pub(crate) shape: config::Shape,
+    /// Number of passes over the slots; pass `p` holds slots `p * files_per_step..`
+    /// of the per-slot vectors below
+    pub(crate) num_passes: usize,
+    /// Root the proof binds its files to
+    pub(crate) binding: ProofBinding,
     /// Aggregated root (derived from ledger and challenge count)
+    #[cfg_attr(not(feature = "prover"), allow(dead_code))]
     pub(crate) aggregated_root: FieldElement,
     /// Challenges sorted by file hash for deterministic processing
     pub(crate) sorted_challenges: Vec<Challenge>,
-    /// Ledger indices for each file slot (padded with zeros)
+    /// Ledger indices for each file slot of every pass (padded with zeros)
     pub(crate) ledger_indices: Vec<usize>,
-    /// Actual depths for each file slot (padded with zeros)
+    /// Actual depths for each file slot of every pass (padded with zeros)
     pub(crate) depths: Vec<usize>,
-    /// Seeds for each file slot (padded with zeros)
+    /// Seeds for each file slot of every pass (padded with zeros)
     pub(crate) seeds: Vec<FieldElement>,
     /// Public I/O layout helper
     pub(crate) public_io_layout: config::PublicIOLayout,
+    /// Leaf exposure mode shared by all challenges
+    pub(crate) mode: ChallengeMode,
+    /// Ledger index checks enforced by the circuit, shared by all challenges
+    pub(crate) circuit_mode: CircuitMode,
+    /// Per-slot seed schedules when challenges use per-step seeds (padding slots are all zero)
+    pub(crate) seed_schedules: Option<Vec<Vec<FieldElement>>>,
+    /// Per-slot leaf indices when challenges use explicit indices (padding slots are all zero)
+    pub(crate) index_schedules: Option<Vec<Vec<usize>>>,
+    /// File tree arity shared by all challenged files
+    pub(crate) tree_arity: TreeArity,
+    /// File tree hash profile shared by all challenged files
+    pub(crate) hash_profile: HashProfile,
+    /// Initial chain state: the commitment to the sorted challenges' nonces
+    pub(crate) initial_state: FieldElement,
 }
 
 impl Plan {
     /// Create a unified preprocessing plan for both prove() and verify().
     pub(crate) fn make_plan(challenges: &[Challenge], ledger: &FileLedger) -> Result<Plan> {
+        Self::make_plan_at_root(challenges, ledger, ledger.root())
+    }
+
+    /// Like [`Self::make_plan`], but for a statement about `ledger_root`.
+    ///
+    /// When `ledger_root` is a retained historical root rather than the current one, a
+    /// challenged file's entry may since have been overwritten or removed. Its rc is then
+    /// derived from the challenge's metadata alone and its ledger index is left as a zero
+    /// placeholder: verification takes indices from the proof, whose SNARK proves the
+    /// membership of each proven file at its index under `ledger_root`. For the current
+    /// root (or a root the ledger does not know) every challenged rc must resolve in the
+    /// current ledger.
+    pub(crate) fn make_plan_at_root(
+        challenges: &[Challenge],
+        ledger: &FileLedger,
+        ledger_root: FieldElement,
+    ) -> Result<Plan> {
         if challenges.is_empty() {
             return Err(KontorPoRError::InvalidInput(
                 "Cannot create plan from empty challenges".to_string(),
             ));
         }
 
-        // Derive aggregated root internally based on number of challenges
-        // Single challenge = single-file proof (use file root)
-        // Multiple challenges = multi-file proof (use ledger root)
-        let aggregated_root = if challenges.len() > 1 {
-            // Multi-file case: use ledger root
-            ledger.tree.root()
-        } else {
-            // Single-file case: always use file root (even if ledger is provided)
-            challenges[0].file_metadata.root
+        // Metadata sizes the circuit shape, so oversized, overflowing or inconsistent claims
+        // must fail before any parameters are derived from them
+        for challenge in challenges {
+            challenge.file_metadata.check_size_limits()?;
+            challenge.file_metadata.validate()?;
+        }
+
+        // All challenges in a batch share one circuit, so they must agree on leaf exposure
+        let mode = challenges[0].mode;
+        if challenges.iter().any(|c| c.mode != mode) {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "mode".to_string(),
+            });
+        }
+        // A private-mode leaf is blinded with its first Merkle sibling, which a one-leaf file
+        // does not have: its blinding would be derived from the public seed alone
+        if mode.is_private() {
+            if let Some(challenge) = challenges
+                .iter()
+                .find(|c| crate::api::tree_depth_from_metadata(&c.file_metadata) == 0)
+            {
+                return Err(KontorPoRError::InvalidInput(format!(
+                    "Private mode requires files with at least two leaves; {} has one",
+                    challenge.file_metadata.file_id
+                )));
+            }
+        }
+
+        // Strict mode adds in-circuit ledger index checks, which also changes the shape
+        let circuit_mode = challenges[0].circuit_mode;
+        if challenges.iter().any(|c| c.circuit_mode != circuit_mode) {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "circuit_mode".to_string(),
+            });
+        }
+
+        // Padding the aggregation path changes the shape as well
+        let max_agg_depth = challenges[0].max_agg_depth;
+        if challenges.iter().any(|c| c.max_agg_depth != max_agg_depth) {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "max_agg_depth".to_string(),
+            });
+        }
+        if let Some(depth) = max_agg_depth.filter(|&d| d == 0 || d > config::MAX_AGG_DEPTH) {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "max_agg_depth {} must be between 1 and {}",
+                depth,
+                config::MAX_AGG_DEPTH
+            )));
+        }
+
+        // Seed schedules also change the circuit shape, so they must agree too, and a
+        // per-step schedule must cover every step exactly
+        let per_step_seeds = challenges[0].seed_schedule.is_per_step();
+        if challenges
+            .iter()
+            .any(|c| c.seed_schedule.is_per_step() != per_step_seeds)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "seed_schedule".to_string(),
+            });
+        }
+        for challenge in challenges {
+            if let SeedSchedule::PerStep(seeds) = &challenge.seed_schedule {
+                if seeds.len() != challenge.num_challenges {
+                    return Err(KontorPoRError::InvalidSeedSchedule {
+                        expected: challenge.num_challenges,
+                        got: seeds.len(),
+                    });
+                }
+            }
+        }
+
+        // Explicit indices replace seed-derived ones in the circuit, so the whole batch must
+        // use them or not; they also take over the seed outputs from per-step schedules
+        let explicit_indices = challenges[0].kind.is_explicit();
+        if challenges
+            .iter()
+            .any(|c| c.kind.is_explicit() != explicit_indices)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "kind".to_string(),
+            });
+        }
+        if explicit_indices && per_step_seeds {
+            return Err(KontorPoRError::InvalidInput(
+                "Explicit challenge indices cannot be combined with a per-step seed schedule"
+                    .to_string(),
+            ));
+        }
+        for challenge in challenges {
+            if let ChallengeKind::ExplicitIndices(indices) = &challenge.kind {
+                if indices.len() != challenge.num_challenges {
+                    return Err(KontorPoRError::InvalidExplicitIndices {
+                        expected: challenge.num_challenges,
+                        got: indices.len(),
+                    });
+                }
+                let leaves = challenge.file_metadata.padded_len;
+                if let Some(&index) = indices.iter().find(|&&index| index >= leaves) {
+                    return Err(KontorPoRError::ChallengeIndexOutOfRange {
+                        file_id: challenge.file_metadata.file_id.clone(),
+                        index,
+                        leaves,
+                    });
+                }
+            }
+        }
+
+        // The tree arity fixes the Merkle gadget, so every challenged file must share it
+        let tree_arity = challenges[0].file_metadata.tree_arity;
+        if challenges
+            .iter()
+            .any(|c| c.file_metadata.tree_arity != tree_arity)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "tree_arity".to_string(),
+            });
+        }
+
+        // Likewise the hash profile fixes the constants of the file tree hashes
+        let hash_profile = challenges[0].file_metadata.hash_profile;
+        if challenges
+            .iter()
+            .any(|c| c.file_metadata.hash_profile != hash_profile)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "hash_profile".to_string(),
+            });
+        }
+
+        // The binding decides whether files are opened against the ledger root or their own
+        // root; it also changes the shape, so the batch must agree on it
+        if challenges
+            .iter()
+            .any(|c| c.binding != challenges[0].binding)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "binding".to_string(),
+            });
+        }
+        // The slot cap decides the circuit size and the step count
+        let max_files_per_step = challenges[0].max_files_per_step;
+        if challenges
+            .iter()
+            .any(|c| c.max_files_per_step != max_files_per_step)
+        {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "max_files_per_step".to_string(),
+            });
+        }
+        if let Some(max) = max_files_per_step
+            .filter(|&max| !max.is_power_of_two() || max > config::PRACTICAL_MAX_FILES)
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "max_files_per_step {} must be a power of two up to {}",
+                max,
+                config::PRACTICAL_MAX_FILES
+            )));
+        }
+
+        let binding = ProofBinding::for_challenges(challenges);
+        if challenges.len() > 1 && !binding.is_ledger() {
+            return Err(KontorPoRError::InvalidInput(
+                "File-root binding requires a single challenge".to_string(),
+            ));
+        }
+
+        // Derive the aggregated root from the binding: the ledger root, or the single
+        // challenged file's root
+        let aggregated_root = match binding {
+            ProofBinding::LedgerRoot => ledger_root,
+            ProofBinding::FileRoot => challenges[0].file_metadata.root,
         };
+        let historical = binding.is_ledger()
+            && ledger_root != ledger.root()
+            && ledger.is_valid_root(ledger_root);
 
         // 1. Derive shape from challenges
         let max_file_depth = challenges
@@ -58,13 +449,20 @@ impl Plan {
             .map(|c| crate::api::tree_depth_from_metadata(&c.file_metadata))
             .max()
             .unwrap_or(0);
-        let (files_per_step, file_tree_depth) =
-            config::derive_shape(challenges.len(), max_file_depth);
-        let aggregated_tree_depth = if files_per_step > 1 {
-            ledger.tree.layers.len() - 1
-        } else {
-            0
-        };
+        let (_, file_tree_depth) = config::derive_shape(challenges.len(), max_file_depth);
+        let (files_per_step, num_passes) =
+            config::derive_partition(challenges.len(), max_files_per_step);
+        let num_slots = files_per_step * num_passes;
+
+        // Each slot's seed output accumulates its own schedule across every step, so
+        // schedules cannot follow a file through the passes
+        if num_passes > 1 && (per_step_seeds || explicit_indices) {
+            return Err(KontorPoRError::InvalidInput(
+                "Batches partitioned by max_files_per_step must use fixed seeds".to_string(),
+            ));
+        }
+        let aggregated_tree_depth =
+            config::derive_agg_depth(binding.is_ledger(), ledger.depth(), max_agg_depth);
 
         // 2. Sort challenges canonically by (file_id, challenge_id)
         //
@@ -79,62 +477,380 @@ impl Plan {
             }
         });
 
+        // Strict circuits require distinct ledger indices across active slots, so the same
+        // file cannot fill two slots
+        if circuit_mode.is_strict() {
+            if let Some(pair) = sorted_challenges
+                .windows(2)
+                .find(|pair| pair[0].file_metadata.file_id == pair[1].file_metadata.file_id)
+            {
+                return Err(KontorPoRError::InvalidInput(format!(
+                    "Strict circuit mode requires distinct files per batch; {} is challenged more than once",
+                    pair[0].file_metadata.file_id
+                )));
+            }
+        }
+
         // 3. Compute ledger indices
-        let mut ledger_indices = vec![0usize; files_per_step];
-        use crate::poseidon::calculate_root_commitment;
+        let mut ledger_indices = vec![0usize; num_slots];
 
         for (i, challenge) in sorted_challenges.iter().enumerate() {
             let file_depth = crate::api::tree_depth_from_metadata(&challenge.file_metadata);
-            let rc = calculate_root_commitment(
+            let rc = crate::ledger::compute_rc_with(
                 challenge.file_metadata.root,
-                FieldElement::from(file_depth as u64),
+                file_depth,
+                tree_arity,
+                hash_profile,
             );
 
-            let ledger_idx = ledger.get_canonical_index_for_rc(rc).ok_or_else(|| {
-                KontorPoRError::FileNotInLedger {
-                    file_id: challenge.file_metadata.file_id.clone(),
+            let file_id = &challenge.file_metadata.file_id;
+            let Some((rc_file_id, rc_idx)) = ledger.lookup_by_rc(rc) else {
+                // Entries overwritten since a historical root keep the placeholder index
+                if historical {
+                    continue;
                 }
-            })?;
+                return Err(KontorPoRError::FileNotInLedger {
+                    file_id: file_id.clone(),
+                });
+            };
 
-            ledger_indices[i] = ledger_idx;
+            // Copies of the same content under different IDs share an rc, which resolves
+            // to the lowest index; each copy keeps its own index when its ID is in the ledger
+            ledger_indices[i] = match ledger.lookup(file_id) {
+                Some((index, ledger_rc)) if rc_file_id != file_id && ledger_rc == rc => index,
+                _ => rc_idx,
+            };
         }
 
         // Compute actual depths for each challenge
-        let mut depths = vec![0usize; files_per_step];
+        let mut depths = vec![0usize; num_slots];
         for (i, challenge) in sorted_challenges.iter().enumerate() {
             let depth = crate::api::tree_depth_from_metadata(&challenge.file_metadata);
             depths[i] = depth;
         }
 
+        // Every challenged file and the ledger must fit the shape; anything deeper would be
+        // truncated during witness generation and only fail as an unsatisfied circuit
+        for (challenge, &depth) in sorted_challenges.iter().zip(&depths) {
+            if depth > file_tree_depth {
+                return Err(KontorPoRError::DepthExceedsShape {
+                    file_id: challenge.file_metadata.file_id.clone(),
+                    depth,
+                    max: file_tree_depth,
+                });
+            }
+        }
+        if binding.is_ledger() && ledger.depth() > aggregated_tree_depth {
+            return Err(KontorPoRError::LedgerDepthExceedsShape {
+                depth: ledger.depth(),
+                max: aggregated_tree_depth,
+            });
+        }
+
         // Collect seeds for each challenge
-        let mut seeds = vec![FieldElement::ZERO; files_per_step];
+        let mut seeds = vec![FieldElement::ZERO; num_slots];
         for (i, challenge) in sorted_challenges.iter().enumerate() {
             seeds[i] = challenge.seed;
         }
 
+        // Collect per-step seed schedules for each slot
+        let seed_schedules = per_step_seeds.then(|| {
+            let num_steps = sorted_challenges[0].num_challenges;
+            let mut schedules = vec![vec![FieldElement::ZERO; num_steps]; files_per_step];
+            for (i, challenge) in sorted_challenges.iter().enumerate() {
+                if let SeedSchedule::PerStep(seeds) = &challenge.seed_schedule {
+                    schedules[i] = seeds.clone();
+                }
+            }
+            schedules
+        });
+
+        // Collect explicit leaf indices for each slot
+        let index_schedules = explicit_indices.then(|| {
+            let num_steps = sorted_challenges[0].num_challenges;
+            let mut schedules = vec![vec![0usize; num_steps]; files_per_step];
+            for (i, challenge) in sorted_challenges.iter().enumerate() {
+                if let ChallengeKind::ExplicitIndices(indices) = &challenge.kind {
+                    schedules[i] = indices.clone();
+                }
+            }
+            schedules
+        });
+
+        // Nonces are folded in canonical order so caller ordering cannot change the state
+        let nonces: Vec<u64> = sorted_challenges.iter().map(|c| c.nonce).collect();
+        let initial_state = crate::poseidon::calculate_nonce_commitment(&nonces);
+
         // 4. Create public I/O layout helper
         let public_io_layout = config::PublicIOLayout::new(files_per_step);
 
         Ok(Plan {
-            files_per_step,
-            file_tree_depth,
-            aggregated_tree_depth,
+            shape: config::Shape::new(files_per_step, file_tree_depth, aggregated_tree_depth),
+            num_passes,
+            binding,
             aggregated_root,
             sorted_challenges,
             ledger_indices,
             depths,
             seeds,
             public_io_layout,
+            mode,
+            circuit_mode,
+            seed_schedules,
+            index_schedules,
+            tree_arity,
+            hash_profile,
+            initial_state,
         })
     }
 
+    /// Per-slot seeds for `step` in per-step mode (None for fixed seeds).
+    pub(crate) fn step_seeds(&self, step: usize) -> Option<Vec<FieldElement>> {
+        self.seed_schedules.as_ref().map(|schedules| {
+            schedules
+                .iter()
+                .map(|seeds| seeds.get(step).copied().unwrap_or(FieldElement::ZERO))
+                .collect()
+        })
+    }
+
+    /// Per-slot explicit leaf indices for `step` in explicit-index mode (None for seeded
+    /// challenges).
+    #[cfg(feature = "prover")]
+    pub(crate) fn step_indices(&self, step: usize) -> Option<Vec<FieldElement>> {
+        self.index_schedules.as_ref().map(|schedules| {
+            schedules
+                .iter()
+                .map(|indices| FieldElement::from(indices.get(step).copied().unwrap_or(0) as u64))
+                .collect()
+        })
+    }
+
+    /// Expected final seed outputs per slot in per-step or explicit-index mode (None for
+    /// fixed seeds).
+    ///
+    /// Each slot's seed output accumulates its schedule (or its explicit indices) starting
+    /// from the slot's public seed input, so the verifier can check every step seed or
+    /// opened index and its order.
+    pub(crate) fn expected_seed_outputs(&self) -> Option<Vec<FieldElement>> {
+        if let Some(schedules) = &self.index_schedules {
+            return Some(
+                schedules
+                    .iter()
+                    .zip(&self.seeds)
+                    .map(|(indices, seed)| {
+                        crate::poseidon::accumulate_explicit_indices(*seed, indices)
+                    })
+                    .collect(),
+            );
+        }
+        self.seed_schedules.as_ref().map(|schedules| {
+            schedules
+                .iter()
+                .zip(&self.seeds)
+                .map(|(schedule, seed)| crate::poseidon::accumulate_seed_schedule(*seed, schedule))
+                .collect()
+        })
+    }
+
+    /// Circuit parameter shape for this plan's circuit variant with the given dimensions.
+    ///
+    /// The prover passes the plan's own shape; the verifier passes the shape the proof
+    /// records, once it has checked it against the plan's.
+    pub(crate) fn param_shape(&self, shape: config::Shape) -> crate::params::Shape {
+        crate::params::Shape::from(shape)
+            .with_mode(self.mode)
+            .with_per_step_seeds(self.seed_schedules.is_some())
+            .with_explicit_indices(self.index_schedules.is_some())
+            .with_partitioned(self.num_passes > 1)
+            .with_tree_arity(self.tree_arity)
+            .with_hash_profile(self.hash_profile)
+            .with_circuit_mode(self.circuit_mode)
+    }
+
+    /// Circuit slot of each challenge in `challenges`, matched by challenge ID.
+    ///
+    /// Repeated IDs take the plan's slots for that ID in order, and share the last one once
+    /// they run out, so a plan of collapsed duplicates maps them all to one slot.
+    pub(crate) fn challenge_slots(&self, challenges: &[Challenge]) -> Vec<usize> {
+        let ids: Vec<ChallengeID> = self.sorted_challenges.iter().map(Challenge::id).collect();
+        let mut taken = vec![false; ids.len()];
+        challenges
+            .iter()
+            .map(|challenge| {
+                let id = challenge.id();
+                let mut matching = (0..ids.len()).filter(|&slot| ids[slot] == id);
+                let slot = matching
+                    .clone()
+                    .find(|&slot| !taken[slot])
+                    .or_else(|| matching.next_back());
+                match slot {
+                    Some(slot) => {
+                        taken[slot] = true;
+                        slot
+                    }
+                    None => usize::MAX,
+                }
+            })
+            .collect()
+    }
+
+    /// Number of folded circuit steps (the shared `num_challenges`, once per pass).
+    pub(crate) fn num_steps(&self) -> usize {
+        self.sorted_challenges[0].num_challenges * self.num_passes
+    }
+
+    /// Pass answered by circuit step `step`.
+    pub(crate) fn pass_of(&self, step: usize) -> usize {
+        step % self.num_passes
+    }
+
+    /// Challenge step (the index into each challenge's `num_challenges`) of circuit step
+    /// `step`.
+    pub(crate) fn challenge_step(&self, step: usize) -> usize {
+        step / self.num_passes
+    }
+
+    /// Positions of `pass`'s slots in the per-slot vectors.
+    pub(crate) fn pass_slots(&self, pass: usize) -> std::ops::Range<usize> {
+        let files_per_step = self.shape.files_per_step;
+        pass * files_per_step..(pass + 1) * files_per_step
+    }
+
+    /// The challenges filling `pass`'s slots, in slot order.
+    pub(crate) fn pass_challenges(&self, pass: usize) -> &[Challenge] {
+        let slots = self.pass_slots(pass);
+        let len = self.sorted_challenges.len();
+        &self.sorted_challenges[slots.start.min(len)..slots.end.min(len)]
+    }
+
+    /// Ledger index, depth and seed inputs of `pass`'s slots in public I/O order, for the
+    /// given ledger indices of every slot (the plan's own for the prover, the proof's for
+    /// the verifier).
+    ///
+    /// Every step of a partitioned batch takes its pass's inputs as private circuit inputs
+    /// and folds them into its public slot outputs (see [`Self::expected_slot_outputs`]).
+    pub(crate) fn pass_inputs(&self, pass: usize, ledger_indices: &[usize]) -> Vec<FieldElement> {
+        let slots = self.pass_slots(pass);
+        let field = |value: usize| FieldElement::from(value as u64);
+        let ledger_indices = ledger_indices
+            .get(slots.clone())
+            .unwrap_or_default()
+            .iter()
+            .map(|&i| field(i));
+        let depths = self.depths[slots.clone()].iter().map(|&d| field(d));
+        ledger_indices
+            .chain(depths)
+            .chain(self.seeds[slots].iter().copied())
+            .collect()
+    }
+
+    /// Expected final ledger index, depth and seed outputs of a partitioned batch (None for
+    /// a single pass), for the given ledger indices of every slot.
+    ///
+    /// Each output starts from its public input (the first pass's value) and accumulates
+    /// the inputs of the pass every step answered, in step order, so the verifier checks
+    /// that each step opened the files of its pass.
+    pub(crate) fn expected_slot_outputs(
+        &self,
+        ledger_indices: &[usize],
+    ) -> Option<Vec<FieldElement>> {
+        if self.num_passes == 1 {
+            return None;
+        }
+        let passes: Vec<Vec<FieldElement>> = (0..self.num_passes)
+            .map(|pass| self.pass_inputs(pass, ledger_indices))
+            .collect();
+        Some(
+            passes[0]
+                .iter()
+                .enumerate()
+                .map(|(k, initial)| {
+                    let inputs: Vec<FieldElement> = (0..self.num_steps())
+                        .filter_map(|step| passes[self.pass_of(step)].get(k).copied())
+                        .collect();
+                    crate::poseidon::accumulate_pass_schedule(*initial, &inputs)
+                })
+                .collect(),
+        )
+    }
+
+    /// Public description of this plan's step and slot layout.
+    pub(crate) fn describe(&self) -> ProofPlan {
+        let total_steps = self.num_steps();
+        let steps = (0..total_steps)
+            .map(|step| {
+                let pass = self.pass_of(step);
+                let challenge_step = self.challenge_step(step);
+                let step_seeds = self.step_seeds(challenge_step);
+                let slots = self
+                    .pass_slots(pass)
+                    .enumerate()
+                    .map(|(slot, position)| {
+                        let challenge = self.sorted_challenges.get(position);
+                        let seed = match &step_seeds {
+                            Some(seeds) => seeds[position],
+                            None => self.seeds[position],
+                        };
+                        SlotAssignment {
+                            slot,
+                            file_id: challenge.map(|c| c.file_metadata.file_id.clone()),
+                            challenge_id: challenge.map(Challenge::id),
+                            ledger_index: self.ledger_indices[position],
+                            depth: self.depths[position],
+                            seed,
+                            explicit_index: challenge
+                                .and_then(|c| c.explicit_index(challenge_step)),
+                        }
+                    })
+                    .collect();
+                StepPlan { step, pass, slots }
+            })
+            .collect();
+
+        ProofPlan {
+            files_per_step: self.shape.files_per_step,
+            num_passes: self.num_passes,
+            file_tree_depth: self.shape.file_tree_depth,
+            aggregated_tree_depth: self.shape.aggregated_tree_depth,
+            binding: self.binding,
+            tree_arity: self.tree_arity,
+            hash_profile: self.hash_profile,
+            mode: self.mode,
+            circuit_mode: self.circuit_mode,
+            total_steps,
+            steps,
+            estimated_constraints_per_step: estimate_step_constraints(
+                self.shape.files_per_step,
+                self.shape.file_tree_depth,
+                self.shape.aggregated_tree_depth,
+            ) + self
+                .circuit_mode
+                .extra_constraints(self.shape.files_per_step, self.shape.aggregated_tree_depth),
+        }
+    }
+
     /// Build the z0_primary vector using this plan
+    #[cfg(feature = "prover")]
     pub(crate) fn build_z0_primary(&self) -> Vec<FieldElement> {
+        self.z0_primary(self.aggregated_root, &self.ledger_indices)
+    }
+
+    /// The z0_primary vector for the given root and ledger indices of every slot (the
+    /// plan's own for the prover, the proof's for the verifier). Step 0 answers the first
+    /// pass, so only its slots enter the public inputs.
+    pub(crate) fn z0_primary(
+        &self,
+        aggregated_root: FieldElement,
+        ledger_indices: &[usize],
+    ) -> Vec<FieldElement> {
+        let slots = self.pass_slots(0);
         self.public_io_layout.build_z0_primary(
-            self.aggregated_root,
-            &self.ledger_indices,
-            &self.depths,
-            &self.seeds,
+            aggregated_root,
+            self.initial_state,
+            ledger_indices.get(slots.clone()).unwrap_or(ledger_indices),
+            &self.depths[slots.clone()],
+            &self.seeds[slots],
         )
     }
 }
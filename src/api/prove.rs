@@ -4,18 +4,26 @@
 //! broken down into focused, manageable functions.
 
 use super::{
-    plan::Plan,
-    types::{Challenge, FieldElement, PorParams, PreparedFile, Proof},
-    witness::generate_circuit_witness,
+    checkpoint::{read_checkpoint, CheckpointDigests, CheckpointWriter},
+    plan::{collapse_duplicates, plan_batch, Plan},
+    types::{Challenge, FieldElement, PorParams, PreparedFile, Proof, ProveOptions},
+    witness::generate_pass_witness,
+};
+use crate::instrumentation::{debug, debug_span, field, info_span, trace, warn, Span};
+use crate::{
+    circuit::CircuitWitness,
+    circuit_safety::diagnose_unsatisfied,
+    ledger::FileLedger,
+    metrics::{record_timing, Phase, ProveTimings},
+    KontorPoRError, Result,
 };
-use crate::{config, ledger::FileLedger, KontorPoRError, Result};
-use ff::Field;
 use nova_snark::{
     nova::{CompressedSNARK, RecursiveSNARK},
     provider::{PallasEngine, VestaEngine},
 };
-use std::collections::BTreeMap;
-use tracing::{debug, debug_span, info_span, trace};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Instant;
 
 // Type aliases needed for proving
 type E1 = PallasEngine;
@@ -26,30 +34,148 @@ type NovaProof = RecursiveSNARK<E1, E2, C>;
 /// Generates a proof for one or more file challenges.
 ///
 /// This function implements the Option 1 security model with public ledger indices.
-/// Parameters are derived automatically from the challenges.
+/// Parameters are derived automatically from the challenges. The returned
+/// [`ProveTimings`] break the call down into its phases.
 pub fn prove(
     challenges: &[Challenge],
     files: &BTreeMap<String, &PreparedFile>,
     ledger: &FileLedger,
+    options: &ProveOptions,
     progress_callback: Option<&dyn Fn()>,
-) -> Result<Proof> {
+) -> Result<(Proof, ProveTimings)> {
+    prove_from(challenges, files, ledger, options, progress_callback, None)
+}
+
+/// Generates a proof like [`prove`], saving a checkpoint to `checkpoint` after every
+/// `every_n_steps` folded steps so an interrupted run can continue with [`resume_prove`].
+pub(crate) fn prove_with_checkpoint(
+    challenges: &[Challenge],
+    files: &BTreeMap<String, &PreparedFile>,
+    ledger: &FileLedger,
+    checkpoint: &Path,
+    every_n_steps: usize,
+) -> Result<(Proof, ProveTimings)> {
+    if every_n_steps == 0 {
+        return Err(KontorPoRError::InvalidInput(
+            "Checkpoint interval must be at least one step".to_string(),
+        ));
+    }
+    prove_from(
+        challenges,
+        files,
+        ledger,
+        &ProveOptions::default(),
+        None,
+        Some((checkpoint, CheckpointStart::Fresh { every_n_steps })),
+    )
+}
+
+/// Continues a proof from the checkpoint written by [`prove_with_checkpoint`].
+///
+/// Fails with [`KontorPoRError::CheckpointMismatch`] if the checkpoint was taken for
+/// different challenges, ledger or parameters. Proving keeps checkpointing at the saved
+/// interval.
+pub(crate) fn resume_prove(
+    challenges: &[Challenge],
+    files: &BTreeMap<String, &PreparedFile>,
+    ledger: &FileLedger,
+    checkpoint: &Path,
+) -> Result<(Proof, ProveTimings)> {
+    prove_from(
+        challenges,
+        files,
+        ledger,
+        &ProveOptions::default(),
+        None,
+        Some((checkpoint, CheckpointStart::Resume)),
+    )
+}
+
+/// How a checkpointed proof starts.
+enum CheckpointStart {
+    /// Fold from step 0, checkpointing every `every_n_steps` steps
+    Fresh { every_n_steps: usize },
+    /// Continue from the steps saved in the checkpoint
+    Resume,
+}
+
+/// Shared body of [`prove`], [`prove_with_checkpoint`] and [`resume_prove`].
+fn prove_from(
+    challenges: &[Challenge],
+    files: &BTreeMap<String, &PreparedFile>,
+    ledger: &FileLedger,
+    options: &ProveOptions,
+    progress_callback: Option<&dyn Fn()>,
+    checkpoint: Option<(&Path, CheckpointStart)>,
+) -> Result<(Proof, ProveTimings)> {
     let _span = info_span!(
         "prove",
         num_challenges = challenges.len(),
         num_files = files.len(),
         has_ledger = true,
-        files_per_step = tracing::field::Empty,
-        file_tree_depth = tracing::field::Empty,
-        aggregated_tree_depth = tracing::field::Empty,
+        files_per_step = field::Empty,
+        file_tree_depth = field::Empty,
+        aggregated_tree_depth = field::Empty,
     )
     .entered();
 
-    // Setup: validate inputs, create plan, load parameters
-    let (plan, params, num_challenges) = setup_proving_environment(challenges, files, ledger)?;
+    let mut timings = ProveTimings::default();
+
+    // Identical challenges prove the same statement, so they share one slot unless the
+    // caller opts out; the proof still lists every challenge and maps it to its slot
+    let collapsed = if options.allow_duplicates {
+        None
+    } else {
+        collapse_duplicates(challenges)
+    };
+    let planned = collapsed.as_deref().unwrap_or(challenges);
 
-    // Initialize recursive SNARK with first witness and circuit
-    let (mut recursive_snark, current_state) =
-        initialize_recursive_snark(&plan, &params, files, ledger)?;
+    // Setup: validate inputs, create plan, load parameters
+    let (plan, params, num_challenges) =
+        setup_proving_environment(planned, files, ledger, options, &mut timings)?;
+    transcript_record!(|t| t.record_plan(&plan, plan.aggregated_root, &plan.ledger_indices));
+
+    // Initialize recursive SNARK with first witness and circuit, or pick up from a checkpoint
+    let mut checkpoint_writer = None;
+    let (mut recursive_snark, current_state, start_step) = match checkpoint {
+        Some((path, CheckpointStart::Resume)) => {
+            let digests = CheckpointDigests::new(&plan, &params);
+            let saved = read_checkpoint(path, &digests, num_challenges)?;
+            checkpoint_writer = Some(CheckpointWriter {
+                path,
+                every_n_steps: saved.every_n_steps,
+                digests,
+            });
+            (saved.recursive_snark, saved.state, saved.next_step)
+        }
+        Some((path, CheckpointStart::Fresh { every_n_steps })) => {
+            checkpoint_writer = Some(CheckpointWriter {
+                path,
+                every_n_steps,
+                digests: CheckpointDigests::new(&plan, &params),
+            });
+            let (recursive_snark, current_state) = initialize_recursive_snark(
+                &plan,
+                &params,
+                files,
+                ledger,
+                options.precheck_constraints,
+                &mut timings,
+            )?;
+            (recursive_snark, current_state, 0)
+        }
+        None => {
+            let (recursive_snark, current_state) = initialize_recursive_snark(
+                &plan,
+                &params,
+                files,
+                ledger,
+                options.precheck_constraints,
+                &mut timings,
+            )?;
+            (recursive_snark, current_state, 0)
+        }
+    };
 
     // Execute the main proving loop
     let _final_state = execute_proving_loop(
@@ -58,161 +184,243 @@ pub fn prove(
         &params,
         files,
         ledger,
+        start_step,
         num_challenges,
         current_state,
         progress_callback,
+        checkpoint_writer.as_ref(),
+        options.precheck_constraints,
+        &mut timings,
     )?;
+    transcript_record!(|t| t.record_final_state(_final_state));
 
     let _compress_span = info_span!("CompressedSNARK::prove").entered();
+    let compress_start = Instant::now();
     let compressed_snark = CompressedSNARK::prove(&params.pp, &params.keys.pk, &recursive_snark)
         .map_err(|e| KontorPoRError::Snark(format!("Proof compression failed: {e:?}")))?;
+    timings.compression_duration = compress_start.elapsed();
+    record_timing(Phase::Compress, None, timings.compression_duration);
+    fail_point!(crate::failpoints::points::PROVE_BEFORE_SERIALIZATION);
 
     // Collect challenge IDs in order
     let challenge_ids: Vec<super::types::ChallengeID> = challenges.iter().map(|c| c.id()).collect();
 
-    Ok(super::types::Proof {
+    let proof = super::types::Proof {
         compressed_snark,
         challenge_ids,
         ledger_root: plan.aggregated_root,
         ledger_indices: plan.ledger_indices.clone(),
-        aggregated_tree_depth: plan.aggregated_tree_depth,
-    })
+        file_depths: plan.depths.clone(),
+        shape: plan.shape,
+        challenge_nonce_commitment: plan.initial_state,
+        challenge_slots: plan.challenge_slots(challenges),
+        public_inputs: plan.build_z0_primary(),
+        public_outputs: recursive_snark.outputs().to_vec(),
+        hash_profile: plan.hash_profile,
+        schema_version: crate::config::PUBLIC_IO_SCHEMA_VERSION,
+        embedded_challenges: options.embed_challenges.then(|| challenges.to_vec()),
+    };
+
+    Ok((proof, timings))
 }
 
-/// Setup proving environment: validate inputs, create plan, load parameters.
-fn setup_proving_environment(
+/// Pre-flight check that `files` covers exactly the files referenced by `challenges`.
+///
+/// Every challenged `file_id` without a prepared file is reported at once in
+/// [`KontorPoRError::MissingPreparedFile`]. Prepared files that no challenge refers to
+/// are logged, or rejected with [`KontorPoRError::UnusedPreparedFile`] if
+/// `reject_unused` is set. A prepared file keyed under several file IDs is referenced if
+/// any of them is challenged.
+pub(crate) fn check_file_coverage(
     challenges: &[Challenge],
     files: &BTreeMap<String, &PreparedFile>,
-    ledger: &FileLedger,
-) -> Result<(Plan, PorParams, usize)> {
-    if challenges.is_empty() {
-        return Err(KontorPoRError::InvalidInput(
-            "prove: Must provide at least one challenge to generate a proof".to_string(),
-        ));
+    reject_unused: bool,
+) -> Result<()> {
+    let mut missing: Vec<String> = Vec::new();
+    for challenge in challenges {
+        let file_id = &challenge.file_metadata.file_id;
+        if !files.contains_key(file_id) && !missing.contains(file_id) {
+            missing.push(file_id.clone());
+        }
     }
-
-    // Practical limit for number of files (can be adjusted based on needs)
-    if challenges.len() > config::PRACTICAL_MAX_FILES {
-        return Err(KontorPoRError::TooManyFiles {
-            got: challenges.len(),
-            max: config::PRACTICAL_MAX_FILES,
-        });
+    if !missing.is_empty() {
+        return Err(KontorPoRError::MissingPreparedFile { file_ids: missing });
     }
 
-    // Verify all challenges use the same num_challenges (Nova requirement)
-    let num_challenges = challenges[0].num_challenges;
-
-    if num_challenges == 0 || num_challenges > config::MAX_NUM_CHALLENGES {
-        return Err(KontorPoRError::InvalidChallengeCount {
-            count: num_challenges,
+    for (file_id, file) in files {
+        let referenced = challenges.iter().any(|c| {
+            files
+                .get(&c.file_metadata.file_id)
+                .is_some_and(|challenged| std::ptr::eq(*challenged, *file))
         });
-    }
-
-    for challenge in challenges.iter() {
-        if challenge.num_challenges != num_challenges {
-            return Err(KontorPoRError::ChallengeMismatch {
-                field: "num_challenges".to_string(),
-            });
+        if !referenced {
+            if reject_unused {
+                return Err(KontorPoRError::UnusedPreparedFile {
+                    file_id: file_id.clone(),
+                });
+            }
+            warn!(file_id = %file_id, "Prepared file is not referenced by any challenge");
         }
     }
 
-    // Validate all files
+    Ok(())
+}
+
+/// Setup proving environment: validate inputs, create plan, load parameters.
+fn setup_proving_environment(
+    challenges: &[Challenge],
+    files: &BTreeMap<String, &PreparedFile>,
+    ledger: &FileLedger,
+    options: &ProveOptions,
+    timings: &mut ProveTimings,
+) -> Result<(Plan, PorParams, usize)> {
+    let plan_start = Instant::now();
+
+    // Validate all files. A prepared file must match the challenged content, not the
+    // file ID, so one copy can serve the same data registered under several IDs
+    let mut validated = BTreeSet::new();
     for challenge in challenges.iter() {
         let file = files.get(&challenge.file_metadata.file_id).ok_or_else(|| {
-            KontorPoRError::FileNotFound {
-                file_id: challenge.file_metadata.file_id.clone(),
+            KontorPoRError::MissingPreparedFile {
+                file_ids: vec![challenge.file_metadata.file_id.clone()],
             }
         })?;
 
-        if file.tree.root() != challenge.file_metadata.root {
+        if file.is_zeroized() {
+            return Err(KontorPoRError::PreparedFileZeroized {
+                file_id: file.file_id.clone(),
+            });
+        }
+        if !file.has_content_of(&challenge.file_metadata) {
             return Err(KontorPoRError::MetadataMismatch);
         }
+        if validated.insert(&file.file_id) {
+            file.validate_with_seed(options.leaf_validation, options.rng_seed)?;
+        }
     }
 
-    // Create unified preprocessing plan
-    let plan = Plan::make_plan(challenges, ledger)?;
+    // Validate the batch and create the unified preprocessing plan
+    let plan = plan_batch(challenges, ledger)?;
+    let num_challenges = plan.num_steps();
+
+    // The shape comes from the metadata; a prepared tree deeper than its metadata claims
+    // must fail here, before parameters are loaded for a shape it cannot fit
+    check_prepared_file_depths(&plan, files)?;
+    timings.plan_duration = plan_start.elapsed();
+    record_timing(Phase::Plan, None, timings.plan_duration);
 
     // Load or generate parameters for the exact shape
-    let params = crate::params::load_or_generate_params(
-        plan.files_per_step,
-        plan.file_tree_depth,
-        plan.aggregated_tree_depth,
-    )?;
+    let param_start = Instant::now();
+    let params = crate::params::load_or_generate_params_for_shape(&plan.param_shape(plan.shape))?;
+    timings.param_load_duration = param_start.elapsed();
 
     debug!(
         "prove() - Using shape: files_per_step={}, file_tree_depth={}, aggregated_tree_depth={}",
-        plan.files_per_step, plan.file_tree_depth, plan.aggregated_tree_depth
+        plan.shape.files_per_step, plan.shape.file_tree_depth, plan.shape.aggregated_tree_depth
     );
     // Record shape metrics in span for extraction
-    tracing::Span::current().record("files_per_step", plan.files_per_step);
-    tracing::Span::current().record("file_tree_depth", plan.file_tree_depth);
-    tracing::Span::current().record("aggregated_tree_depth", plan.aggregated_tree_depth);
-
-    if plan.aggregated_tree_depth == 0 {
-        debug!("[DEBUG] prove() - Single-file proof:");
-        debug!("  - Number of challenges: {}", plan.sorted_challenges.len());
-        debug!("  - Aggregated depth: {}", plan.aggregated_tree_depth);
-    } else {
-        debug!("[DEBUG] prove() - Multi-file proof:");
-        debug!("  - Number of challenges: {}", plan.sorted_challenges.len());
-        debug!("  - Aggregated depth: {}", plan.aggregated_tree_depth);
-    }
+    Span::current().record("files_per_step", plan.shape.files_per_step);
+    Span::current().record("file_tree_depth", plan.shape.file_tree_depth);
+    Span::current().record("aggregated_tree_depth", plan.shape.aggregated_tree_depth);
 
     debug!(
-        "[DEBUG] prove() - Multi-file proof with {} challenges per file",
-        num_challenges
+        single_file = plan.shape.aggregated_tree_depth == 0,
+        num_files = plan.sorted_challenges.len(),
+        challenges_per_file = num_challenges,
+        aggregated_tree_depth = plan.shape.aggregated_tree_depth,
+        aggregated_root = ?plan.aggregated_root,
+        "prove() - proof generation"
     );
 
-    debug!("prove() - Multi-file proof generation:");
-    debug!("  - Number of files: {}", plan.sorted_challenges.len());
-    debug!("  - Challenges per file: {}", num_challenges);
-    debug!("  - Aggregated tree depth: {}", plan.aggregated_tree_depth);
-    debug!("  - Aggregated root: {:?}", plan.aggregated_root);
-
     Ok((plan, params, num_challenges))
 }
 
+/// Check that every challenged prepared file's tree fits the plan's `file_tree_depth`.
+fn check_prepared_file_depths(plan: &Plan, files: &BTreeMap<String, &PreparedFile>) -> Result<()> {
+    for challenge in &plan.sorted_challenges {
+        let file_id = &challenge.file_metadata.file_id;
+        if let Some(file) = files.get(file_id) {
+            let depth = file.tree.layers.len() - 1;
+            if depth > plan.shape.file_tree_depth {
+                return Err(KontorPoRError::DepthExceedsShape {
+                    file_id: file_id.clone(),
+                    depth,
+                    max: plan.shape.file_tree_depth,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build the step circuit for `step` with the plan's shape, leaf mode, step seeds, tree
+/// arity and hash profile.
+///
+/// The witness is validated against the plan's shape first so malformed witnesses fail
+/// with a [`crate::WitnessError`] instead of inside synthesis.
+fn step_circuit(
+    plan: &Plan,
+    witness: Option<&CircuitWitness<FieldElement>>,
+    step: usize,
+) -> Result<C> {
+    if let Some(witness) = witness {
+        witness.validate_for_tree_arity(
+            plan.shape.files_per_step,
+            plan.shape.file_tree_depth,
+            plan.shape.aggregated_tree_depth,
+            plan.tree_arity,
+        )?;
+    }
+
+    Ok(C::new(plan.shape, witness.map(|w| w.witnesses().to_vec()))
+        .with_ledger_depth(witness.and_then(|w| w.ledger_depth()))
+        .with_private_leaves(plan.mode.is_private())
+        .with_step_seeds(plan.step_seeds(plan.challenge_step(step)))
+        .with_explicit_indices(plan.step_indices(plan.challenge_step(step)))
+        .with_pass_inputs(
+            (plan.num_passes > 1)
+                .then(|| plan.pass_inputs(plan.pass_of(step), &plan.ledger_indices)),
+        )
+        .with_tree_arity(plan.tree_arity)
+        .with_hash_profile(plan.hash_profile)
+        .with_circuit_mode(plan.circuit_mode))
+}
+
 /// Initialize the recursive SNARK with the first witness and circuit.
 fn initialize_recursive_snark(
     plan: &Plan,
     params: &PorParams,
     files: &BTreeMap<String, &PreparedFile>,
     ledger: &FileLedger,
+    precheck: bool,
+    timings: &mut ProveTimings,
 ) -> Result<(NovaProof, FieldElement)> {
-    // Generate witnesses for the first step using the canonical function
-    let current_state = FieldElement::ZERO;
-    let sorted_challenges_refs: Vec<&Challenge> = plan.sorted_challenges.iter().collect();
-    let (circuit_witness, new_state) = generate_circuit_witness(
-        &sorted_challenges_refs,
-        Some(files), // Pass actual files for proving
-        ledger,
-        plan.file_tree_depth,
-        plan.file_tree_depth, // For exact shape, both are the same
-        current_state,
-        plan.aggregated_tree_depth,
-        0,                    // step 0
-        &plan.ledger_indices, // Pass precomputed indices from plan
-    )?;
+    // Generate witnesses for the first step using the canonical function, starting the
+    // chain from the nonce commitment
+    let current_state = plan.initial_state;
+    let witness_start = Instant::now();
+    let (circuit_witness, new_state) =
+        generate_pass_witness(plan, files, ledger, current_state, 0)?;
+    let witness_duration = witness_start.elapsed();
+    timings.witness_gen_duration += witness_duration;
+    record_timing(Phase::WitnessGen, Some(0), witness_duration);
+    fail_point!(crate::failpoints::points::PROVE_AFTER_WITNESS_GEN);
 
-    debug!("prove() - After initial witness generation:");
-    debug!("  - State after step 0: {:?}", new_state);
-    debug!("  - Witness count: {}", circuit_witness.witnesses().len());
-    debug!("  - Real files: {}", circuit_witness.num_real_files());
+    debug!(
+        state = ?new_state,
+        num_witnesses = circuit_witness.witnesses().len(),
+        num_real_files = circuit_witness.num_real_files(),
+        "prove() - after initial witness generation"
+    );
 
     // Build public inputs using the plan
     let z0_primary = plan.build_z0_primary();
-    debug!("PROVER z0_primary: {:?}", z0_primary);
 
     // Create the circuit for new() with witness from first challenge
-    let circuit_first = C::new(
-        plan.files_per_step,
-        plan.file_tree_depth,
-        plan.aggregated_tree_depth,
-        Some(circuit_witness.witnesses().to_vec()),
-    );
+    let circuit_first = step_circuit(plan, Some(&circuit_witness), 0)?;
     trace!(
         "Created circuit_first for new() with agg_depth={}, witnesses={}",
-        plan.aggregated_tree_depth,
+        plan.shape.aggregated_tree_depth,
         circuit_witness.witnesses().len()
     );
 
@@ -228,28 +436,28 @@ fn initialize_recursive_snark(
     }
 
     // Create initial recursive SNARK (handles first challenge)
-    debug!("prove() - Creating NovaProof::new with z0_primary:");
-    debug!("  [0] aggregated_root: {:?}", z0_primary[0]);
-    debug!("  [1] initial_state: {:?}", z0_primary[1]);
-    for (i, idx) in plan.ledger_indices.iter().enumerate() {
-        debug!("  [{}] ledger_index_{}: {:?}", 2 + i, i, idx);
-    }
-    for (i, depth) in plan.depths.iter().enumerate() {
-        debug!("  [{}] depth_{}: {}", 2 + plan.files_per_step + i, i, depth);
-    }
-    for (i, seed) in plan.seeds.iter().enumerate() {
-        debug!(
-            "  [{}] seed_{}: {:?}",
-            2 + plan.files_per_step + plan.files_per_step + i,
-            i,
-            seed
-        );
+    debug!(
+        aggregated_root = ?z0_primary[0],
+        initial_state = ?z0_primary[1],
+        ledger_indices = ?plan.ledger_indices,
+        depths = ?plan.depths,
+        seeds = ?plan.seeds,
+        "prove() - creating NovaProof::new with z0_primary"
+    );
+
+    if precheck {
+        precheck_step(&circuit_first, &z0_primary, 0)?;
     }
 
     let recursive_snark = {
         let _span = debug_span!("RecursiveSNARK::new").entered();
-        NovaProof::new(&params.pp, &circuit_first, &z0_primary)
-            .map_err(|e| KontorPoRError::Snark(format!("Initial SNARK creation failed: {e:?}")))?
+        let fold_start = Instant::now();
+        let snark = NovaProof::new(&params.pp, &circuit_first, &z0_primary)
+            .map_err(|e| KontorPoRError::Snark(format!("Initial SNARK creation failed: {e:?}")))?;
+        let fold_duration = fold_start.elapsed();
+        timings.folding_duration += fold_duration;
+        record_timing(Phase::Fold, Some(0), fold_duration);
+        snark
     };
 
     trace!("NovaProof::new completed successfully");
@@ -257,7 +465,32 @@ fn initialize_recursive_snark(
     Ok((recursive_snark, new_state))
 }
 
-/// Execute the main proving loop with prove_step calls.
+/// Synthesize the circuit for `step` on its inputs `z` into a test constraint system and
+/// fail with [`KontorPoRError::ConstraintPrecheckFailed`] naming the first constraint the
+/// witness breaks, instead of letting Nova fold an unsatisfiable step.
+fn precheck_step(circuit: &C, z: &[FieldElement], step: usize) -> Result<()> {
+    let report = diagnose_unsatisfied(circuit, z).map_err(|e| {
+        KontorPoRError::Circuit(format!("Pre-check synthesis of step {step} failed: {e:?}"))
+    })?;
+    debug!(
+        step,
+        num_constraints = report.num_constraints,
+        satisfied = report.is_satisfied(),
+        "prove() - constraint pre-check"
+    );
+    match report.unsatisfied {
+        None => Ok(()),
+        Some(constraint) => Err(KontorPoRError::ConstraintPrecheckFailed {
+            step,
+            path: constraint.path,
+            class: constraint.class,
+            file_slot: constraint.file_slot,
+        }),
+    }
+}
+
+/// Execute the main proving loop with prove_step calls, starting at `start_step`
+/// (non-zero only when resuming from a checkpoint).
 #[allow(clippy::too_many_arguments)]
 fn execute_proving_loop(
     recursive_snark: &mut NovaProof,
@@ -265,9 +498,13 @@ fn execute_proving_loop(
     params: &PorParams,
     files: &BTreeMap<String, &PreparedFile>,
     ledger: &FileLedger,
+    start_step: usize,
     num_challenges: usize,
     mut current_state: FieldElement,
     progress_callback: Option<&dyn Fn()>,
+    checkpoint: Option<&CheckpointWriter<'_>>,
+    precheck: bool,
+    timings: &mut ProveTimings,
 ) -> Result<FieldElement> {
     // THIS IS IMPORTANT: Main proving loop - call prove_step N times (first call is a no-op)
     // ATTENTION: Arecibo's prove_step explicitly no-ops on first call after new()
@@ -280,9 +517,7 @@ fn execute_proving_loop(
         "Note: First prove_step call is a no-op (Arecibo design), actual steps start from call #2"
     );
 
-    let sorted_challenges_refs: Vec<&Challenge> = plan.sorted_challenges.iter().collect();
-
-    for challenge_num in 0..num_challenges {
+    for challenge_num in start_step..num_challenges {
         let _step_span =
             debug_span!("prove_step", challenge_num, is_noop = (challenge_num == 0)).entered();
 
@@ -296,40 +531,26 @@ fn execute_proving_loop(
             challenge_num + 1,
             num_challenges
         );
-        debug!("  - Current state before step: {:?}", current_state);
+        debug!(state = ?current_state, "prove() - state before step");
 
         // Generate witnesses and circuit for this iteration
         let (circuit_step, new_state) = if challenge_num == 0 {
             // First prove_step call is a no-op - it doesn't synthesize
             // We can pass any valid circuit structure (it won't be used)
             debug!("Creating dummy circuit for no-op prove_step");
-            let dummy_circuit = C::new(
-                plan.files_per_step,
-                plan.file_tree_depth,
-                plan.aggregated_tree_depth,
-                None, // No witnesses needed for no-op
-            );
+            let dummy_circuit = step_circuit(plan, None, 0)?; // No witnesses needed for no-op
             (dummy_circuit, current_state) // Don't update state for no-op
         } else {
             // For real steps (challenge_num >= 1), generate witnesses and update state
             debug!("Generating witnesses for step {}", challenge_num);
-            let (step_circuit_witness, new_state) = generate_circuit_witness(
-                &sorted_challenges_refs,
-                Some(files), // Pass actual files
-                ledger,
-                plan.file_tree_depth,
-                plan.file_tree_depth, // For exact shape, both are the same
-                current_state,
-                plan.aggregated_tree_depth,
-                challenge_num,        // Step number matches challenge_num
-                &plan.ledger_indices, // Pass precomputed indices from plan
-            )?;
-            let circuit = C::new(
-                plan.files_per_step,
-                plan.file_tree_depth,
-                plan.aggregated_tree_depth,
-                Some(step_circuit_witness.witnesses().to_vec()),
-            );
+            let witness_start = Instant::now();
+            let (step_circuit_witness, new_state) =
+                generate_pass_witness(plan, files, ledger, current_state, challenge_num)?;
+            let witness_duration = witness_start.elapsed();
+            timings.witness_gen_duration += witness_duration;
+            record_timing(Phase::WitnessGen, Some(challenge_num), witness_duration);
+            fail_point!(crate::failpoints::points::PROVE_AFTER_WITNESS_GEN);
+            let circuit = step_circuit(plan, Some(&step_circuit_witness), challenge_num)?;
             (circuit, new_state)
         };
 
@@ -355,15 +576,21 @@ fn execute_proving_loop(
             params.file_tree_depth,
             params.aggregated_tree_depth
         );
+        // The first call is a no-op; later steps are synthesized on the previous outputs
+        if precheck && challenge_num > 0 {
+            precheck_step(&circuit_step, recursive_snark.outputs(), challenge_num)?;
+        }
+        let fold_start = Instant::now();
         let prove_result = recursive_snark.prove_step(&params.pp, &circuit_step);
+        let fold_duration = fold_start.elapsed();
+        timings.folding_duration += fold_duration;
+        if challenge_num > 0 {
+            record_timing(Phase::Fold, Some(challenge_num), fold_duration);
+        }
         trace!(
-            "prove_step returned: {:?}{}",
-            prove_result.is_ok(),
-            if challenge_num == 0 {
-                " (expected fast no-op)"
-            } else {
-                ""
-            }
+            ok = prove_result.is_ok(),
+            is_noop = (challenge_num == 0),
+            "prove_step returned"
         );
         prove_result.map_err(|e| {
             KontorPoRError::Snark(format!("Prove step {} failed: {e:?}", challenge_num))
@@ -375,6 +602,16 @@ fn execute_proving_loop(
                 cb();
             }
         }
+
+        if let Some(writer) = checkpoint {
+            writer.after_step(
+                challenge_num + 1,
+                num_challenges,
+                recursive_snark,
+                current_state,
+            )?;
+        }
+        fail_point!(crate::failpoints::points::PROVE_AFTER_STEP);
     }
 
     debug!("prove() - Proof generation complete:");
@@ -388,7 +625,7 @@ fn execute_proving_loop(
         num_challenges,
         num_challenges.saturating_sub(1)
     );
-    debug!("  - Final state: {:?}", current_state);
+    debug!(state = ?current_state, "prove() - final state");
 
     Ok(current_state)
 }
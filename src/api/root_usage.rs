@@ -0,0 +1,100 @@
+//! Per-root verification counters.
+//!
+//! Ledger-bound proofs name the ledger root they were generated against, and stay valid
+//! only while the ledger retains that root. A [`RootUsageTracker`] attached to a
+//! [`super::PorSystem`] counts, for every root that verification requests arrive with, how
+//! many verifications succeeded and failed and when the root was last seen. Operators
+//! read the counters to learn which historical roots provers still use, and
+//! [`crate::FileLedger::prune_unreferenced_roots`] drops the ones nobody referenced
+//! recently.
+
+use super::types::FieldElement;
+use ff::PrimeField;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Verification counters for one ledger root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootUsage {
+    /// The ledger root that proofs were verified against
+    pub root: FieldElement,
+    /// Verifications that returned `Ok(true)`
+    pub successes: u64,
+    /// Verifications that returned `Ok(false)` or an error
+    pub failures: u64,
+    /// When a verification against this root last finished
+    pub last_seen: SystemTime,
+}
+
+impl RootUsage {
+    /// Total number of verifications against this root.
+    pub fn references(&self) -> u64 {
+        self.successes + self.failures
+    }
+
+    /// Whether this root was seen at most `max_age` before `now`. Timestamps after `now`
+    /// (from clock adjustments) count as recent.
+    pub(crate) fn seen_within(&self, max_age: std::time::Duration, now: SystemTime) -> bool {
+        now.duration_since(self.last_seen)
+            .map_or(true, |age| age < max_age)
+    }
+}
+
+/// Counters of verifications per ledger root, shared by reference between
+/// [`super::PorSystem`] instances (see [`super::PorSystem::with_root_usage`]).
+///
+/// Only ledger-bound proofs are counted, under their `ledger_root`, and only while the
+/// ledger accepts that root; proofs naming an unknown root fail with
+/// [`crate::KontorPoRError::InvalidLedgerRoot`] and are not recorded. File-root-bound
+/// proofs do not depend on any ledger root and are not counted either.
+#[derive(Default)]
+pub struct RootUsageTracker {
+    roots: Mutex<HashMap<[u8; 32], RootUsage>>,
+}
+
+impl RootUsageTracker {
+    /// Creates a tracker with no recorded verifications.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counters for every root seen since creation or the last [`Self::reset`], ordered by
+    /// the roots' canonical byte encoding.
+    pub fn stats(&self) -> Vec<RootUsage> {
+        let roots = self.lock();
+        let mut keys: Vec<_> = roots.keys().copied().collect();
+        keys.sort_unstable();
+        keys.iter().map(|key| roots[key].clone()).collect()
+    }
+
+    /// Forgets every counter.
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<[u8; 32], RootUsage>> {
+        self.roots
+            .lock()
+            .expect("Root usage lock should not be poisoned")
+    }
+
+    /// Counts one verification against `root` that ended with `outcome`.
+    pub(crate) fn record(&self, root: FieldElement, outcome: &crate::Result<bool>) {
+        let now = SystemTime::now();
+        let mut roots = self.lock();
+        let usage = roots
+            .entry(root.to_repr().into())
+            .or_insert_with(|| RootUsage {
+                root,
+                successes: 0,
+                failures: 0,
+                last_seen: now,
+            });
+        match outcome {
+            Ok(true) => usage.successes += 1,
+            _ => usage.failures += 1,
+        }
+        usage.last_seen = now;
+    }
+}
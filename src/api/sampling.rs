@@ -0,0 +1,244 @@
+//! How many challenges detect a given data loss with a given confidence.
+//!
+//! Each step of a challenge opens one leaf chosen uniformly at random. If a fraction `ρ`
+//! of the leaves can no longer be opened, `n` steps miss every one of them with
+//! probability `(1 - ρ)^n`, so the loss is detected with probability `1 - (1 - ρ)^n`.
+//!
+//! Erasure coding changes which losses matter. A Reed-Solomon codeword of
+//! [`TOTAL_SYMBOLS_PER_CODEWORD`](crate::config::TOTAL_SYMBOLS_PER_CODEWORD) symbols still
+//! decodes with up to [`PARITY_SYMBOLS_PER_CODEWORD`](crate::config::PARITY_SYMBOLS_PER_CODEWORD)
+//! of them missing, so data is only lost once more than
+//! the parity budget of a codeword is gone. The cheapest way for a prover to lose a
+//! fraction `loss` of a file's codewords is to drop exactly one symbol over the budget
+//! from each, leaving `ρ = loss × 25 / 255` of the symbols missing. The functions here
+//! size challenges for that worst case, so detecting 1% data loss with 99.9% confidence
+//! takes about ten times as many challenges as it would for a file stored raw.
+//!
+//! Over GF(2^16) ([`ErasureCode::ReedSolomonGf16`]) a shard is a pair of symbols and is
+//! lost with either of them, so one symbol from each of 97 shards, `ρ = loss × 97 /
+//! 2040`, loses a codeword: such files need about twice as many challenges.
+//!
+//! # Example
+//!
+//! ```rust
+//! use kontor_crypto::api::sampling;
+//!
+//! let n = sampling::required_challenges(0.01, 0.999);
+//! assert_eq!(n, 7043);
+//! assert!(sampling::detection_probability(n, 0.01) >= 0.999);
+//! ```
+
+use super::types::{ErasureCode, FileMetadata};
+
+/// Loss fraction sized for by [`default_num_challenges`]: 1% of a file's data.
+pub const DEFAULT_LOSS_FRACTION: f64 = 0.01;
+
+/// Detection confidence targeted by [`default_num_challenges`].
+pub const DEFAULT_CONFIDENCE: f64 = 0.999;
+
+/// The smallest fraction of a file's symbols a prover must drop to lose `loss_fraction`
+/// of its data under `erasure` (`None` for files prepared without erasure coding).
+///
+/// # Panics
+///
+/// Panics if `loss_fraction` is not in `[0, 1]`.
+pub fn detectable_fraction(loss_fraction: f64, erasure: Option<ErasureCode>) -> f64 {
+    assert_unit("loss_fraction", loss_fraction);
+    match erasure {
+        Some(code) => {
+            let parity_shards = code.parity_symbols_per_codeword() / code.symbols_per_shard();
+            loss_fraction * (parity_shards + 1) as f64 / code.total_symbols_per_codeword() as f64
+        }
+        None => loss_fraction,
+    }
+}
+
+/// Probability that `num_challenges` steps detect the loss of `loss_fraction` of a
+/// Reed-Solomon coded file's data.
+///
+/// # Panics
+///
+/// Panics if `loss_fraction` is not in `[0, 1]`.
+pub fn detection_probability(num_challenges: usize, loss_fraction: f64) -> f64 {
+    detection_probability_with(Some(ErasureCode::default()), num_challenges, loss_fraction)
+}
+
+/// Number of steps that detect the loss of `loss_fraction` of a Reed-Solomon coded
+/// file's data with probability at least `confidence`.
+///
+/// Returns `usize::MAX` when no number of steps reaches `confidence` (no loss, or
+/// certainty asked of a partial loss).
+///
+/// # Panics
+///
+/// Panics if `loss_fraction` or `confidence` is not in `[0, 1]`.
+pub fn required_challenges(loss_fraction: f64, confidence: f64) -> usize {
+    required_challenges_with(Some(ErasureCode::default()), loss_fraction, confidence)
+}
+
+/// [`detection_probability`] for files coded with `erasure`.
+pub fn detection_probability_with(
+    erasure: Option<ErasureCode>,
+    num_challenges: usize,
+    loss_fraction: f64,
+) -> f64 {
+    probability_for_fraction(detectable_fraction(loss_fraction, erasure), num_challenges)
+}
+
+/// [`required_challenges`] for files coded with `erasure`.
+pub fn required_challenges_with(
+    erasure: Option<ErasureCode>,
+    loss_fraction: f64,
+    confidence: f64,
+) -> usize {
+    assert_unit("confidence", confidence);
+    challenges_for_fraction(detectable_fraction(loss_fraction, erasure), confidence)
+}
+
+/// [`required_challenges`] for a specific file.
+///
+/// Uses the file's erasure code, and counts only challenges that land on its symbols:
+/// the leaves padding the tree to `padded_len` hold no data, so a file whose symbols
+/// fill half its tree needs about twice as many steps.
+pub fn required_challenges_for_file(
+    metadata: &FileMetadata,
+    loss_fraction: f64,
+    confidence: f64,
+) -> usize {
+    assert_unit("confidence", confidence);
+    let occupancy = if metadata.padded_len == 0 {
+        0.0
+    } else {
+        metadata.total_symbols().min(metadata.padded_len) as f64 / metadata.padded_len as f64
+    };
+    challenges_for_fraction(
+        detectable_fraction(loss_fraction, metadata.erasure) * occupancy,
+        confidence,
+    )
+}
+
+/// Steps detecting the loss of [`DEFAULT_LOSS_FRACTION`] of a Reed-Solomon coded file
+/// with [`DEFAULT_CONFIDENCE`].
+pub fn default_num_challenges() -> usize {
+    required_challenges(DEFAULT_LOSS_FRACTION, DEFAULT_CONFIDENCE)
+}
+
+/// 1 - (1 - fraction)^n, without losing precision for small fractions.
+fn probability_for_fraction(fraction: f64, num_challenges: usize) -> f64 {
+    -(num_challenges as f64 * (-fraction).ln_1p()).exp_m1()
+}
+
+/// Smallest n with 1 - (1 - fraction)^n >= confidence.
+fn challenges_for_fraction(fraction: f64, confidence: f64) -> usize {
+    if confidence <= 0.0 {
+        return 0;
+    }
+    if fraction >= 1.0 {
+        return 1;
+    }
+    if fraction <= 0.0 || confidence >= 1.0 {
+        return usize::MAX;
+    }
+
+    let estimate = ((-confidence).ln_1p() / (-fraction).ln_1p()).ceil();
+    if estimate >= (1u64 << 53) as f64 {
+        return usize::MAX;
+    }
+    // The closed form can be off by one through rounding; settle on the exact boundary
+    let mut n = (estimate as usize).max(1);
+    while probability_for_fraction(fraction, n) < confidence {
+        n += 1;
+    }
+    while n > 1 && probability_for_fraction(fraction, n - 1) >= confidence {
+        n -= 1;
+    }
+    n
+}
+
+fn assert_unit(name: &str, value: f64) {
+    assert!(
+        (0.0..=1.0).contains(&value),
+        "{} must be in [0, 1], got {}",
+        name,
+        value
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_values() {
+        // Raw files follow the textbook (1 - loss)^n analysis
+        assert_eq!(required_challenges_with(None, 0.01, 0.99), 459);
+        assert_eq!(required_challenges_with(None, 0.01, 0.999), 688);
+        assert_eq!(required_challenges_with(None, 0.05, 0.99), 90);
+
+        // Reed-Solomon files need 255 / 25 times the detectable loss
+        assert_eq!(required_challenges(0.01, 0.999), 7043);
+        assert_eq!(required_challenges(0.01, 0.99), 4695);
+        assert_eq!(required_challenges(0.1, 0.95), 305);
+        assert_eq!(required_challenges(1.0, 0.999), 67);
+        assert_eq!(default_num_challenges(), 7043);
+
+        // GF(2^16) shards pair symbols, so losing one symbol of each of 97 shards suffices
+        assert_eq!(
+            required_challenges_with(Some(ErasureCode::ReedSolomonGf16), 0.01, 0.999),
+            14_525
+        );
+
+        let p = detection_probability_with(None, 100, 0.01);
+        assert!((p - 0.633_967_658_726_770_9).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_required_challenges_is_minimal() {
+        for &(loss, confidence) in &[(0.01, 0.999), (0.2, 0.5), (0.5, 0.9999), (0.003, 0.9)] {
+            for erasure in [
+                None,
+                Some(ErasureCode::ReedSolomon),
+                Some(ErasureCode::ReedSolomonGf16),
+            ] {
+                let n = required_challenges_with(erasure, loss, confidence);
+                assert!(detection_probability_with(erasure, n, loss) >= confidence);
+                assert!(detection_probability_with(erasure, n - 1, loss) < confidence);
+            }
+        }
+    }
+
+    #[test]
+    fn test_monotonicity() {
+        let losses = [0.001, 0.01, 0.05, 0.2, 0.7];
+        let confidences = [0.5, 0.9, 0.99, 0.999, 0.999_999];
+
+        for window in losses.windows(2) {
+            assert!(required_challenges(window[0], 0.99) >= required_challenges(window[1], 0.99));
+            assert!(detection_probability(100, window[0]) < detection_probability(100, window[1]));
+        }
+        for window in confidences.windows(2) {
+            assert!(required_challenges(0.01, window[0]) <= required_challenges(0.01, window[1]));
+        }
+        for n in [1, 10, 100, 1000] {
+            assert!(detection_probability(n, 0.01) < detection_probability(n + 1, 0.01));
+            // Erasure coding only ever makes detection harder
+            assert!(detection_probability(n, 0.01) < detection_probability_with(None, n, 0.01));
+        }
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        assert_eq!(required_challenges(0.01, 0.0), 0);
+        assert_eq!(required_challenges(0.0, 0.5), usize::MAX);
+        assert_eq!(required_challenges(0.5, 1.0), usize::MAX);
+        assert_eq!(required_challenges_with(None, 1.0, 1.0), 1);
+        assert_eq!(detection_probability(0, 0.5), 0.0);
+        assert_eq!(detection_probability_with(None, 1, 1.0), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "loss_fraction must be in [0, 1]")]
+    fn test_rejects_out_of_range_loss() {
+        required_challenges(1.5, 0.9);
+    }
+}
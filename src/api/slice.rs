@@ -0,0 +1,258 @@
+//! Per-challenge slices of a multi-file proof.
+//!
+//! A SNARK over several files cannot be split, but a client can hand a third party one
+//! challenge together with the slot-specific public data of the proof that answers it.
+//! [`verify_slice`] checks that data against the proof's recorded public inputs and
+//! outputs and then verifies the whole SNARK once, without the other files' challenges.
+
+use super::types::{Challenge, ChallengeID, ChallengeKind, FieldElement, Proof, SeedSchedule};
+use crate::instrumentation::{debug, info_span};
+use crate::{config, params::Shape, KontorPoRError, Result};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The public data of one challenge's slot in a proof.
+///
+/// Produced by [`Proof::public_slice`] and checked by [`verify_slice`]. The slice binds
+/// its proof by digest, so it must travel with the serialized proof. It does not show that
+/// `ledger_index` holds the challenged file; a third party without the ledger should
+/// check that with a membership proof ([`crate::FileLedger::get_aggregation_proof`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofSlice {
+    /// SHA-256 of the proof's serialized bytes
+    pub proof_digest: [u8; 32],
+    /// The challenge this slice answers
+    pub challenge_id: ChallengeID,
+    /// Circuit slot of the challenge
+    pub slot: usize,
+    /// Position of the challenged file in the ledger at `ledger_root`
+    pub ledger_index: usize,
+    /// Tree depth of the challenged file
+    pub depth: usize,
+    /// Public seed of the slot
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub seed: FieldElement,
+    /// The slot's leaf output after the last step: the challenged leaf, or its commitment
+    /// for private challenges
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub leaf_output: FieldElement,
+    /// Ledger root the proof was generated against (the file root for single-file proofs)
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub ledger_root: FieldElement,
+}
+
+impl Proof {
+    /// Extracts the public data of `challenge`'s slot from this proof.
+    ///
+    /// # Errors
+    ///
+    /// [`KontorPoRError::InvalidInput`] if the proof does not cover `challenge` or its
+    /// recorded public inputs and outputs do not fit its slot count, as for batches
+    /// partitioned into several passes.
+    pub fn public_slice(&self, challenge: &Challenge) -> Result<ProofSlice> {
+        let id = challenge.id();
+        let slot = self
+            .challenge_ids
+            .iter()
+            .position(|covered| *covered == id)
+            .and_then(|position| self.challenge_slots.get(position).copied())
+            .ok_or_else(|| {
+                KontorPoRError::InvalidInput(format!("Proof does not cover challenge {}", id))
+            })?;
+        let layout = checked_layout(self, slot)?;
+
+        Ok(ProofSlice {
+            proof_digest: proof_digest(self)?,
+            challenge_id: id,
+            slot,
+            ledger_index: self.ledger_indices[slot],
+            depth: challenge.file_metadata.depth(),
+            seed: self.public_inputs[layout.idx_seed(slot)],
+            leaf_output: self.public_outputs[layout.idx_leaf(slot)],
+            ledger_root: self.ledger_root,
+        })
+    }
+}
+
+/// Verifies `slice` as `challenge`'s part of `proof`.
+///
+/// The slice must match the challenge, its slot in the proof and the proof's public inputs
+/// and outputs. For ledger-bound proofs the ledger root must be one of `valid_roots`
+/// (otherwise [`KontorPoRError::InvalidLedgerRoot`]); file-root-bound proofs must be rooted
+/// at the challenged file. The SNARK is then verified once against the recorded public
+/// inputs, which must produce the recorded outputs.
+///
+/// # Returns
+///
+/// Returns Ok(true) if the slice and proof are valid, Ok(false) if anything does not
+/// match, or an error for malformed proofs or unexpected failures.
+pub fn verify_slice(
+    proof: &Proof,
+    slice: &ProofSlice,
+    challenge: &Challenge,
+    valid_roots: &[FieldElement],
+) -> Result<bool> {
+    let _span = info_span!("verify_slice", slot = slice.slot).entered();
+    proof.check_schema_version()?;
+
+    let id = challenge.id();
+    if slice.challenge_id != id || slice.proof_digest != proof_digest(proof)? {
+        debug!("Slice belongs to another challenge or proof");
+        return Ok(false);
+    }
+    let covered_at = proof
+        .challenge_ids
+        .iter()
+        .position(|covered| *covered == id);
+    if covered_at.and_then(|position| proof.challenge_slots.get(position)) != Some(&slice.slot) {
+        debug!("Slice slot {} is not the challenge's slot", slice.slot);
+        return Ok(false);
+    }
+    let layout = checked_layout(proof, slice.slot)?;
+
+    // The slice must describe the challenge...
+    let depth = challenge.file_metadata.depth();
+    if slice.depth != depth
+        || slice.seed != challenge.seed
+        || slice.ledger_root != proof.ledger_root
+    {
+        debug!("Slice does not match the challenge");
+        return Ok(false);
+    }
+
+    // ...and the proof's public inputs and outputs at its slot
+    let inputs = &proof.public_inputs;
+    let field = |value: usize| FieldElement::from(value as u64);
+    if slice.ledger_index != proof.ledger_indices[slice.slot]
+        || inputs[layout.idx_agg_root()] != slice.ledger_root
+        || inputs[layout.idx_state_in()] != proof.challenge_nonce_commitment
+        || inputs[layout.idx_ledger(slice.slot)] != field(slice.ledger_index)
+        || inputs[layout.idx_depth(slice.slot)] != field(depth)
+        || inputs[layout.idx_seed(slice.slot)] != slice.seed
+        || proof.public_outputs[layout.idx_leaf(slice.slot)] != slice.leaf_output
+    {
+        debug!("Slice does not match the proof's public inputs and outputs");
+        return Ok(false);
+    }
+
+    // Per-step and explicit-index challenges fold their schedule into the seed output
+    let expected_seed_output = match (&challenge.kind, &challenge.seed_schedule) {
+        (ChallengeKind::ExplicitIndices(indices), _) => Some(
+            crate::poseidon::accumulate_explicit_indices(challenge.seed, indices),
+        ),
+        (_, SeedSchedule::PerStep(seeds)) => Some(crate::poseidon::accumulate_seed_schedule(
+            challenge.seed,
+            seeds,
+        )),
+        _ => None,
+    };
+    if let Some(expected) = expected_seed_output {
+        if proof.public_outputs[layout.idx_seed(slice.slot)] != expected {
+            debug!("Slot seed output does not match the challenge's schedule");
+            return Ok(false);
+        }
+    }
+
+    if proof.binding().is_ledger() {
+        if !valid_roots.contains(&proof.ledger_root) {
+            return Err(KontorPoRError::InvalidLedgerRoot {
+                proof_root: format!("{:?}", proof.ledger_root),
+                reason: "Slice's ledger_root is not among the supplied valid roots".to_string(),
+            });
+        }
+    } else if proof.ledger_root != challenge.file_metadata.root {
+        debug!("Single-file proof is not rooted at the challenged file");
+        return Ok(false);
+    }
+
+    if proof.hash_profile != challenge.file_metadata.hash_profile {
+        return Err(KontorPoRError::HashProfileMismatch {
+            proof: proof.hash_profile,
+            metadata: challenge.file_metadata.hash_profile,
+        });
+    }
+
+    let num_iterations = challenge.num_challenges;
+    if num_iterations == 0 || num_iterations > config::MAX_NUM_CHALLENGES {
+        return Err(KontorPoRError::InvalidChallengeCount {
+            count: num_iterations,
+        });
+    }
+
+    // The file tree depth of the shape is the deepest slot's public depth
+    let max_depth = layout
+        .depths_range()
+        .map(|i| small_value(&inputs[i]))
+        .try_fold(0usize, |max, depth| depth.map(|d| max.max(d)))
+        .ok_or_else(|| {
+            KontorPoRError::InvalidInput("Proof public inputs hold an invalid depth".to_string())
+        })?;
+    let expected = config::Shape::derive(
+        layout.files_per_step,
+        max_depth,
+        proof.shape.aggregated_tree_depth,
+    );
+    if proof.shape != expected {
+        return Err(KontorPoRError::ShapeMismatch {
+            proof: proof.shape,
+            expected,
+        });
+    }
+    let shape = Shape::from(proof.shape)
+        .with_mode(challenge.mode)
+        .with_per_step_seeds(challenge.seed_schedule.is_per_step())
+        .with_explicit_indices(challenge.kind.is_explicit())
+        .with_tree_arity(challenge.file_metadata.tree_arity)
+        .with_hash_profile(challenge.file_metadata.hash_profile)
+        .with_circuit_mode(challenge.circuit_mode);
+    let params = crate::params::load_or_generate_params_for_shape(&shape)?;
+
+    match proof
+        .compressed_snark
+        .verify(&params.keys.vk, num_iterations, inputs)
+    {
+        Ok(zn) => Ok(zn == proof.public_outputs),
+        Err(nova_snark::errors::NovaError::ProofVerifyError { reason: _ }) => Ok(false),
+        Err(e) => Err(KontorPoRError::Snark(format!(
+            "An unexpected error occurred during verification: {e:?}"
+        ))),
+    }
+}
+
+/// SHA-256 of the proof's serialized bytes.
+pub(super) fn proof_digest(proof: &Proof) -> Result<[u8; 32]> {
+    Ok(Sha256::digest(proof.to_bytes()?).into())
+}
+
+/// The proof's public I/O layout, after checking that its recorded inputs and outputs fit
+/// it and that `slot` is one of its slots.
+fn checked_layout(proof: &Proof, slot: usize) -> Result<config::PublicIOLayout> {
+    let layout = config::PublicIOLayout::new(proof.ledger_indices.len());
+    // Only the first pass's slots enter the public inputs of a partitioned batch
+    if proof.public_io_layout().files_per_step < layout.files_per_step {
+        return Err(KontorPoRError::InvalidInput(
+            "Proofs partitioned into several passes cannot be sliced".to_string(),
+        ));
+    }
+    if proof.public_inputs.len() != layout.arity()
+        || proof.public_outputs.len() != layout.arity()
+        || slot >= layout.files_per_step
+    {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "Proof public inputs and outputs do not fit slot {} of {}",
+            slot, layout.files_per_step
+        )));
+    }
+    Ok(layout)
+}
+
+/// The value of a field element below 2^64, if it is one.
+fn small_value(element: &FieldElement) -> Option<usize> {
+    let repr = element.to_repr();
+    let (low, high) = repr.as_ref().split_at(8);
+    if high.iter().any(|&byte| byte != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_le_bytes(low.try_into().ok()?)).ok()
+}
@@ -0,0 +1,177 @@
+//! Native (non-SNARK) spot checks against stored symbols.
+//!
+//! A verifier holding the actual symbols of a file (e.g. a hot replica) can replay the
+//! challenge sequence a proof would answer and check each challenged symbol against the
+//! file's Merkle root directly. This costs milliseconds rather than a SNARK verification,
+//! making it a cheap plausibility check before the full proof is verified.
+
+use super::types::{Challenge, PreparedFile};
+use crate::instrumentation::debug_span;
+use crate::{
+    merkle::{
+        get_leaf_hash, get_padded_proof_for_leaf_with_profile,
+        verify_merkle_proof_in_place_with_profile, CircuitMerkleProof, TreeArity,
+    },
+    utils::{advance_challenge_state, derive_leaf_index_for_file, field_to_bytes31_le},
+    KontorPoRError, Result,
+};
+
+/// Source of stored symbols and their Merkle authentication paths.
+///
+/// `symbol` returns the bytes actually held for a leaf; `merkle_proof` returns the
+/// authentication path recorded for that leaf. Keeping the two separate means a
+/// damaged symbol fails only its own check rather than every path through its subtree.
+pub trait SymbolStore {
+    /// The 31-byte symbol stored at `index`, or `None` if it is unavailable.
+    fn symbol(&self, index: usize) -> Option<Vec<u8>>;
+
+    /// The authentication path for the leaf at `index`. `leaf` is the committed leaf value
+    /// and `siblings` run from the leaf level up to the root.
+    fn merkle_proof(&self, index: usize) -> Result<CircuitMerkleProof>;
+}
+
+impl SymbolStore for PreparedFile {
+    fn symbol(&self, index: usize) -> Option<Vec<u8>> {
+        self.tree
+            .layers
+            .first()
+            .and_then(|leaves| leaves.get(index))
+            .map(|leaf| field_to_bytes31_le(leaf).to_vec())
+    }
+
+    fn merkle_proof(&self, index: usize) -> Result<CircuitMerkleProof> {
+        if self.tree_arity != TreeArity::Binary {
+            return Err(KontorPoRError::InvalidInput(
+                "Binary authentication paths are not available for arity-4 trees".to_string(),
+            ));
+        }
+        get_padded_proof_for_leaf_with_profile(
+            &self.tree,
+            index,
+            self.tree.layers.len() - 1,
+            self.hash_profile,
+        )
+    }
+}
+
+/// Outcome of checking a single challenged leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpotCheckIndex {
+    /// Challenge step (0-based) at which this leaf is challenged
+    pub step: usize,
+    /// Leaf index derived for this step
+    pub leaf_index: usize,
+    /// Whether the stored symbol authenticated against the file root
+    pub passed: bool,
+}
+
+/// Result of [`spot_check`], one entry per challenge step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpotCheckResult {
+    /// Per-step results, in challenge order
+    pub indices: Vec<SpotCheckIndex>,
+}
+
+impl SpotCheckResult {
+    /// Returns true if every challenged symbol authenticated.
+    pub fn all_passed(&self) -> bool {
+        self.indices.iter().all(|i| i.passed)
+    }
+
+    /// Leaf indices whose stored symbols failed authentication.
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.indices
+            .iter()
+            .filter(|i| !i.passed)
+            .map(|i| i.leaf_index)
+            .collect()
+    }
+}
+
+/// Natively check the symbols a proof of `challenge` would open.
+///
+/// Replays the challenge sequence of a single-file proof for `challenge` (the same
+/// index derivation and state chain used by witness generation), fetches each
+/// challenged symbol from `store`, and verifies it against
+/// `challenge.file_metadata.root` using the store's authentication path.
+///
+/// The state chain advances with the committed leaf from the authentication path, so a
+/// damaged symbol fails only its own index and does not perturb later indices.
+///
+/// # Errors
+///
+/// Returns an error if the store cannot produce an authentication path, or if the path
+/// it produces does not match the depth implied by the challenge metadata. Spot checks
+/// only support binary file trees; arity-4 challenges are rejected.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::{prepare_file, spot_check, Challenge, FieldElement};
+///
+/// let (prepared, metadata) = prepare_file(b"hot replica data", "replica.dat")?;
+/// let challenge = Challenge::new(metadata, 1000, 5, FieldElement::from(7u64), "node_1".into());
+///
+/// let result = spot_check(&prepared, &challenge)?;
+/// assert!(result.all_passed());
+/// # Ok::<(), kontor_crypto::KontorPoRError>(())
+/// ```
+pub fn spot_check(store: &impl SymbolStore, challenge: &Challenge) -> Result<SpotCheckResult> {
+    let _span = debug_span!(
+        "spot_check",
+        file_id = %challenge.file_metadata.file_id,
+        num_challenges = challenge.num_challenges
+    )
+    .entered();
+
+    if challenge.file_metadata.tree_arity != TreeArity::Binary {
+        return Err(KontorPoRError::InvalidInput(
+            "Spot checks are only supported for binary file trees".to_string(),
+        ));
+    }
+
+    let file_depth = challenge.file_metadata.depth();
+    let root = challenge.file_metadata.root;
+    // Same initial state as a single-challenge proof
+    let mut state = crate::poseidon::calculate_nonce_commitment(&[challenge.nonce]);
+    let mut indices = Vec::with_capacity(challenge.num_challenges);
+
+    for step in 0..challenge.num_challenges {
+        let leaf_index = challenge.explicit_index(step).unwrap_or_else(|| {
+            derive_leaf_index_for_file(0, file_depth, challenge.seed_for_step(step), state, false)
+        });
+
+        let path = store.merkle_proof(leaf_index)?;
+        if path.siblings.len() != file_depth {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Authentication path for leaf {} has depth {}, expected {}",
+                leaf_index,
+                path.siblings.len(),
+                file_depth
+            )));
+        }
+
+        let passed = match store.symbol(leaf_index).map(|bytes| get_leaf_hash(&bytes)) {
+            Some(Ok(leaf)) => verify_merkle_proof_in_place_with_profile(
+                root,
+                &CircuitMerkleProof {
+                    leaf,
+                    ..path.clone()
+                },
+                challenge.file_metadata.hash_profile,
+            ),
+            _ => false,
+        };
+
+        indices.push(SpotCheckIndex {
+            step,
+            leaf_index,
+            passed,
+        });
+        if file_depth > 0 {
+            state = advance_challenge_state(state, path.leaf);
+        }
+    }
+
+    Ok(SpotCheckResult { indices })
+}
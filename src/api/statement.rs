@@ -0,0 +1,150 @@
+//! Compact public statements of proofs for light clients.
+//!
+//! Contracts and other light clients cannot run the Nova verifier, but they can store and
+//! compare a single field element. [`Proof::statement`] lists what a proof claims (the
+//! ledger root, each challenged file with its rc, ledger index, depth and seed, the final
+//! chain state and the step count) and [`ProofStatement::digest`] folds it into one
+//! Poseidon hash. A contract stores the digest while an off-chain verifier attests that
+//! the SNARK behind it verifies; [`super::PorSystem::verify_statement`] returns the same
+//! statement from full verification.
+
+use super::types::{Challenge, FieldElement, Proof};
+use crate::{
+    config::PublicIOLayout,
+    poseidon::{domain_tags, poseidon_hash_tagged},
+    KontorPoRError, Result,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One challenged file of a [`ProofStatement`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatementEntry {
+    /// The challenged file
+    pub file_id: String,
+    /// The file's root commitment, as stored in the ledger
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub rc: FieldElement,
+    /// Position of the file in the ledger at the statement's `ledger_root`
+    pub ledger_index: usize,
+    /// Tree depth of the file
+    pub depth: usize,
+    /// Public seed of the file's slot
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub seed: FieldElement,
+}
+
+/// The public statement of a proof, ordered by circuit slot.
+///
+/// Produced by [`Proof::statement`] and [`super::PorSystem::verify_statement`]; two
+/// statements describe the same claim exactly when their digests are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStatement {
+    /// Ledger root the proof was generated against (the file root for single-file proofs)
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub ledger_root: FieldElement,
+    /// The challenged files, in slot order
+    pub entries: Vec<StatementEntry>,
+    /// The chain state after the last step
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub final_state: FieldElement,
+    /// Number of recursive steps the proof covers
+    pub num_steps: usize,
+}
+
+impl ProofStatement {
+    /// Poseidon digest of the statement.
+    ///
+    /// acc_0 = H(TAG_STATEMENT, ledger_root, final_state), then the step count, the entry
+    /// count and each entry's file, rc, ledger index, depth and seed are folded in as
+    /// acc = H(TAG_STATEMENT, acc, value). A file ID enters as the first 31 bytes of
+    /// SHA-256(file_id) read as a little-endian field element.
+    pub fn digest(&self) -> FieldElement {
+        let tag = domain_tags::proof_statement();
+        let mut values = vec![
+            FieldElement::from(self.num_steps as u64),
+            FieldElement::from(self.entries.len() as u64),
+        ];
+        for entry in &self.entries {
+            let file_id = Sha256::digest(entry.file_id.as_bytes());
+            values.extend([
+                crate::utils::bytes31_to_field_le(&file_id[..31]),
+                entry.rc,
+                FieldElement::from(entry.ledger_index as u64),
+                FieldElement::from(entry.depth as u64),
+                entry.seed,
+            ]);
+        }
+        values.into_iter().fold(
+            poseidon_hash_tagged(tag, self.ledger_root, self.final_state),
+            |acc, value| poseidon_hash_tagged(tag, acc, value),
+        )
+    }
+}
+
+impl Proof {
+    /// Returns the public statement this proof makes about `challenges`.
+    ///
+    /// The proof records challenge IDs rather than files, so the challenges it answers
+    /// supply each file's ID, rc and depth; ledger indices, seeds and the final state come
+    /// from the proof. This does not verify the SNARK.
+    ///
+    /// # Errors
+    ///
+    /// [`KontorPoRError::InvalidInput`] if `challenges` are not exactly the proof's
+    /// challenges in order, the proof's public inputs and outputs do not fit its slots, or
+    /// its batch was partitioned into several passes.
+    pub fn statement(&self, challenges: &[Challenge]) -> Result<ProofStatement> {
+        let ids: Vec<_> = challenges.iter().map(Challenge::id).collect();
+        if challenges.is_empty() || ids != self.challenge_ids {
+            return Err(KontorPoRError::InvalidInput(
+                "Statement needs exactly the proof's challenges, in order".to_string(),
+            ));
+        }
+        let layout = PublicIOLayout::new(self.ledger_indices.len());
+        // Later passes' seeds are not public inputs of a partitioned batch
+        if self.public_io_layout().files_per_step < layout.files_per_step {
+            return Err(KontorPoRError::InvalidInput(
+                "Statements of proofs partitioned into several passes are not supported"
+                    .to_string(),
+            ));
+        }
+        if self.public_inputs.len() != layout.arity()
+            || self.public_outputs.len() != layout.arity()
+            || self.challenge_slots.len() != challenges.len()
+            || self
+                .challenge_slots
+                .iter()
+                .any(|&slot| slot >= layout.files_per_step)
+        {
+            return Err(KontorPoRError::InvalidInput(
+                "Proof public inputs and outputs do not fit its challenge slots".to_string(),
+            ));
+        }
+
+        let mut slotted: Vec<(usize, &Challenge)> = self
+            .challenge_slots
+            .iter()
+            .copied()
+            .zip(challenges)
+            .collect();
+        slotted.sort_by_key(|(slot, _)| *slot);
+        let entries = slotted
+            .into_iter()
+            .map(|(slot, challenge)| StatementEntry {
+                file_id: challenge.file_metadata.file_id.clone(),
+                rc: challenge.file_metadata.commitment(),
+                ledger_index: self.ledger_indices[slot],
+                depth: challenge.file_metadata.depth(),
+                seed: self.public_inputs[layout.idx_seed(slot)],
+            })
+            .collect();
+
+        Ok(ProofStatement {
+            ledger_root: self.ledger_root,
+            entries,
+            final_state: self.public_outputs[layout.idx_state_in()],
+            num_steps: challenges[0].num_challenges,
+        })
+    }
+}
@@ -4,25 +4,142 @@
 //! proving and verification operations, managing dependencies like the FileLedger
 //! and parameter caching internally.
 
-use super::types::{Challenge, FileMetadata, PreparedFile, Proof};
+use super::aggregate::AggregatedProof;
+use super::root_usage::{RootUsage, RootUsageTracker};
+use super::statement::ProofStatement;
+#[cfg(feature = "transcript")]
+use super::transcript::Transcript;
+use super::types::{Challenge, FieldElement, Proof, VerifyOptions};
+#[cfg(feature = "prover")]
+use super::types::{FileMetadata, PreparedFile, ProveOptions};
+use super::verify::PreverifiedStatement;
+use super::verify_cache::VerificationCache;
+use crate::instrumentation::debug;
 use crate::{ledger::FileLedger, KontorPoRError, Result};
+#[cfg(feature = "prover")]
+use crate::{ledger::FileLedgerEntry, metrics::ProveTimings};
 use std::collections::BTreeMap;
-use tracing::debug;
+#[cfg(feature = "prover")]
+use std::path::Path;
 
 /// The unified API entry point for the Nova-based Proof-of-Retrievability system.
 ///
 /// PorSystem encapsulates the FileLedger and provides methods for file preparation,
 /// proof generation, and verification. It manages parameter caching and shape
 /// derivation internally.
+///
+/// A system built with [`Self::new_multi`] holds several named ledgers (e.g. one per
+/// tenant) and routes each batch to the ledger named by its challenges'
+/// [`Challenge::ledger_id`].
 pub struct PorSystem<'a> {
-    /// Reference to the file ledger containing the aggregated Merkle tree
-    ledger: &'a FileLedger,
+    /// Ledger for challenges without a `ledger_id` (None for multi-ledger systems)
+    ledger: Option<&'a FileLedger>,
+    /// Named ledgers for challenges with a `ledger_id`
+    ledgers: BTreeMap<String, &'a FileLedger>,
+    /// Reject prepared files that no challenge refers to (otherwise they are only logged)
+    reject_unused_files: bool,
+    /// Cache of verification outcomes, if attached
+    verification_cache: Option<&'a VerificationCache>,
+    /// Per-root verification counters, if attached
+    root_usage: Option<&'a RootUsageTracker>,
 }
 
 impl<'a> PorSystem<'a> {
     /// Create a new PorSystem with the given FileLedger.
     pub fn new(ledger: &'a FileLedger) -> Self {
-        Self { ledger }
+        Self {
+            ledger: Some(ledger),
+            ledgers: BTreeMap::new(),
+            reject_unused_files: false,
+            verification_cache: None,
+            root_usage: None,
+        }
+    }
+
+    /// Create a PorSystem over several named ledgers.
+    ///
+    /// Every challenge passed to [`Self::prove`] or [`Self::verify`] must name one of
+    /// these ledgers with [`Challenge::with_ledger_id`], and all challenges of one batch
+    /// must name the same ledger. The ledger-root validity check and ledger index lookups
+    /// then use that ledger; an unknown name fails with [`KontorPoRError::UnknownLedger`].
+    pub fn new_multi(ledgers: BTreeMap<String, &'a FileLedger>) -> Self {
+        Self {
+            ledger: None,
+            ledgers,
+            reject_unused_files: false,
+            verification_cache: None,
+            root_usage: None,
+        }
+    }
+
+    /// The ledger that `challenges` are addressed to.
+    ///
+    /// Challenges without a `ledger_id` use the ledger passed to [`Self::new`].
+    fn ledger_for(&self, challenges: &[Challenge]) -> Result<&'a FileLedger> {
+        let ledger_id = challenges.first().and_then(|c| c.ledger_id.as_ref());
+        if challenges.iter().any(|c| c.ledger_id.as_ref() != ledger_id) {
+            return Err(KontorPoRError::ChallengeMismatch {
+                field: "ledger_id".to_string(),
+            });
+        }
+
+        match ledger_id {
+            Some(id) => self
+                .ledgers
+                .get(id)
+                .copied()
+                .ok_or_else(|| KontorPoRError::UnknownLedger { id: id.clone() }),
+            None => self.ledger.ok_or_else(|| {
+                KontorPoRError::InvalidInput(
+                    "Challenges must name a ledger_id for a multi-ledger PorSystem".to_string(),
+                )
+            }),
+        }
+    }
+
+    /// Make [`Self::prove`] fail with [`KontorPoRError::UnusedPreparedFile`] when given a
+    /// prepared file that no challenge refers to. By default such files are ignored with a
+    /// warning.
+    pub fn with_reject_unused_files(mut self, reject: bool) -> Self {
+        self.reject_unused_files = reject;
+        self
+    }
+
+    /// Answer repeated [`Self::verify`] calls for the same proof and challenges from `cache`.
+    ///
+    /// The cache may be shared by several systems. Each outcome is stored with the ledger
+    /// roots it was validated against (the ledger's root at the time, plus the proof's
+    /// `ledger_root` for multi-file proofs) and is only reused while the ledger still
+    /// accepts all of them.
+    pub fn with_verification_cache(mut self, cache: &'a VerificationCache) -> Self {
+        self.verification_cache = Some(cache);
+        self
+    }
+
+    /// Count [`Self::verify`] outcomes per ledger root in `tracker`.
+    ///
+    /// The tracker may be shared by several systems. Ledger-bound proofs are counted under
+    /// their `ledger_root` once the ledger accepts it, whether verification then succeeds
+    /// or fails; read the counters with [`Self::root_usage_stats`].
+    pub fn with_root_usage(mut self, tracker: &'a RootUsageTracker) -> Self {
+        self.root_usage = Some(tracker);
+        self
+    }
+
+    /// Verification counters per ledger root from the attached [`RootUsageTracker`], or an
+    /// empty list if none is attached. Pass them to
+    /// [`FileLedger::prune_unreferenced_roots`] to drop historical roots nobody uses.
+    pub fn root_usage_stats(&self) -> Vec<RootUsage> {
+        self.root_usage
+            .map(RootUsageTracker::stats)
+            .unwrap_or_default()
+    }
+
+    /// Clears the counters of the attached [`RootUsageTracker`], if any.
+    pub fn reset_root_usage(&self) {
+        if let Some(tracker) = self.root_usage {
+            tracker.reset();
+        }
     }
 
     /// Prepare a file for proving by applying erasure coding, chunking, and building a Merkle tree.
@@ -41,6 +158,7 @@ impl<'a> PorSystem<'a> {
     /// Returns a tuple of (PreparedFile, FileMetadata) where:
     /// - PreparedFile contains the private Merkle tree and file identifiers
     /// - FileMetadata contains the public commitment and reconstruction information
+    #[cfg(feature = "prover")]
     pub fn prepare_file(
         &self,
         data: &[u8],
@@ -50,11 +168,67 @@ impl<'a> PorSystem<'a> {
         super::prepare_file(data, filename)
     }
 
+    /// Prepare a file and register it in `ledger` in a single step.
+    ///
+    /// A `PorSystem` only borrows its ledger, so registration takes the ledger mutably
+    /// here; build the `PorSystem` afterwards to prove against the updated ledger.
+    /// The new ledger root is recorded in the historical roots exactly once.
+    ///
+    /// Registering the same data again is a no-op that returns the prepared file without
+    /// touching the ledger. If the `file_id` is already registered with a different root
+    /// commitment, returns [`KontorPoRError::FileIdConflict`] and leaves the ledger unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::api::{Challenge, FieldElement, PorSystem};
+    /// use kontor_crypto::FileLedger;
+    ///
+    /// let mut ledger = FileLedger::new();
+    /// let (prepared, metadata) =
+    ///     PorSystem::prepare_and_register(&mut ledger, b"hello", "hello.dat").unwrap();
+    ///
+    /// let system = PorSystem::new(&ledger);
+    /// let challenge = Challenge::new(metadata, 1000, 3, FieldElement::from(1u64), "node_1".into());
+    /// let proof = system.prove(vec![&prepared], &[challenge]).unwrap();
+    /// ```
+    #[cfg(feature = "prover")]
+    pub fn prepare_and_register(
+        ledger: &mut FileLedger,
+        data: &[u8],
+        filename: &str,
+    ) -> Result<(PreparedFile, FileMetadata)> {
+        let (prepared, metadata) = super::prepare_file(data, filename)?;
+
+        let entry = FileLedgerEntry::from(&metadata);
+        match ledger.entry(&metadata.file_id) {
+            Some(existing) if existing.rc == entry.rc => {
+                debug!(file_id = %metadata.file_id, "File already registered");
+            }
+            Some(_) => {
+                return Err(KontorPoRError::FileIdConflict {
+                    file_id: metadata.file_id,
+                });
+            }
+            None => ledger.add_file(&metadata)?,
+        }
+
+        Ok((prepared, metadata))
+    }
+
     /// Generate a single compact proof for any set of open Challenges.
     ///
     /// This method accepts a `Vec<PreparedFile>` and internally maps it to the
-    /// BTreeMap structure required by the underlying proving logic. Seeds
-    /// must be identical across all challenges in the batch.
+    /// BTreeMap structure required by the underlying proving logic, so the order of
+    /// `files` does not matter. Before any proving work, every challenge must be covered
+    /// by a prepared file with the same `file_id`; otherwise this returns
+    /// [`KontorPoRError::MissingPreparedFile`] listing all uncovered file IDs. Extra files
+    /// are ignored unless [`Self::with_reject_unused_files`] is set.
+    ///
+    /// Challenges with identical IDs (same file, seed, count and options) are proven once:
+    /// the proof lists every challenge in `challenge_ids` but maps the copies to one slot,
+    /// and [`Self::verify`] accepts it for the original list. Challenges for the same file
+    /// with different seeds are proven separately.
     ///
     /// # Arguments
     ///
@@ -64,41 +238,175 @@ impl<'a> PorSystem<'a> {
     /// # Returns
     ///
     /// Returns a Proof containing the compressed SNARK and the challenge IDs
+    #[cfg(feature = "prover")]
     pub fn prove(&self, files: Vec<&PreparedFile>, challenges: &[Challenge]) -> Result<Proof> {
+        self.prove_with_timings(files, challenges)
+            .map(|(proof, _)| proof)
+    }
+
+    /// Generate a proof like [`Self::prove`] and report how long each phase took.
+    ///
+    /// The [`ProveTimings`] cover plan construction, parameter loading, witness
+    /// generation, folding and compression, so callers such as benchmarks and the
+    /// simulator can attribute time without re-running phases themselves.
+    #[cfg(feature = "prover")]
+    pub fn prove_with_timings(
+        &self,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+    ) -> Result<(Proof, ProveTimings)> {
+        self.prove_with_options_and_timings(files, challenges, &ProveOptions::default())
+    }
+
+    /// Generate a proof like [`Self::prove`] under non-default [`ProveOptions`].
+    ///
+    /// [`Self::prove`] validates a random sample of each challenged file's leaves (every
+    /// leaf of small files) and fails with [`KontorPoRError::InvalidLeafEncoding`] if one
+    /// does not encode a 31-byte symbol; set [`ProveOptions::leaf_validation`] to
+    /// [`super::types::LeafValidation::Exhaustive`] to check every leaf of every file.
+    /// Set [`ProveOptions::allow_duplicates`] to prove challenges with identical IDs once
+    /// per occurrence instead of collapsing them.
+    #[cfg(feature = "prover")]
+    pub fn prove_with_options(
+        &self,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+        options: &ProveOptions,
+    ) -> Result<Proof> {
+        self.prove_with_options_and_timings(files, challenges, options)
+            .map(|(proof, _)| proof)
+    }
+
+    /// Shared body of [`Self::prove_with_timings`] and [`Self::prove_with_options`].
+    #[cfg(feature = "prover")]
+    fn prove_with_options_and_timings(
+        &self,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+        options: &ProveOptions,
+    ) -> Result<(Proof, ProveTimings)> {
+        let ledger = self.ledger_for(challenges)?;
+        let files_map = self.files_for(files, challenges)?;
+
+        // Use the existing prove function from prove.rs
+        super::prove::prove(challenges, &files_map, ledger, options, None)
+    }
+
+    /// Generate a proof like [`Self::prove`], saving progress to `checkpoint` every
+    /// `every_n_steps` folded steps.
+    ///
+    /// Each checkpoint holds the in-progress recursive SNARK, the next step to fold and
+    /// digests of the plan and parameters. If the process dies, [`Self::resume_prove`]
+    /// continues from the last checkpoint instead of folding from step 0. No checkpoint is
+    /// written after the final step, and the file is left in place once the proof is
+    /// returned; callers delete it when they no longer need to resume.
+    #[cfg(feature = "prover")]
+    pub fn prove_with_checkpoint(
+        &self,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+        checkpoint: &Path,
+        every_n_steps: usize,
+    ) -> Result<Proof> {
+        let ledger = self.ledger_for(challenges)?;
+        let files_map = self.files_for(files, challenges)?;
+
+        super::prove::prove_with_checkpoint(
+            challenges,
+            &files_map,
+            ledger,
+            checkpoint,
+            every_n_steps,
+        )
+        .map(|(proof, _)| proof)
+    }
+
+    /// Continue a proof started by [`Self::prove_with_checkpoint`] from its last checkpoint.
+    ///
+    /// `files` and `challenges` must be the ones the checkpoint was taken with; otherwise
+    /// this fails with [`KontorPoRError::CheckpointMismatch`]. The resulting proof is
+    /// identical in form to one from [`Self::prove`] and verifies normally.
+    #[cfg(feature = "prover")]
+    pub fn resume_prove(
+        &self,
+        checkpoint: &Path,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+    ) -> Result<Proof> {
+        let ledger = self.ledger_for(challenges)?;
+        let files_map = self.files_for(files, challenges)?;
+
+        super::prove::resume_prove(challenges, &files_map, ledger, checkpoint)
+            .map(|(proof, _)| proof)
+    }
+
+    /// Generate a proof like [`Self::prove`], appending the public values computed along
+    /// the way to `transcript`.
+    ///
+    /// Records the `z0` public inputs, per-step seeds, the leaf index opened and the chain
+    /// state after every slot at every step, and the final state. Entries recorded before
+    /// a failure are kept, so a transcript of a failed run shows how far proving got.
+    /// Compare it with the verifier's from [`Self::verify_with_transcript`] using
+    /// [`Transcript::diff`].
+    #[cfg(all(feature = "prover", feature = "transcript"))]
+    pub fn prove_with_transcript(
+        &self,
+        files: Vec<&PreparedFile>,
+        challenges: &[Challenge],
+        transcript: &mut Transcript,
+    ) -> Result<Proof> {
+        super::transcript::capture(transcript, || self.prove(files, challenges))
+    }
+
+    /// Key prepared files by file ID and check they cover the challenged files.
+    ///
+    /// A challenged file without a prepared file of its own ID is served by a prepared
+    /// file with the same content (root, tree arity and hash profile), so one stored copy
+    /// answers challenges for the same data registered under several file IDs.
+    #[cfg(feature = "prover")]
+    fn files_for<'f>(
+        &self,
+        files: Vec<&'f PreparedFile>,
+        challenges: &[Challenge],
+    ) -> Result<BTreeMap<String, &'f PreparedFile>> {
         // Convert Vec<&PreparedFile> to BTreeMap<String, &PreparedFile>
         let mut files_map = BTreeMap::new();
-        for file in files {
-            if files_map.insert(file.file_id.clone(), file).is_some() {
+        for file in &files {
+            if files_map.insert(file.file_id.clone(), *file).is_some() {
                 return Err(KontorPoRError::InvalidInput(format!(
                     "Duplicate file_id provided: {}",
                     file.file_id
                 )));
             }
         }
-
-        // Validate that all files referenced by challenges are present
         for challenge in challenges {
-            if !files_map.contains_key(&challenge.file_metadata.file_id) {
-                return Err(KontorPoRError::FileNotFound {
-                    file_id: challenge.file_metadata.file_id.clone(),
-                });
+            let metadata = &challenge.file_metadata;
+            if files_map.contains_key(&metadata.file_id) {
+                continue;
+            }
+            if let Some(file) = files.iter().find(|file| file.has_content_of(metadata)) {
+                files_map.insert(metadata.file_id.clone(), *file);
             }
         }
 
+        // Validate that the prepared files match the challenged files
+        super::prove::check_file_coverage(challenges, &files_map, self.reject_unused_files)?;
+
         debug!(
             "PorSystem::prove - {} files, {} challenges",
             files_map.len(),
             challenges.len()
         );
 
-        // Use the existing prove function from prove.rs
-        super::prove::prove(challenges, &files_map, self.ledger, None)
+        Ok(files_map)
     }
 
     /// Verify a proof against the Challenges it claims to answer.
     ///
     /// This method validates that the proof's challenge_ids exactly match
-    /// the provided challenges and then performs SNARK verification.
+    /// the provided challenges, in order, before any expensive work, and then performs
+    /// SNARK verification. A mismatch is reported as [`KontorPoRError::InvalidInput`]
+    /// naming the first mismatched position and both IDs in hex.
     ///
     /// # Arguments
     ///
@@ -110,6 +418,242 @@ impl<'a> PorSystem<'a> {
     /// Returns Ok(true) if the proof is valid, Ok(false) if invalid,
     /// or an error if verification fails unexpectedly.
     pub fn verify(&self, proof: &Proof, challenges: &[Challenge]) -> Result<bool> {
+        self.verify_with_options(proof, challenges, &VerifyOptions::default())
+    }
+
+    /// Verify a proof like [`Self::verify`], enforcing the limits in `options`.
+    ///
+    /// Challenges implying more recursive steps than `options.max_steps` are rejected
+    /// with [`KontorPoRError::TooManySteps`] before any other check. Once
+    /// `options.timeout` has elapsed, verification stops at the next phase boundary with
+    /// [`KontorPoRError::VerificationTimedOut`].
+    ///
+    /// Challenges with a block height above `options.current_block_height` fail with
+    /// [`KontorPoRError::ChallengeFromFuture`]. If the ledger tracks roots by height
+    /// ([`FileLedger::record_root_at_height`]), a multi-file proof's `ledger_root` must be
+    /// the root in effect at every challenge's height, or verification fails with
+    /// [`KontorPoRError::RootNotValidAtHeight`]. A proof generated under another hash
+    /// profile than the challenged metadata records fails with
+    /// [`KontorPoRError::HashProfileMismatch`]. Once the SNARK verifies, final leaf outputs
+    /// that differ from `options.expected_leaves` fail with
+    /// [`KontorPoRError::LeafValueMismatch`].
+    ///
+    /// Without a verification cache this is [`Self::preverify_with_options`] followed by
+    /// [`Self::verify_snark`]. With a [`RootUsageTracker`] attached, the outcome is counted
+    /// under the proof's ledger root.
+    pub fn verify_with_options(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+        options: &VerifyOptions,
+    ) -> Result<bool> {
+        let outcome = self.verify_cached(proof, challenges, options);
+        if let Some(tracker) = self.root_usage {
+            if proof.binding().is_ledger()
+                && self
+                    .ledger_for(challenges)
+                    .is_ok_and(|ledger| ledger.is_valid_root(proof.ledger_root))
+            {
+                tracker.record(proof.ledger_root, &outcome);
+            }
+        }
+        outcome
+    }
+
+    /// Verify a proof against the challenges embedded in it
+    /// ([`crate::api::ProveOptions::embed_challenges`]), for proofs whose original
+    /// challenges are lost.
+    ///
+    /// Before verifying like [`Self::verify`], each embedded challenge's metadata is checked
+    /// against the ledger: for proofs against the current ledger root, and for
+    /// file-root-bound proofs, the file must be in the ledger under the same root commitment
+    /// (rc). Proofs against a historical root skip this check, since entries may have been
+    /// replaced since; verification still binds the metadata to the historical root.
+    ///
+    /// The prover chose the embedded challenges, seeds included, so a valid result only
+    /// shows that the prover held the files when it answered those seeds. It does not show
+    /// that the proof answers a challenge the verifier issued: a verifier holding its own
+    /// challenges must use [`Self::verify`], or compare the embedded challenge IDs against
+    /// the ones it issued.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::InvalidInput`] if the proof embeds no challenges
+    /// - [`KontorPoRError::FileNotInLedger`] if an embedded file is not in the ledger
+    /// - [`KontorPoRError::EmbeddedMetadataMismatch`] if its metadata has another rc than
+    ///   the ledger entry
+    /// - any error of [`Self::verify`]
+    pub fn verify_self_contained(&self, proof: &Proof) -> Result<bool> {
+        let challenges = proof.embedded_challenges.as_deref().ok_or_else(|| {
+            KontorPoRError::InvalidInput(
+                "Proof does not embed its challenges; prove with ProveOptions::embed_challenges"
+                    .to_string(),
+            )
+        })?;
+
+        let ledger = self.ledger_for(challenges)?;
+        if !proof.binding().is_ledger() || proof.ledger_root == ledger.root() {
+            for challenge in challenges {
+                let file_id = &challenge.file_metadata.file_id;
+                let entry =
+                    ledger
+                        .entry(file_id)
+                        .ok_or_else(|| KontorPoRError::FileNotInLedger {
+                            file_id: file_id.clone(),
+                        })?;
+                if entry.rc != challenge.file_metadata.commitment() {
+                    return Err(KontorPoRError::EmbeddedMetadataMismatch {
+                        file_id: file_id.clone(),
+                    });
+                }
+            }
+        }
+
+        self.verify(proof, challenges)
+    }
+
+    /// Verify a proof like [`Self::verify`], first appending to `transcript` the public
+    /// values the verifier derives from `challenges` and `proof`.
+    ///
+    /// Records the `z0` public inputs (with the proof's ledger root and indices, as
+    /// verification uses them), per-step seeds and the leaf indices of explicit-index
+    /// challenges. They are recorded before any check of the proof, so the transcript is
+    /// filled even when verification then fails, e.g. because the challenges do not match
+    /// the proof's challenge IDs; only challenges that cannot be planned at all leave it
+    /// untouched.
+    #[cfg(feature = "transcript")]
+    pub fn verify_with_transcript(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+        transcript: &mut Transcript,
+    ) -> Result<bool> {
+        let ledger = self.ledger_for(challenges)?;
+        let plan = super::plan::Plan::make_plan_at_root(
+            &super::verify::planned_challenges(challenges, proof),
+            ledger,
+            proof.ledger_root,
+        )?;
+        transcript.record_plan(&plan, proof.ledger_root, &proof.ledger_indices);
+        transcript.record_explicit_indices(&plan);
+        self.verify(proof, challenges)
+    }
+
+    /// [`Self::verify_with_options`] without root usage counting.
+    fn verify_cached(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+        options: &VerifyOptions,
+    ) -> Result<bool> {
+        let Some(cache) = self.verification_cache else {
+            let ledger = self.check_request(proof, challenges, options)?;
+            return super::verify::verify_with_options(challenges, proof, ledger, options);
+        };
+
+        let ledger = self.check_request(proof, challenges, options)?;
+        let expected_ids: Vec<_> = challenges.iter().map(|c| c.id()).collect();
+
+        // Cached outcomes do not depend on expected leaves; those are checked afterwards
+        let key = VerificationCache::key(proof, &expected_ids, options.max_steps)?;
+        let outcome = match cache.get(&key, ledger) {
+            Some(outcome) => {
+                debug!("PorSystem::verify - answered from verification cache");
+                outcome
+            }
+            None => {
+                let unpinned = VerifyOptions {
+                    expected_leaves: None,
+                    ..options.clone()
+                };
+                let outcome =
+                    super::verify::verify_with_options(challenges, proof, ledger, &unpinned);
+                let mut anchors = vec![ledger.root()];
+                if proof.shape.aggregated_tree_depth > 0 {
+                    anchors.push(proof.ledger_root);
+                }
+                cache.insert(key, &outcome, anchors);
+                outcome
+            }
+        };
+        if let Ok(true) = outcome {
+            options.check_leaves(challenges, proof)?;
+        }
+        outcome
+    }
+
+    /// Run the cheap public checks of [`Self::verify`] without verifying the SNARK.
+    ///
+    /// Covers everything verification checks before loading parameters: challenge IDs,
+    /// step and height limits, hash profile, binding and shape, ledger indices and depths,
+    /// and the validity of the proof's ledger root. Failures are the errors
+    /// [`Self::verify`] would return for them. Proofs whose recorded public inputs do not
+    /// describe the challenges, which [`Self::verify`] answers with `Ok(false)`, yield a
+    /// statement that is not [`PreverifiedStatement::is_admissible`].
+    ///
+    /// Use it to admit requests cheaply and hand the serialized statement with the proof to
+    /// a worker calling [`Self::verify_snark`]. The ledger root is checked now, so a worker
+    /// running much later should preverify again if the ledger may have dropped the root.
+    pub fn preverify(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+    ) -> Result<PreverifiedStatement> {
+        self.preverify_with_options(proof, challenges, &VerifyOptions::default())
+    }
+
+    /// [`Self::preverify`] enforcing the step, height and time limits in `options`.
+    ///
+    /// `options.expected_leaves` needs the SNARK's outputs and is only checked by
+    /// [`Self::verify_with_options`].
+    pub fn preverify_with_options(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+        options: &VerifyOptions,
+    ) -> Result<PreverifiedStatement> {
+        let ledger = self.check_request(proof, challenges, options)?;
+        super::verify::preverify_with_options(challenges, proof, ledger, options)
+    }
+
+    /// Verify the SNARK of a proof that passed [`Self::preverify`].
+    ///
+    /// Loads the parameters for the statement's shape and checks the proof's compressed
+    /// SNARK and public outputs. Returns `Ok(false)` for invalid proofs and for statements
+    /// that are not [`PreverifiedStatement::is_admissible`], and
+    /// [`KontorPoRError::InvalidInput`] if `statement` was produced for another proof. The
+    /// ledger is not consulted.
+    pub fn verify_snark(&self, statement: &PreverifiedStatement, proof: &Proof) -> Result<bool> {
+        super::verify::verify_snark(statement, proof)
+    }
+
+    /// Checks that apply before planning: limits, the addressed ledger, the hash profile
+    /// and the proof's challenge IDs. Returns the ledger the challenges are addressed to.
+    fn check_request(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+        options: &VerifyOptions,
+    ) -> Result<&'a FileLedger> {
+        options.check_steps(challenges)?;
+        let ledger = self.ledger_for(challenges)?;
+        options.check_heights(challenges, proof, ledger)?;
+        if let Some(challenge) = challenges.first() {
+            let metadata = challenge.file_metadata.hash_profile;
+            if proof.hash_profile != metadata {
+                return Err(KontorPoRError::HashProfileMismatch {
+                    proof: proof.hash_profile,
+                    metadata,
+                });
+            }
+        }
+
+        // Challenge IDs cover the metadata, so report inconsistent metadata as such rather
+        // than as an ID mismatch
+        for challenge in challenges {
+            challenge.file_metadata.validate()?;
+        }
+
         // Validate that proof.challenge_ids matches the provided challenges
         let expected_ids: Vec<_> = challenges.iter().map(|c| c.id()).collect();
 
@@ -130,8 +674,8 @@ impl<'a> PorSystem<'a> {
         {
             if proof_id != expected_id {
                 return Err(KontorPoRError::InvalidInput(format!(
-                    "Challenge ID mismatch at position {}: proof has {:?}, expected {:?}",
-                    i, proof_id.0, expected_id.0
+                    "Challenge ID mismatch at position {}: proof has {}, expected {}",
+                    i, proof_id, expected_id
                 )));
             }
         }
@@ -140,8 +684,95 @@ impl<'a> PorSystem<'a> {
             "PorSystem::verify - validated {} challenge IDs",
             challenges.len()
         );
+        Ok(ledger)
+    }
+
+    /// Verify a proof like [`Self::verify`] and return its public statement.
+    ///
+    /// Returns `Ok(Some(statement))` when the proof is valid, where the statement is
+    /// [`Proof::statement`] of the verified proof. Verification checks the proof's public
+    /// inputs and outputs against the challenges and the SNARK, so a light client storing
+    /// [`ProofStatement::digest`] and a full verifier calling this describe the same claim.
+    /// Returns `Ok(None)` for invalid proofs.
+    pub fn verify_statement(
+        &self,
+        proof: &Proof,
+        challenges: &[Challenge],
+    ) -> Result<Option<ProofStatement>> {
+        if !self.verify(proof, challenges)? {
+            return Ok(None);
+        }
+        proof.statement(challenges).map(Some)
+    }
+
+    /// Verify an aggregated proof against the challenge sets of its inner proofs.
+    ///
+    /// `challenge_sets[i]` must be the challenges answered by `aggregated.proofs[i]`.
+    /// The aggregate's commitment is checked first, then every inner proof is verified
+    /// with [`Self::verify`] against the ledger its challenges are addressed to.
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok(true) if the commitment matches and all inner proofs are valid,
+    /// Ok(false) otherwise, or an error for structural mismatches or unexpected failures.
+    pub fn verify_aggregated(
+        &self,
+        aggregated: &AggregatedProof,
+        challenge_sets: &[Vec<Challenge>],
+    ) -> Result<bool> {
+        if !aggregated.check_commitment(challenge_sets)? {
+            debug!("PorSystem::verify_aggregated - commitment mismatch");
+            return Ok(false);
+        }
+
+        for (proof, challenges) in aggregated.proofs.iter().zip(challenge_sets) {
+            if !self.verify(proof, challenges)? {
+                return Ok(false);
+            }
+        }
+
+        debug!(
+            "PorSystem::verify_aggregated - verified {} inner proofs",
+            aggregated.proofs.len()
+        );
+        Ok(true)
+    }
+
+    /// Generate a heartbeat proof: a liveness proof for an epoch in which no file is
+    /// challenged.
+    ///
+    /// The proof runs a single step with every slot inactive and binds the current root of
+    /// the ledger passed to [`Self::new`], `seed` and `prover_id`. Multi-ledger systems have
+    /// no default ledger and fail with [`KontorPoRError::InvalidInput`].
+    #[cfg(feature = "prover")]
+    pub fn prove_heartbeat(&self, seed: FieldElement, prover_id: &str) -> Result<Proof> {
+        super::heartbeat::prove_heartbeat(self.default_ledger()?, seed, prover_id)
+    }
+
+    /// Verify a heartbeat proof produced by [`Self::prove_heartbeat`].
+    ///
+    /// Fails with [`KontorPoRError::InvalidLedgerRoot`] once the proof's root is neither
+    /// the ledger's current root nor one of its retained historical roots.
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok(true) if the proof is valid for `seed` and `prover_id`, Ok(false) if
+    /// invalid, or an error for non-heartbeat proofs or unexpected failures.
+    pub fn verify_heartbeat(
+        &self,
+        proof: &Proof,
+        seed: FieldElement,
+        prover_id: &str,
+    ) -> Result<bool> {
+        super::heartbeat::verify_heartbeat(self.default_ledger()?, proof, seed, prover_id)
+    }
 
-        // Use the existing verify function from verify.rs
-        super::verify::verify(challenges, proof, self.ledger)
+    /// The ledger passed to [`Self::new`], for operations without challenges.
+    fn default_ledger(&self) -> Result<&'a FileLedger> {
+        self.ledger.ok_or_else(|| {
+            KontorPoRError::InvalidInput(
+                "Heartbeats need the single ledger of a PorSystem built with new()".to_string(),
+            )
+        })
     }
 }
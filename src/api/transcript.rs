@@ -0,0 +1,419 @@
+//! Ordered records of the public values computed while proving and verifying.
+//!
+//! A proof that the prover built but the verifier rejects usually means the two sides
+//! disagree on some public value: a seed, a ledger index, a depth. A [`Transcript`]
+//! records those values in the order they are computed, each labeled with the step and
+//! circuit slot it belongs to, so the prover's and the verifier's transcripts can be
+//! compared with [`Transcript::diff`] instead of by reading debug logs.
+//!
+//! Both sides record the `z0` public inputs and, under per-step schedules, the seed of
+//! every step. The prover also records the leaf index it opens and the chain state after
+//! each slot at each step, and the final state; the verifier records the leaf indices of
+//! explicit-index challenges. Values only one side can compute are skipped by `diff`.
+//!
+//! Available with the `transcript` feature.
+
+use super::plan::Plan;
+use super::types::FieldElement;
+use crate::{KontorPoRError, Result};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// What a [`TranscriptEntry`] records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptLabel {
+    /// `z0` aggregated root
+    AggregatedRoot,
+    /// `z0` initial chain state (the nonce commitment)
+    InitialState,
+    /// `z0` ledger index of a slot
+    LedgerIndex,
+    /// `z0` actual tree depth of a slot
+    Depth,
+    /// `z0` public seed of a slot
+    Seed,
+    /// Seed of a slot at one step under a per-step schedule
+    StepSeed,
+    /// Leaf index opened in a slot at one step
+    LeafIndex,
+    /// Chain state after a slot's opening at one step
+    StateAfter,
+    /// Chain state after the last step
+    FinalState,
+}
+
+impl fmt::Display for TranscriptLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::AggregatedRoot => "aggregated_root",
+            Self::InitialState => "initial_state",
+            Self::LedgerIndex => "ledger_index",
+            Self::Depth => "depth",
+            Self::Seed => "seed",
+            Self::StepSeed => "step_seed",
+            Self::LeafIndex => "leaf_index",
+            Self::StateAfter => "state_after",
+            Self::FinalState => "final_state",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One recorded public value.
+///
+/// Entries are identified by `(label, step, slot)`; `file_id` names the file challenged
+/// in the slot, if any. Field elements are recorded as 64 lowercase hex characters of
+/// their canonical little-endian encoding, indices and depths in decimal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// What the value is
+    pub label: TranscriptLabel,
+    /// Folding step, for per-step values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+    /// Circuit slot, for per-slot values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<usize>,
+    /// File challenged in the slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    /// The value
+    pub value: String,
+}
+
+impl TranscriptEntry {
+    fn key(&self) -> (TranscriptLabel, Option<usize>, Option<usize>) {
+        (self.label, self.step, self.slot)
+    }
+}
+
+impl fmt::Display for TranscriptEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)?;
+        if let Some(step) = self.step {
+            write!(f, " step {}", step)?;
+        }
+        if let Some(slot) = self.slot {
+            write!(f, " slot {}", slot)?;
+        }
+        if let Some(file_id) = &self.file_id {
+            write!(f, " ({})", file_id)?;
+        }
+        write!(f, " = {}", self.value)
+    }
+}
+
+/// An entry on which two transcripts disagree, found by [`Transcript::diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptDivergence {
+    /// Position of the entry in the transcript it was taken from (`ours` if present)
+    pub position: usize,
+    /// The entry in the transcript `diff` was called on, if it has one for this key
+    pub ours: Option<TranscriptEntry>,
+    /// The entry in the other transcript, if it has one for this key
+    pub theirs: Option<TranscriptEntry>,
+    /// Up to [`Transcript::CONTEXT_ENTRIES`] entries preceding the divergence
+    pub context: Vec<TranscriptEntry>,
+}
+
+impl fmt::Display for TranscriptDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |entry: &Option<TranscriptEntry>| {
+            entry
+                .as_ref()
+                .map_or_else(|| "missing".to_string(), ToString::to_string)
+        };
+        write!(
+            f,
+            "entry {}: ours {}, theirs {}",
+            self.position,
+            show(&self.ours),
+            show(&self.theirs)
+        )
+    }
+}
+
+/// The ordered public values one side computed for a proof.
+///
+/// Fill one with [`super::PorSystem::prove_with_transcript`] or
+/// [`super::PorSystem::verify_with_transcript`], store or send it with
+/// [`Self::to_json`], and compare two with [`Self::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// Number of preceding entries reported with each divergence.
+    pub const CONTEXT_ENTRIES: usize = 3;
+
+    /// Creates an empty transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded entries, in the order they were computed.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// Encodes the transcript as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| KontorPoRError::Serialization(e.to_string()))
+    }
+
+    /// Decodes a transcript produced by [`Self::to_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| KontorPoRError::Serialization(format!("Invalid transcript JSON: {}", e)))
+    }
+
+    /// Entries on which this transcript and `other` disagree, in this transcript's order;
+    /// the first one is where the two computations first diverged.
+    ///
+    /// Only labels both transcripts recorded are compared, so a prover's transcript can be
+    /// diffed against a verifier's. An entry diverges if the other transcript has a
+    /// different value or file for its key, or no entry for it at all. Entries only
+    /// `other` has are reported last, positioned in `other`.
+    pub fn diff(&self, other: &Transcript) -> Vec<TranscriptDivergence> {
+        let labels = |t: &Transcript| t.entries.iter().map(|e| e.label).collect::<HashSet<_>>();
+        let shared: HashSet<_> = labels(self).intersection(&labels(other)).copied().collect();
+        let (our_index, their_index) = (self.index(), other.index());
+
+        let mut divergences = Vec::new();
+        for (position, entry) in self.entries.iter().enumerate() {
+            if !shared.contains(&entry.label) {
+                continue;
+            }
+            let theirs = their_index.get(&entry.key()).copied();
+            if theirs != Some(entry) {
+                divergences.push(TranscriptDivergence {
+                    position,
+                    ours: Some(entry.clone()),
+                    theirs: theirs.cloned(),
+                    context: self.context_before(position),
+                });
+            }
+        }
+        for (position, entry) in other.entries.iter().enumerate() {
+            if shared.contains(&entry.label) && !our_index.contains_key(&entry.key()) {
+                divergences.push(TranscriptDivergence {
+                    position,
+                    ours: None,
+                    theirs: Some(entry.clone()),
+                    context: other.context_before(position),
+                });
+            }
+        }
+        divergences
+    }
+
+    fn index(&self) -> HashMap<(TranscriptLabel, Option<usize>, Option<usize>), &TranscriptEntry> {
+        self.entries.iter().map(|e| (e.key(), e)).collect()
+    }
+
+    fn context_before(&self, position: usize) -> Vec<TranscriptEntry> {
+        self.entries[position.saturating_sub(Self::CONTEXT_ENTRIES)..position].to_vec()
+    }
+
+    fn push(
+        &mut self,
+        label: TranscriptLabel,
+        step: Option<usize>,
+        slot: Option<usize>,
+        file_id: Option<&str>,
+        value: String,
+    ) {
+        self.entries.push(TranscriptEntry {
+            label,
+            step,
+            slot,
+            file_id: file_id.map(str::to_string),
+            value,
+        });
+    }
+
+    /// Records the `z0` public inputs of `plan` with the given aggregated root and ledger
+    /// indices (the plan's own for the prover, the proof's for the verifier), followed by
+    /// the seed of every step under a per-step schedule.
+    pub(crate) fn record_plan(
+        &mut self,
+        plan: &Plan,
+        aggregated_root: FieldElement,
+        ledger_indices: &[usize],
+    ) {
+        let file_id = |slot: usize| {
+            plan.sorted_challenges
+                .get(slot)
+                .map(|c| c.file_metadata.file_id.as_str())
+        };
+        self.push(
+            TranscriptLabel::AggregatedRoot,
+            None,
+            None,
+            None,
+            field_hex(&aggregated_root),
+        );
+        self.push(
+            TranscriptLabel::InitialState,
+            None,
+            None,
+            None,
+            field_hex(&plan.initial_state),
+        );
+        for slot in 0..plan.ledger_indices.len() {
+            let index = ledger_indices.get(slot).copied().unwrap_or_default();
+            let label = TranscriptLabel::LedgerIndex;
+            self.push(label, None, Some(slot), file_id(slot), index.to_string());
+        }
+        for (slot, depth) in plan.depths.iter().enumerate() {
+            let label = TranscriptLabel::Depth;
+            self.push(label, None, Some(slot), file_id(slot), depth.to_string());
+        }
+        for (slot, seed) in plan.seeds.iter().enumerate() {
+            let label = TranscriptLabel::Seed;
+            self.push(label, None, Some(slot), file_id(slot), field_hex(seed));
+        }
+
+        for step in 0..plan.num_steps() {
+            let Some(seeds) = plan.step_seeds(step) else {
+                break;
+            };
+            for (slot, seed) in seeds.iter().enumerate().take(plan.sorted_challenges.len()) {
+                let label = TranscriptLabel::StepSeed;
+                self.push(
+                    label,
+                    Some(step),
+                    Some(slot),
+                    file_id(slot),
+                    field_hex(seed),
+                );
+            }
+        }
+    }
+
+    /// Records the leaf index each explicit-index challenge opens at every step.
+    pub(crate) fn record_explicit_indices(&mut self, plan: &Plan) {
+        for step in 0..plan.num_steps() {
+            for (slot, challenge) in plan.sorted_challenges.iter().enumerate() {
+                if let Some(index) = challenge.explicit_index(step) {
+                    self.push(
+                        TranscriptLabel::LeafIndex,
+                        Some(step),
+                        Some(slot),
+                        Some(&challenge.file_metadata.file_id),
+                        index.to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Records the leaf index opened in `slot` at `step` and the chain state after it.
+    pub(crate) fn record_opening(
+        &mut self,
+        step: usize,
+        slot: usize,
+        file_id: &str,
+        leaf_index: usize,
+        state_after: FieldElement,
+    ) {
+        let (step, slot, file_id) = (Some(step), Some(slot), Some(file_id));
+        let index = leaf_index.to_string();
+        self.push(TranscriptLabel::LeafIndex, step, slot, file_id, index);
+        let state = field_hex(&state_after);
+        self.push(TranscriptLabel::StateAfter, step, slot, file_id, state);
+    }
+
+    /// Records the chain state after the last step.
+    #[cfg(feature = "prover")]
+    pub(crate) fn record_final_state(&mut self, state: FieldElement) {
+        let label = TranscriptLabel::FinalState;
+        self.push(label, None, None, None, field_hex(&state));
+    }
+}
+
+fn field_hex(element: &FieldElement) -> String {
+    element
+        .to_repr()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+thread_local! {
+    /// Transcript being captured by [`capture`] on this thread.
+    static ACTIVE: RefCell<Option<Transcript>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, appending whatever it records with `transcript_record!` on this thread to
+/// `transcript`.
+#[cfg(feature = "prover")]
+pub(crate) fn capture<R>(transcript: &mut Transcript, f: impl FnOnce() -> R) -> R {
+    let outer = ACTIVE.with(|active| active.replace(Some(std::mem::take(transcript))));
+    let result = f();
+    *transcript = ACTIVE
+        .with(|active| active.replace(outer))
+        .unwrap_or_default();
+    result
+}
+
+/// Calls `f` with the transcript being captured on this thread, if any.
+pub(crate) fn with_active(f: impl FnOnce(&mut Transcript)) {
+    ACTIVE.with(|active| {
+        if let Some(transcript) = active.borrow_mut().as_mut() {
+            f(transcript);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(values: &[(TranscriptLabel, Option<usize>, &str)]) -> Transcript {
+        let mut transcript = Transcript::new();
+        for (label, slot, value) in values {
+            transcript.push(*label, None, *slot, None, value.to_string());
+        }
+        transcript
+    }
+
+    #[test]
+    fn test_diff_compares_shared_labels() {
+        use TranscriptLabel::*;
+        let prover = transcript(&[
+            (InitialState, None, "00"),
+            (Seed, Some(0), "01"),
+            (Seed, Some(1), "02"),
+            (FinalState, None, "03"),
+        ]);
+        let verifier = transcript(&[
+            (InitialState, None, "00"),
+            (Seed, Some(0), "01"),
+            (Seed, Some(1), "05"),
+            (Seed, Some(2), "06"),
+        ]);
+
+        // The final state is only in the prover's transcript and is not compared
+        let divergences = prover.diff(&verifier);
+        assert_eq!(divergences.len(), 2);
+        let first = &divergences[0];
+        assert_eq!(first.position, 2);
+        assert_eq!(first.ours.as_ref().unwrap().value, "02");
+        assert_eq!(first.theirs.as_ref().unwrap().value, "05");
+        assert_eq!(first.context, prover.entries()[..2]);
+        let extra = &divergences[1];
+        assert_eq!((extra.position, extra.ours.is_none()), (3, true));
+        assert_eq!(extra.theirs.as_ref().unwrap().slot, Some(2));
+
+        assert!(prover.diff(&prover).is_empty());
+        let decoded = Transcript::from_json(&prover.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, prover);
+    }
+}
@@ -0,0 +1,375 @@
+//! Canonical JSON encoding of challenges for transport between services.
+//!
+//! Verifier services create challenges and send them to provers. [`Challenge::to_json`]
+//! encodes a challenge as a versioned JSON object with field elements in hex and the
+//! file metadata as an explicit sub-object; [`Challenge::from_json`] rejects unknown
+//! fields, unknown schema versions, non-canonical field elements and challenges failing
+//! [`Challenge::validate`]. The encoding is deterministic, so it doubles as the message
+//! a [`SignedChallenge`] signs: a prover checks the verifier's signature with
+//! [`SignedChallenge::verify`] before doing any expensive work.
+//!
+//! [`FileMetadata::to_json`] encodes metadata on its own as the same `file_metadata`
+//! object, for handing a file's public commitment to the service issuing its challenges.
+
+use super::types::{
+    Challenge, ChallengeKind, ChallengeMode, ErasureCode, FieldElement, FileMetadata, ProofBinding,
+    SeedSchedule,
+};
+use crate::circuit::CircuitMode;
+use crate::config::CHALLENGE_SCHEMA_VERSION;
+use crate::merkle::{
+    canonical::{field_from_bytes, field_to_bytes},
+    TreeArity,
+};
+use crate::poseidon::HashProfile;
+use crate::{KontorPoRError, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Domain separator prefixed to the canonical encoding before signing.
+const SIGNATURE_DOMAIN: &[u8] = b"kontor-por-signed-challenge";
+
+/// 32 bytes encoded as 64 lowercase hex characters.
+#[derive(Clone, Copy)]
+struct Hex32([u8; 32]);
+
+impl Hex32 {
+    fn field(element: &FieldElement) -> Self {
+        Self(field_to_bytes(element))
+    }
+
+    fn to_field(self, context: &str) -> Result<FieldElement> {
+        field_from_bytes(self.0).ok_or_else(|| KontorPoRError::NonCanonicalFieldElement {
+            context: context.to_string(),
+        })
+    }
+}
+
+impl Serialize for Hex32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Hex32 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        from_hex(&hex)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Hex32)
+            .ok_or_else(|| D::Error::custom("expected 64 lowercase hex characters"))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Parses lowercase hex; uppercase is rejected so every value has one encoding.
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Wire form of [`FileMetadata`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WireMetadata {
+    file_id: String,
+    root: Hex32,
+    padded_len: usize,
+    original_size: usize,
+    tree_arity: TreeArity,
+    /// `null` for raw files; required so a missing field never reads as raw
+    #[serde(deserialize_with = "Option::deserialize")]
+    erasure: Option<ErasureCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt_commitment: Option<Hex32>,
+    hash_profile: HashProfile,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<String, String>,
+}
+
+/// Wire form of [`Challenge`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WireChallenge {
+    schema_version: u32,
+    file_metadata: WireMetadata,
+    block_height: u64,
+    num_challenges: usize,
+    seed: Hex32,
+    prover_id: String,
+    mode: ChallengeMode,
+    circuit_mode: CircuitMode,
+    nonce: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    per_step_seeds: Option<Vec<Hex32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explicit_indices: Option<Vec<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ledger_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_agg_depth: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_files_per_step: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    binding: Option<ProofBinding>,
+}
+
+/// Just the version, read before the rest so newer encodings fail with a clear error.
+#[derive(Deserialize)]
+struct WireVersion {
+    schema_version: u32,
+}
+
+impl WireMetadata {
+    fn new(metadata: &FileMetadata) -> Self {
+        Self {
+            file_id: metadata.file_id.clone(),
+            root: Hex32::field(&metadata.root),
+            padded_len: metadata.padded_len,
+            original_size: metadata.original_size,
+            tree_arity: metadata.tree_arity,
+            erasure: metadata.erasure,
+            salt_commitment: metadata.salt_commitment.map(Hex32),
+            hash_profile: metadata.hash_profile,
+            annotations: metadata.annotations.clone(),
+        }
+    }
+
+    fn into_metadata(self) -> Result<FileMetadata> {
+        Ok(FileMetadata {
+            root: self.root.to_field("file root")?,
+            file_id: self.file_id,
+            padded_len: self.padded_len,
+            original_size: self.original_size,
+            annotations: self.annotations,
+            tree_arity: self.tree_arity,
+            erasure: self.erasure,
+            salt_commitment: self.salt_commitment.map(|salt| salt.0),
+            hash_profile: self.hash_profile,
+        })
+    }
+}
+
+impl WireChallenge {
+    fn new(challenge: &Challenge) -> Self {
+        Self {
+            schema_version: CHALLENGE_SCHEMA_VERSION,
+            file_metadata: WireMetadata::new(&challenge.file_metadata),
+            block_height: challenge.block_height,
+            num_challenges: challenge.num_challenges,
+            seed: Hex32::field(&challenge.seed),
+            prover_id: challenge.prover_id.clone(),
+            mode: challenge.mode,
+            circuit_mode: challenge.circuit_mode,
+            nonce: challenge.nonce,
+            per_step_seeds: match &challenge.seed_schedule {
+                SeedSchedule::Fixed => None,
+                SeedSchedule::PerStep(seeds) => Some(seeds.iter().map(Hex32::field).collect()),
+            },
+            explicit_indices: match &challenge.kind {
+                ChallengeKind::Seeded => None,
+                ChallengeKind::ExplicitIndices(indices) => Some(indices.clone()),
+            },
+            ledger_id: challenge.ledger_id.clone(),
+            max_agg_depth: challenge.max_agg_depth,
+            max_files_per_step: challenge.max_files_per_step,
+            binding: challenge.binding,
+        }
+    }
+
+    /// Converts back to a challenge, failing as [`Challenge::from_json`] documents.
+    fn into_challenge(self) -> Result<Challenge> {
+        let file_metadata = self.file_metadata.into_metadata()?;
+        let seed_schedule = match self.per_step_seeds {
+            None => SeedSchedule::Fixed,
+            Some(seeds) => SeedSchedule::PerStep(
+                seeds
+                    .into_iter()
+                    .map(|seed| seed.to_field("challenge per-step seed"))
+                    .collect::<Result<_>>()?,
+            ),
+        };
+
+        let challenge = Challenge {
+            file_metadata,
+            block_height: self.block_height,
+            num_challenges: self.num_challenges,
+            seed: self.seed.to_field("challenge seed")?,
+            prover_id: self.prover_id,
+            mode: self.mode,
+            seed_schedule,
+            nonce: self.nonce,
+            ledger_id: self.ledger_id,
+            circuit_mode: self.circuit_mode,
+            kind: self
+                .explicit_indices
+                .map_or(ChallengeKind::Seeded, ChallengeKind::ExplicitIndices),
+            max_agg_depth: self.max_agg_depth,
+            max_files_per_step: self.max_files_per_step,
+            binding: self.binding,
+        };
+        challenge.validate()?;
+        Ok(challenge)
+    }
+}
+
+fn check_version(json: &str) -> Result<()> {
+    let version: WireVersion = serde_json::from_str(json)
+        .map_err(|e| KontorPoRError::Serialization(format!("Invalid challenge JSON: {}", e)))?;
+    if version.schema_version != CHALLENGE_SCHEMA_VERSION {
+        return Err(KontorPoRError::UnsupportedChallengeVersion {
+            found: version.schema_version,
+            supported: CHALLENGE_SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+impl Challenge {
+    /// Encodes this challenge as canonical JSON for sending to a prover.
+    ///
+    /// The object carries `schema_version` ([`crate::config::CHALLENGE_SCHEMA_VERSION`]),
+    /// the file metadata under `file_metadata`, and field elements (root, seeds, salt
+    /// commitment) as 64 lowercase hex characters of their canonical little-endian
+    /// encoding. Optional settings left at their defaults are omitted. Equal challenges
+    /// always encode to the same string.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&WireChallenge::new(self))
+            .map_err(|e| KontorPoRError::Serialization(e.to_string()))
+    }
+
+    /// Decodes a challenge produced by [`Self::to_json`].
+    ///
+    /// Fails with [`KontorPoRError::UnsupportedChallengeVersion`] for another schema
+    /// version, [`KontorPoRError::Serialization`] for malformed JSON, unknown fields or
+    /// bad hex, [`KontorPoRError::NonCanonicalFieldElement`] for field elements not below
+    /// the modulus, and the errors of [`Self::validate`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        check_version(json)?;
+        let wire: WireChallenge = serde_json::from_str(json)
+            .map_err(|e| KontorPoRError::Serialization(format!("Invalid challenge JSON: {}", e)))?;
+        wire.into_challenge()
+    }
+}
+
+impl FileMetadata {
+    /// Encodes this metadata as the `file_metadata` object of [`Challenge::to_json`], e.g.
+    /// for handing a file's public commitment to the service that issues its challenges.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&WireMetadata::new(self))
+            .map_err(|e| KontorPoRError::Serialization(e.to_string()))
+    }
+
+    /// Decodes metadata produced by [`Self::to_json`], rejecting unknown fields, bad hex
+    /// and non-canonical field elements like [`Challenge::from_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        let wire: WireMetadata = serde_json::from_str(json)
+            .map_err(|e| KontorPoRError::Serialization(format!("Invalid metadata JSON: {}", e)))?;
+        wire.into_metadata()
+    }
+}
+
+/// Wire form of [`SignedChallenge`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WireSignedChallenge {
+    challenge: WireChallenge,
+    signature: String,
+}
+
+/// A challenge signed by the verifier service that issued it.
+///
+/// The Ed25519 signature covers a domain separator followed by the challenge's canonical
+/// JSON ([`Challenge::to_json`]), so any change to the challenge, including its metadata
+/// or annotations, invalidates it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedChallenge {
+    challenge: Challenge,
+    signature: Signature,
+}
+
+impl SignedChallenge {
+    /// Signs `challenge` with the verifier's key.
+    pub fn sign(challenge: Challenge, key: &SigningKey) -> Result<Self> {
+        let signature = key.sign(&Self::message(&challenge)?);
+        Ok(Self {
+            challenge,
+            signature,
+        })
+    }
+
+    fn message(challenge: &Challenge) -> Result<Vec<u8>> {
+        let mut message = SIGNATURE_DOMAIN.to_vec();
+        message.extend_from_slice(challenge.to_json()?.as_bytes());
+        Ok(message)
+    }
+
+    /// Checks the signature under the verifier's public key and returns the challenge.
+    ///
+    /// Fails with [`KontorPoRError::InvalidChallengeSignature`] if the challenge was
+    /// altered or signed with another key. Verification is strict: malleable and
+    /// small-order signatures and keys are rejected.
+    pub fn verify(&self, key: &VerifyingKey) -> Result<&Challenge> {
+        key.verify_strict(&Self::message(&self.challenge)?, &self.signature)
+            .map_err(|_| KontorPoRError::InvalidChallengeSignature)?;
+        Ok(&self.challenge)
+    }
+
+    /// The signed challenge, whose signature has not necessarily been checked.
+    pub fn challenge(&self) -> &Challenge {
+        &self.challenge
+    }
+
+    /// The Ed25519 signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Encodes the challenge and signature as JSON: `{"challenge": ..., "signature": ...}`
+    /// with the challenge in the form of [`Challenge::to_json`] and the signature as 128
+    /// lowercase hex characters.
+    pub fn to_json(&self) -> Result<String> {
+        let wire = WireSignedChallenge {
+            challenge: WireChallenge::new(&self.challenge),
+            signature: to_hex(&self.signature.to_bytes()),
+        };
+        serde_json::to_string(&wire).map_err(|e| KontorPoRError::Serialization(e.to_string()))
+    }
+
+    /// Decodes a signed challenge produced by [`Self::to_json`], validating the challenge
+    /// as [`Challenge::from_json`] does. The signature is only checked by
+    /// [`Self::verify`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| {
+            KontorPoRError::Serialization(format!("Invalid signed challenge JSON: {}", e))
+        })?;
+        if let Some(challenge) = value.get("challenge") {
+            check_version(&challenge.to_string())?;
+        }
+        let wire: WireSignedChallenge = serde_json::from_value(value).map_err(|e| {
+            KontorPoRError::Serialization(format!("Invalid signed challenge JSON: {}", e))
+        })?;
+        let signature = from_hex(&wire.signature)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .map(|bytes| Signature::from_bytes(&bytes))
+            .ok_or_else(|| {
+                KontorPoRError::Serialization(
+                    "Challenge signature must be 128 lowercase hex characters".to_string(),
+                )
+            })?;
+        Ok(Self {
+            challenge: wire.challenge.into_challenge()?,
+            signature,
+        })
+    }
+}
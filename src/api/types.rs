@@ -7,7 +7,11 @@
 //! - Proof: Final succinct proof object
 //! - PorParams: Cryptographic parameters
 
+use crate::circuit::CircuitMode;
+use crate::merkle::TreeArity;
+use crate::poseidon::HashProfile;
 use bincode::Options;
+use ff::FromUniformBytes;
 use nova_snark::{
     nova::{CompressedSNARK, ProverKey, PublicParams, VerifierKey},
     provider::{ipa_pc, PallasEngine, VestaEngine},
@@ -15,8 +19,10 @@ use nova_snark::{
     traits::Engine,
 };
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // --- Type Aliases for Core SNARK Components ---
 
@@ -33,9 +39,42 @@ pub type FieldElement = <E1 as Engine>::Scalar;
 type C = crate::circuit::PorCircuit<FieldElement>;
 
 /// Deterministic identity for a Challenge.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Computed by [`Challenge::id`]; displayed (and parsed with [`Self::from_hex`]) as
+/// 64 lowercase hex characters, so IDs can key external databases. IDs order by their
+/// bytes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ChallengeID(pub [u8; 32]);
 
+impl ChallengeID {
+    /// Returns the ID as a lowercase hex string (same as its `Display` form).
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Parses an ID from the 64-character hex string produced by [`Self::to_hex`].
+    pub fn from_hex(hex: &str) -> crate::Result<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(crate::KontorPoRError::Serialization(
+                "Challenge ID hex must be 64 ASCII characters".to_string(),
+            ));
+        }
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|e| {
+                crate::KontorPoRError::Serialization(format!("Invalid challenge ID hex: {}", e))
+            })?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl std::fmt::Display for ChallengeID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
 /// The final, succinct proof object that is sent to the verifier.
 #[derive(Serialize, Deserialize)]
 pub struct Proof {
@@ -47,6 +86,7 @@ pub struct Proof {
     /// Verifiers must validate this root is in their accepted historical roots set
     /// before verification. This enables cross-block aggregation without requiring
     /// provers to regenerate proofs when new files activate.
+    #[serde(with = "crate::merkle::canonical::field")]
     pub ledger_root: FieldElement,
     /// The ledger indices for each file at proof generation time.
     /// These are the positions of each file's root commitment (rc) in the ledger tree.
@@ -58,9 +98,65 @@ pub struct Proof {
     ///
     /// The SNARK proves these indices are correct for the claimed ledger_root.
     pub ledger_indices: Vec<usize>,
-    /// The aggregated tree depth at proof generation time.
-    /// Required for verification to load the correct circuit parameters.
-    pub aggregated_tree_depth: usize,
+    /// Tree depth of the file in each slot, parallel to `ledger_indices` (zero for padding
+    /// slots). Verification checks them against the challenges.
+    pub file_depths: Vec<usize>,
+    /// The circuit shape at proof generation time: file slots per step, file tree depth
+    /// and aggregated tree depth. Required for verification to load the correct circuit
+    /// parameters; verification rejects a shape other than the one its challenges derive
+    /// with [`crate::KontorPoRError::ShapeMismatch`].
+    pub shape: crate::config::Shape,
+    /// Commitment to the challenges' nonces, used as the initial chain state.
+    ///
+    /// Zero when every challenge has nonce 0. Verification checks it against the
+    /// challenges; a verifier that records the commitments it has accepted can reject a
+    /// replayed (proof, challenges) pair by looking it up.
+    #[serde(with = "crate::merkle::canonical::field")]
+    pub challenge_nonce_commitment: FieldElement,
+    /// Circuit slot of each challenge, parallel to `challenge_ids`.
+    ///
+    /// Slots follow the canonical (file_id, challenge ID) order, so they can differ from
+    /// the order of `challenge_ids`. Verification checks them against the challenges.
+    pub challenge_slots: Vec<usize>,
+    /// The circuit's initial public inputs (`z0`), laid out as in
+    /// [`crate::config::PublicIOLayout`]. Verification checks them against the challenges.
+    #[serde(with = "crate::merkle::canonical::fields")]
+    pub public_inputs: Vec<FieldElement>,
+    /// The circuit's public outputs after the last step (`zn`), as returned by the SNARK
+    /// verifier.
+    #[serde(with = "crate::merkle::canonical::fields")]
+    pub public_outputs: Vec<FieldElement>,
+    /// Hash profile of the files' trees the proof was generated for.
+    ///
+    /// Verification rejects challenges whose metadata records another profile with
+    /// [`crate::KontorPoRError::HashProfileMismatch`].
+    pub hash_profile: HashProfile,
+    /// The challenges the proof answers, in `challenge_ids` order, when proved with
+    /// [`ProveOptions::embed_challenges`]; `None` otherwise. They let
+    /// [`crate::api::PorSystem::verify_self_contained`] verify the proof without the
+    /// original challenges.
+    pub embedded_challenges: Option<Vec<Challenge>>,
+    /// Public input/output layout the proof was generated under,
+    /// [`crate::config::PUBLIC_IO_SCHEMA_VERSION`] at proving time.
+    ///
+    /// Verification rejects other versions with
+    /// [`crate::KontorPoRError::SchemaVersionMismatch`] before any other check.
+    pub schema_version: u32,
+}
+
+/// Serialized size of a [`Proof`]'s parts in bytes, from [`Proof::size_breakdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofSizeBreakdown {
+    /// The compressed SNARK
+    pub snark: usize,
+    /// The embedded challenges; one byte unless proved with
+    /// [`ProveOptions::embed_challenges`]
+    pub embedded_challenges: usize,
+    /// Everything else: the format header, challenge IDs, ledger root, indices, shape,
+    /// slots and public inputs and outputs
+    pub statement: usize,
+    /// The whole encoding, as returned by [`Proof::to_bytes`]
+    pub total: usize,
 }
 
 /// Constants for proof serialization format
@@ -69,13 +165,294 @@ mod proof_format {
     pub const MAGIC: &[u8] = b"NPOR";
 
     /// Current format version for forward compatibility
-    pub const VERSION: u16 = 1;
+    ///
+    /// Version 3 encodes `ledger_root` and `challenge_nonce_commitment` canonically.
+    /// Version 4 adds the challenge slots and the circuit's public inputs and outputs.
+    /// Version 5 adds the hash profile.
+    /// Version 6 adds the public input/output schema version.
+    /// Version 7 adds the per-slot file depths.
+    /// Version 8 replaces the aggregated tree depth with the full circuit shape.
+    /// Version 9 adds the optional embedded challenges.
+    pub const VERSION: u16 = 9;
 
     /// Header size in bytes: magic(4) + version(2) + length(4)
     pub const HEADER_SIZE: usize = 10;
 }
 
+/// One-line summary for logs: challenge count, ledger root and aggregated depth.
+impl std::fmt::Display for Proof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "proof ({} challenges, ledger root {:?}, aggregated depth {})",
+            self.challenge_ids.len(),
+            self.ledger_root,
+            self.shape.aggregated_tree_depth
+        )
+    }
+}
+
 impl Proof {
+    /// The root this proof binds its files to.
+    ///
+    /// Ledger-bound proofs always open rcs along an aggregation path, padded to at least
+    /// one level for ledgers holding a single file, so the binding follows from the
+    /// shape's `aggregated_tree_depth`: [`ProofBinding::LedgerRoot`] when it is non-zero.
+    /// Heartbeat proofs open no file and report [`ProofBinding::FileRoot`].
+    pub fn binding(&self) -> ProofBinding {
+        if self.shape.aggregated_tree_depth > 0 {
+            ProofBinding::LedgerRoot
+        } else {
+            ProofBinding::FileRoot
+        }
+    }
+
+    /// Public I/O layout of the proof's step circuit, read from its recorded inputs.
+    ///
+    /// Batches partitioned into passes record ledger indices for the slots of every pass,
+    /// so their circuit has fewer slots than [`Self::ledger_indices`].
+    pub(crate) fn public_io_layout(&self) -> crate::config::PublicIOLayout {
+        let slot_inputs = self
+            .public_inputs
+            .len()
+            .saturating_sub(crate::config::PublicIOLayout::FIXED);
+        crate::config::PublicIOLayout::new(slot_inputs / 4)
+    }
+
+    /// Ledger indices of the files this proof covers, in slot order.
+    ///
+    /// Padding slots are left out, as are the indices of file-root-bound proofs, which open
+    /// no aggregation path (their single index is always zero). Heartbeat proofs cover no
+    /// files. Use [`Self::resolve_files`] to map the indices to file IDs.
+    pub fn covered_indices(&self) -> &[usize] {
+        if !self.binding().is_ledger() {
+            return &[];
+        }
+        let covered = self.challenge_ids.len().min(self.ledger_indices.len());
+        &self.ledger_indices[..covered]
+    }
+
+    /// IDs of the files in `ledger` this proof covers, in slot order.
+    ///
+    /// Ledger-bound proofs map [`Self::covered_indices`] to files through the ordering of
+    /// `ledger` at the proof's `ledger_root`, and each file's depth must match
+    /// [`Self::file_depths`]. The ledger keeps only the hashes of its historical roots, not
+    /// their entries, so a proof against a historical root can be resolved only while the
+    /// indices still hold the same files:
+    /// - under [`crate::ledger::IndexPolicy::InsertionOrder`], slots never move, so the
+    ///   file currently in each slot is the one the proof covers unless the slot was
+    ///   tombstoned or its file replaced;
+    /// - under [`crate::ledger::IndexPolicy::Canonical`], any change to the file set may
+    ///   shift indices, so historical roots are never resolved.
+    ///
+    /// Those cases fail with [`crate::KontorPoRError::CannotResolveHistorical`]. A
+    /// file-root-bound proof names no ledger index; it resolves to every file in `ledger`
+    /// with its root and depth, which may be several when the same data is registered
+    /// under several IDs.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::KontorPoRError::InvalidLedgerRoot`] if a ledger-bound proof's root is not
+    /// a valid root of `ledger`, and [`crate::KontorPoRError::InvalidInput`] if an index at
+    /// the current root holds no file of the recorded depth.
+    pub fn resolve_files(&self, ledger: &crate::ledger::FileLedger) -> crate::Result<Vec<String>> {
+        use crate::ledger::IndexPolicy;
+        use crate::KontorPoRError;
+
+        if self.challenge_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.binding().is_ledger() {
+            let depth = self.file_depths.first().copied().unwrap_or_default();
+            return Ok(ledger
+                .entries()
+                .filter(|(_, entry)| entry.root == self.ledger_root && entry.depth == depth)
+                .map(|(file_id, _)| file_id.to_string())
+                .collect());
+        }
+
+        let proof_root = || format!("{:?}", self.ledger_root);
+        if !ledger.is_valid_root(self.ledger_root) {
+            return Err(KontorPoRError::InvalidLedgerRoot {
+                proof_root: proof_root(),
+                reason: "Proof's ledger_root is not in the set of valid historical roots"
+                    .to_string(),
+            });
+        }
+        let historical = self.ledger_root != ledger.root();
+        // Only insertion-order slots keep their files across ledger changes
+        let stable = !historical || ledger.index_policy() == IndexPolicy::InsertionOrder;
+
+        self.covered_indices()
+            .iter()
+            .enumerate()
+            .map(|(slot, &index)| {
+                let depth = self.file_depths.get(slot).copied();
+                let resolved = ledger.file_id_at(index).filter(|file_id| {
+                    stable && ledger.entry(file_id).map(|entry| entry.depth) == depth
+                });
+                match resolved {
+                    Some(file_id) => Ok(file_id.to_string()),
+                    None if historical => Err(KontorPoRError::CannotResolveHistorical {
+                        proof_root: proof_root(),
+                        index,
+                    }),
+                    None => Err(KontorPoRError::InvalidInput(format!(
+                        "Proof ledger index {} holds no file of depth {:?}",
+                        index, depth
+                    ))),
+                }
+            })
+            .collect()
+    }
+
+    /// IDs of the files this proof covers, for a proof made against a ledger that `ledger`
+    /// was migrated from with [`crate::ledger::FileLedger::migrate_from`].
+    ///
+    /// If the proof's root is one of the old ledger's roots in `receipt`, each covered
+    /// index is mapped through the receipt to the file that held it in the old ledger;
+    /// that file must still be in `ledger` with the depth recorded in
+    /// [`Self::file_depths`]. As in [`Self::resolve_files`], indices are known only at the
+    /// old ledger's final root, or at any of its roots under
+    /// [`crate::ledger::IndexPolicy::InsertionOrder`]. Proofs against any other root are
+    /// resolved by [`Self::resolve_files`].
+    ///
+    /// # Errors
+    ///
+    /// [`crate::KontorPoRError::InvalidLedgerRoot`] if the proof's root is no longer a
+    /// valid root of `ledger` (for example after
+    /// [`crate::ledger::FileLedger::prune_migrated_roots`]), and
+    /// [`crate::KontorPoRError::CannotResolveHistorical`] if a covered index cannot be
+    /// mapped to a file.
+    pub fn resolve_migrated_files(
+        &self,
+        ledger: &crate::ledger::FileLedger,
+        receipt: &crate::ledger::MigrationReceipt,
+    ) -> crate::Result<Vec<String>> {
+        use crate::ledger::IndexPolicy;
+        use crate::KontorPoRError;
+
+        if self.challenge_ids.is_empty()
+            || !self.binding().is_ledger()
+            || !receipt.covers_root(self.ledger_root)
+        {
+            return self.resolve_files(ledger);
+        }
+
+        let proof_root = || format!("{:?}", self.ledger_root);
+        if !ledger.is_valid_root(self.ledger_root) {
+            return Err(KontorPoRError::InvalidLedgerRoot {
+                proof_root: proof_root(),
+                reason: "Proof's ledger_root is not in the set of valid historical roots"
+                    .to_string(),
+            });
+        }
+        let stable = crate::merkle::canonical::field_from_bytes(receipt.old_root)
+            == Some(self.ledger_root)
+            || receipt.old_index_policy == IndexPolicy::InsertionOrder;
+
+        self.covered_indices()
+            .iter()
+            .enumerate()
+            .map(|(slot, &index)| {
+                let depth = self.file_depths.get(slot).copied();
+                receipt
+                    .files
+                    .get(&index)
+                    .map(|file| &file.file_id)
+                    .filter(|file_id| {
+                        stable && ledger.entry(file_id).map(|entry| entry.depth) == depth
+                    })
+                    .cloned()
+                    .ok_or_else(|| KontorPoRError::CannotResolveHistorical {
+                        proof_root: proof_root(),
+                        index,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns true if both proofs make the same public statement.
+    ///
+    /// Compares the challenges covered, ledger root, indices and file depths, circuit
+    /// shape, nonce commitment, slots, hash profile, schema version and the circuit's
+    /// public inputs and outputs. The
+    /// compressed SNARKs are not compared: proving is randomized, so two valid proofs of
+    /// one statement generally differ in their SNARK bytes.
+    pub fn same_statement(&self, other: &Proof) -> bool {
+        self.challenge_ids == other.challenge_ids
+            && self.ledger_root == other.ledger_root
+            && self.ledger_indices == other.ledger_indices
+            && self.file_depths == other.file_depths
+            && self.shape == other.shape
+            && self.challenge_nonce_commitment == other.challenge_nonce_commitment
+            && self.challenge_slots == other.challenge_slots
+            && self.hash_profile == other.hash_profile
+            && self.schema_version == other.schema_version
+            && self.public_inputs == other.public_inputs
+            && self.public_outputs == other.public_outputs
+    }
+
+    /// Approximate heap memory held by this proof in bytes.
+    ///
+    /// The compressed SNARK's internal buffers cannot be walked from outside nova, so they
+    /// count as their bincode-encoded size, as do the embedded challenges; the challenge
+    /// IDs, indices, slots and public inputs and outputs count their allocated capacity.
+    pub fn heap_size(&self) -> usize {
+        use std::mem::size_of;
+
+        let snark = bincode::serialized_size(&self.compressed_snark).unwrap_or(0) as usize;
+        let embedded = bincode::serialized_size(&self.embedded_challenges).unwrap_or(0) as usize;
+        snark
+            + embedded
+            + self.challenge_ids.capacity() * size_of::<ChallengeID>()
+            + (self.ledger_indices.capacity()
+                + self.file_depths.capacity()
+                + self.challenge_slots.capacity())
+                * size_of::<usize>()
+            + (self.public_inputs.capacity() + self.public_outputs.capacity())
+                * size_of::<FieldElement>()
+    }
+
+    /// Fails with [`crate::KontorPoRError::SchemaVersionMismatch`] unless the proof was
+    /// generated under [`crate::config::PUBLIC_IO_SCHEMA_VERSION`].
+    pub fn check_schema_version(&self) -> crate::Result<()> {
+        if self.schema_version != crate::config::PUBLIC_IO_SCHEMA_VERSION {
+            return Err(crate::KontorPoRError::SchemaVersionMismatch {
+                expected: crate::config::PUBLIC_IO_SCHEMA_VERSION,
+                found: self.schema_version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Size of each part of [`Self::to_bytes`]'s encoding of this proof.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::KontorPoRError::Serialization`] if the proof cannot be serialized.
+    pub fn size_breakdown(&self) -> crate::Result<ProofSizeBreakdown> {
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .with_little_endian();
+        let sizing_failed = |e: bincode::Error| {
+            crate::KontorPoRError::Serialization(format!("Failed to size proof: {}", e))
+        };
+        let total = self.to_bytes()?.len();
+        let snark = options
+            .serialized_size(&self.compressed_snark)
+            .map_err(sizing_failed)? as usize;
+        let embedded_challenges = options
+            .serialized_size(&self.embedded_challenges)
+            .map_err(sizing_failed)? as usize;
+        Ok(ProofSizeBreakdown {
+            snark,
+            embedded_challenges,
+            statement: total - snark - embedded_challenges,
+            total,
+        })
+    }
+
     /// Serialize this proof to bytes for network transport.
     ///
     /// The format includes a magic number, version, and the proof data.
@@ -171,9 +548,9 @@ impl Proof {
             .with_fixint_encoding()
             .with_little_endian()
             .reject_trailing_bytes();
-        let proof = options.deserialize(proof_bytes).map_err(|e| {
-            KontorPoRError::Serialization(format!("Failed to deserialize proof: {}", e))
-        })?;
+        let proof = options
+            .deserialize(proof_bytes)
+            .map_err(|e| crate::merkle::canonical::deserialize_error(e, "proof"))?;
 
         Ok(proof)
     }
@@ -198,6 +575,11 @@ pub struct PorParams {
     pub max_supported_depth: usize,
     /// Declared aggregated tree depth (0 for single-file params)
     pub aggregated_tree_depth: usize,
+    /// How challenged leaves are exposed by circuits using these params
+    pub challenge_mode: ChallengeMode,
+    /// Public input/output layout of the circuits these params were generated for
+    /// ([`crate::config::PUBLIC_IO_SCHEMA_VERSION`] at generation time)
+    pub schema_version: u32,
 }
 
 impl Clone for PorParams {
@@ -208,23 +590,161 @@ impl Clone for PorParams {
             file_tree_depth: self.file_tree_depth,
             max_supported_depth: self.max_supported_depth,
             aggregated_tree_depth: self.aggregated_tree_depth,
+            challenge_mode: self.challenge_mode,
+            schema_version: self.schema_version,
         }
     }
 }
 
 /// The public commitment to a file, which is shared with verifiers.
+///
+/// Only `root`, `padded_len`, `tree_arity` and `hash_profile` enter the file's root commitment
+/// ([`Self::commitment`]); `file_id`, `padded_len` and `original_size` also enter challenge
+/// IDs. Operator-only details such as the filename live in `annotations`, which no
+/// commitment reads; [`Self::redact`] drops them before metadata is shared with verifiers.
+///
+/// Equality compares every field; hashing uses the canonical byte encoding of `root`, so
+/// equal metadata hashes equally however it was decoded.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileMetadata {
     /// The Merkle root over all symbols (data + parity)
+    #[serde(with = "crate::merkle::canonical::field")]
     pub root: FieldElement,
     /// SHA256 hash of the original file for identification
     pub file_id: String,
-    /// The total number of leaves in the Merkle tree (padded to power of 2)
+    /// The total number of leaves in the Merkle tree (padded to a power of the tree arity)
     pub padded_len: usize,
     /// Size of original file in bytes (for reconstruction)
     pub original_size: usize,
-    /// Filename for operator UX and integration
-    pub filename: String,
+    /// Operator-only annotations (e.g. the filename), excluded from every commitment
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    /// Branching factor of the Merkle tree (binary unless prepared with arity 4)
+    #[serde(default)]
+    pub tree_arity: TreeArity,
+    /// Erasure code applied before chunking, or `None` for files prepared raw
+    #[serde(default = "default_erasure")]
+    pub erasure: Option<ErasureCode>,
+    /// Commitment to the salt of a salted file ID (`None` when `file_id = SHA-256(data)`)
+    #[serde(default)]
+    pub salt_commitment: Option<[u8; 32]>,
+    /// Poseidon instantiation of the Merkle tree and root commitment (standard unless
+    /// prepared with [`crate::api::prepare_file_with_profile`])
+    #[serde(default)]
+    pub hash_profile: HashProfile,
+}
+
+impl std::hash::Hash for FileMetadata {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        crate::merkle::canonical::field_to_bytes(&self.root).hash(state);
+        self.file_id.hash(state);
+        self.padded_len.hash(state);
+        self.original_size.hash(state);
+        self.annotations.hash(state);
+        self.tree_arity.hash(state);
+        self.erasure.hash(state);
+        self.salt_commitment.hash(state);
+        self.hash_profile.hash(state);
+    }
+}
+
+/// Erasure code applied to a file's data before it is chunked into leaves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErasureCode {
+    /// Multi-codeword Reed-Solomon RS(255, 231) over 31-byte symbols.
+    #[default]
+    ReedSolomon,
+    /// Multi-codeword Reed-Solomon over GF(2^16): 924 data and 96 parity shards per
+    /// codeword, each shard a pair of 31-byte symbols (2040 symbols per codeword).
+    ReedSolomonGf16,
+}
+
+/// Galois field a Reed-Solomon code works over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErasureField {
+    /// GF(2^8): at most 255 shards per codeword
+    #[default]
+    Gf8,
+    /// GF(2^16): at most 65535 shards per codeword
+    Gf16,
+}
+
+impl std::fmt::Display for ErasureField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErasureField::Gf8 => write!(f, "GF(2^8)"),
+            ErasureField::Gf16 => write!(f, "GF(2^16)"),
+        }
+    }
+}
+
+impl ErasureCode {
+    /// The Reed-Solomon code over `field`.
+    pub const fn for_field(field: ErasureField) -> Self {
+        match field {
+            ErasureField::Gf8 => ErasureCode::ReedSolomon,
+            ErasureField::Gf16 => ErasureCode::ReedSolomonGf16,
+        }
+    }
+
+    /// Galois field of the code.
+    pub const fn field(&self) -> ErasureField {
+        match self {
+            ErasureCode::ReedSolomon => ErasureField::Gf8,
+            ErasureCode::ReedSolomonGf16 => ErasureField::Gf16,
+        }
+    }
+
+    /// Number of 31-byte symbols making up one shard of the code.
+    ///
+    /// GF(2^16) elements are two bytes wide, so a shard pairs two symbols; it is
+    /// missing if either of them is.
+    pub const fn symbols_per_shard(&self) -> usize {
+        match self {
+            ErasureCode::ReedSolomon => 1,
+            ErasureCode::ReedSolomonGf16 => crate::config::GF16_SYMBOLS_PER_SHARD,
+        }
+    }
+
+    /// Data symbols per codeword.
+    pub const fn data_symbols_per_codeword(&self) -> usize {
+        match self {
+            ErasureCode::ReedSolomon => crate::config::DATA_SYMBOLS_PER_CODEWORD,
+            ErasureCode::ReedSolomonGf16 => crate::config::GF16_DATA_SYMBOLS_PER_CODEWORD,
+        }
+    }
+
+    /// Parity symbols per codeword.
+    pub const fn parity_symbols_per_codeword(&self) -> usize {
+        match self {
+            ErasureCode::ReedSolomon => crate::config::PARITY_SYMBOLS_PER_CODEWORD,
+            ErasureCode::ReedSolomonGf16 => crate::config::GF16_PARITY_SYMBOLS_PER_CODEWORD,
+        }
+    }
+
+    /// Total symbols per codeword (data + parity).
+    pub const fn total_symbols_per_codeword(&self) -> usize {
+        self.data_symbols_per_codeword() + self.parity_symbols_per_codeword()
+    }
+}
+
+/// Metadata predating the `erasure` field always describes Reed-Solomon coded files.
+fn default_erasure() -> Option<ErasureCode> {
+    Some(ErasureCode::ReedSolomon)
+}
+
+/// One-line summary for logs: file ID, size, depth and root. Annotations are omitted.
+impl std::fmt::Display for FileMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file {} ({} bytes, depth {}, root {:?})",
+            self.file_id,
+            self.original_size,
+            self.depth(),
+            self.root
+        )
+    }
 }
 
 impl FileMetadata {
@@ -233,25 +753,185 @@ impl FileMetadata {
         self.original_size.div_ceil(crate::config::CHUNK_SIZE_BYTES)
     }
 
-    /// Number of RS codewords.
+    /// Number of RS codewords (zero for files prepared without erasure coding).
     pub fn num_codewords(&self) -> usize {
-        self.num_data_symbols()
-            .div_ceil(crate::config::DATA_SYMBOLS_PER_CODEWORD)
+        match self.erasure {
+            Some(code) => self
+                .num_data_symbols()
+                .div_ceil(code.data_symbols_per_codeword()),
+            None => 0,
+        }
     }
 
-    /// Total symbols including parity (num_codewords × 255).
+    /// Total symbols including parity (num_codewords × symbols per codeword, 255 for
+    /// [`ErasureCode::ReedSolomon`]), or just the data symbols for files prepared without
+    /// erasure coding.
+    ///
+    /// This cannot overflow: a codeword's symbols cover at least its data symbols × 31
+    /// bytes of `original_size`.
     pub fn total_symbols(&self) -> usize {
-        self.num_codewords() * crate::config::TOTAL_SYMBOLS_PER_CODEWORD
+        match self.erasure {
+            Some(code) => self.num_codewords() * code.total_symbols_per_codeword(),
+            None => self.num_data_symbols(),
+        }
+    }
+
+    /// Largest `padded_len` of a file of at most `MAX_FILE_SIZE` bytes with this
+    /// metadata's tree arity and erasure code.
+    pub fn max_padded_len(&self) -> usize {
+        let data_symbols =
+            crate::config::MAX_FILE_SIZE.div_ceil(crate::config::CHUNK_SIZE_BYTES as u64);
+        let total_symbols = match self.erasure {
+            Some(code) => {
+                data_symbols.div_ceil(code.data_symbols_per_codeword() as u64)
+                    * code.total_symbols_per_codeword() as u64
+            }
+            None => data_symbols,
+        };
+        usize::try_from(total_symbols)
+            .ok()
+            .and_then(|total| self.tree_arity.checked_padded_len(total))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Checks that the metadata is internally consistent: a non-empty `file_id`, a
+    /// `padded_len` that is a power of two (and of the tree arity), and an `original_size`
+    /// that fits in [`Self::total_symbols`] symbols.
+    ///
+    /// Depth is derived from `padded_len` with `trailing_zeros`, so a `padded_len` of 1000
+    /// would otherwise be read as depth 3. Ledger insertion, challenge validation and
+    /// planning (hence every prove and verify) run this check, failing with
+    /// [`crate::KontorPoRError::InvalidMetadata`].
+    pub fn validate(&self) -> crate::Result<()> {
+        let invalid = |field: &str, reason: String| crate::KontorPoRError::InvalidMetadata {
+            field: field.to_string(),
+            reason,
+        };
+        if self.file_id.is_empty() {
+            return Err(invalid("file_id", "must not be empty".to_string()));
+        }
+        if !self.padded_len.is_power_of_two()
+            || self.tree_arity.checked_padded_len(self.padded_len) != Some(self.padded_len)
+        {
+            return Err(invalid(
+                "padded_len",
+                format!(
+                    "{} of file {} is not a power of the tree arity",
+                    self.padded_len, self.file_id
+                ),
+            ));
+        }
+        let capacity = self
+            .total_symbols()
+            .checked_mul(crate::config::CHUNK_SIZE_BYTES);
+        if capacity.is_none_or(|capacity| self.original_size > capacity) {
+            return Err(invalid(
+                "original_size",
+                format!(
+                    "{} of file {} exceeds its {} symbols",
+                    self.original_size,
+                    self.file_id,
+                    self.total_symbols()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks `original_size` and `padded_len` against the limits `prepare_file` enforces.
+    ///
+    /// Functions that size buffers or loops from metadata call this first, so hostile
+    /// metadata is rejected with [`crate::KontorPoRError::FileTooLarge`] or
+    /// `InvalidInput` instead of overflowing or exhausting memory.
+    pub fn check_size_limits(&self) -> crate::Result<()> {
+        let size = self.original_size as u64;
+        if size > crate::config::MAX_FILE_SIZE {
+            return Err(crate::KontorPoRError::FileTooLarge {
+                size,
+                max: crate::config::MAX_FILE_SIZE,
+            });
+        }
+        let max_padded_len = self.max_padded_len();
+        if self.padded_len > max_padded_len {
+            return Err(crate::KontorPoRError::InvalidInput(format!(
+                "File {} has padded_len {}, exceeding the maximum {} for files of at most {} bytes",
+                self.file_id,
+                self.padded_len,
+                max_padded_len,
+                crate::config::MAX_FILE_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    /// Annotation key under which `prepare_file` records the filename.
+    pub const FILENAME_ANNOTATION: &'static str = "filename";
+
+    /// The filename annotation, or `""` if the metadata carries none (e.g. after [`Self::redact`]).
+    pub fn filename(&self) -> &str {
+        self.annotations
+            .get(Self::FILENAME_ANNOTATION)
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+
+    /// Returns a copy of this metadata under a different filename.
+    ///
+    /// The filename is an annotation, not part of the file ID, the root or the root
+    /// commitment, so the copy stays valid for every existing challenge, ledger entry and
+    /// proof.
+    pub fn with_filename(&self, new_name: &str) -> FileMetadata {
+        self.with_annotation(Self::FILENAME_ANNOTATION, new_name)
+    }
+
+    /// Returns a copy of this metadata with the annotation `key` set to `value`.
+    pub fn with_annotation(&self, key: &str, value: &str) -> FileMetadata {
+        let mut metadata = self.clone();
+        metadata
+            .annotations
+            .insert(key.to_string(), value.to_string());
+        metadata
+    }
+
+    /// Returns a verifier-safe copy with every annotation removed.
+    ///
+    /// Redacted metadata has the same commitment and produces the same challenge IDs, so
+    /// proofs made against the full metadata verify against the redacted copy.
+    pub fn redact(&self) -> FileMetadata {
+        FileMetadata {
+            annotations: BTreeMap::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Commitment recorded for a file prepared with `salt`: `SHA-256("file_id_salt" || salt)`.
+    pub fn salt_commitment_for(salt: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"file_id_salt");
+        hasher.update(salt);
+        hasher.finalize().into()
+    }
+
+    /// Whether `salt` is the salt this file's ID was derived from.
+    ///
+    /// Always false for unsalted files.
+    pub fn has_salt(&self, salt: &[u8; 32]) -> bool {
+        self.salt_commitment == Some(Self::salt_commitment_for(salt))
+    }
+
+    /// The file's root commitment rc, the value the ledger stores for it.
+    ///
+    /// Computed from `root`, the tree depth, `tree_arity` and `hash_profile` only;
+    /// annotations cannot affect it.
+    pub fn commitment(&self) -> FieldElement {
+        crate::ledger::compute_rc_with(self.root, self.depth(), self.tree_arity, self.hash_profile)
     }
 
     /// Computes the Merkle tree depth from padded_len.
-    /// Depth is log2(padded_len), assuming padded_len is a power of 2.
+    /// Depth is log2(padded_len) for binary trees and log4(padded_len) for arity-4 trees,
+    /// assuming padded_len is a power of the arity.
     pub fn depth(&self) -> usize {
-        if self.padded_len == 0 {
-            0
-        } else {
-            self.padded_len.trailing_zeros() as usize
-        }
+        self.tree_arity.depth_for_len(self.padded_len)
     }
 }
 
@@ -260,6 +940,10 @@ impl crate::ledger::FileDescriptor for FileMetadata {
         &self.file_id
     }
 
+    fn filename(&self) -> &str {
+        FileMetadata::filename(self)
+    }
+
     fn root(&self) -> FieldElement {
         self.root
     }
@@ -267,21 +951,508 @@ impl crate::ledger::FileDescriptor for FileMetadata {
     fn depth(&self) -> usize {
         self.depth() // Delegates to FileMetadata::depth()
     }
+
+    fn tree_arity(&self) -> TreeArity {
+        self.tree_arity
+    }
+
+    fn hash_profile(&self) -> HashProfile {
+        self.hash_profile
+    }
+
+    fn validate(&self) -> crate::Result<()> {
+        FileMetadata::validate(self)
+    }
 }
 
 /// The prover's representation of a file, containing the full Merkle tree.
-#[derive(Debug, Clone)]
+///
+/// Serializable so provers can persist prepared trees; field elements use the canonical
+/// encoding from [`crate::merkle::canonical`].
+///
+/// `Debug` prints only the file ID, root and tree sizes, never the leaves, so prepared
+/// files can be logged safely.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PreparedFile {
     /// The full Merkle tree structure held by the prover
     pub(crate) tree: crate::merkle::MerkleTree,
     /// SHA256 hash of the original file for identification
     pub file_id: String,
     /// The Merkle root for quick access
+    #[serde(with = "crate::merkle::canonical::field")]
     pub root: FieldElement,
+    /// Branching factor of `tree`
+    pub(crate) tree_arity: TreeArity,
+    /// Poseidon instantiation `tree` was hashed with
+    #[serde(default)]
+    pub(crate) hash_profile: HashProfile,
+}
+
+impl PreparedFile {
+    /// Drops interior tree layers to reduce the memory held for proving.
+    ///
+    /// Keeps the leaves, the root and the `keep_top_levels` layers below the root; siblings
+    /// from dropped layers are recomputed from the leaves when a proof needs them, so
+    /// proofs are unchanged. Lower values save more memory (up to about half of a binary
+    /// tree's nodes) but make every challenged leaf cost more hashes: a dropped layer `m`
+    /// levels above the leaves costs up to `2^m` hashes per sibling (`4^m` for arity-4
+    /// trees). See [`crate::merkle::MerkleTree::prune`].
+    pub fn prune(&mut self, keep_top_levels: usize) {
+        self.tree.prune(keep_top_levels);
+    }
+
+    /// Zeroes and drops the tree, for disposing of a file whose contents must not outlive
+    /// it in memory.
+    ///
+    /// The tree's leaves are the file's 31-byte symbols, so dropping a `PreparedFile`
+    /// without calling this leaves the data in freed memory. The file ID and root are
+    /// public and kept. A zeroized file can no longer prove: proving with it fails with
+    /// [`crate::KontorPoRError::PreparedFileZeroized`].
+    #[cfg(feature = "zeroize")]
+    pub fn zeroize(&mut self) {
+        for layer in &mut self.tree.layers {
+            crate::utils::scrub_field_elements(layer);
+        }
+        self.tree.layers = Vec::new();
+    }
+
+    /// Whether [`PreparedFile::zeroize`] has disposed of this file's tree.
+    pub fn is_zeroized(&self) -> bool {
+        self.tree.layers.is_empty()
+    }
+
+    /// Number of tree nodes (32 bytes each) held in memory.
+    pub fn stored_nodes(&self) -> usize {
+        self.tree.stored_nodes()
+    }
+
+    /// Heap memory held by this file in bytes: its tree ([`crate::merkle::MerkleTree::heap_size`])
+    /// and file ID. Prune the file to shrink it.
+    pub fn heap_size(&self) -> usize {
+        self.tree.heap_size() + self.file_id.capacity()
+    }
+
+    /// Whether this file holds the content `metadata` describes: the same root, tree
+    /// arity and hash profile. The file IDs may differ, e.g. for the same data prepared
+    /// under two salts.
+    #[cfg(feature = "prover")]
+    pub(crate) fn has_content_of(&self, metadata: &FileMetadata) -> bool {
+        self.tree.root() == metadata.root
+            && self.tree_arity == metadata.tree_arity
+            && self.hash_profile == metadata.hash_profile
+    }
+
+    /// Checks that the file's leaves encode 31-byte symbols, failing with
+    /// [`crate::KontorPoRError::InvalidLeafEncoding`] at the first leaf that does not.
+    ///
+    /// A leaf wider than 31 bytes cannot come from file preparation; it means the tree was
+    /// tampered with or corrupted, and a proof over it would not verify. See
+    /// [`LeafValidation`] for how many leaves are checked.
+    pub fn validate(&self, mode: LeafValidation) -> crate::Result<()> {
+        self.validate_with_seed(mode, None)
+    }
+
+    /// Like [`Self::validate`], drawing a sampled check's leaves from an RNG seeded with
+    /// `rng_seed`, so the same seed checks the same leaves. With `None` the RNG is seeded
+    /// from the file root, so repeated checks of one file agree.
+    pub fn validate_with_seed(
+        &self,
+        mode: LeafValidation,
+        rng_seed: Option<u64>,
+    ) -> crate::Result<()> {
+        use ff::PrimeField;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = match rng_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_seed(self.root.to_repr().into()),
+        };
+        let leaves = self.tree.layers.first().map_or(&[][..], Vec::as_slice);
+        mode.try_for_each_index(leaves.len(), &mut rng, |index| {
+            if crate::utils::is_bytes31_leaf(&leaves[index]) {
+                Ok(())
+            } else {
+                Err(crate::KontorPoRError::InvalidLeafEncoding { index })
+            }
+        })
+    }
+
+    /// Debug output including the full Merkle tree, for tests only.
+    #[doc(hidden)]
+    pub fn debug_full(&self) -> String {
+        format!(
+            "PreparedFile {{ file_id: {:?}, root: {:?}, tree_arity: {:?}, hash_profile: {:?}, tree: {} }}",
+            self.file_id,
+            self.root,
+            self.tree_arity,
+            self.hash_profile,
+            self.tree.debug_full()
+        )
+    }
+}
+
+impl std::fmt::Debug for PreparedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedFile")
+            .field("file_id", &self.file_id)
+            .field("root", &self.root)
+            .field("tree_arity", &self.tree_arity)
+            .field("hash_profile", &self.hash_profile)
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+/// How many leaves [`PreparedFile::validate`] checks, and how many precomputed leaf hashes
+/// [`crate::api::prepare_file_with_leaf_hashes_validated`] checks against their symbols.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeafValidation {
+    /// Check every leaf of files with at most
+    /// [`crate::config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD`] leaves, and
+    /// [`crate::config::LEAF_VALIDATION_SAMPLES`] randomly chosen leaves of larger files.
+    /// Proving draws the sample from [`ProveOptions::rng_seed`] or the file root, so the
+    /// same file and options always check the same leaves.
+    #[default]
+    Sampled,
+    /// Check every leaf
+    Exhaustive,
+}
+
+impl LeafValidation {
+    /// Runs `check` on the indices of `len` leaves this mode selects, in ascending order,
+    /// stopping at the first error. A sample is drawn from `rng`.
+    pub(crate) fn try_for_each_index(
+        self,
+        len: usize,
+        rng: &mut impl rand::Rng,
+        check: impl FnMut(usize) -> crate::Result<()>,
+    ) -> crate::Result<()> {
+        if self == LeafValidation::Exhaustive
+            || len <= crate::config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD
+        {
+            return (0..len).try_for_each(check);
+        }
+        let mut indices =
+            rand::seq::index::sample(rng, len, crate::config::LEAF_VALIDATION_SAMPLES).into_vec();
+        indices.sort_unstable();
+        indices.into_iter().try_for_each(check)
+    }
+}
+
+/// Options for [`crate::api::PorSystem::prove_with_options`].
+///
+/// The default matches [`crate::api::PorSystem::prove`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProveOptions {
+    /// How many leaves of each challenged file are validated before proving. The
+    /// challenged leaves themselves are always checked during witness generation.
+    pub leaf_validation: LeafValidation,
+    /// Prove every occurrence of challenges with identical IDs in its own slot. By default
+    /// they are collapsed into one slot, which the proof's `challenge_slots` record.
+    pub allow_duplicates: bool,
+    /// Before each step is folded, synthesize it into a test constraint system and fail
+    /// with [`crate::KontorPoRError::ConstraintPrecheckFailed`] if the witness breaks a
+    /// constraint. This roughly doubles the cost of a step, so it is meant for staging.
+    pub precheck_constraints: bool,
+    /// Store the challenges in the proof's `embedded_challenges`, so it can be verified
+    /// with [`crate::api::PorSystem::verify_self_contained`] once the original challenges
+    /// are gone. Each challenge adds its metadata to the proof's size.
+    pub embed_challenges: bool,
+    /// Seed for the randomness drawn while proving: the leaves a
+    /// [`LeafValidation::Sampled`] check picks. With `None` each file's sample is seeded
+    /// from its root.
+    pub rng_seed: Option<u64>,
+}
+
+impl ProveOptions {
+    /// Sets how many leaves of each challenged file are validated before proving.
+    pub fn with_leaf_validation(mut self, leaf_validation: LeafValidation) -> Self {
+        self.leaf_validation = leaf_validation;
+        self
+    }
+
+    /// Sets whether challenges with identical IDs are proven once per occurrence.
+    pub fn with_allow_duplicates(mut self, allow_duplicates: bool) -> Self {
+        self.allow_duplicates = allow_duplicates;
+        self
+    }
+
+    /// Sets whether every step is checked for unsatisfied constraints before folding.
+    pub fn with_precheck_constraints(mut self, precheck_constraints: bool) -> Self {
+        self.precheck_constraints = precheck_constraints;
+        self
+    }
+
+    /// Sets whether the challenges are embedded in the proof.
+    pub fn with_embed_challenges(mut self, embed_challenges: bool) -> Self {
+        self.embed_challenges = embed_challenges;
+        self
+    }
+
+    /// Sets the seed for the randomness drawn while proving.
+    pub fn with_rng_seed(mut self, rng_seed: u64) -> Self {
+        self.rng_seed = Some(rng_seed);
+        self
+    }
+}
+
+/// Resource limits for [`crate::api::PorSystem::verify_with_options`].
+///
+/// The default sets no limits, matching [`crate::api::PorSystem::verify`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Reject challenge sets implying more recursive steps than this before any SNARK work
+    pub max_steps: Option<usize>,
+    /// Give up once verification has run this long. Elapsed time is checked between
+    /// phases; a SNARK verification already in progress is not interrupted.
+    pub timeout: Option<Duration>,
+    /// Reject challenges whose block height is after this one
+    pub current_block_height: Option<u64>,
+    /// Leaf outputs the proof must carry, keyed by file ID and step. Only the last step's
+    /// leaf output is public, so every step must be `num_challenges - 1`. The value is the
+    /// challenged leaf, or its commitment for private challenges.
+    pub expected_leaves: Option<BTreeMap<(String, usize), FieldElement>>,
+}
+
+impl VerifyOptions {
+    /// Sets the maximum number of recursive steps a proof may claim.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Sets the verification time budget.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the verifier's current block height.
+    pub fn with_current_block_height(mut self, height: u64) -> Self {
+        self.current_block_height = Some(height);
+        self
+    }
+
+    /// Sets the leaf outputs the proof must carry, keyed by file ID and step.
+    pub fn with_expected_leaves(
+        mut self,
+        expected_leaves: BTreeMap<(String, usize), FieldElement>,
+    ) -> Self {
+        self.expected_leaves = Some(expected_leaves);
+        self
+    }
+
+    /// Checks the step count implied by `challenges` against [`Self::max_steps`].
+    pub(crate) fn check_steps(&self, challenges: &[Challenge]) -> crate::Result<()> {
+        let (_, num_passes) = crate::config::derive_partition(
+            challenges.len(),
+            challenges.first().and_then(|c| c.max_files_per_step),
+        );
+        let steps = challenges
+            .iter()
+            .map(|c| c.num_challenges)
+            .max()
+            .unwrap_or(0)
+            .saturating_mul(num_passes);
+        match self.max_steps {
+            Some(max) if steps > max => Err(crate::KontorPoRError::TooManySteps { steps, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the challenges' block heights against [`Self::current_block_height`] and,
+    /// for multi-file proofs against a ledger that tracks roots by height, that the proof's
+    /// ledger root was the root in effect at each challenge's height.
+    pub(crate) fn check_heights(
+        &self,
+        challenges: &[Challenge],
+        proof: &Proof,
+        ledger: &crate::ledger::FileLedger,
+    ) -> crate::Result<()> {
+        if let Some(current) = self.current_block_height {
+            if let Some(challenge) = challenges.iter().find(|c| c.block_height > current) {
+                return Err(crate::KontorPoRError::ChallengeFromFuture {
+                    height: challenge.block_height,
+                    current,
+                });
+            }
+        }
+
+        if proof.shape.aggregated_tree_depth == 0 || !ledger.tracks_root_heights() {
+            return Ok(());
+        }
+        for challenge in challenges {
+            if ledger.root_at_height(challenge.block_height) != Some(proof.ledger_root) {
+                return Err(crate::KontorPoRError::RootNotValidAtHeight {
+                    proof_root: format!("{:?}", proof.ledger_root),
+                    height: challenge.block_height,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the proof's final leaf outputs against [`Self::expected_leaves`]. Must only be
+    /// called once the SNARK has produced `proof.public_outputs` for `challenges`.
+    pub(crate) fn check_leaves(
+        &self,
+        challenges: &[Challenge],
+        proof: &Proof,
+    ) -> crate::Result<()> {
+        let Some(expected_leaves) = &self.expected_leaves else {
+            return Ok(());
+        };
+        // The last step answers the last pass of a partitioned batch, so only the files in
+        // its slots have public leaves
+        let layout = proof.public_io_layout();
+        let last_pass_start = proof
+            .ledger_indices
+            .len()
+            .saturating_sub(layout.files_per_step);
+        for ((file_id, step), expected) in expected_leaves {
+            let position = challenges
+                .iter()
+                .position(|c| c.file_metadata.file_id == *file_id)
+                .ok_or_else(|| {
+                    crate::KontorPoRError::InvalidInput(format!(
+                        "Expected leaf for file {} which is not challenged",
+                        file_id
+                    ))
+                })?;
+            let last_step = challenges[position].num_challenges.saturating_sub(1);
+            if *step != last_step {
+                return Err(crate::KontorPoRError::InvalidInput(format!(
+                    "Expected leaf for file {} at step {}, but only the last step ({}) is public",
+                    file_id, step, last_step
+                )));
+            }
+            let slot = proof.challenge_slots.get(position).copied();
+            if slot.is_some_and(|slot| slot < last_pass_start) {
+                return Err(crate::KontorPoRError::InvalidInput(format!(
+                    "Expected leaf for file {}, but only the last pass's leaves are public",
+                    file_id
+                )));
+            }
+            let output = slot.and_then(|slot| {
+                proof
+                    .public_outputs
+                    .get(layout.idx_leaf(slot - last_pass_start))
+            });
+            if output != Some(expected) {
+                return Err(crate::KontorPoRError::LeafValueMismatch {
+                    file_id: file_id.clone(),
+                    step: *step,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails with [`crate::KontorPoRError::VerificationTimedOut`] if verification started at
+    /// `start` has exceeded [`Self::timeout`].
+    pub(crate) fn check_deadline(&self, start: Instant) -> crate::Result<()> {
+        let elapsed = start.elapsed();
+        match self.timeout {
+            Some(timeout) if elapsed > timeout => {
+                Err(crate::KontorPoRError::VerificationTimedOut { elapsed, timeout })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Controls how challenged leaf values appear in a proof's public outputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChallengeMode {
+    /// Each challenged leaf (31 bytes of file content) is exposed as a raw public output.
+    #[default]
+    Public,
+    /// Each challenged leaf is replaced by the commitment `H(TAG_LEAF_COMMITMENT, leaf, blinding)`,
+    /// so file content never appears in the proof. The blinding comes from the leaf's first
+    /// Merkle sibling, so files with a single leaf cannot be challenged in this mode.
+    Private,
+}
+
+impl ChallengeMode {
+    /// Returns true if challenged leaves are hidden behind commitments.
+    pub fn is_private(&self) -> bool {
+        matches!(self, ChallengeMode::Private)
+    }
+}
+
+/// The root a proof's circuit binds its challenged files to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProofBinding {
+    /// The challenged file's own Merkle root (single-challenge proofs only). The proof
+    /// stays valid for as long as the file does, regardless of the ledger.
+    FileRoot,
+    /// The ledger root: each file's rc is opened at its ledger index, so the proof only
+    /// verifies while its root is current or retained as historical.
+    LedgerRoot,
+}
+
+impl ProofBinding {
+    /// The binding a batch of challenges is proven under: the one the challenges request
+    /// ([`Challenge::with_binding`]), otherwise the file root for a single challenge and
+    /// the ledger root for several.
+    pub fn for_challenges(challenges: &[Challenge]) -> ProofBinding {
+        match challenges.first().and_then(|c| c.binding) {
+            Some(binding) => binding,
+            None if challenges.len() > 1 => ProofBinding::LedgerRoot,
+            None => ProofBinding::FileRoot,
+        }
+    }
+
+    /// Returns true if proofs are bound to the ledger root.
+    pub fn is_ledger(&self) -> bool {
+        matches!(self, ProofBinding::LedgerRoot)
+    }
+}
+
+/// How the seed used to derive each step's challenge index is chosen.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedSchedule {
+    /// Every step uses the challenge's `seed`; indices are chained through the state hash.
+    #[default]
+    Fixed,
+    /// Step `k` uses the `k`-th seed (e.g. successive block hashes). The schedule length
+    /// must equal `num_challenges`, and the challenge's `seed` only initialises the
+    /// in-circuit seed accumulator.
+    PerStep(Vec<FieldElement>),
+}
+
+impl SeedSchedule {
+    /// Returns true if an explicit seed is supplied for every step.
+    pub fn is_per_step(&self) -> bool {
+        matches!(self, SeedSchedule::PerStep(_))
+    }
+}
+
+/// How the leaf opened at each step of a challenge is chosen.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeKind {
+    /// Leaf indices are derived from the seed (see [`SeedSchedule`]) and the chain state.
+    #[default]
+    Seeded,
+    /// Step `k` opens leaf `indices[k]`, e.g. a sector an auditor suspects is missing.
+    /// The list length must equal `num_challenges` and every index must be below the
+    /// file's `padded_len`. The challenge's `seed` only initialises the in-circuit index
+    /// accumulator, and explicit indices cannot be combined with a per-step seed schedule.
+    ExplicitIndices(Vec<usize>),
+}
+
+impl ChallengeKind {
+    /// Returns true if leaf indices are given explicitly rather than derived.
+    pub fn is_explicit(&self) -> bool {
+        matches!(self, ChallengeKind::ExplicitIndices(_))
+    }
 }
 
 /// Encapsulates a verifier's challenge request for a specific file.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Equality compares every field. Hashing uses the challenge ID ([`Self::id`]), which
+/// equal challenges share, so challenges can be deduplicated in hash sets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Challenge {
     /// The public metadata of the file being challenged.
     pub file_metadata: FileMetadata,
@@ -290,9 +1461,61 @@ pub struct Challenge {
     /// The number of proof iterations requested.
     pub num_challenges: usize,
     /// A deterministic seed used to generate challenges.
+    #[serde(with = "crate::merkle::canonical::field")]
     pub seed: FieldElement,
     /// Identifier of the Storage Node being challenged
     pub prover_id: String,
+    /// How challenged leaves are exposed in the proof (all challenges in a batch must match).
+    #[serde(default)]
+    pub mode: ChallengeMode,
+    /// Per-step seed schedule (all challenges in a batch must use the same kind).
+    #[serde(default)]
+    pub seed_schedule: SeedSchedule,
+    /// Replay-protection nonce mixed into the initial chain state (0 = none).
+    #[serde(default)]
+    pub nonce: u64,
+    /// Ledger the file is registered in, for systems holding several ledgers (all
+    /// challenges in a batch must match).
+    #[serde(default)]
+    pub ledger_id: Option<String>,
+    /// Ledger index checks the proof's circuit enforces (all challenges in a batch must match).
+    #[serde(default)]
+    pub circuit_mode: CircuitMode,
+    /// Seed-derived or explicit leaf indices (all challenges in a batch must use the same kind).
+    #[serde(default)]
+    pub kind: ChallengeKind,
+    /// Aggregated depth multi-file proofs are synthesized at, independent of the ledger's
+    /// depth (`None` = the ledger's depth; all challenges in a batch must match).
+    #[serde(default)]
+    pub max_agg_depth: Option<usize>,
+    /// Most file slots a step circuit may have; larger batches are proven in several passes
+    /// over the slots (`None` = one slot per file; all challenges in a batch must match).
+    #[serde(default)]
+    pub max_files_per_step: Option<usize>,
+    /// Root the proof binds to (`None` = chosen by [`ProofBinding::for_challenges`]; all
+    /// challenges in a batch must match).
+    #[serde(default)]
+    pub binding: Option<ProofBinding>,
+}
+
+impl std::hash::Hash for Challenge {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+/// One-line summary for logs: challenge ID, file, block height and step count.
+impl std::fmt::Display for Challenge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "challenge {} for file {} at height {} ({} steps)",
+            self.id(),
+            self.file_metadata.file_id,
+            self.block_height,
+            self.num_challenges
+        )
+    }
 }
 
 impl Challenge {
@@ -310,6 +1533,227 @@ impl Challenge {
             num_challenges,
             seed,
             prover_id,
+            mode: ChallengeMode::Public,
+            seed_schedule: SeedSchedule::Fixed,
+            nonce: 0,
+            ledger_id: None,
+            circuit_mode: CircuitMode::Lenient,
+            kind: ChallengeKind::Seeded,
+            max_agg_depth: None,
+            max_files_per_step: None,
+            binding: None,
+        }
+    }
+
+    /// Derives a challenge seed from a block hash.
+    ///
+    /// The seed is the 64-byte SHA-512 digest of a domain separator and `block_hash`,
+    /// reduced into the field, so every verifier derives the same seed from the same block
+    /// and the seed is close to uniform whatever the hash's byte layout.
+    pub fn seed_from_block_hash(block_hash: &[u8; 32]) -> FieldElement {
+        let mut hasher = Sha512::new();
+        hasher.update(b"kontor-por-challenge-seed");
+        hasher.update(block_hash);
+        let digest: [u8; 64] = hasher.finalize().into();
+        FieldElement::from_uniform_bytes(&digest)
+    }
+
+    /// Returns this challenge with the given replay-protection nonce.
+    ///
+    /// The nonces of a batch are folded into the proof's initial chain state, so proofs
+    /// for different nonces differ and do not cross-verify; see
+    /// [`Proof::challenge_nonce_commitment`].
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Returns this challenge addressed to the named ledger of a multi-ledger
+    /// [`super::PorSystem`]. The ledger ID is bound into [`Self::id`], so a proof for one
+    /// ledger does not verify under another.
+    pub fn with_ledger_id(mut self, ledger_id: impl Into<String>) -> Self {
+        self.ledger_id = Some(ledger_id.into());
+        self
+    }
+
+    /// Returns this challenge with the given leaf exposure mode.
+    pub fn with_mode(mut self, mode: ChallengeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns this challenge with the given circuit mode.
+    ///
+    /// [`CircuitMode::Strict`] proofs check ledger index ranges and distinctness inside the
+    /// SNARK; a strict batch must challenge distinct files.
+    pub fn with_circuit_mode(mut self, circuit_mode: CircuitMode) -> Self {
+        self.circuit_mode = circuit_mode;
+        self
+    }
+
+    /// Returns this challenge with multi-file proofs padded to the given aggregated depth.
+    ///
+    /// The aggregation path is gated per level, so one set of parameters for
+    /// `max_agg_depth` serves every ledger of depth up to it (at most
+    /// [`config::MAX_AGG_DEPTH`](crate::config::MAX_AGG_DEPTH)) and does not need
+    /// regenerating as the ledger grows. Each padding level costs about one Poseidon hash
+    /// per slot; single-file proofs are unaffected.
+    pub fn with_max_agg_depth(mut self, max_agg_depth: usize) -> Self {
+        self.max_agg_depth = Some(max_agg_depth);
+        self
+    }
+
+    /// Returns this challenge with step circuits capped at `max_files_per_step` slots (a
+    /// power of two).
+    ///
+    /// A batch of more files is partitioned in canonical order into passes of that many
+    /// slots, and each step of the proof answers one pass, so the proof takes
+    /// `num_challenges` steps per pass while the circuit (and its parameters) stays the
+    /// size of one pass. Partitioned batches must use fixed seeds.
+    pub fn with_max_files_per_step(mut self, max_files_per_step: usize) -> Self {
+        self.max_files_per_step = Some(max_files_per_step);
+        self
+    }
+
+    /// Returns this challenge proven under the given binding.
+    ///
+    /// [`ProofBinding::LedgerRoot`] makes even a single challenge, or several challenges
+    /// against a ledger holding one file, open the file's rc in the ledger tree, so
+    /// verifiers can tell the ledger state the proof was made against.
+    /// [`ProofBinding::FileRoot`] is only valid for a single challenge.
+    pub fn with_binding(mut self, binding: ProofBinding) -> Self {
+        self.binding = Some(binding);
+        self
+    }
+
+    /// Returns this challenge with enough steps to detect the loss of `loss_fraction` of
+    /// the file's data with probability at least `confidence`.
+    ///
+    /// The step count comes from [`super::sampling::required_challenges_for_file`], which
+    /// accounts for the file's erasure code and tree padding. Counts above
+    /// [`config::MAX_NUM_CHALLENGES`](crate::config::MAX_NUM_CHALLENGES) are rejected when
+    /// planning a proof, so very small losses may need a larger tolerance. Use
+    /// [`super::sampling::DEFAULT_LOSS_FRACTION`] and
+    /// [`super::sampling::DEFAULT_CONFIDENCE`] for the crate's defaults.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `loss_fraction` or `confidence` is not in `[0, 1]`.
+    pub fn with_detection_target(mut self, loss_fraction: f64, confidence: f64) -> Self {
+        self.num_challenges = super::sampling::required_challenges_for_file(
+            &self.file_metadata,
+            loss_fraction,
+            confidence,
+        );
+        self
+    }
+
+    /// Returns this challenge with the given seed schedule.
+    pub fn with_seed_schedule(mut self, seed_schedule: SeedSchedule) -> Self {
+        self.seed_schedule = seed_schedule;
+        self
+    }
+
+    /// Returns this challenge with the given kind.
+    pub fn with_kind(mut self, kind: ChallengeKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// The explicitly requested leaf index at `step`, or `None` for seeded challenges
+    /// (and for steps beyond the index list, which planning rejects).
+    pub fn explicit_index(&self, step: usize) -> Option<usize> {
+        match &self.kind {
+            ChallengeKind::Seeded => None,
+            ChallengeKind::ExplicitIndices(indices) => indices.get(step).copied(),
+        }
+    }
+
+    /// Checks everything about this challenge that does not depend on a ledger or on the
+    /// rest of its batch, failing with the error planning would report.
+    ///
+    /// Rejects a step count of zero or above the maximum
+    /// ([`crate::KontorPoRError::InvalidChallengeCount`]), metadata failing
+    /// [`FileMetadata::check_size_limits`] or [`FileMetadata::validate`], an out-of-range `max_agg_depth`, a `max_files_per_step` that is not
+    /// a power of two up to [`config::PRACTICAL_MAX_FILES`](crate::config::PRACTICAL_MAX_FILES),
+    /// seed schedules and explicit
+    /// indices of the wrong length, explicit indices beyond the file, and explicit indices
+    /// combined with a per-step schedule. Decoding with [`Self::from_json`] runs it.
+    pub fn validate(&self) -> crate::Result<()> {
+        use crate::{config, KontorPoRError};
+
+        if self.num_challenges == 0 || self.num_challenges > config::MAX_NUM_CHALLENGES {
+            return Err(KontorPoRError::InvalidChallengeCount {
+                count: self.num_challenges,
+            });
+        }
+
+        let metadata = &self.file_metadata;
+        metadata.check_size_limits()?;
+        metadata.validate()?;
+        let padded_len = metadata.padded_len;
+
+        if let Some(depth) = self
+            .max_agg_depth
+            .filter(|&d| d == 0 || d > config::MAX_AGG_DEPTH)
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "max_agg_depth {} must be between 1 and {}",
+                depth,
+                config::MAX_AGG_DEPTH
+            )));
+        }
+        if let Some(max) = self
+            .max_files_per_step
+            .filter(|&max| !max.is_power_of_two() || max > config::PRACTICAL_MAX_FILES)
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "max_files_per_step {} must be a power of two up to {}",
+                max,
+                config::PRACTICAL_MAX_FILES
+            )));
+        }
+
+        if let SeedSchedule::PerStep(seeds) = &self.seed_schedule {
+            if seeds.len() != self.num_challenges {
+                return Err(KontorPoRError::InvalidSeedSchedule {
+                    expected: self.num_challenges,
+                    got: seeds.len(),
+                });
+            }
+        }
+        if let ChallengeKind::ExplicitIndices(indices) = &self.kind {
+            if self.seed_schedule.is_per_step() {
+                return Err(KontorPoRError::InvalidInput(
+                    "Explicit challenge indices cannot be combined with a per-step seed schedule"
+                        .to_string(),
+                ));
+            }
+            if indices.len() != self.num_challenges {
+                return Err(KontorPoRError::InvalidExplicitIndices {
+                    expected: self.num_challenges,
+                    got: indices.len(),
+                });
+            }
+            if let Some(&index) = indices.iter().find(|&&index| index >= padded_len) {
+                return Err(KontorPoRError::ChallengeIndexOutOfRange {
+                    file_id: metadata.file_id.clone(),
+                    index,
+                    leaves: padded_len,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The seed used to derive the challenge index at `step`.
+    ///
+    /// Falls back to `seed` for fixed schedules (and for steps beyond a per-step
+    /// schedule, which planning rejects before proving or verifying).
+    pub fn seed_for_step(&self, step: usize) -> FieldElement {
+        match &self.seed_schedule {
+            SeedSchedule::Fixed => self.seed,
+            SeedSchedule::PerStep(seeds) => seeds.get(step).copied().unwrap_or(self.seed),
         }
     }
 
@@ -330,7 +1774,32 @@ impl Challenge {
         )
     }
 
-    /// Compute the deterministic ID for this challenge
+    /// Compute the deterministic ID for this challenge.
+    ///
+    /// The derivation is stable: IDs can be precomputed to index challenges and
+    /// correlated with [`Proof::challenge_ids`]. It is SHA-256 over, in order:
+    ///
+    /// 1. `TAG_CHALLENGE_ID` as a 32-byte little-endian field element
+    /// 2. `block_height` (u64 LE)
+    /// 3. `seed` (32-byte field element repr)
+    /// 4. `file_id` (UTF-8 bytes), `root` (field repr), `log2(padded_len)` (u64 LE)
+    /// 5. `b"tree_arity4"`, for arity-4 files only
+    /// 6. `b"nonce"` then `nonce` (u64 LE), for non-zero nonces only
+    /// 7. `num_challenges` (u64 LE), then `prover_id` (UTF-8 bytes)
+    /// 8. `ChallengeMode::Private as u8`, for private-mode challenges only
+    /// 9. `b"per_step_seeds"`, the schedule length (u64 LE) and each seed, for per-step
+    ///    schedules only
+    /// 10. `b"ledger_id"`, the ID length (u64 LE) and its UTF-8 bytes, for challenges
+    ///     addressed to a named ledger only
+    /// 11. `b"circuit_mode_strict"`, for strict-mode challenges only
+    /// 12. `b"explicit_indices"`, the index count (u64 LE) and each index (u64 LE), for
+    ///     explicit-index challenges only
+    /// 13. `b"hash_profile_conservative"`, for files hashed under
+    ///     [`HashProfile::Conservative`] only
+    /// 14. `b"binding"` then `0` (file root) or `1` (ledger root), for challenges that
+    ///     request a [`ProofBinding`] only
+    ///
+    /// The optional parts leave the IDs of challenges that do not use them unchanged.
     pub fn id(&self) -> ChallengeID {
         use crate::poseidon::domain_tags;
         use ff::PrimeField;
@@ -352,12 +1821,81 @@ impl Challenge {
         hasher.update(self.file_metadata.root.to_repr());
         hasher.update((self.file_metadata.padded_len.trailing_zeros() as u64).to_le_bytes()); // depth
 
+        // Bind arity-4 trees (binary-tree IDs are unchanged)
+        if self.file_metadata.tree_arity == TreeArity::Arity4 {
+            hasher.update(b"tree_arity4");
+        }
+
+        // Bind non-zero nonces (IDs of nonce-free challenges are unchanged)
+        if self.nonce != 0 {
+            hasher.update(b"nonce");
+            hasher.update(self.nonce.to_le_bytes());
+        }
+
         // Add num_challenges
         hasher.update((self.num_challenges as u64).to_le_bytes());
 
         // Add prover_id
         hasher.update(self.prover_id.as_bytes());
 
+        // Bind private mode (public-mode IDs are unchanged)
+        if self.mode.is_private() {
+            hasher.update([ChallengeMode::Private as u8]);
+        }
+
+        // Bind the per-step seed schedule, in order (fixed-schedule IDs are unchanged)
+        if let SeedSchedule::PerStep(seeds) = &self.seed_schedule {
+            hasher.update(b"per_step_seeds");
+            hasher.update((seeds.len() as u64).to_le_bytes());
+            for seed in seeds {
+                hasher.update(seed.to_repr());
+            }
+        }
+
+        // Bind the ledger name (IDs of unnamed-ledger challenges are unchanged)
+        if let Some(ledger_id) = &self.ledger_id {
+            hasher.update(b"ledger_id");
+            hasher.update((ledger_id.len() as u64).to_le_bytes());
+            hasher.update(ledger_id.as_bytes());
+        }
+
+        // Bind strict circuit mode (lenient-mode IDs are unchanged)
+        if self.circuit_mode.is_strict() {
+            hasher.update(b"circuit_mode_strict");
+        }
+
+        // Bind the padded aggregated depth (unpadded IDs are unchanged)
+        if let Some(max_agg_depth) = self.max_agg_depth {
+            hasher.update(b"max_agg_depth");
+            hasher.update((max_agg_depth as u64).to_le_bytes());
+        }
+
+        // Bind the slot cap (uncapped IDs are unchanged)
+        if let Some(max_files_per_step) = self.max_files_per_step {
+            hasher.update(b"max_files_per_step");
+            hasher.update((max_files_per_step as u64).to_le_bytes());
+        }
+
+        // Bind explicit indices, in order (seeded IDs are unchanged)
+        if let ChallengeKind::ExplicitIndices(indices) = &self.kind {
+            hasher.update(b"explicit_indices");
+            hasher.update((indices.len() as u64).to_le_bytes());
+            for index in indices {
+                hasher.update((*index as u64).to_le_bytes());
+            }
+        }
+
+        // Bind the conservative hash profile (standard-profile IDs are unchanged)
+        if self.file_metadata.hash_profile.is_conservative() {
+            hasher.update(b"hash_profile_conservative");
+        }
+
+        // Bind a requested binding (IDs of challenges using the default are unchanged)
+        if let Some(binding) = self.binding {
+            hasher.update(b"binding");
+            hasher.update([binding.is_ledger() as u8]);
+        }
+
         let result = hasher.finalize();
         ChallengeID(result.into())
     }
@@ -4,12 +4,22 @@
 //! secure root derivation from the ledger.
 
 use super::{
-    plan::Plan,
-    types::{Challenge, FieldElement, Proof},
+    plan::{collapse_duplicates, Plan},
+    slice::proof_digest,
+    types::{Challenge, FieldElement, Proof, VerifyOptions},
 };
-use crate::{config, ledger::FileLedger, KontorPoRError, Result};
-use ff::Field;
-use tracing::{debug, info_span};
+use crate::instrumentation::{debug, debug_span, field, info_span, Span};
+use crate::{
+    config::{self, PublicIOLayout},
+    ledger::FileLedger,
+    metrics::{record_timing, Phase},
+    params::Shape,
+    KontorPoRError, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::time::Instant;
 
 /// Verifies a proof against one or more challenges.
 ///
@@ -18,8 +28,9 @@ use tracing::{debug, info_span};
 ///
 /// # Historical Root Validation
 ///
-/// For multi-file proofs (k > 1), this function validates that `proof.ledger_root` is
-/// in the ledger's set of valid roots (current or historical). This enables cross-block
+/// For ledger-bound proofs (k > 1, or challenges requesting
+/// [`ProofBinding::LedgerRoot`](super::types::ProofBinding)), this function validates that
+/// `proof.ledger_root` is in the ledger's set of valid roots (current or historical). This enables cross-block
 /// aggregation: proofs generated against older ledger states remain valid as long as
 /// the root is in the historical set.
 ///
@@ -27,8 +38,22 @@ use tracing::{debug, info_span};
 /// these indices are correct for the claimed root, so the verifier doesn't need to
 /// recompute them from the current ledger state.
 ///
-/// For single-file proofs (k = 1), the ledger root check is skipped because the circuit
-/// uses the file's Merkle root directly instead of the ledger root.
+/// Each file's rc is derived from its challenge's metadata (root and depth). Against the
+/// current root that rc must be in the ledger, or verification fails with
+/// [`KontorPoRError::FileNotInLedger`]. Against a historical root it need not be: an entry
+/// overwritten by [`FileLedger::add_file`] since proving no longer holds the old rc, yet
+/// the proof still verifies. The verifier then trusts only the retained root, never the
+/// current entries: the SNARK proves that a file of the challenged depth sits at each of
+/// the proof's indices under that root, and [`crate::api::PorSystem::verify`] checks the
+/// proof's challenge IDs, which cover the metadata, against the challenges.
+///
+/// For file-root-bound proofs (k = 1 by default), the ledger root check is skipped because
+/// the circuit uses the file's Merkle root directly instead of the ledger root. A proof
+/// whose binding differs from the challenges' is rejected with
+/// [`KontorPoRError::InvalidInput`].
+///
+/// Challenges repeated with identical IDs are answered once when the proof maps them to
+/// one slot (the default when proving), so the duplicated list verifies as given.
 ///
 /// # Security
 ///
@@ -49,59 +74,226 @@ use tracing::{debug, info_span};
 /// Returns `Ok(true)` if the proof is valid, `Ok(false)` if invalid, or an error
 /// if verification fails due to invalid inputs, invalid ledger root, or unexpected errors.
 pub fn verify(challenges: &[Challenge], proof: &Proof, ledger: &FileLedger) -> Result<bool> {
+    verify_with_options(challenges, proof, ledger, &VerifyOptions::default())
+}
+
+/// Verifies a proof like [`verify`], enforcing the limits in `options`.
+///
+/// A proof generated under another public IO layout than
+/// [`crate::config::PUBLIC_IO_SCHEMA_VERSION`] fails first, with
+/// [`KontorPoRError::SchemaVersionMismatch`].
+/// The step count implied by the challenges is checked against `options.max_steps`
+/// before planning or parameter loading, returning [`KontorPoRError::TooManySteps`].
+/// Elapsed time is checked after planning and again before SNARK verification,
+/// returning [`KontorPoRError::VerificationTimedOut`] once `options.timeout` is exceeded.
+/// A proof generated under another hash profile than the challenged metadata records
+/// fails with [`KontorPoRError::HashProfileMismatch`] before planning. Once the SNARK
+/// verifies, final leaf outputs that differ from `options.expected_leaves` fail with
+/// [`KontorPoRError::LeafValueMismatch`].
+///
+/// This runs the public checks of [`super::PorSystem::preverify`], then the SNARK check
+/// of [`super::PorSystem::verify_snark`].
+pub fn verify_with_options(
+    challenges: &[Challenge],
+    proof: &Proof,
+    ledger: &FileLedger,
+    options: &VerifyOptions,
+) -> Result<bool> {
+    let start = Instant::now();
     let _span = info_span!(
         "verify",
         num_challenges = challenges.len(),
         has_ledger = true,
-        num_iterations = tracing::field::Empty,
-        files_per_step = tracing::field::Empty,
+        num_iterations = field::Empty,
+        files_per_step = field::Empty,
     )
     .entered();
 
+    let statement = preverify_with_options(challenges, proof, ledger, options)?;
+    Span::current().record("num_iterations", statement.num_steps);
+    Span::current().record("files_per_step", statement.shape.files_per_step);
+    finish_verification(&statement, proof, challenges, options, start)
+}
+
+/// The outcome of the public checks of a proof, ready for SNARK verification.
+///
+/// Produced by [`super::PorSystem::preverify`] and consumed by
+/// [`super::PorSystem::verify_snark`]. It records everything the SNARK check needs (the
+/// circuit shape, step count and public inputs), so admission and verification can run
+/// in different processes: serialize it with serde alongside the proof. Field elements
+/// use their canonical 32-byte encoding.
+///
+/// The SNARK check trusts the statement, so it must come from a `preverify` run by the
+/// verifier itself (or passed over a channel it trusts), not from the prover.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreverifiedStatement {
+    /// SHA-256 of the serialized proof the checks were run on
+    pub proof_digest: [u8; 32],
+    /// Circuit shape whose verifier key checks the proof
+    pub shape: Shape,
+    /// Number of recursive steps the proof covers
+    pub num_steps: usize,
+    /// Public inputs of the first step, derived from the challenges and the proof's root
+    /// and indices
+    #[serde(with = "crate::merkle::canonical::fields")]
+    pub z0_primary: Vec<FieldElement>,
+    /// Final seed outputs required under per-step seeds or explicit indices, else empty
+    #[serde(with = "crate::merkle::canonical::fields")]
+    pub expected_seed_outputs: Vec<FieldElement>,
+    /// Final ledger index, depth and seed outputs required of a batch partitioned into
+    /// passes: each accumulates the inputs of the pass every step answered. Empty for
+    /// unpartitioned batches.
+    #[serde(default, with = "crate::merkle::canonical::fields")]
+    pub expected_slot_outputs: Vec<FieldElement>,
+    /// Why the proof cannot verify, if a public check already rejected it.
+    ///
+    /// Set when the proof's recorded nonce commitment, public inputs or challenge slots
+    /// do not describe the challenges. Full verification returns `Ok(false)` for these
+    /// rather than an error, so they are reported here instead of failing `preverify`.
+    pub rejection: Option<String>,
+}
+
+impl PreverifiedStatement {
+    /// Whether the public checks passed, so only the SNARK check remains.
+    pub fn is_admissible(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// The challenges `proof` was planned for: `challenges` with duplicate IDs collapsed when
+/// the proof maps several of them to one slot, as proving does by default
+/// ([`super::types::ProveOptions::allow_duplicates`]), and `challenges` otherwise.
+pub(crate) fn planned_challenges<'c>(
+    challenges: &'c [Challenge],
+    proof: &Proof,
+) -> Cow<'c, [Challenge]> {
+    let mut slots = BTreeSet::new();
+    let shares_slots = !proof.challenge_slots.iter().all(|slot| slots.insert(*slot));
+    match collapse_duplicates(challenges) {
+        Some(unique) if shares_slots => Cow::Owned(unique),
+        _ => Cow::Borrowed(challenges),
+    }
+}
+
+/// Runs every check of [`verify_with_options`] except SNARK verification.
+///
+/// Errors are those full verification reports before loading parameters: schema
+/// version, step and height limits, hash profile, planning failures, binding and shape mismatches,
+/// out-of-range ledger indices, unknown ledger roots, challenge counts and file depths.
+pub(crate) fn preverify_with_options(
+    challenges: &[Challenge],
+    proof: &Proof,
+    ledger: &FileLedger,
+    options: &VerifyOptions,
+) -> Result<PreverifiedStatement> {
+    let start = Instant::now();
+
+    // Proofs of another public IO layout would only fail inside the SNARK
+    proof.check_schema_version()?;
     if challenges.is_empty() {
         return Err(KontorPoRError::InvalidInput(
             "Must provide at least one challenge".to_string(),
         ));
     }
+    options.check_steps(challenges)?;
+    options.check_heights(challenges, proof, ledger)?;
 
-    // Create unified preprocessing plan (derives root internally for security)
-    let plan = Plan::make_plan(challenges, ledger)?;
+    // The shape (and so the verifier key) depends on the hash profile; a proof made under
+    // another profile can never verify, so report the mismatch before planning.
+    let metadata_profile = challenges[0].file_metadata.hash_profile;
+    if proof.hash_profile != metadata_profile {
+        return Err(KontorPoRError::HashProfileMismatch {
+            proof: proof.hash_profile,
+            metadata: metadata_profile,
+        });
+    }
 
+    // Create unified preprocessing plan (derives root internally for security)
+    let plan_start = Instant::now();
+    let plan = {
+        let _span = debug_span!("make_plan", num_challenges = challenges.len()).entered();
+        let planned = planned_challenges(challenges, proof);
+        Plan::make_plan_at_root(&planned, ledger, proof.ledger_root)?
+    };
+    record_timing(Phase::Plan, None, plan_start.elapsed());
+    options.check_deadline(start)?;
     // --- Proof shape + index sanity checks ---
     //
     // The circuit consumes `ledger_root` and `ledger_indices` as public inputs. It uses the
     // low `aggregated_tree_depth` bits of each ledger index to select the aggregation path.
     // Enforcing index range here makes the statement canonical (avoids equivalent indices
     // differing only in high bits).
-    let is_multi_file = plan.files_per_step > 1;
+    //
+    // Ledger-bound proofs always have an aggregation path (at least one level, even for a
+    // ledger holding one file) and file-root-bound proofs never do, so the proof's binding
+    // must be the one the challenges ask for.
+    let is_ledger_bound = plan.binding.is_ledger();
 
-    if is_multi_file {
-        if proof.aggregated_tree_depth == 0 {
-            return Err(KontorPoRError::InvalidInput(
-                "Multi-file proof must have non-zero aggregated_tree_depth".to_string(),
-            ));
+    if proof.binding() != plan.binding {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "Proof binding {:?} does not match the challenges' binding {:?}",
+            proof.binding(),
+            plan.binding
+        )));
+    }
+
+    // The proof must record the shape the challenges derive, so parameters are never
+    // loaded for a shape the prover did not plan. Against a historical root the ledger
+    // may have been shallower, so the aggregated depth is the proof's, bound by the SNARK.
+    // It is bounded first, by the challenges' `max_agg_depth` or else the deeper of the
+    // ledger and `config::MAX_AGG_DEPTH`, so a proof cannot make the verifier generate
+    // parameters for an arbitrarily deep aggregation path.
+    let expected = if is_ledger_bound && proof.ledger_root != ledger.root() {
+        let max_depth = plan.sorted_challenges[0]
+            .max_agg_depth
+            .unwrap_or_else(|| ledger.depth().max(config::MAX_AGG_DEPTH));
+        if !(1..=max_depth).contains(&proof.shape.aggregated_tree_depth) {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Proof aggregated_tree_depth {} against a historical root must be between 1 and {}",
+                proof.shape.aggregated_tree_depth, max_depth
+            )));
+        }
+        config::Shape {
+            aggregated_tree_depth: proof.shape.aggregated_tree_depth,
+            ..plan.shape
+        }
+    } else {
+        plan.shape
+    };
+    if proof.shape != expected {
+        return Err(KontorPoRError::ShapeMismatch {
+            proof: proof.shape,
+            expected,
+        });
+    }
+
+    // Padded challenges fix the aggregated depth, so the proof must be synthesized at it
+    if let Some(max_agg_depth) = plan.sorted_challenges[0].max_agg_depth {
+        if is_ledger_bound && proof.shape.aggregated_tree_depth != max_agg_depth {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Proof aggregated_tree_depth {} does not match challenged max_agg_depth {}",
+                proof.shape.aggregated_tree_depth, max_agg_depth
+            )));
         }
-    } else if proof.aggregated_tree_depth != 0 {
-        return Err(KontorPoRError::InvalidInput(
-            "Single-file proof must have aggregated_tree_depth = 0".to_string(),
-        ));
     }
 
-    if proof.ledger_indices.len() != plan.files_per_step {
+    if proof.ledger_indices.len() != plan.ledger_indices.len() {
         return Err(KontorPoRError::InvalidInput(format!(
-            "Proof ledger_indices length {} does not match circuit files_per_step {}",
+            "Proof ledger_indices length {} does not match the {} slots of {} pass(es) of files_per_step {}",
             proof.ledger_indices.len(),
-            plan.files_per_step
+            plan.ledger_indices.len(),
+            plan.num_passes,
+            plan.shape.files_per_step
         )));
     }
 
-    if is_multi_file {
+    if is_ledger_bound {
         let max_leaf_count = 1usize
-            .checked_shl(proof.aggregated_tree_depth as u32)
+            .checked_shl(proof.shape.aggregated_tree_depth as u32)
             .ok_or_else(|| {
                 KontorPoRError::InvalidInput(format!(
                     "Invalid aggregated_tree_depth {}: too large for host usize",
-                    proof.aggregated_tree_depth
+                    proof.shape.aggregated_tree_depth
                 ))
             })?;
 
@@ -111,33 +303,30 @@ pub fn verify(challenges: &[Challenge], proof: &Proof, ledger: &FileLedger) -> R
                     "Proof ledger_indices[{}] = {} is out of range for aggregated_tree_depth {} (max {})",
                     i,
                     idx,
-                    proof.aggregated_tree_depth,
+                    proof.shape.aggregated_tree_depth,
                     max_leaf_count - 1
                 )));
             }
         }
     }
 
-    // --- Historical root validation (multi-file only) ---
+    // --- Historical root validation (ledger-bound only) ---
     //
-    // For multi-file proofs, `proof.ledger_root` must be either the current root or a retained
-    // historical root. For single-file proofs, the circuit uses the file's root directly.
-    if is_multi_file && !ledger.is_valid_root(proof.ledger_root) {
+    // For ledger-bound proofs, `proof.ledger_root` must be either the current root or a
+    // retained historical root. File-root-bound proofs use the file's root directly.
+    if is_ledger_bound && !ledger.is_valid_root(proof.ledger_root) {
         debug!(
-            "Proof ledger_root {:?} is not in ledger's valid roots (current: {:?})",
-            proof.ledger_root,
-            ledger.root()
+            proof_root = ?proof.ledger_root,
+            current_root = ?ledger.root(),
+            "Proof ledger_root is not in ledger's valid roots"
         );
         return Err(KontorPoRError::InvalidLedgerRoot {
             proof_root: format!("{:?}", proof.ledger_root),
             reason: "Proof's ledger_root is not in the set of valid historical roots".to_string(),
         });
     }
-    if is_multi_file {
-        debug!(
-            "Proof ledger_root {:?} validated as historical root",
-            proof.ledger_root
-        );
+    if is_ledger_bound {
+        debug!(proof_root = ?proof.ledger_root, "Proof ledger_root validated as historical root");
     }
 
     // Basic validation
@@ -148,18 +337,13 @@ pub fn verify(challenges: &[Challenge], proof: &Proof, ledger: &FileLedger) -> R
         });
     }
 
-    // Load or generate parameters for the exact shape (same as prover)
-    // Use proof.aggregated_tree_depth to ensure we match the prover's circuit
-    let params = crate::params::load_or_generate_params(
-        plan.files_per_step,
-        plan.file_tree_depth,
-        proof.aggregated_tree_depth,
-    )?;
+    // Parameters for the exact shape (same as prover) are loaded by the SNARK phase.
+    // Use the proof's checked shape to ensure we match the prover's circuit
+    // The challenge mode selects whether leaf output slots carry raw leaves or
+    // blinded commitments; the matching keys enforce the corresponding interpretation.
+    let shape = plan.param_shape(proof.shape);
 
-    debug!(
-        "verify() - Using shape: files_per_step={}, file_tree_depth={}, aggregated_tree_depth={}",
-        plan.files_per_step, plan.file_tree_depth, proof.aggregated_tree_depth
-    );
+    debug!("verify() - Using shape {}", proof.shape);
 
     // Verify all challenges have the same num_challenges and seed
     for challenge in challenges.iter() {
@@ -173,10 +357,10 @@ pub fn verify(challenges: &[Challenge], proof: &Proof, ledger: &FileLedger) -> R
     // Verify all file depths are within the derived circuit shape bounds
     for challenge in challenges {
         let file_depth = crate::api::tree_depth_from_metadata(&challenge.file_metadata);
-        if file_depth > plan.file_tree_depth {
+        if file_depth > plan.shape.file_tree_depth {
             return Err(KontorPoRError::InvalidInput(format!(
                 "File {} depth {} exceeds circuit shape depth {} - circuit cannot handle this file",
-                challenge.file_metadata.file_id, file_depth, plan.file_tree_depth
+                challenge.file_metadata.file_id, file_depth, plan.shape.file_tree_depth
             )));
         }
     }
@@ -184,64 +368,141 @@ pub fn verify(challenges: &[Challenge], proof: &Proof, ledger: &FileLedger) -> R
     // Build public inputs using:
     // - proof.ledger_root and proof.ledger_indices (from proof, enables historical validation)
     // - depths and seeds from plan (derived from challenges)
-    debug!("Verify: building z0_primary from proof + challenges");
-    debug!("  - Using proof.ledger_root: {:?}", proof.ledger_root);
-    debug!("  - Using proof.ledger_indices: {:?}", proof.ledger_indices);
-    debug!("  - Depths from challenges: {:?}", plan.depths);
-
     // Build z0_primary with proof's values for root/indices
-    let z0_primary = plan.public_io_layout.build_z0_primary(
-        proof.ledger_root,
-        &proof.ledger_indices,
-        &plan.depths,
-        &plan.seeds,
-    );
-    debug!("VERIFIER z0_primary: {:?}", z0_primary);
+    let z0_primary = plan.z0_primary(proof.ledger_root, &proof.ledger_indices);
 
-    let num_iterations = plan.sorted_challenges[0].num_challenges;
+    let num_iterations = plan.num_steps();
 
-    // Record metrics in span
-    tracing::Span::current().record("num_iterations", num_iterations);
-    tracing::Span::current().record("files_per_step", plan.files_per_step);
+    debug!(
+        single_file = plan.shape.aggregated_tree_depth == 0,
+        num_files = plan.sorted_challenges.len(),
+        num_iterations,
+        aggregated_root = ?proof.ledger_root,
+        initial_state = ?plan.initial_state,
+        ledger_indices = ?proof.ledger_indices,
+        depths = ?plan.depths,
+        seeds = ?plan.seeds,
+        "verify() - built z0_primary from proof and challenges"
+    );
 
-    if plan.aggregated_tree_depth == 0 {
-        debug!("verify() - Single-file verification:");
+    // The proof's nonce commitment must describe these challenges; the SNARK itself is
+    // checked against the state derived from the challenges either way.
+    // The recorded public inputs and slots must describe these challenges, so that slices
+    // of the proof (`Proof::public_slice`) only carry data a full verifier would accept
+    let rejection = if proof.challenge_nonce_commitment != plan.initial_state {
+        Some("Proof challenge_nonce_commitment does not match the challenges' nonces")
+    } else if proof.public_inputs != z0_primary {
+        Some("Proof public_inputs do not match the challenges")
+    } else if proof.file_depths != plan.depths {
+        Some("Proof file_depths do not match the challenges")
+    } else if proof.challenge_slots != plan.challenge_slots(challenges) {
+        Some("Proof challenge_slots do not match the challenges")
     } else {
-        debug!("verify() - Multi-file verification:");
+        None
+    };
+    if let Some(reason) = rejection {
+        debug!("{}", reason);
     }
-    debug!("  - Number of files: {}", plan.sorted_challenges.len());
-    debug!("  - Number of iterations to verify: {}", num_iterations);
-    debug!("  - z0_primary[0] aggregated_root: {:?}", proof.ledger_root);
-    debug!("  - z0_primary[1] initial_state: {:?}", FieldElement::ZERO);
-    for (i, idx) in proof.ledger_indices.iter().enumerate() {
-        debug!("  - z0_primary[{}] ledger_index_{}: {}", 2 + i, i, idx);
+
+    let expected_slot_outputs = plan
+        .expected_slot_outputs(&proof.ledger_indices)
+        .unwrap_or_default();
+
+    Ok(PreverifiedStatement {
+        proof_digest: proof_digest(proof)?,
+        shape,
+        num_steps: num_iterations,
+        z0_primary,
+        expected_seed_outputs: plan.expected_seed_outputs().unwrap_or_default(),
+        expected_slot_outputs,
+        rejection: rejection.map(str::to_string),
+    })
+}
+
+/// Completes verification of a preverified proof: checks the deadline, verifies the
+/// SNARK and then the expected leaves of `options`.
+pub(crate) fn finish_verification(
+    statement: &PreverifiedStatement,
+    proof: &Proof,
+    challenges: &[Challenge],
+    options: &VerifyOptions,
+    start: Instant,
+) -> Result<bool> {
+    options.check_deadline(start)?;
+    if !verify_snark(statement, proof)? {
+        return Ok(false);
     }
-    for (i, depth) in plan.depths.iter().enumerate() {
-        debug!(
-            "  - z0_primary[{}] depth_{}: {}",
-            2 + plan.files_per_step + i,
-            i,
-            depth
-        );
+    options.check_leaves(challenges, proof)?;
+    Ok(true)
+}
+
+/// Verifies the SNARK of a proof that passed [`preverify_with_options`].
+///
+/// Returns `Ok(false)` for statements recording a rejection and for proofs whose SNARK
+/// or public outputs do not check out, and [`KontorPoRError::InvalidInput`] if the
+/// statement was produced for another proof.
+pub(crate) fn verify_snark(statement: &PreverifiedStatement, proof: &Proof) -> Result<bool> {
+    if statement.proof_digest != proof_digest(proof)? {
+        return Err(KontorPoRError::InvalidInput(
+            "Preverified statement was produced for a different proof".to_string(),
+        ));
     }
-    for (i, seed) in plan.seeds.iter().enumerate() {
-        debug!(
-            "  - z0_primary[{}] seed_{}: {:?}",
-            2 + plan.files_per_step + plan.files_per_step + i,
-            i,
-            seed
-        );
+    if let Some(reason) = &statement.rejection {
+        debug!("Preverified statement rejected the proof: {}", reason);
+        return Ok(false);
     }
 
-    let result = proof
-        .compressed_snark
-        .verify(&params.keys.vk, num_iterations, &z0_primary);
+    let params = crate::params::load_or_generate_params_for_shape(&statement.shape)?;
+    let num_iterations = statement.num_steps;
+    let result = {
+        let _span = debug_span!("CompressedSNARK::verify", num_iterations).entered();
+        let verify_start = Instant::now();
+        let result =
+            proof
+                .compressed_snark
+                .verify(&params.keys.vk, num_iterations, &statement.z0_primary);
+        record_timing(Phase::SnarkVerify, None, verify_start.elapsed());
+        result
+    };
 
     match result {
-        Ok(_) => Ok(true),
+        Ok(zn) => {
+            if zn != proof.public_outputs {
+                debug!("Proof public_outputs do not match the SNARK's outputs");
+                return Ok(false);
+            }
+
+            // Under per-step seeds, each slot's final seed output must equal the
+            // accumulator of that slot's schedule (binding every step seed and its order)
+            if !statement.expected_seed_outputs.is_empty() {
+                let range = PublicIOLayout::new(statement.shape.files_per_step).seeds_range();
+                if zn.get(range) != Some(statement.expected_seed_outputs.as_slice()) {
+                    debug!("Per-step seed accumulator mismatch");
+                    return Ok(false);
+                }
+            }
+
+            // A partitioned proof must end on the accumulated inputs of its passes
+            if !statement.expected_slot_outputs.is_empty() {
+                let layout = PublicIOLayout::new(statement.shape.files_per_step);
+                if zn.get(slot_inputs_range(&layout))
+                    != Some(statement.expected_slot_outputs.as_slice())
+                {
+                    debug!("Pass schedule accumulator mismatch");
+                    return Ok(false);
+                }
+            }
+
+            Ok(true)
+        }
         Err(nova_snark::errors::NovaError::ProofVerifyError { reason: _ }) => Ok(false),
         Err(e) => Err(KontorPoRError::Snark(format!(
             "An unexpected error occurred during verification: {e:?}"
         ))),
     }
 }
+
+/// Positions of the ledger index, depth and seed sections in the public I/O.
+fn slot_inputs_range(layout: &PublicIOLayout) -> std::ops::Range<usize> {
+    layout.ledger_indices_range().start..layout.seeds_range().end
+}
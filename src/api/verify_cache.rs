@@ -0,0 +1,185 @@
+//! Bounded cache of proof verification outcomes.
+//!
+//! Gateways often verify the same proof for several clients within one epoch. A
+//! [`VerificationCache`] attached to a [`super::PorSystem`] remembers each outcome keyed by
+//! a digest of the serialized proof and the challenge IDs it answers, so repeat queries
+//! skip SNARK verification. Every entry records the ledger roots its outcome depended on;
+//! once the ledger stops accepting one of them (for example after
+//! [`crate::FileLedger::set_historical_roots`] prunes it) the entry is dropped and the
+//! proof is verified again.
+
+use super::types::{ChallengeID, FieldElement, Proof};
+use crate::{ledger::FileLedger, KontorPoRError, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Cache key: SHA-256 over the serialized proof and the challenge IDs.
+pub(crate) type CacheKey = [u8; 32];
+
+/// A cached outcome and the ledger roots it was validated against.
+struct CachedOutcome {
+    outcome: Result<bool>,
+    anchors: Vec<FieldElement>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CachedOutcome>,
+    /// Monotonic counter used as the recency stamp for LRU eviction
+    clock: u64,
+}
+
+/// Least-recently-used cache of verification outcomes, shared by reference between
+/// [`super::PorSystem`] instances (see [`super::PorSystem::with_verification_cache`]).
+///
+/// Valid, invalid and deterministic error outcomes are cached. Outcomes that may change on
+/// retry (timeouts, I/O and parameter lock failures, unknown ledger roots) are not.
+pub struct VerificationCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerificationCache {
+    /// Creates a cache holding at most `capacity` outcomes. A capacity of zero caches
+    /// nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Maximum number of cached outcomes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached outcomes.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns true if no outcomes are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups that required verification, including entries dropped because
+    /// the ledger no longer accepts a root they were validated against.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Removes every cached outcome. The hit and miss counters are kept.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .expect("Verification cache lock should not be poisoned")
+    }
+
+    /// Cache key for `proof` answering the challenges with `challenge_ids`.
+    ///
+    /// The step limit is part of the key because it can turn an otherwise valid proof into
+    /// a [`KontorPoRError::TooManySteps`] rejection.
+    pub(crate) fn key(
+        proof: &Proof,
+        challenge_ids: &[ChallengeID],
+        max_steps: Option<usize>,
+    ) -> Result<CacheKey> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"kontor-por-verify-cache");
+        hasher.update(proof.to_bytes()?);
+        for id in challenge_ids {
+            hasher.update(id.0);
+        }
+        match max_steps {
+            Some(max) => {
+                hasher.update([1u8]);
+                hasher.update((max as u64).to_le_bytes());
+            }
+            None => hasher.update([0u8]),
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    /// Returns the cached outcome for `key` if every root it was validated against is
+    /// still accepted by `ledger`. Stale entries are removed.
+    pub(crate) fn get(&self, key: &CacheKey, ledger: &FileLedger) -> Option<Result<bool>> {
+        let mut state = self.lock();
+        state.clock += 1;
+        let now = state.clock;
+
+        let mut stale = false;
+        match state.entries.get_mut(key) {
+            Some(entry) if entry.anchors.iter().all(|root| ledger.is_valid_root(*root)) => {
+                entry.last_used = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(entry.outcome.clone());
+            }
+            Some(_) => stale = true,
+            None => {}
+        }
+        if stale {
+            state.entries.remove(key);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    /// Records `outcome` for `key`, validated against the ledger roots in `anchors`,
+    /// evicting the least recently used entry if the cache is full.
+    pub(crate) fn insert(&self, key: CacheKey, outcome: &Result<bool>, anchors: Vec<FieldElement>) {
+        if self.capacity == 0 || !is_cacheable(outcome) {
+            return;
+        }
+
+        let mut state = self.lock();
+        state.clock += 1;
+        let now = state.clock;
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            if let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+            {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(
+            key,
+            CachedOutcome {
+                outcome: outcome.clone(),
+                anchors,
+                last_used: now,
+            },
+        );
+    }
+}
+
+/// Whether an outcome would be the same on retry with the same ledger roots.
+fn is_cacheable(outcome: &Result<bool>) -> bool {
+    !matches!(
+        outcome,
+        Err(KontorPoRError::VerificationTimedOut { .. }
+            | KontorPoRError::ParamsLockTimeout { .. }
+            | KontorPoRError::IO(_)
+            | KontorPoRError::InvalidLedgerRoot { .. })
+    )
+}
@@ -3,17 +3,19 @@
 //! This module contains all the logic for creating properly structured
 //! circuit witnesses for both real files and padding.
 
-use super::types::{Challenge, FieldElement, PreparedFile};
+use super::types::{Challenge, FieldElement, PreparedFile, ProofBinding};
+use crate::instrumentation::{debug, debug_span};
 use crate::{
     circuit::{CircuitWitness, FileProofWitness},
-    config, get_padded_proof_for_leaf,
+    config,
     ledger::FileLedger,
-    poseidon::{domain_tags, poseidon_hash_tagged},
+    merkle::{
+        get_padded_proof4_for_leaf_with_profile, get_padded_proof_for_leaf_with_profile, TreeArity,
+    },
     KontorPoRError, Result,
 };
 use ff::Field;
 use std::collections::BTreeMap;
-use tracing::{debug, debug_span};
 
 /// Generates a properly structured CircuitWitness with guaranteed padding.
 ///
@@ -33,6 +35,9 @@ use tracing::{debug, debug_span};
 /// - Single-file proofs (aggregated_tree_depth == 0): Returns 1 witness
 /// - Multi-file proofs: Returns next_power_of_two(num_files) witnesses
 ///
+/// The file tree arity is taken from the first challenge's metadata (batches never mix
+/// arities); arity-4 witnesses carry three flattened siblings per level.
+///
 /// # Arguments
 ///
 /// * `sorted_challenges` - Challenges sorted by file hash for deterministic ordering
@@ -41,7 +46,7 @@ use tracing::{debug, debug_span};
 /// * `params` - The public parameters
 /// * `current_state` - The current state in the hash chain
 /// * `aggregated_tree_depth` - Depth of the aggregated tree (0 for single-file)
-/// * `step_num` - The current step number (for debugging)
+/// * `step_num` - The current step number (selects the seed under a per-step schedule)
 ///
 /// # Returns
 ///
@@ -59,6 +64,77 @@ pub fn generate_circuit_witness(
     aggregated_tree_depth: usize,
     step_num: usize,
     precomputed_ledger_indices: &[usize], // Pass precomputed indices to ensure consistency
+) -> Result<(CircuitWitness<FieldElement>, FieldElement)> {
+    // For exact shape, derive_shape gives the slot count
+    let (target_witness_count, _) =
+        config::derive_shape(sorted_challenges.len(), max_supported_depth);
+    build_circuit_witness(
+        sorted_challenges,
+        files,
+        ledger,
+        file_tree_depth,
+        max_supported_depth,
+        current_state,
+        aggregated_tree_depth,
+        step_num,
+        precomputed_ledger_indices,
+        SlotRange {
+            first: 0,
+            count: target_witness_count,
+        },
+    )
+}
+
+/// Generates the witness of circuit step `step` of `plan`, which answers the step's pass.
+///
+/// The pass's challenges fill the slots from the first, padded to `plan.shape.files_per_step`.
+#[cfg(feature = "prover")]
+pub(crate) fn generate_pass_witness(
+    plan: &super::plan::Plan,
+    files: &BTreeMap<String, &PreparedFile>,
+    ledger: &FileLedger,
+    current_state: FieldElement,
+    step: usize,
+) -> Result<(CircuitWitness<FieldElement>, FieldElement)> {
+    let pass = plan.pass_of(step);
+    let challenges: Vec<&Challenge> = plan.pass_challenges(pass).iter().collect();
+    let slots = plan.pass_slots(pass);
+    build_circuit_witness(
+        &challenges,
+        Some(files),
+        ledger,
+        plan.shape.file_tree_depth,
+        plan.shape.file_tree_depth, // For exact shape, both are the same
+        current_state,
+        plan.shape.aggregated_tree_depth,
+        plan.challenge_step(step),
+        &plan.ledger_indices[slots.clone()],
+        SlotRange {
+            first: slots.start,
+            count: plan.shape.files_per_step,
+        },
+    )
+}
+
+/// Slots a witness fills: `count` slots, numbered from `first` in transcripts.
+struct SlotRange {
+    first: usize,
+    count: usize,
+}
+
+/// Shared body of [`generate_circuit_witness`] and [`generate_pass_witness`].
+#[allow(clippy::too_many_arguments)]
+fn build_circuit_witness(
+    sorted_challenges: &[&Challenge],
+    files: Option<&BTreeMap<String, &PreparedFile>>,
+    ledger: &FileLedger,
+    file_tree_depth: usize,
+    max_supported_depth: usize,
+    current_state: FieldElement,
+    aggregated_tree_depth: usize,
+    step_num: usize,
+    precomputed_ledger_indices: &[usize],
+    slots: SlotRange,
 ) -> Result<(CircuitWitness<FieldElement>, FieldElement)> {
     let _span = debug_span!(
         "generate_circuit_witness",
@@ -70,11 +146,17 @@ pub fn generate_circuit_witness(
 
     let mut file_witnesses = Vec::new();
     let mut local_state = current_state;
+    let tree_arity = sorted_challenges
+        .first()
+        .map(|c| c.file_metadata.tree_arity)
+        .unwrap_or_default();
 
-    debug!("generate_circuit_witness - Step {}:", step_num);
-    debug!("  - Input state: {:?}", current_state);
-    debug!("  - Processing {} challenges", sorted_challenges.len());
-    debug!("  - Aggregated tree depth: {}", aggregated_tree_depth);
+    debug!(
+        input_state = ?current_state,
+        aggregated_tree_depth,
+        "generate_circuit_witness - step {}",
+        step_num
+    );
 
     // Process real file challenges
     if let Some(files) = files {
@@ -85,6 +167,7 @@ pub fn generate_circuit_witness(
                     challenge.file_metadata.file_id
                 ))
             })?;
+            challenge.file_metadata.check_size_limits()?;
 
             let (witness, new_state) = create_single_file_witness(
                 challenge,
@@ -93,8 +176,10 @@ pub fn generate_circuit_witness(
                 local_state,
                 file_tree_depth,
                 aggregated_tree_depth,
+                step_num,
                 ledger,
                 precomputed_ledger_indices,
+                slots.first + file_idx,
             )?;
 
             file_witnesses.push(witness);
@@ -105,7 +190,8 @@ pub fn generate_circuit_witness(
         // Create "worst-case" dummy witnesses with varied depths
         let num_dummies = sorted_challenges.len().max(1);
         for i in 0..num_dummies {
-            let mut dummy_witness = create_padding_witness(file_tree_depth, aggregated_tree_depth);
+            let mut dummy_witness =
+                create_padding_witness(file_tree_depth, aggregated_tree_depth, tree_arity);
             // Alternate between max depth and 0 for robustness testing
             if i == 0 {
                 dummy_witness.actual_depth = max_supported_depth;
@@ -123,16 +209,13 @@ pub fn generate_circuit_witness(
         file_witnesses.iter().filter(|w| w.actual_depth > 0).count()
     };
 
-    // Determine target witness count based on exact shape
-    // For exact shape, we use derive_shape to get the right count
-    let (target_witness_count, _) =
-        config::derive_shape(sorted_challenges.len(), max_supported_depth);
-
     // Pad with null witnesses to reach target count
+    let target_witness_count = slots.count;
     while file_witnesses.len() < target_witness_count {
         file_witnesses.push(create_padding_witness(
             file_tree_depth,
             aggregated_tree_depth,
+            tree_arity,
         ));
     }
 
@@ -145,15 +228,20 @@ pub fn generate_circuit_witness(
         file_witnesses.len()
     );
 
-    debug!("generate_circuit_witness complete:");
-    debug!("  - Real files: {}", num_real_files);
-    debug!("  - Total witnesses: {}", file_witnesses.len());
-    debug!("  - Output state: {:?}", local_state);
+    debug!(
+        num_real_files,
+        num_witnesses = file_witnesses.len(),
+        output_state = ?local_state,
+        "generate_circuit_witness complete"
+    );
 
     // Create the CircuitWitness with guaranteed structure
+    // Real witnesses activate only the ledger's aggregation levels of a padded shape
+    let ledger_depth = (files.is_some() && aggregated_tree_depth > 0).then(|| ledger.depth());
     let circuit_witness = CircuitWitness {
         witnesses: file_witnesses,
         num_real_files,
+        ledger_depth,
     };
 
     Ok((circuit_witness, local_state))
@@ -168,29 +256,62 @@ fn create_single_file_witness(
     current_state: FieldElement,
     file_tree_depth: usize,
     aggregated_tree_depth: usize,
+    step_num: usize,
     ledger: &FileLedger,
     precomputed_ledger_indices: &[usize],
+    transcript_slot: usize,
 ) -> Result<(FileProofWitness<FieldElement>, FieldElement)> {
+    let _span = debug_span!(
+        "file_witness",
+        file_id = %challenge.file_metadata.file_id,
+        file_idx,
+        slot = transcript_slot,
+        step_num
+    )
+    .entered();
     let file_depth = file.tree.layers.len() - 1;
+    if file_depth > file_tree_depth {
+        return Err(KontorPoRError::DepthExceedsShape {
+            file_id: challenge.file_metadata.file_id.clone(),
+            depth: file_depth,
+            max: file_tree_depth,
+        });
+    }
+    let leaf_index = challenged_leaf_index(
+        challenge,
+        file,
+        file_idx,
+        current_state,
+        step_num,
+        aggregated_tree_depth > 0,
+    );
 
-    // Calculate leaf index with proper domain separation
-    let challenge_hash =
-        poseidon_hash_tagged(domain_tags::challenge(), challenge.seed, current_state);
-    let hash = if aggregated_tree_depth > 0 {
-        // Multi-file: use domain-separated hash to combine challenge with file_idx
-        poseidon_hash_tagged(
-            domain_tags::challenge_per_file(),
-            challenge_hash,
-            FieldElement::from(file_idx as u64),
-        )
-    } else {
-        // Single-file: use challenge directly
-        challenge_hash
+    // Get proof padded to MAX depth for circuit uniformity
+    let (leaf, file_siblings) = match file.tree_arity {
+        TreeArity::Binary => {
+            let proof = get_padded_proof_for_leaf_with_profile(
+                &file.tree,
+                leaf_index,
+                file_tree_depth,
+                file.hash_profile,
+            )?;
+            (proof.leaf, proof.siblings)
+        }
+        TreeArity::Arity4 => {
+            let proof = get_padded_proof4_for_leaf_with_profile(
+                &file.tree,
+                leaf_index,
+                file_tree_depth,
+                file.hash_profile,
+            )?;
+            (proof.leaf, proof.flat_siblings())
+        }
     };
-    let leaf_index = crate::utils::derive_index_unbiased(hash, 1usize << file_depth);
 
-    // Get proof padded to MAX depth for circuit uniformity
-    let merkle_proof = get_padded_proof_for_leaf(&file.tree, leaf_index, file_tree_depth)?;
+    // A tampered tree can hold leaves no 31-byte symbol encodes; such a proof cannot verify
+    if !crate::utils::is_bytes31_leaf(&leaf) {
+        return Err(KontorPoRError::InvalidLeafEncoding { index: leaf_index });
+    }
 
     // Get aggregation proof for this file
     let agg_proof = if aggregated_tree_depth > 0 {
@@ -207,36 +328,115 @@ fn create_single_file_witness(
         }
     };
 
+    // Padded shapes take zero siblings above the ledger's depth; those levels are gated off
+    let mut agg_siblings = agg_proof.siblings;
+    if agg_siblings.len() < aggregated_tree_depth {
+        agg_siblings.resize(aggregated_tree_depth, FieldElement::ZERO);
+    }
+
     // Use precomputed ledger index
     let ledger_index = precomputed_ledger_indices[file_idx];
 
     let witness = FileProofWitness {
-        leaf: merkle_proof.leaf,
-        file_siblings: merkle_proof.siblings,
+        leaf,
+        file_siblings,
         file_root: challenge.file_metadata.root,
         actual_depth: file_depth,
-        agg_siblings: agg_proof.siblings,
+        agg_siblings,
         ledger_index,
     };
 
-    // Update state for next file with domain separation
-    let new_state = poseidon_hash_tagged(
-        domain_tags::state_update(),
-        current_state,
-        merkle_proof.leaf,
-    );
+    // Update state for next file with domain separation. Depth-0 (empty) files are gated
+    // like padding slots in the circuit, which leaves the state unchanged.
+    let new_state = if file_depth > 0 {
+        crate::utils::advance_challenge_state(current_state, leaf)
+    } else {
+        current_state
+    };
+    transcript_record!(|t| t.record_opening(
+        step_num,
+        transcript_slot,
+        &challenge.file_metadata.file_id,
+        leaf_index,
+        new_state
+    ));
 
     Ok((witness, new_state))
 }
 
+/// Leaf index opened for `file` in slot `file_idx` at `step_num`, given the chain state
+/// before this slot.
+///
+/// Explicit-index challenges open the requested index directly. Otherwise this uses the
+/// domain-separated derivation of [`crate::utils::derive_leaf_index_for_file`] (two bits
+/// per level for arity-4 trees).
+pub(crate) fn challenged_leaf_index(
+    challenge: &Challenge,
+    file: &PreparedFile,
+    file_idx: usize,
+    current_state: FieldElement,
+    step_num: usize,
+    is_multi_file: bool,
+) -> usize {
+    if let Some(index) = challenge.explicit_index(step_num) {
+        return index;
+    }
+    let file_depth = file.tree.layers.len() - 1;
+    crate::utils::derive_leaf_index_for_file(
+        file_idx,
+        file.tree_arity.index_bits(file_depth),
+        challenge.seed_for_step(step_num),
+        current_state,
+        is_multi_file,
+    )
+}
+
+impl Challenge {
+    /// Leaf indices this challenge opens in `file`, one per step, when it is proven on its
+    /// own.
+    ///
+    /// Replays the prover's challenge chain from the challenge's nonce commitment,
+    /// advancing the state with each opened leaf. In a batch with other challenges the
+    /// chain also passes through their leaves, so the indices differ from these.
+    ///
+    /// # Errors
+    ///
+    /// - The errors of [`Challenge::validate`]
+    /// - [`KontorPoRError::InvalidInput`] if `file` is not the challenged file
+    pub fn preview_leaf_indices(&self, file: &PreparedFile) -> Result<Vec<usize>> {
+        self.validate()?;
+        if file.root != self.file_metadata.root {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Prepared file {} is not the challenged file {}",
+                file.file_id, self.file_metadata.file_id
+            )));
+        }
+
+        let is_multi_file = ProofBinding::for_challenges(std::slice::from_ref(self)).is_ledger();
+        let file_depth = file.tree.layers.len() - 1;
+        let mut state = crate::poseidon::calculate_nonce_commitment(&[self.nonce]);
+        let mut indices = Vec::with_capacity(self.num_challenges);
+        for step_num in 0..self.num_challenges {
+            let leaf_index = challenged_leaf_index(self, file, 0, state, step_num, is_multi_file);
+            if file_depth > 0 {
+                let leaf = file.tree.layers[0][leaf_index];
+                state = crate::utils::advance_challenge_state(state, leaf);
+            }
+            indices.push(leaf_index);
+        }
+        Ok(indices)
+    }
+}
+
 /// Create a padding witness for circuit uniformity.
-fn create_padding_witness(
+pub(crate) fn create_padding_witness(
     file_tree_depth: usize,
     aggregated_tree_depth: usize,
+    tree_arity: TreeArity,
 ) -> FileProofWitness<FieldElement> {
     FileProofWitness {
         leaf: FieldElement::ZERO,
-        file_siblings: vec![FieldElement::ZERO; file_tree_depth],
+        file_siblings: vec![FieldElement::ZERO; tree_arity.siblings_per_level() * file_tree_depth],
         file_root: FieldElement::ZERO,
         actual_depth: 0, // Padding witnesses have no depth
         agg_siblings: vec![FieldElement::ZERO; aggregated_tree_depth],
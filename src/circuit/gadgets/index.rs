@@ -0,0 +1,59 @@
+//! Bounded index decomposition gadget for circuit operations.
+//!
+//! This module turns a leaf index supplied as a field element into exactly the path bits
+//! a Merkle gadget consumes, rejecting indices that do not fit.
+
+use ff::{PrimeField, PrimeFieldBits};
+use nova_snark::frontend::{
+    gadgets::{
+        boolean::{AllocatedBit, Boolean},
+        num::AllocatedNum,
+    },
+    ConstraintSystem, SynthesisError,
+};
+
+/// Decomposes `index` into `num_bits` little-endian bits and enforces that they
+/// recompose to `index`, so the index is constrained to `[0, 2^num_bits)`.
+///
+/// Unlike `AllocatedNum::to_bits_le`, which always yields a full field decomposition,
+/// this allocates exactly `num_bits` bits, one constraint each plus one for the
+/// recomposition. `num_bits` must be below the field's capacity.
+pub fn bounded_index_bits<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    index: &AllocatedNum<F>,
+    num_bits: usize,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    assert!(
+        num_bits < F::CAPACITY as usize,
+        "Index width {} does not fit below the field capacity",
+        num_bits
+    );
+
+    let value_bits = index.get_value().map(|value| value.to_le_bits());
+    let bits = (0..num_bits)
+        .map(|i| {
+            AllocatedBit::alloc(
+                cs.namespace(|| format!("index_bit_{}", i)),
+                value_bits.as_ref().map(|bits| bits[i]),
+            )
+            .map(Boolean::from)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Enforce: sum(bit_i * 2^i) * 1 = index
+    cs.enforce(
+        || "index_recomposition",
+        |lc| {
+            let mut coeff = F::ONE;
+            bits.iter().fold(lc, |lc, bit| {
+                let lc = lc + &bit.lc(CS::one(), coeff);
+                coeff = coeff.double();
+                lc
+            })
+        },
+        |lc| lc + CS::one(),
+        |lc| lc + index.get_variable(),
+    );
+
+    Ok(bits)
+}
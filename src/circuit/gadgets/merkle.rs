@@ -1,7 +1,8 @@
 //! Merkle path verification gadgets for circuits.
 //!
 //! This module provides gated Merkle path verification for both file trees
-//! and aggregation trees within the Nova circuit.
+//! and aggregation trees within the Nova circuit, plus the arity-4 variant for
+//! file trees built with `merkle::build_tree_arity4`.
 
 use ff::PrimeField;
 use ff::PrimeFieldBits;
@@ -10,9 +11,11 @@ use nova_snark::frontend::{
     ConstraintSystem, SynthesisError,
 };
 
-use super::poseidon::poseidon_hash_tagged_gadget;
+use super::poseidon::{
+    poseidon_hash_shared_tag4_gadget_with, poseidon_hash_shared_tag_gadget_with,
+};
 use super::select::conditional_select;
-use crate::poseidon::domain_tags;
+use crate::poseidon::HashProfile;
 
 /// Generic Merkle path verification with gating support for uniform circuit structure.
 /// This unified function handles both file tree and aggregation tree verification.
@@ -21,6 +24,9 @@ use crate::poseidon::domain_tags;
 /// * `namespace_prefix` - Prefix for constraint namespaces ("merkle" or "agg_merkle")
 /// * `is_active_flags` - Optional flags for gating (None creates always-active flags)
 /// * `max_depth` - Maximum depth to process for uniform circuit structure
+/// * `hash_profile` - Poseidon instantiation of the tree's node hashes
+/// * `node_tag` - The step's shared `TAG_NODE` variable (see [`super::poseidon::TagConstants`])
+#[allow(clippy::too_many_arguments)]
 pub fn verify_gated_merkle_path<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
     mut cs: CS,
     leaf: &AllocatedNum<F>,
@@ -29,6 +35,8 @@ pub fn verify_gated_merkle_path<F: PrimeField + PrimeFieldBits, CS: ConstraintSy
     is_active_flags: Option<&[Boolean]>,
     max_depth: usize,
     namespace_prefix: &str,
+    hash_profile: HashProfile,
+    node_tag: &AllocatedNum<F>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
     let mut current_hash = leaf.clone();
 
@@ -77,9 +85,10 @@ pub fn verify_gated_merkle_path<F: PrimeField + PrimeFieldBits, CS: ConstraintSy
         )?;
 
         // Use domain-separated hashing for Merkle nodes
-        let level_hash = poseidon_hash_tagged_gadget(
+        let level_hash = poseidon_hash_shared_tag_gadget_with(
             step_cs.namespace(|| "hash_nodes"),
-            domain_tags::node(),
+            hash_profile,
+            node_tag,
             &left,
             &right,
         )?;
@@ -99,7 +108,9 @@ pub fn verify_gated_merkle_path<F: PrimeField + PrimeFieldBits, CS: ConstraintSy
 }
 
 /// File tree Merkle path verification with gating support.
-/// This is a wrapper around the generic verification function.
+/// This is a wrapper around the generic verification function, hashing under the file's
+/// `hash_profile`.
+#[allow(clippy::too_many_arguments)]
 pub fn verify_merkle_path_gated<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
     cs: CS,
     leaf: &AllocatedNum<F>,
@@ -107,6 +118,8 @@ pub fn verify_merkle_path_gated<F: PrimeField + PrimeFieldBits, CS: ConstraintSy
     path_indices: &[Boolean],
     is_active_flags: Option<&[Boolean]>,
     max_depth: usize,
+    hash_profile: HashProfile,
+    node_tag: &AllocatedNum<F>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
     verify_gated_merkle_path(
         cs,
@@ -116,25 +129,114 @@ pub fn verify_merkle_path_gated<F: PrimeField + PrimeFieldBits, CS: ConstraintSy
         is_active_flags,
         max_depth,
         "merkle",
+        hash_profile,
+        node_tag,
     )
 }
 
 /// Aggregation tree Merkle path verification with uniform structure.
 /// This is a wrapper around the generic verification function.
+///
+/// `is_active_flags` gates levels like the file tree gadget, so a circuit synthesized at a
+/// padded `depth` can verify paths of shallower ledgers; `None` keeps every level active.
 pub fn verify_aggregation_path_gated<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
     cs: CS,
     leaf: &AllocatedNum<F>,
     siblings: &[AllocatedNum<F>],
     path_indices: &[Boolean],
+    is_active_flags: Option<&[Boolean]>,
     depth: usize,
+    node_tag: &AllocatedNum<F>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
     verify_gated_merkle_path(
         cs,
         leaf,
         siblings,
         path_indices,
-        None, // No gating flags - all levels within depth are active
+        is_active_flags,
         depth,
         "agg_merkle",
+        HashProfile::Standard,
+        node_tag,
     )
 }
+
+/// Arity-4 file tree Merkle path verification with gating support.
+///
+/// `siblings` holds three siblings per level (flattened, left-to-right) and `path_indices`
+/// two bits per level `(b0, b1)`, giving the current node's position `b0 + 2 * b1` among its
+/// parent's four children. Missing siblings or bits are padded like the binary gadget, so
+/// exactly `max_depth` levels are always synthesized. Nodes are hashed under `hash_profile`
+/// with the step's shared `TAG_NODE4` variable `node_tag`.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_merkle_path4_gated<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    leaf: &AllocatedNum<F>,
+    siblings: &[AllocatedNum<F>],
+    path_indices: &[Boolean],
+    is_active_flags: Option<&[Boolean]>,
+    max_depth: usize,
+    hash_profile: HashProfile,
+    node_tag: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let mut current_hash = leaf.clone();
+
+    for i in 0..max_depth {
+        let mut step_cs = cs.namespace(|| format!("merkle4_step_{}", i));
+
+        let is_active = match is_active_flags {
+            Some(flags) => flags.get(i).cloned().unwrap_or(Boolean::constant(false)),
+            None => Boolean::constant(true),
+        };
+
+        let mut level_siblings = Vec::with_capacity(3);
+        for j in 0..3 {
+            let sibling = match siblings.get(3 * i + j) {
+                Some(sibling) => sibling.clone(),
+                None => AllocatedNum::alloc(
+                    step_cs.namespace(|| format!("dummy_sibling_{}", j)),
+                    || Ok(F::ZERO),
+                )?,
+            };
+            level_siblings.push(sibling);
+        }
+        let (s0, s1, s2) = (&level_siblings[0], &level_siblings[1], &level_siblings[2]);
+
+        let b0 = path_indices
+            .get(2 * i)
+            .cloned()
+            .unwrap_or(Boolean::constant(false));
+        let b1 = path_indices
+            .get(2 * i + 1)
+            .cloned()
+            .unwrap_or(Boolean::constant(false));
+
+        // Children by position: 0 -> [cur,s0,s1,s2], 1 -> [s0,cur,s1,s2],
+        // 2 -> [s0,s1,cur,s2], 3 -> [s0,s1,s2,cur]
+        let low0 = conditional_select(step_cs.namespace(|| "low0"), &b0, &current_hash, s0)?;
+        let low1 = conditional_select(step_cs.namespace(|| "low1"), &b0, s0, &current_hash)?;
+        let high2 = conditional_select(step_cs.namespace(|| "high2"), &b0, &current_hash, s2)?;
+        let high3 = conditional_select(step_cs.namespace(|| "high3"), &b0, s2, &current_hash)?;
+
+        let child0 = conditional_select(step_cs.namespace(|| "child0"), &b1, &low0, s0)?;
+        let child1 = conditional_select(step_cs.namespace(|| "child1"), &b1, &low1, s1)?;
+        let child2 = conditional_select(step_cs.namespace(|| "child2"), &b1, s1, &high2)?;
+        let child3 = conditional_select(step_cs.namespace(|| "child3"), &b1, s2, &high3)?;
+
+        let level_hash = poseidon_hash_shared_tag4_gadget_with(
+            step_cs.namespace(|| "hash_nodes"),
+            hash_profile,
+            node_tag,
+            [&child0, &child1, &child2, &child3],
+        )?;
+
+        current_hash = conditional_select(
+            step_cs.namespace(|| "select_output"),
+            &is_active,
+            &current_hash,
+            &level_hash,
+        )?;
+    }
+
+    Ok(current_hash)
+}
@@ -2,11 +2,20 @@
 //!
 //! This module contains low-level circuit components used in the synthesis.
 
+pub mod index;
 pub mod merkle;
 pub mod poseidon;
 pub mod select;
 
 // Re-export commonly used gadgets
-pub use merkle::{verify_aggregation_path_gated, verify_merkle_path_gated};
-pub use poseidon::poseidon_hash_tagged_gadget;
+pub use index::bounded_index_bits;
+pub use merkle::{
+    verify_aggregation_path_gated, verify_merkle_path4_gated, verify_merkle_path_gated,
+};
+pub use poseidon::{
+    alloc_tag_constant, poseidon_hash_shared_tag4_gadget_with, poseidon_hash_shared_tag_gadget,
+    poseidon_hash_shared_tag_gadget_with, poseidon_hash_tagged4_gadget,
+    poseidon_hash_tagged4_gadget_with, poseidon_hash_tagged_gadget,
+    poseidon_hash_tagged_gadget_with, TagConstants,
+};
 pub use select::conditional_select;
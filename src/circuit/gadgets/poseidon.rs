@@ -9,50 +9,241 @@ use nova_snark::frontend::{
     gadgets::{
         num::AllocatedNum,
         poseidon::{
-            Elt, IOPattern, Simplex, Sponge, SpongeAPI, SpongeCircuit, SpongeOp, SpongeTrait,
-            Strength,
+            Elt, IOPattern, PoseidonConstants, Simplex, Sponge, SpongeAPI, SpongeCircuit, SpongeOp,
+            SpongeTrait,
         },
     },
     ConstraintSystem, SynthesisError,
 };
 use once_cell::sync::Lazy;
+use std::any::{Any, TypeId};
+
+use crate::poseidon::HashProfile;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Poseidon constants for circuit hashing, built once per field type and profile and leaked.
+///
+/// The gadgets are generic over the field, so a single typed `Lazy` cannot hold them;
+/// entries are keyed by `TypeId` and [`HashProfile`] instead. Every tag and arity shares
+/// the same width-3 constants, so one entry per field and profile is all that is ever built.
+type ConstantsCache = HashMap<(TypeId, HashProfile), &'static (dyn Any + Send + Sync)>;
+static CIRCUIT_CONSTANTS: Lazy<Mutex<ConstantsCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Cached IO pattern for 3-input circuit Poseidon hashing
 static CIRCUIT_IO_PATTERN_3: Lazy<IOPattern> =
     Lazy::new(|| IOPattern(vec![SpongeOp::Absorb(3), SpongeOp::Squeeze(1)]));
 
+/// Cached IO pattern for 5-input circuit Poseidon hashing
+static CIRCUIT_IO_PATTERN_5: Lazy<IOPattern> =
+    Lazy::new(|| IOPattern(vec![SpongeOp::Absorb(5), SpongeOp::Squeeze(1)]));
+
+/// Returns the cached Poseidon constants for field `F`, building them on first use.
+pub fn circuit_constants<F: PrimeField>() -> &'static PoseidonConstants<F, U2> {
+    circuit_constants_with::<F>(HashProfile::Standard)
+}
+
+/// Returns the cached Poseidon constants of `profile` for field `F`, building them on first use.
+pub fn circuit_constants_with<F: PrimeField>(
+    profile: HashProfile,
+) -> &'static PoseidonConstants<F, U2> {
+    let mut cache = CIRCUIT_CONSTANTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = *cache.entry((TypeId::of::<F>(), profile)).or_insert_with(
+        || -> &'static (dyn Any + Send + Sync) {
+            Box::leak(Box::new(Sponge::<F, U2>::api_constants(profile.strength())))
+        },
+    );
+    entry
+        .downcast_ref()
+        .expect("circuit constants are keyed by their field type")
+}
+
+/// Builds the circuit constants of every profile and the IO patterns for field `F` ahead
+/// of synthesis.
+pub fn warm_up<F: PrimeField>() {
+    circuit_constants_with::<F>(HashProfile::Standard);
+    circuit_constants_with::<F>(HashProfile::Conservative);
+    Lazy::force(&CIRCUIT_IO_PATTERN_3);
+    Lazy::force(&CIRCUIT_IO_PATTERN_5);
+}
+
 /// Domain-separated Poseidon hash gadget for in-circuit use
 /// Optimized to use a single 3-input hash operation
 pub fn poseidon_hash_tagged_gadget<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
+    cs: CS,
+    tag: F, // Should be a constant
+    x: &AllocatedNum<F>,
+    y: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    poseidon_hash_tagged_gadget_with(cs, HashProfile::Standard, tag, x, y)
+}
+
+/// Like [`poseidon_hash_tagged_gadget`], but with the constants of `profile`
+/// In-circuit counterpart of `poseidon::poseidon_hash_tagged_with`
+pub fn poseidon_hash_tagged_gadget_with<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
     mut cs: CS,
+    profile: HashProfile,
     tag: F, // Should be a constant
     x: &AllocatedNum<F>,
     y: &AllocatedNum<F>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
-    let poseidon_constants = Sponge::<F, U2>::api_constants(Strength::Standard);
+    let tag_alloc = alloc_tag_constant(cs.namespace(|| "hash_tag"), tag)?;
+    poseidon_hash_shared_tag_gadget_with(cs, profile, &tag_alloc, x, y)
+}
+
+/// Like [`poseidon_hash_tagged_gadget`], but over a shared, already allocated tag
+/// (see [`poseidon_hash_shared_tag_gadget_with`])
+pub fn poseidon_hash_shared_tag_gadget<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
+    cs: CS,
+    tag: &AllocatedNum<F>,
+    x: &AllocatedNum<F>,
+    y: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    poseidon_hash_shared_tag_gadget_with(cs, HashProfile::Standard, tag, x, y)
+}
+
+/// Like [`poseidon_hash_tagged_gadget_with`], but over a tag already allocated with
+/// [`alloc_tag_constant`] (or held in [`TagConstants`]), so repeated hashes under one tag
+/// share a single tag variable and its constraint.
+pub fn poseidon_hash_shared_tag_gadget_with<
+    F: PrimeField + PrimeFieldBits,
+    CS: ConstraintSystem<F>,
+>(
+    mut cs: CS,
+    profile: HashProfile,
+    tag: &AllocatedNum<F>,
+    x: &AllocatedNum<F>,
+    y: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let poseidon_constants = circuit_constants_with::<F>(profile);
     let io_pattern = &*CIRCUIT_IO_PATTERN_3;
 
     let mut hash_ns = cs.namespace(|| "hash_tag_x_y");
-    let mut sponge = SpongeCircuit::new_with_constants(&poseidon_constants, Simplex);
+    let mut sponge = SpongeCircuit::new_with_constants(poseidon_constants, Simplex);
 
-    // Allocate tag as a constant
-    let tag_alloc = AllocatedNum::alloc(hash_ns.namespace(|| "tag"), || Ok(tag))?;
-    // Constrain tag to be the expected constant
-    hash_ns.enforce(
+    // Hash all three inputs in one operation
+    let elts = [
+        Elt::Allocated(tag.clone()),
+        Elt::Allocated(x.clone()),
+        Elt::Allocated(y.clone()),
+    ];
+    sponge.start(io_pattern.to_owned(), None, &mut hash_ns);
+    SpongeAPI::absorb(&mut sponge, 3, &elts, &mut hash_ns);
+    let output = SpongeAPI::squeeze(&mut sponge, 1, &mut hash_ns);
+    sponge
+        .finish(&mut hash_ns)
+        .map_err(|_| SynthesisError::Unsatisfiable)?;
+    let result = Elt::ensure_allocated(&output[0], &mut hash_ns, true)?;
+
+    Ok(result)
+}
+
+/// Allocates `tag` and constrains it to equal the constant (one constraint)
+pub fn alloc_tag_constant<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    tag: F,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let tag_alloc = AllocatedNum::alloc(cs.namespace(|| "tag"), || Ok(tag))?;
+    cs.enforce(
         || "tag_is_constant",
         |lc| lc + tag_alloc.get_variable(),
         |lc| lc + CS::one(),
         |lc| lc + (tag, CS::one()),
     );
+    Ok(tag_alloc)
+}
+
+/// Domain tags allocated once per step and shared by every hash that uses them.
+///
+/// Without sharing, each tagged hash allocates and constrains its own copy of the tag; a
+/// step hashing under the same tag at every Merkle level of every slot pays that
+/// constraint once instead.
+pub struct TagConstants<F: PrimeField> {
+    tags: Vec<(F, AllocatedNum<F>)>,
+}
+
+impl<F: PrimeField> TagConstants<F> {
+    /// Allocates each distinct tag of `tags` once, in order.
+    pub fn alloc<CS: ConstraintSystem<F>>(mut cs: CS, tags: &[F]) -> Result<Self, SynthesisError> {
+        let mut allocated: Vec<(F, AllocatedNum<F>)> = Vec::with_capacity(tags.len());
+        for &tag in tags {
+            if allocated.iter().all(|(t, _)| *t != tag) {
+                let tag_alloc =
+                    alloc_tag_constant(cs.namespace(|| format!("tag_{}", allocated.len())), tag)?;
+                allocated.push((tag, tag_alloc));
+            }
+        }
+        Ok(Self { tags: allocated })
+    }
+
+    /// The shared variable of `tag`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` was not passed to [`Self::alloc`]; the set of tags a circuit
+    /// hashes under is fixed by its shape.
+    pub fn get(&self, tag: F) -> &AllocatedNum<F> {
+        self.tags
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, tag_alloc)| tag_alloc)
+            .unwrap_or_else(|| panic!("domain tag {:?} was not allocated for this step", tag))
+    }
+}
+
+/// Domain-separated Poseidon hash gadget over a tag and four elements
+/// In-circuit counterpart of `poseidon::poseidon_hash_tagged4` (arity-4 Merkle nodes)
+pub fn poseidon_hash_tagged4_gadget<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
+    cs: CS,
+    tag: F, // Should be a constant
+    children: [&AllocatedNum<F>; 4],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    poseidon_hash_tagged4_gadget_with(cs, HashProfile::Standard, tag, children)
+}
+
+/// Like [`poseidon_hash_tagged4_gadget`], but with the constants of `profile`
+/// In-circuit counterpart of `poseidon::poseidon_hash_tagged4_with`
+pub fn poseidon_hash_tagged4_gadget_with<
+    F: PrimeField + PrimeFieldBits,
+    CS: ConstraintSystem<F>,
+>(
+    mut cs: CS,
+    profile: HashProfile,
+    tag: F, // Should be a constant
+    children: [&AllocatedNum<F>; 4],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let tag_alloc = alloc_tag_constant(cs.namespace(|| "hash_tag"), tag)?;
+    poseidon_hash_shared_tag4_gadget_with(cs, profile, &tag_alloc, children)
+}
+
+/// Like [`poseidon_hash_tagged4_gadget_with`], but over a shared, already allocated tag
+/// (see [`poseidon_hash_shared_tag_gadget_with`])
+pub fn poseidon_hash_shared_tag4_gadget_with<
+    F: PrimeField + PrimeFieldBits,
+    CS: ConstraintSystem<F>,
+>(
+    mut cs: CS,
+    profile: HashProfile,
+    tag: &AllocatedNum<F>,
+    children: [&AllocatedNum<F>; 4],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let poseidon_constants = circuit_constants_with::<F>(profile);
+    let io_pattern = &*CIRCUIT_IO_PATTERN_5;
+
+    let mut hash_ns = cs.namespace(|| "hash_tag_children");
+    let mut sponge = SpongeCircuit::new_with_constants(poseidon_constants, Simplex);
 
-    // Hash all three inputs in one operation
     let elts = [
-        Elt::Allocated(tag_alloc),
-        Elt::Allocated(x.clone()),
-        Elt::Allocated(y.clone()),
+        Elt::Allocated(tag.clone()),
+        Elt::Allocated(children[0].clone()),
+        Elt::Allocated(children[1].clone()),
+        Elt::Allocated(children[2].clone()),
+        Elt::Allocated(children[3].clone()),
     ];
     sponge.start(io_pattern.to_owned(), None, &mut hash_ns);
-    SpongeAPI::absorb(&mut sponge, 3, &elts, &mut hash_ns);
+    SpongeAPI::absorb(&mut sponge, 5, &elts, &mut hash_ns);
     let output = SpongeAPI::squeeze(&mut sponge, 1, &mut hash_ns);
     sponge
         .finish(&mut hash_ns)
@@ -24,9 +24,48 @@ use nova_snark::{
     frontend::{gadgets::num::AllocatedNum, ConstraintSystem, SynthesisError},
     traits::circuit::StepCircuit,
 };
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 use crate::config;
+use crate::merkle::TreeArity;
+use crate::poseidon::HashProfile;
+
+/// Which statement the circuit enforces about the public ledger indices.
+///
+/// Changing the mode changes the circuit shape, so it selects its own parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CircuitMode {
+    /// Ledger indices are trusted public inputs: the circuit uses their low
+    /// `aggregated_tree_depth` bits and leaves range and distinctness checks to the verifier.
+    #[default]
+    Lenient,
+    /// The circuit itself enforces `ledger_index < 2^aggregated_tree_depth` for every slot
+    /// and that active slots of a step use pairwise distinct ledger indices, so the SNARK
+    /// and its public inputs alone carry the full statement.
+    Strict,
+}
+
+impl CircuitMode {
+    /// Returns true if ledger index checks are enforced in-circuit.
+    pub fn is_strict(&self) -> bool {
+        matches!(self, CircuitMode::Strict)
+    }
+
+    /// Constraints this mode adds per step over [`CircuitMode::Lenient`].
+    ///
+    /// Strict multi-file circuits add per slot one range constraint and one constraint per
+    /// aggregation level, and two constraints per slot pair,
+    /// `files_per_step * (aggregated_tree_depth + files_per_step)` in total; single-file
+    /// circuits have no ledger index to check.
+    pub fn extra_constraints(&self, files_per_step: usize, aggregated_tree_depth: usize) -> usize {
+        if self.is_strict() && aggregated_tree_depth > 0 {
+            files_per_step * (aggregated_tree_depth + files_per_step)
+        } else {
+            0
+        }
+    }
+}
 
 /// A Nova step circuit for Proof-of-Retrievability that verifies Merkle proofs.
 ///
@@ -45,6 +84,24 @@ pub struct PorCircuit<F: PrimeField> {
     pub aggregated_tree_depth: usize,
     /// Structured witness data, guaranteed to be properly formed with correct padding.
     pub witness: Option<CircuitWitness<F>>,
+    /// Whether challenged leaves are exposed as blinded commitments instead of raw values.
+    pub private_leaves: bool,
+    /// Per-slot seeds for this step when challenges use per-step seed schedules.
+    /// `Some` switches the circuit to per-step mode; missing entries (e.g. during setup) are zero.
+    pub step_seeds: Option<Vec<F>>,
+    /// Per-slot leaf indices for this step when challenges use explicit indices.
+    /// `Some` switches the circuit to explicit-index mode; missing entries are zero.
+    pub explicit_indices: Option<Vec<F>>,
+    /// Per-slot ledger index, depth and seed inputs of this step's pass when a batch is
+    /// partitioned into passes. `Some` switches the circuit to partitioned mode; missing
+    /// entries are zero.
+    pub pass_inputs: Option<Vec<F>>,
+    /// Branching factor of the file trees verified by this circuit.
+    pub tree_arity: TreeArity,
+    /// Poseidon instantiation of the file trees and root commitments verified by this circuit.
+    pub hash_profile: HashProfile,
+    /// Whether ledger index range and distinctness checks are enforced in-circuit.
+    pub circuit_mode: CircuitMode,
     /// Phantom data to make the struct generic over the field `F`.
     _p: PhantomData<F>,
 }
@@ -57,7 +114,7 @@ impl<F: PrimeField> PorCircuit<F> {
     ///
     /// # Arguments
     ///
-    /// * `aggregated_tree_depth` - The depth of the aggregated tree (0 for single-file).
+    /// * `shape` - File slots, file tree depth and aggregated tree depth (0 for single-file).
     /// * `witnesses` - Optional vector of file proof witnesses (None for setup).
     ///
     /// # Single-file usage:
@@ -70,21 +127,17 @@ impl<F: PrimeField> PorCircuit<F> {
     ///     agg_siblings: vec![],            // empty for single-file
     ///     ledger_index: 0,                 // single-file doesn't use ledger
     /// };
-    /// let circuit = PorCircuit::new(0, Some(vec![witness]));
+    /// let circuit = PorCircuit::new(Shape::new(1, file_depth, 0), Some(vec![witness]));
     /// ```
     ///
     /// # Multi-file usage:
     /// ```rust,ignore
     /// // Supports 1 to files_per_step files with automatic padding
     /// let witnesses = vec![witness1, witness2];  // one per file
-    /// let circuit = PorCircuit::new(files_per_step, file_tree_depth, aggregated_depth, Some(witnesses));
+    /// let shape = Shape::derive(witnesses.len(), file_tree_depth, aggregated_depth);
+    /// let circuit = PorCircuit::new(shape, Some(witnesses));
     /// ```
-    pub fn new(
-        files_per_step: usize,
-        file_tree_depth: usize,
-        aggregated_tree_depth: usize,
-        witnesses: Option<Vec<FileProofWitness<F>>>,
-    ) -> Self {
+    pub fn new(shape: config::Shape, witnesses: Option<Vec<FileProofWitness<F>>>) -> Self {
         // Wrap witness vector in CircuitWitness
         // Note: The canonical way to create witnesses is via generate_circuit_witness()
         let circuit_witness = witnesses.map(|w| {
@@ -94,13 +147,106 @@ impl<F: PrimeField> PorCircuit<F> {
         });
 
         Self {
-            file_tree_depth,
-            files_per_step,
-            aggregated_tree_depth,
+            file_tree_depth: shape.file_tree_depth,
+            files_per_step: shape.files_per_step,
+            aggregated_tree_depth: shape.aggregated_tree_depth,
             witness: circuit_witness,
+            private_leaves: false,
+            step_seeds: None,
+            explicit_indices: None,
+            pass_inputs: None,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
             _p: PhantomData,
         }
     }
+
+    /// The circuit's file slots, file tree depth and aggregated tree depth.
+    pub fn shape(&self) -> config::Shape {
+        config::Shape::new(
+            self.files_per_step,
+            self.file_tree_depth,
+            self.aggregated_tree_depth,
+        )
+    }
+
+    /// Sets the per-slot seeds for this step, switching the circuit to per-step seed mode.
+    ///
+    /// In per-step mode each slot derives its challenge from a private step seed and its
+    /// seed output becomes `H(TAG_SEED_SCHEDULE, seed_in, step_seed)`. Like
+    /// `with_private_leaves`, this is part of the circuit shape.
+    pub fn with_step_seeds(mut self, step_seeds: Option<Vec<F>>) -> Self {
+        self.step_seeds = step_seeds;
+        self
+    }
+
+    /// Sets the per-slot leaf indices for this step, switching the circuit to explicit-index mode.
+    ///
+    /// In explicit-index mode each slot opens the given leaf instead of deriving one from
+    /// its seed, and its seed output becomes `H(TAG_EXPLICIT_INDEX, seed_in, index)`. This
+    /// takes precedence over `with_step_seeds` and is part of the circuit shape.
+    pub fn with_explicit_indices(mut self, explicit_indices: Option<Vec<F>>) -> Self {
+        self.explicit_indices = explicit_indices;
+        self
+    }
+
+    /// Sets the slot inputs of this step's pass, switching the circuit to partitioned mode.
+    ///
+    /// `pass_inputs` holds the ledger index, depth and seed of each slot in public I/O
+    /// order (`3 * files_per_step` values). In partitioned mode the slots open the files
+    /// these private inputs describe, and each public ledger index, depth and seed output
+    /// becomes `H(TAG_PASS_SCHEDULE, input, pass_input)`, so the verifier can check which
+    /// pass every step answered. This is part of the circuit shape.
+    pub fn with_pass_inputs(mut self, pass_inputs: Option<Vec<F>>) -> Self {
+        self.pass_inputs = pass_inputs;
+        self
+    }
+
+    /// Sets the file tree arity verified by this circuit.
+    ///
+    /// Arity-4 circuits take three siblings and two challenge bits per level, so
+    /// `file_tree_depth` counts 4-ary levels. This is part of the circuit shape.
+    pub fn with_tree_arity(mut self, tree_arity: TreeArity) -> Self {
+        self.tree_arity = tree_arity;
+        self
+    }
+
+    /// Sets the Poseidon instantiation of the file trees verified by this circuit.
+    ///
+    /// The profile selects the constants of the file tree node hashes and the root
+    /// commitment. This is part of the circuit shape.
+    pub fn with_hash_profile(mut self, hash_profile: HashProfile) -> Self {
+        self.hash_profile = hash_profile;
+        self
+    }
+
+    /// Sets which ledger index checks the circuit enforces.
+    ///
+    /// [`CircuitMode::Strict`] adds the range and distinctness constraints for multi-file
+    /// circuits (`aggregated_tree_depth > 0`). This is part of the circuit shape.
+    pub fn with_circuit_mode(mut self, circuit_mode: CircuitMode) -> Self {
+        self.circuit_mode = circuit_mode;
+        self
+    }
+
+    /// Sets the depth of the ledger the witness slots open, for circuits whose
+    /// `aggregated_tree_depth` is padded beyond it (see [`CircuitWitness::with_ledger_depth`]).
+    ///
+    /// Only the witness changes, so the circuit shape is the same for every ledger depth.
+    pub fn with_ledger_depth(mut self, ledger_depth: Option<usize>) -> Self {
+        self.witness = self.witness.map(|w| w.with_ledger_depth(ledger_depth));
+        self
+    }
+
+    /// Sets whether the circuit exposes blinded leaf commitments instead of raw leaves.
+    ///
+    /// This is part of the circuit shape: parameters generated with one setting cannot
+    /// verify proofs produced with the other.
+    pub fn with_private_leaves(mut self, private_leaves: bool) -> Self {
+        self.private_leaves = private_leaves;
+        self
+    }
 }
 
 impl<F: PrimeField> Default for PorCircuit<F> {
@@ -112,6 +258,13 @@ impl<F: PrimeField> Default for PorCircuit<F> {
             files_per_step: 1,
             aggregated_tree_depth: 0,
             witness: None,
+            private_leaves: false,
+            step_seeds: None,
+            explicit_indices: None,
+            pass_inputs: None,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
             _p: PhantomData,
         }
     }
@@ -135,6 +288,13 @@ impl<F: PrimeField + PrimeFieldBits> StepCircuit<F> for PorCircuit<F> {
             self.file_tree_depth,
             self.aggregated_tree_depth,
             self.witness.as_ref(),
+            self.private_leaves,
+            self.step_seeds.as_deref(),
+            self.explicit_indices.as_deref(),
+            self.pass_inputs.as_deref(),
+            self.tree_arity,
+            self.hash_profile,
+            self.circuit_mode,
         )
     }
 }
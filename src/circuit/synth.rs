@@ -3,6 +3,7 @@
 //! This module contains the main synthesize function that implements the
 //! Step Circuit logic for Proof-of-Retrievability verification.
 
+use crate::instrumentation::debug;
 use ff::PrimeField;
 use ff::PrimeFieldBits;
 use nova_snark::frontend::{
@@ -12,19 +13,58 @@ use nova_snark::frontend::{
     },
     ConstraintSystem, SynthesisError,
 };
-#[cfg(debug_assertions)]
-use tracing::debug;
 
 use super::gadgets::{
-    merkle::{verify_aggregation_path_gated, verify_merkle_path_gated},
-    poseidon::poseidon_hash_tagged_gadget,
+    index::bounded_index_bits,
+    merkle::{verify_aggregation_path_gated, verify_merkle_path4_gated, verify_merkle_path_gated},
+    poseidon::{
+        poseidon_hash_shared_tag_gadget, poseidon_hash_shared_tag_gadget_with, TagConstants,
+    },
     select::conditional_select,
 };
 use super::witness::{CircuitWitness, FileProofWitness};
+use super::CircuitMode;
 use crate::config;
-use crate::poseidon::domain_tags;
+use crate::merkle::TreeArity;
+use crate::poseidon::{domain_tags, HashProfile};
 
 /// Main circuit synthesis function for the Nova PoR circuit
+///
+/// When `private_leaves` is set, each public leaf output carries
+/// `H(TAG_LEAF_COMMITMENT, leaf, blinding)` instead of the raw leaf value.
+///
+/// When `step_seeds` is `Some`, each slot derives its challenge from a private step seed
+/// (missing entries are zero) and its seed output is `H(TAG_SEED_SCHEDULE, seed_in, step_seed)`.
+///
+/// When `explicit_indices` is `Some`, each slot opens the leaf at a private step index
+/// (missing entries are zero) instead of deriving it from the seed, the index is
+/// constrained to the file tree's index width, and the seed output is
+/// `H(TAG_EXPLICIT_INDEX, seed_in, index)`. Explicit indices take precedence over `step_seeds`.
+///
+/// `tree_arity` selects the file tree layout: `file_tree_depth` counts levels of that arity,
+/// each consuming `tree_arity.siblings_per_level()` siblings and `bits_per_level()` challenge bits.
+/// `hash_profile` selects the Poseidon constants of the file tree nodes and the root
+/// commitment; every other hash uses [`HashProfile::Standard`].
+///
+/// In [`CircuitMode::Strict`], multi-file circuits also constrain every public ledger index
+/// to `aggregated_tree_depth` bits and require the ledger indices of active slots to be
+/// pairwise distinct. This adds `files_per_step * (aggregated_tree_depth + files_per_step)`
+/// constraints: per slot one range constraint and one per aggregation level (bits of
+/// inactive levels are zero), and two (an AND of the slot gates and an inverse check) per
+/// slot pair.
+///
+/// When `pass_inputs` is `Some`, the batch is partitioned into passes: each slot opens the
+/// file described by its private pass inputs (ledger index, depth and seed in public I/O
+/// order, missing entries are zero) instead of its public inputs, and its ledger index,
+/// depth and seed outputs are `H(TAG_PASS_SCHEDULE, input, pass_input)`. The verifier
+/// recomputes these accumulators from the pass order, so every step is bound to the files
+/// of its pass. Otherwise each step outputs its ledger index and depth inputs unchanged,
+/// and so its seed inputs unless per-step seeds or explicit indices accumulate them.
+///
+/// Multi-file circuits gate the aggregation path with private monotone level flags taken
+/// from the witness's ledger depth, so a circuit synthesized at a padded
+/// `aggregated_tree_depth` verifies membership in any shallower ledger.
+#[allow(clippy::too_many_arguments)]
 pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSystem<F>>(
     cs: &mut CS,
     z: &[AllocatedNum<F>],
@@ -32,9 +72,22 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
     file_tree_depth: usize,
     aggregated_tree_depth: usize,
     witness: Option<&CircuitWitness<F>>,
+    private_leaves: bool,
+    step_seeds: Option<&[F]>,
+    explicit_indices: Option<&[F]>,
+    pass_inputs: Option<&[F]>,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+    circuit_mode: CircuitMode,
 ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
     // Use centralized layout helper
     let layout = config::PublicIOLayout::new(files_per_step);
+    // Both modes accumulate into the seed outputs, so only one of them can be active
+    let step_seeds = if explicit_indices.is_some() {
+        None
+    } else {
+        step_seeds
+    };
 
     // Assert that the public inputs match the expected circuit arity
     assert_eq!(
@@ -54,70 +107,55 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
     let root = &z[layout.idx_agg_root()]; // The public root (aggregated tree root)
     let state_in = &z[layout.idx_state_in()]; // The input state for the current step's hash chain.
 
+    // In partitioned mode the slots take their ledger index, depth and seed from the
+    // private inputs of this step's pass; the public slot inputs only accumulate them
+    let pass_slot_inputs: Option<Vec<AllocatedNum<F>>> = pass_inputs
+        .map(|pass_inputs| {
+            (0..3 * files_per_step)
+                .map(|k| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("pass_input_{}", k)), || {
+                        Ok(pass_inputs.get(k).copied().unwrap_or(F::ZERO))
+                    })
+                })
+                .collect::<Result<_, _>>()
+        })
+        .transpose()?;
+    let slot_input = |section: usize, i: usize, public_idx: usize| match &pass_slot_inputs {
+        Some(pass_slot_inputs) => &pass_slot_inputs[section * files_per_step + i],
+        None => &z[public_idx],
+    };
+
     // Extract public ledger indices for each file slot using layout helper
     let ledger_indices_public: Vec<&AllocatedNum<F>> = (0..files_per_step)
-        .map(|i| &z[layout.idx_ledger(i)])
+        .map(|i| slot_input(0, i, layout.idx_ledger(i)))
         .collect();
 
     // Extract public depths for each file slot
     let depths_public: Vec<&AllocatedNum<F>> = (0..files_per_step)
-        .map(|i| &z[layout.idx_depth(i)])
+        .map(|i| slot_input(1, i, layout.idx_depth(i)))
         .collect();
 
     // Extract public seeds for each file slot
     let seeds_public: Vec<&AllocatedNum<F>> = (0..files_per_step)
-        .map(|i| &z[layout.idx_seed(i)])
+        .map(|i| slot_input(2, i, layout.idx_seed(i)))
         .collect();
 
-    #[cfg(debug_assertions)]
-    {
-        debug!("PorCircuit::synthesize() entry:");
-        debug!("  - file_tree_depth (uniform): {}", file_tree_depth);
-        debug!("  - aggregated_tree_depth: {}", aggregated_tree_depth);
-        debug!("  - has witness: {}", witness.is_some());
-        if let Some(circuit_witness) = witness {
-            debug!("  - total witnesses: {}", circuit_witness.witnesses().len());
-            debug!("  - num_real_files: {}", circuit_witness.num_real_files());
-            for (i, w) in circuit_witness.witnesses().iter().enumerate().take(5) {
-                // Show first 5
-                debug!("    - witness {}: actual_depth={}", i, w.actual_depth);
-            }
-        }
-        debug!(
-            "  - Input z[{}] (aggregated_root): {:?}",
-            layout.idx_agg_root(),
-            root.get_value()
-        );
-        debug!(
-            "  - Input z[{}] (state_in): {:?}",
-            layout.idx_state_in(),
-            state_in.get_value()
-        );
-        for (i, idx) in ledger_indices_public.iter().enumerate() {
-            debug!(
-                "  - Input z[{}] (ledger_index_{}): {:?}",
-                layout.idx_ledger(i),
-                i,
-                idx.get_value()
-            );
-        }
-        for (i, depth) in depths_public.iter().enumerate() {
-            debug!(
-                "  - Input z[{}] (depth_{}): {:?}",
-                layout.idx_depth(i),
-                i,
-                depth.get_value()
-            );
-        }
-        for (i, seed) in seeds_public.iter().enumerate() {
-            debug!(
-                "  - Input z[{}] (seed_{}): {:?}",
-                layout.idx_seed(i),
-                i,
-                seed.get_value()
-            );
-        }
-    }
+    debug!(
+        file_tree_depth,
+        aggregated_tree_depth,
+        has_witness = witness.is_some(),
+        num_witnesses = witness.map(|w| w.witnesses().len()),
+        num_real_files = witness.map(|w| w.num_real_files()),
+        "PorCircuit::synthesize() entry"
+    );
+    debug!(
+        aggregated_root = ?root.get_value(),
+        state_in = ?state_in.get_value(),
+        ledger_indices = ?ledger_indices_public.iter().map(|n| n.get_value()).collect::<Vec<_>>(),
+        depths = ?depths_public.iter().map(|n| n.get_value()).collect::<Vec<_>>(),
+        seeds = ?seeds_public.iter().map(|n| n.get_value()).collect::<Vec<_>>(),
+        "PorCircuit::synthesize() public inputs"
+    );
 
     // CircuitWitness ensures we have exactly the right number of witnesses,
     // properly padded, with padding determined by actual_depth == 0
@@ -127,7 +165,7 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
         let files_count = files_per_step;
         let default_witness = FileProofWitness {
             leaf: F::ZERO,
-            file_siblings: vec![F::ZERO; file_tree_depth],
+            file_siblings: vec![F::ZERO; tree_arity.siblings_per_level() * file_tree_depth],
             file_root: F::ZERO,
             actual_depth: 0,
             agg_siblings: vec![F::ZERO; aggregated_tree_depth.max(1)],
@@ -145,40 +183,127 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
 
     let witnesses = circuit_witness.witnesses();
 
-    #[cfg(debug_assertions)]
-    {
-        debug!("  - Witness count: {}", witnesses.len());
-        debug!(
-            "  - Number of actual files: {}",
-            circuit_witness.num_real_files()
-        );
-    }
-
     // Track state through all file verifications for replay protection
     let mut current_state = state_in.clone();
 
     // Collect public leaf values (gated by depth > 0)
     let mut public_leaf_values: Vec<AllocatedNum<F>> = Vec::new();
+    // Constrained seed outputs in per-step and explicit-index modes (one accumulator per slot)
+    let mut seed_accumulators: Vec<AllocatedNum<F>> = Vec::new();
+    // Activity gate of each slot, for strict-mode distinctness checks
+    let mut slot_gates: Vec<Boolean> = Vec::with_capacity(files_per_step);
+
+    // Aggregation levels are gated like file tree levels so one shape serves every ledger
+    // up to `aggregated_tree_depth`. The flags are shared by all slots and must be monotone
+    // (`flag[i + 1] * (1 - flag[i]) = 0`), so the active levels are exactly the lowest
+    // `ledger_depth` ones and the path bits they consume are the low bits of the index.
+    let active_agg_levels = circuit_witness.active_agg_levels(aggregated_tree_depth);
+    let agg_active_flags: Vec<Boolean> = (0..aggregated_tree_depth)
+        .map(|level| {
+            let bit = AllocatedBit::alloc(
+                cs.namespace(|| format!("agg_active_flag_lvl{}", level)),
+                Some(level < active_agg_levels),
+            )?;
+            Ok(Boolean::from(bit))
+        })
+        .collect::<Result<_, SynthesisError>>()?;
+    for (level, pair) in agg_active_flags.windows(2).enumerate() {
+        cs.enforce(
+            || format!("agg_active_flags_monotone_{}", level),
+            |lc| lc + &pair[1].lc(CS::one(), F::ONE),
+            |lc| lc + &pair[0].not().lc(CS::one(), F::ONE),
+            |lc| lc,
+        );
+    }
+
+    // Every slot hashes under the same domain tags, so each tag is allocated (and
+    // constrained) once per step, as is the zero used for dummy slots and empty sums
+    let mut step_tags = Vec::new();
+    if step_seeds.is_some() {
+        step_tags.push(domain_tags::seed_schedule());
+    }
+    if explicit_indices.is_some() {
+        step_tags.push(domain_tags::explicit_index());
+    } else {
+        step_tags.push(domain_tags::challenge());
+        if aggregated_tree_depth > 0 {
+            step_tags.push(domain_tags::challenge_per_file());
+        }
+    }
+    step_tags.push(match tree_arity {
+        TreeArity::Binary => domain_tags::node(),
+        TreeArity::Arity4 => domain_tags::node_arity4(),
+    });
+    if aggregated_tree_depth > 0 {
+        step_tags.push(domain_tags::node());
+    }
+    step_tags.push(tree_arity.root_commitment_tag());
+    step_tags.push(domain_tags::state_update());
+    if pass_inputs.is_some() {
+        step_tags.push(domain_tags::pass_schedule());
+    }
+    if private_leaves {
+        step_tags.push(domain_tags::leaf_blinding());
+        step_tags.push(domain_tags::leaf_commitment());
+    }
+    let tags = TagConstants::alloc(cs.namespace(|| "step_tags"), &step_tags)?;
+    let zero = AllocatedNum::alloc(cs.namespace(|| "zero"), || Ok(F::ZERO))?;
 
     // Process each witness in the guaranteed structure
     for (file_idx, witness) in witnesses.iter().enumerate() {
         let mut file_cs = cs.namespace(|| format!("file_{}", file_idx));
 
-        #[cfg(debug_assertions)]
-        {
-            debug!("synthesize() - Processing file_idx={}:", file_idx);
-            debug!("  - Leaf value: {:?}", witness.leaf);
-            debug!("  - File siblings count: {}", witness.file_siblings.len());
-            debug!("  - File root: {:?}", witness.file_root);
-            debug!("  - Actual depth: {}", witness.actual_depth);
-            debug!("  - Agg siblings count: {}", witness.agg_siblings.len());
-            debug!("  - Ledger index: {}", witness.ledger_index);
-        }
+        debug!(
+            file_idx,
+            leaf = ?witness.leaf,
+            file_siblings = witness.file_siblings.len(),
+            file_root = ?witness.file_root,
+            actual_depth = witness.actual_depth,
+            agg_siblings = witness.agg_siblings.len(),
+            ledger_index = witness.ledger_index,
+            "synthesize() - processing file"
+        );
 
         // Get public depth and seed for this slot
         let depth_public = depths_public[file_idx];
         let seed_public = seeds_public[file_idx];
 
+        // In per-step mode the challenge uses a private step seed, which is folded into the
+        // slot's seed accumulator so the verifier can check the whole schedule at the end
+        let challenge_seed = match step_seeds {
+            Some(step_seeds) => {
+                let step_seed = AllocatedNum::alloc(file_cs.namespace(|| "step_seed"), || {
+                    Ok(step_seeds.get(file_idx).copied().unwrap_or(F::ZERO))
+                })?;
+                seed_accumulators.push(poseidon_hash_shared_tag_gadget(
+                    file_cs.namespace(|| "seed_schedule_accumulate"),
+                    tags.get(domain_tags::seed_schedule()),
+                    seed_public,
+                    &step_seed,
+                )?);
+                step_seed
+            }
+            None => seed_public.clone(),
+        };
+
+        // In explicit-index mode the opened leaf is given per step rather than derived, and
+        // is folded into the slot's seed accumulator just like a per-step seed
+        let explicit_index = match explicit_indices {
+            Some(explicit_indices) => {
+                let step_index = AllocatedNum::alloc(file_cs.namespace(|| "step_index"), || {
+                    Ok(explicit_indices.get(file_idx).copied().unwrap_or(F::ZERO))
+                })?;
+                seed_accumulators.push(poseidon_hash_shared_tag_gadget(
+                    file_cs.namespace(|| "explicit_index_accumulate"),
+                    tags.get(domain_tags::explicit_index()),
+                    seed_public,
+                    &step_index,
+                )?);
+                Some(step_index)
+            }
+            None => None,
+        };
+
         // 1. Allocate leaf for this file
         let leaf_alloc = AllocatedNum::alloc(file_cs.namespace(|| "leaf"), || Ok(witness.leaf))?;
 
@@ -194,74 +319,80 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
             })
             .collect::<Result<_, _>>()?;
 
-        // 2. Calculate challenge index for this file
-        // Include file_idx to ensure different challenges per file (only for multi-file)
-        let file_idx_field = if aggregated_tree_depth > 0 {
-            F::from(file_idx as u64)
-        } else {
-            F::ZERO // Single-file doesn't use file_idx
-        };
-        let file_idx_alloc =
-            AllocatedNum::alloc(file_cs.namespace(|| "file_index"), || Ok(file_idx_field))?;
-
-        #[cfg(debug_assertions)]
-        {
-            debug!(
-                "synthesize() - Calculating challenge for file_idx={}:",
-                file_idx
-            );
-            if let (Some(seed_val), Some(state_val)) =
-                (seed_public.get_value(), current_state.get_value())
-            {
-                debug!("  - Seed for this file: {:?}", seed_val);
-                debug!("  - Current state: {:?}", state_val);
-            }
-        }
+        let num_path_bits = tree_arity.index_bits(file_tree_depth);
+        let file_path_indices: Vec<Boolean> = match &explicit_index {
+            // Explicit indices must fit the shape's index width; the verifier additionally
+            // checks each one against the file's own leaf count
+            Some(step_index) => bounded_index_bits(
+                file_cs.namespace(|| "explicit_index_bits"),
+                step_index,
+                num_path_bits,
+            )?,
+            None => {
+                // 2. Calculate challenge index for this file
+                // Include file_idx to ensure different challenges per file (only for multi-file)
+                let file_idx_field = if aggregated_tree_depth > 0 {
+                    F::from(file_idx as u64)
+                } else {
+                    F::ZERO // Single-file doesn't use file_idx
+                };
+                let file_idx_alloc =
+                    AllocatedNum::alloc(file_cs.namespace(|| "file_index"), || Ok(file_idx_field))?;
 
-        // 2. Calculate challenge index for this file using per-file seed
-        let challenge_with_idx = {
-            let challenge = poseidon_hash_tagged_gadget(
-                file_cs.namespace(|| "challenge_hash"),
-                domain_tags::challenge(),
-                seed_public,
-                &current_state,
-            )?;
+                debug!(
+                    file_idx,
+                    seed = ?seed_public.get_value(),
+                    state = ?current_state.get_value(),
+                    "synthesize() - calculating challenge"
+                );
 
-            if aggregated_tree_depth > 0 {
-                // Multi-file: use domain-separated hash to combine challenge with file_idx
-                poseidon_hash_tagged_gadget(
-                    file_cs.namespace(|| "challenge_with_file_idx"),
-                    domain_tags::challenge_per_file(),
-                    &challenge,
-                    &file_idx_alloc,
-                )?
-            } else {
-                // Single-file: use challenge directly without file_idx
-                challenge
+                // 2. Calculate challenge index for this file using per-file seed
+                let challenge_with_idx = {
+                    let challenge = poseidon_hash_shared_tag_gadget(
+                        file_cs.namespace(|| "challenge_hash"),
+                        tags.get(domain_tags::challenge()),
+                        &challenge_seed,
+                        &current_state,
+                    )?;
+
+                    if aggregated_tree_depth > 0 {
+                        // Multi-file: use domain-separated hash to combine challenge with file_idx
+                        poseidon_hash_shared_tag_gadget(
+                            file_cs.namespace(|| "challenge_with_file_idx"),
+                            tags.get(domain_tags::challenge_per_file()),
+                            &challenge,
+                            &file_idx_alloc,
+                        )?
+                    } else {
+                        // Single-file: use challenge directly without file_idx
+                        challenge
+                    }
+                };
+
+                // 3. Get binary decomposition of challenge and extract path bits
+                let index_bits = {
+                    let mut bits_ns = file_cs.namespace(|| "challenge_with_idx_bits");
+                    challenge_with_idx.to_bits_le(&mut bits_ns)?
+                };
+                // Build exactly the index bits for file_tree_depth levels, allocating false for padding (not constants!)
+                let mut file_path_indices: Vec<Boolean> = Vec::with_capacity(num_path_bits);
+                for i in 0..num_path_bits {
+                    if let Some(b) = index_bits.get(i) {
+                        file_path_indices.push(b.clone());
+                    } else {
+                        // Allocate padding bit as variable (not constant)
+                        let pad = AllocatedBit::alloc(
+                            file_cs.namespace(|| format!("file_pad_bit_{}", i)),
+                            Some(false),
+                        )
+                        .map_err(|_| SynthesisError::AssignmentMissing)?;
+                        file_path_indices.push(Boolean::from(pad));
+                    }
+                }
+                file_path_indices
             }
         };
 
-        // 3. Get binary decomposition of challenge and extract path bits
-        let index_bits = {
-            let mut bits_ns = file_cs.namespace(|| "challenge_with_idx_bits");
-            challenge_with_idx.to_bits_le(&mut bits_ns)?
-        };
-        // Build exactly file_tree_depth bits, allocating false for padding (not constants!)
-        let mut file_path_indices: Vec<Boolean> = Vec::with_capacity(file_tree_depth);
-        for i in 0..file_tree_depth {
-            if let Some(b) = index_bits.get(i) {
-                file_path_indices.push(b.clone());
-            } else {
-                // Allocate padding bit as variable (not constant)
-                let pad = AllocatedBit::alloc(
-                    file_cs.namespace(|| format!("file_pad_bit_{}", i)),
-                    Some(false),
-                )
-                .map_err(|_| SynthesisError::AssignmentMissing)?;
-                file_path_indices.push(Boolean::from(pad));
-            }
-        }
-
         // 4. Verify Merkle path within this file's tree (gated for correct depth)
         // IMPORTANT: active_flags must be allocated variables (not constants) to maintain uniform constraint count
         // Boolean::Constant() would create different circuit shapes between parameter generation and proving
@@ -296,21 +427,34 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
 
         let gate_for_slot = depth_is_positive;
 
-        let computed_file_root = verify_merkle_path_gated(
-            file_cs.namespace(|| "verify_file_merkle"),
-            &leaf_alloc,
-            &file_siblings_alloc,
-            &file_path_indices,
-            Some(&active_flags),
-            file_tree_depth,
-        )?;
+        let computed_file_root = match tree_arity {
+            TreeArity::Binary => verify_merkle_path_gated(
+                file_cs.namespace(|| "verify_file_merkle"),
+                &leaf_alloc,
+                &file_siblings_alloc,
+                &file_path_indices,
+                Some(&active_flags),
+                file_tree_depth,
+                hash_profile,
+                tags.get(domain_tags::node()),
+            )?,
+            TreeArity::Arity4 => verify_merkle_path4_gated(
+                file_cs.namespace(|| "verify_file_merkle4"),
+                &leaf_alloc,
+                &file_siblings_alloc,
+                &file_path_indices,
+                Some(&active_flags),
+                file_tree_depth,
+                hash_profile,
+                tags.get(domain_tags::node_arity4()),
+            )?,
+        };
 
-        #[cfg(debug_assertions)]
-        {
-            if let Some(computed_val) = computed_file_root.get_value() {
-                debug!("synthesize() - Computed file root: {:?}", computed_val);
-            }
-        }
+        debug!(
+            file_idx,
+            computed_file_root = ?computed_file_root.get_value(),
+            "synthesize() - computed file root"
+        );
 
         // Compute declared depth as sum of active_flags
         let depth_num = if active_flags.is_empty() {
@@ -319,8 +463,7 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
                 Ok(F::from(witness.actual_depth as u64))
             })?
         } else {
-            let mut sum_active =
-                AllocatedNum::alloc(file_cs.namespace(|| "sum_active_init"), || Ok(F::ZERO))?;
+            let mut sum_active = zero.clone();
             for (j, flag) in active_flags.iter().enumerate() {
                 let new_sum = AllocatedNum::alloc(
                     file_cs.namespace(|| format!("sum_active_file{}_lvl{}", file_idx, j)),
@@ -349,10 +492,12 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
             sum_active
         };
 
-        // Compute rc = Poseidon(TAG_RC, root, depth) for this file
-        let rc = poseidon_hash_tagged_gadget(
+        // Compute rc = Poseidon(TAG_RC, root, depth) for this file (arity-4 trees use TAG_RC4),
+        // under the file's hash profile
+        let rc = poseidon_hash_shared_tag_gadget_with(
             file_cs.namespace(|| "compute_rc"),
-            domain_tags::root_commitment(),
+            hash_profile,
+            tags.get(tree_arity.root_commitment_tag()),
             &computed_file_root,
             &depth_num,
         )?;
@@ -376,8 +521,33 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
                 .cloned()
                 .collect();
 
-            // === PHASE 2: Removed ledger index range checks ===
-            // Phase 2: Rely on verifier-provided public indices (already range-checked by verifier)
+            // Lenient mode relies on the verifier's range check of the public indices.
+            // Strict mode enforces it here: every bit above the aggregation path is zero,
+            // and so is every bit of an inactive (padding) level, so the index is exactly
+            // the path and cannot alias a larger value.
+            if circuit_mode.is_strict() {
+                file_cs.enforce(
+                    || "ledger_index_in_range",
+                    |lc| {
+                        ledger_index_bits
+                            .iter()
+                            .skip(aggregated_tree_depth)
+                            .fold(lc, |lc, bit| lc + &bit.lc(CS::one(), F::ONE))
+                    },
+                    |lc| lc + CS::one(),
+                    |lc| lc,
+                );
+                for (level, (bit, flag)) in
+                    agg_path_indices.iter().zip(&agg_active_flags).enumerate()
+                {
+                    file_cs.enforce(
+                        || format!("ledger_index_bit_{}_within_ledger", level),
+                        |lc| lc + &bit.lc(CS::one(), F::ONE),
+                        |lc| lc + &flag.not().lc(CS::one(), F::ONE),
+                        |lc| lc,
+                    );
+                }
+            }
 
             // Allocate aggregation siblings from witness
             let agg_siblings_alloc: Vec<AllocatedNum<F>> = witness
@@ -397,7 +567,9 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
                 &rc, // Verify rc (not just root) is in the ledger
                 &agg_siblings_alloc,
                 &agg_path_indices,
+                Some(&agg_active_flags),
                 aggregated_tree_depth,
+                tags.get(domain_tags::node()),
             )?;
 
             // Verify aggregated root matches public input (gated by gate_for_slot)
@@ -419,22 +591,17 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
         }
 
         // 6. Update state with this file's leaf
-        #[cfg(debug_assertions)]
-        {
-            if let (Some(state_val), Some(leaf_val)) =
-                (current_state.get_value(), leaf_alloc.get_value())
-            {
-                debug!(
-                    "Circuit state update file {}: H_state({:?}, {:?})",
-                    file_idx, state_val, leaf_val
-                );
-            }
-        }
+        debug!(
+            file_idx,
+            state = ?current_state.get_value(),
+            leaf = ?leaf_alloc.get_value(),
+            "synthesize() - state update H_state(state, leaf)"
+        );
 
         // Conditionally update state based on gate_for_slot with domain separation
-        let updated_state = poseidon_hash_tagged_gadget(
+        let updated_state = poseidon_hash_shared_tag_gadget(
             file_cs.namespace(|| "state_update"),
-            domain_tags::state_update(),
+            tags.get(domain_tags::state_update()),
             &current_state,
             &leaf_alloc,
         )?;
@@ -447,32 +614,83 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
             &updated_state, // if_true: when active, use updated state
         )?;
 
-        // Expose the challenged leaf as public output (gated by gate_for_slot)
-        // Allocate a canonical zero for dummy slots
-        let zero = AllocatedNum::alloc(file_cs.namespace(|| "zero"), || Ok(F::ZERO))?;
+        // In private mode, replace the raw leaf with H(TAG_LEAF_COMMITMENT, leaf, blinding).
+        // The blinding is derived from the challenge seed and the first private sibling,
+        // so it is deterministic for the prover but never part of the public I/O.
+        // This branch depends only on the circuit shape, not on witness values.
+        let exposed_leaf = if private_leaves {
+            let blinding_source = file_siblings_alloc.first().unwrap_or(&zero);
+            let blinding = poseidon_hash_shared_tag_gadget(
+                file_cs.namespace(|| "leaf_blinding"),
+                tags.get(domain_tags::leaf_blinding()),
+                &challenge_seed,
+                blinding_source,
+            )?;
+            poseidon_hash_shared_tag_gadget(
+                file_cs.namespace(|| "leaf_commitment"),
+                tags.get(domain_tags::leaf_commitment()),
+                &leaf_alloc,
+                &blinding,
+            )?
+        } else {
+            leaf_alloc.clone()
+        };
 
-        // Select: leaf_pub = gate_for_slot ? leaf_alloc : zero
+        // Expose the challenged leaf (or its commitment) as public output (gated by gate_for_slot)
+        // Select: leaf_pub = gate_for_slot ? exposed_leaf : zero
         let leaf_pub = conditional_select(
             file_cs.namespace(|| "public_leaf_select"),
             &gate_for_slot,
-            &zero,       // if_false: when not active, output zero
-            &leaf_alloc, // if_true: when active, output the actual leaf
+            &zero,         // if_false: when not active, output zero
+            &exposed_leaf, // if_true: when active, output the leaf or its commitment
         )?;
 
         public_leaf_values.push(leaf_pub);
+        slot_gates.push(gate_for_slot);
 
-        #[cfg(debug_assertions)]
-        {
-            if let Some(new_state) = current_state.get_value() {
-                debug!("  = {:?}", new_state);
-            }
-        }
+        debug!(file_idx, state = ?current_state.get_value(), "synthesize() - updated state");
     } // End of file loop
 
     // Security comes from public depth binding per slot
 
+    // Lenient mode trusts the verifier to provide distinct indices. Strict mode requires
+    // `(index_i - index_j) * inverse = active_i AND active_j` for every slot pair, which has
+    // a solution only if two active slots differ; padding slots are unconstrained.
+    if circuit_mode.is_strict() && aggregated_tree_depth > 0 {
+        let slots: Vec<_> = slot_gates.iter().zip(&ledger_indices_public).collect();
+        for (i, (gate_i, index_i)) in slots.iter().enumerate() {
+            for (j, (gate_j, index_j)) in slots.iter().enumerate().skip(i + 1) {
+                let mut pair_cs = cs.namespace(|| format!("distinct_ledger_index_{}_{}", i, j));
+                let both_active =
+                    Boolean::and(pair_cs.namespace(|| "both_active"), gate_i, gate_j)?;
+                let inverse =
+                    AllocatedNum::alloc(pair_cs.namespace(|| "difference_inverse"), || {
+                        let a = index_i
+                            .get_value()
+                            .ok_or(SynthesisError::AssignmentMissing)?;
+                        let b = index_j
+                            .get_value()
+                            .ok_or(SynthesisError::AssignmentMissing)?;
+                        let active = both_active
+                            .get_value()
+                            .ok_or(SynthesisError::AssignmentMissing)?;
+                        Ok(if active {
+                            Option::<F>::from((a - b).invert()).unwrap_or(F::ZERO)
+                        } else {
+                            F::ZERO
+                        })
+                    })?;
+                pair_cs.enforce(
+                    || "difference_invertible_when_active",
+                    |lc| lc + index_i.get_variable() - index_j.get_variable(),
+                    |lc| lc + inverse.get_variable(),
+                    |lc| lc + &both_active.lc(CS::one(), F::ONE),
+                );
+            }
+        }
+    }
+
     // Verifier provides public indices and ensures they are valid
-    // Circuit trusts verifier-provided indices (no ordering constraints)
 
     // Security comes from public depth binding and rc membership in ledger
 
@@ -482,36 +700,65 @@ pub fn synthesize_por_circuit<F: PrimeField + PrimeFieldBits, CS: ConstraintSyst
         root.get_value().ok_or(SynthesisError::AssignmentMissing)
     })?;
 
-    // Carry forward all ledger indices
-    let mut ledger_indices_out = Vec::new();
-    for (i, idx) in ledger_indices_public.iter().enumerate() {
-        let idx_out =
-            AllocatedNum::alloc(cs.namespace(|| format!("ledger_index_out_{}", i)), || {
-                idx.get_value().ok_or(SynthesisError::AssignmentMissing)
-            })?;
-
-        ledger_indices_out.push(idx_out);
-    }
+    let (ledger_indices_out, depths_out, seeds_out) = match &pass_slot_inputs {
+        // Fold each slot's pass inputs into its public inputs (the ledger index, depth and
+        // seed sections are contiguous, in the order of the pass inputs)
+        Some(pass_slot_inputs) => {
+            let mut accumulators = Vec::with_capacity(3 * files_per_step);
+            for (k, pass_input) in pass_slot_inputs.iter().enumerate() {
+                accumulators.push(poseidon_hash_shared_tag_gadget(
+                    cs.namespace(|| format!("pass_schedule_accumulate_{}", k)),
+                    tags.get(domain_tags::pass_schedule()),
+                    &z[layout.idx_ledger(0) + k],
+                    pass_input,
+                )?);
+            }
+            let seeds_out = accumulators.split_off(2 * files_per_step);
+            let depths_out = accumulators.split_off(files_per_step);
+            (accumulators, depths_out, seeds_out)
+        }
+        None => {
+            // Carry forward all ledger indices
+            let mut ledger_indices_out = Vec::new();
+            for (i, idx) in ledger_indices_public.iter().enumerate() {
+                let idx_out = AllocatedNum::alloc(
+                    cs.namespace(|| format!("ledger_index_out_{}", i)),
+                    || idx.get_value().ok_or(SynthesisError::AssignmentMissing),
+                )?;
 
-    // Carry forward all depths
-    let mut depths_out = Vec::new();
-    for (i, depth) in depths_public.iter().enumerate() {
-        let depth_out = AllocatedNum::alloc(cs.namespace(|| format!("depth_out_{}", i)), || {
-            depth.get_value().ok_or(SynthesisError::AssignmentMissing)
-        })?;
+                ledger_indices_out.push(idx_out);
+            }
 
-        depths_out.push(depth_out);
-    }
+            // Carry forward all depths
+            let mut depths_out = Vec::new();
+            for (i, depth) in depths_public.iter().enumerate() {
+                let depth_out =
+                    AllocatedNum::alloc(cs.namespace(|| format!("depth_out_{}", i)), || {
+                        depth.get_value().ok_or(SynthesisError::AssignmentMissing)
+                    })?;
 
-    // Carry forward all seeds
-    let mut seeds_out = Vec::new();
-    for (i, seed) in seeds_public.iter().enumerate() {
-        let seed_out = AllocatedNum::alloc(cs.namespace(|| format!("seed_out_{}", i)), || {
-            seed.get_value().ok_or(SynthesisError::AssignmentMissing)
-        })?;
+                depths_out.push(depth_out);
+            }
 
-        seeds_out.push(seed_out);
-    }
+            // Carry forward all seeds (per-step and explicit-index modes output the
+            // constrained accumulators instead)
+            let seeds_out = if step_seeds.is_some() || explicit_indices.is_some() {
+                seed_accumulators
+            } else {
+                let mut seeds_out = Vec::new();
+                for (i, seed) in seeds_public.iter().enumerate() {
+                    let seed_out =
+                        AllocatedNum::alloc(cs.namespace(|| format!("seed_out_{}", i)), || {
+                            seed.get_value().ok_or(SynthesisError::AssignmentMissing)
+                        })?;
+
+                    seeds_out.push(seed_out);
+                }
+                seeds_out
+            };
+            (ledger_indices_out, depths_out, seeds_out)
+        }
+    };
 
     // Build output vector: [root_out, current_state, ledger_indices..., depths..., seeds..., leaves...]
     let mut outputs = vec![root_out, current_state];
@@ -3,6 +3,8 @@
 //! This module defines the witness types and ensures proper padding and structure
 //! for Nova's folding requirements.
 
+use crate::error::WitnessError;
+use crate::merkle::TreeArity;
 use ff::PrimeField;
 
 /// Witness data for a single file's proof.
@@ -11,7 +13,8 @@ use ff::PrimeField;
 pub struct FileProofWitness<F: PrimeField> {
     /// The leaf value being proven
     pub leaf: F,
-    /// The siblings in the file's Merkle tree (padded to file_tree_depth)
+    /// The siblings in the file's Merkle tree (padded to file_tree_depth levels; arity-4
+    /// trees store three siblings per level, flattened)
     pub file_siblings: Vec<F>,
     /// The root of this file's Merkle tree
     pub file_root: F,
@@ -31,7 +34,7 @@ pub struct FileProofWitness<F: PrimeField> {
 ///
 /// ## Invariants
 ///
-/// - Contains exactly `next_power_of_two(num_files)` witnesses (real files + padding)
+/// - Contains exactly `files_per_step` witnesses (real files + padding)
 /// - Real file witnesses come first, followed by padding witnesses
 /// - All padding witnesses have `actual_depth: 0` (Phase 3: no is_real field)
 /// - The structure is guaranteed to be uniform for Nova's folding requirements
@@ -41,6 +44,9 @@ pub struct CircuitWitness<F: PrimeField> {
     pub witnesses: Vec<FileProofWitness<F>>,
     /// The number of real (non-padding) files in this witness
     pub(crate) num_real_files: usize,
+    /// Depth of the ledger the slots open, when the circuit's aggregation path is padded
+    /// beyond it (`None` means every aggregation level is active)
+    pub(crate) ledger_depth: Option<usize>,
 }
 
 impl<F: PrimeField> CircuitWitness<F> {
@@ -49,9 +55,29 @@ impl<F: PrimeField> CircuitWitness<F> {
         Self {
             witnesses,
             num_real_files,
+            ledger_depth: None,
         }
     }
 
+    /// Returns this witness with the depth of the ledger its slots open.
+    ///
+    /// Circuits synthesized at a padded `aggregated_tree_depth` activate only the lowest
+    /// `ledger_depth` aggregation levels; `agg_siblings` stay padded to the full depth.
+    pub fn with_ledger_depth(mut self, ledger_depth: Option<usize>) -> Self {
+        self.ledger_depth = ledger_depth;
+        self
+    }
+
+    /// Depth of the ledger the slots open, if set with [`Self::with_ledger_depth`].
+    pub fn ledger_depth(&self) -> Option<usize> {
+        self.ledger_depth
+    }
+
+    /// Number of active aggregation levels in a circuit of the given aggregated depth.
+    pub fn active_agg_levels(&self, aggregated_tree_depth: usize) -> usize {
+        self.ledger_depth.unwrap_or(aggregated_tree_depth)
+    }
+
     /// Get the witnesses for use in the circuit
     pub fn witnesses(&self) -> &[FileProofWitness<F>] {
         &self.witnesses
@@ -61,4 +87,243 @@ impl<F: PrimeField> CircuitWitness<F> {
     pub(crate) fn num_real_files(&self) -> usize {
         self.num_real_files
     }
+
+    /// Checks that this witness fits the given circuit shape.
+    ///
+    /// Malformed witnesses otherwise surface as unsatisfied constraints or index panics
+    /// inside synthesis. Checks, in order: slot count, real file count, and per slot the
+    /// file sibling length, depth bound, aggregation sibling length and ledger index range.
+    /// Ledger indices are only checked for multi-file shapes (`aggregated_tree_depth > 0`),
+    /// since single-file circuits ignore them, and must fit the ledger depth when one is set.
+    pub fn validate(
+        &self,
+        files_per_step: usize,
+        file_tree_depth: usize,
+        aggregated_tree_depth: usize,
+    ) -> Result<(), WitnessError> {
+        self.validate_for_tree_arity(
+            files_per_step,
+            file_tree_depth,
+            aggregated_tree_depth,
+            TreeArity::Binary,
+        )
+    }
+
+    /// Like [`Self::validate`], for circuits over file trees of the given arity.
+    ///
+    /// Each slot must carry `tree_arity.siblings_per_level()` file siblings per level.
+    pub fn validate_for_tree_arity(
+        &self,
+        files_per_step: usize,
+        file_tree_depth: usize,
+        aggregated_tree_depth: usize,
+        tree_arity: TreeArity,
+    ) -> Result<(), WitnessError> {
+        let expected_file_siblings = tree_arity.siblings_per_level() * file_tree_depth;
+        if self.witnesses.len() != files_per_step {
+            return Err(WitnessError::SlotCount {
+                expected: files_per_step,
+                got: self.witnesses.len(),
+            });
+        }
+        if self.num_real_files > self.witnesses.len() {
+            return Err(WitnessError::RealFileCount {
+                num_real_files: self.num_real_files,
+                slots: self.witnesses.len(),
+            });
+        }
+        let active_agg_levels = self.active_agg_levels(aggregated_tree_depth);
+        if active_agg_levels > aggregated_tree_depth {
+            return Err(WitnessError::LedgerDepthOutOfBounds {
+                depth: active_agg_levels,
+                max: aggregated_tree_depth,
+            });
+        }
+
+        for (slot, witness) in self.witnesses.iter().enumerate() {
+            if witness.file_siblings.len() != expected_file_siblings {
+                return Err(WitnessError::FileSiblingsLength {
+                    slot,
+                    expected: expected_file_siblings,
+                    got: witness.file_siblings.len(),
+                });
+            }
+            if witness.actual_depth > file_tree_depth {
+                return Err(WitnessError::DepthOutOfBounds {
+                    slot,
+                    depth: witness.actual_depth,
+                    max: file_tree_depth,
+                });
+            }
+            if witness.agg_siblings.len() != aggregated_tree_depth {
+                return Err(WitnessError::AggSiblingsLength {
+                    slot,
+                    expected: aggregated_tree_depth,
+                    got: witness.agg_siblings.len(),
+                });
+            }
+            let index_out_of_range = 1usize
+                .checked_shl(active_agg_levels as u32)
+                .is_some_and(|leaf_count| witness.ledger_index >= leaf_count);
+            if aggregated_tree_depth > 0 && index_out_of_range {
+                return Err(WitnessError::LedgerIndexOutOfBounds {
+                    slot,
+                    index: witness.ledger_index,
+                    depth: active_agg_levels,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+    use ff::Field;
+
+    const FILE_DEPTH: usize = 3;
+    const AGG_DEPTH: usize = 2;
+
+    fn slot(actual_depth: usize) -> FileProofWitness<FieldElement> {
+        FileProofWitness {
+            leaf: FieldElement::from(1u64),
+            file_siblings: vec![FieldElement::ZERO; FILE_DEPTH],
+            file_root: FieldElement::ZERO,
+            actual_depth,
+            agg_siblings: vec![FieldElement::ZERO; AGG_DEPTH],
+            ledger_index: 0,
+        }
+    }
+
+    fn witness() -> CircuitWitness<FieldElement> {
+        CircuitWitness::new(vec![slot(FILE_DEPTH), slot(0)], 1)
+    }
+
+    #[test]
+    fn test_validate_accepts_well_formed_witness() {
+        assert_eq!(witness().validate(2, FILE_DEPTH, AGG_DEPTH), Ok(()));
+
+        // Single-file shapes ignore ledger indices
+        let mut single = CircuitWitness::new(vec![slot(FILE_DEPTH)], 1);
+        single.witnesses[0].agg_siblings.clear();
+        single.witnesses[0].ledger_index = 9;
+        assert_eq!(single.validate(1, FILE_DEPTH, 0), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_each_malformed_case() {
+        assert_eq!(
+            witness().validate(4, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::SlotCount {
+                expected: 4,
+                got: 2
+            })
+        );
+
+        let mut w = witness();
+        w.num_real_files = 3;
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::RealFileCount {
+                num_real_files: 3,
+                slots: 2
+            })
+        );
+
+        let mut w = witness();
+        w.witnesses[1].file_siblings.pop();
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::FileSiblingsLength {
+                slot: 1,
+                expected: FILE_DEPTH,
+                got: FILE_DEPTH - 1
+            })
+        );
+
+        let mut w = witness();
+        w.witnesses[0].actual_depth = FILE_DEPTH + 1;
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::DepthOutOfBounds {
+                slot: 0,
+                depth: FILE_DEPTH + 1,
+                max: FILE_DEPTH
+            })
+        );
+
+        let mut w = witness();
+        w.witnesses[0].agg_siblings.push(FieldElement::ZERO);
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::AggSiblingsLength {
+                slot: 0,
+                expected: AGG_DEPTH,
+                got: AGG_DEPTH + 1
+            })
+        );
+
+        let mut w = witness();
+        w.witnesses[1].ledger_index = 1 << AGG_DEPTH;
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::LedgerIndexOutOfBounds {
+                slot: 1,
+                index: 1 << AGG_DEPTH,
+                depth: AGG_DEPTH
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_checks_ledger_depth_of_padded_witness() {
+        // Siblings stay padded to the circuit depth; indices must fit the ledger depth
+        let w = witness().with_ledger_depth(Some(1));
+        assert_eq!(w.validate(2, FILE_DEPTH, AGG_DEPTH), Ok(()));
+
+        let mut w = witness().with_ledger_depth(Some(1));
+        w.witnesses[0].ledger_index = 2;
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::LedgerIndexOutOfBounds {
+                slot: 0,
+                index: 2,
+                depth: 1
+            })
+        );
+
+        let w = witness().with_ledger_depth(Some(AGG_DEPTH + 1));
+        assert_eq!(
+            w.validate(2, FILE_DEPTH, AGG_DEPTH),
+            Err(WitnessError::LedgerDepthOutOfBounds {
+                depth: AGG_DEPTH + 1,
+                max: AGG_DEPTH
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_arity4_expects_three_siblings_per_level() {
+        let w = witness();
+        assert_eq!(
+            w.validate_for_tree_arity(2, FILE_DEPTH, AGG_DEPTH, TreeArity::Arity4),
+            Err(WitnessError::FileSiblingsLength {
+                slot: 0,
+                expected: 3 * FILE_DEPTH,
+                got: FILE_DEPTH
+            })
+        );
+
+        let mut w = witness();
+        for slot in &mut w.witnesses {
+            slot.file_siblings = vec![FieldElement::ZERO; 3 * FILE_DEPTH];
+        }
+        assert_eq!(
+            w.validate_for_tree_arity(2, FILE_DEPTH, AGG_DEPTH, TreeArity::Arity4),
+            Ok(())
+        );
+    }
 }
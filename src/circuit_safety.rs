@@ -1,7 +1,18 @@
 //! Compile-time and runtime safety checks for circuit uniformity.
 //!
 //! This module provides macros and functions to ensure that circuit
-//! constraint structures remain uniform across all execution paths.
+//! constraint structures remain uniform across all execution paths, and
+//! [`diagnose_unsatisfied`] to locate the constraint a bad witness breaks.
+
+use crate::circuit::PorCircuit;
+use ff::{PrimeField, PrimeFieldBits};
+use nova_snark::{
+    frontend::{
+        gadgets::num::AllocatedNum, util_cs::test_cs::TestConstraintSystem, ConstraintSystem,
+        Index, LinearCombination, SynthesisError, Variable,
+    },
+    traits::circuit::StepCircuit,
+};
 
 /// Macro to mark circuit code that MUST maintain uniform constraint count.
 ///
@@ -109,6 +120,263 @@ where
     result
 }
 
+/// The part of the PoR circuit a constraint belongs to, read from its namespace path.
+///
+/// Paths follow the namespace names used in `circuit::synth`; the first path segment that
+/// names a known section decides the class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintClass {
+    /// The challenged leaf's path to the file root (`verify_file_merkle`, `file_sibling_*`)
+    FileMerklePath,
+    /// The root commitment's path to the ledger root (`compute_rc`,
+    /// `verify_ledger_membership`, `agg_sibling_*`, `agg_active_flag*`)
+    AggregationPath,
+    /// The tie between the witness depth and the public depth (`depth_equals_public*`,
+    /// `public_depth_bits`, `active_flag_file*`, `sum_active*`, `depth_*`)
+    DepthBinding,
+    /// The state accumulator update (`state_update`, `gate_state_update`)
+    StateUpdate,
+    /// The computed root against the public root (`aggregated_root_matches_gated`,
+    /// `single_file_root_matches_gated`)
+    RootEquality,
+    /// Strict-mode ledger index checks (`ledger_index_*`, `distinct_ledger_index_*`)
+    LedgerIndex,
+    /// Any other constraint (challenge derivation, public outputs, ...)
+    Other,
+}
+
+impl ConstraintClass {
+    /// Classifies a constraint by its full namespace path (segments separated by `/`).
+    pub fn from_path(path: &str) -> Self {
+        path.split('/')
+            .map(Self::from_segment)
+            .find(|class| *class != ConstraintClass::Other)
+            .unwrap_or(ConstraintClass::Other)
+    }
+
+    fn from_segment(segment: &str) -> Self {
+        match segment {
+            "verify_file_merkle" | "verify_file_merkle4" => ConstraintClass::FileMerklePath,
+            s if s.starts_with("file_sibling_") => ConstraintClass::FileMerklePath,
+            "compute_rc" | "verify_ledger_membership" => ConstraintClass::AggregationPath,
+            s if s.starts_with("agg_sibling_") || s.starts_with("agg_active_flag") => {
+                ConstraintClass::AggregationPath
+            }
+            "public_depth_bits" | "depth_direct" | "sum_active_init" => {
+                ConstraintClass::DepthBinding
+            }
+            s if s.starts_with("depth_equals_public")
+                || s.starts_with("depth_positive_or_")
+                || s.starts_with("sum_active_file")
+                || s.starts_with("active_flag_file") =>
+            {
+                ConstraintClass::DepthBinding
+            }
+            "state_update" | "gate_state_update" => ConstraintClass::StateUpdate,
+            "aggregated_root_matches_gated" | "single_file_root_matches_gated" => {
+                ConstraintClass::RootEquality
+            }
+            s if s.starts_with("ledger_index_") || s.starts_with("distinct_ledger_index_") => {
+                ConstraintClass::LedgerIndex
+            }
+            _ => ConstraintClass::Other,
+        }
+    }
+}
+
+/// The first unsatisfied constraint found by [`diagnose_unsatisfied`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<F: PrimeField> {
+    /// Full namespace path, e.g. `file_1/aggregated_root_matches_gated`
+    pub path: String,
+    /// The circuit section the path belongs to
+    pub class: ConstraintClass,
+    /// The file slot from a leading `file_<n>` segment, if any
+    pub file_slot: Option<usize>,
+    /// Path and assigned value of each variable the constraint reads, in first-use order
+    pub assignments: Vec<(String, F)>,
+}
+
+/// Result of [`diagnose_unsatisfied`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosisReport<F: PrimeField> {
+    /// Number of constraints synthesized
+    pub num_constraints: usize,
+    /// The first constraint the assignment violates, or `None` if all are satisfied
+    pub unsatisfied: Option<UnsatisfiedConstraint<F>>,
+}
+
+impl<F: PrimeField> DiagnosisReport<F> {
+    /// Whether every constraint is satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        self.unsatisfied.is_none()
+    }
+
+    /// The class of the first unsatisfied constraint, if any.
+    pub fn class(&self) -> Option<ConstraintClass> {
+        self.unsatisfied.as_ref().map(|constraint| constraint.class)
+    }
+}
+
+/// Synthesizes `circuit` on the public inputs `z` into a [`TestConstraintSystem`] and
+/// reports the first unsatisfied constraint.
+///
+/// Besides the namespace path, the report classifies the constraint and lists the
+/// assignments of the variables it reads, so a security test can assert *why* a witness
+/// is rejected rather than only that it is. `z` is allocated as `z0`, `z1`, ... ahead of
+/// the circuit's own variables.
+///
+/// # Example
+///
+/// ```ignore
+/// let report = diagnose_unsatisfied(&malicious_circuit, &z)?;
+/// assert_eq!(report.class(), Some(ConstraintClass::RootEquality));
+/// ```
+pub fn diagnose_unsatisfied<F: PrimeField + PrimeFieldBits>(
+    circuit: &PorCircuit<F>,
+    z: &[F],
+) -> Result<DiagnosisReport<F>, SynthesisError> {
+    let mut cs = RecordingCS::new();
+    let z_alloc = z
+        .iter()
+        .enumerate()
+        .map(|(i, value)| AllocatedNum::alloc(cs.namespace(|| format!("z{}", i)), || Ok(*value)))
+        .collect::<Result<Vec<_>, _>>()?;
+    circuit.synthesize(&mut cs, &z_alloc)?;
+
+    let unsatisfied = cs.inner.which_is_unsatisfied().map(|path| {
+        let variables = cs
+            .constraints
+            .iter()
+            .find(|(constraint_path, _)| constraint_path == path)
+            .map(|(_, variables)| variables.as_slice())
+            .unwrap_or_default();
+        UnsatisfiedConstraint {
+            path: path.to_string(),
+            class: ConstraintClass::from_path(path),
+            file_slot: path
+                .split('/')
+                .next()
+                .and_then(|segment| segment.strip_prefix("file_"))
+                .and_then(|slot| slot.parse().ok()),
+            assignments: variables
+                .iter()
+                .map(|variable| match variable.get_unchecked() {
+                    Index::Input(i) => cs.inputs[i].clone(),
+                    Index::Aux(i) => cs.aux[i].clone(),
+                })
+                .collect(),
+        }
+    });
+
+    Ok(DiagnosisReport {
+        num_constraints: cs.inner.num_constraints(),
+        unsatisfied,
+    })
+}
+
+/// A [`TestConstraintSystem`] wrapper that also records variable paths and values and the
+/// variables each constraint reads, which the test system keeps private.
+struct RecordingCS<F: PrimeField> {
+    inner: TestConstraintSystem<F>,
+    namespace: Vec<String>,
+    inputs: Vec<(String, F)>,
+    aux: Vec<(String, F)>,
+    constraints: Vec<(String, Vec<Variable>)>,
+}
+
+impl<F: PrimeField> RecordingCS<F> {
+    fn new() -> Self {
+        Self {
+            inner: TestConstraintSystem::new(),
+            namespace: Vec::new(),
+            inputs: vec![("ONE".to_string(), F::ONE)],
+            aux: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    fn path(&self, name: &str) -> String {
+        self.namespace
+            .iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl<F: PrimeField> ConstraintSystem<F> for RecordingCS<F> {
+    type Root = Self;
+
+    fn alloc<FN, A, AR>(&mut self, annotation: A, f: FN) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let name = annotation().into();
+        let value = f()?;
+        self.aux.push((self.path(&name), value));
+        self.inner.alloc(|| name, || Ok(value))
+    }
+
+    fn alloc_input<FN, A, AR>(&mut self, annotation: A, f: FN) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let name = annotation().into();
+        let value = f()?;
+        self.inputs.push((self.path(&name), value));
+        self.inner.alloc_input(|| name, || Ok(value))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        let name = annotation().into();
+        let (a, b, c) = (
+            a(LinearCombination::zero()),
+            b(LinearCombination::zero()),
+            c(LinearCombination::zero()),
+        );
+        let mut variables = Vec::new();
+        for (variable, _) in a.iter().chain(b.iter()).chain(c.iter()) {
+            if !variables.contains(&variable) {
+                variables.push(variable);
+            }
+        }
+        self.constraints.push((self.path(&name), variables));
+        self.inner.enforce(|| name, |_| a, |_| b, |_| c);
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.inner.push_namespace(|| name.clone());
+        self.namespace.push(name);
+    }
+
+    fn pop_namespace(&mut self) {
+        self.inner.pop_namespace();
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,6 +25,28 @@ pub fn circuit_arity(files_per_step: usize) -> usize {
     BASE_CIRCUIT_ARITY + files_per_step + files_per_step + files_per_step + files_per_step
 }
 
+/// Version of the circuit's public input/output layout (see [`PublicIOLayout`]).
+///
+/// Recorded in serialized proofs and parameter cache files; verification and parameter
+/// loading reject other versions with [`crate::KontorPoRError::SchemaVersionMismatch`]
+/// instead of failing inside the SNARK. Version 1 carried one shared seed after
+/// `state_in`; version 2 moved to per-file seeds after the depths. Bump it whenever the
+/// layout or the meaning of a field changes.
+pub const PUBLIC_IO_SCHEMA_VERSION: u32 = 2;
+
+/// Version of the JSON encoding of challenges ([`crate::api::Challenge::to_json`]).
+///
+/// Decoding rejects other versions with
+/// [`crate::KontorPoRError::UnsupportedChallengeVersion`]. Bump it whenever a field is
+/// added, removed or reinterpreted, since signed challenges cover the exact encoding.
+pub const CHALLENGE_SCHEMA_VERSION: u32 = 1;
+
+/// Version of [`crate::ledger::AggregatedTreeExport`].
+///
+/// [`crate::ledger::AggregatedTreeExport::verify`] rejects exports of other versions. Bump
+/// it whenever a field is added, removed or reinterpreted.
+pub const AGGREGATED_TREE_EXPORT_VERSION: u16 = 1;
+
 /// Public input/output layout helper to centralize index management.
 ///
 /// This prevents bugs from manually managing indices in multiple places.
@@ -43,6 +65,22 @@ impl PublicIOLayout {
         Self { files_per_step }
     }
 
+    /// Schema version of this layout, [`PUBLIC_IO_SCHEMA_VERSION`]
+    pub fn schema_version(&self) -> u32 {
+        PUBLIC_IO_SCHEMA_VERSION
+    }
+
+    /// Name of every field, indexed by its position in `z0`/`zn`, for tooling that
+    /// prints or checks public inputs: `aggregated_root`, `state_in`, then
+    /// `ledger_index_i`, `actual_depth_i`, `seed_i` and `leaf_i` for each file slot.
+    pub fn describe(&self) -> Vec<String> {
+        let mut names = vec!["aggregated_root".to_string(), "state_in".to_string()];
+        for section in ["ledger_index", "actual_depth", "seed", "leaf"] {
+            names.extend((0..self.files_per_step).map(|i| format!("{}_{}", section, i)));
+        }
+        names
+    }
+
     /// Total arity: fixed + ledger indices + depths + seeds + leaf outputs
     pub fn arity(&self) -> usize {
         Self::FIXED + 4 * self.files_per_step
@@ -119,6 +157,7 @@ impl PublicIOLayout {
     pub fn build_z0_primary(
         &self,
         aggregated_root: crate::api::FieldElement,
+        state_in: crate::api::FieldElement,
         ledger_indices: &[usize],
         depths: &[usize],
         seeds: &[crate::api::FieldElement],
@@ -129,7 +168,7 @@ impl PublicIOLayout {
 
         // Fixed fields
         z0_primary.push(aggregated_root); // [0]
-        z0_primary.push(FieldElement::ZERO); // [1] state_in
+        z0_primary.push(state_in); // [1] state_in
 
         // Ledger indices
         for &idx in ledger_indices.iter() {
@@ -190,6 +229,152 @@ pub fn derive_shape(num_files: usize, max_depth: usize) -> (usize, usize) {
     (files_per_step, file_tree_depth)
 }
 
+/// Split `num_files` challenged files into passes over at most `max_files_per_step` slots.
+/// Returns (files_per_step, num_passes) tuple.
+///
+/// Without a cap, or when the files fit under it, this is the single pass of
+/// [`derive_shape`]. Otherwise the circuit has `max_files_per_step` slots (a power of
+/// two) and each pass fills them with the next files in canonical order, so only the
+/// last pass holds padding slots.
+#[inline]
+pub fn derive_partition(num_files: usize, max_files_per_step: Option<usize>) -> (usize, usize) {
+    let files_per_step = num_files.next_power_of_two().max(1);
+    match max_files_per_step {
+        Some(max) if max > 0 && files_per_step > max => (max, num_files.div_ceil(max)),
+        _ => (files_per_step, 1),
+    }
+}
+
+/// Largest aggregated tree depth a padded multi-file shape may use (a ledger of
+/// [`PRACTICAL_MAX_FILES`] entries).
+pub const MAX_AGG_DEPTH: usize = 10;
+
+/// Derive the aggregated tree depth of a circuit shape.
+///
+/// File-root-bound shapes have no aggregation path. Ledger-bound shapes use `max_agg_depth`
+/// when set, so one parameter set serves every ledger up to that depth, and the ledger's own
+/// depth otherwise. A ledger holding a single file has depth 0 (its root is the file's rc),
+/// so ledger-bound shapes take at least one gated level, which that ledger leaves inactive.
+#[inline]
+pub fn derive_agg_depth(
+    ledger_bound: bool,
+    ledger_depth: usize,
+    max_agg_depth: Option<usize>,
+) -> usize {
+    if ledger_bound {
+        max_agg_depth.unwrap_or(ledger_depth).max(1)
+    } else {
+        0
+    }
+}
+
+/// The dimensions of a circuit shape: file slots per step, file tree depth and aggregated
+/// tree depth.
+///
+/// Passing these together instead of as three `usize`s keeps their order fixed between
+/// planning, parameter loading and circuit construction. Proofs record the shape they
+/// were generated for, and verification rejects one that differs from the shape derived
+/// from the challenges with [`crate::KontorPoRError::ShapeMismatch`].
+/// [`crate::params::Shape`] extends it with the circuit variant parameters are keyed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Shape {
+    /// Number of file slots in the circuit (power of 2)
+    pub files_per_step: usize,
+    /// Maximum Merkle tree depth for files (at least 1)
+    pub file_tree_depth: usize,
+    /// Depth of the aggregation tree (0 for file-root-bound proofs)
+    pub aggregated_tree_depth: usize,
+}
+
+impl Shape {
+    /// Creates a shape from its dimensions, without validating them.
+    pub const fn new(
+        files_per_step: usize,
+        file_tree_depth: usize,
+        aggregated_tree_depth: usize,
+    ) -> Self {
+        Self {
+            files_per_step,
+            file_tree_depth,
+            aggregated_tree_depth,
+        }
+    }
+
+    /// Like [`Shape::new`], but fails unless the shape is valid (see [`Shape::validate`]).
+    pub fn try_new(
+        files_per_step: usize,
+        file_tree_depth: usize,
+        aggregated_tree_depth: usize,
+    ) -> crate::Result<Self> {
+        let shape = Self::new(files_per_step, file_tree_depth, aggregated_tree_depth);
+        shape.validate()?;
+        Ok(shape)
+    }
+
+    /// The shape for `num_files` files of at most `max_depth` levels, as [`derive_shape`]
+    /// sizes them, with the given aggregated tree depth.
+    pub fn derive(num_files: usize, max_depth: usize, aggregated_tree_depth: usize) -> Self {
+        let (files_per_step, file_tree_depth) = derive_shape(num_files, max_depth);
+        Self::new(files_per_step, file_tree_depth, aggregated_tree_depth)
+    }
+
+    /// The shape `challenges` are proven in against `ledger` by default.
+    ///
+    /// Runs the planning of [`crate::api::PorSystem::prove`], with duplicate challenges
+    /// collapsed as proving does unless
+    /// [`crate::api::ProveOptions::allow_duplicates`] is set, so it fails with the same
+    /// errors for challenges that cannot be proven.
+    pub fn for_challenges(
+        challenges: &[crate::api::Challenge],
+        ledger: &crate::ledger::FileLedger,
+    ) -> crate::Result<Self> {
+        let collapsed = crate::api::plan::collapse_duplicates(challenges);
+        let plan =
+            crate::api::plan::Plan::make_plan(collapsed.as_deref().unwrap_or(challenges), ledger)?;
+        Ok(plan.shape)
+    }
+
+    /// Checks that `files_per_step` is a non-zero power of two of at most
+    /// [`PRACTICAL_MAX_FILES`] and that `file_tree_depth` is at least 1.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::KontorPoRError::TooManyFiles`] above `PRACTICAL_MAX_FILES` slots, and
+    /// [`crate::KontorPoRError::InvalidInput`] otherwise.
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.files_per_step == 0 || !self.files_per_step.is_power_of_two() {
+            return Err(crate::KontorPoRError::InvalidInput(format!(
+                "files_per_step must be a non-zero power of two, got {}",
+                self.files_per_step
+            )));
+        }
+        if self.files_per_step > PRACTICAL_MAX_FILES {
+            return Err(crate::KontorPoRError::TooManyFiles {
+                got: self.files_per_step,
+                max: PRACTICAL_MAX_FILES,
+            });
+        }
+        if self.file_tree_depth == 0 {
+            return Err(crate::KontorPoRError::InvalidInput(
+                "file_tree_depth must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Formats as `{files_per_step}x{file_tree_depth}_agg{aggregated_tree_depth}`, the naming
+/// of parameter cache files.
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}_agg{}",
+            self.files_per_step, self.file_tree_depth, self.aggregated_tree_depth
+        )
+    }
+}
+
 // --- Hashing and Merkle Tree Parameters ---
 
 /// The size of chunks/symbols (in bytes) for proof-of-retrievability.
@@ -198,6 +383,20 @@ pub fn derive_shape(num_files: usize, max_depth: usize) -> (usize, usize) {
 /// This is the fundamental unit: chunk = symbol = shard = leaf = 31 bytes.
 pub const CHUNK_SIZE_BYTES: usize = 31;
 
+/// Prepared files with at most this many leaves have every leaf checked by the default
+/// sampled leaf validation before proving.
+pub const LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD: usize = 1 << 12;
+
+/// Number of randomly chosen leaves checked by sampled leaf validation of larger files.
+pub const LEAF_VALIDATION_SAMPLES: usize = 256;
+
+/// Largest file accepted by `prepare_file`, in bytes (1 TiB).
+///
+/// Every size derived from a file (symbol counts, padded leaf counts, byte lengths) stays
+/// far inside `usize` below this bound. Metadata claiming a larger `original_size` is
+/// rejected by the functions that consume it instead of being allowed to overflow.
+pub const MAX_FILE_SIZE: u64 = 1 << 40;
+
 // --- Reed-Solomon Multi-Codeword Parameters ---
 
 /// Data symbols per RS codeword (GF(2^8) constraint: total ≤ 255)
@@ -209,6 +408,22 @@ pub const PARITY_SYMBOLS_PER_CODEWORD: usize = 24;
 /// Total symbols per codeword (data + parity)
 pub const TOTAL_SYMBOLS_PER_CODEWORD: usize = 255;
 
+/// Symbols per GF(2^16) shard: two 31-byte symbols make 31 two-byte field elements
+pub const GF16_SYMBOLS_PER_SHARD: usize = 2;
+
+/// Data symbols per GF(2^16) codeword (924 shards, 8 times the GF(2^8) codeword)
+///
+/// Setting up the code (once per process) and decoding a damaged codeword each invert a
+/// matrix of this many shards, which costs seconds at this size and grows cubically, so
+/// codewords stay far below the field's 65535 shard limit.
+pub const GF16_DATA_SYMBOLS_PER_CODEWORD: usize = 1848;
+
+/// Parity symbols per GF(2^16) codeword (96 shards, the same overhead as GF(2^8))
+pub const GF16_PARITY_SYMBOLS_PER_CODEWORD: usize = 192;
+
+/// Total symbols per GF(2^16) codeword (1020 shards)
+pub const GF16_TOTAL_SYMBOLS_PER_CODEWORD: usize = 2040;
+
 // --- Erasure Coding Parameters ---
 
 /// The default number of data shards for erasure coding.
@@ -226,7 +441,15 @@ pub const DEFAULT_ERASURE_PARITY_SHARDS: usize = 1;
 pub const MAX_LEDGER_SIZE_BYTES: usize = 100 * 1024 * 1024;
 
 /// Current ledger format version
-pub const LEDGER_FORMAT_VERSION: u16 = 1;
+///
+/// Version 2 fills unused slots of fixed-capacity ledgers with the domain-separated
+/// empty-slot leaf instead of zero. Version 3 stores roots and root commitments as
+/// canonical 32-byte encodings and rejects non-canonical ones on load. Version 4 records
+/// each file's filename for inspection. Version 5 records the index policy and the
+/// insertion-order slot assignments. Version 6 records the root in effect from each recorded
+/// block height. Version 7 records the roots migrated from other ledgers. Ledgers of
+/// version 1, the format of the 0.1 releases, still load; other older versions do not.
+pub const LEDGER_FORMAT_VERSION: u16 = 7;
 
 // --- Test-related Constants ---
 
@@ -10,6 +10,9 @@
 //! - **Codeword Structure**: 231 data symbols + 24 parity symbols = 255 total (GF(2^8) constraint)
 //! - **Multi-Codeword**: Files larger than 231 symbols are encoded as multiple independent codewords
 //! - **Redundancy**: ~10% overhead (24/231 ≈ 10.4%)
+//! - **GF(2^16) backend**: [`ErasureConfig::field`] selects codewords of 1848 data + 192
+//!   parity symbols instead, with each pair of symbols forming one GF(2^16) shard, so a
+//!   burst of loss hits far fewer codewords
 //!
 //! ## Example
 //!
@@ -32,8 +35,198 @@
 //! # Ok::<(), kontor_crypto::KontorPoRError>(())
 //! ```
 
-use crate::{config, KontorPoRError, Result};
-use reed_solomon_erasure::galois_8::ReedSolomon;
+use crate::instrumentation::warn;
+use crate::{
+    api::{ErasureCode, ErasureField, FileMetadata},
+    config, KontorPoRError, Result,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reed_solomon_erasure::{galois_16, galois_8::ReedSolomon};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Selects the Reed-Solomon backend a file is encoded with.
+///
+/// The default codes over GF(2^8) in codewords of 255 symbols. Over GF(2^16)
+/// (`ErasureConfig::field(ErasureField::Gf16)`) a codeword holds 2040 symbols, paired
+/// into 1020 shards, so it survives a burst of up to 192 lost symbols where a GF(2^8)
+/// codeword survives 24. Symbols are 31 bytes either way, so Merkle trees and circuits
+/// do not depend on the choice; it is recorded in [`FileMetadata::erasure`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErasureConfig {
+    code: ErasureCode,
+}
+
+impl ErasureConfig {
+    /// Reed-Solomon over `field`.
+    pub const fn field(field: ErasureField) -> Self {
+        Self {
+            code: ErasureCode::for_field(field),
+        }
+    }
+
+    /// The erasure code recorded in the metadata of files encoded with this config.
+    pub const fn code(&self) -> ErasureCode {
+        self.code
+    }
+
+    /// The config a file was encoded with.
+    ///
+    /// # Errors
+    ///
+    /// [`KontorPoRError::ErasureCoding`] for files prepared without erasure coding.
+    pub fn for_metadata(metadata: &FileMetadata) -> Result<Self> {
+        match metadata.erasure {
+            Some(code) => Ok(Self { code }),
+            None => Err(KontorPoRError::ErasureCoding {
+                details: format!(
+                    "file {} was prepared without erasure coding",
+                    metadata.file_id
+                ),
+            }),
+        }
+    }
+
+    /// Checks that `metadata` describes a file encoded with this config, so its symbols
+    /// may be decoded with it.
+    ///
+    /// # Errors
+    ///
+    /// [`KontorPoRError::ErasureFieldMismatch`] if the file was coded over the other field,
+    /// and [`KontorPoRError::ErasureCoding`] if it was prepared without erasure coding.
+    pub fn check(&self, metadata: &FileMetadata) -> Result<()> {
+        let found = Self::for_metadata(metadata)?.code.field();
+        if found != self.code.field() {
+            return Err(KontorPoRError::ErasureFieldMismatch {
+                expected: self.code.field(),
+                found,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A Reed-Solomon encoder for one [`ErasureCode`], working on codewords of symbols.
+enum Codec {
+    Gf8(Box<ReedSolomon>),
+    Gf16(&'static galois_16::ReedSolomon),
+}
+
+/// The GF(2^16) code takes seconds to set up, so it is built once per process.
+static GF16_CODEC: OnceLock<galois_16::ReedSolomon> = OnceLock::new();
+
+impl Codec {
+    fn new(code: ErasureCode) -> Result<Self> {
+        let data_shards = code.data_symbols_per_codeword() / code.symbols_per_shard();
+        let parity_shards = code.parity_symbols_per_codeword() / code.symbols_per_shard();
+        let setup_failed =
+            |e| KontorPoRError::Cryptographic(format!("Reed-Solomon setup failed: {e}"));
+        match code.field() {
+            ErasureField::Gf8 => ReedSolomon::new(data_shards, parity_shards)
+                .map(|rs| Codec::Gf8(Box::new(rs)))
+                .map_err(setup_failed),
+            ErasureField::Gf16 => {
+                if let Some(rs) = GF16_CODEC.get() {
+                    return Ok(Codec::Gf16(rs));
+                }
+                let rs = galois_16::ReedSolomon::new(data_shards, parity_shards)
+                    .map_err(setup_failed)?;
+                Ok(Codec::Gf16(GF16_CODEC.get_or_init(|| rs)))
+            }
+        }
+    }
+
+    /// Computes the parity symbols of one codeword's `data` symbols into `parity`.
+    fn encode_parity<S: AsRef<[u8]>>(&self, data: &[S], parity: &mut [Vec<u8>]) -> Result<()> {
+        match self {
+            Codec::Gf8(rs) => rs.encode_sep(data, parity),
+            Codec::Gf16(rs) => {
+                let mut data: Vec<Vec<[u8; 2]>> = data.chunks(2).map(pair_to_shard).collect();
+                let mut shards = vec![vec![[0; 2]; config::CHUNK_SIZE_BYTES]; parity.len() / 2];
+                let encoded = rs.encode_sep(&data, &mut shards).map(|()| {
+                    for (pair, shard) in parity.chunks_mut(2).zip(&shards) {
+                        shard_to_pair(shard, pair);
+                    }
+                });
+                for shard in data.iter_mut().chain(&mut shards) {
+                    crate::utils::scrub_bytes(shard.as_flattened_mut());
+                }
+                encoded
+            }
+        }
+        .map_err(|e| KontorPoRError::Cryptographic(format!("RS encode failed: {e}")))
+    }
+
+    /// Fills in the missing symbols of `codeword`, which must have enough shards left.
+    fn reconstruct(&self, codeword: &mut [Option<Vec<u8>>]) -> std::result::Result<(), String> {
+        match self {
+            Codec::Gf8(rs) => rs.reconstruct(codeword).map_err(|e| e.to_string()),
+            Codec::Gf16(rs) => {
+                let mut shards: Vec<Option<Vec<[u8; 2]>>> = codeword
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [Some(first), Some(second)] => Some(pair_to_shard(&[first, second])),
+                        _ => None,
+                    })
+                    .collect();
+                let reconstructed = rs.reconstruct(&mut shards).map_err(|e| e.to_string());
+                if reconstructed.is_ok() {
+                    for (pair, shard) in codeword.chunks_mut(2).zip(&shards) {
+                        if let Some(shard) = shard {
+                            let mut symbols = vec![Vec::new(), Vec::new()];
+                            shard_to_pair(shard, &mut symbols);
+                            for (slot, mut symbol) in pair.iter_mut().zip(symbols) {
+                                if slot.is_none() {
+                                    *slot = Some(symbol);
+                                } else {
+                                    crate::utils::scrub_bytes(&mut symbol);
+                                }
+                            }
+                        }
+                    }
+                }
+                for shard in shards.iter_mut().flatten() {
+                    crate::utils::scrub_bytes(shard.as_flattened_mut());
+                }
+                reconstructed
+            }
+        }
+    }
+}
+
+/// Joins two 31-byte symbols into a GF(2^16) shard of 31 elements.
+fn pair_to_shard<S: AsRef<[u8]>>(pair: &[S]) -> Vec<[u8; 2]> {
+    let mut bytes: Vec<u8> = pair
+        .iter()
+        .flat_map(|symbol| symbol.as_ref())
+        .copied()
+        .collect();
+    let shard = bytes.chunks_exact(2).map(|b| [b[0], b[1]]).collect();
+    crate::utils::scrub_bytes(&mut bytes);
+    shard
+}
+
+/// Splits a GF(2^16) shard back into its two symbols.
+fn shard_to_pair(shard: &[[u8; 2]], pair: &mut [Vec<u8>]) {
+    let bytes = shard.as_flattened();
+    pair[0] = bytes[..config::CHUNK_SIZE_BYTES].to_vec();
+    pair[1] = bytes[config::CHUNK_SIZE_BYTES..].to_vec();
+}
+
+/// Number of shards of `codeword` with at least one missing symbol.
+fn missing_shards(codeword: &[Option<Vec<u8>>], code: ErasureCode) -> usize {
+    codeword
+        .chunks(code.symbols_per_shard())
+        .filter(|shard| shard.iter().any(Option::is_none))
+        .count()
+}
 
 /// Encodes a file into 31-byte symbols using multi-codeword Reed-Solomon.
 ///
@@ -49,6 +242,7 @@ use reed_solomon_erasure::galois_8::ReedSolomon;
 ///
 /// Returns a vector of all symbols (each 31 bytes), including both data and parity.
 /// Symbols are ordered by codeword: [cw0_data, cw0_parity, cw1_data, cw1_parity, ...]
+/// Empty input encodes to zero codewords, i.e. an empty vector.
 ///
 /// # Example
 ///
@@ -60,54 +254,107 @@ use reed_solomon_erasure::galois_8::ReedSolomon;
 /// // Each symbol is 31 bytes, symbols.len() will be a multiple of 255
 /// # Ok::<(), kontor_crypto::KontorPoRError>(())
 /// ```
-pub fn encode_file_symbols(data: &[u8]) -> Result<Vec<Vec<u8>>> {
-    if data.is_empty() {
-        return Err(KontorPoRError::EmptyData {
-            operation: "encode_file_symbols".to_string(),
-        });
-    }
+pub fn encode_file_symbols(data: &(impl AsRef<[u8]> + ?Sized)) -> Result<Vec<Vec<u8>>> {
+    encode_file_symbols_with(data, ErasureConfig::default())
+}
 
-    // Partition into 31-byte chunks
-    let mut chunks = Vec::new();
-    for chunk_start in (0..data.len()).step_by(config::CHUNK_SIZE_BYTES) {
-        let chunk_end = std::cmp::min(chunk_start + config::CHUNK_SIZE_BYTES, data.len());
-        let mut chunk = data[chunk_start..chunk_end].to_vec();
-        chunk.resize(config::CHUNK_SIZE_BYTES, 0); // Pad final chunk
-        chunks.push(chunk);
-    }
+/// Like [`encode_file_symbols`], but with the Reed-Solomon backend chosen by `config`.
+///
+/// Over GF(2^16) codewords hold 1848 data and 192 parity symbols, so the result is a
+/// multiple of 2040 symbols.
+pub fn encode_file_symbols_with(
+    data: &(impl AsRef<[u8]> + ?Sized),
+    config: ErasureConfig,
+) -> Result<Vec<Vec<u8>>> {
+    encode_file_symbols_cancellable(data.as_ref(), config, None)
+}
 
-    // Create RS encoder
-    let rs = ReedSolomon::new(
-        config::DATA_SYMBOLS_PER_CODEWORD,
-        config::PARITY_SYMBOLS_PER_CODEWORD,
-    )
-    .map_err(|e| KontorPoRError::Cryptographic(format!("Reed-Solomon setup failed: {e}")))?;
+/// [`encode_file_symbols_with`], checking `cancel` before each codeword.
+pub(crate) fn encode_file_symbols_cancellable(
+    data: &[u8],
+    erasure: ErasureConfig,
+    cancel: Option<&AtomicBool>,
+) -> Result<Vec<Vec<u8>>> {
+    let code = erasure.code();
+    let num_codewords = data
+        .len()
+        .div_ceil(config::CHUNK_SIZE_BYTES * code.data_symbols_per_codeword());
+    let mut all_symbols = Vec::with_capacity(num_codewords * code.total_symbols_per_codeword());
+    for_each_codeword(data, erasure, cancel, |codeword| {
+        all_symbols.extend(codeword.iter().map(|symbol| symbol.to_vec()));
+        Ok(())
+    })?;
+    Ok(all_symbols)
+}
 
-    // Encode each codeword
-    let mut all_symbols = Vec::new();
+/// All-zero symbol padding a partial last codeword.
+static ZERO_SYMBOL: [u8; config::CHUNK_SIZE_BYTES] = [0; config::CHUNK_SIZE_BYTES];
 
-    for codeword_chunks in chunks.chunks(config::DATA_SYMBOLS_PER_CODEWORD) {
-        // Create codeword with data + parity slots
-        let mut codeword = codeword_chunks.to_vec();
+/// Encodes `data` one codeword at a time, passing each codeword's symbols (data, then
+/// parity) to `visit` before the next codeword is built, and checking `cancel` before each.
+///
+/// Full data symbols borrow from `data`; only the zero-padded last symbol and the parity
+/// symbols are allocated, and only for the codeword being visited. Callers that keep
+/// something smaller than the symbols (e.g. Merkle leaves) encode in bounded memory.
+///
+/// The backend's [`self_test_field`] runs once per process before the first codeword of
+/// each field is encoded; if it fails, this fails too, so no parity from a broken backend
+/// is ever stored.
+pub(crate) fn for_each_codeword<'d>(
+    data: &'d [u8],
+    erasure: ErasureConfig,
+    cancel: Option<&AtomicBool>,
+    visit: impl FnMut(&[Cow<'d, [u8]>]) -> Result<()>,
+) -> Result<()> {
+    ensure_self_test(erasure.code().field())?;
+    encode_codewords(data, erasure, cancel, visit)
+}
 
-        // Add parity symbol slots
-        for _ in 0..config::PARITY_SYMBOLS_PER_CODEWORD {
-            codeword.push(vec![0; config::CHUNK_SIZE_BYTES]);
-        }
+/// [`for_each_codeword`] without the self-test.
+fn encode_codewords<'d>(
+    data: &'d [u8],
+    erasure: ErasureConfig,
+    cancel: Option<&AtomicBool>,
+    mut visit: impl FnMut(&[Cow<'d, [u8]>]) -> Result<()>,
+) -> Result<()> {
+    let code = erasure.code();
+    let codec = Codec::new(code)?;
+    let data_symbols = code.data_symbols_per_codeword();
 
-        // Pad partial last codeword to 255 symbols
-        while codeword.len() < config::TOTAL_SYMBOLS_PER_CODEWORD {
-            codeword.push(vec![0; config::CHUNK_SIZE_BYTES]);
-        }
+    let mut codeword: Vec<Cow<'d, [u8]>> = Vec::with_capacity(code.total_symbols_per_codeword());
+    for block in data.chunks(data_symbols * config::CHUNK_SIZE_BYTES) {
+        crate::utils::check_cancelled(cancel)?;
+
+        // Data symbols, with the final chunk and a partial codeword padded with zeros
+        codeword.clear();
+        codeword.extend(block.chunks(config::CHUNK_SIZE_BYTES).map(|chunk| {
+            if chunk.len() == config::CHUNK_SIZE_BYTES {
+                Cow::Borrowed(chunk)
+            } else {
+                // Allocated at full size, so no shorter copy is freed unscrubbed
+                let mut symbol = vec![0; config::CHUNK_SIZE_BYTES];
+                symbol[..chunk.len()].copy_from_slice(chunk);
+                Cow::Owned(symbol)
+            }
+        }));
+        codeword.resize(data_symbols, Cow::Borrowed(&ZERO_SYMBOL[..]));
 
         // RS encode this codeword
-        rs.encode(&mut codeword)
-            .map_err(|e| KontorPoRError::Cryptographic(format!("RS encode failed: {e}")))?;
+        let mut parity =
+            vec![vec![0; config::CHUNK_SIZE_BYTES]; code.parity_symbols_per_codeword()];
+        codec.encode_parity(&codeword, &mut parity)?;
+        codeword.extend(parity.into_iter().map(Cow::Owned));
 
-        all_symbols.extend(codeword);
+        let visited = visit(&codeword);
+        for symbol in &mut codeword {
+            if let Cow::Owned(symbol) = symbol {
+                crate::utils::scrub_bytes(symbol);
+            }
+        }
+        visited?;
     }
 
-    Ok(all_symbols)
+    Ok(())
 }
 
 /// Reconstructs original file from erasure-coded symbols.
@@ -124,7 +371,9 @@ pub fn encode_file_symbols(data: &[u8]) -> Result<Vec<Vec<u8>>> {
 ///
 /// # Returns
 ///
-/// Returns the reconstructed file data, or an error if too many symbols are missing.
+/// Returns the reconstructed file data, or [`KontorPoRError::ReconstructionFailed`]
+/// naming every codeword with more than 24 missing symbols, together with the missing
+/// count of each codeword, so the symbols worth re-fetching are known up front.
 ///
 /// # Example
 ///
@@ -148,38 +397,978 @@ pub fn decode_file_symbols(
     num_codewords: usize,
     original_size: usize,
 ) -> Result<Vec<u8>> {
-    let rs = ReedSolomon::new(
-        config::DATA_SYMBOLS_PER_CODEWORD,
-        config::PARITY_SYMBOLS_PER_CODEWORD,
+    decode_file_symbols_with(
+        symbols,
+        num_codewords,
+        original_size,
+        ErasureConfig::default(),
     )
-    .map_err(|e| KontorPoRError::Cryptographic(format!("Reed-Solomon setup failed: {e}")))?;
+}
 
-    let mut reconstructed = Vec::new();
+/// Like [`decode_file_symbols`], for symbols encoded with `config`.
+///
+/// Over GF(2^16) a codeword decodes while at most 96 of its 1020 symbol pairs have a
+/// missing symbol. Use [`ErasureConfig::for_metadata`] (or check a config against the
+/// metadata with [`ErasureConfig::check`]) so symbols are never decoded under the other
+/// field, which would silently produce garbage.
+pub fn decode_file_symbols_with(
+    symbols: &mut [Option<Vec<u8>>],
+    num_codewords: usize,
+    original_size: usize,
+    config: ErasureConfig,
+) -> Result<Vec<u8>> {
+    let code = config.code();
+    check_symbol_count(symbols.len(), num_codewords, code)?;
+    let codec = Codec::new(code)?;
+    let mut reconstructed =
+        decode_codewords(&codec, code, symbols, num_codewords, 0..num_codewords)?;
 
-    // Decode each codeword independently
-    for cw_idx in 0..num_codewords {
-        let start = cw_idx * config::TOTAL_SYMBOLS_PER_CODEWORD;
-        let end = std::cmp::min(start + config::TOTAL_SYMBOLS_PER_CODEWORD, symbols.len());
+    // Truncate to original size
+    reconstructed.truncate(original_size);
+    Ok(reconstructed)
+}
+
+/// Rejects hostile metadata claiming more codewords than there are symbols (or than fit
+/// in `usize`).
+fn check_symbol_count(len: usize, num_codewords: usize, code: ErasureCode) -> Result<()> {
+    let expected = num_codewords.checked_mul(code.total_symbols_per_codeword());
+    if expected.is_none_or(|expected| expected > len) {
+        return Err(KontorPoRError::ErasureCoding {
+            details: format!(
+                "{} codewords need {} symbols each, got {} symbols",
+                num_codewords,
+                code.total_symbols_per_codeword(),
+                len
+            ),
+        });
+    }
+    Ok(())
+}
 
-        let mut codeword_symbols = symbols[start..end].to_vec();
+/// Reconstructs only the bytes `range` of the original file.
+///
+/// Codewords decode independently, so only those holding data bytes in `range` are
+/// decoded; symbols of other codewords may all be missing.
+///
+/// # Errors
+///
+/// Returns [`KontorPoRError::InvalidInput`] if `range` is not within `original_size`,
+/// [`KontorPoRError::ErasureCoding`] if there are too few symbols for `num_codewords`, and
+/// [`KontorPoRError::ReconstructionFailed`] if a needed codeword has too many missing
+/// symbols (codewords outside `range` are not checked).
+pub fn decode_byte_range(
+    symbols: &[Option<Vec<u8>>],
+    num_codewords: usize,
+    original_size: usize,
+    range: Range<usize>,
+) -> Result<Vec<u8>> {
+    decode_byte_range_with(
+        symbols,
+        num_codewords,
+        original_size,
+        range,
+        ErasureConfig::default(),
+    )
+}
+
+/// Like [`decode_byte_range`], for symbols encoded with `config`.
+pub fn decode_byte_range_with(
+    symbols: &[Option<Vec<u8>>],
+    num_codewords: usize,
+    original_size: usize,
+    range: Range<usize>,
+    config: ErasureConfig,
+) -> Result<Vec<u8>> {
+    let code = config.code();
+    if range.start > range.end || range.end > original_size {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "byte range {}..{} is outside a file of {} bytes",
+            range.start, range.end, original_size
+        )));
+    }
+    check_symbol_count(symbols.len(), num_codewords, code)?;
+    if range.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let codeword_bytes = code.data_symbols_per_codeword() * config::CHUNK_SIZE_BYTES;
+    let first = range.start / codeword_bytes;
+    let last = (range.end - 1) / codeword_bytes;
+    if last >= num_codewords {
+        return Err(KontorPoRError::ErasureCoding {
+            details: format!(
+                "byte {} lies in codeword {}, but the file has {} codewords",
+                range.end - 1,
+                last,
+                num_codewords
+            ),
+        });
+    }
+
+    let codec = Codec::new(code)?;
+    let mut decoded = decode_codewords(&codec, code, symbols, num_codewords, first..last + 1)?;
+
+    let offset = first * codeword_bytes;
+    let bytes = decoded[range.start - offset..range.end - offset].to_vec();
+    crate::utils::scrub_bytes(&mut decoded);
+    Ok(bytes)
+}
+
+/// Decodes the given codewords and concatenates their data symbols.
+///
+/// `symbols` must hold `num_codewords` full codewords. Before decoding, every codeword in
+/// `codewords` is checked for too many missing symbols, so a failure names all of them.
+fn decode_codewords(
+    codec: &Codec,
+    code: ErasureCode,
+    symbols: &[Option<Vec<u8>>],
+    num_codewords: usize,
+    codewords: Range<usize>,
+) -> Result<Vec<u8>> {
+    let total = code.total_symbols_per_codeword();
+    let parity_shards = code.parity_symbols_per_codeword() / code.symbols_per_shard();
+    let missing_per_codeword: Vec<usize> = symbols
+        .chunks(total)
+        .take(num_codewords)
+        .map(|codeword| codeword.iter().filter(|symbol| symbol.is_none()).count())
+        .collect();
+    let unrecoverable_codewords: Vec<usize> = codewords
+        .clone()
+        .filter(|&cw_idx| {
+            let start = cw_idx * total;
+            missing_shards(&symbols[start..start + total], code) > parity_shards
+        })
+        .collect();
+    if !unrecoverable_codewords.is_empty() {
+        return Err(KontorPoRError::ReconstructionFailed {
+            unrecoverable_codewords,
+            missing_per_codeword,
+            needed: code.data_symbols_per_codeword(),
+        });
+    }
+
+    // Sized up front so growing it never frees a partial copy of the plaintext
+    let mut reconstructed = Vec::with_capacity(
+        codewords.len() * code.data_symbols_per_codeword() * config::CHUNK_SIZE_BYTES,
+    );
+
+    // Decode each codeword independently
+    for cw_idx in codewords {
+        let start = cw_idx * total;
+        let mut codeword_symbols = symbols[start..start + total].to_vec();
 
         // Reconstruct this codeword
-        rs.reconstruct(&mut codeword_symbols).map_err(|e| {
+        let decoded = codec.reconstruct(&mut codeword_symbols).map_err(|e| {
             KontorPoRError::Cryptographic(format!("RS decode failed for codeword {}: {e}", cw_idx))
-        })?;
+        });
 
-        // Extract data symbols (first 231)
-        let data_end = std::cmp::min(config::DATA_SYMBOLS_PER_CODEWORD, codeword_symbols.len());
-        for sym in codeword_symbols.iter().take(data_end).flatten() {
-            reconstructed.extend_from_slice(sym);
+        // Extract data symbols
+        if decoded.is_ok() {
+            for sym in codeword_symbols
+                .iter()
+                .take(code.data_symbols_per_codeword())
+                .flatten()
+            {
+                reconstructed.extend_from_slice(sym);
+            }
+        }
+        crate::utils::scrub_symbols(&mut codeword_symbols);
+        if let Err(e) = decoded {
+            crate::utils::scrub_bytes(&mut reconstructed);
+            return Err(e);
         }
     }
 
-    // Truncate to original size
-    reconstructed.truncate(original_size);
     Ok(reconstructed)
 }
 
+/// Number of codewords and data symbols behind an [`EncodedFile`].
+///
+/// Split encoding always uses the default GF(2^8) codewords.
+///
+/// Together with `DATA_SYMBOLS_PER_CODEWORD` and `PARITY_SYMBOLS_PER_CODEWORD` this is
+/// enough to interleave data and parity back into the flat order of
+/// [`encode_file_symbols`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodewordLayout {
+    /// Number of RS codewords
+    pub num_codewords: usize,
+    /// Number of data symbols, excluding the zero symbols that pad the last codeword
+    pub num_data_symbols: usize,
+}
+
+impl CodewordLayout {
+    /// Layout of a file of `size` bytes.
+    pub fn for_size(size: usize) -> Self {
+        let num_data_symbols = size.div_ceil(config::CHUNK_SIZE_BYTES);
+        Self {
+            num_codewords: num_data_symbols.div_ceil(config::DATA_SYMBOLS_PER_CODEWORD),
+            num_data_symbols,
+        }
+    }
+
+    /// Total number of parity symbols.
+    pub fn num_parity_symbols(&self) -> usize {
+        self.num_codewords * config::PARITY_SYMBOLS_PER_CODEWORD
+    }
+
+    /// Number of symbols in the flat form (a multiple of 255).
+    pub fn total_symbols(&self) -> usize {
+        self.num_codewords * config::TOTAL_SYMBOLS_PER_CODEWORD
+    }
+}
+
+/// An erasure-coded file with data and parity symbols held separately.
+///
+/// `data_symbols` are the file's 31-byte chunks in order (the final one zero padded);
+/// `parity_symbols` hold each codeword's 24 parity symbols, codeword by codeword.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedFile {
+    /// Data symbols (hot tier), `layout.num_data_symbols` of them
+    pub data_symbols: Vec<Vec<u8>>,
+    /// Parity symbols (cold tier), 24 per codeword
+    pub parity_symbols: Vec<Vec<u8>>,
+    /// Codeword layout needed to merge the two back together
+    pub layout: CodewordLayout,
+}
+
+/// Encodes a file like [`encode_file_symbols`], returning data and parity symbols separately.
+///
+/// The zero symbols that pad the last codeword to 231 data symbols are not stored;
+/// [`merge_for_tree`] restores them.
+pub fn encode_file_symbols_split(data: &[u8]) -> Result<EncodedFile> {
+    let layout = CodewordLayout::for_size(data.len());
+    let symbols = encode_file_symbols(data)?;
+
+    let mut data_symbols = Vec::with_capacity(layout.num_data_symbols);
+    let mut parity_symbols = Vec::with_capacity(layout.num_parity_symbols());
+    for (cw_idx, codeword) in symbols
+        .chunks(config::TOTAL_SYMBOLS_PER_CODEWORD)
+        .enumerate()
+    {
+        let data_in_codeword = codeword_data_len(&layout, cw_idx);
+        data_symbols.extend_from_slice(&codeword[..data_in_codeword]);
+        parity_symbols.extend_from_slice(&codeword[config::DATA_SYMBOLS_PER_CODEWORD..]);
+    }
+
+    Ok(EncodedFile {
+        data_symbols,
+        parity_symbols,
+        layout,
+    })
+}
+
+/// Interleaves an [`EncodedFile`] into the flat symbol order that `prepare_file` builds
+/// its Merkle tree over: `[cw0_data, cw0_parity, cw1_data, cw1_parity, ...]`.
+///
+/// # Panics
+///
+/// Panics if the symbol counts do not match `encoded.layout`.
+pub fn merge_for_tree(encoded: &EncodedFile) -> Vec<Vec<u8>> {
+    interleave(
+        &encoded.data_symbols,
+        &encoded.parity_symbols,
+        &encoded.layout,
+        vec![0; config::CHUNK_SIZE_BYTES],
+    )
+}
+
+/// Reconstructs the original file from separately stored data and parity symbols.
+///
+/// Missing symbols in either tier are `None`. The padding of the last codeword is
+/// known to be zero and never counts as missing.
+///
+/// # Errors
+///
+/// Returns [`KontorPoRError::ErasureCoding`] if the symbol counts do not match `layout`,
+/// and fails like [`decode_file_symbols`] if a codeword has too many missing symbols.
+pub fn decode_split_symbols(
+    data_symbols: &[Option<Vec<u8>>],
+    parity_symbols: &[Option<Vec<u8>>],
+    layout: &CodewordLayout,
+    original_size: usize,
+) -> Result<Vec<u8>> {
+    if data_symbols.len() != layout.num_data_symbols
+        || parity_symbols.len() != layout.num_parity_symbols()
+    {
+        return Err(KontorPoRError::ErasureCoding {
+            details: format!(
+                "expected {} data and {} parity symbols, got {} and {}",
+                layout.num_data_symbols,
+                layout.num_parity_symbols(),
+                data_symbols.len(),
+                parity_symbols.len()
+            ),
+        });
+    }
+
+    let mut symbols = interleave(
+        data_symbols,
+        parity_symbols,
+        layout,
+        Some(vec![0; config::CHUNK_SIZE_BYTES]),
+    );
+    decode_file_symbols(&mut symbols, layout.num_codewords, original_size)
+}
+
+/// Number of stored data symbols in codeword `cw_idx`.
+fn codeword_data_len(layout: &CodewordLayout, cw_idx: usize) -> usize {
+    let start = cw_idx * config::DATA_SYMBOLS_PER_CODEWORD;
+    layout
+        .num_data_symbols
+        .saturating_sub(start)
+        .min(config::DATA_SYMBOLS_PER_CODEWORD)
+}
+
+/// Lays out data and parity per codeword, filling the data padding with `pad`.
+fn interleave<T: Clone>(
+    data_symbols: &[T],
+    parity_symbols: &[T],
+    layout: &CodewordLayout,
+    pad: T,
+) -> Vec<T> {
+    let mut symbols = Vec::with_capacity(layout.total_symbols());
+    for cw_idx in 0..layout.num_codewords {
+        let data_start = cw_idx * config::DATA_SYMBOLS_PER_CODEWORD;
+        let data_in_codeword = codeword_data_len(layout, cw_idx);
+        symbols.extend_from_slice(&data_symbols[data_start..data_start + data_in_codeword]);
+        symbols.resize(
+            symbols.len() + config::DATA_SYMBOLS_PER_CODEWORD - data_in_codeword,
+            pad.clone(),
+        );
+
+        let parity_start = cw_idx * config::PARITY_SYMBOLS_PER_CODEWORD;
+        symbols.extend_from_slice(
+            &parity_symbols[parity_start..parity_start + config::PARITY_SYMBOLS_PER_CODEWORD],
+        );
+    }
+    symbols
+}
+
+/// A symbol file whose length is not `CHUNK_SIZE_BYTES`; it is treated as missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorruptSymbol {
+    /// Symbol index (the file's name)
+    pub index: usize,
+    /// Length of the file found on disk
+    pub len: usize,
+}
+
+/// Symbols read from a [`DirSymbolStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedSymbols {
+    /// One entry per symbol index; missing and corrupt files are `None`
+    pub symbols: Vec<Option<Vec<u8>>>,
+    /// Files that were present but had the wrong length
+    pub corrupt: Vec<CorruptSymbol>,
+}
+
+/// Outcome of [`crate::api::reconstruct_from_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirReconstruction {
+    /// The reconstructed file
+    pub data: Vec<u8>,
+    /// Symbol indices that were missing or corrupt and had to be recovered
+    pub missing: Vec<usize>,
+    /// One message per symbol file of the wrong length
+    pub warnings: Vec<String>,
+}
+
+/// Symbols of one file kept in a directory, one file per symbol named by its index.
+///
+/// Symbol `i` lives at `dir/i` and holds exactly 31 bytes. A missing file is an erasure;
+/// a file of any other length is treated as missing too. Files whose names are not a
+/// symbol index below `metadata.total_symbols()` are ignored.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::prepare_file;
+/// use kontor_crypto::erasure::{encode_file_symbols, DirSymbolStore};
+///
+/// let data = vec![7u8; 10_000];
+/// let (_, metadata) = prepare_file(&data, "example.dat")?;
+/// let store = DirSymbolStore::open(std::path::Path::new("symbols"), &metadata)?;
+/// for (index, symbol) in encode_file_symbols(&data)?.iter().enumerate() {
+///     store.write_symbol(index, symbol)?;
+/// }
+/// assert!(store.missing_indices()?.is_empty());
+/// # Ok::<(), kontor_crypto::KontorPoRError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirSymbolStore {
+    dir: PathBuf,
+    total_symbols: usize,
+}
+
+impl DirSymbolStore {
+    /// Opens (creating if needed) the symbol directory for the file described by `metadata`.
+    pub fn open(dir: &Path, metadata: &FileMetadata) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(|e| {
+            KontorPoRError::IO(format!(
+                "Failed to create symbol directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            total_symbols: metadata.total_symbols(),
+        })
+    }
+
+    /// The directory holding the symbol files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Number of symbols the file has (the length of [`load_all`](Self::load_all)).
+    pub fn total_symbols(&self) -> usize {
+        self.total_symbols
+    }
+
+    /// Path of the file holding symbol `index`.
+    pub fn symbol_path(&self, index: usize) -> PathBuf {
+        self.dir.join(index.to_string())
+    }
+
+    /// Writes symbol `index`, replacing any existing file.
+    pub fn write_symbol(&self, index: usize, bytes: &[u8]) -> Result<()> {
+        if index >= self.total_symbols {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Symbol index {} out of range for a file of {} symbols",
+                index, self.total_symbols
+            )));
+        }
+        if bytes.len() != config::CHUNK_SIZE_BYTES {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "Symbol {} has {} bytes, expected {}",
+                index,
+                bytes.len(),
+                config::CHUNK_SIZE_BYTES
+            )));
+        }
+        let path = self.symbol_path(index);
+        fs::write(&path, bytes).map_err(|e| {
+            KontorPoRError::IO(format!("Failed to write symbol {}: {}", path.display(), e))
+        })
+    }
+
+    /// Reads every symbol, reporting files of the wrong length alongside the symbols.
+    pub fn load(&self) -> Result<LoadedSymbols> {
+        let mut symbols = Vec::with_capacity(self.total_symbols);
+        let mut corrupt = Vec::new();
+        for index in 0..self.total_symbols {
+            let path = self.symbol_path(index);
+            match fs::read(&path) {
+                Ok(bytes) if bytes.len() == config::CHUNK_SIZE_BYTES => symbols.push(Some(bytes)),
+                Ok(bytes) => {
+                    corrupt.push(CorruptSymbol {
+                        index,
+                        len: bytes.len(),
+                    });
+                    symbols.push(None);
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => symbols.push(None),
+                Err(e) => {
+                    return Err(KontorPoRError::IO(format!(
+                        "Failed to read symbol {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(LoadedSymbols { symbols, corrupt })
+    }
+
+    /// Reads every symbol; missing and corrupt files are `None`.
+    pub fn load_all(&self) -> Result<Vec<Option<Vec<u8>>>> {
+        Ok(self.load()?.symbols)
+    }
+
+    /// Indices whose file is missing or has the wrong length, in ascending order.
+    pub fn missing_indices(&self) -> Result<Vec<usize>> {
+        let mut missing = Vec::new();
+        for index in 0..self.total_symbols {
+            let path = self.symbol_path(index);
+            match fs::metadata(&path) {
+                Ok(meta) if meta.is_file() && meta.len() == config::CHUNK_SIZE_BYTES as u64 => {}
+                Ok(_) => missing.push(index),
+                Err(e) if e.kind() == ErrorKind::NotFound => missing.push(index),
+                Err(e) => {
+                    return Err(KontorPoRError::IO(format!(
+                        "Failed to inspect symbol {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(missing)
+    }
+}
+
+/// How symbols are lost in [`simulate_loss`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LossModel {
+    /// Lose `round(fraction × total_symbols)` symbols at uniformly random positions.
+    /// The fraction is clamped to `[0, 1]`.
+    Random(f64),
+    /// Lose the contiguous run of symbols `[start, start + len)` (clamped to the file).
+    Burst { start: usize, len: usize },
+    /// Lose a uniformly random number of symbols in `0..=max_lost` from each codeword,
+    /// at random positions within that codeword.
+    PerCodeword(usize),
+}
+
+/// Produce a damaged copy of `symbols` according to `loss`.
+///
+/// The result has the same length as `symbols`, with lost symbols replaced by `None`,
+/// and is ready to pass to [`decode_file_symbols`] or [`recoverability`]. The same
+/// `rng_seed` always produces the same loss pattern.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::prepare_file;
+/// use kontor_crypto::erasure::{encode_file_symbols, recoverability, simulate_loss, LossModel};
+///
+/// let data = vec![7u8; 10_000];
+/// let (_, metadata) = prepare_file(&data, "example.dat")?;
+/// let symbols = encode_file_symbols(&data)?;
+///
+/// let damaged = simulate_loss(&symbols, &metadata, LossModel::PerCodeword(24), 1);
+/// assert!(recoverability(&damaged, &metadata).is_recoverable());
+/// # Ok::<(), kontor_crypto::KontorPoRError>(())
+/// ```
+pub fn simulate_loss(
+    symbols: &[Vec<u8>],
+    metadata: &FileMetadata,
+    loss: LossModel,
+    rng_seed: u64,
+) -> Vec<Option<Vec<u8>>> {
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    let mut damaged: Vec<Option<Vec<u8>>> = symbols.iter().cloned().map(Some).collect();
+    let total = damaged.len();
+
+    match loss {
+        LossModel::Random(fraction) => {
+            let amount = (fraction.clamp(0.0, 1.0) * total as f64).round() as usize;
+            for i in rand::seq::index::sample(&mut rng, total, amount.min(total)) {
+                damaged[i] = None;
+            }
+        }
+        LossModel::Burst { start, len } => {
+            let end = start.saturating_add(len).min(total);
+            for slot in damaged.iter_mut().take(end).skip(start) {
+                *slot = None;
+            }
+        }
+        LossModel::PerCodeword(max_lost) => {
+            let codeword_len = metadata
+                .erasure
+                .unwrap_or_default()
+                .total_symbols_per_codeword();
+            for cw_idx in 0..metadata.num_codewords() {
+                let start = cw_idx * codeword_len;
+                if start >= total {
+                    break;
+                }
+                let cw_len = codeword_len.min(total - start);
+                let lost = rng.gen_range(0..=max_lost.min(cw_len));
+                for i in rand::seq::index::sample(&mut rng, cw_len, lost) {
+                    damaged[start + i] = None;
+                }
+            }
+        }
+    }
+
+    damaged
+}
+
+/// Recoverability of a single codeword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodewordStatus {
+    /// Index of the codeword within the file
+    pub index: usize,
+    /// Number of missing symbols (including any beyond the end of the input)
+    pub missing: usize,
+    /// Whether [`decode_file_symbols`] will reconstruct this codeword
+    pub recoverable: bool,
+}
+
+/// Per-codeword prediction of whether decoding will succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoverabilityReport {
+    /// One entry per codeword, in file order
+    pub codewords: Vec<CodewordStatus>,
+}
+
+impl RecoverabilityReport {
+    /// Returns true if every codeword can be reconstructed (i.e. the file is recoverable).
+    pub fn is_recoverable(&self) -> bool {
+        self.codewords.iter().all(|cw| cw.recoverable)
+    }
+
+    /// Indices of codewords that cannot be reconstructed.
+    pub fn unrecoverable_codewords(&self) -> Vec<usize> {
+        self.codewords
+            .iter()
+            .filter(|cw| !cw.recoverable)
+            .map(|cw| cw.index)
+            .collect()
+    }
+
+    /// Total number of missing symbols across all codewords.
+    pub fn total_missing(&self) -> usize {
+        self.codewords.iter().map(|cw| cw.missing).sum()
+    }
+}
+
+/// Predict, per codeword, whether [`decode_file_symbols`] will succeed, without decoding.
+///
+/// A codeword is recoverable iff at most `PARITY_SYMBOLS_PER_CODEWORD` (24) of its 255
+/// symbols are missing; for files coded over GF(2^16), iff at most 96 of its 1020 symbol
+/// pairs have a missing symbol. Symbols are assumed to be well-formed 31-byte symbols as
+/// produced by [`encode_file_symbols`]; only their presence is inspected. A codeword cut
+/// short by a truncated `symbols` slice is reported as unrecoverable.
+pub fn recoverability(
+    symbols: &[Option<Vec<u8>>],
+    metadata: &FileMetadata,
+) -> RecoverabilityReport {
+    let code = metadata.erasure.unwrap_or_default();
+    let total = code.total_symbols_per_codeword();
+    let parity_shards = code.parity_symbols_per_codeword() / code.symbols_per_shard();
+    let codewords = (0..metadata.num_codewords())
+        .map(|index| {
+            let start = index * total;
+            let end = start + total;
+            let present = symbols
+                .get(start.min(symbols.len())..end.min(symbols.len()))
+                .unwrap_or(&[]);
+            let missing = total - present.iter().filter(|symbol| symbol.is_some()).count();
+            CodewordStatus {
+                index,
+                missing,
+                // The decoder needs every slot, so a truncated codeword never decodes
+                recoverable: end <= symbols.len() && missing_shards(present, code) <= parity_shards,
+            }
+        })
+        .collect();
+
+    RecoverabilityReport { codewords }
+}
+
+/// Symbols to fetch so one codeword becomes recoverable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodewordRecovery {
+    /// Index of the codeword within the file
+    pub index: usize,
+    /// Number of additional symbols needed, i.e. `fetch.len()`
+    pub needed: usize,
+    /// Global symbol indices to fetch, in ascending order (data symbols precede parity)
+    pub fetch: Vec<usize>,
+}
+
+/// The fewest additional symbols that make every codeword of a file recoverable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryPlan {
+    /// One entry per unrecoverable codeword, in file order
+    pub codewords: Vec<CodewordRecovery>,
+    /// Total number of symbols to fetch across all codewords
+    pub total_needed: usize,
+}
+
+impl RecoveryPlan {
+    /// Returns true if nothing needs to be fetched (the file is already recoverable).
+    pub fn is_empty(&self) -> bool {
+        self.codewords.is_empty()
+    }
+
+    /// Every global symbol index to fetch, in file order.
+    pub fn fetch_indices(&self) -> Vec<usize> {
+        self.codewords
+            .iter()
+            .flat_map(|cw| cw.fetch.iter().copied())
+            .collect()
+    }
+}
+
+/// Plan which missing symbols to fetch so that [`decode_file_symbols`] succeeds.
+///
+/// `available[i]` says whether global symbol `i` is held; indices past the end of
+/// `available` count as missing. For each codeword with more missing shards than it has
+/// parity shards, the plan completes just enough shards, choosing those with the fewest
+/// missing symbols and, among equals, data shards before parity shards, so that decoding
+/// has less to reconstruct. Fetching exactly the planned symbols makes every codeword
+/// recoverable, and leaving out any one of them leaves its codeword unrecoverable.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::prepare_file;
+/// use kontor_crypto::erasure::recovery_plan;
+///
+/// let (_, metadata) = prepare_file(&vec![7u8; 10_000], "example.dat")?;
+/// let mut available = vec![true; metadata.padded_len];
+/// available[..30].fill(false);
+///
+/// let plan = recovery_plan(&available, &metadata);
+/// assert_eq!(plan.total_needed, 6);
+/// assert_eq!(plan.fetch_indices(), (0..6).collect::<Vec<_>>());
+/// # Ok::<(), kontor_crypto::KontorPoRError>(())
+/// ```
+pub fn recovery_plan(available: &[bool], metadata: &FileMetadata) -> RecoveryPlan {
+    let code = metadata.erasure.unwrap_or_default();
+    let total = code.total_symbols_per_codeword();
+    let per_shard = code.symbols_per_shard();
+    let parity_shards = code.parity_symbols_per_codeword() / per_shard;
+    let is_available = |i: usize| available.get(i).copied().unwrap_or(false);
+
+    let mut plan = RecoveryPlan::default();
+    for index in 0..metadata.num_codewords() {
+        let start = index * total;
+        // Missing symbols of each incomplete shard, with whether the shard holds data
+        let mut incomplete: Vec<(Vec<usize>, bool)> = (start..start + total)
+            .step_by(per_shard)
+            .map(|shard_start| {
+                let missing: Vec<usize> = (shard_start..shard_start + per_shard)
+                    .filter(|&i| !is_available(i))
+                    .collect();
+                let is_data = shard_start - start < code.data_symbols_per_codeword();
+                (missing, is_data)
+            })
+            .filter(|(missing, _)| !missing.is_empty())
+            .collect();
+        if incomplete.len() <= parity_shards {
+            continue;
+        }
+
+        // Cheapest shards first, data before parity; the sort is stable, so ties keep
+        // index order
+        incomplete.sort_by_key(|(missing, is_data)| (missing.len(), !is_data));
+        let mut fetch: Vec<usize> = incomplete[..incomplete.len() - parity_shards]
+            .iter()
+            .flat_map(|(missing, _)| missing.iter().copied())
+            .collect();
+        fetch.sort_unstable();
+
+        plan.total_needed += fetch.len();
+        plan.codewords.push(CodewordRecovery {
+            index,
+            needed: fetch.len(),
+            fetch,
+        });
+    }
+    plan
+}
+
+/// A known-answer vector checked by [`self_test`].
+#[derive(Debug, Clone, Copy)]
+struct GoldenVector {
+    /// Name reported when the vector fails
+    name: &'static str,
+    /// Field the input is coded over
+    field: ErasureField,
+    /// Length of the input produced by [`golden_input`]
+    len: usize,
+    /// Hex SHA-256 of the encoded symbols, concatenated in order
+    digest: &'static str,
+}
+
+/// Golden vectors, recorded from a build whose parity was checked by reconstruction.
+///
+/// Inputs cover an empty file, a partial symbol, an exactly full codeword and a partial
+/// second codeword over GF(2^8), and one codeword over GF(2^16).
+const GOLDEN_VECTORS: &[GoldenVector] = &[
+    GoldenVector {
+        name: "gf8_empty",
+        field: ErasureField::Gf8,
+        len: 0,
+        digest: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    },
+    GoldenVector {
+        name: "gf8_partial_symbol",
+        field: ErasureField::Gf8,
+        len: 20,
+        digest: "422665bd9d67d10052f2aa76565c07ee06b70120ed769fd3be43abc1703caa32",
+    },
+    GoldenVector {
+        name: "gf8_full_codeword",
+        field: ErasureField::Gf8,
+        len: 231 * config::CHUNK_SIZE_BYTES,
+        digest: "9653047ea9fdd78d32753a774012161937ba7a57ae3581b65b39988a8a168ebf",
+    },
+    GoldenVector {
+        name: "gf8_two_codewords",
+        field: ErasureField::Gf8,
+        len: 231 * config::CHUNK_SIZE_BYTES + 100,
+        digest: "0eeba749d8123a2d9cc60faaa99bc46dfebad0b4555cc69fc7ee76a7b0cd0f66",
+    },
+    GoldenVector {
+        name: "gf16_one_codeword",
+        field: ErasureField::Gf16,
+        len: 1000,
+        digest: "cb5a772fec03fa436445def35a441380f344c30299bc305a111f91026b259c0b",
+    },
+];
+
+/// Arithmetic path of the Reed-Solomon backend. The crate builds `reed-solomon-erasure`
+/// without its `simd-accel` feature, so every CPU takes the same table-driven path.
+const ERASURE_BACKEND: &str = "reed-solomon-erasure 6 (portable lookup tables, simd-accel off)";
+
+/// Outcome of [`self_test`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Fields whose vectors were checked
+    pub fields: Vec<ErasureField>,
+    /// Number of golden vectors that passed
+    pub vectors: usize,
+    /// Arithmetic path the Reed-Solomon backend uses
+    pub backend: &'static str,
+    /// SIMD features the CPU reports, for diagnosing differences between hosts
+    pub cpu_features: Vec<&'static str>,
+    /// Time taken by the vectors
+    pub elapsed: Duration,
+}
+
+/// Runs the GF(2^8) known-answer vectors against the Reed-Solomon backend.
+///
+/// Each vector is encoded and compared to a golden digest of its symbols, then decoded
+/// after losing as many symbols of its first codeword as the code tolerates. This takes
+/// milliseconds, so nodes can call it at startup; encoding also runs it once per process
+/// before the first codeword. GF(2^16) vectors are checked by [`self_test_field`], since
+/// setting up that code takes seconds.
+///
+/// # Errors
+///
+/// [`KontorPoRError::ErasureCoding`] naming the first vector whose parity or recovery
+/// differs from the expected answer.
+pub fn self_test() -> Result<SelfTestReport> {
+    self_test_field(ErasureField::Gf8)
+}
+
+/// Like [`self_test`], but for the vectors coded over `field`.
+pub fn self_test_field(field: ErasureField) -> Result<SelfTestReport> {
+    let vectors: Vec<GoldenVector> = GOLDEN_VECTORS
+        .iter()
+        .filter(|vector| vector.field == field)
+        .copied()
+        .collect();
+    run_vectors(&vectors)
+}
+
+/// Outcome of the one-time self-test of each field.
+static SELF_TEST_GF8: OnceLock<Result<()>> = OnceLock::new();
+static SELF_TEST_GF16: OnceLock<Result<()>> = OnceLock::new();
+
+/// Runs [`self_test_field`] for `field` unless it already ran in this process, returning
+/// its (cached) outcome.
+fn ensure_self_test(field: ErasureField) -> Result<()> {
+    let outcome = match field {
+        ErasureField::Gf8 => &SELF_TEST_GF8,
+        ErasureField::Gf16 => &SELF_TEST_GF16,
+    };
+    outcome
+        .get_or_init(|| {
+            self_test_field(field).map(|_| ()).inspect_err(|e| {
+                warn!(?field, error = %e, "Reed-Solomon backend failed its self-test");
+            })
+        })
+        .clone()
+}
+
+/// Deterministic input of a golden vector.
+fn golden_input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 167 + (i >> 8)) as u8).collect()
+}
+
+/// Checks `vectors`, stopping at the first failure.
+fn run_vectors(vectors: &[GoldenVector]) -> Result<SelfTestReport> {
+    let start = Instant::now();
+    let mut fields = Vec::new();
+    for vector in vectors {
+        let failed = |what: &str| KontorPoRError::ErasureCoding {
+            details: format!("self-test vector {} {what}", vector.name),
+        };
+        let erasure = ErasureConfig::field(vector.field);
+        let code = erasure.code();
+        let input = golden_input(vector.len);
+
+        let mut symbols = Vec::new();
+        let mut hasher = Sha256::new();
+        encode_codewords(&input, erasure, None, |codeword| {
+            for symbol in codeword {
+                hasher.update(symbol);
+                symbols.push(Some(symbol.to_vec()));
+            }
+            Ok(())
+        })?;
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if digest != vector.digest {
+            return Err(failed(&format!(
+                "encoded to {digest}, expected {}",
+                vector.digest
+            )));
+        }
+
+        // Lose whole shards, spread over the first codeword, up to the parity count
+        let num_codewords = symbols.len() / code.total_symbols_per_codeword();
+        if num_codewords > 0 {
+            let per_shard = code.symbols_per_shard();
+            let shards = code.total_symbols_per_codeword() / per_shard;
+            let lost = code.parity_symbols_per_codeword() / per_shard;
+            for shard in (0..lost).map(|j| j * shards / lost) {
+                symbols[shard * per_shard..(shard + 1) * per_shard].fill(None);
+            }
+        }
+        match decode_file_symbols_with(&mut symbols, num_codewords, vector.len, erasure) {
+            Ok(decoded) if decoded == input => {}
+            Ok(_) => return Err(failed("decoded to different data")),
+            Err(e) => return Err(failed(&format!("failed to decode: {e}"))),
+        }
+
+        if !fields.contains(&vector.field) {
+            fields.push(vector.field);
+        }
+    }
+
+    Ok(SelfTestReport {
+        fields,
+        vectors: vectors.len(),
+        backend: ERASURE_BACKEND,
+        cpu_features: cpu_features(),
+        elapsed: start.elapsed(),
+    })
+}
+
+/// SIMD features the CPU reports.
+fn cpu_features() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut features = Vec::new();
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    for (name, detected) in [
+        ("sse2", std::arch::is_x86_feature_detected!("sse2")),
+        ("ssse3", std::arch::is_x86_feature_detected!("ssse3")),
+        ("avx2", std::arch::is_x86_feature_detected!("avx2")),
+        ("avx512f", std::arch::is_x86_feature_detected!("avx512f")),
+    ] {
+        if detected {
+            features.push(name);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        features.push("neon");
+    }
+    features
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,7 +1420,11 @@ mod tests {
     #[test]
     fn test_empty_data() {
         let data = b"";
-        assert!(encode_file_symbols(data).is_err());
+        let symbols = encode_file_symbols(data).unwrap();
+        assert!(symbols.is_empty());
+
+        let mut none: Vec<Option<Vec<u8>>> = Vec::new();
+        assert!(decode_file_symbols(&mut none, 0, 0).unwrap().is_empty());
     }
 
     #[test]
@@ -265,4 +1458,78 @@ mod tests {
         let reconstructed = decode_file_symbols(&mut full, 1, data.len()).unwrap();
         assert_eq!(reconstructed, data);
     }
+
+    #[test]
+    fn test_split_decode_with_missing_symbols() {
+        let data: Vec<u8> = (0..9000).map(|i| (i % 251) as u8).collect();
+        let encoded = encode_file_symbols_split(&data).unwrap();
+        assert_eq!(encoded.layout.num_codewords, 2);
+        assert_eq!(encoded.data_symbols.len(), 291);
+        assert_eq!(encoded.parity_symbols.len(), 48);
+
+        let mut data_tier: Vec<Option<Vec<u8>>> =
+            encoded.data_symbols.into_iter().map(Some).collect();
+        let mut parity_tier: Vec<Option<Vec<u8>>> =
+            encoded.parity_symbols.into_iter().map(Some).collect();
+        data_tier[3] = None;
+        data_tier[250] = None;
+        parity_tier[30] = None;
+
+        let reconstructed =
+            decode_split_symbols(&data_tier, &parity_tier, &encoded.layout, data.len()).unwrap();
+        assert_eq!(reconstructed, data);
+
+        // Losing the whole parity tier leaves nothing to repair the data tier with
+        let no_parity = vec![None; parity_tier.len()];
+        assert!(decode_split_symbols(&data_tier, &no_parity, &encoded.layout, data.len()).is_err());
+
+        assert!(matches!(
+            decode_split_symbols(&data_tier[1..], &parity_tier, &encoded.layout, data.len()),
+            Err(KontorPoRError::ErasureCoding { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_more_codewords_than_symbols() {
+        let data = b"Short file";
+        let mut symbols: Vec<Option<Vec<u8>>> = encode_file_symbols(data)
+            .unwrap()
+            .into_iter()
+            .map(Some)
+            .collect();
+
+        for num_codewords in [2, usize::MAX / 2, usize::MAX] {
+            assert!(matches!(
+                decode_file_symbols(&mut symbols, num_codewords, data.len()),
+                Err(KontorPoRError::ErasureCoding { .. })
+            ));
+        }
+        assert_eq!(
+            decode_file_symbols(&mut symbols, 1, data.len()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_self_test_vectors_pass() {
+        let report = self_test().unwrap();
+        assert_eq!(report.fields, vec![ErasureField::Gf8]);
+        assert_eq!(report.vectors, 4);
+        assert_eq!(report.backend, ERASURE_BACKEND);
+
+        let report = self_test_field(ErasureField::Gf16).unwrap();
+        assert_eq!(report.fields, vec![ErasureField::Gf16]);
+        assert_eq!(report.vectors, 1);
+    }
+
+    #[test]
+    fn test_self_test_detects_corrupted_vector() {
+        let mut vectors = GOLDEN_VECTORS[..4].to_vec();
+        vectors[2].digest = "9653047ea9fdd78d32753a774012161937ba7a57ae3581b65b39988a8a168ebe";
+        let err = run_vectors(&vectors).unwrap_err();
+        assert!(
+            matches!(err, KontorPoRError::ErasureCoding { ref details } if details.contains("gf8_full_codeword")),
+            "unexpected error: {err:?}"
+        );
+    }
 }
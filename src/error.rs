@@ -1,7 +1,7 @@
 //! Error types for the Kontor PoR library
 
 /// Error types for the Kontor PoR library
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum KontorPoRError {
     /// Merkle tree construction or operations failed
     #[error("Merkle tree error: {0}")]
@@ -31,14 +31,54 @@ pub enum KontorPoRError {
     #[error("File not found: {file_id}")]
     FileNotFound { file_id: String },
 
+    /// Challenged files with no matching prepared file
+    #[error("Missing prepared file for challenged file IDs: {}", file_ids.join(", "))]
+    MissingPreparedFile { file_ids: Vec<String> },
+
+    /// Prepared file disposed of with `PreparedFile::zeroize`
+    #[error("Prepared file {file_id} was zeroized and can no longer prove")]
+    PreparedFileZeroized { file_id: String },
+
+    /// Prepared file not referenced by any challenge
+    #[error("Unused prepared file: {file_id} is not referenced by any challenge")]
+    UnusedPreparedFile { file_id: String },
+
     /// File not found in ledger
     #[error("File not in ledger: {file_id} (possible depth spoofing)")]
     FileNotInLedger { file_id: String },
 
+    /// A challenge embedded in a proof names a file the ledger commits to differently
+    #[error("Embedded metadata mismatch: {file_id} does not match its ledger entry")]
+    EmbeddedMetadataMismatch { file_id: String },
+
+    /// File ID already registered in the ledger with a different root commitment
+    #[error("File ID conflict: {file_id} is already registered with a different root")]
+    FileIdConflict { file_id: String },
+
+    /// Challenges name a ledger the `PorSystem` was not given
+    #[error("Unknown ledger: {id}")]
+    UnknownLedger { id: String },
+
     /// Inconsistent challenge parameters
     #[error("Challenge mismatch: {field} values must be consistent across all challenges")]
     ChallengeMismatch { field: String },
 
+    /// Per-step seed schedule does not cover exactly one seed per step
+    #[error("Invalid seed schedule: expected {expected} per-step seeds, got {got}")]
+    InvalidSeedSchedule { expected: usize, got: usize },
+
+    /// Explicit-index challenge does not give exactly one index per step
+    #[error("Invalid explicit indices: expected {expected} indices, got {got}")]
+    InvalidExplicitIndices { expected: usize, got: usize },
+
+    /// Explicit challenge index is not a leaf of the file's tree
+    #[error("Challenge index {index} out of range for file {file_id} with {leaves} leaves")]
+    ChallengeIndexOutOfRange {
+        file_id: String,
+        index: usize,
+        leaves: usize,
+    },
+
     /// Invalid challenge count
     #[error("Invalid challenge count: {count} (must be > 0 and <= {max})", max = crate::config::MAX_NUM_CHALLENGES)]
     InvalidChallengeCount { count: usize },
@@ -51,18 +91,157 @@ pub enum KontorPoRError {
     #[error("Metadata mismatch: file root does not match expected value")]
     MetadataMismatch,
 
+    /// File metadata is internally inconsistent (see [`crate::api::FileMetadata::validate`])
+    #[error("Invalid metadata: {field} {reason}")]
+    InvalidMetadata { field: String, reason: String },
+
+    /// A tree rebuilt from stored symbols does not match the file's metadata root
+    #[error("Root mismatch: symbols for {file_id} do not rebuild the metadata root")]
+    RootMismatch { file_id: String },
+
+    /// A prepared file's leaf does not encode a 31-byte symbol
+    #[error("Invalid leaf encoding: leaf {index} is wider than 31 bytes")]
+    InvalidLeafEncoding { index: usize },
+
+    /// A precomputed leaf hash does not match the symbol it was supplied for
+    #[error("Leaf hash mismatch: the hash supplied for symbol {index} does not encode it")]
+    LeafHashMismatch { index: usize },
+
+    /// A file, or metadata describing one, exceeds `config::MAX_FILE_SIZE`
+    #[error("File size {size} bytes exceeds the maximum of {max} bytes")]
+    FileTooLarge { size: u64, max: u64 },
+
     /// Erasure coding validation failed
     #[error("Erasure coding error: {details}")]
     ErasureCoding { details: String },
 
+    /// Too many symbols are missing to decode one or more codewords
+    ///
+    /// `missing_per_codeword` counts the missing symbols of every codeword in the file;
+    /// each codeword needs `needed` of its symbols (231 of 255 over GF(2^8)). Over
+    /// GF(2^16) the symbols present must also make up `needed / 2` whole shards.
+    #[error(
+        "Reconstruction failed: codewords {unrecoverable_codewords:?} have fewer than {needed} usable symbols"
+    )]
+    ReconstructionFailed {
+        unrecoverable_codewords: Vec<usize>,
+        missing_per_codeword: Vec<usize>,
+        needed: usize,
+    },
+
+    /// Symbols coded over one Reed-Solomon field were handed to the other
+    #[error("Erasure field mismatch: file is coded over {found}, but {expected} was requested")]
+    ErasureFieldMismatch {
+        expected: crate::api::ErasureField,
+        found: crate::api::ErasureField,
+    },
+
     /// Ledger validation failed
     #[error("Ledger validation failed: {reason}")]
     LedgerValidation { reason: String },
 
+    /// Ledger has reached its fixed capacity
+    #[error("Ledger full: capacity of {capacity} files reached")]
+    LedgerFull { capacity: usize },
+
     /// Invalid ledger root in proof (not in historical roots)
     #[error("Invalid ledger root in proof: {proof_root} - {reason}")]
     InvalidLedgerRoot { proof_root: String, reason: String },
 
+    /// A proof's ledger index cannot be mapped to a file at its historical ledger root
+    #[error(
+        "Cannot resolve ledger index {index} at historical root {proof_root}: the ledger no longer records which file it held"
+    )]
+    CannotResolveHistorical { proof_root: String, index: usize },
+
+    /// A challenged file's tree is deeper than the circuit shape's file_tree_depth
+    #[error(
+        "File {file_id} has tree depth {depth}, exceeding the circuit's file_tree_depth {max}"
+    )]
+    DepthExceedsShape {
+        file_id: String,
+        depth: usize,
+        max: usize,
+    },
+
+    /// The ledger's aggregated tree is deeper than the circuit shape's aggregated_tree_depth
+    #[error("Ledger depth {depth} exceeds the circuit's aggregated_tree_depth {max}")]
+    LedgerDepthExceedsShape { depth: usize, max: usize },
+
+    /// A proof was generated under another hash profile than its challenges' files record
+    #[error("Hash profile mismatch: proof uses {proof} hashing, file metadata records {metadata}")]
+    HashProfileMismatch {
+        proof: crate::poseidon::HashProfile,
+        metadata: crate::poseidon::HashProfile,
+    },
+
+    /// A proof records another circuit shape than the one derived from its challenges
+    #[error("Shape mismatch: proof records shape {proof}, the challenges derive {expected}")]
+    ShapeMismatch {
+        proof: crate::config::Shape,
+        expected: crate::config::Shape,
+    },
+
+    /// A step's witness breaks a circuit constraint, found by the pre-check enabled with
+    /// [`crate::api::ProveOptions::precheck_constraints`]
+    #[error("Constraint pre-check failed at step {step}: {path} ({class:?}) is not satisfied")]
+    ConstraintPrecheckFailed {
+        step: usize,
+        /// Namespace path of the first unsatisfied constraint
+        path: String,
+        class: crate::circuit_safety::ConstraintClass,
+        /// File slot the constraint belongs to, if any
+        file_slot: Option<usize>,
+    },
+
+    /// A proof or parameter set was produced under another public input/output layout
+    /// than [`crate::config::PUBLIC_IO_SCHEMA_VERSION`]
+    #[error("Public IO schema version mismatch: expected {expected}, found {found}")]
+    SchemaVersionMismatch { expected: u32, found: u32 },
+
+    /// A serialized challenge declares a schema version this build does not read
+    #[error("Unsupported challenge schema version {found} (supported: {supported})")]
+    UnsupportedChallengeVersion { found: u32, supported: u32 },
+
+    /// A signed challenge's signature does not verify under the given key
+    #[error("Invalid challenge signature")]
+    InvalidChallengeSignature,
+
+    /// A proof's final leaf output differs from the value the verifier expected
+    #[error("Leaf value mismatch for file {file_id} at step {step}")]
+    LeafValueMismatch { file_id: String, step: usize },
+
+    /// A challenge's block height is later than the verifier's current height
+    #[error("Challenge from the future: block height {height} is after current height {current}")]
+    ChallengeFromFuture { height: u64, current: u64 },
+
+    /// A proof's ledger root is not the root the ledger recorded for a challenge's height
+    #[error("Ledger root {proof_root} was not in effect at block height {height}")]
+    RootNotValidAtHeight { proof_root: String, height: u64 },
+
+    /// Challenges imply more recursive steps than the verifier allows
+    #[error("Too many steps: challenges imply {steps} recursive steps, limit is {max}")]
+    TooManySteps { steps: usize, max: usize },
+
+    /// A prove checkpoint was taken with different challenges, ledger or parameters
+    #[error("Checkpoint mismatch: {reason}")]
+    CheckpointMismatch { reason: String },
+
+    /// Verification exceeded its time budget
+    #[error("Verification timed out after {elapsed:?} (limit {timeout:?})")]
+    VerificationTimedOut {
+        elapsed: std::time::Duration,
+        timeout: std::time::Duration,
+    },
+
+    /// A failure injected through the `failpoints` feature
+    #[error("Injected failure at {point}")]
+    InjectedFailure { point: String },
+
+    /// The operation was stopped through its cancellation flag
+    #[error("Operation cancelled")]
+    Cancelled,
+
     /// Cryptographic operation failed
     #[error("Cryptographic error: {0}")]
     Cryptographic(String),
@@ -71,10 +250,21 @@ pub enum KontorPoRError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    /// Bytes decoded as a field element are not below the field modulus
+    #[error("Non-canonical field element encoding in {context}")]
+    NonCanonicalFieldElement { context: String },
+
     /// IO operation failed
     #[error("IO error: {0}")]
     IO(String),
 
+    /// Timed out waiting for another writer's parameter cache lock
+    #[error("Timed out after {timeout:?} waiting for parameter lock {path}")]
+    ParamsLockTimeout {
+        path: String,
+        timeout: std::time::Duration,
+    },
+
     /// Index out of bounds
     #[error("Index out of bounds: index {index}, length {length}")]
     IndexOutOfBounds { index: usize, length: usize },
@@ -82,6 +272,60 @@ pub enum KontorPoRError {
     /// Invalid chunk encoding (chunk too large for field)
     #[error("Invalid chunk encoding: chunk size {size} bytes exceeds maximum {max} bytes")]
     InvalidChunkEncoding { size: usize, max: usize },
+
+    /// Circuit witness does not match the circuit shape
+    #[error("Invalid witness: {0}")]
+    Witness(#[from] WitnessError),
+}
+
+/// Structural problems in a circuit witness, detected before synthesis
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WitnessError {
+    /// Number of witness slots differs from files_per_step
+    #[error("expected {expected} witness slots, got {got}")]
+    SlotCount { expected: usize, got: usize },
+
+    /// More real files than witness slots
+    #[error("{num_real_files} real files exceed {slots} witness slots")]
+    RealFileCount { num_real_files: usize, slots: usize },
+
+    /// File sibling vector not padded to file_tree_depth
+    #[error("slot {slot}: file_siblings length {got} does not match file_tree_depth {expected}")]
+    FileSiblingsLength {
+        slot: usize,
+        expected: usize,
+        got: usize,
+    },
+
+    /// Declared file depth exceeds the circuit's file_tree_depth
+    #[error("slot {slot}: actual_depth {depth} exceeds file_tree_depth {max}")]
+    DepthOutOfBounds {
+        slot: usize,
+        depth: usize,
+        max: usize,
+    },
+
+    /// Aggregation sibling vector does not match aggregated_tree_depth
+    #[error(
+        "slot {slot}: agg_siblings length {got} does not match aggregated_tree_depth {expected}"
+    )]
+    AggSiblingsLength {
+        slot: usize,
+        expected: usize,
+        got: usize,
+    },
+
+    /// Ledger index does not fit in aggregated_tree_depth bits
+    #[error("slot {slot}: ledger_index {index} out of range for aggregated_tree_depth {depth}")]
+    LedgerIndexOutOfBounds {
+        slot: usize,
+        index: usize,
+        depth: usize,
+    },
+
+    /// Ledger depth exceeds the circuit's aggregated_tree_depth
+    #[error("ledger_depth {depth} exceeds aggregated_tree_depth {max}")]
+    LedgerDepthOutOfBounds { depth: usize, max: usize },
 }
 
 /// Convenience Result type for Kontor PoR operations
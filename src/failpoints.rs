@@ -0,0 +1,121 @@
+//! Failure injection points for robustness testing.
+//!
+//! Requires the `failpoints` feature. The library evaluates a small set of named points at
+//! sensitive moments of proving, parameter caching and ledger updates. A point does
+//! nothing until a test arms it with [`configure`] or [`configure_after`]; once armed it
+//! either returns [`KontorPoRError::InjectedFailure`] from the surrounding operation or
+//! panics, so embedding applications can check how they handle failures at exactly that
+//! moment.
+//!
+//! The registry is process-wide. Tests that arm points should not run concurrently with
+//! other tests exercising the same code paths, and should call [`reset`] when done.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use kontor_crypto::failpoints::{self, points, FailAction};
+//!
+//! // Fail the third folded step of the next proof
+//! failpoints::configure_after(points::PROVE_AFTER_STEP, 2, FailAction::Error);
+//! // ... prove, expecting Err(KontorPoRError::InjectedFailure { .. }) ...
+//! failpoints::reset();
+//! ```
+
+use crate::instrumentation::warn;
+use crate::{KontorPoRError, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Names of the injection points evaluated by the library.
+pub mod points {
+    /// After the witness for a proving step has been generated, before it is folded.
+    pub const PROVE_AFTER_WITNESS_GEN: &str = "prove::after_witness_gen";
+    /// After each step is folded and, for checkpointed proofs, after its checkpoint is
+    /// written. The `k`-th hit follows step `k`.
+    pub const PROVE_AFTER_STEP: &str = "prove::after_step";
+    /// After compression, before the final proof is assembled.
+    pub const PROVE_BEFORE_SERIALIZATION: &str = "prove::before_serialization";
+    /// After parameters for a shape are generated, before they are cached.
+    pub const PARAMS_AFTER_GENERATION: &str = "params::after_generation";
+    /// Before generated parameters are written to the disk cache.
+    pub const PARAMS_BEFORE_CACHE_WRITE: &str = "params::before_cache_write";
+    /// After a ledger update rebuilds the aggregated tree, before the new root is appended
+    /// to the historical roots.
+    pub const LEDGER_BEFORE_HISTORICAL_ROOT: &str = "ledger::before_historical_root";
+
+    /// Every point above.
+    pub const ALL: [&str; 6] = [
+        PROVE_AFTER_WITNESS_GEN,
+        PROVE_AFTER_STEP,
+        PROVE_BEFORE_SERIALIZATION,
+        PARAMS_AFTER_GENERATION,
+        PARAMS_BEFORE_CACHE_WRITE,
+        LEDGER_BEFORE_HISTORICAL_ROOT,
+    ];
+}
+
+/// What an armed point does when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailAction {
+    /// Return [`KontorPoRError::InjectedFailure`] from the surrounding operation
+    Error,
+    /// Panic with a message naming the point
+    Panic,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Armed {
+    action: FailAction,
+    /// Hits to let pass before firing
+    skip: usize,
+}
+
+static REGISTRY: Lazy<Mutex<HashMap<String, Armed>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn registry() -> std::sync::MutexGuard<'static, HashMap<String, Armed>> {
+    // A panic injected by this module never happens while the lock is held
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Arm `name` to fire on every hit.
+pub fn configure(name: &str, action: FailAction) {
+    configure_after(name, 0, action);
+}
+
+/// Arm `name` to let the first `skip` hits pass and fire on every later hit.
+pub fn configure_after(name: &str, skip: usize, action: FailAction) {
+    registry().insert(name.to_string(), Armed { action, skip });
+}
+
+/// Disarm `name`.
+pub fn remove(name: &str) {
+    registry().remove(name);
+}
+
+/// Disarm every point.
+pub fn reset() {
+    registry().clear();
+}
+
+/// Evaluate the point `name`, failing as configured if it is armed.
+pub(crate) fn eval(name: &str) -> Result<()> {
+    let action = {
+        let mut registry = registry();
+        match registry.get_mut(name) {
+            None => return Ok(()),
+            Some(armed) if armed.skip > 0 => {
+                armed.skip -= 1;
+                return Ok(());
+            }
+            Some(armed) => armed.action,
+        }
+    };
+    warn!("Failure injected at {}", name);
+    match action {
+        FailAction::Error => Err(KontorPoRError::InjectedFailure {
+            point: name.to_string(),
+        }),
+        FailAction::Panic => panic!("failpoint {} panicked", name),
+    }
+}
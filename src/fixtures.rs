@@ -0,0 +1,378 @@
+//! Deterministic test vectors for cross-implementation verification.
+//!
+//! [`generate_fixture`] builds files from seeded randomness, registers them in a ledger,
+//! challenges every file and proves the batch, recording every intermediate value an
+//! independent verifier needs: file bytes and metadata, the ledger's rc leaves and root,
+//! challenges and their IDs, the leaf index opened for each file at each step, the
+//! public input vector `z0` and the output vector `zn`, and the serialized proof.
+//!
+//! Everything except the proof bytes is a pure function of the [`FixtureSpec`]; Nova
+//! proofs are randomized, so two runs produce different (equally valid) proofs.
+//!
+//! Field elements are exported as lowercase hex of their canonical 32-byte
+//! little-endian representation; byte strings (file data, proof) as lowercase hex.
+//! Requires the `test-vectors` feature.
+
+#[cfg(feature = "prover")]
+use crate::{
+    api::{self, PorSystem},
+    merkle::canonical::field_to_hex,
+    params,
+};
+use crate::{
+    api::{Challenge, ErasureCode, FieldElement, FileMetadata, Proof, TreeArity},
+    config,
+    ledger::FileLedger,
+    merkle::canonical::field_from_bytes,
+    KontorPoRError, Result,
+};
+#[cfg(feature = "prover")]
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version of the exported fixture layout.
+pub const FIXTURE_FORMAT_VERSION: u16 = 1;
+
+/// Inputs that fully determine a fixture (apart from proof randomness).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureSpec {
+    /// Size in bytes of each generated file; every file is challenged
+    pub file_sizes: Vec<usize>,
+    /// Number of challenged sectors (recursive steps) per file
+    pub num_challenges: usize,
+    /// Seed for file contents and the challenge seed
+    pub seed: u64,
+    /// Block height recorded in every challenge
+    pub block_height: u64,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            file_sizes: vec![100, 1000],
+            num_challenges: 3,
+            seed: config::TEST_RANDOM_SEED,
+            block_height: 1000,
+        }
+    }
+}
+
+/// A generated file with its public metadata and ledger position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureFile {
+    pub filename: String,
+    /// Hex of the raw file bytes
+    pub data: String,
+    pub file_id: String,
+    pub root: String,
+    pub padded_len: usize,
+    pub original_size: usize,
+    pub depth: usize,
+    /// Root commitment, the file's leaf in the ledger tree
+    pub rc: String,
+    /// Canonical index of `rc` in the ledger tree
+    pub ledger_index: usize,
+}
+
+/// The aggregated ledger tree the proof was generated against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureLedger {
+    pub root: String,
+    pub depth: usize,
+    /// Leaves of the ledger tree (rc values in file_id order, then padding)
+    pub leaves: Vec<String>,
+}
+
+/// A challenge in the order the proof's `challenge_ids` list them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureChallenge {
+    pub file_id: String,
+    pub block_height: u64,
+    pub num_challenges: usize,
+    pub seed: String,
+    pub prover_id: String,
+    pub challenge_id: String,
+}
+
+/// Openings of one recursive step, one entry per real file slot in circuit order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureStep {
+    /// File ID in each slot (challenges sorted by file_id)
+    pub file_ids: Vec<String>,
+    /// Leaf index opened in each slot
+    pub leaf_indices: Vec<usize>,
+    /// Leaf value opened in each slot
+    pub leaves: Vec<String>,
+    /// Challenge chain state after the step
+    pub state_out: String,
+}
+
+/// A complete, self-consistent test vector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub format_version: u16,
+    pub spec: FixtureSpec,
+    pub files: Vec<FixtureFile>,
+    pub ledger: FixtureLedger,
+    pub challenges: Vec<FixtureChallenge>,
+    /// Circuit shape: file slots per step
+    pub files_per_step: usize,
+    /// Circuit shape: padded file tree depth
+    pub file_tree_depth: usize,
+    /// Circuit shape: ledger tree depth (0 for single-file proofs)
+    pub aggregated_tree_depth: usize,
+    /// Number of recursive steps the verifier checks
+    pub num_steps: usize,
+    pub steps: Vec<FixtureStep>,
+    /// Public inputs of the first step
+    pub z0: Vec<String>,
+    /// Public outputs after the last step
+    pub zn: Vec<String>,
+    /// Hex of [`Proof::to_bytes`]
+    pub proof: String,
+}
+
+/// Generates a fixture for `spec`: prepares the files, builds the ledger, proves a
+/// challenge on every file and records all intermediate values. Needs the `prover` feature.
+#[cfg(feature = "prover")]
+pub fn generate_fixture(spec: FixtureSpec) -> Result<Fixture> {
+    if spec.file_sizes.is_empty() {
+        return Err(KontorPoRError::InvalidInput(
+            "Fixture spec must contain at least one file".to_string(),
+        ));
+    }
+
+    let mut ledger = FileLedger::new();
+    let mut prepared_files = BTreeMap::new();
+    let mut data_by_id = BTreeMap::new();
+    let mut challenges = Vec::new();
+    for (i, &size) in spec.file_sizes.iter().enumerate() {
+        let mut data = vec![0u8; size];
+        StdRng::seed_from_u64(spec.seed.wrapping_add(i as u64)).fill_bytes(&mut data);
+        let (prepared, metadata) = api::prepare_file(&data, &format!("fixture_{}.dat", i))?;
+        ledger.add_file(&metadata)?;
+
+        challenges.push(Challenge::new(
+            metadata.clone(),
+            spec.block_height,
+            spec.num_challenges,
+            FieldElement::from(spec.seed),
+            String::from("fixture"),
+        ));
+        data_by_id.insert(metadata.file_id.clone(), (data, metadata));
+        prepared_files.insert(prepared.file_id.clone(), prepared);
+    }
+
+    let files_ref: Vec<_> = challenges
+        .iter()
+        .map(|c| &prepared_files[&c.file_metadata.file_id])
+        .collect();
+    let proof = PorSystem::new(&ledger).prove(files_ref, &challenges)?;
+
+    // Replay the prover's challenge chain to expose the opened leaves
+    let plan = api::plan::Plan::make_plan(&challenges, &ledger)?;
+    let is_multi_file = plan.shape.aggregated_tree_depth > 0;
+    let mut state = plan.initial_state;
+    let mut steps = Vec::with_capacity(spec.num_challenges);
+    for step_num in 0..spec.num_challenges {
+        let mut step = FixtureStep {
+            file_ids: Vec::new(),
+            leaf_indices: Vec::new(),
+            leaves: Vec::new(),
+            state_out: String::new(),
+        };
+        for (file_idx, challenge) in plan.sorted_challenges.iter().enumerate() {
+            let file = &prepared_files[&challenge.file_metadata.file_id];
+            let leaf_index = api::witness::challenged_leaf_index(
+                challenge,
+                file,
+                file_idx,
+                state,
+                step_num,
+                is_multi_file,
+            );
+            let leaf = file.tree.layers[0][leaf_index];
+            if challenge.file_metadata.depth() > 0 {
+                state = crate::utils::advance_challenge_state(state, leaf);
+            }
+            step.file_ids.push(challenge.file_metadata.file_id.clone());
+            step.leaf_indices.push(leaf_index);
+            step.leaves.push(field_to_hex(&leaf));
+        }
+        step.state_out = field_to_hex(&state);
+        steps.push(step);
+    }
+
+    // Run the verifier's SNARK check directly to capture the output vector
+    let params = params::load_or_generate_params_for_shape(&plan.param_shape(proof.shape))?;
+    let z0 = plan.build_z0_primary();
+    let zn = proof
+        .compressed_snark
+        .verify(&params.keys.vk, spec.num_challenges, &z0)
+        .map_err(|e| KontorPoRError::Snark(format!("Fixture proof failed to verify: {e:?}")))?;
+
+    let files = data_by_id
+        .values()
+        .map(|(data, metadata)| {
+            let depth = metadata.depth();
+            let rc = metadata.tree_arity.root_commitment(metadata.root, depth);
+            FixtureFile {
+                filename: metadata.filename().to_string(),
+                data: bytes_hex(data),
+                file_id: metadata.file_id.clone(),
+                root: field_to_hex(&metadata.root),
+                padded_len: metadata.padded_len,
+                original_size: metadata.original_size,
+                depth,
+                rc: field_to_hex(&rc),
+                ledger_index: ledger
+                    .lookup_by_rc(rc)
+                    .expect("fixture file is in the ledger")
+                    .1,
+            }
+        })
+        .collect();
+
+    Ok(Fixture {
+        format_version: FIXTURE_FORMAT_VERSION,
+        files,
+        ledger: FixtureLedger {
+            root: field_to_hex(&ledger.root()),
+            depth: ledger.depth(),
+            leaves: ledger.leaves().iter().map(field_to_hex).collect(),
+        },
+        challenges: challenges
+            .iter()
+            .map(|c| FixtureChallenge {
+                file_id: c.file_metadata.file_id.clone(),
+                block_height: c.block_height,
+                num_challenges: c.num_challenges,
+                seed: field_to_hex(&c.seed),
+                prover_id: c.prover_id.clone(),
+                challenge_id: c.id().to_hex(),
+            })
+            .collect(),
+        files_per_step: plan.shape.files_per_step,
+        file_tree_depth: plan.shape.file_tree_depth,
+        aggregated_tree_depth: proof.shape.aggregated_tree_depth,
+        num_steps: spec.num_challenges,
+        steps,
+        z0: z0.iter().map(field_to_hex).collect(),
+        zn: zn.iter().map(field_to_hex).collect(),
+        proof: bytes_hex(&proof.to_bytes()?),
+        spec,
+    })
+}
+
+impl Fixture {
+    /// Exports the fixture as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            KontorPoRError::Serialization(format!("Failed to serialize fixture: {}", e))
+        })
+    }
+
+    /// Parses a fixture exported with [`Self::to_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        let fixture: Fixture = serde_json::from_str(json).map_err(|e| {
+            KontorPoRError::Serialization(format!("Failed to deserialize fixture: {}", e))
+        })?;
+        if fixture.format_version != FIXTURE_FORMAT_VERSION {
+            return Err(KontorPoRError::Serialization(format!(
+                "Unsupported fixture format version: {}",
+                fixture.format_version
+            )));
+        }
+        Ok(fixture)
+    }
+
+    /// Public metadata of every file, in the order of [`Self::files`].
+    pub fn file_metadata(&self) -> Result<Vec<FileMetadata>> {
+        self.files
+            .iter()
+            .map(|file| {
+                Ok(FileMetadata {
+                    root: parse_field_hex(&file.root)?,
+                    file_id: file.file_id.clone(),
+                    padded_len: file.padded_len,
+                    original_size: file.original_size,
+                    annotations: Default::default(),
+                    tree_arity: TreeArity::Binary,
+                    erasure: Some(ErasureCode::ReedSolomon),
+                    salt_commitment: None,
+                    hash_profile: Default::default(),
+                }
+                .with_filename(&file.filename))
+            })
+            .collect()
+    }
+
+    /// Rebuilds the ledger from the exported file metadata.
+    pub fn ledger(&self) -> Result<FileLedger> {
+        let mut ledger = FileLedger::new();
+        ledger.add_files(&self.file_metadata()?)?;
+        Ok(ledger)
+    }
+
+    /// Rebuilds the challenges, in the order of [`Self::challenges`].
+    pub fn challenges(&self) -> Result<Vec<Challenge>> {
+        let metadata: BTreeMap<_, _> = self
+            .file_metadata()?
+            .into_iter()
+            .map(|m| (m.file_id.clone(), m))
+            .collect();
+        self.challenges
+            .iter()
+            .map(|c| {
+                let file_metadata = metadata.get(&c.file_id).cloned().ok_or_else(|| {
+                    KontorPoRError::FileNotFound {
+                        file_id: c.file_id.clone(),
+                    }
+                })?;
+                Ok(Challenge::new(
+                    file_metadata,
+                    c.block_height,
+                    c.num_challenges,
+                    parse_field_hex(&c.seed)?,
+                    c.prover_id.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Decodes the exported proof.
+    pub fn proof(&self) -> Result<Proof> {
+        Proof::from_bytes(&parse_bytes_hex(&self.proof)?)
+    }
+}
+
+#[cfg(feature = "prover")]
+fn bytes_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_bytes_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(KontorPoRError::Serialization(
+            "Hex string must have an even number of ASCII characters".to_string(),
+        ));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| {
+                KontorPoRError::Serialization(format!("Invalid hex '{}': {}", &hex[i..i + 2], e))
+            })
+        })
+        .collect()
+}
+
+fn parse_field_hex(hex: &str) -> Result<FieldElement> {
+    let bytes: [u8; 32] = parse_bytes_hex(hex)?.try_into().map_err(|_| {
+        KontorPoRError::Serialization("Field element hex must encode 32 bytes".to_string())
+    })?;
+    field_from_bytes(bytes).ok_or_else(|| KontorPoRError::NonCanonicalFieldElement {
+        context: "fixture".to_string(),
+    })
+}
@@ -0,0 +1,64 @@
+//! Crate-internal logging facade over `tracing`.
+//!
+//! With the `instrumentation` feature (on by default) this re-exports the `tracing` macros
+//! and span types the library uses, and the caller's subscriber decides what is recorded.
+//! Without it the event macros expand to nothing and spans are zero-sized, so the library
+//! neither depends on `tracing` nor pays for span bookkeeping or field evaluation.
+//!
+//! Library code logs through this module rather than `tracing` directly, and records
+//! values as fields (`debug!(root = ?root, "...")`) so they are only evaluated when the
+//! event is enabled.
+
+#[cfg(feature = "instrumentation")]
+pub(crate) use tracing::{debug, debug_span, field, info, info_span, trace, warn, Span};
+
+#[cfg(not(feature = "instrumentation"))]
+pub(crate) use disabled::{debug, debug_span, field, info, info_span, trace, warn, Span};
+
+#[cfg(not(feature = "instrumentation"))]
+mod disabled {
+    macro_rules! event {
+        ($($arg:tt)*) => {};
+    }
+
+    macro_rules! span {
+        ($($arg:tt)*) => {
+            $crate::instrumentation::Span
+        };
+    }
+
+    pub(crate) use event as debug;
+    pub(crate) use event as info;
+    pub(crate) use event as trace;
+    pub(crate) use event as warn;
+    pub(crate) use span as debug_span;
+    pub(crate) use span as info_span;
+
+    /// Stand-in for `tracing::Span` that records nothing.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(crate) struct Span;
+
+    impl Span {
+        pub(crate) fn current() -> Self {
+            Span
+        }
+
+        pub(crate) fn entered(self) -> Self {
+            self
+        }
+
+        pub(crate) fn enter(&self) -> Self {
+            Span
+        }
+
+        pub(crate) fn record<V>(&self, _field: &str, _value: V) -> &Self {
+            self
+        }
+    }
+
+    pub(crate) mod field {
+        pub(crate) fn debug<T>(value: T) -> T {
+            value
+        }
+    }
+}
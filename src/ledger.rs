@@ -2,27 +2,42 @@
 //!
 //! This module defines the `FileLedger`, which manages the aggregated Merkle tree
 //! of file root commitments (rc values). It provides functionality for adding files,
-//! persisting the ledger, and generating aggregation proofs.
+//! persisting the ledger, and generating aggregation proofs and standalone membership
+//! certificates.
 //!
 //! ## Canonical Index Ordering
 //!
-//! **INVARIANT**: Canonical file indices are determined by lexicographic ordering
-//! of file identifiers in the `BTreeMap`. This ensures deterministic, stable indices:
+//! **INVARIANT**: Under the default [`IndexPolicy::Canonical`], file indices are determined
+//! by lexicographic ordering of file identifiers in the `BTreeMap`. This ensures
+//! deterministic indices:
 //! - Index 0 = first file in lexicographic order
 //! - Index i = i-th file in sorted key order
-//! - Indices remain stable as long as files aren't removed
+//! - Adding or removing a file shifts the indices of every file sorting after it
 //!
-//! The aggregated tree is built from rc values in this same key order, ensuring
-//! that `get_canonical_index_for_rc()` returns the correct tree position.
+//! Under [`IndexPolicy::InsertionOrder`], each file keeps the slot it was assigned when
+//! first added; removed files leave a tombstoned slot that is never reused.
+//!
+//! The aggregated tree is built from rc values in index order, ensuring that
+//! `get_canonical_index_for_rc()` returns the correct tree position.
 
-use crate::merkle::{build_tree_from_leaves, get_padded_proof_for_leaf, MerkleTree, F};
-use crate::poseidon::calculate_root_commitment;
+use crate::api::{Proof, RootUsage};
+use crate::ledger_journal::{self, Journal, JournalHandle, JournalOp};
+use crate::merkle::{
+    build_tree_from_leaves,
+    canonical::{field_from_bytes, field_to_hex},
+    get_padded_proof_for_leaf, verify_merkle_proof_in_place, CircuitMerkleProof, MerkleTree,
+    TreeArity, F,
+};
+use crate::poseidon::{calculate_ledger_attestation_commitment, HashProfile};
 use crate::KontorPoRError;
-use ff::Field;
+use ff::{Field, PrimeField};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 
 /// Trait for types that can be added to a [`FileLedger`].
 ///
@@ -31,27 +46,55 @@ use std::path::Path;
 pub trait FileDescriptor {
     /// Returns the unique identifier for this file.
     fn file_id(&self) -> &str;
+    /// Returns the file's name, recorded for inspection only (empty by default).
+    fn filename(&self) -> &str {
+        ""
+    }
     /// Returns the Merkle root of this file's tree.
     fn root(&self) -> F;
     /// Returns the depth of this file's Merkle tree.
     fn depth(&self) -> usize;
+    /// Returns the branching factor of this file's Merkle tree (binary by default).
+    fn tree_arity(&self) -> TreeArity {
+        TreeArity::Binary
+    }
+    /// Returns the Poseidon instantiation of this file's Merkle tree (standard by default).
+    fn hash_profile(&self) -> HashProfile {
+        HashProfile::Standard
+    }
+    /// Checks the descriptor before the ledger records it (accepts everything by default;
+    /// `FileMetadata` runs [`crate::api::FileMetadata::validate`]).
+    fn validate(&self) -> Result<(), KontorPoRError> {
+        Ok(())
+    }
 }
 
 /// Entry for a single file in the ledger, combining all file information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileLedgerEntry {
+    /// The file's name (informational; not part of any commitment)
+    pub filename: String,
     /// The Merkle root of this file
+    #[serde(with = "crate::merkle::canonical::field")]
     pub root: F,
     /// The depth of this file's Merkle tree
     pub depth: usize,
-    /// The root commitment (rc = H(TAG_RC, root, depth))
+    /// The root commitment (rc = H(TAG_RC, root, depth), or TAG_RC4 for arity-4 trees,
+    /// hashed under the file's hash profile)
+    #[serde(with = "crate::merkle::canonical::field")]
     pub rc: F,
 }
 
 impl<T: FileDescriptor> From<&T> for FileLedgerEntry {
     fn from(entry: &T) -> Self {
-        let rc = calculate_root_commitment(entry.root(), F::from(entry.depth() as u64));
+        let rc = compute_rc_with(
+            entry.root(),
+            entry.depth(),
+            entry.tree_arity(),
+            entry.hash_profile(),
+        );
         FileLedgerEntry {
+            filename: entry.filename().to_string(),
             root: entry.root(),
             depth: entry.depth(),
             rc,
@@ -59,6 +102,61 @@ impl<T: FileDescriptor> From<&T> for FileLedgerEntry {
     }
 }
 
+impl FileLedgerEntry {
+    /// The tree arity whose root commitment tag produced `rc`, or `None` if `rc` matches
+    /// no arity's commitment of `(root, depth)` under any hash profile.
+    pub fn tree_arity(&self) -> Option<TreeArity> {
+        self.tree_params().map(|(arity, _)| arity)
+    }
+
+    /// The hash profile whose constants produced `rc`, or `None` if `rc` matches no
+    /// commitment of `(root, depth)`.
+    pub fn hash_profile(&self) -> Option<HashProfile> {
+        self.tree_params().map(|(_, profile)| profile)
+    }
+
+    /// The (arity, profile) pair whose root commitment of `(root, depth)` is `rc`.
+    fn tree_params(&self) -> Option<(TreeArity, HashProfile)> {
+        [HashProfile::Standard, HashProfile::Conservative]
+            .into_iter()
+            .flat_map(|profile| {
+                [TreeArity::Binary, TreeArity::Arity4].map(|arity| (arity, profile))
+            })
+            .find(|(arity, profile)| {
+                compute_rc_with(self.root, self.depth, *arity, *profile) == self.rc
+            })
+    }
+}
+
+/// Root commitment of a file: `rc = Poseidon(TAG_RC, root, depth)`.
+///
+/// This is the value the ledger stores for each file and the leaf of the aggregated tree
+/// a multi-file proof opens, so a verifier holding a file's `(root, depth)` can check
+/// ledger membership with [`verify_aggregation_proof`] without preparing the file. `TAG_RC`
+/// is domain tag 8 ([`crate::poseidon::domain_tags::root_commitment`]); `root` is
+/// absorbed as a field element and `depth` as the field element of its integer value, so a
+/// single-leaf tree has depth 0.
+///
+/// Covers binary trees under [`HashProfile::Standard`], the default for prepared files;
+/// use [`compute_rc_with`] for other arities and profiles.
+pub fn compute_rc(root: F, depth: usize) -> F {
+    compute_rc_with(root, depth, TreeArity::Binary, HashProfile::Standard)
+}
+
+/// Root commitment of a file tree of `arity` hashed under `profile`.
+///
+/// Arity-4 trees use `TAG_RC4` (domain tag 16) in place of `TAG_RC`, so the commitments
+/// of the two arities never collide, and the hash uses `profile`'s Poseidon constants.
+/// The in-circuit `compute_rc` gadget computes the same value.
+pub fn compute_rc_with(root: F, depth: usize, arity: TreeArity, profile: HashProfile) -> F {
+    crate::poseidon::poseidon_hash_tagged_with(
+        profile,
+        arity.root_commitment_tag(),
+        root,
+        F::from(depth as u64),
+    )
+}
+
 /// Versioned wrapper for ledger serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct LedgerData {
@@ -67,11 +165,85 @@ struct LedgerData {
     /// The actual ledger data (unified file entries)
     files: BTreeMap<String, FileLedgerEntry>,
     /// Stored root for validation on load
+    #[serde(with = "crate::merkle::canonical::field")]
+    root: F,
+    historical_roots: Vec<[u8; 32]>,
+    /// Fixed file capacity (`None` for a growing ledger)
+    capacity: Option<usize>,
+    /// How file indices are assigned
+    index_policy: IndexPolicy,
+    /// Slot assignments under [`IndexPolicy::InsertionOrder`]
+    slots: Vec<Option<String>>,
+    /// Root in effect from each recorded block height
+    root_heights: BTreeMap<u64, [u8; 32]>,
+    /// Roots taken over from other ledgers by [`FileLedger::migrate_from`]
+    migrated_roots: Vec<[u8; 32]>,
+}
+
+/// Ledger serialization of format version 1 (the 0.1 releases).
+///
+/// Bincode has no optional fields, so a version 1 ledger only decodes as this struct.
+/// Entries have no filename, and field elements use their own serde encoding.
+#[derive(Deserialize)]
+struct LedgerDataV1 {
+    _version: u16,
+    files: BTreeMap<String, FileLedgerEntryV1>,
     root: F,
-    #[serde(default)]
     historical_roots: Vec<[u8; 32]>,
 }
 
+/// File entry of a version 1 ledger.
+#[derive(Deserialize)]
+struct FileLedgerEntryV1 {
+    root: F,
+    depth: usize,
+    rc: F,
+}
+
+impl From<LedgerDataV1> for LedgerData {
+    /// Upgrades a version 1 ledger: a growing, canonically indexed ledger with no recorded
+    /// filenames, block heights or migrated roots.
+    fn from(data: LedgerDataV1) -> Self {
+        let files = data
+            .files
+            .into_iter()
+            .map(|(file_id, entry)| {
+                let entry = FileLedgerEntry {
+                    filename: String::new(),
+                    root: entry.root,
+                    depth: entry.depth,
+                    rc: entry.rc,
+                };
+                (file_id, entry)
+            })
+            .collect();
+        LedgerData {
+            version: crate::config::LEDGER_FORMAT_VERSION,
+            files,
+            root: data.root,
+            historical_roots: data.historical_roots,
+            capacity: None,
+            index_policy: IndexPolicy::Canonical,
+            slots: Vec::new(),
+            root_heights: BTreeMap::new(),
+            migrated_roots: Vec::new(),
+        }
+    }
+}
+
+/// How a [`FileLedger`] assigns file indices (leaf positions in the aggregated tree).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IndexPolicy {
+    /// A file's index is its rank among all file IDs in lexicographic order, so adding a
+    /// file shifts the index of every file that sorts after it.
+    #[default]
+    Canonical,
+    /// A file's index is assigned when it is first added (the next unused slot) and never
+    /// changes. Removing a file tombstones its slot; tombstoned slots are not reused, so a
+    /// cached index never comes to refer to a different file.
+    InsertionOrder,
+}
+
 /// The `FileLedger` manages the aggregated Merkle tree of all file roots.
 /// It acts as the central, persistent state for the collection of files.
 ///
@@ -81,19 +253,55 @@ struct LedgerData {
 /// ## Historical Root Tracking
 ///
 /// The ledger maintains a set of historical roots for proof validation.
-/// When files are added, the pre-modification root is appended to `historical_roots`.
-/// Verifiers check that a proof's `ledger_root` is in this set before accepting it.
-/// This enables cross-block aggregation without proof regeneration.
+/// When files are added, the pre-modification root is appended to the historical roots
+/// ([`Self::historical_roots`]). Verifiers check that a proof's `ledger_root` is in this
+/// set before accepting it. This enables cross-block aggregation without proof
+/// regeneration.
+///
+/// Use [`Self::set_historical_roots`] or [`Self::clear_historical_roots`] to replace the
+/// historical roots when needed.
+///
+/// ## Fixed Capacity
 ///
-/// Use [`Self::set_historical_roots`] to replace the historical roots when needed.
+/// By default the aggregated tree grows with the number of files, so its depth (and
+/// therefore the circuit shape) changes as files are added. A ledger created with
+/// [`Self::with_capacity`] (or [`Self::new_with_depth`]) instead pads the tree to a
+/// constant number of leaves, keeping `aggregated_tree_depth` stable so previously
+/// generated parameters remain usable. Unused slots hold the domain-separated
+/// [`crate::poseidon::empty_ledger_slot`] leaf.
+///
+/// ## Index Policy
+///
+/// File indices follow the ledger's [`IndexPolicy`], chosen at construction with
+/// [`Self::new_with_policy`]. The default canonical policy orders files by ID; the
+/// insertion-order policy gives each file a permanent slot so indices cached by external
+/// systems stay valid as files are added. Plans, witnesses, aggregation proofs and
+/// membership certificates all use [`Self::lookup`], so proving and verification follow
+/// the policy automatically.
+///
+/// ## Migrating from 0.1
+///
+/// The `files`, `tree` and `historical_roots` fields are private since 0.2, so the tree
+/// and the rc index can no longer fall out of sync with the entries. Replace
+/// - `ledger.files.len()` with [`Self::file_count`],
+/// - `ledger.files.get(id)` and `ledger.files[id]` with [`Self::entry`],
+/// - iteration over `ledger.files` with [`Self::entries`] or [`Self::file_ids`],
+/// - `ledger.tree.root()` with [`Self::root`], `ledger.tree.layers.len()` with
+///   [`Self::tree_layers_len`] and `ledger.tree.layers[0]` with [`Self::leaves`],
+/// - reads of `ledger.historical_roots` with [`Self::historical_roots`], and writes with
+///   [`Self::set_historical_roots`] or [`Self::clear_historical_roots`].
+///
+/// Entries change through [`Self::add_file`], [`Self::remove_file`],
+/// [`Self::rename_file`] and [`Self::apply_diff`]. Ledgers saved by 0.1 (format version 1)
+/// still load with [`Self::load`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileLedger {
     /// Unified map from file identifier to complete file information.
     /// BTreeMap ensures deterministic ordering for canonical ledger construction.
-    pub files: BTreeMap<String, FileLedgerEntry>,
+    files: BTreeMap<String, FileLedgerEntry>,
     /// The aggregated Merkle tree built from rc values (not raw roots).
     #[serde(skip)]
-    pub tree: MerkleTree,
+    tree: MerkleTree,
     /// Accepted historical roots for proof validation.
     ///
     /// Proofs generated against a ledger root are valid as long as that root is either:
@@ -102,7 +310,36 @@ pub struct FileLedger {
     ///
     /// Use [`Self::set_historical_roots`] to replace this list.
     #[serde(default)]
-    pub historical_roots: Vec<[u8; 32]>,
+    historical_roots: Vec<[u8; 32]>,
+    /// Maximum number of files this ledger accepts, if fixed.
+    ///
+    /// When set, the aggregated tree is always padded to `capacity.next_power_of_two()`
+    /// leaves so its depth never changes.
+    #[serde(default)]
+    capacity: Option<usize>,
+    /// How file indices are assigned.
+    #[serde(default)]
+    index_policy: IndexPolicy,
+    /// File ID in each slot under [`IndexPolicy::InsertionOrder`], `None` for a removed
+    /// file. Always empty under [`IndexPolicy::Canonical`].
+    #[serde(default)]
+    slots: Vec<Option<String>>,
+    /// Root in effect from each recorded block height on, as canonical `to_repr()` bytes.
+    /// Empty unless [`Self::record_root_at_height`] is used.
+    #[serde(default)]
+    root_heights: BTreeMap<u64, [u8; 32]>,
+    /// Roots of other ledgers this ledger was migrated from ([`Self::migrate_from`]), as
+    /// canonical `to_repr()` bytes. Accepted like historical roots but kept apart, so they
+    /// can be pruned on their own.
+    #[serde(default)]
+    migrated_roots: Vec<[u8; 32]>,
+    /// File ID and index of the file holding each rc, keyed by canonical `to_repr()` bytes.
+    /// Rebuilt with the tree.
+    #[serde(skip)]
+    rc_index: HashMap<[u8; 32], (String, usize)>,
+    /// Journal receiving every update, for ledgers opened with [`Self::open_journaled`]
+    #[serde(skip)]
+    journal: JournalHandle,
 }
 
 impl Default for FileLedger {
@@ -113,6 +350,13 @@ impl Default for FileLedger {
                 layers: vec![vec![]],
             },
             historical_roots: Vec::new(),
+            capacity: None,
+            index_policy: IndexPolicy::Canonical,
+            slots: Vec::new(),
+            root_heights: BTreeMap::new(),
+            migrated_roots: Vec::new(),
+            rc_index: HashMap::new(),
+            journal: JournalHandle::default(),
         }
     }
 }
@@ -123,6 +367,113 @@ impl FileLedger {
         Self::default()
     }
 
+    /// Creates a new, empty `FileLedger` that assigns file indices according to `policy`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::api::prepare_file;
+    /// use kontor_crypto::{FileLedger, IndexPolicy};
+    ///
+    /// let (_, zeta) = prepare_file(b"zeta", "zeta.dat").unwrap();
+    /// let (_, alpha) = prepare_file(b"alpha", "alpha.dat").unwrap();
+    /// let mut ledger = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    /// ledger.add_file(&zeta).unwrap();
+    /// ledger.add_file(&alpha).unwrap();
+    /// assert_eq!(ledger.lookup(&zeta.file_id).unwrap().0, 0);
+    /// ```
+    pub fn new_with_policy(policy: IndexPolicy) -> Self {
+        Self {
+            index_policy: policy,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new, empty `FileLedger` with a fixed capacity of `max_files`.
+    ///
+    /// The aggregated tree is padded with empty-slot leaves
+    /// ([`crate::poseidon::empty_ledger_slot`]) to `max_files.next_power_of_two()`
+    /// entries, so its depth is fixed for the lifetime of the ledger. Once `max_files`
+    /// distinct files have been added, [`Self::add_file`] returns
+    /// [`KontorPoRError::LedgerFull`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::FileLedger;
+    ///
+    /// let ledger = FileLedger::with_capacity(1024);
+    /// assert_eq!(ledger.depth(), 10);
+    /// assert_eq!(ledger.remaining_capacity(), Some(1024));
+    /// ```
+    pub fn with_capacity(max_files: usize) -> Self {
+        let mut ledger = Self {
+            capacity: Some(max_files),
+            ..Self::default()
+        };
+        ledger
+            .rebuild_tree()
+            .expect("Building an empty fixed-capacity ledger tree should never fail");
+        ledger
+    }
+
+    /// Creates a new, empty `FileLedger` whose aggregated tree always has `2^agg_depth`
+    /// leaves.
+    ///
+    /// Equivalent to [`Self::with_capacity`]`(1 << agg_depth)`: [`Self::depth`] is
+    /// `agg_depth` from the start, so the circuit shape (and cached parameters) never
+    /// change as files are added, until [`Self::add_file`] fails with
+    /// [`KontorPoRError::LedgerFull`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^agg_depth` does not fit in a `usize`.
+    pub fn new_with_depth(agg_depth: usize) -> Self {
+        let capacity = u32::try_from(agg_depth)
+            .ok()
+            .and_then(|depth| 1usize.checked_shl(depth))
+            .expect("Ledger depth too large for host usize");
+        Self::with_capacity(capacity)
+    }
+
+    /// Returns the fixed file capacity, or `None` if the ledger grows without bound.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns how file indices are assigned.
+    pub fn index_policy(&self) -> IndexPolicy {
+        self.index_policy
+    }
+
+    /// Returns how many more files can be added, or `None` for a growing ledger.
+    ///
+    /// Under [`IndexPolicy::InsertionOrder`], tombstoned slots still count against the
+    /// capacity.
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        self.capacity
+            .map(|capacity| capacity.saturating_sub(self.used_slots()))
+    }
+
+    /// Number of index slots in use: one per file, plus tombstones under
+    /// [`IndexPolicy::InsertionOrder`].
+    fn used_slots(&self) -> usize {
+        match self.index_policy {
+            IndexPolicy::Canonical => self.files.len(),
+            IndexPolicy::InsertionOrder => self.slots.len(),
+        }
+    }
+
+    /// Returns an error if adding `new_files` distinct files would exceed the capacity.
+    fn check_capacity(&self, new_files: usize) -> Result<(), KontorPoRError> {
+        if let Some(capacity) = self.capacity {
+            if self.used_slots() + new_files > capacity {
+                return Err(KontorPoRError::LedgerFull { capacity });
+            }
+        }
+        Ok(())
+    }
+
     // --- Historical Root Management ---
 
     /// Returns the current root of the aggregated Merkle tree.
@@ -134,31 +485,165 @@ impl FileLedger {
     ///
     /// Call this before modifying the ledger (e.g., before adding files) to preserve the
     /// old root for proof validation.
-    pub fn record_current_root(&mut self) {
-        use ff::PrimeField;
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::IO`] if the ledger is journaled and the update cannot be
+    /// appended to its journal; the ledger is then unchanged.
+    pub fn record_current_root(&mut self) -> Result<(), KontorPoRError> {
+        self.apply_journaled(JournalOp::RecordCurrentRoot)
+    }
+
+    /// Appends the current root to the historical roots.
+    fn push_current_root(&mut self) {
         let root = self.tree.root();
         let repr: [u8; 32] = root.to_repr().into();
         self.historical_roots.push(repr);
     }
 
-    /// Checks if a root is valid (either current or in historical set).
-    /// Use this to validate `proof.ledger_root` before verification.
+    /// Checks if a root is valid (current, in the historical set or migrated from another
+    /// ledger). Use this to validate `proof.ledger_root` before verification.
+    ///
+    /// Historical roots are compared as field elements; entries that are not canonical
+    /// encodings never match, so an alternate encoding of a root cannot stand in for it.
     pub fn is_valid_root(&self, root: F) -> bool {
-        use ff::PrimeField;
         // Current root is always valid
         if root == self.tree.root() {
             return true;
         }
-        // Check historical roots
-        let repr: [u8; 32] = root.to_repr().into();
-        self.historical_roots.iter().any(|r| r == &repr)
+        // Check historical and migrated roots
+        self.historical_roots
+            .iter()
+            .chain(&self.migrated_roots)
+            .any(|r| field_from_bytes(*r) == Some(root))
     }
 
     /// Sets the historical roots to the given list.
     ///
-    /// This replaces any existing historical roots with the provided values.
-    pub fn set_historical_roots(&mut self, roots: Vec<[u8; 32]>) {
-        self.historical_roots = roots;
+    /// This replaces any existing historical roots with the provided values. Entries should
+    /// be canonical `to_repr()` bytes: others are never accepted by [`Self::is_valid_root`]
+    /// and make [`Self::load`] fail after a save.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::IO`] if the ledger is journaled and the update cannot be
+    /// appended to its journal; the ledger is then unchanged.
+    pub fn set_historical_roots(&mut self, roots: Vec<[u8; 32]>) -> Result<(), KontorPoRError> {
+        self.apply_journaled(JournalOp::SetHistoricalRoots(roots))
+    }
+
+    /// Returns the accepted historical roots as canonical `to_repr()` bytes, oldest first.
+    pub fn historical_roots(&self) -> &[[u8; 32]] {
+        &self.historical_roots
+    }
+
+    /// Forgets every historical root, so only the current root and migrated roots
+    /// ([`Self::migrated_roots`]) stay valid.
+    ///
+    /// # Errors
+    ///
+    /// As for [`Self::set_historical_roots`].
+    pub fn clear_historical_roots(&mut self) -> Result<(), KontorPoRError> {
+        self.set_historical_roots(Vec::new())
+    }
+
+    /// Returns the roots taken over by [`Self::migrate_from`] as canonical `to_repr()`
+    /// bytes, oldest first.
+    pub fn migrated_roots(&self) -> &[[u8; 32]] {
+        &self.migrated_roots
+    }
+
+    /// Forgets every migrated root and returns them oldest first.
+    ///
+    /// Proofs made against the ledgers this one was migrated from stop verifying, unless
+    /// their root is also the current or a historical root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::IO`] if the ledger is journaled and the update cannot be
+    /// appended to its journal; the ledger is then unchanged.
+    pub fn prune_migrated_roots(&mut self) -> Result<Vec<[u8; 32]>, KontorPoRError> {
+        let pruned = self.migrated_roots.clone();
+        self.apply_journaled(JournalOp::ClearMigratedRoots)?;
+        Ok(pruned)
+    }
+
+    /// Removes the historical roots that no verification referenced within `min_age`, and
+    /// returns them oldest first.
+    ///
+    /// `stats` are the counters of a [`crate::api::RootUsageTracker`], typically from
+    /// [`crate::PorSystem::root_usage_stats`]. A historical root is kept if it is the
+    /// current root or its counters were last seen less than `min_age` ago; roots without
+    /// counters count as unreferenced. Start tracking at least `min_age` before pruning,
+    /// or roots whose provers have not been challenged yet are dropped. Root heights
+    /// ([`Self::record_root_at_height`]) and migrated roots are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// As for [`Self::set_historical_roots`].
+    pub fn prune_unreferenced_roots(
+        &mut self,
+        stats: &[RootUsage],
+        min_age: Duration,
+    ) -> Result<Vec<[u8; 32]>, KontorPoRError> {
+        let now = SystemTime::now();
+        let current: [u8; 32] = self.tree.root().to_repr().into();
+        let referenced = |repr: &[u8; 32]| {
+            *repr == current
+                || stats.iter().any(|usage| {
+                    usage.seen_within(min_age, now) && field_from_bytes(*repr) == Some(usage.root)
+                })
+        };
+
+        let (kept, pruned) = self
+            .historical_roots
+            .iter()
+            .copied()
+            .partition(|repr| referenced(repr));
+        self.set_historical_roots(kept)?;
+        Ok(pruned)
+    }
+
+    /// Records the current root as the ledger root in effect from `block_height` on.
+    ///
+    /// Call this at every height where the ledger changes. Once any height is recorded,
+    /// verification requires a multi-file proof's `ledger_root` to be the root in effect
+    /// at each of its challenges' heights ([`Self::root_at_height`]), not merely a valid
+    /// historical root. Recording a height again replaces its root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::IO`] if the ledger is journaled and the update cannot be
+    /// appended to its journal; the ledger is then unchanged.
+    pub fn record_root_at_height(&mut self, block_height: u64) -> Result<(), KontorPoRError> {
+        self.apply_journaled(JournalOp::RecordRootAtHeight(block_height))
+    }
+
+    /// The root in effect at `block_height`: the one recorded for the greatest height not
+    /// above it, or `None` if no such height has been recorded.
+    pub fn root_at_height(&self, block_height: u64) -> Option<F> {
+        self.root_heights
+            .range(..=block_height)
+            .next_back()
+            .and_then(|(_, root)| field_from_bytes(*root))
+    }
+
+    /// Whether roots are tracked by height (any height has been recorded).
+    pub fn tracks_root_heights(&self) -> bool {
+        !self.root_heights.is_empty()
+    }
+
+    /// Builds an attestation of the current ledger state for on-chain anchoring.
+    ///
+    /// Post [`LedgerAttestation::to_bytes`] or [`LedgerAttestation::to_hex`] rather than
+    /// serializing [`Self::root`] directly, so every integrator publishes the same encoding.
+    pub fn attestation(&self, block_height: u64) -> LedgerAttestation {
+        LedgerAttestation::new(
+            self.root(),
+            self.files.len() as u64,
+            self.depth() as u64,
+            block_height,
+        )
     }
 
     // --- File Management ---
@@ -168,6 +653,15 @@ impl FileLedger {
     /// On success, the new root is recorded in [`Self::historical_roots`],
     /// ensuring every valid ledger state is tracked for proof validation.
     ///
+    /// For fixed-capacity ledgers, returns [`KontorPoRError::LedgerFull`] if the file is
+    /// new and the ledger already holds [`Self::capacity`] files. Re-adding an existing
+    /// `file_id` replaces its entry (keeping its index) and is always allowed. Entries
+    /// failing [`FileDescriptor::validate`] (e.g. metadata whose `padded_len` is not a power
+    /// of two) are rejected with [`KontorPoRError::InvalidMetadata`] before anything changes.
+    ///
+    /// Under [`IndexPolicy::InsertionOrder`], a new file takes the slot after the last
+    /// assigned one.
+    ///
     /// # Arguments
     ///
     /// * `entry` - Any type that implements [`FileDescriptor`], providing
@@ -184,19 +678,86 @@ impl FileLedger {
     /// ledger.add_file(&metadata).unwrap();
     /// ```
     pub fn add_file(&mut self, entry: &impl FileDescriptor) -> Result<(), KontorPoRError> {
+        entry.validate()?;
+        self.apply_journaled(JournalOp::AddFile {
+            file_id: entry.file_id().to_string(),
+            entry: FileLedgerEntry::from(entry),
+        })
+    }
+
+    fn insert_file(&mut self, file_id: &str, entry: FileLedgerEntry) -> Result<(), KontorPoRError> {
+        if !self.files.contains_key(file_id) && self.index_policy == IndexPolicy::InsertionOrder {
+            self.slots.push(Some(file_id.to_string()));
+        }
+
         // Insert the new file
-        self.files
-            .insert(entry.file_id().to_string(), FileLedgerEntry::from(entry));
+        self.files.insert(file_id.to_string(), entry);
 
         // Rebuild tree
         self.rebuild_tree()?;
+        fail_point!(crate::failpoints::points::LEDGER_BEFORE_HISTORICAL_ROOT);
 
         // Record the new root as a historical root (every valid state is tracked)
-        self.record_current_root();
+        self.push_current_root();
 
         Ok(())
     }
 
+    /// Removes a file from the ledger and rebuilds the aggregated tree.
+    ///
+    /// Like [`Self::add_file`], the new root is recorded in [`Self::historical_roots`].
+    /// Under [`IndexPolicy::Canonical`], every file sorting after the removed one moves
+    /// down one index. Under [`IndexPolicy::InsertionOrder`], the file's slot is tombstoned
+    /// (it holds the same leaf as an unused slot) and other indices are unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::FileNotFound`] if `file_id` is not in the ledger.
+    pub fn remove_file(&mut self, file_id: &str) -> Result<FileLedgerEntry, KontorPoRError> {
+        let entry =
+            self.files
+                .get(file_id)
+                .cloned()
+                .ok_or_else(|| KontorPoRError::FileNotFound {
+                    file_id: file_id.to_string(),
+                })?;
+        self.apply_journaled(JournalOp::RemoveFile(file_id.to_string()))?;
+        Ok(entry)
+    }
+
+    fn delete_file(&mut self, file_id: &str) -> Result<(), KontorPoRError> {
+        self.files.remove(file_id);
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.as_deref() == Some(file_id))
+        {
+            *slot = None;
+        }
+
+        self.rebuild_tree()?;
+        fail_point!(crate::failpoints::points::LEDGER_BEFORE_HISTORICAL_ROOT);
+        self.push_current_root();
+
+        Ok(())
+    }
+
+    /// Changes the recorded filename of a file already in the ledger.
+    ///
+    /// Only the informational filename is updated; the entry's root, depth and rc are left
+    /// alone, so the aggregated tree, its root and every proof against it stay valid. No
+    /// historical root is recorded because the root does not change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KontorPoRError::FileNotFound`] if `file_id` is not in the ledger.
+    pub fn rename_file(&mut self, file_id: &str, new_name: &str) -> Result<(), KontorPoRError> {
+        self.apply_journaled(JournalOp::RenameFile {
+            file_id: file_id.to_string(),
+            new_name: new_name.to_string(),
+        })
+    }
+
     /// Adds multiple files to the ledger in a single batch, rebuilding the tree only once.
     ///
     /// This method is intended for bulk initialization or reconstruction of the ledger
@@ -213,26 +774,383 @@ impl FileLedger {
     /// # Duplicate Handling
     ///
     /// If a file with the same `file_id` already exists in the ledger or appears
-    /// multiple times in the batch, the last entry wins.
+    /// multiple times in the batch, the last entry wins. Under
+    /// [`IndexPolicy::InsertionOrder`], new files take slots in batch order.
+    ///
+    /// # Capacity
+    ///
+    /// For fixed-capacity ledgers, the whole batch is rejected with
+    /// [`KontorPoRError::LedgerFull`] if it would exceed the capacity; the ledger
+    /// is left unchanged in that case. So is it if any entry fails
+    /// [`FileDescriptor::validate`].
     ///
     pub fn add_files<'a, T: FileDescriptor + 'a>(
         &mut self,
         files: impl IntoIterator<Item = &'a T>,
     ) -> Result<(), KontorPoRError> {
-        for entry in files {
-            self.files
-                .insert(entry.file_id().to_string(), FileLedgerEntry::from(entry));
+        let entries = files
+            .into_iter()
+            .map(|entry| {
+                entry.validate()?;
+                Ok((entry.file_id().to_string(), FileLedgerEntry::from(entry)))
+            })
+            .collect::<Result<_, KontorPoRError>>()?;
+        self.apply_journaled(JournalOp::AddFiles(entries))
+    }
+
+    fn insert_files(
+        &mut self,
+        entries: Vec<(String, FileLedgerEntry)>,
+    ) -> Result<(), KontorPoRError> {
+        if self.index_policy == IndexPolicy::InsertionOrder {
+            let new_files = self.new_file_ids(&entries);
+            self.slots.extend(new_files.into_iter().map(Some));
         }
+        self.files.extend(entries);
 
         self.rebuild_tree()
     }
 
+    /// IDs of the files in `entries` not yet in the ledger, in first-seen order.
+    fn new_file_ids(&self, entries: &[(String, FileLedgerEntry)]) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        entries
+            .iter()
+            .map(|(file_id, _)| file_id)
+            .filter(|file_id| !self.files.contains_key(*file_id) && seen.insert(*file_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Computes the changes that take this ledger to the state of `other`.
+    ///
+    /// Files are matched by ID. The diff lists files only in `self` (to remove), files only
+    /// in `other` (to add, with `other`'s entries), and files in both whose root, depth or
+    /// rc differ (to replace, with `other`'s entries). Filename-only differences are not
+    /// listed, since filenames are not part of any commitment.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::api::prepare_file;
+    /// use kontor_crypto::FileLedger;
+    ///
+    /// let (_, metadata) = prepare_file(b"hello", "test.dat").unwrap();
+    /// let mut leader = FileLedger::new();
+    /// leader.add_file(&metadata).unwrap();
+    ///
+    /// let mut follower = FileLedger::new();
+    /// let diff = follower.diff(&leader);
+    /// follower.apply_diff(&diff).unwrap();
+    /// assert_eq!(follower.root(), leader.root());
+    /// ```
+    pub fn diff(&self, other: &FileLedger) -> LedgerDiff {
+        let removed = self
+            .files
+            .keys()
+            .filter(|file_id| !other.files.contains_key(*file_id))
+            .cloned()
+            .collect();
+        let mut added = BTreeMap::new();
+        let mut changed = BTreeMap::new();
+        for (file_id, entry) in &other.files {
+            match self.files.get(file_id) {
+                None => {
+                    added.insert(file_id.clone(), entry.clone());
+                }
+                Some(ours)
+                    if ours.root != entry.root
+                        || ours.depth != entry.depth
+                        || ours.rc != entry.rc =>
+                {
+                    changed.insert(file_id.clone(), entry.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        LedgerDiff {
+            removed,
+            added,
+            changed,
+        }
+    }
+
+    /// Applies a diff produced by [`Self::diff`], rebuilding the tree once.
+    ///
+    /// Removed files are dropped, added files inserted and changed files replaced; the new
+    /// root is then recorded in [`Self::historical_roots`] once for the whole diff. Under
+    /// [`IndexPolicy::Canonical`], a ledger equal to the diff's source ends with the same
+    /// root as its target. Under [`IndexPolicy::InsertionOrder`], removed files are
+    /// tombstoned and added files take new slots in file ID order, so the root matches the
+    /// target only if it assigned those slots in the same order.
+    ///
+    /// The diff is checked against the ledger before anything changes, so on error the
+    /// ledger is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::FileNotFound`] if a removed or changed file is not in the ledger
+    /// - [`KontorPoRError::InvalidInput`] if an added file is already in the ledger, or a
+    ///   file is listed more than once
+    /// - [`KontorPoRError::LedgerFull`] if the resulting ledger would exceed a fixed capacity
+    pub fn apply_diff(&mut self, diff: &LedgerDiff) -> Result<(), KontorPoRError> {
+        self.apply_journaled(JournalOp::ApplyDiff(diff.clone()))
+    }
+
+    /// Checks that `diff` applies to the ledger, for [`Self::apply_diff`].
+    fn check_diff(&self, diff: &LedgerDiff) -> Result<(), KontorPoRError> {
+        let mut seen = BTreeSet::new();
+        if let Some(file_id) = diff
+            .removed
+            .iter()
+            .chain(diff.added.keys())
+            .chain(diff.changed.keys())
+            .find(|file_id| !seen.insert(*file_id))
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "file {} is listed more than once in the diff",
+                file_id
+            )));
+        }
+        if let Some(file_id) = diff
+            .removed
+            .iter()
+            .chain(diff.changed.keys())
+            .find(|file_id| !self.files.contains_key(*file_id))
+        {
+            return Err(KontorPoRError::FileNotFound {
+                file_id: file_id.clone(),
+            });
+        }
+        if let Some(file_id) = diff
+            .added
+            .keys()
+            .find(|file_id| self.files.contains_key(*file_id))
+        {
+            return Err(KontorPoRError::InvalidInput(format!(
+                "file {} is added by the diff but already in the ledger",
+                file_id
+            )));
+        }
+        if let Some(capacity) = self.capacity {
+            // Removed files free their index only under the canonical policy
+            let freed = match self.index_policy {
+                IndexPolicy::Canonical => diff.removed.len(),
+                IndexPolicy::InsertionOrder => 0,
+            };
+            if self.used_slots() - freed + diff.added.len() > capacity {
+                return Err(KontorPoRError::LedgerFull { capacity });
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a diff that passed [`Self::check_diff`].
+    fn merge_diff(&mut self, diff: &LedgerDiff) -> Result<(), KontorPoRError> {
+        for file_id in &diff.removed {
+            self.files.remove(file_id);
+            if let Some(slot) = self
+                .slots
+                .iter_mut()
+                .find(|slot| slot.as_deref() == Some(file_id.as_str()))
+            {
+                *slot = None;
+            }
+        }
+        if self.index_policy == IndexPolicy::InsertionOrder {
+            self.slots.extend(diff.added.keys().cloned().map(Some));
+        }
+        self.files.extend(
+            diff.added
+                .iter()
+                .chain(&diff.changed)
+                .map(|(file_id, entry)| (file_id.clone(), entry.clone())),
+        );
+
+        self.rebuild_tree()?;
+        self.push_current_root();
+
+        Ok(())
+    }
+
+    /// Takes over every file of `old`, so proofs made against `old` keep verifying here.
+    ///
+    /// Files are added in `old`'s index order, and the new root is then recorded in
+    /// [`Self::historical_roots`]. `old`'s current, historical and migrated roots become
+    /// migrated roots of this ledger ([`Self::migrated_roots`]), which
+    /// [`Self::is_valid_root`] accepts until [`Self::prune_migrated_roots`] drops them.
+    /// Files already registered here with the same rc are kept as they are.
+    ///
+    /// The returned receipt maps each file's index in `old` to its index here; pass it
+    /// to [`Proof::resolve_migrated_files`] to name the files of a proof made against
+    /// `old`.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::FileIdConflict`] if a file ID of `old` is registered here with
+    ///   a different rc
+    /// - [`KontorPoRError::LedgerFull`] if the files would exceed a fixed capacity
+    ///
+    /// The ledger is left unchanged on error.
+    pub fn migrate_from(&mut self, old: &FileLedger) -> Result<MigrationReceipt, KontorPoRError> {
+        let indexed = old.indexed_files();
+        if let Some((_, file_id)) = indexed.iter().find(|(_, file_id)| {
+            self.files
+                .get(*file_id)
+                .is_some_and(|entry| entry.rc != old.files[*file_id].rc)
+        }) {
+            return Err(KontorPoRError::FileIdConflict {
+                file_id: (*file_id).clone(),
+            });
+        }
+
+        let entries = indexed
+            .iter()
+            .filter(|(_, file_id)| !self.files.contains_key(*file_id))
+            .map(|(_, file_id)| ((*file_id).clone(), old.files[*file_id].clone()))
+            .collect();
+
+        let old_root: [u8; 32] = old.root().to_repr().into();
+        let mut migrated_roots = Vec::new();
+        for root in old
+            .historical_roots
+            .iter()
+            .chain(std::iter::once(&old_root))
+        {
+            if !migrated_roots.contains(root) {
+                migrated_roots.push(*root);
+            }
+        }
+        let roots = old
+            .migrated_roots
+            .iter()
+            .chain(&migrated_roots)
+            .copied()
+            .collect();
+
+        // The files, the new root and the migrated roots are one journal update, so a
+        // reopened ledger never holds the files without the roots that vouch for them
+        self.apply_journaled(JournalOp::Migrate { entries, roots })?;
+
+        let files = indexed
+            .into_iter()
+            .map(|(old_index, file_id)| {
+                let (new_index, _) = self
+                    .lookup(file_id)
+                    .expect("migrated files are in the ledger");
+                (
+                    old_index,
+                    MigratedFile {
+                        file_id: file_id.clone(),
+                        new_index,
+                    },
+                )
+            })
+            .collect();
+
+        Ok(MigrationReceipt {
+            old_root,
+            old_index_policy: old.index_policy,
+            migrated_roots,
+            files,
+        })
+    }
+
+    /// The files to challenge in `epoch` under a deterministic rotation over every file.
+    ///
+    /// Epochs are grouped into cycles of `ceil(n / files_per_epoch)` epochs for a ledger
+    /// of `n` files, starting at epoch 0. Each cycle visits the files in an order shuffled
+    /// by the ledger root and the cycle's first epoch, `files_per_epoch` at a time (the
+    /// last epoch may take fewer), so every file is selected exactly once per cycle.
+    /// While the ledger is unchanged, every epoch of a cycle sees the same order.
+    ///
+    /// Adding or removing files changes the root and possibly the cycle length, so the
+    /// rest of the cycle would be reshuffled. To add files mid-cycle without losing
+    /// coverage, keep the [`RotationCycle`] from [`Self::rotation_cycle`] for the current
+    /// cycle and start the next one from the updated ledger at its end: the new files
+    /// join at that boundary.
+    ///
+    /// A `files_per_epoch` of 0 is treated as 1. An empty ledger selects nothing.
+    pub fn rotation_schedule(&self, epoch: u64, files_per_epoch: usize) -> Vec<String> {
+        let cycle_len = rotation_cycle_len(self.files.len(), files_per_epoch);
+        let start_epoch = epoch - epoch % cycle_len;
+        self.rotation_cycle(start_epoch, files_per_epoch)
+            .files_at(epoch)
+            .unwrap_or_default()
+            .to_vec()
+    }
+
+    /// The rotation cycle over the current files starting at `start_epoch`.
+    ///
+    /// The visiting order is a permutation of canonical indices, ranked by
+    /// `SHA-256("rotation_schedule" || root || start_epoch || index)`. Cycles need not
+    /// start at a multiple of their length: chaining them with
+    /// `ledger.rotation_cycle(previous.epochs().end, files_per_epoch)` lets each cycle
+    /// use the ledger as it stood at its first epoch.
+    pub fn rotation_cycle(&self, start_epoch: u64, files_per_epoch: usize) -> RotationCycle {
+        let mut seed_hasher = Sha256::new();
+        seed_hasher.update(b"rotation_schedule");
+        seed_hasher.update(self.root().to_repr());
+        seed_hasher.update(start_epoch.to_le_bytes());
+
+        let mut ranked: Vec<([u8; 32], &String)> = self
+            .files
+            .keys()
+            .enumerate()
+            .map(|(index, file_id)| {
+                let mut hasher = seed_hasher.clone();
+                hasher.update((index as u64).to_le_bytes());
+                (hasher.finalize().into(), file_id)
+            })
+            .collect();
+        ranked.sort();
+
+        RotationCycle {
+            start_epoch,
+            files_per_epoch: files_per_epoch.max(1),
+            order: ranked
+                .into_iter()
+                .map(|(_, file_id)| file_id.clone())
+                .collect(),
+        }
+    }
+
     /// Rebuilds the aggregated Merkle tree from rc values (root commitments).
     /// The tree is built from rc = Poseidon(TAG_RC, root, depth) for each file,
     /// padded to the next power of two to ensure a fixed depth.
+    ///
+    /// Fixed-capacity ledgers always pad to `capacity.next_power_of_two()` leaves, filling
+    /// unused slots with the domain-separated empty-slot leaf. Tombstoned slots of
+    /// insertion-order ledgers hold the same padding leaf as unused slots.
     fn rebuild_tree(&mut self) -> Result<(), KontorPoRError> {
-        // Collect rc values in sorted key order (BTreeMap is deterministic)
-        let rc_values: Vec<F> = self.files.values().map(|entry| entry.rc).collect();
+        self.rebuild_rc_index();
+
+        let padding = if self.capacity.is_some() {
+            crate::poseidon::empty_ledger_slot()
+        } else {
+            F::ZERO
+        };
+
+        // Collect rc values in index order (BTreeMap is deterministic)
+        let rc_values: Vec<F> = match self.index_policy {
+            IndexPolicy::Canonical => self.files.values().map(|entry| entry.rc).collect(),
+            IndexPolicy::InsertionOrder => self
+                .slots
+                .iter()
+                .map(|slot| match slot {
+                    Some(file_id) => self.files[file_id].rc,
+                    None => padding,
+                })
+                .collect(),
+        };
+
+        if let Some(capacity) = self.capacity {
+            let padded_len = capacity.max(rc_values.len()).next_power_of_two();
+            let mut padded_rcs = rc_values;
+            padded_rcs.resize(padded_len, padding);
+            self.tree = build_tree_from_leaves(&padded_rcs)?;
+            return Ok(());
+        }
 
         if rc_values.is_empty() {
             // An empty ledger has a tree with a single zero leaf.
@@ -242,26 +1160,112 @@ impl FileLedger {
 
         let padded_len = rc_values.len().next_power_of_two();
         let mut padded_rcs = rc_values;
-        padded_rcs.resize(padded_len, F::ZERO); // Pad with neutral element
+        padded_rcs.resize(padded_len, padding); // Pad with neutral element
 
         self.tree = build_tree_from_leaves(&padded_rcs)?;
         Ok(())
     }
 
-    /// Get the canonical ledger index for a specific rc value.
-    /// This allows checking if a file with specific (root, depth) exists in the ledger.
-    pub fn get_canonical_index_for_rc(&self, rc: F) -> Option<usize> {
-        // Find position by file_id order (same as rebuild_tree) - BTreeMap iteration is deterministic
-        self.files.values().position(|entry| entry.rc == rc)
+    /// Rebuilds the reverse map from rc to file ID and index. Files sharing an rc (copies
+    /// of the same content under different IDs) map to the lowest index, matching the
+    /// first leaf holding that rc.
+    fn rebuild_rc_index(&mut self) {
+        let files = &self.files;
+        let indexed: Vec<(usize, &String)> = match self.index_policy {
+            IndexPolicy::Canonical => files.keys().enumerate().collect(),
+            IndexPolicy::InsertionOrder => self
+                .slots
+                .iter()
+                .enumerate()
+                .filter_map(|(index, slot)| slot.as_ref().map(|file_id| (index, file_id)))
+                .collect(),
+        };
+
+        let mut rc_index = HashMap::with_capacity(indexed.len());
+        for (index, file_id) in indexed {
+            let repr: [u8; 32] = files[file_id].rc.to_repr().into();
+            rc_index
+                .entry(repr)
+                .or_insert_with(|| (file_id.clone(), index));
+        }
+        self.rc_index = rc_index;
     }
 
-    /// Saves the `FileLedger` to the specified path using bincode serialization.
-    pub fn save(&self, path: &Path) -> Result<(), KontorPoRError> {
+    /// Looks up the file committed to by `rc` and returns its ID and index under the
+    /// ledger's [`IndexPolicy`], in constant time.
+    ///
+    /// If several files share an rc, the one at the lowest index is returned.
+    pub fn lookup_by_rc(&self, rc: F) -> Option<(&str, usize)> {
+        let repr: [u8; 32] = rc.to_repr().into();
+        self.rc_index
+            .get(&repr)
+            .map(|(file_id, index)| (file_id.as_str(), *index))
+    }
+
+    /// Get the ledger index for a specific rc value under the ledger's [`IndexPolicy`].
+    /// This allows checking if a file with specific (root, depth) exists in the ledger.
+    pub fn get_canonical_index_for_rc(&self, rc: F) -> Option<usize> {
+        self.lookup_by_rc(rc).map(|(_, index)| index)
+    }
+
+    /// Checks that insertion-order slots and file entries correspond one to one, and that
+    /// canonical ledgers carry no slots.
+    fn validate_slots(&self) -> Result<(), KontorPoRError> {
+        let invalid = |reason: String| Err(KontorPoRError::LedgerValidation { reason });
+        match self.index_policy {
+            IndexPolicy::Canonical if !self.slots.is_empty() => {
+                invalid("canonical ledger has slot assignments".to_string())
+            }
+            IndexPolicy::Canonical => Ok(()),
+            IndexPolicy::InsertionOrder => {
+                let mut assigned = BTreeSet::new();
+                for file_id in self.slots.iter().flatten() {
+                    if !self.files.contains_key(file_id) {
+                        return invalid(format!("slot refers to unknown file {}", file_id));
+                    }
+                    if !assigned.insert(file_id) {
+                        return invalid(format!("file {} occupies more than one slot", file_id));
+                    }
+                }
+                if assigned.len() != self.files.len() {
+                    return invalid(format!(
+                        "{} files but {} occupied slots",
+                        self.files.len(),
+                        assigned.len()
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Saves the `FileLedger` to the specified path using bincode serialization.
+    ///
+    /// The file is rewritten in place, so a crash mid-write can leave it unloadable. Use
+    /// [`Self::open_journaled`] for a ledger that must survive crashes.
+    pub fn save(&self, path: &Path) -> Result<(), KontorPoRError> {
+        let encoded = self.encode()?;
+        fs::write(path, encoded).map_err(|e| {
+            KontorPoRError::IO(format!(
+                "Failed to write ledger to {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// The serialized form written by [`Self::save`].
+    fn encode(&self) -> Result<Vec<u8>, KontorPoRError> {
         let data = LedgerData {
             version: crate::config::LEDGER_FORMAT_VERSION,
             files: self.files.clone(),
             root: self.tree.root(),
             historical_roots: self.historical_roots.clone(),
+            capacity: self.capacity,
+            index_policy: self.index_policy,
+            slots: self.slots.clone(),
+            root_heights: self.root_heights.clone(),
+            migrated_roots: self.migrated_roots.clone(),
         };
 
         let encoded = bincode::serialize(&data).map_err(|e| {
@@ -275,17 +1279,14 @@ impl FileLedger {
                 crate::config::MAX_LEDGER_SIZE_BYTES
             )));
         }
-
-        fs::write(path, encoded).map_err(|e| {
-            KontorPoRError::IO(format!(
-                "Failed to write ledger to {}: {}",
-                path.display(),
-                e
-            ))
-        })
+        Ok(encoded)
     }
 
     /// Loads a `FileLedger` from the specified path with validation.
+    ///
+    /// Ledgers saved in format version 1 (the 0.1 releases) load as growing, canonically
+    /// indexed ledgers with empty filenames; other versions than the current
+    /// [`crate::config::LEDGER_FORMAT_VERSION`] are rejected.
     pub fn load(path: &Path) -> Result<Self, KontorPoRError> {
         let encoded = fs::read(path).map_err(|e| {
             KontorPoRError::IO(format!(
@@ -294,7 +1295,11 @@ impl FileLedger {
                 e
             ))
         })?;
+        Self::decode(&encoded)
+    }
 
+    /// Validates and deserializes a ledger in the form written by [`Self::save`].
+    fn decode(encoded: &[u8]) -> Result<Self, KontorPoRError> {
         if encoded.len() > crate::config::MAX_LEDGER_SIZE_BYTES {
             return Err(KontorPoRError::InvalidInput(format!(
                 "Ledger file size {} bytes exceeds maximum {} bytes",
@@ -303,23 +1308,81 @@ impl FileLedger {
             )));
         }
 
-        let data: LedgerData = bincode::deserialize(&encoded).map_err(|e| {
-            KontorPoRError::Serialization(format!("Failed to deserialize ledger: {}", e))
-        })?;
+        // Every format starts with its version as a little-endian u16
+        let version = encoded
+            .get(..2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+        let data: LedgerData = if version == Some(1) {
+            let data: LedgerDataV1 = bincode::deserialize(encoded)
+                .map_err(|e| crate::merkle::canonical::deserialize_error(e, "ledger"))?;
+            data.into()
+        } else {
+            let data: LedgerData = bincode::deserialize(encoded)
+                .map_err(|e| crate::merkle::canonical::deserialize_error(e, "ledger"))?;
+            if data.version != crate::config::LEDGER_FORMAT_VERSION {
+                return Err(KontorPoRError::InvalidInput(format!(
+                    "Ledger format version {} is not compatible with current version {}",
+                    data.version,
+                    crate::config::LEDGER_FORMAT_VERSION
+                )));
+            }
+            data
+        };
 
-        if data.version != crate::config::LEDGER_FORMAT_VERSION {
-            return Err(KontorPoRError::InvalidInput(format!(
-                "Ledger format version {} is not compatible with current version {}",
-                data.version,
-                crate::config::LEDGER_FORMAT_VERSION
-            )));
+        if data
+            .historical_roots
+            .iter()
+            .any(|root| field_from_bytes(*root).is_none())
+        {
+            return Err(KontorPoRError::NonCanonicalFieldElement {
+                context: "ledger historical roots".to_string(),
+            });
+        }
+        if data
+            .root_heights
+            .values()
+            .any(|root| field_from_bytes(*root).is_none())
+        {
+            return Err(KontorPoRError::NonCanonicalFieldElement {
+                context: "ledger root heights".to_string(),
+            });
+        }
+        if data
+            .migrated_roots
+            .iter()
+            .any(|root| field_from_bytes(*root).is_none())
+        {
+            return Err(KontorPoRError::NonCanonicalFieldElement {
+                context: "ledger migrated roots".to_string(),
+            });
         }
 
         let mut ledger = FileLedger {
             files: data.files,
             tree: MerkleTree::default(),
             historical_roots: data.historical_roots,
+            capacity: data.capacity,
+            index_policy: data.index_policy,
+            slots: data.slots,
+            root_heights: data.root_heights,
+            migrated_roots: data.migrated_roots,
+            rc_index: HashMap::new(),
+            journal: JournalHandle::default(),
         };
+        ledger.validate_slots()?;
+
+        if let Some(capacity) = ledger.capacity {
+            if ledger.used_slots() > capacity {
+                return Err(KontorPoRError::LedgerValidation {
+                    reason: format!(
+                        "ledger uses {} slots but its capacity is {}",
+                        ledger.used_slots(),
+                        capacity
+                    ),
+                });
+            }
+        }
+
         ledger.rebuild_tree()?;
 
         if ledger.tree.root() != data.root {
@@ -331,34 +1394,1095 @@ impl FileLedger {
         Ok(ledger)
     }
 
+    /// Opens a crash-safe ledger: the snapshot at `path` plus an append-only journal at
+    /// `path` with `.journal` appended.
+    ///
+    /// The snapshot is loaded like [`Self::load`] (an empty ledger is created if it does
+    /// not exist) and the journal's updates are replayed on top of it. From then on every
+    /// update ([`Self::add_file`], [`Self::add_files`], [`Self::remove_file`],
+    /// [`Self::rename_file`], [`Self::apply_diff`] and the historical root and root height
+    /// setters) is checked, appended to the journal as one checksummed frame and synced,
+    /// and only then applied; if the append fails the ledger is unchanged and the error
+    /// returned. A crash mid-append leaves a torn final frame, which the next open drops,
+    /// recovering the ledger as of the last complete update. [`Self::compact`] folds the
+    /// journal into a new snapshot.
+    ///
+    /// To journal a ledger with a fixed capacity or [`IndexPolicy::InsertionOrder`],
+    /// [`Self::save`] it to `path` before the first open. Calling `save` on `path` while
+    /// the ledger is open makes the journal stale, and it is discarded on the next open;
+    /// use `compact` instead. Clones of a journaled ledger are not journaled.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::IO`] if the snapshot or journal cannot be read or written
+    /// - [`KontorPoRError::LedgerValidation`] if the snapshot fails validation, the journal
+    ///   is corrupt before its final frame, or a journal exists without its snapshot
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::api::prepare_file;
+    /// use kontor_crypto::FileLedger;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("ledger.bin");
+    /// let (_, metadata) = prepare_file(b"hello", "test.dat").unwrap();
+    /// let mut ledger = FileLedger::open_journaled(path).unwrap();
+    /// ledger.add_file(&metadata).unwrap(); // durable once this returns
+    ///
+    /// let reopened = FileLedger::open_journaled(path).unwrap();
+    /// assert_eq!(reopened.root(), ledger.root());
+    /// ```
+    pub fn open_journaled(path: &Path) -> Result<Self, KontorPoRError> {
+        let (mut ledger, digest) = match fs::read(path) {
+            Ok(encoded) => (
+                Self::decode(&encoded)?,
+                ledger_journal::snapshot_digest(&encoded),
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let journal = ledger_journal::journal_path(path);
+                if journal.exists() {
+                    return Err(KontorPoRError::LedgerValidation {
+                        reason: format!(
+                            "ledger journal {} has no snapshot at {}",
+                            journal.display(),
+                            path.display()
+                        ),
+                    });
+                }
+                let ledger = Self::new();
+                let encoded = ledger.encode()?;
+                ledger_journal::write_atomic(path, &encoded)?;
+                (ledger, ledger_journal::snapshot_digest(&encoded))
+            }
+            Err(e) => {
+                return Err(KontorPoRError::IO(format!(
+                    "Failed to read ledger from {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        let (journal, ops) = Journal::open(path, digest)?;
+        for (i, op) in ops.iter().enumerate() {
+            ledger
+                .check_op(op)
+                .and_then(|()| ledger.apply_op(op))
+                .map_err(|e| KontorPoRError::LedgerValidation {
+                    reason: format!("journal update {} does not apply: {}", i, e),
+                })?;
+        }
+        ledger.journal = JournalHandle(Some(journal));
+        Ok(ledger)
+    }
+
+    /// Writes the ledger to a new snapshot and empties its journal.
+    ///
+    /// The snapshot is written to a temporary file and atomically renamed into place
+    /// before the journal is reset. A crash in between leaves a journal for the old
+    /// snapshot, which the next [`Self::open_journaled`] discards.
+    ///
+    /// # Errors
+    ///
+    /// - [`KontorPoRError::InvalidInput`] if the ledger was not opened with
+    ///   [`Self::open_journaled`]
+    /// - [`KontorPoRError::IO`] if the snapshot or journal cannot be written
+    pub fn compact(&mut self) -> Result<(), KontorPoRError> {
+        let encoded = self.encode()?;
+        let journal = self.journal.0.as_mut().ok_or_else(|| {
+            KontorPoRError::InvalidInput(
+                "compact requires a ledger opened with open_journaled".to_string(),
+            )
+        })?;
+        let snapshot_path = journal.snapshot_path().to_path_buf();
+        ledger_journal::write_atomic(&snapshot_path, &encoded)?;
+        *journal = Journal::create(&snapshot_path, ledger_journal::snapshot_digest(&encoded))?;
+        Ok(())
+    }
+
+    /// Whether updates are journaled, i.e. the ledger was opened with
+    /// [`Self::open_journaled`].
+    pub fn is_journaled(&self) -> bool {
+        self.journal.0.is_some()
+    }
+
+    /// Checks `op`, appends it to the journal of a journaled ledger, then applies it.
+    ///
+    /// Every rejection happens in the check, before the append, so the ledger is left
+    /// unchanged on error and never runs ahead of its journal.
+    fn apply_journaled(&mut self, op: JournalOp) -> Result<(), KontorPoRError> {
+        self.check_op(&op)?;
+        if let Some(journal) = self.journal.0.as_mut() {
+            journal.append(&op)?;
+        }
+        self.apply_op(&op)
+    }
+
+    /// Checks that `op` applies to the ledger.
+    fn check_op(&self, op: &JournalOp) -> Result<(), KontorPoRError> {
+        let require_file = |file_id: &str| {
+            if self.files.contains_key(file_id) {
+                Ok(())
+            } else {
+                Err(KontorPoRError::FileNotFound {
+                    file_id: file_id.to_string(),
+                })
+            }
+        };
+        match op {
+            JournalOp::AddFile { file_id, .. } if !self.files.contains_key(file_id) => {
+                self.check_capacity(1)
+            }
+            JournalOp::AddFiles(entries) | JournalOp::Migrate { entries, .. } => {
+                self.check_capacity(self.new_file_ids(entries).len())
+            }
+            JournalOp::RemoveFile(file_id) | JournalOp::RenameFile { file_id, .. } => {
+                require_file(file_id)
+            }
+            JournalOp::ApplyDiff(diff) => self.check_diff(diff),
+            JournalOp::AddFile { .. }
+            | JournalOp::SetHistoricalRoots(_)
+            | JournalOp::RecordCurrentRoot
+            | JournalOp::RecordRootAtHeight(_)
+            | JournalOp::ClearMigratedRoots => Ok(()),
+        }
+    }
+
+    /// Applies an `op` that passed [`Self::check_op`].
+    fn apply_op(&mut self, op: &JournalOp) -> Result<(), KontorPoRError> {
+        match op {
+            JournalOp::AddFile { file_id, entry } => self.insert_file(file_id, entry.clone()),
+            JournalOp::AddFiles(entries) => self.insert_files(entries.clone()),
+            JournalOp::RemoveFile(file_id) => self.delete_file(file_id),
+            JournalOp::ApplyDiff(diff) => self.merge_diff(diff),
+            JournalOp::RenameFile { file_id, new_name } => {
+                if let Some(entry) = self.files.get_mut(file_id) {
+                    entry.filename = new_name.clone();
+                }
+                Ok(())
+            }
+            JournalOp::SetHistoricalRoots(roots) => {
+                self.historical_roots = roots.clone();
+                Ok(())
+            }
+            JournalOp::RecordCurrentRoot => {
+                self.push_current_root();
+                Ok(())
+            }
+            JournalOp::RecordRootAtHeight(block_height) => {
+                let repr: [u8; 32] = self.tree.root().to_repr().into();
+                self.root_heights.insert(*block_height, repr);
+                Ok(())
+            }
+            JournalOp::Migrate { entries, roots } => {
+                self.insert_files(entries.clone())?;
+                self.push_current_root();
+                for root in roots {
+                    if !self.migrated_roots.contains(root) {
+                        self.migrated_roots.push(*root);
+                    }
+                }
+                Ok(())
+            }
+            JournalOp::ClearMigratedRoots => {
+                self.migrated_roots.clear();
+                Ok(())
+            }
+        }
+    }
+
     /// Returns the depth of the aggregated Merkle tree.
     pub fn depth(&self) -> usize {
         self.tree.layers.len().saturating_sub(1)
     }
 
-    /// Looks up a file by its ID and returns its canonical index and leaf value (rc).
-    /// The index is its lexicographical rank among all file IDs in the ledger.
+    /// Number of layers in the aggregated Merkle tree, leaves and root included.
+    pub fn tree_layers_len(&self) -> usize {
+        self.tree.layers.len()
+    }
+
+    /// The leaves of the aggregated Merkle tree in index order, including padding leaves.
+    pub fn leaves(&self) -> &[F] {
+        &self.tree.layers[0]
+    }
+
+    /// Number of files in the ledger.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Approximate heap memory held by the ledger in bytes.
+    ///
+    /// Counts the entries with their file IDs and filenames, the aggregated tree, the rc
+    /// index, insertion-order slots, historical and migrated roots and recorded root
+    /// heights. Map nodes
+    /// count the size of their keys and values; allocator and node bookkeeping is not
+    /// included.
+    pub fn heap_size(&self) -> usize {
+        use std::mem::size_of;
+
+        let entries: usize = self
+            .files
+            .iter()
+            .map(|(file_id, entry)| {
+                size_of::<String>()
+                    + size_of::<FileLedgerEntry>()
+                    + file_id.capacity()
+                    + entry.filename.capacity()
+            })
+            .sum();
+        let rc_index = self.rc_index.capacity() * size_of::<([u8; 32], (String, usize))>()
+            + self
+                .rc_index
+                .values()
+                .map(|(file_id, _)| file_id.capacity())
+                .sum::<usize>();
+        let slots = self.slots.capacity() * size_of::<Option<String>>()
+            + self
+                .slots
+                .iter()
+                .flatten()
+                .map(String::capacity)
+                .sum::<usize>();
+
+        entries
+            + self.tree.heap_size()
+            + rc_index
+            + slots
+            + (self.historical_roots.capacity() + self.migrated_roots.capacity())
+                * size_of::<[u8; 32]>()
+            + self.root_heights.len() * size_of::<(u64, [u8; 32])>()
+    }
+
+    /// Returns the entry stored for `file_id`, if the ledger holds that file.
+    pub fn entry(&self, file_id: &str) -> Option<&FileLedgerEntry> {
+        self.files.get(file_id)
+    }
+
+    /// Iterates over the files and their entries, ordered by file ID.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &FileLedgerEntry)> {
+        self.files.iter().map(|(id, entry)| (id.as_str(), entry))
+    }
+
+    /// Iterates over the file IDs, in order.
+    pub fn file_ids(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Mutable access to a stored entry without rebuilding the tree, for tests only.
+    ///
+    /// Edits leave the tree and rc index stale until the next add, remove or load, which
+    /// is what consistency tests need to simulate corruption.
+    #[doc(hidden)]
+    pub fn entry_mut_unchecked(&mut self, file_id: &str) -> Option<&mut FileLedgerEntry> {
+        self.files.get_mut(file_id)
+    }
+
+    /// Mutable access to the stored tree layers, for tests only.
+    #[doc(hidden)]
+    pub fn tree_layers_mut_unchecked(&mut self) -> &mut Vec<Vec<F>> {
+        &mut self.tree.layers
+    }
+
+    /// Looks up a file by its ID and returns its index and leaf value (rc).
+    /// Under [`IndexPolicy::Canonical`] the index is its lexicographical rank among all
+    /// file IDs in the ledger; under [`IndexPolicy::InsertionOrder`] it is the slot the
+    /// file was assigned when first added.
     pub fn lookup(&self, file_id: &str) -> Option<(usize, F)> {
-        if let Some(entry) = self.files.get(file_id) {
-            // The index is the position in the BTreeMap's sorted keys. O(n) but simple.
-            self.files
-                .keys()
-                .position(|k| k == file_id)
-                .map(|index| (index, entry.rc))
-        } else {
-            None
+        let entry = self.files.get(file_id)?;
+        // The index is the position in the BTreeMap's sorted keys or in the slot list. O(n) but simple.
+        let index = match self.index_policy {
+            IndexPolicy::Canonical => self.files.keys().position(|k| k == file_id),
+            IndexPolicy::InsertionOrder => self
+                .slots
+                .iter()
+                .position(|slot| slot.as_deref() == Some(file_id)),
+        }?;
+        Some((index, entry.rc))
+    }
+
+    /// Returns the ID of the file at `index`, the inverse of [`Self::lookup`].
+    ///
+    /// `None` if `index` is past the last file, or is a removed file's tombstoned slot
+    /// under [`IndexPolicy::InsertionOrder`].
+    pub fn file_id_at(&self, index: usize) -> Option<&str> {
+        match self.index_policy {
+            IndexPolicy::Canonical => self.files.keys().nth(index).map(String::as_str),
+            IndexPolicy::InsertionOrder => self.slots.get(index)?.as_deref(),
         }
     }
 
     /// Returns the Merkle proof of inclusion for a given file ID in the aggregated tree.
-    pub fn get_aggregation_proof(
-        &self,
-        file_id: &str,
-    ) -> Option<crate::merkle::CircuitMerkleProof> {
+    ///
+    /// The proof uses the same [`CircuitMerkleProof`] layout as file-tree proofs: its `leaf`
+    /// is the file's rc value and `path_indices[i] == true` means the node at level `i` is
+    /// the right child (its sibling is on the left). Check it off-circuit with
+    /// [`verify_aggregation_proof`].
+    pub fn get_aggregation_proof(&self, file_id: &str) -> Option<CircuitMerkleProof> {
         // Use the efficient cached lookup
         let (index, _rc) = self.lookup(file_id)?;
 
         let depth = self.depth();
         get_padded_proof_for_leaf(&self.tree, index, depth).ok()
     }
+
+    /// Returns a self-contained certificate that `file_id` is registered at its canonical
+    /// index under the current ledger root.
+    ///
+    /// The certificate can be checked with [`verify_membership_certificate`] without a
+    /// ledger instance. It embeds the ledger root it was issued against, so it stays
+    /// checkable after later adds; whether that root is still accepted is up to the caller.
+    pub fn membership_certificate(&self, file_id: &str) -> Option<MembershipCertificate> {
+        let entry = self.files.get(file_id)?;
+        let proof = self.get_aggregation_proof(file_id)?;
+        let (index, _rc) = self.lookup(file_id)?;
+
+        // Entries store only rc; recover the arity and profile it was built with
+        let tree_arity = entry.tree_arity().unwrap_or(TreeArity::Binary);
+        let hash_profile = entry.hash_profile().unwrap_or(HashProfile::Standard);
+
+        Some(MembershipCertificate {
+            file_id: file_id.to_string(),
+            root: entry.root,
+            depth: entry.depth,
+            tree_arity,
+            hash_profile,
+            rc: entry.rc,
+            index,
+            siblings: proof.siblings,
+            ledger_root: self.root(),
+        })
+    }
+
+    /// Exports the aggregated tree without per-file metadata.
+    ///
+    /// The export holds the rc leaves in index order (padding leaves included), the root
+    /// and the depth, and can be checked with [`AggregatedTreeExport::verify`] and queried
+    /// with [`AggregatedTreeExport::proof_for_index`] without a ledger instance.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use kontor_crypto::api::prepare_file;
+    /// use kontor_crypto::ledger::FileLedger;
+    ///
+    /// let (_, metadata) = prepare_file(b"hello", "test.dat").unwrap();
+    /// let mut ledger = FileLedger::new();
+    /// ledger.add_file(&metadata).unwrap();
+    ///
+    /// let export = ledger.export_aggregated_tree();
+    /// assert!(export.verify());
+    /// assert_eq!(export.root, ledger.root());
+    /// ```
+    pub fn export_aggregated_tree(&self) -> AggregatedTreeExport {
+        AggregatedTreeExport {
+            schema_version: crate::config::AGGREGATED_TREE_EXPORT_VERSION,
+            leaves: self.leaves().to_vec(),
+            root: self.root(),
+            depth: self.depth(),
+        }
+    }
+
+    /// Returns a hex-encoded, human-readable summary of the ledger for inspection.
+    ///
+    /// Files are listed in index order; field elements are the lowercase hex of their
+    /// canonical 32-byte little-endian encoding.
+    pub fn describe(&self) -> LedgerDescription {
+        let files = self
+            .indexed_files()
+            .into_iter()
+            .map(|(index, file_id)| {
+                let entry = &self.files[file_id];
+                LedgerFileDescription {
+                    file_id: file_id.clone(),
+                    filename: entry.filename.clone(),
+                    root: field_to_hex(&entry.root),
+                    depth: entry.depth,
+                    tree_arity: entry.tree_arity(),
+                    hash_profile: entry.hash_profile(),
+                    rc: field_to_hex(&entry.rc),
+                    index,
+                }
+            })
+            .collect();
+
+        let to_hex = |root: &[u8; 32]| root.iter().map(|byte| format!("{:02x}", byte)).collect();
+        LedgerDescription {
+            root: field_to_hex(&self.root()),
+            depth: self.depth(),
+            file_count: self.files.len(),
+            capacity: self.capacity,
+            index_policy: self.index_policy,
+            files,
+            historical_roots: self.historical_roots.iter().map(to_hex).collect(),
+            migrated_roots: self.migrated_roots.iter().map(to_hex).collect(),
+        }
+    }
+
+    /// Every file with its index, in index order.
+    fn indexed_files(&self) -> Vec<(usize, &String)> {
+        match self.index_policy {
+            IndexPolicy::Canonical => self.files.keys().enumerate().collect(),
+            IndexPolicy::InsertionOrder => self
+                .slots
+                .iter()
+                .enumerate()
+                .filter_map(|(index, slot)| slot.as_ref().map(|file_id| (index, file_id)))
+                .collect(),
+        }
+    }
+
+    /// Recomputes every root commitment and the aggregated tree from the file entries
+    /// and checks them, and the historical roots, against the stored values.
+    ///
+    /// Every discrepancy is collected into the returned [`ConsistencyReport`], naming the
+    /// offending file ID, tree layer or historical root. Use
+    /// [`ConsistencyReport::into_result`] to turn an inconsistent report into an error.
+    ///
+    /// # Errors
+    ///
+    /// Only if the aggregated tree cannot be rebuilt at all.
+    pub fn check_consistency(&self) -> Result<ConsistencyReport, KontorPoRError> {
+        let mut issues = Vec::new();
+
+        for (file_id, entry) in &self.files {
+            if entry.tree_arity().is_none() {
+                issues.push(ConsistencyIssue::RcMismatch {
+                    file_id: file_id.clone(),
+                });
+            }
+        }
+
+        for (index, root) in self.historical_roots.iter().enumerate() {
+            if field_from_bytes(*root).is_none() {
+                issues.push(ConsistencyIssue::NonCanonicalHistoricalRoot { index });
+            }
+        }
+
+        // Invalid slots cannot be rebuilt into a tree, so stop at reporting them
+        if let Err(KontorPoRError::LedgerValidation { reason }) = self.validate_slots() {
+            issues.push(ConsistencyIssue::InvalidSlots { reason });
+            return Ok(ConsistencyReport { issues });
+        }
+
+        let mut rebuilt = FileLedger {
+            files: self.files.clone(),
+            tree: MerkleTree::default(),
+            historical_roots: Vec::new(),
+            capacity: self.capacity,
+            index_policy: self.index_policy,
+            slots: self.slots.clone(),
+            root_heights: BTreeMap::new(),
+            migrated_roots: Vec::new(),
+            rc_index: HashMap::new(),
+            journal: JournalHandle::default(),
+        };
+        rebuilt.rebuild_tree()?;
+
+        let stored = &self.tree.layers;
+        let expected = &rebuilt.tree.layers;
+        for layer in 0..stored.len().max(expected.len()) {
+            let stored_layer = stored.get(layer).map(Vec::as_slice).unwrap_or_default();
+            let expected_layer = expected.get(layer).map(Vec::as_slice).unwrap_or_default();
+            if stored_layer != expected_layer {
+                let first_difference = stored_layer
+                    .iter()
+                    .zip(expected_layer)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(stored_layer.len().min(expected_layer.len()));
+                issues.push(ConsistencyIssue::LayerMismatch {
+                    layer,
+                    first_difference,
+                });
+            }
+        }
+        if rebuilt.root() != self.root() {
+            issues.push(ConsistencyIssue::RootMismatch);
+        }
+
+        Ok(ConsistencyReport { issues })
+    }
+
+    /// Loads a ledger like [`Self::load`] and then runs [`Self::check_consistency`],
+    /// failing with [`KontorPoRError::LedgerValidation`] on the first discrepancy.
+    ///
+    /// `load` already rebuilds the tree and compares the stored root; this additionally
+    /// checks that every rc commits to its entry's root and depth.
+    pub fn load_checked(path: &Path) -> Result<Self, KontorPoRError> {
+        let ledger = Self::load(path)?;
+        ledger.check_consistency()?.into_result()?;
+        Ok(ledger)
+    }
+}
+
+/// A discrepancy found by [`FileLedger::check_consistency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    /// The entry's rc matches neither arity's commitment of its root and depth
+    RcMismatch { file_id: String },
+    /// Insertion-order slots and file entries do not correspond one to one
+    InvalidSlots { reason: String },
+    /// A stored tree layer differs from the one rebuilt from the entries; `first_difference`
+    /// is the first differing node (or the shorter length if one layer is a prefix)
+    LayerMismatch {
+        layer: usize,
+        first_difference: usize,
+    },
+    /// The stored root differs from the root rebuilt from the entries
+    RootMismatch,
+    /// The historical root at `index` is not a canonical field encoding
+    NonCanonicalHistoricalRoot { index: usize },
+}
+
+impl std::fmt::Display for ConsistencyIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsistencyIssue::RcMismatch { file_id } => {
+                write!(f, "rc of {} does not commit to its root and depth", file_id)
+            }
+            ConsistencyIssue::InvalidSlots { reason } => write!(f, "invalid slots: {}", reason),
+            ConsistencyIssue::LayerMismatch {
+                layer,
+                first_difference,
+            } => write!(
+                f,
+                "tree layer {} differs from the recomputed layer at node {}",
+                layer, first_difference
+            ),
+            ConsistencyIssue::RootMismatch => {
+                write!(f, "recomputed root does not match stored root")
+            }
+            ConsistencyIssue::NonCanonicalHistoricalRoot { index } => {
+                write!(
+                    f,
+                    "historical root {} is not a canonical field element",
+                    index
+                )
+            }
+        }
+    }
+}
+
+/// Outcome of [`FileLedger::check_consistency`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Every discrepancy found, entries first, then historical roots, slots and tree layers
+    pub issues: Vec<ConsistencyIssue>,
+}
+
+impl ConsistencyReport {
+    /// Returns true if no discrepancy was found.
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// `Ok(())` for a consistent ledger, otherwise [`KontorPoRError::LedgerValidation`]
+    /// describing the first issue.
+    pub fn into_result(self) -> Result<(), KontorPoRError> {
+        match self.issues.first() {
+            None => Ok(()),
+            Some(issue) => Err(KontorPoRError::LedgerValidation {
+                reason: issue.to_string(),
+            }),
+        }
+    }
+}
+
+/// Human-readable summary of a [`FileLedger`], produced by [`FileLedger::describe`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerDescription {
+    /// Current aggregated root (hex)
+    pub root: String,
+    /// Aggregated tree depth
+    pub depth: usize,
+    /// Number of registered files
+    pub file_count: usize,
+    /// Fixed file capacity, if any
+    pub capacity: Option<usize>,
+    /// How file indices are assigned
+    pub index_policy: IndexPolicy,
+    /// Registered files in index order
+    pub files: Vec<LedgerFileDescription>,
+    /// Accepted historical roots (hex), oldest first
+    pub historical_roots: Vec<String>,
+    /// Roots accepted from ledgers this one was migrated from (hex), oldest first
+    #[serde(default)]
+    pub migrated_roots: Vec<String>,
+}
+
+/// Record of a [`FileLedger::migrate_from`], mapping the old ledger's file indices to the
+/// new ledger's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReceipt {
+    /// Current root of the old ledger at migration (canonical `to_repr()` bytes)
+    pub old_root: [u8; 32],
+    /// How the old ledger assigned file indices
+    pub old_index_policy: IndexPolicy,
+    /// Historical roots of the old ledger, oldest first, ending with [`Self::old_root`].
+    /// Roots the old ledger had itself migrated are accepted by the new ledger too, but
+    /// not listed, since the receipt cannot map their indices.
+    pub migrated_roots: Vec<[u8; 32]>,
+    /// Migrated files keyed by their index in the old ledger
+    pub files: BTreeMap<usize, MigratedFile>,
+}
+
+impl MigrationReceipt {
+    /// The new index of the file that sat at `old_index` in the old ledger.
+    pub fn new_index(&self, old_index: usize) -> Option<usize> {
+        self.files.get(&old_index).map(|file| file.new_index)
+    }
+
+    /// Whether `root` is one of the old ledger's roots.
+    pub fn covers_root(&self, root: F) -> bool {
+        self.migrated_roots
+            .iter()
+            .any(|r| field_from_bytes(*r) == Some(root))
+    }
+}
+
+/// One file moved by [`FileLedger::migrate_from`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigratedFile {
+    /// ID of the file, the same in both ledgers
+    pub file_id: String,
+    /// Index in the new ledger
+    pub new_index: usize,
+}
+
+/// One registered file within a [`LedgerDescription`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerFileDescription {
+    pub file_id: String,
+    pub filename: String,
+    /// File Merkle root (hex)
+    pub root: String,
+    pub depth: usize,
+    /// Arity recovered from the rc domain tag (`None` if rc is inconsistent)
+    pub tree_arity: Option<TreeArity>,
+    /// Hash profile recovered from rc (`None` if rc is inconsistent)
+    pub hash_profile: Option<HashProfile>,
+    /// Root commitment (hex)
+    pub rc: String,
+    /// Index in the aggregated tree
+    pub index: usize,
+}
+
+/// The changes between two ledgers, produced by [`FileLedger::diff`] and applied with
+/// [`FileLedger::apply_diff`].
+///
+/// Entries are the target ledger's, so a diff can be serialized and shipped to a follower
+/// that holds the source state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerDiff {
+    /// File IDs present only in the source ledger
+    pub removed: Vec<String>,
+    /// Files present only in the target ledger
+    pub added: BTreeMap<String, FileLedgerEntry>,
+    /// Files in both ledgers whose root, depth or rc differ, with the target's entry
+    pub changed: BTreeMap<String, FileLedgerEntry>,
+}
+
+impl LedgerDiff {
+    /// Whether the two ledgers hold the same files with the same commitments.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Number of epochs in a rotation cycle over `num_files` files: at least one, so an empty
+/// ledger still advances through cycles.
+fn rotation_cycle_len(num_files: usize, files_per_epoch: usize) -> u64 {
+    num_files.div_ceil(files_per_epoch.max(1)).max(1) as u64
+}
+
+/// One cycle of the challenge rotation produced by [`FileLedger::rotation_cycle`].
+///
+/// Epoch `start_epoch + i` challenges `order[i * files_per_epoch..]`, up to
+/// `files_per_epoch` files, so the cycle selects every file exactly once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationCycle {
+    /// The first epoch of the cycle
+    pub start_epoch: u64,
+    /// Files challenged per epoch (at least 1)
+    pub files_per_epoch: usize,
+    /// Every file ID in the ledger, in visiting order
+    pub order: Vec<String>,
+}
+
+impl RotationCycle {
+    /// The epochs this cycle covers.
+    pub fn epochs(&self) -> Range<u64> {
+        let len = rotation_cycle_len(self.order.len(), self.files_per_epoch);
+        self.start_epoch..self.start_epoch + len
+    }
+
+    /// The files challenged in `epoch`, or `None` if `epoch` is outside the cycle.
+    pub fn files_at(&self, epoch: u64) -> Option<&[String]> {
+        if !self.epochs().contains(&epoch) {
+            return None;
+        }
+        let start = (epoch - self.start_epoch) as usize * self.files_per_epoch;
+        let end = (start + self.files_per_epoch).min(self.order.len());
+        Some(&self.order[start.min(end)..end])
+    }
+}
+
+/// Standalone evidence that a file is registered in a ledger at a given index.
+///
+/// Produced by [`FileLedger::membership_certificate`] and checked with
+/// [`verify_membership_certificate`]. The sibling path is bottom-up; the direction at
+/// each level is the corresponding bit of `index`, least significant first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MembershipCertificate {
+    /// The registered file's identifier
+    pub file_id: String,
+    /// The Merkle root of the file's tree
+    pub root: F,
+    /// The depth of the file's tree
+    pub depth: usize,
+    /// Branching factor of the file's tree (selects the rc domain tag)
+    #[serde(default)]
+    pub tree_arity: TreeArity,
+    /// Poseidon instantiation of the file's tree (selects the rc constants)
+    #[serde(default)]
+    pub hash_profile: HashProfile,
+    /// The file's root commitment, the leaf of the aggregated tree
+    pub rc: F,
+    /// Canonical index of the file in the ledger
+    pub index: usize,
+    /// Sibling hashes from the rc leaf up to the ledger root
+    pub siblings: Vec<F>,
+    /// The ledger root the certificate was issued against
+    pub ledger_root: F,
+}
+
+/// Verifies a [`MembershipCertificate`] as a pure cryptographic statement.
+///
+/// Returns `true` iff `rc` is the root commitment of `(root, depth)` for the
+/// certificate's tree arity and hash profile, `index` fits in the path length, and folding `rc` up the
+/// sibling path along the bits of `index` reproduces `ledger_root`. It does not check
+/// that `ledger_root` is current or among a ledger's historical roots.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::prepare_file;
+/// use kontor_crypto::ledger::{verify_membership_certificate, FileLedger};
+///
+/// let (_, metadata) = prepare_file(b"hello", "test.dat").unwrap();
+/// let mut ledger = FileLedger::new();
+/// ledger.add_file(&metadata).unwrap();
+///
+/// let certificate = ledger.membership_certificate(&metadata.file_id).unwrap();
+/// assert!(verify_membership_certificate(&certificate));
+/// ```
+pub fn verify_membership_certificate(certificate: &MembershipCertificate) -> bool {
+    if compute_rc_with(
+        certificate.root,
+        certificate.depth,
+        certificate.tree_arity,
+        certificate.hash_profile,
+    ) != certificate.rc
+    {
+        return false;
+    }
+
+    let path_len = certificate.siblings.len();
+    let shifted = |level: usize| certificate.index.checked_shr(level as u32).unwrap_or(0);
+    if shifted(path_len) != 0 {
+        return false;
+    }
+
+    let proof = CircuitMerkleProof {
+        leaf: certificate.rc,
+        siblings: certificate.siblings.clone(),
+        path_indices: (0..path_len).map(|level| shifted(level) & 1 == 1).collect(),
+    };
+    verify_merkle_proof_in_place(certificate.ledger_root, &proof)
+}
+
+/// The aggregated tree of a ledger, detached from its per-file metadata.
+///
+/// Produced by [`FileLedger::export_aggregated_tree`] for indexers and explorers that only
+/// need the rc leaves. Serializes with serde, e.g. to JSON or bincode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedTreeExport {
+    /// Export format version ([`crate::config::AGGREGATED_TREE_EXPORT_VERSION`])
+    pub schema_version: u16,
+    /// Leaves of the aggregated tree in index order: each file's rc, then padding leaves
+    pub leaves: Vec<F>,
+    /// The ledger root
+    pub root: F,
+    /// Depth of the aggregated tree; `leaves` holds exactly `2^depth` entries unless the
+    /// ledger is empty
+    pub depth: usize,
+}
+
+impl AggregatedTreeExport {
+    /// Returns `true` iff the export has the current schema version, `2^depth` leaves, and
+    /// rebuilding the tree from its leaves reproduces `root`. The export of an empty ledger
+    /// has no leaves, depth 0 and a zero root.
+    pub fn verify(&self) -> bool {
+        if self.schema_version != crate::config::AGGREGATED_TREE_EXPORT_VERSION {
+            return false;
+        }
+        if self.leaves.is_empty() {
+            return self.depth == 0 && self.root == F::ZERO;
+        }
+        if 1usize.checked_shl(self.depth as u32) != Some(self.leaves.len()) {
+            return false;
+        }
+        match build_tree_from_leaves(&self.leaves) {
+            Ok(tree) => tree.root() == self.root,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the aggregation proof for the leaf at `index`, or `None` if `index` is out of
+    /// range or the export does not [`verify`](Self::verify).
+    ///
+    /// For a file's index this equals [`FileLedger::get_aggregation_proof`] on the exporting
+    /// ledger and checks with [`verify_aggregation_proof`] against `root`.
+    pub fn proof_for_index(&self, index: usize) -> Option<CircuitMerkleProof> {
+        if index >= self.leaves.len() || !self.verify() {
+            return None;
+        }
+        let tree = build_tree_from_leaves(&self.leaves).ok()?;
+        get_padded_proof_for_leaf(&tree, index, self.depth).ok()
+    }
+}
+
+/// Verifies an aggregation proof from [`FileLedger::get_aggregation_proof`] outside the SNARK.
+///
+/// Returns `true` iff the proof's leaf equals `rc` and folding the siblings up the path
+/// reproduces `root`. Direction semantics match [`verify_merkle_proof_in_place`]:
+/// `path_indices[i] == true` hashes `H(sibling, current)`, `false` hashes `H(current, sibling)`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use kontor_crypto::api::prepare_file;
+/// use kontor_crypto::ledger::{verify_aggregation_proof, FileLedger};
+///
+/// let (_, metadata) = prepare_file(b"hello", "test.dat").unwrap();
+/// let mut ledger = FileLedger::new();
+/// ledger.add_file(&metadata).unwrap();
+///
+/// let (_, rc) = ledger.lookup(&metadata.file_id).unwrap();
+/// let proof = ledger.get_aggregation_proof(&metadata.file_id).unwrap();
+/// assert!(verify_aggregation_proof(ledger.root(), &proof, rc));
+/// ```
+pub fn verify_aggregation_proof(root: F, proof: &CircuitMerkleProof, rc: F) -> bool {
+    proof.leaf == rc && verify_merkle_proof_in_place(root, proof)
+}
+
+/// Wire format constants for ledger attestations
+mod attestation_format {
+    /// Magic bytes identifying a Kontor ledger attestation
+    pub const MAGIC: &[u8] = b"KLAT";
+
+    /// Current attestation format version. Version 1 attestations did not commit to the
+    /// aggregated depth and are no longer accepted.
+    pub const VERSION: u16 = 2;
+
+    /// Encoded size: magic(4) + version(2) + root(32) + file_count(8) + depth(8) +
+    /// block_height(8) + commitment(32)
+    pub const ENCODED_SIZE: usize = 94;
+}
+
+/// A snapshot of a ledger's root, posted on-chain each epoch.
+///
+/// The root and commitment are stored as canonical 32-byte field representations.
+/// `commitment = H(TAG_LA, H(TAG_LA, H(TAG_LA, root, file_count), aggregated_depth),
+/// block_height)` binds the root to the file count, aggregated depth and height it was
+/// attested at; see [`calculate_ledger_attestation_commitment`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerAttestation {
+    /// Canonical representation of the aggregated ledger root
+    pub root: [u8; 32],
+    /// Number of files in the ledger
+    pub file_count: u64,
+    /// Depth of the aggregated Merkle tree
+    pub aggregated_depth: u64,
+    /// Block height the attestation was produced for
+    pub block_height: u64,
+    /// Commitment over (root, file_count, aggregated_depth, block_height)
+    pub commitment: [u8; 32],
+}
+
+impl LedgerAttestation {
+    /// Creates an attestation for the given root and computes its commitment.
+    pub fn new(root: F, file_count: u64, aggregated_depth: u64, block_height: u64) -> Self {
+        let commitment = calculate_ledger_attestation_commitment(
+            root,
+            file_count,
+            aggregated_depth,
+            block_height,
+        );
+        Self {
+            root: root.to_repr().into(),
+            file_count,
+            aggregated_depth,
+            block_height,
+            commitment: commitment.to_repr().into(),
+        }
+    }
+
+    /// Returns the attested root as a field element, or `None` if the bytes are not canonical.
+    pub fn root_field(&self) -> Option<F> {
+        Option::from(F::from_repr(self.root.into()))
+    }
+
+    /// Checks that the root is canonical and the commitment matches the attested fields.
+    pub fn is_consistent(&self) -> bool {
+        let Some(root) = self.root_field() else {
+            return false;
+        };
+        let expected = calculate_ledger_attestation_commitment(
+            root,
+            self.file_count,
+            self.aggregated_depth,
+            self.block_height,
+        );
+        let expected: [u8; 32] = expected.to_repr().into();
+        expected == self.commitment
+    }
+
+    /// Checks this attestation against a live ledger.
+    ///
+    /// Returns `true` iff the attestation is consistent and its root, file count and
+    /// aggregated depth match the ledger's current state.
+    pub fn verify_against(&self, ledger: &FileLedger) -> bool {
+        self.is_consistent()
+            && self.root_field() == Some(ledger.root())
+            && self.file_count == ledger.files.len() as u64
+            && self.aggregated_depth == ledger.depth() as u64
+    }
+
+    /// Serializes the attestation to its fixed-size canonical encoding.
+    ///
+    /// Layout: magic, version (u16 LE), root, file_count (u64 LE), aggregated_depth (u64 LE),
+    /// block_height (u64 LE), commitment.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(attestation_format::ENCODED_SIZE);
+        bytes.extend_from_slice(attestation_format::MAGIC);
+        bytes.extend_from_slice(&attestation_format::VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.root);
+        bytes.extend_from_slice(&self.file_count.to_le_bytes());
+        bytes.extend_from_slice(&self.aggregated_depth.to_le_bytes());
+        bytes.extend_from_slice(&self.block_height.to_le_bytes());
+        bytes.extend_from_slice(&self.commitment);
+        bytes
+    }
+
+    /// Deserializes an attestation produced by [`Self::to_bytes`].
+    ///
+    /// Rejects malformed encodings, non-canonical roots, and attestations whose
+    /// commitment does not match their fields (e.g. a tampered file count or depth).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KontorPoRError> {
+        if bytes.len() != attestation_format::ENCODED_SIZE {
+            return Err(KontorPoRError::Serialization(format!(
+                "Ledger attestation must be {} bytes, got {}",
+                attestation_format::ENCODED_SIZE,
+                bytes.len()
+            )));
+        }
+        if &bytes[0..4] != attestation_format::MAGIC {
+            return Err(KontorPoRError::Serialization(
+                "Invalid magic bytes in ledger attestation".to_string(),
+            ));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != attestation_format::VERSION {
+            return Err(KontorPoRError::Serialization(format!(
+                "Unsupported ledger attestation version: {}",
+                version
+            )));
+        }
+
+        let array32 = |range: std::ops::Range<usize>| -> [u8; 32] {
+            bytes[range].try_into().expect("range is 32 bytes")
+        };
+        let u64_at = |start: usize| -> u64 {
+            u64::from_le_bytes(
+                bytes[start..start + 8]
+                    .try_into()
+                    .expect("range is 8 bytes"),
+            )
+        };
+
+        let attestation = Self {
+            root: array32(6..38),
+            file_count: u64_at(38),
+            aggregated_depth: u64_at(46),
+            block_height: u64_at(54),
+            commitment: array32(62..94),
+        };
+
+        if !attestation.is_consistent() {
+            return Err(KontorPoRError::LedgerValidation {
+                reason: "attestation commitment does not match its contents".to_string(),
+            });
+        }
+
+        Ok(attestation)
+    }
+
+    /// Returns the canonical encoding as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        self.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Parses an attestation from the hex string produced by [`Self::to_hex`].
+    pub fn from_hex(hex: &str) -> Result<Self, KontorPoRError> {
+        if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+            return Err(KontorPoRError::Serialization(
+                "Ledger attestation hex must be an even-length ASCII string".to_string(),
+            ));
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| {
+                KontorPoRError::Serialization(format!("Invalid ledger attestation hex: {}", e))
+            })?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Validates a proof's `ledger_root` against posted attestations instead of a live ledger.
+///
+/// Returns the first consistent attestation whose root matches the proof and whose
+/// committed aggregated depth is at most the proof's: proofs padded with
+/// [`crate::api::Challenge::with_max_agg_depth`] are synthesized deeper than the ledger,
+/// while a proof shallower than the attested ledger cannot open its root. This replaces
+/// the [`FileLedger::is_valid_root`] check for verifiers that only track on-chain
+/// attestations. File-root-bound proofs carry the file root rather than a ledger root
+/// and are rejected with [`KontorPoRError::InvalidInput`].
+pub fn verify_ledger_root_with_attestations<'a>(
+    proof: &Proof,
+    attestations: &'a [LedgerAttestation],
+) -> Result<&'a LedgerAttestation, KontorPoRError> {
+    if !proof.binding().is_ledger() {
+        return Err(KontorPoRError::InvalidInput(
+            "File-root-bound proofs do not commit to a ledger root".to_string(),
+        ));
+    }
+
+    attestations
+        .iter()
+        .find(|attestation| {
+            attestation.is_consistent()
+                && attestation.root_field() == Some(proof.ledger_root)
+                && attestation.aggregated_depth <= proof.shape.aggregated_tree_depth as u64
+        })
+        .ok_or_else(|| KontorPoRError::InvalidLedgerRoot {
+            proof_root: format!("{:?}", proof.ledger_root),
+            reason: "Proof's ledger_root does not match any attestation".to_string(),
+        })
 }
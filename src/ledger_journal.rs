@@ -0,0 +1,314 @@
+//! Append-only journal for crash-safe ledger persistence.
+//!
+//! [`FileLedger::save`](crate::ledger::FileLedger::save) rewrites the whole ledger, so a
+//! process that dies mid-write leaves a file that no longer loads. A journaled ledger
+//! (opened with [`FileLedger::open_journaled`](crate::ledger::FileLedger::open_journaled))
+//! instead keeps a snapshot written with an atomic rename and, next to it, a journal of
+//! the updates made since. Each update is appended as one frame and synced before the
+//! call returns; reopening loads the snapshot and replays the frames.
+//!
+//! The journal starts with a header (magic, version and the SHA-256 of the snapshot it
+//! extends), followed by frames of a little-endian `u32` payload length, the first
+//! [`journal_format::CHECKSUM_LEN`] bytes of the payload's SHA-256, and the bincode
+//! payload. A crash during an append leaves a torn final frame, which is dropped on
+//! replay; a bad frame followed by more data is corruption and fails the open. A journal
+//! whose header names another snapshot was already folded into the current one by a
+//! compaction that crashed before resetting the journal, and is discarded.
+
+use crate::instrumentation::{debug, warn};
+use crate::ledger::{FileLedgerEntry, LedgerDiff};
+use crate::{KontorPoRError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Constants for the on-disk journal format
+mod journal_format {
+    /// Magic bytes identifying a ledger journal
+    pub const MAGIC: &[u8] = b"KLJN";
+
+    /// Current format version
+    pub const VERSION: u16 = 1;
+
+    /// Magic, version and the SHA-256 of the snapshot the journal extends
+    pub const HEADER_LEN: usize = 4 + 2 + 32;
+
+    /// Length of the truncated SHA-256 checksum over each frame's payload
+    pub const CHECKSUM_LEN: usize = 8;
+
+    /// Payload length and checksum preceding each frame's payload
+    pub const FRAME_HEADER_LEN: usize = 4 + CHECKSUM_LEN;
+}
+
+/// A ledger update recorded in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum JournalOp {
+    /// [`FileLedger::add_file`](crate::ledger::FileLedger::add_file)
+    AddFile {
+        file_id: String,
+        entry: FileLedgerEntry,
+    },
+    /// [`FileLedger::add_files`](crate::ledger::FileLedger::add_files)
+    AddFiles(Vec<(String, FileLedgerEntry)>),
+    /// [`FileLedger::remove_file`](crate::ledger::FileLedger::remove_file)
+    RemoveFile(String),
+    /// [`FileLedger::apply_diff`](crate::ledger::FileLedger::apply_diff)
+    ApplyDiff(LedgerDiff),
+    /// [`FileLedger::rename_file`](crate::ledger::FileLedger::rename_file)
+    RenameFile { file_id: String, new_name: String },
+    /// [`FileLedger::set_historical_roots`](crate::ledger::FileLedger::set_historical_roots),
+    /// also recording the outcome of clearing or pruning them
+    SetHistoricalRoots(Vec<[u8; 32]>),
+    /// [`FileLedger::record_current_root`](crate::ledger::FileLedger::record_current_root)
+    RecordCurrentRoot,
+    /// [`FileLedger::record_root_at_height`](crate::ledger::FileLedger::record_root_at_height)
+    RecordRootAtHeight(u64),
+    /// [`FileLedger::migrate_from`](crate::ledger::FileLedger::migrate_from): the files
+    /// not yet in the ledger and the roots to accept as migrated roots
+    Migrate {
+        entries: Vec<(String, FileLedgerEntry)>,
+        roots: Vec<[u8; 32]>,
+    },
+    /// [`FileLedger::prune_migrated_roots`](crate::ledger::FileLedger::prune_migrated_roots)
+    ClearMigratedRoots,
+}
+
+/// Path of the journal kept alongside the snapshot at `snapshot_path`: the snapshot's
+/// file name with `.journal` appended.
+pub(crate) fn journal_path(snapshot_path: &Path) -> PathBuf {
+    let mut name = snapshot_path.as_os_str().to_owned();
+    name.push(".journal");
+    PathBuf::from(name)
+}
+
+/// SHA-256 of a serialized snapshot, as recorded in the journal header.
+pub(crate) fn snapshot_digest(encoded: &[u8]) -> [u8; 32] {
+    Sha256::digest(encoded).into()
+}
+
+/// Write `bytes` to a temporary file, sync it and atomically rename it to `path`.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let io_err = |e: std::io::Error| {
+        KontorPoRError::IO(format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(format!(".tmp.{}", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_name);
+    let mut file = File::create(&tmp_path).map_err(io_err)?;
+    file.write_all(bytes).map_err(io_err)?;
+    file.sync_all().map_err(io_err)?;
+    drop(file);
+    fs::rename(&tmp_path, path).map_err(io_err)?;
+
+    // Make the rename itself durable where directories can be synced
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// The open journal of a journaled ledger.
+#[derive(Debug)]
+pub(crate) struct Journal {
+    snapshot_path: PathBuf,
+    path: PathBuf,
+    file: File,
+    /// Length of the journal's complete frames, where the next frame is appended
+    len: u64,
+}
+
+/// The journal attached to a ledger, if any.
+///
+/// Clones of a ledger are in-memory copies: cloning the handle detaches it, so only the
+/// ledger that opened the journal appends to it.
+#[derive(Debug, Default)]
+pub(crate) struct JournalHandle(pub(crate) Option<Journal>);
+
+impl Clone for JournalHandle {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl Journal {
+    /// Opens the journal of the snapshot at `snapshot_path`, whose serialized bytes hash to
+    /// `digest`, and returns it with the operations to replay on top of the snapshot.
+    ///
+    /// A missing, stale or headerless journal is replaced by an empty one, and a torn
+    /// final frame is truncated away.
+    pub(crate) fn open(snapshot_path: &Path, digest: [u8; 32]) -> Result<(Self, Vec<JournalOp>)> {
+        use journal_format::*;
+
+        let path = journal_path(snapshot_path);
+        let encoded = match fs::read(&path) {
+            Ok(encoded) => encoded,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(KontorPoRError::IO(format!(
+                    "Failed to read ledger journal {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let corrupt = |reason: String| KontorPoRError::LedgerValidation {
+            reason: format!("ledger journal {}: {}", path.display(), reason),
+        };
+
+        if encoded.len() < HEADER_LEN {
+            // Never written, or torn while being created: nothing to replay
+            return Ok((Self::create(snapshot_path, digest)?, Vec::new()));
+        }
+        if &encoded[..4] != MAGIC {
+            return Err(corrupt("bad magic bytes".to_string()));
+        }
+        let version = u16::from_le_bytes([encoded[4], encoded[5]]);
+        if version != VERSION {
+            return Err(corrupt(format!(
+                "unsupported version {} (expected {})",
+                version, VERSION
+            )));
+        }
+        if encoded[6..HEADER_LEN] != digest {
+            debug!(
+                "Discarding ledger journal {} written for an earlier snapshot",
+                path.display()
+            );
+            return Ok((Self::create(snapshot_path, digest)?, Vec::new()));
+        }
+
+        let mut ops = Vec::new();
+        let mut offset = HEADER_LEN;
+        while offset < encoded.len() {
+            let rest = &encoded[offset..];
+            if rest.len() < FRAME_HEADER_LEN {
+                break;
+            }
+            let payload_len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+            let frame_len = FRAME_HEADER_LEN.saturating_add(payload_len);
+            if frame_len > rest.len() {
+                break;
+            }
+            let payload = &rest[FRAME_HEADER_LEN..frame_len];
+            if rest[4..FRAME_HEADER_LEN] != checksum(payload) {
+                if frame_len == rest.len() {
+                    break;
+                }
+                return Err(corrupt(format!(
+                    "bad checksum in frame at offset {}",
+                    offset
+                )));
+            }
+            let op = bincode::deserialize(payload)
+                .map_err(|e| corrupt(format!("undecodable frame at offset {}: {}", offset, e)))?;
+            ops.push(op);
+            offset += frame_len;
+        }
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|e| corrupt(format!("cannot open for appending: {}", e)))?;
+        if offset < encoded.len() {
+            warn!(
+                "Dropping torn final frame of ledger journal {} ({} bytes)",
+                path.display(),
+                encoded.len() - offset
+            );
+            file.set_len(offset as u64)
+                .and_then(|()| file.sync_all())
+                .map_err(|e| corrupt(format!("cannot truncate torn frame: {}", e)))?;
+        }
+
+        let journal = Self {
+            snapshot_path: snapshot_path.to_path_buf(),
+            path,
+            file,
+            len: offset as u64,
+        };
+        Ok((journal, ops))
+    }
+
+    /// Replaces the journal with an empty one extending the snapshot hashing to `digest`.
+    pub(crate) fn create(snapshot_path: &Path, digest: [u8; 32]) -> Result<Self> {
+        use journal_format::*;
+
+        let path = journal_path(snapshot_path);
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&digest);
+        write_atomic(&path, &header)?;
+
+        let file = OpenOptions::new().append(true).open(&path).map_err(|e| {
+            KontorPoRError::IO(format!(
+                "Failed to open ledger journal {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Self {
+            snapshot_path: snapshot_path.to_path_buf(),
+            path,
+            file,
+            len: HEADER_LEN as u64,
+        })
+    }
+
+    /// Path of the snapshot this journal extends.
+    pub(crate) fn snapshot_path(&self) -> &Path {
+        &self.snapshot_path
+    }
+
+    /// Appends `op` as one frame and syncs it to disk.
+    ///
+    /// On failure the journal is truncated back to its last complete frame, so a failed
+    /// append never leaves a partial frame ahead of later ones.
+    pub(crate) fn append(&mut self, op: &JournalOp) -> Result<()> {
+        let payload = bincode::serialize(op).map_err(|e| {
+            KontorPoRError::Serialization(format!("Failed to serialize journal frame: {}", e))
+        })?;
+        let payload_len = u32::try_from(payload.len())
+            .ok()
+            .filter(|&len| len as usize <= crate::config::MAX_LEDGER_SIZE_BYTES)
+            .ok_or_else(|| {
+                KontorPoRError::InvalidInput(format!(
+                    "Journal frame of {} bytes exceeds maximum {} bytes",
+                    payload.len(),
+                    crate::config::MAX_LEDGER_SIZE_BYTES
+                ))
+            })?;
+
+        let mut frame = Vec::with_capacity(journal_format::FRAME_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&payload_len.to_le_bytes());
+        frame.extend_from_slice(&checksum(&payload));
+        frame.extend_from_slice(&payload);
+
+        let written = self
+            .file
+            .write_all(&frame)
+            .and_then(|()| self.file.sync_data());
+        if let Err(e) = written {
+            let _ = self.file.set_len(self.len);
+            return Err(KontorPoRError::IO(format!(
+                "Failed to append to ledger journal {}: {}",
+                self.path.display(),
+                e
+            )));
+        }
+        self.len += frame.len() as u64;
+        Ok(())
+    }
+}
+
+/// Truncated SHA-256 of a frame payload.
+fn checksum(payload: &[u8]) -> [u8; journal_format::CHECKSUM_LEN] {
+    Sha256::digest(payload)[..journal_format::CHECKSUM_LEN]
+        .try_into()
+        .unwrap()
+}
@@ -74,27 +74,76 @@
 //! # Ok::<(), KontorPoRError>(())
 //! ```
 
+// Without `instrumentation` the logging macros discard their arguments, leaving values
+// that exist only to be logged unused. The default build still lints them.
+#![cfg_attr(
+    not(feature = "instrumentation"),
+    allow(unused_variables, unused_imports, dead_code)
+)]
+
+/// Evaluate a named failure injection point (see [`failpoints`]). Compiles to nothing
+/// without the `failpoints` feature.
+macro_rules! fail_point {
+    ($name:expr) => {
+        #[cfg(feature = "failpoints")]
+        $crate::failpoints::eval($name)?;
+    };
+}
+
+/// Record public values in the transcript being captured on this thread (see
+/// `api::Transcript`). Compiles to nothing without the `transcript` feature.
+macro_rules! transcript_record {
+    (|$transcript:ident| $body:expr) => {
+        #[cfg(feature = "transcript")]
+        $crate::api::transcript::with_active(|$transcript| $body);
+    };
+}
+
 pub mod api;
 pub mod circuit;
 pub mod circuit_safety;
 pub mod config;
+#[cfg(feature = "prover")]
 pub mod erasure;
 pub mod error;
+#[cfg(feature = "failpoints")]
+pub mod failpoints;
+#[cfg(feature = "test-vectors")]
+pub mod fixtures;
+mod instrumentation;
 pub mod ledger;
+mod ledger_journal;
 pub mod merkle;
 pub mod metrics;
 pub mod params;
 pub mod poseidon;
+#[cfg(feature = "prover")]
+pub mod reporting;
 pub mod utils;
 
 // Re-export commonly used types and functions for convenience
-pub use api::{prepare_file, reconstruct_file, tree_depth_from_metadata, PorSystem};
-pub use api::{Challenge, FieldElement, FileMetadata, PorParams, PreparedFile, Proof};
-pub use circuit::{CircuitWitness, FileProofWitness, PorCircuit};
-pub use error::{KontorPoRError, Result};
-pub use ledger::{FileDescriptor, FileLedger};
+#[cfg(feature = "prover")]
+pub use api::{
+    assemble_raw, prepare_file, prepare_file_matching, prepare_file_raw, prepare_file_salted,
+    prepare_file_with_arity, prepare_file_with_erasure, prepare_file_with_profile,
+    rebuild_prepared, reconstruct_file,
+};
+pub use api::{compute_file_id, tree_depth_from_metadata, PorSystem};
+pub use api::{
+    Challenge, ChallengeKind, ChallengeMode, ErasureCode, ErasureField, FieldElement, FileMetadata,
+    PorParams, PreparedFile, Proof, ProofBinding, SeedSchedule,
+};
+pub use circuit::{CircuitMode, CircuitWitness, FileProofWitness, PorCircuit};
+pub use error::{KontorPoRError, Result, WitnessError};
+pub use ledger::{
+    compute_rc, compute_rc_with, verify_aggregation_proof, verify_ledger_root_with_attestations,
+    verify_membership_certificate, AggregatedTreeExport, ConsistencyIssue, ConsistencyReport,
+    FileDescriptor, FileLedger, IndexPolicy, LedgerAttestation, LedgerDescription, LedgerDiff,
+    LedgerFileDescription, MembershipCertificate, MigratedFile, MigrationReceipt, RotationCycle,
+};
 pub use merkle::{
-    build_tree, build_tree_from_leaves, get_leaf_hash, get_padded_proof_for_leaf, hash_leaf_data,
-    hash_node, verify_merkle_proof_in_place, CircuitMerkleProof, MerkleTree,
+    build_tree, build_tree_from_leaves, build_tree_streaming, get_leaf_hash,
+    get_padded_proof_for_leaf, hash_leaf_data, hash_node, verify_merkle_proof_in_place,
+    CircuitMerkleProof, LayerRetention, MerkleTree, TreeArity,
 };
 pub use utils::{derive_index_from_bits, leaf_to_bytes31};
@@ -7,15 +7,44 @@
 //! - Bitcoin transaction fee economics
 //!
 //! Run with: cargo run --release
+//!
+//! Parameters for a range of circuit shapes can be generated ahead of time with:
+//! `cargo run --release -- params pregen --files 1,2,4 --depth 10..=20 --agg 0..=6 --cache-dir <DIR>`
+//! and old ones removed with:
+//! `cargo run --release -- params gc --older-than 30d --cache-dir <DIR> --dry-run`
+//!
+//! Phase durations, proof size and peak RSS can be written as JSON for regression tracking:
+//! `cargo run --release -- bench --files 2 --num-challenges 10 --json bench.json`
+//!
+//! Erasure-coding fault tolerance can be explored with:
+//! `cargo run --release -- simulate --file-size 100000 --loss random:0.1 --trials 100`
+//!
+//! The number of challenges needed to detect a data loss can be computed with:
+//! `cargo run --release -- challenges suggest --loss 0.01 --confidence 0.999`
+//!
+//! A challenge for a file's metadata can be minted with:
+//! `cargo run --release -- challenge new --meta meta.json --block-height 1000 --block-hash <HEX> --num 5 --prover node_1 --out challenge.json`
+//!
+//! A saved ledger can be inspected with:
+//! `cargo run --release -- ledger inspect ledger.bin --verify-consistency`
+//!
+//! Cross-implementation test vectors can be dumped with:
+//! `cargo run --release --features test-vectors -- fixtures --file-sizes 100,1000 --out fixture.json`
 
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
 use kontor_crypto::{
-    api::{self, Challenge, FieldElement, PorSystem},
+    api::{
+        self, sampling, Challenge, ChallengeMode, ErasureCode, FieldElement, FileMetadata,
+        PorSystem,
+    },
     config,
-    metrics::{EconomicMetrics, FileSizeCategory, ProofMetrics, VerificationMetrics},
-    FileLedger,
+    erasure::{self, LossModel},
+    metrics::{EconomicMetrics, FileSizeCategory, MemoryReport, ProofMetrics, VerificationMetrics},
+    params::{self, GcPolicy, PregenStatus, Shape},
+    reporting, FileLedger, IndexPolicy, KontorPoRError,
 };
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use tracing::{error, info, info_span};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
@@ -24,6 +53,9 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilte
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Total number of files in the network ledger
     #[arg(long, default_value_t = 100)]
     total_files_in_ledger: usize,
@@ -48,11 +80,424 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     profile_memory: bool,
 
+    /// Seed for generated file contents and challenge seeds
+    #[arg(long, default_value_t = config::TEST_RANDOM_SEED)]
+    seed: u64,
+
     /// Increase output verbosity (-v for DEBUG, -vv for TRACE)
-    #[arg(short, long, action = ArgAction::Count)]
+    #[arg(short, long, action = ArgAction::Count, global = true)]
     verbose: u8,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage cached circuit parameters
+    Params {
+        #[command(subcommand)]
+        action: ParamsCommand,
+    },
+    /// Inspect a saved file ledger
+    Ledger {
+        #[command(subcommand)]
+        action: LedgerCommand,
+    },
+    /// Create challenges for provers
+    Challenge {
+        #[command(subcommand)]
+        action: ChallengeCommand,
+    },
+    /// Size challenges for detecting data loss
+    Challenges {
+        #[command(subcommand)]
+        action: ChallengesCommand,
+    },
+    /// Simulate symbol loss and check erasure-coding recoverability
+    Simulate(SimulateArgs),
+    /// Run one proving epoch and report phase durations, proof size and peak RSS
+    Bench(BenchArgs),
+    /// Dump a deterministic test vector (files, ledger, challenges, indices, z0/zn, proof) as JSON
+    #[cfg(feature = "test-vectors")]
+    Fixtures(FixturesArgs),
+}
+
+/// Arguments for `fixtures`.
+#[cfg(feature = "test-vectors")]
+#[derive(Args, Debug)]
+struct FixturesArgs {
+    /// Comma-separated sizes in bytes of the generated files
+    #[arg(long, value_delimiter = ',', default_value = "100,1000")]
+    file_sizes: Vec<usize>,
+
+    /// Challenged sectors (recursive steps) per file
+    #[arg(long, default_value_t = 3)]
+    num_challenges: usize,
+
+    /// Seed for file contents and challenges
+    #[arg(long, default_value_t = config::TEST_RANDOM_SEED)]
+    seed: u64,
+
+    /// Block height recorded in the challenges
+    #[arg(long, default_value_t = 1000)]
+    block_height: u64,
+
+    /// Write the JSON here instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum LedgerCommand {
+    /// Print the root, depth, files and historical roots of a saved ledger
+    Inspect(InspectArgs),
+}
+
+/// Output format for `ledger inspect`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Arguments for `ledger inspect`.
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// Ledger file written by `FileLedger::save`
+    path: PathBuf,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Only show the file with this ID
+    #[arg(long = "file")]
+    file_id: Option<String>,
+
+    /// Recompute root commitments and the aggregated tree and compare to the stored root
+    #[arg(long, default_value_t = false)]
+    verify_consistency: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum ChallengeCommand {
+    /// Write a challenge as canonical JSON (see `Challenge::to_json`)
+    New(NewChallengeArgs),
+}
+
+/// Arguments for `challenge new`.
+#[derive(Args, Debug)]
+#[command(group(clap::ArgGroup::new("seed_source").required(true).args(["block_hash", "seed"])))]
+struct NewChallengeArgs {
+    /// File metadata JSON written by `FileMetadata::to_json`
+    #[arg(long)]
+    meta: PathBuf,
+
+    /// Block height the challenge is issued at
+    #[arg(long)]
+    block_height: u64,
+
+    /// Block hash (64 hex characters, optionally 0x-prefixed) the seed is derived from
+    #[arg(long, value_parser = parse_block_hash)]
+    block_hash: Option<[u8; 32]>,
+
+    /// Use this seed instead of deriving one from --block-hash
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Challenged symbols (recursive steps)
+    #[arg(long, default_value_t = config::S_CHAL)]
+    num: usize,
+
+    /// ID of the prover that must answer the challenge
+    #[arg(long)]
+    prover: String,
+
+    /// Print the leaf index opened at each step when the challenge is proven on its own
+    #[arg(long, default_value_t = false, requires = "data")]
+    preview: bool,
+
+    /// Original file, prepared again to compute --preview
+    #[arg(long)]
+    data: Option<PathBuf>,
+
+    /// Write the JSON here instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Parse a 32-byte block hash from hex, with or without a `0x` prefix.
+fn parse_block_hash(input: &str) -> Result<[u8; 32], String> {
+    let hex = input.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(format!(
+            "block hash must be 64 hex characters, got '{}'",
+            input
+        ));
+    }
+    let mut hash = [0u8; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+            .map_err(|e| format!("invalid block hash '{}': {}", input, e))?;
+    }
+    Ok(hash)
+}
+
+#[derive(Subcommand, Debug)]
+enum ChallengesCommand {
+    /// Print how many challenges detect a data loss with a given confidence
+    Suggest(SuggestArgs),
+}
+
+/// Arguments for `challenges suggest`.
+#[derive(Args, Debug)]
+struct SuggestArgs {
+    /// Fraction of the file's data lost
+    #[arg(long, value_parser = parse_fraction, default_value_t = sampling::DEFAULT_LOSS_FRACTION)]
+    loss: f64,
+
+    /// Required probability of detecting the loss
+    #[arg(long, value_parser = parse_fraction, default_value_t = sampling::DEFAULT_CONFIDENCE)]
+    confidence: f64,
+
+    /// Size for files prepared without erasure coding
+    #[arg(long, default_value_t = false)]
+    raw: bool,
+}
+
+/// Parse a fraction in [0, 1].
+fn parse_fraction(input: &str) -> Result<f64, String> {
+    let fraction: f64 = input
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid fraction '{}': {}", input, e))?;
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(format!("fraction must be in [0, 1], got {}", fraction));
+    }
+    Ok(fraction)
+}
+
+/// Arguments for `simulate`.
+#[derive(Args, Debug)]
+struct SimulateArgs {
+    /// Size of the random test file in bytes
+    #[arg(long, default_value_t = 100_000)]
+    file_size: usize,
+
+    /// Loss model: "random:<fraction>", "burst:<start>:<len>" or "per-codeword:<max_lost>"
+    #[arg(long, value_parser = parse_loss_model, default_value = "random:0.1")]
+    loss: LossModel,
+
+    /// Number of trials (each uses a different loss seed)
+    #[arg(long, default_value_t = 100)]
+    trials: u64,
+
+    /// Base seed for file contents and loss patterns
+    #[arg(long, default_value_t = config::TEST_RANDOM_SEED)]
+    seed: u64,
+}
+
+/// Arguments for `bench`.
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Number of challenged files
+    #[arg(long, default_value_t = 5)]
+    files: usize,
+
+    /// Size of each file in bytes
+    #[arg(long, default_value_t = 10_000)]
+    file_size: usize,
+
+    /// Files in the ledger, including the challenged ones (defaults to --files)
+    #[arg(long)]
+    ledger_files: Option<usize>,
+
+    /// Challenged symbols (recursive steps) per file
+    #[arg(long, default_value_t = config::S_CHAL)]
+    num_challenges: usize,
+
+    /// Seed for file contents and challenges
+    #[arg(long, default_value_t = config::TEST_RANDOM_SEED)]
+    seed: u64,
+
+    /// Write the report as JSON (see `kontor_crypto::reporting`) to this path
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+/// Parse a loss model specification such as "random:0.1", "burst:100:30" or "per-codeword:24".
+fn parse_loss_model(input: &str) -> Result<LossModel, String> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    let number = |s: &str| {
+        s.parse::<usize>()
+            .map_err(|e| format!("invalid number '{}': {}", s, e))
+    };
+    match parts.as_slice() {
+        ["random", fraction] => Ok(LossModel::Random(parse_fraction(fraction)?)),
+        ["burst", start, len] => Ok(LossModel::Burst {
+            start: number(start)?,
+            len: number(len)?,
+        }),
+        ["per-codeword", max_lost] => Ok(LossModel::PerCodeword(number(max_lost)?)),
+        _ => Err(format!(
+            "unknown loss model '{}' (expected random:<f>, burst:<start>:<len> or per-codeword:<n>)",
+            input
+        )),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum ParamsCommand {
+    /// Generate and cache parameters for every combination of the given shapes
+    Pregen(PregenArgs),
+    /// Delete cached parameter files selected by one policy
+    Gc(GcArgs),
+}
+
+/// Arguments for `params pregen`. Lists accept values and ranges, e.g. "1,2,4", "10..=20", "0..6".
+#[derive(Args, Debug)]
+struct PregenArgs {
+    /// File slots per step (powers of two)
+    #[arg(long, value_parser = parse_usize_list, default_value = "1")]
+    files: UsizeList,
+
+    /// File tree depths
+    #[arg(long, value_parser = parse_usize_list)]
+    depth: UsizeList,
+
+    /// Aggregated tree depths
+    #[arg(long, value_parser = parse_usize_list, default_value = "0")]
+    agg: UsizeList,
+
+    /// Generate private-mode (blinded leaf) parameters instead of public-mode
+    #[arg(long, default_value_t = false)]
+    private: bool,
+
+    /// Generate shapes concurrently (uses much more memory)
+    #[arg(long, default_value_t = false)]
+    parallel: bool,
+
+    /// Cache directory (defaults to $KONTOR_PARAMS_DIR)
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+}
+
+/// Arguments for `params gc`.
+#[derive(Args, Debug)]
+struct GcArgs {
+    #[command(flatten)]
+    policy: GcPolicyArgs,
+
+    /// File slots per step of the shapes to keep
+    #[arg(long, value_parser = parse_usize_list, default_value = "1", requires = "keep_depth")]
+    keep_files: UsizeList,
+
+    /// Aggregated tree depths of the shapes to keep
+    #[arg(long, value_parser = parse_usize_list, default_value = "0", requires = "keep_depth")]
+    keep_agg: UsizeList,
+
+    /// Keep private-mode shapes instead of public-mode ones
+    #[arg(long, default_value_t = false, requires = "keep_depth")]
+    private: bool,
+
+    /// List what would be deleted without deleting anything
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Cache directory (defaults to $KONTOR_PARAMS_DIR)
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+}
+
+/// The `params gc` policy: exactly one of these is required.
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+struct GcPolicyArgs {
+    /// Delete files not accessed within this long, e.g. "30d", "12h", "90m"
+    #[arg(long, value_parser = parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Delete least recently used files until the cache fits, e.g. "500M", "20G"
+    #[arg(long, value_parser = parse_byte_size)]
+    max_size: Option<u64>,
+
+    /// Keep only shapes with these file tree depths (with --keep-files and --keep-agg)
+    #[arg(long, value_parser = parse_usize_list)]
+    keep_depth: Option<UsizeList>,
+}
+
+/// Parse a duration such as "30d", "12h", "90m" or "45s".
+fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let seconds_per_unit = match input.chars().last() {
+        Some('d') => 24 * 60 * 60,
+        Some('h') => 60 * 60,
+        Some('m') => 60,
+        Some('s') => 1,
+        _ => return Err(format!("'{}' needs a d, h, m or s suffix", input)),
+    };
+    let number = &input[..input.len() - 1];
+    let number: u64 = number
+        .parse()
+        .map_err(|e| format!("invalid number '{}': {}", number, e))?;
+    Ok(Duration::from_secs(number * seconds_per_unit))
+}
+
+/// Parse a byte size such as "1048576", "500K", "500M" or "20G" (binary units).
+fn parse_byte_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let (number, shift) = match input.chars().last() {
+        Some('K' | 'k') => (&input[..input.len() - 1], 10),
+        Some('M' | 'm') => (&input[..input.len() - 1], 20),
+        Some('G' | 'g') => (&input[..input.len() - 1], 30),
+        _ => (input, 0),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|e| format!("invalid size '{}': {}", input, e))?;
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| format!("size '{}' is too large", input))
+}
+
+/// A sorted, de-duplicated list of values parsed from a comma-separated range expression.
+#[derive(Debug, Clone)]
+struct UsizeList(Vec<usize>);
+
+/// Parse "1,2,4", "10..=20", "0..6" (or any comma-separated mix) into a list of values.
+fn parse_usize_list(input: &str) -> Result<UsizeList, String> {
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .map_err(|e| format!("invalid number '{}': {}", s.trim(), e))
+    };
+
+    let mut values = Vec::new();
+    for part in input.split(',').filter(|p| !p.trim().is_empty()) {
+        if let Some((start, end)) = part.split_once("..=") {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                return Err(format!("empty range '{}'", part.trim()));
+            }
+            values.extend(start..=end);
+        } else if let Some((start, end)) = part.split_once("..") {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start >= end {
+                return Err(format!("empty range '{}'", part.trim()));
+            }
+            values.extend(start..end);
+        } else {
+            values.push(parse(part)?);
+        }
+    }
+
+    if values.is_empty() {
+        return Err("expected at least one value".to_string());
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(UsizeList(values))
+}
+
 /// Information about a file stored by this node
 struct StoredFile {
     prepared: api::PreparedFile,
@@ -67,6 +512,55 @@ fn main() {
     // Initialize tracing
     init_tracing(cli.verbose);
 
+    if let Some(Command::Params {
+        action: ParamsCommand::Pregen(args),
+    }) = &cli.command
+    {
+        run_params_pregen(args);
+        return;
+    }
+    if let Some(Command::Params {
+        action: ParamsCommand::Gc(args),
+    }) = &cli.command
+    {
+        run_params_gc(args);
+        return;
+    }
+    if let Some(Command::Ledger {
+        action: LedgerCommand::Inspect(args),
+    }) = &cli.command
+    {
+        run_ledger_inspect(args);
+        return;
+    }
+    if let Some(Command::Challenge {
+        action: ChallengeCommand::New(args),
+    }) = &cli.command
+    {
+        run_challenge_new(args);
+        return;
+    }
+    if let Some(Command::Challenges {
+        action: ChallengesCommand::Suggest(args),
+    }) = &cli.command
+    {
+        run_challenges_suggest(args);
+        return;
+    }
+    if let Some(Command::Simulate(args)) = &cli.command {
+        run_loss_simulation(args);
+        return;
+    }
+    if let Some(Command::Bench(args)) = &cli.command {
+        run_bench(args);
+        return;
+    }
+    #[cfg(feature = "test-vectors")]
+    if let Some(Command::Fixtures(args)) = &cli.command {
+        run_fixtures(args);
+        return;
+    }
+
     // Validate inputs
     if cli.files_stored_by_node > cli.total_files_in_ledger {
         error!("Error: --files-stored-by-node cannot exceed --total-files-in-ledger");
@@ -91,12 +585,13 @@ fn main() {
         cli.total_files_in_ledger,
         cli.files_stored_by_node,
         &cli.file_size_distribution,
+        cli.seed,
     );
     info!("");
 
     // Phase 2: Challenge Simulation
     info!("[2/4] Challenge Simulation");
-    let challenges = simulate_challenges(&node_files, cli.challenges_to_simulate);
+    let challenges = simulate_challenges(&node_files, cli.challenges_to_simulate, cli.seed);
     display_challenge_info(&challenges);
     info!("");
 
@@ -108,6 +603,12 @@ fn main() {
         generate_proof(&node_files, &challenges, &ledger, cli.profile_memory);
 
     info!("{}", proof_metrics.format_table());
+    let mut memory = MemoryReport::default();
+    for file in &node_files {
+        memory.add_prepared_file(&file.prepared);
+    }
+    memory.add_proof(&proof).add_ledger(&ledger);
+    info!("{}", memory.format_table());
     info!("");
     info!(
         "  ✓ Generated aggregated proof: {:.1} KB",
@@ -182,6 +683,7 @@ fn setup_network(
     total_files: usize,
     files_stored: usize,
     distribution: &str,
+    seed: u64,
 ) -> (FileLedger, Vec<StoredFile>) {
     let _span = info_span!("network_setup").entered();
 
@@ -221,7 +723,7 @@ fn setup_network(
     // Create files that this node stores (subset of network)
     let mut node_files = Vec::new();
     let mut category_counts = [0usize; 4]; // Small, Medium, Large, XLarge
-    let mut rng = StdRng::seed_from_u64(config::TEST_RANDOM_SEED);
+    let mut rng = StdRng::seed_from_u64(seed);
 
     for (i, &category) in categories.iter().enumerate().take(files_stored) {
         let size = category.sample_size(i as u64);
@@ -329,7 +831,11 @@ fn setup_network(
 }
 
 /// Simulate challenges arriving over time with staggered block heights
-fn simulate_challenges(node_files: &[StoredFile], num_challenges: usize) -> Vec<Challenge> {
+fn simulate_challenges(
+    node_files: &[StoredFile],
+    num_challenges: usize,
+    seed: u64,
+) -> Vec<Challenge> {
     let _span = info_span!("challenge_simulation").entered();
 
     // Use protocol challenge frequency for realistic spacing
@@ -339,7 +845,7 @@ fn simulate_challenges(node_files: &[StoredFile], num_challenges: usize) -> Vec<
     let base_block = 1000u64;
 
     let mut challenges = Vec::new();
-    let mut rng = StdRng::seed_from_u64(config::TEST_RANDOM_SEED);
+    let mut rng = StdRng::seed_from_u64(seed);
 
     for (i, file) in node_files.iter().enumerate().take(num_challenges) {
         let block_height = base_block + (i as u64 * spacing);
@@ -424,25 +930,11 @@ fn generate_proof(
         kontor_crypto::metrics::reset_peak_memory();
     }
 
-    let max_file_depth = challenges
-        .iter()
-        .map(|c| api::tree_depth_from_metadata(&c.file_metadata))
-        .max()
-        .unwrap_or(1);
-    let (files_per_step, file_tree_depth) = config::derive_shape(challenges.len(), max_file_depth);
-    let aggregated_tree_depth = if files_per_step > 1 {
-        ledger.tree.layers.len() - 1
-    } else {
-        0
-    };
+    // The shape `prove` plans for these challenges, so its parameters are warm below
+    let shape = config::Shape::for_challenges(challenges, ledger).unwrap();
 
     let cache_size_before = kontor_crypto::params::memory_cache_size();
-    let _params = kontor_crypto::params::load_or_generate_params(
-        files_per_step,
-        file_tree_depth,
-        aggregated_tree_depth,
-    )
-    .unwrap();
+    let _params = kontor_crypto::params::load_or_generate_params(shape).unwrap();
     let cache_size_after = kontor_crypto::params::memory_cache_size();
     let param_cache_hit = cache_size_after == cache_size_before;
 
@@ -461,7 +953,7 @@ fn generate_proof(
     let proving_start = Instant::now();
     let system = PorSystem::new(ledger);
     let files_vec: Vec<&_> = node_files.iter().map(|f| &f.prepared).collect();
-    let proof = system.prove(files_vec, challenges).unwrap();
+    let (proof, timings) = system.prove_with_timings(files_vec, challenges).unwrap();
     let proving_duration = proving_start.elapsed();
 
     let proving_memory_mb = if profile_memory {
@@ -487,17 +979,17 @@ fn generate_proof(
         ProofMetrics {
             total_duration,
             param_gen_duration: param_duration,
-            witness_gen_duration: Duration::from_secs(0), // Not separately measured
+            witness_gen_duration: timings.witness_gen_duration,
             proving_duration,
-            compression_duration: Duration::from_secs(0), // Not separately measured
+            compression_duration: timings.compression_duration,
             proof_size_bytes: proof_bytes,
             num_files: challenges.len(),
             num_challenges_per_file: challenges.first().map(|c| c.num_challenges).unwrap_or(0),
             total_steps: challenges.first().map(|c| c.num_challenges).unwrap_or(0),
-            aggregated_tree_depth,
-            max_file_tree_depth: file_tree_depth,
+            aggregated_tree_depth: shape.aggregated_tree_depth,
+            max_file_tree_depth: shape.file_tree_depth,
             memory_peak_mb: total_memory_mb,
-            files_per_step,
+            files_per_step: shape.files_per_step,
             param_cache_hit,
             param_gen_memory_mb: param_memory_mb,
             proving_memory_mb,
@@ -561,6 +1053,507 @@ fn display_economic_analysis(metrics: &ProofMetrics) {
     info!("");
 }
 
+/// Run a benchmark epoch, print its phase durations and optionally write the JSON report
+fn run_bench(args: &BenchArgs) {
+    let bench_config = reporting::BenchConfig {
+        num_files: args.files,
+        file_size_bytes: args.file_size,
+        ledger_files: args.ledger_files.unwrap_or(args.files),
+        num_challenges: args.num_challenges,
+        seed: args.seed,
+    };
+    let mut report = match reporting::run_bench(&bench_config) {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Error: benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    report.git_revision = git_revision();
+
+    let phases = &report.phases;
+    info!(
+        "Benchmark: {} files x {} bytes, {} challenges each",
+        bench_config.num_files, bench_config.file_size_bytes, bench_config.num_challenges
+    );
+    for (name, us) in [
+        ("prepare", phases.prepare_us),
+        ("plan", phases.plan_us),
+        ("param load", phases.param_load_us),
+        ("witness gen", phases.witness_gen_us),
+        ("folding", phases.folding_us),
+        ("compression", phases.compression_us),
+        ("verify", phases.verify_us),
+    ] {
+        info!("  {:<12} {:>10.1} ms", name, us as f64 / 1000.0);
+    }
+    info!("  proof size   {:>10} bytes", report.proof_size_bytes);
+    match report.peak_rss_bytes {
+        Some(bytes) => info!(
+            "  peak RSS     {:>10.1} MB",
+            bytes as f64 / (1024.0 * 1024.0)
+        ),
+        None => info!("  peak RSS     unavailable"),
+    }
+
+    if let Some(path) = &args.json {
+        let written = report.to_json().and_then(|json| {
+            std::fs::write(path, json).map_err(|e| KontorPoRError::IO(e.to_string()))
+        });
+        if let Err(e) = written {
+            error!("Error: cannot write {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+        info!("  ✓ Report written to {}", path.display());
+    }
+}
+
+/// Short commit hash of the working directory's git checkout, if there is one
+fn git_revision() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let revision = String::from_utf8(output.stdout).ok()?;
+    Some(revision.trim().to_string()).filter(|r| !r.is_empty())
+}
+
+/// Pregenerate parameters for the requested shape matrix and print a per-shape report
+fn run_params_pregen(args: &PregenArgs) {
+    if let Some(dir) = &args.cache_dir {
+        params::set_cache_dir(Some(dir.clone()));
+    }
+    let Some(cache_dir) = params::cache_dir() else {
+        error!(
+            "Error: params pregen requires --cache-dir or {}",
+            params::CACHE_DIR_ENV
+        );
+        std::process::exit(1);
+    };
+
+    let mode = if args.private {
+        ChallengeMode::Private
+    } else {
+        ChallengeMode::Public
+    };
+    let shapes = Shape::matrix(&args.files.0, &args.depth.0, &args.agg.0, mode);
+
+    info!(
+        "Pregenerating {} shape(s) ({:?} mode) into {}",
+        shapes.len(),
+        mode,
+        cache_dir.display()
+    );
+
+    let start = Instant::now();
+    let report = match params::pregenerate(&shapes, args.parallel) {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Error: parameter pregeneration failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for entry in &report.entries {
+        let status = match entry.status {
+            PregenStatus::Generated => "generated",
+            PregenStatus::Cached => "cached",
+        };
+        let size = entry
+            .cache_file_size
+            .map(|bytes| format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)))
+            .unwrap_or_else(|| "-".to_string());
+        info!(
+            "  files={:<4} depth={:<3} agg={:<3} {:<9} {:>8.1}s  {}",
+            entry.shape.files_per_step,
+            entry.shape.file_tree_depth,
+            entry.shape.aggregated_tree_depth,
+            status,
+            entry.elapsed.as_secs_f64(),
+            size
+        );
+    }
+    info!(
+        "  ✓ {} generated, {} already cached, {:.1} MB on disk, {:.1}s total",
+        report.generated(),
+        report.skipped(),
+        report.total_cache_bytes() as f64 / (1024.0 * 1024.0),
+        start.elapsed().as_secs_f64()
+    );
+}
+
+/// Delete (or with --dry-run, list) cached parameter files selected by the policy
+fn run_params_gc(args: &GcArgs) {
+    if let Some(dir) = &args.cache_dir {
+        params::set_cache_dir(Some(dir.clone()));
+    }
+    let Some(cache_dir) = params::cache_dir() else {
+        error!(
+            "Error: params gc requires --cache-dir or {}",
+            params::CACHE_DIR_ENV
+        );
+        std::process::exit(1);
+    };
+
+    let mode = if args.private {
+        ChallengeMode::Private
+    } else {
+        ChallengeMode::Public
+    };
+    let keep = args
+        .policy
+        .keep_depth
+        .as_ref()
+        .map(|depths| Shape::matrix(&args.keep_files.0, &depths.0, &args.keep_agg.0, mode))
+        .unwrap_or_default();
+    let policy = match (args.policy.older_than, args.policy.max_size) {
+        (Some(age), _) => GcPolicy::OlderThan(age),
+        (None, Some(max)) => GcPolicy::MaxTotalBytes(max),
+        (None, None) => GcPolicy::KeepShapes(&keep),
+    };
+
+    let result = if args.dry_run {
+        params::gc_dry_run(&cache_dir, policy)
+    } else {
+        params::gc(&cache_dir, policy)
+    };
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Error: parameter cache gc failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let action = if args.dry_run {
+        "would delete"
+    } else {
+        "deleted"
+    };
+    for entry in &report.removed {
+        let name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        info!(
+            "  {:<12} {:>8.1} MB  {}",
+            action,
+            entry.size as f64 / (1024.0 * 1024.0),
+            name
+        );
+    }
+    info!(
+        "  ✓ {} {} file(s), {:.1} MB; {} file(s) kept in {}",
+        action,
+        report.removed.len(),
+        report.freed_bytes() as f64 / (1024.0 * 1024.0),
+        report.kept.len(),
+        cache_dir.display()
+    );
+}
+
+/// Load a ledger (with the same validation as `FileLedger::load`) and print its contents
+fn run_ledger_inspect(args: &InspectArgs) {
+    let ledger = match FileLedger::load(&args.path) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            error!(
+                "Error: failed to load ledger {}: {}",
+                args.path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    if args.verify_consistency {
+        if let Err(e) = ledger
+            .check_consistency()
+            .and_then(|report| report.into_result())
+        {
+            error!(
+                "Error: ledger {} is inconsistent: {}",
+                args.path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    }
+
+    let mut description = ledger.describe();
+    if let Some(file_id) = &args.file_id {
+        description.files.retain(|file| &file.file_id == file_id);
+        if description.files.is_empty() {
+            error!("Error: file {} is not in the ledger", file_id);
+            std::process::exit(1);
+        }
+    }
+
+    match args.format {
+        OutputFormat::Json => match serde_json::to_string_pretty(&description) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                error!("Error: failed to encode ledger description: {}", e);
+                std::process::exit(1);
+            }
+        },
+        OutputFormat::Table => {
+            println!("Root:       {}", description.root);
+            println!("Depth:      {}", description.depth);
+            println!("Files:      {}", description.file_count);
+            if let Some(capacity) = description.capacity {
+                println!("Capacity:   {}", capacity);
+            }
+            if description.index_policy == IndexPolicy::InsertionOrder {
+                println!("Indices:    insertion order");
+            }
+            if args.verify_consistency {
+                println!("Consistency: ok");
+            }
+            println!();
+            println!(
+                "{:>5}  {:<64}  {:<20}  {:>5}  {:<6}  {:<64}  {:<64}",
+                "index", "file_id", "filename", "depth", "arity", "root", "rc"
+            );
+            for file in &description.files {
+                let arity = match file.tree_arity {
+                    Some(api::TreeArity::Binary) => "2",
+                    Some(api::TreeArity::Arity4) => "4",
+                    None => "?",
+                };
+                println!(
+                    "{:>5}  {:<64}  {:<20}  {:>5}  {:<6}  {:<64}  {:<64}",
+                    file.index, file.file_id, file.filename, file.depth, arity, file.root, file.rc
+                );
+            }
+            println!();
+            println!("Historical roots ({}):", description.historical_roots.len());
+            for root in &description.historical_roots {
+                println!("  {}", root);
+            }
+            if !description.migrated_roots.is_empty() {
+                println!("Migrated roots ({}):", description.migrated_roots.len());
+                for root in &description.migrated_roots {
+                    println!("  {}", root);
+                }
+            }
+        }
+    }
+}
+
+/// Build a challenge from `--meta` and write its canonical JSON to `--out` or stdout
+fn run_challenge_new(args: &NewChallengeArgs) {
+    let metadata = match std::fs::read_to_string(&args.meta)
+        .map_err(|e| KontorPoRError::IO(e.to_string()))
+        .and_then(|json| FileMetadata::from_json(&json))
+    {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            error!(
+                "Error: failed to read metadata {}: {}",
+                args.meta.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let seed = match (&args.block_hash, args.seed) {
+        (Some(block_hash), _) => Challenge::seed_from_block_hash(block_hash),
+        (None, Some(seed)) => FieldElement::from(seed),
+        (None, None) => unreachable!("clap requires --block-hash or --seed"),
+    };
+    let challenge = Challenge::new(
+        metadata,
+        args.block_height,
+        args.num,
+        seed,
+        args.prover.clone(),
+    );
+    if let Err(e) = challenge.validate() {
+        error!("Error: invalid challenge: {}", e);
+        std::process::exit(1);
+    }
+
+    if args.preview {
+        let data_path = args
+            .data
+            .as_ref()
+            .expect("clap requires --data with --preview");
+        let indices = std::fs::read(data_path)
+            .map_err(|e| KontorPoRError::IO(e.to_string()))
+            .and_then(|data| api::prepare_file_matching(&data, &challenge.file_metadata))
+            .and_then(|prepared| challenge.preview_leaf_indices(&prepared));
+        match indices {
+            Ok(indices) => {
+                info!("Leaf indices opened by {}:", challenge);
+                for (step, index) in indices.iter().enumerate() {
+                    info!("  step {:>3}: leaf {}", step, index);
+                }
+            }
+            Err(e) => {
+                error!("Error: failed to preview {}: {}", data_path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let json = match challenge.to_json() {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Error: failed to encode challenge: {}", e);
+            std::process::exit(1);
+        }
+    };
+    match &args.out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, json) {
+                error!("Error: failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            info!("Wrote {} to {}", challenge, path.display());
+        }
+        None => println!("{}", json),
+    }
+}
+
+/// Print the number of challenges that detect `--loss` with `--confidence`
+fn run_challenges_suggest(args: &SuggestArgs) {
+    let erasure = (!args.raw).then_some(ErasureCode::ReedSolomon);
+    let required = sampling::required_challenges_with(erasure, args.loss, args.confidence);
+    if required == usize::MAX {
+        error!(
+            "Error: no number of challenges detects a loss of {} with confidence {}",
+            args.loss, args.confidence
+        );
+        std::process::exit(1);
+    }
+
+    println!("{}", required);
+    info!(
+        "{} challenges detect {:.4}% data loss with probability {:.6} ({}, {:.4}% of symbols missing)",
+        required,
+        args.loss * 100.0,
+        sampling::detection_probability_with(erasure, required, args.loss),
+        if args.raw { "raw file" } else { "Reed-Solomon coded" },
+        sampling::detectable_fraction(args.loss, erasure) * 100.0
+    );
+    if required > config::MAX_NUM_CHALLENGES {
+        info!(
+            "  Above the limit of {} steps per challenge; accept a larger loss or lower confidence",
+            config::MAX_NUM_CHALLENGES
+        );
+    }
+}
+
+/// Generate a test vector and write it as JSON to `--out` or stdout
+#[cfg(feature = "test-vectors")]
+fn run_fixtures(args: &FixturesArgs) {
+    use kontor_crypto::fixtures::{generate_fixture, FixtureSpec};
+
+    let spec = FixtureSpec {
+        file_sizes: args.file_sizes.clone(),
+        num_challenges: args.num_challenges,
+        seed: args.seed,
+        block_height: args.block_height,
+    };
+    let json = match generate_fixture(spec).and_then(|fixture| fixture.to_json()) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Error: failed to generate fixture: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match &args.out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, json) {
+                error!("Error: failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            info!("Wrote fixture to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+}
+
+/// Run repeated loss simulations, comparing predicted recoverability with actual decoding
+fn run_loss_simulation(args: &SimulateArgs) {
+    if args.file_size == 0 {
+        error!("Error: --file-size must be greater than zero");
+        std::process::exit(1);
+    }
+
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut data = vec![0u8; args.file_size];
+    rng.fill_bytes(&mut data);
+
+    let (symbols, metadata) = match erasure::encode_file_symbols(&data)
+        .and_then(|symbols| Ok((symbols, api::prepare_file(&data, "simulate.dat")?.1)))
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Error: failed to encode test file: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    info!(
+        "Simulating {:?} over {} trials ({} bytes, {} codewords, {} symbols)",
+        args.loss,
+        args.trials,
+        args.file_size,
+        metadata.num_codewords(),
+        symbols.len()
+    );
+
+    let mut recovered = 0u64;
+    let mut mismatches = 0u64;
+    let mut total_missing = 0usize;
+    for trial in 0..args.trials {
+        let damaged = erasure::simulate_loss(
+            &symbols,
+            &metadata,
+            args.loss,
+            args.seed.wrapping_add(trial),
+        );
+        let report = erasure::recoverability(&damaged, &metadata);
+        total_missing += report.total_missing();
+
+        let decoded = api::reconstruct_file(&damaged, &metadata);
+        let actual = matches!(&decoded, Ok(bytes) if *bytes == data);
+        if actual {
+            recovered += 1;
+        }
+        if actual != report.is_recoverable() {
+            mismatches += 1;
+            error!(
+                "  Trial {}: predicted {} but decode {}",
+                trial,
+                report.is_recoverable(),
+                actual
+            );
+        }
+    }
+
+    let trials = args.trials.max(1) as f64;
+    info!(
+        "  ✓ Recovered {}/{} ({:.1}%), mean {:.1} symbols lost per trial",
+        recovered,
+        args.trials,
+        recovered as f64 / trials * 100.0,
+        total_missing as f64 / trials
+    );
+    if mismatches > 0 {
+        error!("  ✗ {} predictions disagreed with decoding", mismatches);
+        std::process::exit(1);
+    }
+}
+
 fn init_tracing(verbosity: u8) {
     let level = match verbosity {
         0 => "info,kontor_crypto=info,nova_snark=warn",
@@ -3,8 +3,17 @@
 //! This module provides functions for building and verifying Poseidon-based Merkle trees.
 //! It includes helpers for hashing data into field elements (`get_leaf_hash`), constructing
 //! the tree (`build_tree`), and generating proofs of inclusion (`get_padded_proof_for_leaf`).
+//!
+//! Files may alternatively use arity-4 trees (`build_tree_arity4`, `get_padded_proof4_for_leaf`),
+//! which halve the depth for the same number of leaves at the cost of three siblings per level.
+//!
+//! File trees may also be hashed under [`HashProfile::Conservative`] with the `_with_profile`
+//! builders and proof helpers; the plain functions use [`HashProfile::Standard`].
+//!
+//! Large binary trees can be built from a leaf iterator with `build_tree_streaming`, which
+//! needs only a logarithmic frontier beyond the layers it is asked to keep.
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use nova_snark::provider::PallasEngine;
 use nova_snark::traits::Engine;
 use serde::{Deserialize, Serialize};
@@ -12,8 +21,14 @@ use serde::{Deserialize, Serialize};
 use super::KontorPoRError;
 use crate::config;
 use crate::poseidon::domain_tags;
-use crate::poseidon::poseidon_hash_tagged;
-use crate::utils::bytes31_to_field_le;
+use crate::poseidon::{
+    poseidon_hash_tagged, poseidon_hash_tagged4_with, poseidon_hash_tagged_with, HashProfile,
+};
+use crate::utils::{bytes31_to_field_le, check_cancelled, is_bytes31_leaf};
+use std::sync::atomic::AtomicBool;
+
+/// Nodes hashed between checks of a cancellation flag while building a tree.
+const CANCEL_CHECK_NODES: usize = 1 << 12;
 
 /// A type alias for the scalar field of the Pallas curve.
 pub type F = <PallasEngine as Engine>::Scalar;
@@ -26,7 +41,22 @@ pub fn poseidon_hash_pair(left: F, right: F) -> F {
 
 /// Domain-separated hash for Merkle tree nodes
 pub fn hash_node(left: F, right: F) -> F {
-    poseidon_hash_tagged(domain_tags::node(), left, right)
+    hash_node_with_profile(HashProfile::Standard, left, right)
+}
+
+/// Like [`hash_node`], but with the Poseidon constants of `profile`
+pub fn hash_node_with_profile(profile: HashProfile, left: F, right: F) -> F {
+    poseidon_hash_tagged_with(profile, domain_tags::node(), left, right)
+}
+
+/// Domain-separated hash for arity-4 Merkle tree nodes (children in left-to-right order)
+pub fn hash_node4(children: [F; 4]) -> F {
+    hash_node4_with_profile(HashProfile::Standard, children)
+}
+
+/// Like [`hash_node4`], but with the Poseidon constants of `profile`
+pub fn hash_node4_with_profile(profile: HashProfile, children: [F; 4]) -> F {
+    poseidon_hash_tagged4_with(profile, domain_tags::node_arity4(), children)
 }
 
 /// Domain-separated hash for leaf data
@@ -64,7 +94,7 @@ pub fn get_leaf_hash(data: &[u8]) -> Result<F, KontorPoRError> {
         )));
     }
 
-    // Handle empty data
+    // Empty data encodes like an all-zero (padding) symbol
     if data.is_empty() {
         return Ok(F::ZERO);
     }
@@ -73,7 +103,99 @@ pub fn get_leaf_hash(data: &[u8]) -> Result<F, KontorPoRError> {
     Ok(element)
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Branching factor of a file's Merkle tree.
+///
+/// The arity is part of a file's public commitment: it selects the node hash, the root
+/// commitment tag and the circuit shape used to prove the file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TreeArity {
+    /// Two children per node: one sibling and one path bit per level.
+    #[default]
+    Binary,
+    /// Four children per node: three siblings and two path bits per level.
+    Arity4,
+}
+
+impl TreeArity {
+    /// Number of children of each internal node.
+    pub fn children_per_node(&self) -> usize {
+        match self {
+            TreeArity::Binary => 2,
+            TreeArity::Arity4 => 4,
+        }
+    }
+
+    /// Number of sibling hashes in a proof for each tree level.
+    pub fn siblings_per_level(&self) -> usize {
+        self.children_per_node() - 1
+    }
+
+    /// Number of leaf index bits consumed by each tree level.
+    pub fn bits_per_level(&self) -> usize {
+        self.children_per_node().trailing_zeros() as usize
+    }
+
+    /// Number of leaf index bits addressing a tree of the given depth.
+    pub fn index_bits(&self, depth: usize) -> usize {
+        self.bits_per_level() * depth
+    }
+
+    /// Depth of a tree with `padded_len` leaves (a power of [`Self::children_per_node`]).
+    pub fn depth_for_len(&self, padded_len: usize) -> usize {
+        if padded_len == 0 {
+            0
+        } else {
+            padded_len.trailing_zeros() as usize / self.bits_per_level()
+        }
+    }
+
+    /// Smallest leaf count of a full tree with at least `len` leaves (at least one leaf).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded count overflows `usize`; see [`Self::checked_padded_len`].
+    pub fn padded_len(&self, len: usize) -> usize {
+        self.checked_padded_len(len)
+            .expect("padded leaf count overflows usize")
+    }
+
+    /// Like [`Self::padded_len`], but `None` if the padded count does not fit in `usize`.
+    pub fn checked_padded_len(&self, len: usize) -> Option<usize> {
+        let padded = len.checked_next_power_of_two()?;
+        if (padded.trailing_zeros() as usize).is_multiple_of(self.bits_per_level()) {
+            Some(padded)
+        } else {
+            padded.checked_mul(2)
+        }
+    }
+
+    /// Root commitment rc for a file tree of this arity.
+    ///
+    /// Binary trees keep the original `H(TAG_RC, root, depth)`; arity-4 trees use their
+    /// own tag so the two can never collide.
+    pub fn root_commitment(&self, root: F, depth: usize) -> F {
+        self.root_commitment_with_profile(HashProfile::Standard, root, depth)
+    }
+
+    /// Root commitment rc for a file tree of this arity hashed under `profile`.
+    ///
+    /// The commitment is computed with the profile's constants, so files of different
+    /// profiles never share an rc. See [`crate::ledger::compute_rc_with`].
+    pub fn root_commitment_with_profile(&self, profile: HashProfile, root: F, depth: usize) -> F {
+        crate::ledger::compute_rc_with(root, depth, *self, profile)
+    }
+
+    /// Domain tag of this arity's root commitment: `TAG_RC` for binary trees, `TAG_RC4`
+    /// for arity-4 trees.
+    pub(crate) fn root_commitment_tag<G: PrimeField>(&self) -> G {
+        match self {
+            TreeArity::Binary => domain_tags::root_commitment(),
+            TreeArity::Arity4 => domain_tags::root_commitment_arity4(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
 /// A Merkle tree implementation with layers stored from leaves (layer 0) to root.
 ///
 /// The tree stores all layers explicitly, where:
@@ -81,12 +203,30 @@ pub fn get_leaf_hash(data: &[u8]) -> Result<F, KontorPoRError> {
 /// - `layers[layers.len() - 1]` contains the root (single element)
 ///
 /// Each internal layer contains the hash of pairs from the layer below.
+///
+/// A tree may be pruned with [`Self::prune`]: pruned interior layers are stored empty and
+/// their nodes are recomputed from the closest stored layer below when a proof needs them.
+///
+/// Serializes as a length-prefixed list of length-prefixed layers of canonical 32-byte
+/// little-endian field representations; see [`canonical`].
 pub struct MerkleTree {
     /// The layers of the tree, with layer 0 being the leaves and the last layer being the root.
     /// Each layer contains the hash values at that level of the tree.
     pub layers: Vec<Vec<F>>,
 }
 
+impl std::fmt::Debug for MerkleTree {
+    /// Prints only the root, depth and sizes; see [`MerkleTree::debug_full`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("root", &self.root())
+            .field("depth", &self.layers.len().saturating_sub(1))
+            .field("leaf_count", &self.layers.first().map_or(0, Vec::len))
+            .field("stored_nodes", &self.stored_nodes())
+            .finish()
+    }
+}
+
 impl MerkleTree {
     /// Returns the root of the tree.
     pub fn root(&self) -> F {
@@ -96,11 +236,125 @@ impl MerkleTree {
             .copied()
             .unwrap_or(F::ZERO) // Default to zero if tree is malformed
     }
+
+    /// Drops interior layers to save memory, keeping the leaves, the root and the
+    /// `keep_top_levels` layers directly below the root.
+    ///
+    /// Proofs from a pruned tree are identical to the unpruned tree's, but each sibling
+    /// taken from a dropped layer is recomputed from the stored layer below it. With `m`
+    /// dropped layers above the leaves, a binary tree of `N` leaves frees about
+    /// `N * (1 - 2^-m)` nodes (roughly half of all stored nodes for `keep_top_levels = 0`)
+    /// and every proof costs about `2^(m+1)` extra hashes; an arity-4 tree frees about `N / 3`
+    /// nodes and every proof costs on the order of `4^m` extra 4-ary hashes. Pruning is one-way:
+    /// dropped layers are not restored by a later call with a larger `keep_top_levels`.
+    pub fn prune(&mut self, keep_top_levels: usize) {
+        let depth = self.layers.len().saturating_sub(1);
+        let first_kept = depth.saturating_sub(keep_top_levels).max(1);
+        for layer in self.layers.iter_mut().take(first_kept).skip(1) {
+            *layer = Vec::new();
+        }
+    }
+
+    /// Number of nodes (leaves included) held in memory.
+    pub fn stored_nodes(&self) -> usize {
+        self.layers.iter().map(Vec::len).sum()
+    }
+
+    /// Heap memory held by the tree in bytes: the allocated capacity of every layer plus
+    /// the vector of layers itself. Pruned layers count only their (empty) vector header.
+    pub fn heap_size(&self) -> usize {
+        self.layers.capacity() * std::mem::size_of::<Vec<F>>()
+            + self
+                .layers
+                .iter()
+                .map(|layer| layer.capacity() * std::mem::size_of::<F>())
+                .sum::<usize>()
+    }
+
+    /// Debug output including every stored node, for tests only.
+    ///
+    /// The regular `Debug` output prints just the root and sizes, since the layers hold
+    /// the private file contents.
+    #[doc(hidden)]
+    pub fn debug_full(&self) -> String {
+        format!("MerkleTree {{ layers: {:?} }}", self.layers)
+    }
+
+    /// Number of nodes in layer `level`, derived from the layer below if it was pruned.
+    fn layer_len(&self, level: usize, tree_arity: TreeArity) -> usize {
+        match &self.layers[level] {
+            layer if level == 0 || !layer.is_empty() => layer.len(),
+            _ => self
+                .layer_len(level - 1, tree_arity)
+                .div_ceil(tree_arity.children_per_node()),
+        }
+    }
+
+    /// Node `index` of layer `level`, recomputed from the layer below if it was pruned.
+    ///
+    /// Returns `None` past the end of the layer. Recomputation follows the builders: a
+    /// binary node without a right child hashes its left child twice, and missing arity-4
+    /// children are zero. Recomputed nodes are hashed under `profile`.
+    fn node(
+        &self,
+        level: usize,
+        index: usize,
+        tree_arity: TreeArity,
+        profile: HashProfile,
+    ) -> Option<F> {
+        let layer = &self.layers[level];
+        if level == 0 || !layer.is_empty() {
+            return layer.get(index).copied();
+        }
+        if index >= self.layer_len(level, tree_arity) {
+            return None;
+        }
+
+        let first_child = index * tree_arity.children_per_node();
+        match tree_arity {
+            TreeArity::Binary => {
+                let left = self.node(level - 1, first_child, tree_arity, profile)?;
+                let right = self
+                    .node(level - 1, first_child + 1, tree_arity, profile)
+                    .unwrap_or(left);
+                Some(hash_node_with_profile(profile, left, right))
+            }
+            TreeArity::Arity4 => {
+                let mut children = [F::ZERO; 4];
+                for (offset, child) in children.iter_mut().enumerate() {
+                    *child = self
+                        .node(level - 1, first_child + offset, tree_arity, profile)
+                        .unwrap_or(F::ZERO);
+                }
+                Some(hash_node4_with_profile(profile, children))
+            }
+        }
+    }
 }
 
 /// Builds a Poseidon-based Merkle tree from a slice of pre-computed leaves.
-/// This is the generic, core logic for tree construction.
+/// This is the generic, core logic for tree construction: the leaves are taken as given,
+/// without checking what they encode, so it also builds trees over roots or other node
+/// values. For leaves of file symbols use [`build_tree_from_prehashed`].
 pub fn build_tree_from_leaves(leaves: &[F]) -> Result<MerkleTree, KontorPoRError> {
+    build_tree_from_leaves_with_profile(leaves, HashProfile::Standard)
+}
+
+/// Like [`build_tree_from_leaves`], but hashes every node under `profile`.
+pub fn build_tree_from_leaves_with_profile(
+    leaves: &[F],
+    profile: HashProfile,
+) -> Result<MerkleTree, KontorPoRError> {
+    build_binary_tree(leaves, profile, None)
+}
+
+/// Binary tree builder behind [`build_tree_from_leaves_with_profile`], checking `cancel`
+/// between layers and every [`CANCEL_CHECK_NODES`] nodes.
+fn build_binary_tree(
+    leaves: &[F],
+    profile: HashProfile,
+    cancel: Option<&AtomicBool>,
+) -> Result<MerkleTree, KontorPoRError> {
     if leaves.is_empty() {
         return Ok(MerkleTree {
             layers: vec![vec![F::ZERO]],
@@ -122,12 +376,15 @@ pub fn build_tree_from_leaves(leaves: &[F]) -> Result<MerkleTree, KontorPoRError
         let mut next_layer = Vec::new();
 
         // Process pairs of nodes
-        for pair in current_layer.chunks(2) {
-            if pair.len() == 2 {
-                next_layer.push(hash_node(pair[0], pair[1]));
-            } else {
-                // Odd number of nodes - duplicate the last one by hashing it with itself
-                next_layer.push(hash_node(pair[0], pair[0]));
+        for block in current_layer.chunks(2 * CANCEL_CHECK_NODES) {
+            check_cancelled(cancel)?;
+            for pair in block.chunks(2) {
+                if pair.len() == 2 {
+                    next_layer.push(hash_node_with_profile(profile, pair[0], pair[1]));
+                } else {
+                    // Odd number of nodes - duplicate the last one by hashing it with itself
+                    next_layer.push(hash_node_with_profile(profile, pair[0], pair[0]));
+                }
             }
         }
         layers.push(next_layer);
@@ -139,6 +396,9 @@ pub fn build_tree_from_leaves(leaves: &[F]) -> Result<MerkleTree, KontorPoRError
 /// Builds a Poseidon-based Merkle tree from the given data chunks.
 /// This function serves as a wrapper around the generic `build_tree_from_leaves`
 /// by first converting the data chunks into leaves.
+///
+/// An empty slice yields the same depth-0 tree as a single all-zero chunk: one zero leaf,
+/// which is also the root. This is the tree `prepare_file` builds for an empty file.
 pub fn build_tree(data_chunks: &[Vec<u8>]) -> Result<(MerkleTree, F), KontorPoRError> {
     if data_chunks.is_empty() {
         let leaf = F::ZERO;
@@ -161,12 +421,270 @@ pub fn build_tree(data_chunks: &[Vec<u8>]) -> Result<(MerkleTree, F), KontorPoRE
     Ok((tree, root))
 }
 
+/// Builds a Poseidon-based Merkle tree over the leaves of 31-byte symbols, returning the
+/// tree and its root.
+///
+/// This is the supported path for storage systems that already hold each symbol's leaf:
+/// `leaves[i]` must be [`get_leaf_hash`] of symbol `i`, and the result is then identical
+/// to [`build_tree`] over the symbols without re-encoding them. Only the leaves' encoding
+/// is checked, not that they match any data; [`crate::api::prepare_file_with_leaf_hashes`]
+/// also checks them against the symbols. An empty slice yields the same depth-0 tree as
+/// [`build_tree`].
+///
+/// # Errors
+///
+/// [`KontorPoRError::InvalidLeafEncoding`] at the first leaf that is wider than 31 bytes,
+/// and so cannot be the leaf of a symbol.
+pub fn build_tree_from_prehashed(leaves: &[F]) -> Result<(MerkleTree, F), KontorPoRError> {
+    if let Some(index) = leaves.iter().position(|leaf| !is_bytes31_leaf(leaf)) {
+        return Err(KontorPoRError::InvalidLeafEncoding { index });
+    }
+    if leaves.is_empty() {
+        return build_tree(&[]);
+    }
+
+    let tree = build_tree_from_leaves(leaves)?;
+    let root = tree.root();
+
+    Ok((tree, root))
+}
+
+/// Which layers [`build_tree_streaming_with_retention`] keeps in the returned tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayerRetention {
+    /// Every layer, identical to [`build_tree_from_leaves`]; what proving needs.
+    #[default]
+    Full,
+    /// Only the leaves and the root, with interior layers stored empty as after
+    /// [`MerkleTree::prune`]`(0)`. Enough for a commitment; proofs still work but recompute
+    /// their siblings from the leaves.
+    LeavesAndRoot,
+}
+
+/// Builds a binary Merkle tree by consuming leaves one at a time, keeping every layer.
+///
+/// Equivalent to [`build_tree_streaming_with_retention`] with [`LayerRetention::Full`].
+pub fn build_tree_streaming(
+    leaves: impl Iterator<Item = F>,
+    expected_count: Option<usize>,
+) -> Result<(MerkleTree, F), KontorPoRError> {
+    build_tree_streaming_with_retention(leaves, expected_count, LayerRetention::Full)
+}
+
+/// Builds a binary Merkle tree by consuming leaves one at a time.
+///
+/// Completed subtree roots are kept on a frontier of at most `log2(n) + 1` entries and
+/// merged as soon as a sibling arrives, so construction needs only O(log n) memory beyond
+/// the layers `retention` keeps. The result is the tree [`build_tree_from_leaves`] builds
+/// from the same leaves, including its odd-node duplication and its single zero leaf for
+/// an empty iterator.
+///
+/// `expected_count`, when given, preallocates the retained layers and must equal the
+/// number of leaves the iterator yields.
+pub fn build_tree_streaming_with_retention(
+    leaves: impl Iterator<Item = F>,
+    expected_count: Option<usize>,
+    retention: LayerRetention,
+) -> Result<(MerkleTree, F), KontorPoRError> {
+    let keep_interior = retention == LayerRetention::Full;
+    let layer_capacity = |level: usize| expected_count.map_or(0, |n| n.div_ceil(1 << level));
+    let mut layers = vec![Vec::with_capacity(layer_capacity(0))];
+    let push_node = |layers: &mut Vec<Vec<F>>, level: usize, node: F| {
+        if keep_interior {
+            if layers.len() == level {
+                layers.push(Vec::with_capacity(layer_capacity(level)));
+            }
+            layers[level].push(node);
+        }
+    };
+
+    // Roots of complete subtrees still waiting for a right sibling, as (level, hash);
+    // levels strictly decrease towards the top of the stack.
+    let mut frontier: Vec<(usize, F)> = Vec::new();
+    for leaf in leaves {
+        layers[0].push(leaf);
+        let (mut level, mut node) = (0, leaf);
+        while let Some(&(left_level, left)) = frontier.last() {
+            if left_level != level {
+                break;
+            }
+            frontier.pop();
+            level += 1;
+            node = hash_node(left, node);
+            push_node(&mut layers, level, node);
+        }
+        frontier.push((level, node));
+    }
+
+    let count = layers[0].len();
+    if let Some(expected) = expected_count {
+        if expected != count {
+            return Err(KontorPoRError::MerkleTree(format!(
+                "build_tree_streaming: expected {} leaves but the iterator yielded {}",
+                expected, count
+            )));
+        }
+    }
+    let Some((mut level, mut node)) = frontier.pop() else {
+        return Ok((
+            MerkleTree {
+                layers: vec![vec![F::ZERO]],
+            },
+            F::ZERO,
+        ));
+    };
+
+    // Close the right edge of the tree, starting from the smallest pending subtree. A node
+    // without a pending left sibling ends an odd-length layer and is hashed with itself.
+    let depth = count.next_power_of_two().trailing_zeros() as usize;
+    while level < depth {
+        node = match frontier.last() {
+            Some(&(left_level, left)) if left_level == level => {
+                frontier.pop();
+                hash_node(left, node)
+            }
+            _ => hash_node(node, node),
+        };
+        level += 1;
+        push_node(&mut layers, level, node);
+    }
+
+    if !keep_interior && depth > 0 {
+        layers.resize(depth, Vec::new());
+        layers.push(vec![node]);
+    }
+    Ok((MerkleTree { layers }, node))
+}
+
+/// Builds an arity-4 Poseidon Merkle tree from a slice of pre-computed leaves.
+///
+/// Each internal node is `hash_node4` over up to four children; a short final group is
+/// padded with zero children. Callers normally pad the leaves to a power of four.
+pub fn build_tree_from_leaves_arity4(leaves: &[F]) -> Result<MerkleTree, KontorPoRError> {
+    build_tree_from_leaves_arity4_with_profile(leaves, HashProfile::Standard)
+}
+
+/// Like [`build_tree_from_leaves_arity4`], but hashes every node under `profile`.
+pub fn build_tree_from_leaves_arity4_with_profile(
+    leaves: &[F],
+    profile: HashProfile,
+) -> Result<MerkleTree, KontorPoRError> {
+    build_arity4_tree(leaves, profile, None)
+}
+
+/// Arity-4 counterpart of [`build_binary_tree`].
+fn build_arity4_tree(
+    leaves: &[F],
+    profile: HashProfile,
+    cancel: Option<&AtomicBool>,
+) -> Result<MerkleTree, KontorPoRError> {
+    if leaves.is_empty() {
+        return Ok(MerkleTree {
+            layers: vec![vec![F::ZERO]],
+        });
+    }
+
+    let mut layers = vec![leaves.to_vec()];
+    while let Some(current_layer) = layers.last().filter(|layer| layer.len() > 1) {
+        let mut next_layer = Vec::with_capacity(current_layer.len().div_ceil(4));
+        for block in current_layer.chunks(4 * CANCEL_CHECK_NODES) {
+            check_cancelled(cancel)?;
+            next_layer.extend(block.chunks(4).map(|group| {
+                let mut children = [F::ZERO; 4];
+                children[..group.len()].copy_from_slice(group);
+                hash_node4_with_profile(profile, children)
+            }));
+        }
+        layers.push(next_layer);
+    }
+
+    Ok(MerkleTree { layers })
+}
+
+/// Builds an arity-4 Merkle tree from the given data chunks.
+///
+/// Arity-4 counterpart of [`build_tree`]; an empty slice yields the same single zero leaf.
+pub fn build_tree_arity4(data_chunks: &[Vec<u8>]) -> Result<(MerkleTree, F), KontorPoRError> {
+    if data_chunks.is_empty() {
+        return build_tree(data_chunks);
+    }
+
+    let leaves: Vec<F> = data_chunks
+        .iter()
+        .map(|chunk| get_leaf_hash(chunk))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tree = build_tree_from_leaves_arity4(&leaves)?;
+    let root = tree.root();
+
+    Ok((tree, root))
+}
+
+/// Builds a file's Merkle tree from its data chunks with the given arity and profile.
+///
+/// [`build_tree`] and [`build_tree_arity4`] are the [`HashProfile::Standard`] cases; an
+/// empty slice yields the same single zero leaf under every profile.
+pub fn build_file_tree(
+    data_chunks: &[Vec<u8>],
+    tree_arity: TreeArity,
+    profile: HashProfile,
+) -> Result<(MerkleTree, F), KontorPoRError> {
+    build_file_tree_cancellable(data_chunks, tree_arity, profile, None)
+}
+
+/// [`build_file_tree`], checking `cancel` while hashing leaves and between tree layers.
+pub(crate) fn build_file_tree_cancellable(
+    data_chunks: &[Vec<u8>],
+    tree_arity: TreeArity,
+    profile: HashProfile,
+    cancel: Option<&AtomicBool>,
+) -> Result<(MerkleTree, F), KontorPoRError> {
+    if data_chunks.is_empty() {
+        return build_tree(data_chunks);
+    }
+
+    let mut leaves: Vec<F> = Vec::with_capacity(data_chunks.len());
+    for block in data_chunks.chunks(CANCEL_CHECK_NODES) {
+        check_cancelled(cancel)?;
+        for chunk in block {
+            leaves.push(get_leaf_hash(chunk)?);
+        }
+    }
+
+    build_file_tree_from_leaves(&leaves, tree_arity, profile, cancel)
+}
+
+/// Builds a file's Merkle tree from leaves already computed with [`get_leaf_hash`],
+/// checking `cancel` between tree layers. No leaves yield the single zero leaf of
+/// [`build_tree`].
+pub(crate) fn build_file_tree_from_leaves(
+    leaves: &[F],
+    tree_arity: TreeArity,
+    profile: HashProfile,
+    cancel: Option<&AtomicBool>,
+) -> Result<(MerkleTree, F), KontorPoRError> {
+    if leaves.is_empty() {
+        return build_tree(&[]);
+    }
+
+    let tree = match tree_arity {
+        TreeArity::Binary => build_binary_tree(leaves, profile, cancel)?,
+        TreeArity::Arity4 => build_arity4_tree(leaves, profile, cancel)?,
+    };
+    let root = tree.root();
+
+    Ok((tree, root))
+}
+
 /// A helper struct to hold the witness components for a Merkle proof.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// A Merkle proof that can be used in a circuit to verify inclusion of a leaf.
 ///
 /// Contains all the necessary information to verify that a leaf is included
 /// in a Merkle tree with a given root.
+///
+/// Serializes with canonical 32-byte field representations and the path packed into
+/// bits; see [`canonical`]. Hashing likewise uses the canonical field bytes.
 pub struct CircuitMerkleProof {
     /// The leaf value being proven for inclusion.
     pub leaf: F,
@@ -179,6 +697,205 @@ pub struct CircuitMerkleProof {
     pub path_indices: Vec<bool>,
 }
 
+impl std::hash::Hash for CircuitMerkleProof {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        canonical::field_to_bytes(&self.leaf).hash(state);
+        self.siblings.len().hash(state);
+        for sibling in &self.siblings {
+            canonical::field_to_bytes(sibling).hash(state);
+        }
+        self.path_indices.hash(state);
+    }
+}
+
+/// Canonical serde encoding for Merkle trees and proofs.
+///
+/// Field elements are written as their 32-byte little-endian `to_repr()` bytes, independent
+/// of the `ff`/curve crates' own serde formats. Deserialization rejects non-canonical
+/// encodings (values not below the field modulus).
+pub mod canonical {
+    use super::{CircuitMerkleProof, KontorPoRError, MerkleTree, F};
+    use ff::PrimeField;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Encoded size of one field element.
+    pub const FIELD_BYTES: usize = 32;
+
+    /// Canonical 32-byte little-endian representation of `element`.
+    pub fn field_to_bytes(element: &F) -> [u8; FIELD_BYTES] {
+        element.to_repr().into()
+    }
+
+    /// Lowercase hex of the canonical representation of `element`.
+    pub fn field_to_hex(element: &F) -> String {
+        field_to_bytes(element)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Parses a canonical representation, returning `None` if it is not below the modulus.
+    pub fn field_from_bytes(bytes: [u8; FIELD_BYTES]) -> Option<F> {
+        F::from_repr(bytes.into()).into()
+    }
+
+    /// `#[serde(with = ...)]` adapter for a single field element in canonical form.
+    pub mod field {
+        use super::{decode, field_to_bytes, FIELD_BYTES};
+        use crate::merkle::F;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Serializes `element` as its canonical 32-byte representation.
+        pub fn serialize<S: Serializer>(element: &F, serializer: S) -> Result<S::Ok, S::Error> {
+            field_to_bytes(element).serialize(serializer)
+        }
+
+        /// Deserializes a canonical 32-byte representation, rejecting non-canonical values.
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<F, D::Error> {
+            decode(<[u8; FIELD_BYTES]>::deserialize(deserializer)?)
+        }
+    }
+
+    /// `#[serde(with = ...)]` adapter for a vector of field elements in canonical form.
+    pub mod fields {
+        use super::{decode, field_to_bytes, FIELD_BYTES};
+        use crate::merkle::F;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Serializes `elements` as a length-prefixed vector of canonical representations.
+        pub fn serialize<S: Serializer>(elements: &[F], serializer: S) -> Result<S::Ok, S::Error> {
+            let bytes: Vec<[u8; FIELD_BYTES]> = elements.iter().map(field_to_bytes).collect();
+            bytes.serialize(serializer)
+        }
+
+        /// Deserializes canonical representations, rejecting any non-canonical value.
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<F>, D::Error> {
+            Vec::<[u8; FIELD_BYTES]>::deserialize(deserializer)?
+                .into_iter()
+                .map(decode)
+                .collect()
+        }
+    }
+
+    /// Deserialization error message for a non-canonical field element.
+    pub(crate) const NON_CANONICAL_MESSAGE: &str = "non-canonical field element encoding";
+
+    fn decode<E: serde::de::Error>(bytes: [u8; FIELD_BYTES]) -> Result<F, E> {
+        field_from_bytes(bytes).ok_or_else(|| E::custom(NON_CANONICAL_MESSAGE))
+    }
+
+    /// Converts a bincode deserialization error for `context`, reporting non-canonical
+    /// field elements as [`KontorPoRError::NonCanonicalFieldElement`].
+    pub(crate) fn deserialize_error(err: bincode::Error, context: &str) -> KontorPoRError {
+        match *err {
+            bincode::ErrorKind::Custom(ref message) if message == NON_CANONICAL_MESSAGE => {
+                KontorPoRError::NonCanonicalFieldElement {
+                    context: context.to_string(),
+                }
+            }
+            _ => {
+                KontorPoRError::Serialization(format!("Failed to deserialize {}: {}", context, err))
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct TreeWire {
+        layers: Vec<Vec<[u8; FIELD_BYTES]>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ProofWire {
+        leaf: [u8; FIELD_BYTES],
+        siblings: Vec<[u8; FIELD_BYTES]>,
+        /// Number of path bits
+        path_len: u32,
+        /// Path bits, least significant bit of byte 0 first
+        path_bits: Vec<u8>,
+    }
+
+    impl Serialize for MerkleTree {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            TreeWire {
+                layers: self
+                    .layers
+                    .iter()
+                    .map(|layer| layer.iter().map(field_to_bytes).collect())
+                    .collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for MerkleTree {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let wire = TreeWire::deserialize(deserializer)?;
+            // Interior layers may be empty (pruned); the leaves may not
+            if wire.layers.first().is_some_and(|leaves| leaves.is_empty()) {
+                return Err(D::Error::custom("Merkle tree leaf layer is empty"));
+            }
+            if wire.layers.last().is_some_and(|root| root.len() != 1) {
+                return Err(D::Error::custom(
+                    "Merkle tree root layer must hold exactly one node",
+                ));
+            }
+            let layers = wire
+                .layers
+                .into_iter()
+                .map(|layer| layer.into_iter().map(decode).collect())
+                .collect::<Result<_, _>>()?;
+            Ok(MerkleTree { layers })
+        }
+    }
+
+    impl Serialize for CircuitMerkleProof {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut path_bits = vec![0u8; self.path_indices.len().div_ceil(8)];
+            for (i, _) in self
+                .path_indices
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+            {
+                path_bits[i / 8] |= 1 << (i % 8);
+            }
+            let path_len = u32::try_from(self.path_indices.len())
+                .map_err(|_| serde::ser::Error::custom("Merkle path too long"))?;
+            ProofWire {
+                leaf: field_to_bytes(&self.leaf),
+                siblings: self.siblings.iter().map(field_to_bytes).collect(),
+                path_len,
+                path_bits,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CircuitMerkleProof {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let wire = ProofWire::deserialize(deserializer)?;
+            let path_len = wire.path_len as usize;
+            if wire.path_bits.len() != path_len.div_ceil(8) {
+                return Err(D::Error::custom("Merkle path bit length mismatch"));
+            }
+            if !path_len.is_multiple_of(8) && wire.path_bits[path_len / 8] >> (path_len % 8) != 0 {
+                return Err(D::Error::custom("Merkle path has trailing bits set"));
+            }
+            Ok(CircuitMerkleProof {
+                leaf: decode(wire.leaf)?,
+                siblings: wire
+                    .siblings
+                    .into_iter()
+                    .map(decode)
+                    .collect::<Result<_, _>>()?,
+                path_indices: (0..path_len)
+                    .map(|i| wire.path_bits[i / 8] >> (i % 8) & 1 == 1)
+                    .collect(),
+            })
+        }
+    }
+}
+
 /// Generates a `CircuitMerkleProof` for the leaf at `leaf_index` and pads it
 /// to the specified `depth`.
 ///
@@ -187,6 +904,18 @@ pub fn get_padded_proof_for_leaf(
     tree: &MerkleTree,
     leaf_index: usize,
     depth: usize,
+) -> Result<CircuitMerkleProof, KontorPoRError> {
+    get_padded_proof_for_leaf_with_profile(tree, leaf_index, depth, HashProfile::Standard)
+}
+
+/// Like [`get_padded_proof_for_leaf`], for a tree hashed under `profile`.
+///
+/// The profile is only used to recompute siblings from pruned layers.
+pub fn get_padded_proof_for_leaf_with_profile(
+    tree: &MerkleTree,
+    leaf_index: usize,
+    depth: usize,
+    profile: HashProfile,
 ) -> Result<CircuitMerkleProof, KontorPoRError> {
     let leaf = tree
         .layers
@@ -204,7 +933,6 @@ pub fn get_padded_proof_for_leaf(
 
     // Walk up the tree from leaf to root
     for level in 0..tree.layers.len() - 1 {
-        let current_layer = &tree.layers[level];
         let is_right_node = current_index % 2 == 1;
 
         let sibling_index = if is_right_node {
@@ -214,10 +942,17 @@ pub fn get_padded_proof_for_leaf(
         };
 
         // Get the sibling, or duplicate current node if no sibling exists
-        let sibling = current_layer
-            .get(sibling_index)
-            .copied()
-            .unwrap_or(current_layer[current_index]);
+        let sibling = match tree.node(level, sibling_index, TreeArity::Binary, profile) {
+            Some(sibling) => sibling,
+            None => tree
+                .node(level, current_index, TreeArity::Binary, profile)
+                .ok_or_else(|| {
+                    KontorPoRError::MerkleTree(format!(
+                        "get_padded_proof_for_leaf: node {} missing from layer {}",
+                        current_index, level
+                    ))
+                })?,
+        };
 
         siblings.push(sibling);
         path_indices.push(is_right_node);
@@ -245,17 +980,147 @@ pub fn get_padded_proof_for_leaf(
 
 /// Verifies a `CircuitMerkleProof` against a given root.
 /// This is a non-circuit implementation for direct verification.
+///
+/// At each level `i`, `path_indices[i] == true` means the current node is the right
+/// child, so the parent is `hash_node(siblings[i], current)`; `false` means it is the
+/// left child and the parent is `hash_node(current, siblings[i])`. The same convention
+/// applies to ledger aggregation proofs (see `ledger::verify_aggregation_proof`).
 pub fn verify_merkle_proof_in_place(root: F, proof: &CircuitMerkleProof) -> bool {
+    verify_merkle_proof_in_place_with_profile(root, proof, HashProfile::Standard)
+}
+
+/// Like [`verify_merkle_proof_in_place`], for a tree hashed under `profile`.
+pub fn verify_merkle_proof_in_place_with_profile(
+    root: F,
+    proof: &CircuitMerkleProof,
+    profile: HashProfile,
+) -> bool {
     let mut current_hash = proof.leaf;
     for (i, sibling) in proof.siblings.iter().enumerate() {
         let path_bit = proof.path_indices.get(i).copied().unwrap_or(false);
         if path_bit {
             // Sibling is on the left
-            current_hash = hash_node(*sibling, current_hash);
+            current_hash = hash_node_with_profile(profile, *sibling, current_hash);
         } else {
             // Sibling is on the right
-            current_hash = hash_node(current_hash, *sibling);
+            current_hash = hash_node_with_profile(profile, current_hash, *sibling);
+        }
+    }
+    current_hash == root
+}
+
+/// A Merkle inclusion proof for an arity-4 tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitMerkleProof4 {
+    /// The leaf value being proven for inclusion.
+    pub leaf: F,
+    /// The three other children of each node on the path, in left-to-right order.
+    /// Length should equal the tree depth.
+    pub siblings: Vec<[F; 3]>,
+    /// Position (0..4) of the current node among its parent's children at each level.
+    /// These are the 2-bit digits of the leaf index, least significant first.
+    pub path_indices: Vec<u8>,
+}
+
+impl CircuitMerkleProof4 {
+    /// Siblings flattened level by level, as allocated by the circuit.
+    pub fn flat_siblings(&self) -> Vec<F> {
+        self.siblings.iter().flatten().copied().collect()
+    }
+}
+
+/// Generates a `CircuitMerkleProof4` for the leaf at `leaf_index` of an arity-4 tree and
+/// pads it to the specified `depth`.
+///
+/// Returns an error if the leaf index is out of bounds.
+pub fn get_padded_proof4_for_leaf(
+    tree: &MerkleTree,
+    leaf_index: usize,
+    depth: usize,
+) -> Result<CircuitMerkleProof4, KontorPoRError> {
+    get_padded_proof4_for_leaf_with_profile(tree, leaf_index, depth, HashProfile::Standard)
+}
+
+/// Like [`get_padded_proof4_for_leaf`], for a tree hashed under `profile`.
+///
+/// The profile is only used to recompute siblings from pruned layers.
+pub fn get_padded_proof4_for_leaf_with_profile(
+    tree: &MerkleTree,
+    leaf_index: usize,
+    depth: usize,
+    profile: HashProfile,
+) -> Result<CircuitMerkleProof4, KontorPoRError> {
+    let leaf = tree
+        .layers
+        .first()
+        .and_then(|layer| layer.get(leaf_index))
+        .copied()
+        .ok_or_else(|| KontorPoRError::IndexOutOfBounds {
+            index: leaf_index,
+            length: tree.layers.first().map(|l| l.len()).unwrap_or(0),
+        })?;
+    let mut siblings = Vec::new();
+    let mut path_indices = Vec::new();
+
+    let mut current_index = leaf_index;
+    for level in 0..tree.layers.len() - 1 {
+        let position = current_index % 4;
+        let first_child = current_index - position;
+
+        // Missing children are the zero padding used by build_tree_from_leaves_arity4
+        let mut level_siblings = [F::ZERO; 3];
+        let others = (0..4).filter(|&child| child != position);
+        for (slot, child) in level_siblings.iter_mut().zip(others) {
+            *slot = tree
+                .node(level, first_child + child, TreeArity::Arity4, profile)
+                .unwrap_or(F::ZERO);
+        }
+
+        siblings.push(level_siblings);
+        path_indices.push(position as u8);
+        current_index /= 4;
+    }
+
+    siblings.resize(depth, [F::ZERO; 3]);
+    path_indices.resize(depth, 0);
+
+    Ok(CircuitMerkleProof4 {
+        leaf,
+        siblings,
+        path_indices,
+    })
+}
+
+/// Verifies a `CircuitMerkleProof4` against a given root.
+///
+/// At each level the current node is inserted at `path_indices[i]` among the three
+/// siblings and the parent is `hash_node4` over the resulting four children.
+pub fn verify_merkle_proof4_in_place(root: F, proof: &CircuitMerkleProof4) -> bool {
+    verify_merkle_proof4_in_place_with_profile(root, proof, HashProfile::Standard)
+}
+
+/// Like [`verify_merkle_proof4_in_place`], for a tree hashed under `profile`.
+pub fn verify_merkle_proof4_in_place_with_profile(
+    root: F,
+    proof: &CircuitMerkleProof4,
+    profile: HashProfile,
+) -> bool {
+    let mut current_hash = proof.leaf;
+    for (i, level_siblings) in proof.siblings.iter().enumerate() {
+        let position = proof.path_indices.get(i).copied().unwrap_or(0) as usize;
+        if position > 3 {
+            return false;
+        }
+        let mut children = [F::ZERO; 4];
+        let mut others = level_siblings.iter();
+        for (child, slot) in children.iter_mut().enumerate() {
+            *slot = if child == position {
+                current_hash
+            } else {
+                *others.next().expect("three siblings per level")
+            };
         }
+        current_hash = hash_node4_with_profile(profile, children);
     }
     current_hash == root
 }
@@ -3,6 +3,7 @@
 //! This module provides shared metric types used by both the simulator and benchmark suite,
 //! with CLI-friendly display formatters for human-readable output.
 
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 #[cfg(feature = "memory-profiling")]
@@ -126,6 +127,99 @@ impl ProofMetrics {
     }
 }
 
+/// Phase durations of a single proof generation, as reported by
+/// [`PorSystem::prove_with_timings`](crate::api::PorSystem::prove_with_timings).
+///
+/// Witness generation is interleaved with folding, so `witness_gen_duration` is the sum
+/// over all steps and is not included in `folding_duration`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProveTimings {
+    /// Input validation and plan construction
+    pub plan_duration: Duration,
+    /// Parameter load (or generation on a cache miss) for the plan's shape
+    pub param_load_duration: Duration,
+    /// Witness generation across all steps
+    pub witness_gen_duration: Duration,
+    /// `RecursiveSNARK::new` and all `prove_step` calls
+    pub folding_duration: Duration,
+    /// `CompressedSNARK::prove`
+    pub compression_duration: Duration,
+}
+
+impl ProveTimings {
+    /// Sum of all phases
+    pub fn total(&self) -> Duration {
+        self.plan_duration
+            + self.param_load_duration
+            + self.witness_gen_duration
+            + self.folding_duration
+            + self.compression_duration
+    }
+}
+
+/// Phase of proof generation or verification reported to a [`MetricsSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Input validation and plan construction (prove and verify)
+    Plan,
+    /// Parameter load or generation for a circuit shape
+    ParamLoad,
+    /// Witness generation for one recursive step
+    WitnessGen,
+    /// `RecursiveSNARK::new` (step 0) or one `prove_step` call
+    Fold,
+    /// `CompressedSNARK::prove`
+    Compress,
+    /// `CompressedSNARK::verify`
+    SnarkVerify,
+}
+
+/// A single timing event reported to a [`MetricsSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingEvent {
+    pub phase: Phase,
+    /// Recursive step index for per-step phases (`WitnessGen`, `Fold`)
+    pub step: Option<usize>,
+    pub duration: Duration,
+}
+
+/// Receiver for timing events from `prove`, `verify` and parameter loading.
+///
+/// Lets embedding applications attribute latency without depending on `tracing`.
+/// Register a sink process-wide with [`set_metrics_sink`]; `record` is called
+/// synchronously on the proving thread, so implementations should be cheap.
+pub trait MetricsSink: Send + Sync {
+    fn record(&self, event: TimingEvent);
+}
+
+static METRICS_SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+
+/// Register (or with `None`, remove) the process-wide [`MetricsSink`].
+pub fn set_metrics_sink(sink: Option<Arc<dyn MetricsSink>>) {
+    *METRICS_SINK
+        .write()
+        .expect("Metrics sink lock should not be poisoned") = sink;
+}
+
+/// The currently registered [`MetricsSink`], if any.
+pub(crate) fn metrics_sink() -> Option<Arc<dyn MetricsSink>> {
+    METRICS_SINK
+        .read()
+        .expect("Metrics sink lock should not be poisoned")
+        .clone()
+}
+
+/// Report a timing event to the registered sink, if any.
+pub(crate) fn record_timing(phase: Phase, step: Option<usize>, duration: Duration) {
+    if let Some(sink) = metrics_sink() {
+        sink.record(TimingEvent {
+            phase,
+            step,
+            duration,
+        });
+    }
+}
+
 /// Metrics collected during verification
 #[derive(Debug, Clone)]
 pub struct VerificationMetrics {
@@ -227,6 +321,97 @@ impl EconomicMetrics {
     }
 }
 
+/// Heap memory held by prepared files, in-flight proofs and ledgers, for capacity planning.
+///
+/// Sizes come from [`PreparedFile::heap_size`](crate::api::PreparedFile::heap_size),
+/// [`Proof::heap_size`](crate::api::Proof::heap_size) and
+/// [`FileLedger::heap_size`](crate::ledger::FileLedger::heap_size). Proving needs more
+/// memory than this while it runs (witnesses, parameters); the report covers what stays
+/// resident.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub prepared_files: usize,
+    pub prepared_file_bytes: usize,
+    pub proofs: usize,
+    pub proof_bytes: usize,
+    pub ledgers: usize,
+    pub ledger_bytes: usize,
+}
+
+impl MemoryReport {
+    /// Counts one prepared file.
+    pub fn add_prepared_file(&mut self, file: &crate::api::PreparedFile) -> &mut Self {
+        self.prepared_files += 1;
+        self.prepared_file_bytes += file.heap_size();
+        self
+    }
+
+    /// Counts one proof.
+    pub fn add_proof(&mut self, proof: &crate::api::Proof) -> &mut Self {
+        self.proofs += 1;
+        self.proof_bytes += proof.heap_size();
+        self
+    }
+
+    /// Counts one ledger.
+    pub fn add_ledger(&mut self, ledger: &crate::ledger::FileLedger) -> &mut Self {
+        self.ledgers += 1;
+        self.ledger_bytes += ledger.heap_size();
+        self
+    }
+
+    /// Total bytes across everything counted.
+    pub fn total_bytes(&self) -> usize {
+        self.prepared_file_bytes + self.proof_bytes + self.ledger_bytes
+    }
+
+    /// Format the report as a table for CLI output
+    pub fn format_table(&self) -> String {
+        let mut output = String::new();
+        output.push_str("  ┌─────────────────────────────────────────────────────┐\n");
+        output.push_str("  │ Resident Data          │ Count     │ Heap Size      │\n");
+        output.push_str("  ├────────────────────────┼───────────┼────────────────┤\n");
+        for (label, count, bytes) in [
+            (
+                "Prepared Files",
+                self.prepared_files,
+                self.prepared_file_bytes,
+            ),
+            ("Proofs", self.proofs, self.proof_bytes),
+            ("Ledgers", self.ledgers, self.ledger_bytes),
+        ] {
+            output.push_str(&format!(
+                "  │ {:<22} │ {:>9} │ {:>14} │\n",
+                label,
+                count,
+                format_bytes(bytes)
+            ));
+        }
+        output.push_str("  ├────────────────────────┼───────────┼────────────────┤\n");
+        output.push_str(&format!(
+            "  │ Total                  │           │ {:>14} │\n",
+            format_bytes(self.total_bytes())
+        ));
+        output.push_str("  └─────────────────────────────────────────────────────┘\n");
+        output
+    }
+}
+
+/// Byte count with a binary unit, e.g. `1.5 MB`
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
 /// Challenge information for display
 #[derive(Debug, Clone)]
 pub struct ChallengeInfo {
@@ -317,6 +502,24 @@ mod tests {
         assert!((metrics.amortized_cost_per_challenge - 0.10).abs() < 0.01);
     }
 
+    #[test]
+    fn test_memory_report_format() {
+        let report = MemoryReport {
+            prepared_files: 2,
+            prepared_file_bytes: 3 << 20,
+            proofs: 1,
+            proof_bytes: 10 * 1024,
+            ledgers: 1,
+            ledger_bytes: 512,
+        };
+        assert_eq!(report.total_bytes(), (3 << 20) + 10 * 1024 + 512);
+        let table = report.format_table();
+        assert!(table.contains("3.0 MB"));
+        assert!(table.contains("10.0 KB"));
+        assert!(table.contains("512 B"));
+        assert_eq!(format_bytes(5 << 30), "5.0 GB");
+    }
+
     #[test]
     fn test_file_size_categories() {
         let small = FileSizeCategory::Small.sample_size(42);
@@ -2,9 +2,33 @@
 //!
 //! This module provides in-memory caching for Nova parameters,
 //! allowing efficient reuse of expensive parameter generation within a session.
-//! Parameters are uniquely determined by (files_per_step, file_tree_depth, aggregated_tree_depth).
+//! Parameters are uniquely determined by a [`crate::config::Shape`] (files_per_step,
+//! file_tree_depth, aggregated_tree_depth) plus the challenge mode (public or private leaf outputs), whether per-step seeds are used,
+//! the file tree arity and the circuit mode (lenient or strict ledger index checks).
+//!
+//! When a cache directory is configured (via [`set_cache_dir`] or the `KONTOR_PARAMS_DIR`
+//! environment variable), generated parameters are also persisted to disk so later processes
+//! can skip generation. Writers take a per-shape lock file and publish with an atomic rename,
+//! so concurrent processes never observe a partially written parameter file. Processes that
+//! miss the cache at the same time wait for the lock (up to [`lock_timeout`]) and then load
+//! the winner's file. Each file ends with a SHA-256 checksum of its contents; files that fail
+//! the checksum (or otherwise fail to load) are deleted and regenerated. Loading streams the
+//! file: a header or shape mismatch is detected before the parameters are read, and the
+//! checksum is computed as they are deserialized, so the file is never buffered whole.
+//! [`pregenerate`] fills the cache ahead of time for a list of shapes; [`cache_stats`] lists
+//! the cached shapes, [`peek_header`] reads the shape of a single file and [`gc`] deletes
+//! the ones a [`GcPolicy`] no longer wants.
 
-use crate::{api::PorParams, circuit::PorCircuit, ledger::FileLedger, KontorPoRError, Result};
+use crate::instrumentation::{debug, debug_span, field, info, info_span, warn, Span};
+use crate::{
+    api::{ChallengeMode, ErasureCode, PorParams},
+    circuit::{CircuitMode, PorCircuit},
+    ledger::FileLedger,
+    merkle::TreeArity,
+    metrics::{record_timing, Phase},
+    poseidon::HashProfile,
+    KontorPoRError, Result,
+};
 use nova_snark::{
     nova::{CompressedSNARK, PublicParams},
     provider::{ipa_pc, PallasEngine, VestaEngine},
@@ -12,9 +36,15 @@ use nova_snark::{
     traits::{snark::RelaxedR1CSSNARKTrait, Engine},
 };
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use tracing::{debug, info};
+use std::time::{Duration, Instant, SystemTime};
 
 // Type aliases for readability
 type E1 = PallasEngine;
@@ -25,15 +55,337 @@ type S1 = RelaxedR1CSSNARK<E1, EE1>;
 type S2 = RelaxedR1CSSNARK<E2, EE2>;
 type F1 = <E1 as Engine>::Scalar;
 type C = PorCircuit<F1>;
+type PK = nova_snark::nova::ProverKey<E1, E2, C, S1, S2>;
+type VK = nova_snark::nova::VerifierKey<E1, E2, C, S1, S2>;
 
 /// Cache key for storing parameters. Parameters depend on the complete circuit shape.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct ParamKey {
     files_per_step: usize,
     file_tree_depth: usize,
     aggregated_tree_depth: usize,
+    mode: ChallengeMode,
+    per_step_seeds: bool,
+    explicit_indices: bool,
+    partitioned: bool,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+    circuit_mode: CircuitMode,
+}
+
+impl ParamKey {
+    /// The dimensions of this shape.
+    fn dims(&self) -> crate::config::Shape {
+        crate::config::Shape::new(
+            self.files_per_step,
+            self.file_tree_depth,
+            self.aggregated_tree_depth,
+        )
+    }
+
+    /// File name of this shape's entry in the on-disk cache.
+    fn file_name(&self) -> String {
+        let mode = if self.mode.is_private() {
+            "private"
+        } else {
+            "public"
+        };
+        let seeds = if self.per_step_seeds {
+            "_stepseeds"
+        } else {
+            ""
+        };
+        let explicit = if self.explicit_indices {
+            "_explicit"
+        } else {
+            ""
+        };
+        let passes = if self.partitioned { "_passes" } else { "" };
+        let arity = match self.tree_arity {
+            TreeArity::Binary => "",
+            TreeArity::Arity4 => "_a4",
+        };
+        let profile = match self.hash_profile {
+            HashProfile::Standard => "",
+            HashProfile::Conservative => "_hc",
+        };
+        let strict = if self.circuit_mode.is_strict() {
+            "_strict"
+        } else {
+            ""
+        };
+        format!(
+            "por_{}{}{}{}{}{}{}_{}_v{}.params",
+            mode,
+            seeds,
+            explicit,
+            passes,
+            arity,
+            profile,
+            strict,
+            self.dims(),
+            disk_format::VERSION
+        )
+    }
+}
+
+impl From<Shape> for ParamKey {
+    fn from(shape: Shape) -> Self {
+        Self {
+            files_per_step: shape.files_per_step,
+            file_tree_depth: shape.file_tree_depth,
+            aggregated_tree_depth: shape.aggregated_tree_depth,
+            mode: shape.mode,
+            per_step_seeds: shape.per_step_seeds,
+            explicit_indices: shape.explicit_indices,
+            partitioned: shape.partitioned,
+            tree_arity: shape.tree_arity,
+            hash_profile: shape.hash_profile,
+            circuit_mode: shape.circuit_mode,
+        }
+    }
+}
+
+/// A circuit shape for which parameters can be generated: the dimensions of a
+/// [`crate::config::Shape`] and the circuit variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Shape {
+    /// Number of file slots in the circuit (power of 2)
+    pub files_per_step: usize,
+    /// Maximum Merkle tree depth for files
+    pub file_tree_depth: usize,
+    /// Depth of the aggregation tree (0 for single-file)
+    pub aggregated_tree_depth: usize,
+    /// How challenged leaves are exposed
+    pub mode: ChallengeMode,
+    /// Whether challenges use per-step seed schedules
+    pub per_step_seeds: bool,
+    /// Whether challenges open explicit leaf indices instead of seed-derived ones
+    pub explicit_indices: bool,
+    /// Whether the challenged files are partitioned into several passes over the slots
+    pub partitioned: bool,
+    /// Branching factor of the file trees (`file_tree_depth` counts levels of this arity)
+    pub tree_arity: TreeArity,
+    /// Poseidon instantiation of the file trees and root commitments
+    pub hash_profile: HashProfile,
+    /// Whether ledger index checks are enforced in-circuit
+    pub circuit_mode: CircuitMode,
+}
+
+impl Shape {
+    /// Create a public-mode shape.
+    pub fn new(
+        files_per_step: usize,
+        file_tree_depth: usize,
+        aggregated_tree_depth: usize,
+    ) -> Self {
+        Self {
+            files_per_step,
+            file_tree_depth,
+            aggregated_tree_depth,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
+        }
+    }
+
+    /// Returns this shape with the given challenge mode.
+    pub fn with_mode(mut self, mode: ChallengeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns this shape with per-step seed schedules enabled or disabled.
+    pub fn with_per_step_seeds(mut self, per_step_seeds: bool) -> Self {
+        self.per_step_seeds = per_step_seeds;
+        self
+    }
+
+    /// Returns this shape with explicit challenge indices enabled or disabled.
+    pub fn with_explicit_indices(mut self, explicit_indices: bool) -> Self {
+        self.explicit_indices = explicit_indices;
+        self
+    }
+
+    /// Returns this shape with partitioned passes enabled or disabled.
+    pub fn with_partitioned(mut self, partitioned: bool) -> Self {
+        self.partitioned = partitioned;
+        self
+    }
+
+    /// Returns this shape with the given file tree arity.
+    pub fn with_tree_arity(mut self, tree_arity: TreeArity) -> Self {
+        self.tree_arity = tree_arity;
+        self
+    }
+
+    /// Returns this shape with the given file tree hash profile.
+    pub fn with_hash_profile(mut self, hash_profile: HashProfile) -> Self {
+        self.hash_profile = hash_profile;
+        self
+    }
+
+    /// Returns this shape with the given circuit mode.
+    pub fn with_circuit_mode(mut self, circuit_mode: CircuitMode) -> Self {
+        self.circuit_mode = circuit_mode;
+        self
+    }
+
+    /// The dimensions of this shape.
+    pub fn dims(&self) -> crate::config::Shape {
+        crate::config::Shape::new(
+            self.files_per_step,
+            self.file_tree_depth,
+            self.aggregated_tree_depth,
+        )
+    }
+
+    /// Cartesian product of the given dimensions, in (files, depth, agg) order.
+    pub fn matrix(
+        files_per_step: &[usize],
+        file_tree_depths: &[usize],
+        aggregated_tree_depths: &[usize],
+        mode: ChallengeMode,
+    ) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for &files in files_per_step {
+            for &depth in file_tree_depths {
+                for &agg in aggregated_tree_depths {
+                    shapes.push(Shape::new(files, depth, agg).with_mode(mode));
+                }
+            }
+        }
+        shapes
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.dims().validate()
+    }
+}
+
+/// A public-mode shape with the given dimensions, as [`Shape::new`].
+impl From<crate::config::Shape> for Shape {
+    fn from(shape: crate::config::Shape) -> Self {
+        Self::new(
+            shape.files_per_step,
+            shape.file_tree_depth,
+            shape.aggregated_tree_depth,
+        )
+    }
+}
+
+/// Where a parameter set came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamSource {
+    Memory,
+    Disk,
+    Generated,
+}
+
+/// Outcome of pregenerating a single shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PregenStatus {
+    /// Parameters were generated and cached by this call
+    Generated,
+    /// Parameters were already cached (in memory or on disk) and were skipped
+    Cached,
+}
+
+/// Per-shape result of [`pregenerate`].
+#[derive(Debug, Clone)]
+pub struct PregenEntry {
+    /// The shape this entry describes
+    pub shape: Shape,
+    /// Whether the shape was generated or already cached
+    pub status: PregenStatus,
+    /// Wall-clock time spent on this shape
+    pub elapsed: Duration,
+    /// Size of the on-disk cache file, if a cache directory is configured
+    pub cache_file_size: Option<u64>,
+}
+
+/// Summary returned by [`pregenerate`], one entry per requested shape in input order.
+#[derive(Debug, Clone, Default)]
+pub struct PregenReport {
+    /// Per-shape results
+    pub entries: Vec<PregenEntry>,
+}
+
+impl PregenReport {
+    /// Number of shapes generated by this call.
+    pub fn generated(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status == PregenStatus::Generated)
+            .count()
+    }
+
+    /// Number of shapes skipped because they were already cached.
+    pub fn skipped(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status == PregenStatus::Cached)
+            .count()
+    }
+
+    /// Total bytes of on-disk cache files covered by this report.
+    pub fn total_cache_bytes(&self) -> u64 {
+        self.entries.iter().filter_map(|e| e.cache_file_size).sum()
+    }
+}
+
+/// Constants for the on-disk parameter format
+mod disk_format {
+    /// Magic bytes identifying a cached parameter file
+    pub const MAGIC: &[u8] = b"KPAR";
+
+    /// Current format version (also part of the file name)
+    ///
+    /// Version 9 adds the public IO schema version to the header.
+    pub const VERSION: u16 = 10;
+
+    /// Magic, format version and public IO schema version
+    pub const HEADER_LEN: usize = 4 + 2 + 4;
+
+    /// Length of the trailing SHA-256 checksum over the serialized parameters
+    pub const CHECKSUM_LEN: usize = 32;
 }
 
+/// Environment variable used to initialise the on-disk cache directory.
+pub const CACHE_DIR_ENV: &str = "KONTOR_PARAMS_DIR";
+
+/// Environment variable overriding the default lock timeout, in seconds.
+pub const LOCK_TIMEOUT_ENV: &str = "KONTOR_PARAMS_LOCK_TIMEOUT_SECS";
+
+/// How long to wait for another writer's shape lock before giving up.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How long to wait between attempts to take a shape lock held by another writer.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Lock files older than this are assumed to belong to a crashed writer.
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Directory for the on-disk cache (None disables disk caching).
+static CACHE_DIR: Lazy<Mutex<Option<PathBuf>>> =
+    Lazy::new(|| Mutex::new(std::env::var_os(CACHE_DIR_ENV).map(PathBuf::from)));
+
+/// Maximum time to wait for a shape lock held by another writer.
+static LOCK_TIMEOUT: Lazy<Mutex<Duration>> = Lazy::new(|| {
+    let from_env = std::env::var(LOCK_TIMEOUT_ENV)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs);
+    Mutex::new(from_env.unwrap_or(DEFAULT_LOCK_TIMEOUT))
+});
+
+/// Number of parameter sets generated from scratch by this process.
+static GENERATED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 /// Maximum number of parameter sets to cache in memory.
 /// Prevents unbounded memory growth from many unique circuit shapes.
 const MAX_CACHE_SIZE: usize = 50;
@@ -45,18 +397,37 @@ static MEMORY_CACHE: Lazy<Mutex<HashMap<ParamKey, PorParams>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Generate new parameters for the given shape.
-fn generate_params_for_shape(
-    files_per_step: usize,
-    file_tree_depth: usize,
-    aggregated_tree_depth: usize,
-) -> Result<PorParams> {
+fn generate_params_for_shape(key: &ParamKey) -> Result<PorParams> {
     use crate::api::generate_circuit_witness;
     use crate::api::{Challenge, FieldElement, FileMetadata};
     use ff::Field;
 
+    let ParamKey {
+        files_per_step,
+        file_tree_depth,
+        aggregated_tree_depth,
+        mode,
+        per_step_seeds,
+        explicit_indices,
+        partitioned,
+        tree_arity,
+        hash_profile,
+        circuit_mode,
+    } = *key;
+
+    let _span = info_span!(
+        "generate_params",
+        files_per_step,
+        file_tree_depth,
+        aggregated_tree_depth,
+        primary_constraints = field::Empty,
+        secondary_constraints = field::Empty,
+    )
+    .entered();
+
     info!(
-        "Generating new parameters for shape {}x{} with agg_depth={}",
-        files_per_step, file_tree_depth, aggregated_tree_depth
+        "Generating new parameters for shape {}x{} with agg_depth={} ({:?} mode, per_step_seeds={}, explicit_indices={}, partitioned={}, {:?} trees, {} hashing, {:?} circuit)",
+        files_per_step, file_tree_depth, aggregated_tree_depth, mode, per_step_seeds, explicit_indices, partitioned, tree_arity, hash_profile, circuit_mode
     );
 
     // Create dummy challenges for parameter generation
@@ -66,12 +437,16 @@ fn generate_params_for_shape(
                 root: FieldElement::ZERO,
                 file_id: format!("dummy{}", i),
                 padded_len: if i == 0 {
-                    1 << file_tree_depth // First file at max depth
+                    1 << tree_arity.index_bits(file_tree_depth) // First file at max depth
                 } else {
                     1 // Other files at minimal depth for diversity
                 },
                 original_size: 0,
-                filename: format!("dummy{}.dat", i),
+                annotations: Default::default(),
+                tree_arity,
+                hash_profile,
+                erasure: Some(ErasureCode::ReedSolomon),
+                salt_commitment: None,
             };
             Challenge::new(
                 metadata,
@@ -109,22 +484,30 @@ fn generate_params_for_shape(
     )?;
 
     // Create the circuit with the generated witness
-    let circuit_primary = C::new(
-        files_per_step,
-        file_tree_depth,
-        aggregated_tree_depth,
-        Some(circuit_witness.witnesses().to_vec()),
-    );
+    let circuit_primary = C::new(key.dims(), Some(circuit_witness.witnesses().to_vec()))
+        .with_private_leaves(mode.is_private())
+        .with_step_seeds(per_step_seeds.then(Vec::new))
+        .with_explicit_indices(explicit_indices.then(Vec::new))
+        .with_pass_inputs(partitioned.then(Vec::new))
+        .with_tree_arity(tree_arity)
+        .with_hash_profile(hash_profile)
+        .with_circuit_mode(circuit_mode);
 
     // Generate public params
     let pp = PublicParams::<E1, E2, C>::setup(&circuit_primary, &*S1::ck_floor(), &*S2::ck_floor())
         .map_err(|e| KontorPoRError::Snark(format!("Failed to setup public params: {:?}", e)))?;
+    let (primary_constraints, secondary_constraints) = pp.num_constraints();
+    Span::current().record("primary_constraints", primary_constraints);
+    Span::current().record("secondary_constraints", secondary_constraints);
 
     // Generate compressed SNARK keys
     let (pk, vk) = CompressedSNARK::setup(&pp).map_err(|e| {
         KontorPoRError::Snark(format!("Failed to setup compressed SNARK keys: {:?}", e))
     })?;
 
+    GENERATED_COUNT.fetch_add(1, Ordering::Relaxed);
+    fail_point!(crate::failpoints::points::PARAMS_AFTER_GENERATION);
+
     Ok(PorParams {
         pp: Arc::new(pp),
         keys: crate::api::KeyPair {
@@ -134,30 +517,59 @@ fn generate_params_for_shape(
         file_tree_depth,
         max_supported_depth: file_tree_depth,
         aggregated_tree_depth,
+        challenge_mode: mode,
+        schema_version: crate::config::PUBLIC_IO_SCHEMA_VERSION,
     })
 }
 
 /// Load or generate parameters for the given shape.
 /// This is the main entry point for getting parameters.
 ///
-/// # Arguments
-/// * `files_per_step` - Number of file slots in the circuit (power of 2)
-/// * `file_tree_depth` - Maximum Merkle tree depth for files
-/// * `aggregated_tree_depth` - Depth of the aggregation tree (0 for single-file)
-///
 /// # Returns
 /// Parameters configured for the exact shape, either from cache or newly generated.
-pub fn load_or_generate_params(
-    files_per_step: usize,
-    file_tree_depth: usize,
-    aggregated_tree_depth: usize,
+pub fn load_or_generate_params(shape: crate::config::Shape) -> Result<PorParams> {
+    load_or_generate_params_for_mode(shape, ChallengeMode::Public)
+}
+
+/// Load or generate parameters for the given shape and challenge mode.
+///
+/// Private-mode circuits expose blinded leaf commitments instead of raw leaves and
+/// therefore have a different constraint system from public-mode circuits.
+pub fn load_or_generate_params_for_mode(
+    shape: crate::config::Shape,
+    mode: ChallengeMode,
 ) -> Result<PorParams> {
-    let key = ParamKey {
-        files_per_step,
-        file_tree_depth,
-        aggregated_tree_depth,
-    };
+    load_or_generate_params_for_shape(&Shape::from(shape).with_mode(mode))
+}
+
+/// Load or generate parameters for a complete circuit shape, including the challenge
+/// mode, seed schedule kind and file tree arity.
+pub fn load_or_generate_params_for_shape(shape: &Shape) -> Result<PorParams> {
+    load_or_generate_with_source(ParamKey::from(*shape)).map(|(params, _)| params)
+}
+
+fn load_or_generate_with_source(key: ParamKey) -> Result<(PorParams, ParamSource)> {
+    let span = debug_span!(
+        "load_params",
+        files_per_step = key.files_per_step,
+        file_tree_depth = key.file_tree_depth,
+        aggregated_tree_depth = key.aggregated_tree_depth,
+        source = field::Empty,
+    );
+    let _enter = span.enter();
+    let start = Instant::now();
+
+    let result = lookup_or_generate(key);
 
+    record_timing(Phase::ParamLoad, None, start.elapsed());
+    if let Ok((_, source)) = &result {
+        span.record("source", field::debug(source));
+    }
+    result
+}
+
+/// Memory cache, then disk cache (if configured), then generation.
+fn lookup_or_generate(key: ParamKey) -> Result<(PorParams, ParamSource)> {
     // Check memory cache first
     {
         let cache = MEMORY_CACHE
@@ -165,12 +577,15 @@ pub fn load_or_generate_params(
             .expect("Parameter cache mutex should not be poisoned");
         if let Some(params) = cache.get(&key) {
             debug!("Using memory-cached parameters for {:?}", key);
-            return Ok(params.clone());
+            return Ok((params.clone(), ParamSource::Memory));
         }
     }
 
-    // Generate new parameters
-    let params = generate_params_for_shape(files_per_step, file_tree_depth, aggregated_tree_depth)?;
+    // Then the disk cache, generating (under a shape lock) on a miss
+    let (params, source) = match cache_dir() {
+        Some(dir) => load_or_generate_on_disk(&dir, &key)?,
+        None => (generate_params_for_shape(&key)?, ParamSource::Generated),
+    };
 
     // Store in memory cache with size limit
     {
@@ -189,7 +604,508 @@ pub fn load_or_generate_params(
         cache.insert(key, params.clone());
     }
 
-    Ok(params)
+    Ok((params, source))
+}
+
+fn load_or_generate_on_disk(dir: &Path, key: &ParamKey) -> Result<(PorParams, ParamSource)> {
+    let path = dir.join(key.file_name());
+    match read_cached_params(&path, key) {
+        CacheRead::Hit(params) => return Ok((params, ParamSource::Disk)),
+        CacheRead::OtherSchema(found) => return Err(schema_mismatch(&path, found)),
+        CacheRead::Missing | CacheRead::Corrupt => {}
+    }
+
+    fs::create_dir_all(dir).map_err(|e| {
+        KontorPoRError::IO(format!(
+            "Failed to create parameter cache directory {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let _lock = ShapeLock::acquire(path.with_extension("lock"), lock_timeout())?;
+
+    // Another writer may have published this shape while we waited for the lock.
+    // A file that still fails to load under the lock is corrupt, not half-written.
+    match read_cached_params(&path, key) {
+        CacheRead::Hit(params) => return Ok((params, ParamSource::Disk)),
+        CacheRead::OtherSchema(found) => return Err(schema_mismatch(&path, found)),
+        CacheRead::Missing => {}
+        CacheRead::Corrupt => {
+            warn!("Deleting corrupt cached parameters {}", path.display());
+            fs::remove_file(&path).map_err(|e| {
+                KontorPoRError::IO(format!(
+                    "Failed to delete corrupt cached parameters {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+    }
+
+    let params = generate_params_for_shape(key)?;
+    write_cached_params(&path, key, &params)?;
+    Ok((params, ParamSource::Generated))
+}
+
+/// A cache file of another public IO schema version is kept for the operator to clear
+/// (e.g. with [`gc`]) rather than silently replaced.
+fn schema_mismatch(path: &Path, found: u32) -> KontorPoRError {
+    warn!(
+        "Cached parameters {} were generated for public IO schema version {}",
+        path.display(),
+        found
+    );
+    KontorPoRError::SchemaVersionMismatch {
+        expected: crate::config::PUBLIC_IO_SCHEMA_VERSION,
+        found,
+    }
+}
+
+/// On-disk representation (serialization side, borrowing from live params).
+#[derive(Serialize)]
+struct CachedParamsRef<'a> {
+    files_per_step: usize,
+    file_tree_depth: usize,
+    aggregated_tree_depth: usize,
+    mode: ChallengeMode,
+    per_step_seeds: bool,
+    explicit_indices: bool,
+    partitioned: bool,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+    circuit_mode: CircuitMode,
+    pp: &'a PublicParams<E1, E2, C>,
+    pk: &'a PK,
+    vk: &'a VK,
+}
+
+/// Parameters following [`CachedShape`] in a cache file (deserialization side).
+///
+/// [`CachedParamsRef`] serializes as the shape fields followed by these, so a reader can
+/// check the shape before deserializing the (much larger) parameters.
+#[derive(Deserialize)]
+struct CachedBody {
+    pp: PublicParams<E1, E2, C>,
+    pk: PK,
+    vk: VK,
+}
+
+/// Leading shape fields of [`CachedParamsRef`], read without the parameters themselves.
+#[derive(Deserialize)]
+struct CachedShape {
+    files_per_step: usize,
+    file_tree_depth: usize,
+    aggregated_tree_depth: usize,
+    mode: ChallengeMode,
+    per_step_seeds: bool,
+    explicit_indices: bool,
+    partitioned: bool,
+    tree_arity: TreeArity,
+    hash_profile: HashProfile,
+    circuit_mode: CircuitMode,
+}
+
+impl From<CachedShape> for Shape {
+    fn from(cached: CachedShape) -> Self {
+        Self {
+            files_per_step: cached.files_per_step,
+            file_tree_depth: cached.file_tree_depth,
+            aggregated_tree_depth: cached.aggregated_tree_depth,
+            mode: cached.mode,
+            per_step_seeds: cached.per_step_seeds,
+            explicit_indices: cached.explicit_indices,
+            partitioned: cached.partitioned,
+            tree_arity: cached.tree_arity,
+            hash_profile: cached.hash_profile,
+            circuit_mode: cached.circuit_mode,
+        }
+    }
+}
+
+pub(crate) fn bincode_options() -> impl bincode::Options {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+}
+
+/// Result of reading a cached parameter file.
+enum CacheRead {
+    Hit(PorParams),
+    Missing,
+    /// Present but unusable: bad header, checksum, encoding or shape
+    Corrupt,
+    /// Intact, but generated for circuits of another public IO schema version
+    OtherSchema(u32),
+}
+
+/// Writer adapter that hashes everything written through it.
+pub(crate) struct HashingWriter<W> {
+    pub(crate) inner: W,
+    pub(crate) hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader adapter that hashes everything read through it.
+pub(crate) struct HashingReader<R> {
+    pub(crate) inner: R,
+    pub(crate) hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Read a cached parameter file. Missing files yield [`CacheRead::Missing`]; unreadable,
+/// corrupted or mismatched files yield [`CacheRead::Corrupt`] so the caller can delete
+/// and regenerate them. Files of another public IO schema version yield
+/// [`CacheRead::OtherSchema`].
+fn read_cached_params(path: &Path, key: &ParamKey) -> CacheRead {
+    use bincode::Options;
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return CacheRead::Missing,
+        Err(e) => {
+            warn!("Failed to open cached parameters {}: {}", path.display(), e);
+            return CacheRead::Corrupt;
+        }
+    };
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; disk_format::HEADER_LEN];
+    if reader.read_exact(&mut header).is_err()
+        || &header[..4] != disk_format::MAGIC
+        || u16::from_le_bytes([header[4], header[5]]) != disk_format::VERSION
+    {
+        warn!(
+            "Ignoring cached parameters with bad header: {}",
+            path.display()
+        );
+        return CacheRead::Corrupt;
+    }
+    let schema_version = u32::from_le_bytes(header[6..].try_into().unwrap());
+    if schema_version != crate::config::PUBLIC_IO_SCHEMA_VERSION {
+        return CacheRead::OtherSchema(schema_version);
+    }
+
+    // Everything after the header is hashed as it streams through, so the body is never
+    // held in memory twice and the checksum needs no second pass
+    let mut hashing = HashingReader {
+        inner: reader,
+        hasher: Sha256::new(),
+    };
+    let stored_key = match bincode_options().deserialize_from::<_, CachedShape>(&mut hashing) {
+        Ok(cached) => ParamKey::from(Shape::from(cached)),
+        Err(e) => {
+            warn!(
+                "Ignoring unreadable cached parameters {}: {}",
+                path.display(),
+                e
+            );
+            return CacheRead::Corrupt;
+        }
+    };
+    if &stored_key != key {
+        warn!(
+            "Ignoring cached parameters {}: stored shape {:?} does not match {:?}",
+            path.display(),
+            stored_key,
+            key
+        );
+        return CacheRead::Corrupt;
+    }
+    let cached: CachedBody = match bincode_options().deserialize_from(&mut hashing) {
+        Ok(cached) => cached,
+        Err(e) => {
+            warn!(
+                "Ignoring unreadable cached parameters {}: {}",
+                path.display(),
+                e
+            );
+            return CacheRead::Corrupt;
+        }
+    };
+
+    let computed = hashing.hasher.finalize();
+    let mut reader = hashing.inner;
+    let mut stored = [0u8; disk_format::CHECKSUM_LEN];
+    let mut trailing = [0u8; 1];
+    if reader.read_exact(&mut stored).is_err()
+        || stored[..] != computed[..]
+        || !matches!(reader.read(&mut trailing), Ok(0))
+    {
+        warn!(
+            "Ignoring cached parameters with bad checksum: {}",
+            path.display()
+        );
+        return CacheRead::Corrupt;
+    }
+
+    debug!("Loaded parameters for {:?} from {}", key, path.display());
+    CacheRead::Hit(PorParams {
+        pp: Arc::new(cached.pp),
+        keys: crate::api::KeyPair {
+            pk: Arc::new(cached.pk),
+            vk: Arc::new(cached.vk),
+        },
+        file_tree_depth: key.file_tree_depth,
+        max_supported_depth: key.file_tree_depth,
+        aggregated_tree_depth: key.aggregated_tree_depth,
+        challenge_mode: key.mode,
+        schema_version,
+    })
+}
+
+/// Write parameters and their checksum to a temporary file and atomically rename it into
+/// place. Must be called while holding the shape lock.
+fn write_cached_params(path: &Path, key: &ParamKey, params: &PorParams) -> Result<()> {
+    use bincode::Options;
+
+    fail_point!(crate::failpoints::points::PARAMS_BEFORE_CACHE_WRITE);
+
+    let io_err = |e: std::io::Error| {
+        KontorPoRError::IO(format!(
+            "Failed to write cached parameters {}: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&tmp_path).map_err(io_err)?);
+    writer.write_all(disk_format::MAGIC).map_err(io_err)?;
+    writer
+        .write_all(&disk_format::VERSION.to_le_bytes())
+        .map_err(io_err)?;
+    writer
+        .write_all(&params.schema_version.to_le_bytes())
+        .map_err(io_err)?;
+
+    let cached = CachedParamsRef {
+        files_per_step: key.files_per_step,
+        file_tree_depth: key.file_tree_depth,
+        aggregated_tree_depth: key.aggregated_tree_depth,
+        mode: key.mode,
+        per_step_seeds: key.per_step_seeds,
+        explicit_indices: key.explicit_indices,
+        partitioned: key.partitioned,
+        tree_arity: key.tree_arity,
+        hash_profile: key.hash_profile,
+        circuit_mode: key.circuit_mode,
+        pp: &params.pp,
+        pk: &params.keys.pk,
+        vk: &params.keys.vk,
+    };
+    let mut hashing = HashingWriter {
+        inner: writer,
+        hasher: Sha256::new(),
+    };
+    bincode_options()
+        .serialize_into(&mut hashing, &cached)
+        .map_err(|e| {
+            KontorPoRError::Serialization(format!("Failed to serialize parameters: {}", e))
+        })?;
+    let mut writer = hashing.inner;
+    writer
+        .write_all(&hashing.hasher.finalize())
+        .map_err(io_err)?;
+
+    let file = writer.into_inner().map_err(|e| io_err(e.into_error()))?;
+    file.sync_all().map_err(io_err)?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).map_err(io_err)?;
+    info!("Cached parameters for {:?} at {}", key, path.display());
+    Ok(())
+}
+
+/// Exclusive per-shape lock backed by a lock file created with `create_new`.
+/// The lock file is removed when the guard is dropped.
+struct ShapeLock {
+    path: PathBuf,
+}
+
+impl ShapeLock {
+    /// Take the lock, waiting up to `timeout` for another writer to release it.
+    fn acquire(path: PathBuf, timeout: Duration) -> Result<Self> {
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > LOCK_STALE_AFTER);
+                    if stale {
+                        warn!("Removing stale parameter lock {}", path.display());
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if start.elapsed() >= timeout {
+                        return Err(KontorPoRError::ParamsLockTimeout {
+                            path: path.display().to_string(),
+                            timeout,
+                        });
+                    }
+                    debug!(
+                        "Waiting for parameter lock {} ({:.1}s)",
+                        path.display(),
+                        start.elapsed().as_secs_f64()
+                    );
+                    std::thread::sleep(LOCK_POLL_INTERVAL);
+                }
+                Err(e) => {
+                    return Err(KontorPoRError::IO(format!(
+                        "Failed to create parameter lock {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ShapeLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Generate and cache parameters for each shape, skipping shapes that are already cached.
+///
+/// With `parallel` set, shapes are generated concurrently on scoped threads (parameter
+/// generation is memory-hungry, so prefer sequential runs for large shapes). Per-shape
+/// lock files make it safe to run several pregeneration processes against the same
+/// cache directory.
+///
+/// # Returns
+/// A report with one entry per shape, in input order. The first error aborts the report.
+pub fn pregenerate(shapes: &[Shape], parallel: bool) -> Result<PregenReport> {
+    for shape in shapes {
+        shape.validate()?;
+    }
+
+    let entries = if parallel {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = shapes
+                .iter()
+                .map(|shape| scope.spawn(move || pregenerate_shape(*shape)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(KontorPoRError::Snark(
+                            "Parameter generation thread panicked".to_string(),
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })?
+    } else {
+        shapes
+            .iter()
+            .map(|shape| pregenerate_shape(*shape))
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    Ok(PregenReport { entries })
+}
+
+fn pregenerate_shape(shape: Shape) -> Result<PregenEntry> {
+    let start = Instant::now();
+    let status = if is_cached(&shape) {
+        debug!("Skipping cached shape {:?}", shape);
+        PregenStatus::Cached
+    } else {
+        match load_or_generate_with_source(shape.into())?.1 {
+            ParamSource::Generated => PregenStatus::Generated,
+            ParamSource::Memory | ParamSource::Disk => PregenStatus::Cached,
+        }
+    };
+
+    Ok(PregenEntry {
+        shape,
+        status,
+        elapsed: start.elapsed(),
+        cache_file_size: cache_file_path(&shape)
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|m| m.len()),
+    })
+}
+
+/// Returns true if parameters for `shape` are in the memory cache or the disk cache.
+pub fn is_cached(shape: &Shape) -> bool {
+    let key = ParamKey::from(*shape);
+    let in_memory = MEMORY_CACHE
+        .lock()
+        .expect("Parameter cache mutex should not be poisoned")
+        .contains_key(&key);
+    in_memory || cache_file_path(shape).is_some_and(|path| path.is_file())
+}
+
+/// Path of the on-disk cache file for `shape`, if a cache directory is configured.
+pub fn cache_file_path(shape: &Shape) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(ParamKey::from(*shape).file_name()))
+}
+
+/// Set (or with `None`, disable) the on-disk cache directory.
+pub fn set_cache_dir(dir: Option<PathBuf>) {
+    *CACHE_DIR
+        .lock()
+        .expect("Cache dir mutex should not be poisoned") = dir;
+}
+
+/// Get the on-disk cache directory, if any.
+pub fn cache_dir() -> Option<PathBuf> {
+    CACHE_DIR
+        .lock()
+        .expect("Cache dir mutex should not be poisoned")
+        .clone()
+}
+
+/// Set how long a process that misses the disk cache waits for another writer
+/// generating the same shape before failing with [`KontorPoRError::ParamsLockTimeout`].
+///
+/// Defaults to [`DEFAULT_LOCK_TIMEOUT`], or `KONTOR_PARAMS_LOCK_TIMEOUT_SECS` if set.
+pub fn set_lock_timeout(timeout: Duration) {
+    *LOCK_TIMEOUT
+        .lock()
+        .expect("Lock timeout mutex should not be poisoned") = timeout;
+}
+
+/// Get the current shape lock timeout.
+pub fn lock_timeout() -> Duration {
+    *LOCK_TIMEOUT
+        .lock()
+        .expect("Lock timeout mutex should not be poisoned")
+}
+
+/// Number of parameter sets generated from scratch (cache misses) by this process.
+pub fn generated_count() -> usize {
+    GENERATED_COUNT.load(Ordering::Relaxed)
 }
 
 /// Clear the in-memory cache. Useful for testing or memory management.
@@ -209,6 +1125,261 @@ pub fn memory_cache_size() -> usize {
     cache.len()
 }
 
+/// Header of a parameter file, read by [`peek_header`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamsHeader {
+    /// On-disk format version
+    pub format_version: u16,
+    /// Public IO schema version the parameters were generated for
+    pub schema_version: u32,
+    /// Shape the parameters were generated for
+    pub shape: Shape,
+}
+
+/// Read the header of the parameter file at `path` without loading the parameters.
+///
+/// Only the leading bytes are read, so this takes the same time for files of any size;
+/// tooling can use it to list cached shapes. The checksum is not verified.
+///
+/// # Errors
+///
+/// [`KontorPoRError::IO`] if the file cannot be opened, and
+/// [`KontorPoRError::Serialization`] if it is not a parameter file, was written by another
+/// format version or has an unreadable shape.
+pub fn peek_header(path: &Path) -> Result<ParamsHeader> {
+    use bincode::Options;
+
+    let file = File::open(path).map_err(|e| {
+        KontorPoRError::IO(format!(
+            "Failed to open parameter file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let mut reader = BufReader::new(file);
+    let invalid =
+        |reason: String| KontorPoRError::Serialization(format!("{}: {}", path.display(), reason));
+
+    let mut header = [0u8; disk_format::HEADER_LEN];
+    if reader.read_exact(&mut header).is_err() || &header[..4] != disk_format::MAGIC {
+        return Err(invalid("not a parameter file".to_string()));
+    }
+    let format_version = u16::from_le_bytes([header[4], header[5]]);
+    if format_version != disk_format::VERSION {
+        return Err(invalid(format!(
+            "parameter format version {} is not supported (expected {})",
+            format_version,
+            disk_format::VERSION
+        )));
+    }
+    let schema_version = u32::from_le_bytes(header[6..].try_into().unwrap());
+    let shape: CachedShape = bincode_options()
+        .deserialize_from(&mut reader)
+        .map_err(|e| invalid(format!("unreadable shape: {}", e)))?;
+
+    Ok(ParamsHeader {
+        format_version,
+        schema_version,
+        shape: Shape::from(shape),
+    })
+}
+
+/// A parameter file found in the disk cache by [`cache_stats`].
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    /// Path of the cache file
+    pub path: PathBuf,
+    /// Shape stored in the file header, or `None` for files written by another format version
+    pub shape: Option<Shape>,
+    /// On-disk format version from the file header
+    pub format_version: u16,
+    /// Public IO schema version of the parameters, or `None` for files written by another
+    /// format version
+    pub schema_version: Option<u32>,
+    /// File size in bytes
+    pub size: u64,
+    /// Creation time, if the filesystem records one
+    pub created: Option<SystemTime>,
+    /// Last access time, falling back to the modification time
+    pub last_accessed: Option<SystemTime>,
+}
+
+impl CacheEntryInfo {
+    /// Whether this file was written by the current format version.
+    pub fn is_current_format(&self) -> bool {
+        self.format_version == disk_format::VERSION
+    }
+
+    /// Whether the parameters can be loaded by this build: the current format version and
+    /// [`crate::config::PUBLIC_IO_SCHEMA_VERSION`].
+    pub fn is_loadable(&self) -> bool {
+        self.is_current_format()
+            && self.schema_version == Some(crate::config::PUBLIC_IO_SCHEMA_VERSION)
+    }
+}
+
+/// Which cache files [`gc`] deletes.
+#[derive(Debug, Clone, Copy)]
+pub enum GcPolicy<'a> {
+    /// Delete files not accessed within this long
+    OlderThan(Duration),
+    /// Delete files for any other shape, including every file of an older format or public
+    /// IO schema version
+    KeepShapes(&'a [Shape]),
+    /// Delete least recently accessed files until the cache holds at most this many bytes
+    MaxTotalBytes(u64),
+}
+
+/// Result of [`gc`].
+#[derive(Debug, Clone, Default)]
+pub struct GcReport {
+    /// Files deleted (or, for [`gc_dry_run`], that would be deleted)
+    pub removed: Vec<CacheEntryInfo>,
+    /// Parameter files left in place
+    pub kept: Vec<CacheEntryInfo>,
+}
+
+impl GcReport {
+    /// Total bytes of the removed files.
+    pub fn freed_bytes(&self) -> u64 {
+        self.removed.iter().map(|e| e.size).sum()
+    }
+}
+
+/// List the parameter files in `dir`, least recently accessed first.
+///
+/// Only `.params` files starting with this crate's magic bytes are listed; anything else in
+/// the directory is ignored. Files of the current format version also report their shape.
+/// A missing directory has no entries.
+pub fn cache_stats(dir: &Path) -> Result<Vec<CacheEntryInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(KontorPoRError::IO(format!(
+                "Failed to read parameter cache directory {}: {}",
+                dir.display(),
+                e
+            )))
+        }
+    };
+
+    let mut infos: Vec<CacheEntryInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "params"))
+        .filter_map(|path| read_cache_entry_info(&path))
+        .collect();
+    infos.sort_by(|a, b| {
+        a.last_accessed
+            .cmp(&b.last_accessed)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(infos)
+}
+
+/// Read the header of a cache file, or `None` if it is not one of ours.
+fn read_cache_entry_info(path: &Path) -> Option<CacheEntryInfo> {
+    use bincode::Options;
+
+    // Take the times before reading, so listing the cache does not count as an access
+    let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let modified = metadata.modified().ok();
+    let accessed = metadata.accessed().ok();
+
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::new(&file);
+    let mut header = [0u8; 6];
+    reader.read_exact(&mut header).ok()?;
+    if &header[..4] != disk_format::MAGIC {
+        return None;
+    }
+    let format_version = u16::from_le_bytes([header[4], header[5]]);
+    let mut schema_version = None;
+    let shape = if format_version == disk_format::VERSION {
+        let mut schema = [0u8; 4];
+        reader.read_exact(&mut schema).ok()?;
+        schema_version = Some(u32::from_le_bytes(schema));
+        let cached: CachedShape = bincode_options().deserialize_from(&mut reader).ok()?;
+        Some(Shape::from(cached))
+    } else {
+        None
+    };
+    drop(reader);
+    if let (Some(accessed), Some(modified)) = (accessed, modified) {
+        let times = fs::FileTimes::new()
+            .set_accessed(accessed)
+            .set_modified(modified);
+        let _ = file.set_times(times);
+    }
+
+    Some(CacheEntryInfo {
+        path: path.to_path_buf(),
+        shape,
+        format_version,
+        schema_version,
+        size: metadata.len(),
+        created: metadata.created().ok(),
+        last_accessed: accessed.or(modified),
+    })
+}
+
+/// Delete the parameter files in `dir` selected by `policy`.
+///
+/// Only files listed by [`cache_stats`] are candidates, so foreign files in the directory
+/// are never touched. Shapes whose lock file exists are being written and are kept.
+pub fn gc(dir: &Path, policy: GcPolicy<'_>) -> Result<GcReport> {
+    run_gc(dir, policy, true)
+}
+
+/// Report what [`gc`] would delete without deleting anything.
+pub fn gc_dry_run(dir: &Path, policy: GcPolicy<'_>) -> Result<GcReport> {
+    run_gc(dir, policy, false)
+}
+
+fn run_gc(dir: &Path, policy: GcPolicy<'_>, delete: bool) -> Result<GcReport> {
+    let now = SystemTime::now();
+    let (locked, candidates): (Vec<_>, Vec<_>) = cache_stats(dir)?
+        .into_iter()
+        .partition(|entry| entry.path.with_extension("lock").exists());
+
+    let mut report = GcReport {
+        kept: locked,
+        ..GcReport::default()
+    };
+    let mut total: u64 = report.kept.iter().chain(&candidates).map(|e| e.size).sum();
+    // Candidates are least recently accessed first
+    for entry in candidates {
+        let remove = match policy {
+            GcPolicy::OlderThan(age) => entry
+                .last_accessed
+                .and_then(|t| now.duration_since(t).ok())
+                .is_some_and(|elapsed| elapsed > age),
+            GcPolicy::KeepShapes(shapes) => {
+                !entry.is_loadable() || entry.shape.is_none_or(|s| !shapes.contains(&s))
+            }
+            GcPolicy::MaxTotalBytes(max) => total > max,
+        };
+        if !remove {
+            report.kept.push(entry);
+            continue;
+        }
+        if delete {
+            fs::remove_file(&entry.path).map_err(|e| {
+                KontorPoRError::IO(format!(
+                    "Failed to delete cached parameters {}: {}",
+                    entry.path.display(),
+                    e
+                ))
+            })?;
+            info!("Deleted cached parameters {}", entry.path.display());
+        }
+        total -= entry.size;
+        report.removed.push(entry);
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,28 +1390,127 @@ mod tests {
             files_per_step: 4,
             file_tree_depth: 10,
             aggregated_tree_depth: 2,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
         };
 
         let key2 = ParamKey {
             files_per_step: 4,
             file_tree_depth: 10,
             aggregated_tree_depth: 2,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
         };
 
         let key3 = ParamKey {
             files_per_step: 8,
             file_tree_depth: 10,
             aggregated_tree_depth: 2,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
         };
 
         let key4 = ParamKey {
             files_per_step: 4,
             file_tree_depth: 10,
             aggregated_tree_depth: 3,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
+        };
+
+        let key5 = ParamKey {
+            files_per_step: 4,
+            file_tree_depth: 10,
+            aggregated_tree_depth: 2,
+            mode: ChallengeMode::Private,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
+        };
+
+        let key6 = ParamKey {
+            files_per_step: 4,
+            file_tree_depth: 10,
+            aggregated_tree_depth: 2,
+            mode: ChallengeMode::Public,
+            per_step_seeds: true,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Binary,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
+        };
+
+        let key7 = ParamKey {
+            files_per_step: 4,
+            file_tree_depth: 10,
+            aggregated_tree_depth: 2,
+            mode: ChallengeMode::Public,
+            per_step_seeds: false,
+            explicit_indices: false,
+            partitioned: false,
+            tree_arity: TreeArity::Arity4,
+            hash_profile: HashProfile::Standard,
+            circuit_mode: CircuitMode::Lenient,
+        };
+
+        let key8 = ParamKey {
+            circuit_mode: CircuitMode::Strict,
+            ..key1
         };
 
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
         assert_ne!(key1, key4);
+        assert_ne!(key1, key5);
+        assert_ne!(key1, key6);
+        assert_ne!(key1, key7);
+        assert_ne!(key1.file_name(), key7.file_name());
+        assert_ne!(key1, key8);
+
+        let key9 = ParamKey {
+            explicit_indices: true,
+            ..key1
+        };
+        assert_ne!(key1, key9);
+        assert_ne!(key1.file_name(), key9.file_name());
+        assert_ne!(key1.file_name(), key8.file_name());
+
+        let key10 = ParamKey {
+            hash_profile: HashProfile::Conservative,
+            ..key1
+        };
+        assert_ne!(key1, key10);
+        assert_ne!(key1.file_name(), key10.file_name());
+
+        let key11 = ParamKey {
+            partitioned: true,
+            ..key1
+        };
+        assert_ne!(key1, key11);
+        assert_ne!(key1.file_name(), key11.file_name());
     }
 }
@@ -5,16 +5,22 @@
 //! API and internal circuit constraints, preventing divergence bugs.
 
 use crate::FieldElement;
+use ff::Field;
 use generic_array::typenum::U2;
 use nova_snark::frontend::gadgets::poseidon::{
     IOPattern, PoseidonConstants, Simplex, Sponge, SpongeAPI, SpongeOp, SpongeTrait, Strength,
 };
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 
 /// Cached Poseidon constants for performance
 static POSEIDON_CONSTANTS: Lazy<PoseidonConstants<FieldElement, U2>> =
     Lazy::new(|| Sponge::<FieldElement, U2>::api_constants(Strength::Standard));
 
+/// Cached Poseidon constants of the [`HashProfile::Conservative`] instantiation
+static CONSERVATIVE_CONSTANTS: Lazy<PoseidonConstants<FieldElement, U2>> =
+    Lazy::new(|| Sponge::<FieldElement, U2>::api_constants(Strength::Strengthened));
+
 /// Cached IO pattern for 2-input, 1-output Poseidon hashing
 static IO_PATTERN_2: Lazy<IOPattern> =
     Lazy::new(|| IOPattern(vec![SpongeOp::Absorb(2), SpongeOp::Squeeze(1)]));
@@ -23,6 +29,58 @@ static IO_PATTERN_2: Lazy<IOPattern> =
 static IO_PATTERN_3: Lazy<IOPattern> =
     Lazy::new(|| IOPattern(vec![SpongeOp::Absorb(3), SpongeOp::Squeeze(1)]));
 
+/// Cached IO pattern for 5-input, 1-output Poseidon hashing
+static IO_PATTERN_5: Lazy<IOPattern> =
+    Lazy::new(|| IOPattern(vec![SpongeOp::Absorb(5), SpongeOp::Squeeze(1)]));
+
+/// Poseidon instantiation used to hash a file's Merkle tree and root commitment.
+///
+/// The profile is part of a file's public commitment: it is recorded in `FileMetadata`,
+/// selects the constants of the file tree hashes natively and in-circuit, and is part of
+/// the circuit shape. Ledger trees, challenge derivation and the other protocol hashes
+/// always use [`HashProfile::Standard`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashProfile {
+    /// The standard-strength Poseidon instantiation.
+    #[default]
+    Standard,
+    /// The strengthened instantiation, with extra partial rounds, for long-lived archival
+    /// commitments. Hashing is slower and each file tree level costs more constraints.
+    Conservative,
+}
+
+impl HashProfile {
+    /// The Poseidon strength of this profile's constants.
+    pub fn strength(&self) -> Strength {
+        match self {
+            HashProfile::Standard => Strength::Standard,
+            HashProfile::Conservative => Strength::Strengthened,
+        }
+    }
+
+    /// Returns true for [`HashProfile::Conservative`].
+    pub fn is_conservative(&self) -> bool {
+        matches!(self, HashProfile::Conservative)
+    }
+
+    /// The cached native constants of this profile.
+    fn constants(&self) -> &'static PoseidonConstants<FieldElement, U2> {
+        match self {
+            HashProfile::Standard => &POSEIDON_CONSTANTS,
+            HashProfile::Conservative => &CONSERVATIVE_CONSTANTS,
+        }
+    }
+}
+
+impl std::fmt::Display for HashProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashProfile::Standard => write!(f, "standard"),
+            HashProfile::Conservative => write!(f, "conservative"),
+        }
+    }
+}
+
 /// Domain separation tag values
 /// These are distinct integers used to prevent hash collisions across different contexts
 mod tag_values {
@@ -33,6 +91,18 @@ mod tag_values {
     pub const ROOT_COMMITMENT: u64 = 8;
     pub const CHALLENGE_PER_FILE: u64 = 9;
     pub const CHALLENGE_ID: u64 = 10;
+    pub const LEAF_BLINDING: u64 = 11;
+    pub const LEAF_COMMITMENT: u64 = 12;
+    pub const SEED_SCHEDULE: u64 = 13;
+    pub const LEDGER_ATTESTATION: u64 = 14;
+    pub const NODE_ARITY4: u64 = 15;
+    pub const ROOT_COMMITMENT_ARITY4: u64 = 16;
+    pub const CHALLENGE_NONCE: u64 = 17;
+    pub const EMPTY_LEDGER_SLOT: u64 = 18;
+    pub const EXPLICIT_INDEX: u64 = 19;
+    pub const HEARTBEAT: u64 = 20;
+    pub const PROOF_STATEMENT: u64 = 21;
+    pub const PASS_SCHEDULE: u64 = 22;
 }
 
 /// Domain separation tags for different Poseidon hash contexts
@@ -74,6 +144,80 @@ pub mod domain_tags {
     pub fn challenge_id<F: PrimeField>() -> F {
         F::from(tag_values::CHALLENGE_ID)
     }
+
+    /// Tag for deriving the blinding factor of a private-mode leaf commitment
+    pub fn leaf_blinding<F: PrimeField>() -> F {
+        F::from(tag_values::LEAF_BLINDING)
+    }
+
+    /// Tag for private-mode leaf commitments (H(tag, leaf, blinding))
+    pub fn leaf_commitment<F: PrimeField>() -> F {
+        F::from(tag_values::LEAF_COMMITMENT)
+    }
+
+    /// Tag for accumulating per-step challenge seeds (H(tag, acc, step_seed))
+    pub fn seed_schedule<F: PrimeField>() -> F {
+        F::from(tag_values::SEED_SCHEDULE)
+    }
+
+    /// Tag for ledger attestation commitments posted on-chain
+    pub fn ledger_attestation<F: PrimeField>() -> F {
+        F::from(tag_values::LEDGER_ATTESTATION)
+    }
+
+    /// Tag for hashing internal nodes of arity-4 Merkle trees
+    pub fn node_arity4<F: PrimeField>() -> F {
+        F::from(tag_values::NODE_ARITY4)
+    }
+
+    /// Tag for root commitments of arity-4 file trees (rc = Poseidon(root, depth))
+    pub fn root_commitment_arity4<F: PrimeField>() -> F {
+        F::from(tag_values::ROOT_COMMITMENT_ARITY4)
+    }
+
+    /// Tag for folding challenge nonces into the initial chain state
+    pub fn challenge_nonce<F: PrimeField>() -> F {
+        F::from(tag_values::CHALLENGE_NONCE)
+    }
+
+    /// Tag for the leaf that fills unused slots of a fixed-capacity ledger
+    pub fn empty_ledger_slot<F: PrimeField>() -> F {
+        F::from(tag_values::EMPTY_LEDGER_SLOT)
+    }
+
+    /// Tag for accumulating explicit challenge indices (H(tag, acc, index))
+    pub fn explicit_index<F: PrimeField>() -> F {
+        F::from(tag_values::EXPLICIT_INDEX)
+    }
+
+    /// Tag for the initial chain state of heartbeat proofs (H(tag, seed, prover))
+    pub fn heartbeat<F: PrimeField>() -> F {
+        F::from(tag_values::HEARTBEAT)
+    }
+
+    /// Tag for folding a proof statement into its digest (H(tag, acc, value))
+    pub fn proof_statement<F: PrimeField>() -> F {
+        F::from(tag_values::PROOF_STATEMENT)
+    }
+
+    /// Tag for accumulating the slot inputs of partitioned batches (H(tag, acc, input))
+    pub fn pass_schedule<F: PrimeField>() -> F {
+        F::from(tag_values::PASS_SCHEDULE)
+    }
+}
+
+/// Builds every cached Poseidon constant and IO pattern ahead of the first hash.
+///
+/// Native hashing and the circuit gadgets share constants across all tags and arities
+/// (one set per [`HashProfile`]), so this is cheap after the first call. Services can call
+/// it at startup to keep the one-off initialization cost out of the first request's latency.
+pub fn warm_up() {
+    Lazy::force(&POSEIDON_CONSTANTS);
+    Lazy::force(&CONSERVATIVE_CONSTANTS);
+    Lazy::force(&IO_PATTERN_2);
+    Lazy::force(&IO_PATTERN_3);
+    Lazy::force(&IO_PATTERN_5);
+    crate::circuit::gadgets::poseidon::warm_up::<FieldElement>();
 }
 
 /// Core Poseidon hash function for two field elements.
@@ -95,7 +239,17 @@ pub fn poseidon_hash2(left: FieldElement, right: FieldElement) -> FieldElement {
 /// Domain-separated Poseidon hash with 3 inputs (tag + 2 data elements)
 /// Optimized to use a single hash operation instead of two
 pub fn poseidon_hash_tagged(tag: FieldElement, x: FieldElement, y: FieldElement) -> FieldElement {
-    let mut sponge = Sponge::<FieldElement, U2>::new_with_constants(&POSEIDON_CONSTANTS, Simplex);
+    poseidon_hash_tagged_with(HashProfile::Standard, tag, x, y)
+}
+
+/// Like [`poseidon_hash_tagged`], but with the constants of `profile`
+pub fn poseidon_hash_tagged_with(
+    profile: HashProfile,
+    tag: FieldElement,
+    x: FieldElement,
+    y: FieldElement,
+) -> FieldElement {
+    let mut sponge = Sponge::<FieldElement, U2>::new_with_constants(profile.constants(), Simplex);
     let mut acc = ();
     sponge.start(IO_PATTERN_3.clone(), None, &mut acc);
     SpongeAPI::absorb(&mut sponge, 3, &[tag, x, y], &mut acc);
@@ -107,6 +261,35 @@ pub fn poseidon_hash_tagged(tag: FieldElement, x: FieldElement, y: FieldElement)
     output[0]
 }
 
+/// Domain-separated Poseidon hash with 5 inputs (tag + 4 data elements)
+/// Used for arity-4 Merkle nodes; absorbs all children in a single sponge pass
+pub fn poseidon_hash_tagged4(tag: FieldElement, children: [FieldElement; 4]) -> FieldElement {
+    poseidon_hash_tagged4_with(HashProfile::Standard, tag, children)
+}
+
+/// Like [`poseidon_hash_tagged4`], but with the constants of `profile`
+pub fn poseidon_hash_tagged4_with(
+    profile: HashProfile,
+    tag: FieldElement,
+    children: [FieldElement; 4],
+) -> FieldElement {
+    let mut sponge = Sponge::<FieldElement, U2>::new_with_constants(profile.constants(), Simplex);
+    let mut acc = ();
+    sponge.start(IO_PATTERN_5.clone(), None, &mut acc);
+    SpongeAPI::absorb(
+        &mut sponge,
+        5,
+        &[tag, children[0], children[1], children[2], children[3]],
+        &mut acc,
+    );
+    let output = SpongeAPI::squeeze(&mut sponge, 1, &mut acc);
+    sponge
+        .finish(&mut acc)
+        .expect("Poseidon sponge finish should not fail");
+
+    output[0]
+}
+
 /// Calculate the root commitment (rc) for a file.
 /// rc = Poseidon(TAG_RC, root, depth)
 ///
@@ -116,6 +299,125 @@ pub fn calculate_root_commitment(root: FieldElement, depth: FieldElement) -> Fie
     poseidon_hash_tagged(domain_tags::root_commitment(), root, depth)
 }
 
+/// Calculate the root commitment (rc) for a file with an arity-4 tree.
+/// rc = Poseidon(TAG_RC4, root, depth)
+///
+/// Uses a separate tag so a 4-ary (root, depth) pair can never collide with a
+/// binary one in the ledger.
+pub fn calculate_root_commitment_arity4(root: FieldElement, depth: FieldElement) -> FieldElement {
+    poseidon_hash_tagged(domain_tags::root_commitment_arity4(), root, depth)
+}
+
+/// Derive the blinding factor for a private-mode leaf commitment.
+/// blinding = Poseidon(TAG_LEAF_BLINDING, seed, sibling)
+///
+/// `sibling` is the first sibling on the challenged leaf's Merkle path, which is
+/// private file data; the blinding therefore never appears in public I/O.
+pub fn derive_leaf_blinding(seed: FieldElement, sibling: FieldElement) -> FieldElement {
+    poseidon_hash_tagged(domain_tags::leaf_blinding(), seed, sibling)
+}
+
+/// Calculate the private-mode commitment to a challenged leaf.
+/// commitment = Poseidon(TAG_LEAF_COMMITMENT, leaf, blinding)
+pub fn calculate_leaf_commitment(leaf: FieldElement, blinding: FieldElement) -> FieldElement {
+    poseidon_hash_tagged(domain_tags::leaf_commitment(), leaf, blinding)
+}
+
+/// Fold a per-step seed schedule into the accumulator exposed in a slot's seed output.
+/// acc_0 = initial, acc_{k+1} = Poseidon(TAG_SEED_SCHEDULE, acc_k, schedule[k])
+///
+/// The verifier recomputes this from the challenge's schedule and compares it with the
+/// proof's final seed outputs, which binds every step seed and its position.
+pub fn accumulate_seed_schedule(initial: FieldElement, schedule: &[FieldElement]) -> FieldElement {
+    schedule.iter().fold(initial, |acc, seed| {
+        poseidon_hash_tagged(domain_tags::seed_schedule(), acc, *seed)
+    })
+}
+
+/// Fold a slot's explicit leaf indices into the accumulator exposed in its seed output.
+/// acc_0 = initial, acc_{k+1} = Poseidon(TAG_EXPLICIT_INDEX, acc_k, indices[k])
+///
+/// Like [`accumulate_seed_schedule`], this lets the verifier check every opened index and
+/// its position against the final seed outputs.
+pub fn accumulate_explicit_indices(initial: FieldElement, indices: &[usize]) -> FieldElement {
+    indices.iter().fold(initial, |acc, index| {
+        poseidon_hash_tagged(
+            domain_tags::explicit_index(),
+            acc,
+            FieldElement::from(*index as u64),
+        )
+    })
+}
+
+/// Fold the inputs a slot of a partitioned batch took at each step into the accumulator
+/// exposed in its public output.
+/// acc_0 = initial, acc_{k+1} = Poseidon(TAG_PASS_SCHEDULE, acc_k, inputs[k])
+///
+/// Each of a slot's ledger index, depth and seed sections accumulates separately. The
+/// verifier recomputes them from the pass order, which binds every step to its pass.
+pub fn accumulate_pass_schedule(initial: FieldElement, inputs: &[FieldElement]) -> FieldElement {
+    inputs.iter().fold(initial, |acc, input| {
+        poseidon_hash_tagged(domain_tags::pass_schedule(), acc, *input)
+    })
+}
+
+/// Initial challenge-chain state binding a batch's challenge nonces (in canonical order).
+/// acc_0 = 0, acc_{k+1} = Poseidon(TAG_NONCE, acc_k, nonce_k)
+///
+/// Batches whose nonces are all zero keep the legacy initial state of zero, so challenges
+/// created before nonces existed still produce and verify the same proofs.
+pub fn calculate_nonce_commitment(nonces: &[u64]) -> FieldElement {
+    if nonces.iter().all(|nonce| *nonce == 0) {
+        return FieldElement::ZERO;
+    }
+    nonces.iter().fold(FieldElement::ZERO, |acc, nonce| {
+        poseidon_hash_tagged(
+            domain_tags::challenge_nonce(),
+            acc,
+            FieldElement::from(*nonce),
+        )
+    })
+}
+
+/// Initial chain state of a heartbeat proof, binding its seed and prover.
+/// commitment = Poseidon(TAG_HEARTBEAT, seed, prover), where `prover` is the first 31
+/// bytes of SHA-256(prover_id) read as a little-endian field element
+pub fn calculate_heartbeat_commitment(seed: FieldElement, prover_id: &str) -> FieldElement {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(prover_id.as_bytes());
+    let prover = crate::utils::bytes31_to_field_le(&digest[..31]);
+    poseidon_hash_tagged(domain_tags::heartbeat(), seed, prover)
+}
+
+/// Leaf held by every unused slot of a fixed-capacity ledger.
+/// empty = Poseidon(TAG_EMPTY_SLOT, 0, 0)
+///
+/// Real slots hold rc values hashed under `TAG_RC`/`TAG_RC4`, so an empty slot can never be
+/// mistaken for (or opened as) a registered file.
+pub fn empty_ledger_slot() -> FieldElement {
+    poseidon_hash_tagged(
+        domain_tags::empty_ledger_slot(),
+        FieldElement::ZERO,
+        FieldElement::ZERO,
+    )
+}
+
+/// Calculate the commitment carried by a ledger attestation.
+/// commitment = Poseidon(TAG_LA, Poseidon(TAG_LA, Poseidon(TAG_LA, root, file_count),
+/// aggregated_depth), block_height)
+pub fn calculate_ledger_attestation_commitment(
+    root: FieldElement,
+    file_count: u64,
+    aggregated_depth: u64,
+    block_height: u64,
+) -> FieldElement {
+    let tag = domain_tags::ledger_attestation();
+    let inner = poseidon_hash_tagged(tag, root, FieldElement::from(file_count));
+    let inner = poseidon_hash_tagged(tag, inner, FieldElement::from(aggregated_depth));
+    poseidon_hash_tagged(tag, inner, FieldElement::from(block_height))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
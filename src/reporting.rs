@@ -0,0 +1,357 @@
+//! Machine-readable benchmark reports for performance regression tracking.
+//!
+//! [`run_bench`] prepares a set of files, proves one epoch of challenges against them and
+//! verifies the proof, recording every phase. The resulting [`BenchReport`] serializes to
+//! JSON (`kontor-crypto bench --json <path>` writes one) so CI can compare runs without
+//! scraping benchmark output.
+//!
+//! ## Schema (version 1)
+//!
+//! Durations are whole microseconds.
+//!
+//! ```text
+//! {
+//!   "schema_version": 1,
+//!   "crate_version": "0.1.5",
+//!   "git_revision": "1ddb95e" | null,
+//!   "config": { "num_files", "file_size_bytes", "ledger_files", "num_challenges", "seed" },
+//!   "phases": {
+//!     "prepare_us",        // preparing every ledger file
+//!     "plan_us",           // proof plan construction
+//!     "param_load_us",     // parameter load, or generation on a cache miss
+//!     "witness_gen_us",    // witness generation summed over steps
+//!     "folding_us",        // recursive folding summed over steps
+//!     "compression_us",    // compressed SNARK
+//!     "verify_us"          // full verification of the proof
+//!   },
+//!   "steps": [ { "step", "witness_gen_us", "fold_us" }, ... ],
+//!   "proof_size_bytes": 10240,
+//!   "peak_rss_bytes": 123456789 | null
+//! }
+//! ```
+//!
+//! `peak_rss_bytes` is the largest resident set size seen by a [`RssSampler`] during the
+//! run; it is `null` where the RSS cannot be read (outside Linux). Fields are only ever
+//! added within a schema version; removing or changing one bumps [`SCHEMA_VERSION`].
+
+use crate::instrumentation::info_span;
+use crate::{
+    api::{Challenge, FieldElement, PorSystem, PreparedFile},
+    metrics::{self, MetricsSink, Phase, TimingEvent},
+    FileLedger, KontorPoRError, Result,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Version of the [`BenchReport`] JSON schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How often [`run_bench`] samples the resident set size.
+pub const RSS_SAMPLE_INTERVAL: Duration = Duration::from_micros(10);
+
+/// Workload of a benchmark run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchConfig {
+    /// Number of challenged files (one challenge each)
+    pub num_files: usize,
+    /// Size of each file in bytes
+    pub file_size_bytes: usize,
+    /// Number of files in the ledger, including the challenged ones
+    pub ledger_files: usize,
+    /// Challenged symbols (recursive steps) per file
+    pub num_challenges: usize,
+    /// Seed for file contents and challenges
+    pub seed: u64,
+}
+
+/// Durations of each phase of a benchmark run, in microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhaseDurations {
+    /// Preparing every ledger file
+    pub prepare_us: u64,
+    /// Proof plan construction
+    pub plan_us: u64,
+    /// Parameter load, or generation on a cache miss
+    pub param_load_us: u64,
+    /// Witness generation summed over all steps
+    pub witness_gen_us: u64,
+    /// Recursive folding summed over all steps
+    pub folding_us: u64,
+    /// Compressed SNARK generation
+    pub compression_us: u64,
+    /// Full verification of the proof
+    pub verify_us: u64,
+}
+
+/// Proving time of one recursive step, in microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepDurations {
+    /// Recursive step index
+    pub step: usize,
+    /// Witness generation for this step
+    pub witness_gen_us: u64,
+    /// `RecursiveSNARK::new` (step 0) or `prove_step`
+    pub fold_us: u64,
+}
+
+/// Result of a benchmark run; see the [module documentation](self) for the JSON schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Always [`SCHEMA_VERSION`] for reports written by this crate version
+    pub schema_version: u32,
+    /// Version of this crate
+    pub crate_version: String,
+    /// Source revision the binary was built from, if known
+    pub git_revision: Option<String>,
+    /// The benchmarked workload
+    pub config: BenchConfig,
+    /// Per-phase durations
+    pub phases: PhaseDurations,
+    /// Per-step proving durations, in step order
+    pub steps: Vec<StepDurations>,
+    /// Size of the serialized proof
+    pub proof_size_bytes: usize,
+    /// Peak resident set size during the run, where it can be measured
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl BenchReport {
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| KontorPoRError::Serialization(e.to_string()))
+    }
+
+    /// Parses a report, rejecting other schema versions.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let report: Self =
+            serde_json::from_str(json).map_err(|e| KontorPoRError::Serialization(e.to_string()))?;
+        if report.schema_version != SCHEMA_VERSION {
+            return Err(KontorPoRError::Serialization(format!(
+                "unsupported benchmark schema version {} (expected {})",
+                report.schema_version, SCHEMA_VERSION
+            )));
+        }
+        Ok(report)
+    }
+}
+
+/// Background thread that samples the process's resident set size and keeps the maximum.
+///
+/// RSS is read from `/proc/self/status`, so samples are only available on Linux. Peaks
+/// shorter than the sampling interval can be missed.
+pub struct RssSampler {
+    peak: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RssSampler {
+    /// Starts sampling every `interval`.
+    pub fn start(interval: Duration) -> Self {
+        let peak = Arc::new(AtomicU64::new(current_rss_bytes().unwrap_or(0)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let peak = peak.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    if let Some(rss) = current_rss_bytes() {
+                        peak.fetch_max(rss, Ordering::Relaxed);
+                    }
+                    std::thread::sleep(interval);
+                }
+            })
+        };
+        Self {
+            peak,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stops sampling and returns the peak RSS observed, or `None` if RSS is unavailable.
+    pub fn finish(mut self) -> Option<u64> {
+        self.stop_thread();
+        let final_sample = current_rss_bytes()?;
+        Some(self.peak.load(Ordering::Relaxed).max(final_sample))
+    }
+
+    fn stop_thread(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for RssSampler {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+/// Current resident set size in bytes, or `None` where it cannot be read.
+pub fn current_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Collects per-step timing events while forwarding everything to the sink that was
+/// registered before the run.
+struct StepRecorder {
+    events: Mutex<Vec<TimingEvent>>,
+    forward: Option<Arc<dyn MetricsSink>>,
+}
+
+impl MetricsSink for StepRecorder {
+    fn record(&self, event: TimingEvent) {
+        if matches!(event.phase, Phase::WitnessGen | Phase::Fold) {
+            self.events
+                .lock()
+                .expect("Step recorder lock should not be poisoned")
+                .push(event);
+        }
+        if let Some(sink) = &self.forward {
+            sink.record(event);
+        }
+    }
+}
+
+impl StepRecorder {
+    fn steps(&self, num_steps: usize) -> Vec<StepDurations> {
+        let mut steps: Vec<StepDurations> = (0..num_steps)
+            .map(|step| StepDurations {
+                step,
+                ..Default::default()
+            })
+            .collect();
+        let events = self
+            .events
+            .lock()
+            .expect("Step recorder lock should not be poisoned");
+        for event in events.iter() {
+            let Some(entry) = event.step.and_then(|step| steps.get_mut(step)) else {
+                continue;
+            };
+            match event.phase {
+                Phase::WitnessGen => entry.witness_gen_us += micros(event.duration),
+                Phase::Fold => entry.fold_us += micros(event.duration),
+                _ => {}
+            }
+        }
+        steps
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Runs one proving epoch for `config` and reports how long each phase took.
+///
+/// Files are random data derived from `config.seed`; the first `num_files` ledger files
+/// are challenged. Per-step durations are collected through a temporary
+/// [`MetricsSink`] that forwards to any sink already registered, so other proofs running
+/// concurrently in the process would be attributed to this run. `git_revision` is left
+/// `None` for the caller to fill in.
+///
+/// # Errors
+///
+/// Returns [`KontorPoRError::InvalidInput`] for an empty workload or fewer ledger files
+/// than challenged files, [`KontorPoRError::Snark`] if the proof does not verify, and
+/// any error from preparing, proving or verifying.
+pub fn run_bench(config: &BenchConfig) -> Result<BenchReport> {
+    let _span = info_span!(
+        "run_bench",
+        num_files = config.num_files,
+        file_size = config.file_size_bytes,
+        num_challenges = config.num_challenges
+    )
+    .entered();
+
+    if config.num_files == 0 || config.num_challenges == 0 {
+        return Err(KontorPoRError::InvalidInput(
+            "benchmark needs at least one file and one challenge".to_string(),
+        ));
+    }
+    if config.ledger_files < config.num_files {
+        return Err(KontorPoRError::InvalidInput(format!(
+            "ledger_files ({}) must be at least num_files ({})",
+            config.ledger_files, config.num_files
+        )));
+    }
+
+    let sampler = RssSampler::start(RSS_SAMPLE_INTERVAL);
+    let mut phases = PhaseDurations::default();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    // Prepare
+    let prepare_start = Instant::now();
+    let mut ledger = FileLedger::new();
+    let mut challenged: Vec<PreparedFile> = Vec::with_capacity(config.num_files);
+    let mut challenges = Vec::with_capacity(config.num_files);
+    for i in 0..config.ledger_files {
+        let mut data = vec![0u8; config.file_size_bytes];
+        rng.fill_bytes(&mut data);
+        let (prepared, metadata) = crate::api::prepare_file(&data, &format!("bench_{}.dat", i))?;
+        ledger.add_file(&metadata)?;
+        if i < config.num_files {
+            let seed = FieldElement::from(rng.next_u64());
+            challenges.push(Challenge::new(
+                metadata,
+                1000,
+                config.num_challenges,
+                seed,
+                String::from("bench"),
+            ));
+            challenged.push(prepared);
+        }
+    }
+    phases.prepare_us = micros(prepare_start.elapsed());
+
+    // Prove, recording per-step events
+    let recorder = Arc::new(StepRecorder {
+        events: Mutex::new(Vec::new()),
+        forward: metrics::metrics_sink(),
+    });
+    metrics::set_metrics_sink(Some(recorder.clone()));
+    let system = PorSystem::new(&ledger);
+    let proved = system.prove_with_timings(challenged.iter().collect(), &challenges);
+    metrics::set_metrics_sink(recorder.forward.clone());
+    let (proof, timings) = proved?;
+
+    phases.plan_us = micros(timings.plan_duration);
+    phases.param_load_us = micros(timings.param_load_duration);
+    phases.witness_gen_us = micros(timings.witness_gen_duration);
+    phases.folding_us = micros(timings.folding_duration);
+    phases.compression_us = micros(timings.compression_duration);
+    let steps = recorder.steps(config.num_challenges);
+    let proof_size_bytes = proof.to_bytes()?.len();
+
+    // Verify
+    let verify_start = Instant::now();
+    let valid = system.verify(&proof, &challenges)?;
+    phases.verify_us = micros(verify_start.elapsed());
+    if !valid {
+        return Err(KontorPoRError::Snark(
+            "benchmark proof failed verification".to_string(),
+        ));
+    }
+
+    Ok(BenchReport {
+        schema_version: SCHEMA_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_revision: None,
+        config: config.clone(),
+        phases,
+        steps,
+        proof_size_bytes,
+        peak_rss_bytes: sampler.finish(),
+    })
+}
@@ -3,6 +3,7 @@
 use crate::api::FieldElement;
 use crate::poseidon::{domain_tags, poseidon_hash_tagged};
 use ff::PrimeField;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Convert up to 31 little-endian bytes into a field element using the
 /// canonical byte representation expected by `ff::PrimeField::from_repr`.
@@ -28,6 +29,50 @@ pub fn field_to_bytes31_le<F: PrimeField>(element: &F) -> [u8; 31] {
     out
 }
 
+/// Fails with [`crate::KontorPoRError::Cancelled`] once `cancel` is set; `None` never
+/// cancels. Long-running loops call this between units of work.
+pub(crate) fn check_cancelled(cancel: Option<&AtomicBool>) -> crate::Result<()> {
+    match cancel {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(crate::KontorPoRError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Overwrites `buf` with zeros in a way the compiler cannot elide, so plaintext does not
+/// linger in freed memory. Compiles to nothing without the `zeroize` feature.
+#[cfg(feature = "prover")]
+pub(crate) fn scrub_bytes(buf: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    #[cfg(not(feature = "zeroize"))]
+    let _ = buf;
+}
+
+/// [`scrub_bytes`] on every present symbol.
+#[cfg(feature = "prover")]
+pub(crate) fn scrub_symbols(symbols: &mut [Option<Vec<u8>>]) {
+    for symbol in symbols.iter_mut().flatten() {
+        scrub_bytes(symbol);
+    }
+}
+
+/// [`scrub_bytes`] for field elements. Merkle leaves are the file's symbols, so they are
+/// scrubbed like the symbols themselves.
+#[cfg(feature = "prover")]
+pub(crate) fn scrub_field_elements(elements: &mut [FieldElement]) {
+    #[cfg(feature = "zeroize")]
+    {
+        use ff::Field;
+        for element in elements.iter_mut() {
+            // SAFETY: `element` is a valid, aligned and exclusive reference
+            unsafe { std::ptr::write_volatile(element, FieldElement::ZERO) };
+        }
+        std::sync::atomic::compiler_fence(Ordering::SeqCst);
+    }
+    #[cfg(not(feature = "zeroize"))]
+    let _ = elements;
+}
+
 /// Utility function to derive a leaf index from a hash value using least-significant bits.
 /// This extracts the first `depth` bits from the hash in little-endian order.
 pub fn derive_index_from_bits<F: ff::PrimeField>(hash: F, depth: usize) -> usize {
@@ -63,6 +108,13 @@ pub fn leaf_to_bytes31<F: ff::PrimeField>(leaf: &F) -> [u8; 31] {
     field_to_bytes31_le(leaf)
 }
 
+/// Whether `leaf` round-trips through [`leaf_to_bytes31`], i.e. encodes a 31-byte symbol.
+/// Leaves built by file preparation always do; a wider leaf can only come from a tampered
+/// or corrupted tree.
+pub(crate) fn is_bytes31_leaf<F: PrimeField>(leaf: &F) -> bool {
+    bytes31_to_field_le::<F>(&leaf_to_bytes31(leaf)) == *leaf
+}
+
 /// Derive an unbiased index in [0, leaf_count) from a field element by
 /// rejection sampling. Falls back to rehashing with Poseidon and a counter
 /// under the challenge domain tag to obtain new samples when needed.
@@ -137,3 +189,11 @@ pub fn derive_leaf_index_for_file(
 
     derive_index_unbiased(per_file, 1usize << file_depth)
 }
+
+/// Advance the challenge state after a file's challenge has been answered with `leaf`.
+///
+/// Shared by witness generation and native spot checks so the state chain (and thus
+/// every subsequent challenge index) is derived identically in both places.
+pub fn advance_challenge_state(state: FieldElement, leaf: FieldElement) -> FieldElement {
+    poseidon_hash_tagged(domain_tags::state_update(), state, leaf)
+}
@@ -0,0 +1,296 @@
+//! Tests for multi-file circuits padded to a fixed aggregated depth
+//!
+//! This module tests that:
+//! 1. A circuit padded to `max_agg_depth` has the same shape for ledgers of 2, 5 and 9
+//!    files and is satisfied only with the ledger's real depth
+//! 2. Each padding level adds the same number of constraints
+//! 3. One parameter set proves and verifies against ledgers of 2, 5 and 9 files, and an
+//!    attestation of each ledger's real depth accepts the padded proof
+//! 4. Plans reject mismatched, out-of-range or too-shallow `max_agg_depth` values
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, generate_circuit_witness, Challenge, FieldElement, PorSystem},
+    circuit::PorCircuit,
+    config,
+    ledger::{verify_ledger_root_with_attestations, LedgerAttestation},
+    params, KontorPoRError,
+};
+use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
+use nova_snark::traits::circuit::StepCircuit;
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_circuit_public_inputs, create_multi_file_ledger, create_test_files};
+
+const MAX_AGG_DEPTH: usize = 4;
+
+/// Synthesizes a padded two-slot step over the first two files of a `num_files` ledger.
+///
+/// `claimed_ledger_depth` overrides the depth the witness activates (the real ledger
+/// depth when `None`). Returns the ledger depth and the synthesized constraint system.
+fn synthesize_padded_step(
+    num_files: usize,
+    claimed_ledger_depth: Option<usize>,
+) -> (usize, TestConstraintSystem<FieldElement>) {
+    let (files, metadatas) = create_test_files(num_files, 300, 71);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let seed = FieldElement::from(9u64);
+
+    let mut challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 1, seed).with_max_agg_depth(MAX_AGG_DEPTH))
+        .collect();
+    challenges.sort_by(|a, b| a.file_metadata.file_id.cmp(&b.file_metadata.file_id));
+
+    let max_depth = challenges
+        .iter()
+        .map(|c| api::tree_depth_from_metadata(&c.file_metadata))
+        .max()
+        .unwrap();
+    let (files_per_step, file_tree_depth) = config::derive_shape(challenges.len(), max_depth);
+    let ledger_indices: Vec<usize> = challenges
+        .iter()
+        .map(|c| ledger.lookup(&c.file_metadata.file_id).unwrap().0)
+        .collect();
+    let depths: Vec<usize> = challenges
+        .iter()
+        .map(|c| api::tree_depth_from_metadata(&c.file_metadata))
+        .collect();
+
+    let file_refs: BTreeMap<String, &_> = files.iter().map(|(k, v)| (k.clone(), v)).collect();
+    let (witness, _) = generate_circuit_witness(
+        &challenges.iter().collect::<Vec<_>>(),
+        Some(&file_refs),
+        &ledger,
+        file_tree_depth,
+        file_tree_depth,
+        FieldElement::ZERO,
+        MAX_AGG_DEPTH,
+        0,
+        &ledger_indices,
+    )
+    .unwrap();
+    assert_eq!(witness.ledger_depth(), Some(ledger.depth()));
+    assert!(witness
+        .witnesses()
+        .iter()
+        .all(|w| w.agg_siblings.len() == MAX_AGG_DEPTH));
+
+    let circuit = PorCircuit::new(
+        config::Shape::new(files_per_step, file_tree_depth, MAX_AGG_DEPTH),
+        Some(witness.witnesses().to_vec()),
+    )
+    .with_ledger_depth(claimed_ledger_depth.or(witness.ledger_depth()));
+
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let leaves = vec![FieldElement::ZERO; files_per_step];
+    let z = create_circuit_public_inputs(
+        &mut cs,
+        ledger.root(),
+        FieldElement::ZERO,
+        seed,
+        &ledger_indices,
+        &depths,
+        &leaves,
+    );
+    circuit.synthesize(&mut cs, &z).expect("synthesis succeeds");
+    (ledger.depth(), cs)
+}
+
+#[test]
+fn test_padded_circuit_shape_is_shared_across_ledger_depths() {
+    println!("Testing one padded circuit shape for ledgers of 2, 5 and 9 files");
+
+    let mut constraint_counts = Vec::new();
+    for (num_files, expected_depth) in [(2, 1), (5, 3), (9, 4)] {
+        let (ledger_depth, cs) = synthesize_padded_step(num_files, None);
+        assert_eq!(ledger_depth, expected_depth);
+        assert!(
+            cs.is_satisfied(),
+            "Padded circuit must accept the {}-file ledger: {:?}",
+            num_files,
+            cs.which_is_unsatisfied()
+        );
+        constraint_counts.push(cs.num_constraints());
+        println!(
+            "  {} files (ledger depth {}): {} constraints",
+            num_files,
+            ledger_depth,
+            cs.num_constraints()
+        );
+    }
+    assert!(
+        constraint_counts.windows(2).all(|w| w[0] == w[1]),
+        "Constraint counts differ: {:?}",
+        constraint_counts
+    );
+
+    // Activating a level the ledger does not have computes a different root
+    let (_, cs) = synthesize_padded_step(5, Some(MAX_AGG_DEPTH));
+    assert!(!cs.is_satisfied(), "Wrong ledger depth must not satisfy");
+
+    println!("✓ One shape serves all three ledgers");
+}
+
+#[test]
+fn test_padding_cost_per_level_is_constant() {
+    println!("Testing constraint cost of each padded aggregation level");
+
+    let count = |aggregated_tree_depth: usize| {
+        let circuit =
+            PorCircuit::<FieldElement>::new(config::Shape::new(2, 2, aggregated_tree_depth), None);
+        let mut cs = TestConstraintSystem::<FieldElement>::new();
+        let zeros = vec![0; 2];
+        let z = create_circuit_public_inputs(
+            &mut cs,
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+            &zeros,
+            &zeros,
+            &[FieldElement::ZERO; 2],
+        );
+        circuit.synthesize(&mut cs, &z).expect("synthesis succeeds");
+        cs.num_constraints()
+    };
+
+    let counts: Vec<usize> = (1..=config::MAX_AGG_DEPTH).map(count).collect();
+    let per_level: Vec<usize> = counts.windows(2).map(|w| w[1] - w[0]).collect();
+    for (depth, constraints) in (1..).zip(&counts) {
+        println!(
+            "  2 slots, agg depth {}: {} constraints",
+            depth, constraints
+        );
+    }
+    assert!(
+        per_level.windows(2).all(|w| w[0] == w[1]),
+        "Per-level cost must be uniform: {:?}",
+        per_level
+    );
+    println!(
+        "✓ Each aggregation level costs {} constraints for 2 slots",
+        per_level[0]
+    );
+}
+
+#[test]
+fn test_one_param_set_serves_ledgers_of_2_5_and_9_files() {
+    println!("Testing that one parameter set proves against ledgers of 2, 5 and 9 files");
+
+    let seed = FieldElement::from(17u64);
+    let mut generated_after_first = None;
+
+    for (num_files, expected_depth) in [(2, 1), (5, 3), (9, 4)] {
+        let (files, metadatas) = create_test_files(num_files, 300, 71);
+        let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+        assert_eq!(ledger.depth(), expected_depth);
+
+        let challenges: Vec<Challenge> = metadatas[..2]
+            .iter()
+            .map(|m| {
+                Challenge::new_test(m.clone(), 1000, 2, seed).with_max_agg_depth(MAX_AGG_DEPTH)
+            })
+            .collect();
+        let prepared: Vec<_> = metadatas[..2].iter().map(|m| &files[&m.file_id]).collect();
+
+        let system = PorSystem::new(&ledger);
+        let proof = system.prove(prepared, &challenges).unwrap();
+        assert_eq!(proof.shape.aggregated_tree_depth, MAX_AGG_DEPTH);
+        assert!(
+            system.verify(&proof, &challenges).unwrap(),
+            "Padded proof must verify against the {}-file ledger",
+            num_files
+        );
+
+        // An attestation of the ledger's real depth accepts the deeper padded proof; one
+        // committing to a depth beyond the proof's does not
+        let attested = ledger.attestation(100);
+        assert_eq!(
+            verify_ledger_root_with_attestations(&proof, std::slice::from_ref(&attested)).unwrap(),
+            &attested
+        );
+        let deeper = LedgerAttestation::new(
+            ledger.root(),
+            num_files as u64,
+            MAX_AGG_DEPTH as u64 + 1,
+            100,
+        );
+        assert!(deeper.is_consistent());
+        assert!(verify_ledger_root_with_attestations(&proof, &[deeper]).is_err());
+
+        match generated_after_first {
+            None => generated_after_first = Some(params::generated_count()),
+            Some(count) => assert_eq!(
+                params::generated_count(),
+                count,
+                "The {}-file ledger must reuse the existing parameters",
+                num_files
+            ),
+        }
+    }
+
+    println!("✓ No parameter regeneration across ledger depths");
+}
+
+#[test]
+fn test_plan_validates_max_agg_depth() {
+    println!("Testing max_agg_depth validation in planning");
+
+    let (_, metadatas) = create_test_files(9, 300, 71);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let seed = FieldElement::from(3u64);
+    let challenge = |i: usize| Challenge::new_test(metadatas[i].clone(), 1000, 1, seed);
+
+    let plan = api::plan::build_plan(
+        &[
+            challenge(0).with_max_agg_depth(MAX_AGG_DEPTH + 2),
+            challenge(1).with_max_agg_depth(MAX_AGG_DEPTH + 2),
+        ],
+        &ledger,
+    )
+    .unwrap();
+    assert_eq!(plan.aggregated_tree_depth, MAX_AGG_DEPTH + 2);
+
+    // Single-file proofs have no aggregation path to pad
+    let single = api::plan::build_plan(&[challenge(0).with_max_agg_depth(MAX_AGG_DEPTH)], &ledger);
+    assert_eq!(single.unwrap().aggregated_tree_depth, 0);
+
+    let mixed = api::plan::build_plan(
+        &[challenge(0).with_max_agg_depth(MAX_AGG_DEPTH), challenge(1)],
+        &ledger,
+    );
+    assert!(matches!(
+        mixed,
+        Err(KontorPoRError::ChallengeMismatch { field }) if field == "max_agg_depth"
+    ));
+
+    let too_shallow = api::plan::build_plan(
+        &[
+            challenge(0).with_max_agg_depth(3),
+            challenge(1).with_max_agg_depth(3),
+        ],
+        &ledger,
+    );
+    assert!(matches!(
+        too_shallow,
+        Err(KontorPoRError::LedgerDepthExceedsShape { depth: 4, max: 3 })
+    ));
+
+    for depth in [0, config::MAX_AGG_DEPTH + 1] {
+        let result = api::plan::build_plan(
+            &[
+                challenge(0).with_max_agg_depth(depth),
+                challenge(1).with_max_agg_depth(depth),
+            ],
+            &ledger,
+        );
+        assert!(
+            matches!(result, Err(KontorPoRError::InvalidInput(_))),
+            "max_agg_depth {} must be rejected",
+            depth
+        );
+    }
+
+    println!("✓ max_agg_depth validated");
+}
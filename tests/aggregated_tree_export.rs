@@ -0,0 +1,116 @@
+//! Tests for exporting the aggregated tree without per-file metadata
+//!
+//! 1. Exports verify and survive JSON and bincode round trips
+//! 2. Tampered leaves, roots, depths and versions are rejected
+//! 3. Proofs from an export equal the ledger's aggregation proofs
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, FieldElement},
+    config::AGGREGATED_TREE_EXPORT_VERSION,
+    ledger::{verify_aggregation_proof, AggregatedTreeExport, FileLedger},
+};
+
+fn ledger_with_files(count: u8) -> (FileLedger, Vec<api::FileMetadata>) {
+    let metadatas: Vec<_> = (0..count)
+        .map(|i| {
+            api::prepare_file(&[i + 1; 90], &format!("export_{}.dat", i))
+                .unwrap()
+                .1
+        })
+        .collect();
+    let mut ledger = FileLedger::new();
+    for metadata in &metadatas {
+        ledger.add_file(metadata).unwrap();
+    }
+    (ledger, metadatas)
+}
+
+#[test]
+fn test_export_round_trip_verifies() {
+    println!("Testing aggregated tree export round trips");
+
+    for count in [0, 1, 3, 8] {
+        let (ledger, _) = ledger_with_files(count);
+        let export = ledger.export_aggregated_tree();
+
+        assert_eq!(export.schema_version, AGGREGATED_TREE_EXPORT_VERSION);
+        assert_eq!(export.root, ledger.root());
+        assert_eq!(export.depth, ledger.depth());
+        assert_eq!(export.leaves, ledger.leaves());
+        assert!(export.verify(), "export of {} files should verify", count);
+
+        let json = serde_json::to_string(&export).unwrap();
+        let from_json: AggregatedTreeExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json, export);
+        assert!(from_json.verify());
+
+        let encoded = bincode::serialize(&export).unwrap();
+        let from_bincode: AggregatedTreeExport = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(from_bincode, export);
+        assert!(from_bincode.verify());
+    }
+
+    println!("✓ Exports verify after JSON and bincode round trips");
+}
+
+#[test]
+fn test_tampered_export_fails() {
+    println!("Testing that tampered exports are rejected");
+
+    let (ledger, _) = ledger_with_files(5);
+    let export = ledger.export_aggregated_tree();
+    assert!(export.verify());
+
+    let mut tampered = export.clone();
+    tampered.leaves[2] += FieldElement::ONE;
+    assert!(!tampered.verify(), "a changed leaf must not verify");
+    assert!(tampered.proof_for_index(0).is_none());
+
+    let mut tampered = export.clone();
+    tampered.root += FieldElement::ONE;
+    assert!(!tampered.verify(), "a changed root must not verify");
+
+    let mut tampered = export.clone();
+    tampered.leaves.swap(0, 1);
+    assert!(!tampered.verify(), "reordered leaves must not verify");
+
+    let mut tampered = export.clone();
+    tampered.leaves.pop();
+    assert!(!tampered.verify(), "a missing leaf must not verify");
+
+    let mut tampered = export.clone();
+    tampered.depth += 1;
+    assert!(!tampered.verify(), "a wrong depth must not verify");
+
+    let mut tampered = export;
+    tampered.schema_version += 1;
+    assert!(
+        !tampered.verify(),
+        "an unknown schema version must not verify"
+    );
+
+    println!("✓ Tampered exports rejected");
+}
+
+#[test]
+fn test_export_proofs_match_ledger() {
+    println!("Testing export proofs against the ledger's aggregation proofs");
+
+    let (ledger, metadatas) = ledger_with_files(6);
+    let export = ledger.export_aggregated_tree();
+
+    for metadata in &metadatas {
+        let (index, rc) = ledger.lookup(&metadata.file_id).unwrap();
+        let expected = ledger.get_aggregation_proof(&metadata.file_id).unwrap();
+        let proof = export.proof_for_index(index).unwrap();
+        assert_eq!(proof, expected);
+        assert!(verify_aggregation_proof(export.root, &proof, rc));
+    }
+
+    // Padding leaves have proofs too; indices past the tree do not
+    assert!(export.proof_for_index(metadatas.len()).is_some());
+    assert!(export.proof_for_index(export.leaves.len()).is_none());
+
+    println!("✓ Export proofs equal ledger proofs");
+}
@@ -1,6 +1,6 @@
 //! Tests for api.rs - state evolution, commitment matching, and ledger requirements
 use kontor_crypto::{
-    api::{self, Challenge, FieldElement, FileMetadata, PorSystem},
+    api::{self, Challenge, ErasureCode, FieldElement, FileMetadata, PorSystem, TreeArity},
     poseidon::{domain_tags, poseidon_hash_tagged},
 };
 use std::collections::BTreeMap;
@@ -201,7 +201,11 @@ fn test_single_file_ignores_ledger() {
         file_id: "other_file".to_string(),
         padded_len: 8, // depth 3
         original_size: 100,
-        filename: "other.dat".to_string(),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     };
     larger_ledger.add_file(&other_metadata).unwrap();
 
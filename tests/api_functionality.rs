@@ -4,11 +4,14 @@
 //! - ChallengeID determinism and collision resistance
 //! - Proof serialization round-trips
 //! - Batch seed validation
-//! - PorSystem interface
+//! - PorSystem interface, including one-step prepare and register and proving phase timings
 
 use kontor_crypto::{
-    api::{Challenge, FieldElement, PorSystem, Proof},
-    FileLedger,
+    api::{
+        Challenge, ChallengeID, ErasureCode, FieldElement, FileMetadata, PorSystem, Proof,
+        TreeArity,
+    },
+    FileLedger, KontorPoRError,
 };
 use std::collections::HashSet;
 
@@ -101,6 +104,51 @@ fn test_challenge_id_collision_resistance() {
     println!("  ✓ No collisions found in {} challenge IDs", ids.len());
 }
 
+#[test]
+fn test_challenge_id_stable_vectors() {
+    println!("Testing challenge ID derivation against fixed vectors");
+
+    let metadata = FileMetadata {
+        root: FieldElement::from(5u64),
+        file_id: "abc".to_string(),
+        padded_len: 8,
+        original_size: 100,
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
+    };
+    let challenge = Challenge::new(
+        metadata,
+        1000,
+        4,
+        FieldElement::from(7u64),
+        "prover".to_string(),
+    );
+
+    // SHA-256(tag 10 || height || seed || "abc" || root || log2(8) || 4 || "prover")
+    let expected = "986d8827a8a9c6314e7b25af48df5063358704babdc0778518a5f483314a6997";
+    assert_eq!(challenge.id().to_string(), expected);
+    assert_eq!(
+        challenge.clone().with_nonce(9).id().to_hex(),
+        "7030a3bdc7b64879f4a7d19125d26cb1c113f66cbc7b5f84c39b14254e49832c"
+    );
+
+    // Hex and serde round-trips preserve the ID
+    let id = challenge.id();
+    assert_eq!(ChallengeID::from_hex(expected).unwrap(), id);
+    assert!(ChallengeID::from_hex(&expected[1..]).is_err());
+    assert!(ChallengeID::from_hex(&expected.replace('9', "g")).is_err());
+    let encoded = bincode::serialize(&id).unwrap();
+    assert_eq!(bincode::deserialize::<ChallengeID>(&encoded).unwrap(), id);
+    let decoded: Challenge =
+        bincode::deserialize(&bincode::serialize(&challenge).unwrap()).unwrap();
+    assert_eq!(decoded.id(), id);
+
+    println!("  ✓ Challenge IDs match fixed vectors and survive serialization");
+}
+
 #[test]
 fn test_proof_serialization_roundtrip() {
     println!("Testing proof serialization round-trip");
@@ -255,6 +303,50 @@ fn test_porsystem_challenge_id_matching() {
     }
 }
 
+#[test]
+fn test_challenge_id_mismatch_names_position() {
+    println!("Testing that verify reports the mismatched challenge position");
+
+    let (prepared1, metadata1) = kontor_crypto::api::prepare_file(b"first", "a.dat").unwrap();
+    let (prepared2, metadata2) = kontor_crypto::api::prepare_file(b"second", "b.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(3u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 2, seed),
+        Challenge::new_test(metadata2.clone(), 1000, 2, seed),
+    ];
+    let proof = system
+        .prove(vec![&prepared1, &prepared2], &challenges)
+        .unwrap();
+    assert_eq!(
+        proof.challenge_ids,
+        challenges.iter().map(|c| c.id()).collect::<Vec<_>>()
+    );
+
+    let mut wrong = challenges.clone();
+    wrong[1] = Challenge::new_test(metadata2, 1001, 2, seed);
+    match system.verify(&proof, &wrong) {
+        Err(KontorPoRError::InvalidInput(msg)) => {
+            assert!(msg.contains("position 1"), "{}", msg);
+            assert!(msg.contains(&challenges[1].id().to_string()), "{}", msg);
+            assert!(msg.contains(&wrong[1].id().to_string()), "{}", msg);
+        }
+        _ => panic!("Expected InvalidInput naming position 1"),
+    }
+
+    let reordered = vec![challenges[1].clone(), challenges[0].clone()];
+    assert!(matches!(
+        system.verify(&proof, &reordered),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("position 0")
+    ));
+
+    println!("  ✓ Mismatches name the position and both IDs");
+}
+
 #[test]
 fn test_porsystem_file_not_found() {
     println!("Testing PorSystem error handling for missing files");
@@ -284,6 +376,103 @@ fn test_porsystem_file_not_found() {
     }
 }
 
+#[test]
+fn test_porsystem_missing_prepared_files_listed() {
+    println!("Testing that every challenge without a prepared file is reported up front");
+
+    let (prepared1, metadata1) = kontor_crypto::api::prepare_file(b"covered", "a.dat").unwrap();
+    let (_prepared2, metadata2) = kontor_crypto::api::prepare_file(b"missing 1", "b.dat").unwrap();
+    let (_prepared3, metadata3) = kontor_crypto::api::prepare_file(b"missing 2", "c.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    for metadata in [&metadata1, &metadata2, &metadata3] {
+        ledger.add_file(metadata).unwrap();
+    }
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(3u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 1, seed),
+        Challenge::new_test(metadata2.clone(), 1000, 1, seed),
+        Challenge::new_test(metadata3.clone(), 1000, 1, seed),
+    ];
+
+    match system.prove(vec![&prepared1], &challenges) {
+        Err(KontorPoRError::MissingPreparedFile { file_ids }) => {
+            assert_eq!(file_ids, vec![metadata2.file_id, metadata3.file_id]);
+            println!("  ✓ All uncovered challenges listed");
+        }
+        other => panic!("Expected MissingPreparedFile, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_porsystem_unused_prepared_file() {
+    println!("Testing handling of prepared files that no challenge refers to");
+
+    let (prepared1, metadata1) = kontor_crypto::api::prepare_file(b"challenged", "a.dat").unwrap();
+    let (prepared2, metadata2) = kontor_crypto::api::prepare_file(b"extra", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let challenge = Challenge::new_test(metadata1, 1000, 1, FieldElement::from(4u64));
+
+    // Strict mode rejects the extra file before any proving work
+    let strict = PorSystem::new(&ledger).with_reject_unused_files(true);
+    match strict.prove(
+        vec![&prepared1, &prepared2],
+        std::slice::from_ref(&challenge),
+    ) {
+        Err(KontorPoRError::UnusedPreparedFile { file_id }) => {
+            assert_eq!(file_id, metadata2.file_id);
+        }
+        other => panic!("Expected UnusedPreparedFile, got {:?}", other.map(|_| ())),
+    }
+
+    // By default the extra file is ignored
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(
+            vec![&prepared1, &prepared2],
+            std::slice::from_ref(&challenge),
+        )
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+    println!("  ✓ Extra files rejected in strict mode and ignored otherwise");
+}
+
+#[test]
+fn test_porsystem_file_order_does_not_matter() {
+    println!("Testing that prepared files are matched to challenges by file_id");
+
+    let (prepared1, metadata1) = kontor_crypto::api::prepare_file(b"first", "a.dat").unwrap();
+    let (prepared2, metadata2) = kontor_crypto::api::prepare_file(b"second", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(5u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 2, seed),
+        Challenge::new_test(metadata2, 1000, 2, seed),
+    ];
+
+    let forward = system
+        .prove(vec![&prepared1, &prepared2], &challenges)
+        .unwrap();
+    let reversed = system
+        .prove(vec![&prepared2, &prepared1], &challenges)
+        .unwrap();
+    assert!(system.verify(&forward, &challenges).unwrap());
+    assert!(system.verify(&reversed, &challenges).unwrap());
+    assert_eq!(forward.ledger_indices, reversed.ledger_indices);
+    println!("  ✓ Reordered file lists prove identically");
+}
+
 #[test]
 fn test_porsystem_prepare_file() {
     println!("Testing PorSystem::prepare_file method");
@@ -304,7 +493,8 @@ fn test_porsystem_prepare_file() {
 
     // Verify the results are correct
     assert_eq!(
-        metadata.filename, "porsystem_test.dat",
+        metadata.filename(),
+        "porsystem_test.dat",
         "Filename should be stored correctly"
     );
     assert_eq!(
@@ -321,19 +511,91 @@ fn test_porsystem_prepare_file() {
         "PreparedFile and FileMetadata should have matching root"
     );
 
-    // Test empty data rejection
-    let empty_result = system.prepare_file(&[], "empty.dat");
-    match empty_result {
-        Err(kontor_crypto::KontorPoRError::EmptyData { operation }) => {
-            assert_eq!(operation, "prepare_file", "Should report correct operation");
-            println!("  ✓ Empty data correctly rejected");
-        }
-        _ => panic!("Expected EmptyData error for empty input"),
-    }
+    // Test empty data support (single zero padding leaf)
+    let (empty_prepared, empty_metadata) = system
+        .prepare_file(&[], "empty.dat")
+        .expect("Empty data should be prepared");
+    assert_eq!(empty_metadata.original_size, 0);
+    assert_eq!(empty_metadata.padded_len, 1);
+    assert_eq!(empty_prepared.root, empty_metadata.root);
+    println!("  ✓ Empty data prepared as a single padding leaf");
 
     println!("  ✓ PorSystem::prepare_file method working correctly");
 }
 
+#[test]
+fn test_porsystem_prepare_and_register() {
+    println!("Testing PorSystem::prepare_and_register");
+
+    let data = b"Test data registered and proven in one call";
+    let mut ledger = FileLedger::new();
+    let (prepared, metadata) =
+        PorSystem::prepare_and_register(&mut ledger, data, "registered.dat").unwrap();
+
+    assert!(ledger.lookup(&metadata.file_id).is_some());
+    assert_eq!(ledger.historical_roots().len(), 1);
+    let root = ledger.root();
+
+    // A proof can be generated immediately, with no separate add_file call
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(5u64));
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+    println!("  ✓ Proof generated right after registration");
+
+    // Registering the same data again leaves the ledger untouched
+    let (_, again) = PorSystem::prepare_and_register(&mut ledger, data, "again.dat").unwrap();
+    assert_eq!(again.root, metadata.root);
+    assert_eq!(ledger.root(), root);
+    assert_eq!(ledger.historical_roots().len(), 1);
+
+    // Same file_id with a different root is a distinct error
+    let mut conflicting = metadata.clone();
+    conflicting.root += FieldElement::from(1u64);
+    let mut conflict_ledger = FileLedger::new();
+    conflict_ledger.add_file(&conflicting).unwrap();
+    let conflict_root = conflict_ledger.root();
+    let result = PorSystem::prepare_and_register(&mut conflict_ledger, data, "conflict.dat");
+    assert!(
+        matches!(result, Err(KontorPoRError::FileIdConflict { ref file_id }) if *file_id == metadata.file_id),
+        "Expected FileIdConflict"
+    );
+    assert_eq!(conflict_ledger.root(), conflict_root);
+    println!("  ✓ Conflicting registration rejected without mutating the ledger");
+}
+
+#[test]
+fn test_porsystem_prove_with_timings() {
+    println!("Testing PorSystem::prove_with_timings phase breakdown");
+
+    let (prepared, metadata) =
+        kontor_crypto::api::prepare_file(b"Timed proof data", "timed.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let challenge = Challenge::new_test(metadata, 1000, 3, FieldElement::from(9u64));
+    let (proof, timings) = system
+        .prove_with_timings(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+
+    assert!(!timings.folding_duration.is_zero());
+    assert!(!timings.compression_duration.is_zero());
+    assert!(!timings.witness_gen_duration.is_zero());
+    assert_eq!(
+        timings.total(),
+        timings.plan_duration
+            + timings.param_load_duration
+            + timings.witness_gen_duration
+            + timings.folding_duration
+            + timings.compression_duration
+    );
+    println!("  ✓ Phases reported: {:?}", timings);
+}
+
 #[test]
 fn test_porsystem_vs_free_function_equivalence() {
     println!("Testing equivalence between PorSystem methods and free functions");
@@ -0,0 +1,237 @@
+//! Tests for arity-4 file trees
+//!
+//! This module tests that:
+//! 1. Arity-4 trees and proofs round-trip and differ from binary trees
+//! 2. The arity-4 circuit needs fewer constraints than a binary circuit over the same leaves
+//! 3. Arity-4 files prove and verify end to end, with arity bound into rc and challenge IDs
+//! 4. Challenge batches cannot mix arities
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, TreeArity},
+    circuit::{FileProofWitness, PorCircuit},
+    config, merkle, poseidon,
+    utils::derive_leaf_index_for_file,
+    FileLedger, KontorPoRError,
+};
+use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
+use nova_snark::traits::circuit::StepCircuit;
+
+mod common;
+use common::fixtures::{create_circuit_public_inputs, create_test_data};
+
+fn synthetic_leaves(count: u64) -> Vec<FieldElement> {
+    (0..count).map(|i| FieldElement::from(i * 7 + 3)).collect()
+}
+
+#[test]
+fn test_arity4_tree_proofs_roundtrip() {
+    let leaves = synthetic_leaves(64);
+    let tree = merkle::build_tree_from_leaves_arity4(&leaves).unwrap();
+    assert_eq!(
+        tree.layers.len() - 1,
+        3,
+        "64 leaves form a depth-3 arity-4 tree"
+    );
+    assert_ne!(
+        tree.root(),
+        merkle::build_tree_from_leaves(&leaves).unwrap().root(),
+        "Arity-4 and binary roots must differ"
+    );
+
+    for leaf_index in [0, 1, 2, 3, 17, 42, 63] {
+        let proof = merkle::get_padded_proof4_for_leaf(&tree, leaf_index, 3).unwrap();
+        assert_eq!(proof.leaf, leaves[leaf_index]);
+        assert_eq!(proof.path_indices[0] as usize, leaf_index % 4);
+        assert!(merkle::verify_merkle_proof4_in_place(tree.root(), &proof));
+
+        let mut tampered = proof.clone();
+        tampered.siblings[1][2] += FieldElement::ONE;
+        assert!(!merkle::verify_merkle_proof4_in_place(
+            tree.root(),
+            &tampered
+        ));
+
+        let mut moved = proof;
+        moved.path_indices[0] = (moved.path_indices[0] + 1) % 4;
+        assert!(!merkle::verify_merkle_proof4_in_place(tree.root(), &moved));
+    }
+
+    // Padding to a deeper shape adds zero levels
+    let padded = merkle::get_padded_proof4_for_leaf(&tree, 5, 5).unwrap();
+    assert_eq!(padded.siblings.len(), 5);
+    assert_eq!(padded.flat_siblings().len(), 15);
+
+    assert_eq!(TreeArity::Arity4.padded_len(255), 256);
+    assert_eq!(TreeArity::Arity4.padded_len(256), 256);
+    assert_eq!(TreeArity::Arity4.padded_len(257), 1024);
+    assert_eq!(TreeArity::Arity4.padded_len(0), 1);
+    assert_eq!(TreeArity::Arity4.depth_for_len(1024), 5);
+}
+
+/// Synthesizes a satisfied single-file circuit for `leaves` with the given arity and
+/// returns its constraint count.
+fn satisfied_constraint_count(leaves: &[FieldElement], tree_arity: TreeArity) -> usize {
+    let seed = FieldElement::from(4242u64);
+    let (root, depth, leaf, file_siblings) = match tree_arity {
+        TreeArity::Binary => {
+            let tree = merkle::build_tree_from_leaves(leaves).unwrap();
+            let depth = tree.layers.len() - 1;
+            let index = derive_leaf_index_for_file(0, depth, seed, FieldElement::ZERO, false);
+            let proof = merkle::get_padded_proof_for_leaf(&tree, index, depth).unwrap();
+            (tree.root(), depth, proof.leaf, proof.siblings)
+        }
+        TreeArity::Arity4 => {
+            let tree = merkle::build_tree_from_leaves_arity4(leaves).unwrap();
+            let depth = tree.layers.len() - 1;
+            let index = derive_leaf_index_for_file(0, 2 * depth, seed, FieldElement::ZERO, false);
+            let proof = merkle::get_padded_proof4_for_leaf(&tree, index, depth).unwrap();
+            (tree.root(), depth, proof.leaf, proof.flat_siblings())
+        }
+    };
+
+    let witness = FileProofWitness {
+        leaf,
+        file_siblings,
+        file_root: root,
+        actual_depth: depth,
+        agg_siblings: vec![],
+        ledger_index: 0,
+    };
+    let circuit =
+        PorCircuit::<FieldElement>::new(config::Shape::new(1, depth, 0), Some(vec![witness]))
+            .with_tree_arity(tree_arity);
+
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let z = create_circuit_public_inputs(
+        &mut cs,
+        root,
+        FieldElement::ZERO,
+        seed,
+        &[0],
+        &[depth],
+        &[FieldElement::ZERO],
+    );
+    let outputs = circuit.synthesize(&mut cs, &z).unwrap();
+    assert!(
+        cs.is_satisfied(),
+        "{:?} circuit should be satisfied: {:?}",
+        tree_arity,
+        cs.which_is_unsatisfied()
+    );
+    assert_eq!(outputs.last().unwrap().get_value(), Some(leaf));
+
+    cs.num_constraints()
+}
+
+#[test]
+fn test_arity4_circuit_uses_fewer_constraints() {
+    println!("Comparing constraint counts for 256 leaves: binary depth 8 vs arity-4 depth 4");
+
+    let leaves = synthetic_leaves(256);
+    let binary = satisfied_constraint_count(&leaves, TreeArity::Binary);
+    let arity4 = satisfied_constraint_count(&leaves, TreeArity::Arity4);
+
+    println!("  binary (depth 8):  {} constraints", binary);
+    println!("  arity-4 (depth 4): {} constraints", arity4);
+    assert!(
+        arity4 < binary,
+        "Arity-4 circuit ({}) should need fewer constraints than binary ({})",
+        arity4,
+        binary
+    );
+
+    println!("✓ Arity-4 trees reduce constraints per challenged file");
+}
+
+#[test]
+fn test_arity4_files_prove_and_verify() {
+    println!("Testing arity-4 prove/verify for a two-file batch");
+
+    let data1 = create_test_data(4000, Some(1));
+    let data2 = create_test_data(900, Some(2));
+    let (prepared1, metadata1) =
+        api::prepare_file_with_arity(&data1, "a4_first.dat", TreeArity::Arity4).unwrap();
+    let (prepared2, metadata2) =
+        api::prepare_file_with_arity(&data2, "a4_second.dat", TreeArity::Arity4).unwrap();
+
+    assert_eq!(metadata1.tree_arity, TreeArity::Arity4);
+    assert_eq!(
+        4usize.pow(metadata1.depth() as u32),
+        metadata1.padded_len,
+        "Arity-4 files pad to a power of four"
+    );
+
+    // The same bytes prepared with a binary tree commit differently
+    let (_binary_prepared, binary_metadata) = api::prepare_file(&data1, "a4_first.dat").unwrap();
+    assert_ne!(binary_metadata.root, metadata1.root);
+    assert_eq!(binary_metadata.depth(), 2 * metadata1.depth());
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    // rc binds the arity through its own domain tag
+    let entry = ledger.entry(&metadata1.file_id).unwrap();
+    assert_eq!(
+        entry.rc,
+        poseidon::calculate_root_commitment_arity4(
+            metadata1.root,
+            FieldElement::from(metadata1.depth() as u64)
+        )
+    );
+    assert_ne!(
+        entry.rc,
+        poseidon::calculate_root_commitment(
+            metadata1.root,
+            FieldElement::from(metadata1.depth() as u64)
+        )
+    );
+
+    let seed = FieldElement::from(77u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1.clone(), 1000, 3, seed),
+        Challenge::new_test(metadata2, 1000, 3, seed),
+    ];
+
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&prepared1, &prepared2], &challenges)
+        .unwrap();
+    assert!(
+        system.verify(&proof, &challenges).unwrap(),
+        "Arity-4 proof should verify"
+    );
+
+    // The arity is part of the challenge identity
+    let mut relabeled = metadata1.clone();
+    relabeled.tree_arity = TreeArity::Binary;
+    assert_ne!(
+        Challenge::new_test(relabeled, 1000, 3, seed).id(),
+        challenges[0].id()
+    );
+
+    println!("✓ Arity-4 files prove and verify end to end");
+}
+
+#[test]
+fn test_mixed_tree_arities_rejected() {
+    let (prepared1, metadata1) =
+        api::prepare_file_with_arity(b"four-ary file", "a.dat", TreeArity::Arity4).unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"binary file", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 1, FieldElement::from(1u64)),
+        Challenge::new_test(metadata2, 1000, 1, FieldElement::from(1u64)),
+    ];
+
+    let result = PorSystem::new(&ledger).prove(vec![&prepared1, &prepared2], &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "tree_arity"),
+        "Mixed arities must be rejected"
+    );
+}
@@ -0,0 +1,162 @@
+//! Tests for machine-readable benchmark reports
+//!
+//! This module tests that:
+//! 1. A `BenchReport` round-trips through JSON with the documented field names
+//! 2. Reports of another schema version are rejected
+//! 3. A tiny end-to-end run populates every phase, each step and the proof size
+//! 4. Invalid workloads are rejected before any proving
+
+use kontor_crypto::{
+    reporting::{self, BenchConfig, BenchReport, PhaseDurations, StepDurations},
+    KontorPoRError,
+};
+
+fn tiny_config() -> BenchConfig {
+    BenchConfig {
+        num_files: 2,
+        file_size_bytes: 300,
+        ledger_files: 3,
+        num_challenges: 2,
+        seed: 7,
+    }
+}
+
+fn sample_report() -> BenchReport {
+    BenchReport {
+        schema_version: reporting::SCHEMA_VERSION,
+        crate_version: "0.1.5".to_string(),
+        git_revision: Some("abc1234".to_string()),
+        config: tiny_config(),
+        phases: PhaseDurations {
+            prepare_us: 1_500,
+            plan_us: 250,
+            param_load_us: 1_200_000,
+            witness_gen_us: 3_000,
+            folding_us: 40_125,
+            compression_us: 900_000,
+            verify_us: 80_000,
+        },
+        steps: vec![
+            StepDurations {
+                step: 0,
+                witness_gen_us: 1_000,
+                fold_us: 20_000,
+            },
+            StepDurations {
+                step: 1,
+                witness_gen_us: 2_000,
+                fold_us: 20_125,
+            },
+        ],
+        proof_size_bytes: 10240,
+        peak_rss_bytes: None,
+    }
+}
+
+#[test]
+fn test_report_schema_round_trip() {
+    let report = sample_report();
+    let json = report.to_json().unwrap();
+    assert_eq!(BenchReport::from_json(&json).unwrap(), report);
+
+    // Field names are part of the documented schema
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    for key in [
+        "schema_version",
+        "crate_version",
+        "git_revision",
+        "config",
+        "phases",
+        "steps",
+        "proof_size_bytes",
+        "peak_rss_bytes",
+    ] {
+        assert!(value.get(key).is_some(), "missing {}", key);
+    }
+    for key in [
+        "prepare_us",
+        "plan_us",
+        "param_load_us",
+        "witness_gen_us",
+        "folding_us",
+        "compression_us",
+        "verify_us",
+    ] {
+        assert!(value["phases"].get(key).is_some(), "missing phases.{}", key);
+    }
+    assert_eq!(value["steps"][1]["fold_us"], 20_125);
+    assert!(value["peak_rss_bytes"].is_null());
+
+    let mut future = value.clone();
+    future["schema_version"] = serde_json::json!(reporting::SCHEMA_VERSION + 1);
+    assert!(matches!(
+        BenchReport::from_json(&future.to_string()),
+        Err(KontorPoRError::Serialization(_))
+    ));
+}
+
+#[test]
+fn test_tiny_run_populates_phases() {
+    println!("Testing a tiny end-to-end benchmark run");
+
+    let config = tiny_config();
+    let report = reporting::run_bench(&config).unwrap();
+    assert_eq!(report.schema_version, reporting::SCHEMA_VERSION);
+    assert_eq!(report.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(report.config, config);
+
+    let phases = &report.phases;
+    for (name, us) in [
+        ("prepare", phases.prepare_us),
+        ("plan", phases.plan_us),
+        ("param load", phases.param_load_us),
+        ("witness gen", phases.witness_gen_us),
+        ("folding", phases.folding_us),
+        ("compression", phases.compression_us),
+        ("verify", phases.verify_us),
+    ] {
+        assert!(us > 0, "{} duration not recorded", name);
+    }
+
+    assert_eq!(report.steps.len(), config.num_challenges);
+    for (i, step) in report.steps.iter().enumerate() {
+        assert_eq!(step.step, i);
+        assert!(step.fold_us > 0, "step {} fold not recorded", i);
+        assert!(step.witness_gen_us > 0, "step {} witness not recorded", i);
+    }
+    // Per-step values are truncated separately, so the sum can fall short by a microsecond each
+    let summed: u64 = report.steps.iter().map(|s| s.fold_us).sum();
+    assert!(summed <= phases.folding_us);
+    assert!(phases.folding_us - summed <= config.num_challenges as u64);
+
+    assert!(report.proof_size_bytes > 0);
+    if cfg!(target_os = "linux") {
+        assert!(report.peak_rss_bytes.unwrap() > 0);
+    }
+
+    let json = report.to_json().unwrap();
+    assert_eq!(BenchReport::from_json(&json).unwrap(), report);
+
+    println!("✓ Phase durations, steps and proof size populated");
+}
+
+#[test]
+fn test_invalid_workloads_rejected() {
+    let no_challenges = BenchConfig {
+        num_challenges: 0,
+        ..tiny_config()
+    };
+    assert!(matches!(
+        reporting::run_bench(&no_challenges),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    let small_ledger = BenchConfig {
+        ledger_files: 1,
+        ..tiny_config()
+    };
+    assert!(matches!(
+        reporting::run_bench(&small_ledger),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+}
@@ -0,0 +1,205 @@
+//! Tests for strict canonical decoding of field elements
+//!
+//! This module tests that:
+//! 1. Ledger files with over-modulus roots, rc values or historical roots fail to load
+//! 2. `Proof::from_bytes` rejects an over-modulus ledger root
+//! 3. Serialized metadata and challenges reject over-modulus field bytes
+//! 4. `is_valid_root` cannot be satisfied by an alternate encoding of a historical root
+
+use ff::PrimeField;
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem, Proof},
+    merkle::canonical::{field_from_bytes, field_to_bytes},
+    FileLedger, KontorPoRError,
+};
+
+/// The field modulus as 32 little-endian bytes.
+fn modulus_bytes() -> [u8; 32] {
+    let hex = FieldElement::MODULUS.trim_start_matches("0x");
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let end = hex.len() - 2 * i;
+        *byte = u8::from_str_radix(&hex[end - 2..end], 16).unwrap();
+    }
+    bytes
+}
+
+/// `element + p`: a 32-byte string that wraps to `element` if reduced instead of rejected.
+fn alternate_encoding(element: FieldElement) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut carry = 0u16;
+    for ((byte, a), b) in out
+        .iter_mut()
+        .zip(field_to_bytes(&element))
+        .zip(modulus_bytes())
+    {
+        let sum = a as u16 + b as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0);
+    out
+}
+
+/// Replaces the last occurrence of `needle` in `haystack`.
+fn replace_last(haystack: &mut [u8], needle: &[u8; 32], replacement: &[u8; 32]) {
+    let position = haystack
+        .windows(32)
+        .rposition(|window| window == needle)
+        .expect("encoded field element should be present");
+    haystack[position..position + 32].copy_from_slice(replacement);
+}
+
+fn two_file_ledger() -> (FileLedger, Vec<api::PreparedFile>, Vec<FileMetadata>) {
+    let (prepared1, metadata1) = api::prepare_file(b"canonical file one", "one.dat").unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"canonical file two", "two.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+    (
+        ledger,
+        vec![prepared1, prepared2],
+        vec![metadata1, metadata2],
+    )
+}
+
+fn assert_non_canonical<T>(result: Result<T, KontorPoRError>, expected_context: &str) {
+    match result {
+        Err(KontorPoRError::NonCanonicalFieldElement { context }) => {
+            assert_eq!(context, expected_context)
+        }
+        Err(other) => panic!("expected NonCanonicalFieldElement, got {:?}", other),
+        Ok(_) => panic!("non-canonical encoding was accepted"),
+    }
+}
+
+#[test]
+fn test_alternate_encoding_is_not_canonical() {
+    let element = FieldElement::from(12345u64);
+    let alias = alternate_encoding(element);
+    assert_ne!(alias, field_to_bytes(&element));
+    assert_eq!(field_from_bytes(field_to_bytes(&element)), Some(element));
+    assert_eq!(field_from_bytes(alias), None);
+}
+
+#[test]
+fn test_ledger_load_rejects_non_canonical_fields() {
+    println!("Testing ledger load with over-modulus field bytes");
+
+    // Keep only the pre-modification root, so the current root is encoded once
+    let (mut ledger, _, metadatas) = two_file_ledger();
+    ledger
+        .set_historical_roots(vec![ledger.historical_roots()[0]])
+        .unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "kontor_canonical_ledger_{}.bin",
+        std::process::id()
+    ));
+    ledger.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    assert!(FileLedger::load(&path).is_ok());
+
+    // The pre-modification root of a one-file ledger is that file's rc, so take the other one
+    let entry_rc = ledger.entry(&metadatas[1].file_id).unwrap().rc;
+    let historical = field_from_bytes(ledger.historical_roots()[0]).unwrap();
+    let cases = [
+        (ledger.root(), "ledger"),
+        (entry_rc, "ledger"),
+        (historical, "ledger historical roots"),
+    ];
+    for (element, context) in cases {
+        let mut tampered = saved.clone();
+        replace_last(
+            &mut tampered,
+            &field_to_bytes(&element),
+            &alternate_encoding(element),
+        );
+        std::fs::write(&path, &tampered).unwrap();
+        assert_non_canonical(FileLedger::load(&path), context);
+    }
+
+    std::fs::remove_file(&path).ok();
+    println!("✓ Over-modulus roots, rc values and historical roots rejected");
+}
+
+#[test]
+fn test_proof_from_bytes_rejects_non_canonical_ledger_root() {
+    println!("Testing Proof::from_bytes with an over-modulus ledger root");
+
+    let (ledger, files, metadata) = two_file_ledger();
+    let seed = FieldElement::from(3u64);
+    let challenges: Vec<Challenge> = metadata
+        .into_iter()
+        .map(|m| Challenge::new_test(m, 1000, 2, seed))
+        .collect();
+    let proof = PorSystem::new(&ledger)
+        .prove(files.iter().collect(), &challenges)
+        .unwrap();
+    let bytes = proof.to_bytes().unwrap();
+    assert!(Proof::from_bytes(&bytes).is_ok());
+
+    let mut tampered = bytes;
+    replace_last(
+        &mut tampered,
+        &field_to_bytes(&proof.ledger_root),
+        &alternate_encoding(proof.ledger_root),
+    );
+    assert_non_canonical(Proof::from_bytes(&tampered), "proof");
+
+    println!("✓ Over-modulus ledger root rejected");
+}
+
+#[test]
+fn test_metadata_and_challenge_reject_non_canonical_fields() {
+    let (_, metadata) = api::prepare_file(b"canonical metadata", "meta.dat").unwrap();
+
+    let mut encoded = bincode::serialize(&metadata).unwrap();
+    replace_last(
+        &mut encoded,
+        &field_to_bytes(&metadata.root),
+        &alternate_encoding(metadata.root),
+    );
+    let error = bincode::deserialize::<FileMetadata>(&encoded).unwrap_err();
+    assert!(error.to_string().contains("non-canonical"), "{}", error);
+
+    let seed = FieldElement::from(987654321u64);
+    let challenge = Challenge::new_test(metadata, 1000, 2, seed);
+    let mut encoded = bincode::serialize(&challenge).unwrap();
+    replace_last(
+        &mut encoded,
+        &field_to_bytes(&seed),
+        &alternate_encoding(seed),
+    );
+    let error = bincode::deserialize::<Challenge>(&encoded).unwrap_err();
+    assert!(error.to_string().contains("non-canonical"), "{}", error);
+}
+
+#[test]
+fn test_is_valid_root_not_spoofed_by_alternate_encoding() {
+    println!("Testing is_valid_root against alternate root encodings");
+
+    let (mut ledger, _, _) = two_file_ledger();
+    let old_root = field_from_bytes(ledger.historical_roots()[0]).unwrap();
+    assert_ne!(old_root, ledger.root());
+    assert!(ledger.is_valid_root(old_root));
+
+    // An over-modulus alias of the old root does not make it valid
+    ledger
+        .set_historical_roots(vec![alternate_encoding(old_root)])
+        .unwrap();
+    assert!(!ledger.is_valid_root(old_root));
+
+    // Nor does an alias of some other root validate whatever it wraps to
+    let unrelated = FieldElement::from(42u64);
+    ledger
+        .set_historical_roots(vec![alternate_encoding(unrelated)])
+        .unwrap();
+    assert!(!ledger.is_valid_root(unrelated));
+
+    ledger
+        .set_historical_roots(vec![field_to_bytes(&old_root)])
+        .unwrap();
+    assert!(ledger.is_valid_root(old_root));
+
+    println!("✓ Only canonical historical roots are accepted");
+}
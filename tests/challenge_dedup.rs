@@ -0,0 +1,87 @@
+//! Tests for collapsing duplicate challenges when proving
+//!
+//! This module tests that:
+//! 1. Challenges with identical IDs share one slot and the proof verifies against the
+//!    original, duplicated list
+//! 2. Challenges for the same file with different seeds are not collapsed
+//! 3. `ProveOptions::allow_duplicates` proves every occurrence in its own slot
+
+use kontor_crypto::api::{Challenge, FieldElement, PorSystem, ProveOptions};
+use kontor_crypto::FileLedger;
+
+mod common;
+use common::fixtures::create_test_files;
+
+#[test]
+fn test_exact_duplicates_share_a_slot() {
+    println!("Testing collapsed duplicate challenges");
+
+    let (files, metadatas) = create_test_files(2, 200, 610);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+
+    let seed = FieldElement::from(61u64);
+    let challenge = |i: usize| Challenge::new_test(metadatas[i].clone(), 1000, 2, seed);
+    let challenges = vec![challenge(0), challenge(1), challenge(0)];
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+
+    // Every challenge is listed, but the two copies are proven in one slot
+    assert_eq!(proof.challenge_ids.len(), 3);
+    assert_eq!(proof.challenge_slots[0], proof.challenge_slots[2]);
+    assert_ne!(proof.challenge_slots[0], proof.challenge_slots[1]);
+    assert_eq!(proof.ledger_indices.len(), 2);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // The proof answers the duplicated list, not the deduplicated one
+    assert!(system.verify(&proof, &challenges[..2]).is_err());
+
+    println!("✓ Duplicate challenges are proven once and verify as given");
+}
+
+#[test]
+fn test_same_file_different_seeds_are_kept() {
+    println!("Testing challenges for one file with different seeds");
+
+    let (files, metadatas) = create_test_files(1, 200, 620);
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadatas[0]).unwrap();
+
+    let challenges: Vec<Challenge> = [62u64, 63]
+        .into_iter()
+        .map(|seed| Challenge::new_test(metadatas[0].clone(), 1000, 2, FieldElement::from(seed)))
+        .collect();
+    assert_ne!(challenges[0].id(), challenges[1].id());
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert_ne!(proof.challenge_slots[0], proof.challenge_slots[1]);
+    assert_eq!(proof.ledger_indices.len(), 2);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Distinct challenges for the same file keep their own slots");
+}
+
+#[test]
+fn test_allow_duplicates_proves_every_occurrence() {
+    println!("Testing ProveOptions::allow_duplicates");
+
+    let (files, metadatas) = create_test_files(1, 200, 630);
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadatas[0]).unwrap();
+
+    let challenge = Challenge::new_test(metadatas[0].clone(), 1000, 2, FieldElement::from(64u64));
+    let challenges = vec![challenge.clone(), challenge];
+
+    let system = PorSystem::new(&ledger);
+    let options = ProveOptions::default().with_allow_duplicates(true);
+    let proof = system
+        .prove_with_options(files.values().collect(), &challenges, &options)
+        .unwrap();
+    assert_eq!(proof.challenge_slots, vec![0, 1]);
+    assert_eq!(proof.ledger_indices.len(), 2);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Opting out proves each duplicate in its own slot");
+}
@@ -0,0 +1,160 @@
+//! Tests for minting challenges as the `challenge new` CLI command does
+//!
+//! This module tests that:
+//! 1. `FileMetadata::to_json` round-trips metadata, and the same metadata, block hash and
+//!    settings always produce the same challenge JSON
+//! 2. `Challenge::seed_from_block_hash` is deterministic and separates block hashes
+//! 3. A challenge minted from metadata JSON and a block hash proves and verifies after a
+//!    round trip through its JSON
+//! 4. `preview_leaf_indices` matches the leaves the prover opens (with the `transcript`
+//!    feature), returns explicit indices as given and rejects another file
+
+use kontor_crypto::{
+    api::{prepare_file_matching, Challenge, ChallengeKind, FieldElement, FileMetadata, PorSystem},
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_data, create_test_files};
+
+const BLOCK_HASH: [u8; 32] = [0xab; 32];
+
+/// Mints a challenge the way `challenge new --meta ... --block-hash ...` does.
+fn mint(metadata_json: &str, block_hash: &[u8; 32], num: usize) -> String {
+    let metadata = FileMetadata::from_json(metadata_json).unwrap();
+    let seed = Challenge::seed_from_block_hash(block_hash);
+    let challenge = Challenge::new(metadata, 1000, num, seed, "node_1".to_string());
+    challenge.validate().unwrap();
+    challenge.to_json().unwrap()
+}
+
+#[test]
+fn test_minting_is_deterministic() {
+    println!("Testing that minting a challenge is deterministic");
+
+    let (_, metadatas) = create_test_files(1, 400, 21);
+    let metadata = metadatas[0].with_annotation("rack", "b7");
+    let metadata_json = metadata.to_json().unwrap();
+    assert_eq!(FileMetadata::from_json(&metadata_json).unwrap(), metadata);
+    assert!(matches!(
+        FileMetadata::from_json(&metadata_json.replace("\"file_id\"", "\"id\"")),
+        Err(KontorPoRError::Serialization(_))
+    ));
+
+    let json = mint(&metadata_json, &BLOCK_HASH, 5);
+    assert_eq!(mint(&metadata_json, &BLOCK_HASH, 5), json);
+    assert_ne!(mint(&metadata_json, &[0xac; 32], 5), json);
+
+    let challenge = Challenge::from_json(&json).unwrap();
+    assert_eq!(challenge.file_metadata, metadata);
+    assert_eq!(challenge.num_challenges, 5);
+    assert_eq!(challenge.prover_id, "node_1");
+
+    println!("✓ Identical inputs mint identical challenges");
+}
+
+#[test]
+fn test_seed_from_block_hash() {
+    println!("Testing seed derivation from block hashes");
+
+    let seed = Challenge::seed_from_block_hash(&BLOCK_HASH);
+    assert_eq!(Challenge::seed_from_block_hash(&BLOCK_HASH), seed);
+    assert_ne!(seed, FieldElement::from(0u64));
+
+    let mut other = BLOCK_HASH;
+    other[31] ^= 1;
+    assert_ne!(Challenge::seed_from_block_hash(&other), seed);
+
+    println!("✓ Seeds are deterministic per block hash");
+}
+
+#[test]
+fn test_minted_challenge_proves_and_verifies() {
+    println!("Testing prove and verify against a minted challenge");
+
+    let (files, metadatas) = create_test_files(1, 600, 23);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let json = mint(&metadatas[0].to_json().unwrap(), &BLOCK_HASH, 3);
+
+    // The prover receives the JSON
+    let challenge = Challenge::from_json(&json).unwrap();
+    let system = PorSystem::new(&ledger);
+    let prepared = &files[&metadatas[0].file_id];
+    let proof = system
+        .prove(vec![prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+
+    println!("✓ Minted challenge proves and verifies");
+}
+
+#[test]
+fn test_preview_leaf_indices() {
+    println!("Testing leaf index previews");
+
+    let data = create_test_data(2000, Some(25));
+    let (_, metadatas) = create_test_files(1, 2000, 25);
+    let metadata = &metadatas[0];
+    let prepared = prepare_file_matching(&data, metadata).unwrap();
+    assert_eq!(prepared.root, metadata.root);
+
+    let seed = Challenge::seed_from_block_hash(&BLOCK_HASH);
+    let challenge = Challenge::new(metadata.clone(), 1000, 4, seed, "node_1".to_string());
+    let indices = challenge.preview_leaf_indices(&prepared).unwrap();
+    assert_eq!(indices.len(), 4);
+    assert!(indices.iter().all(|&i| i < metadata.padded_len));
+    assert_eq!(challenge.preview_leaf_indices(&prepared).unwrap(), indices);
+
+    let explicit = challenge
+        .clone()
+        .with_kind(ChallengeKind::ExplicitIndices(vec![3, 1, 4, 1]));
+    assert_eq!(
+        explicit.preview_leaf_indices(&prepared).unwrap(),
+        vec![3, 1, 4, 1]
+    );
+
+    let (other_files, _) = create_test_files(1, 900, 26);
+    let other = other_files.values().next().unwrap();
+    assert!(matches!(
+        challenge.preview_leaf_indices(other),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        prepare_file_matching(&create_test_data(2000, Some(26)), metadata),
+        Err(KontorPoRError::RootMismatch { .. })
+    ));
+
+    println!("✓ Previews are deterministic and reject other files");
+}
+
+#[cfg(feature = "transcript")]
+#[test]
+fn test_preview_matches_prover_openings() {
+    use kontor_crypto::api::{Transcript, TranscriptLabel};
+
+    println!("Testing previews against the prover's transcript");
+
+    let (files, metadatas) = create_test_files(1, 800, 27);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let prepared = &files[&metadatas[0].file_id];
+    let seed = Challenge::seed_from_block_hash(&BLOCK_HASH);
+    let challenge = Challenge::new(metadatas[0].clone(), 1000, 3, seed, "node_1".to_string());
+
+    let mut transcript = Transcript::new();
+    PorSystem::new(&ledger)
+        .prove_with_transcript(
+            vec![prepared],
+            std::slice::from_ref(&challenge),
+            &mut transcript,
+        )
+        .unwrap();
+    let opened: Vec<usize> = transcript
+        .entries()
+        .iter()
+        .filter(|entry| entry.label == TranscriptLabel::LeafIndex)
+        .map(|entry| entry.value.parse().unwrap())
+        .collect();
+    assert_eq!(challenge.preview_leaf_indices(prepared).unwrap(), opened);
+
+    println!("✓ Preview matches the leaves the prover opened");
+}
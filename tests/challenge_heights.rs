@@ -0,0 +1,130 @@
+//! Tests for temporal checks on challenge block heights
+//!
+//! This module tests that:
+//! 1. Challenges above the verifier's current height fail with `ChallengeFromFuture`
+//! 2. Without a current height, challenge heights are not checked
+//! 3. A ledger tracking roots by height only accepts the root in effect at the challenge
+//!    height, even if a later root is otherwise valid
+//! 4. Root heights survive a ledger save and load
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, VerifyOptions},
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_single_file_ledger, create_test_files};
+
+#[test]
+fn test_challenge_from_future_rejected() {
+    println!("Testing challenges above the current block height");
+
+    let (files, metadatas) = create_test_files(1, 300, 51);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let system = PorSystem::new(&ledger);
+    let challenges = [Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        2,
+        FieldElement::from(4u64),
+    )];
+    let proof = system
+        .prove(vec![&files[&metadatas[0].file_id]], &challenges)
+        .unwrap();
+
+    // Permissive default: no current height, no temporal check
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let at_height = VerifyOptions::default().with_current_block_height(1000);
+    assert!(system
+        .verify_with_options(&proof, &challenges, &at_height)
+        .unwrap());
+
+    let before = VerifyOptions::default().with_current_block_height(999);
+    let result = system.verify_with_options(&proof, &challenges, &before);
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::ChallengeFromFuture {
+                height: 1000,
+                current: 999
+            })
+        ),
+        "Expected ChallengeFromFuture, got {:?}",
+        result
+    );
+
+    println!("✓ Future challenge rejected");
+}
+
+#[test]
+fn test_root_must_be_in_effect_at_challenge_height() {
+    println!("Testing ledger roots tracked by block height");
+
+    let (files, metadatas) = create_test_files(3, 300, 52);
+    let mut ledger = create_multi_file_ledger(&metadatas[..2].iter().collect::<Vec<_>>());
+    ledger.record_root_at_height(900).unwrap();
+    let old_root = ledger.root();
+
+    let seed = FieldElement::from(5u64);
+    let challenges_at = |height: u64| -> Vec<Challenge> {
+        metadatas[..2]
+            .iter()
+            .map(|m| Challenge::new_test(m.clone(), height, 1, seed))
+            .collect()
+    };
+    let prepared: Vec<_> = metadatas[..2].iter().map(|m| &files[&m.file_id]).collect();
+
+    let challenges = challenges_at(1000);
+    let old_proof = PorSystem::new(&ledger)
+        .prove(prepared.clone(), &challenges)
+        .unwrap();
+
+    // The ledger changes at height 1001
+    ledger.add_file(&metadatas[2]).unwrap();
+    ledger.record_root_at_height(1001).unwrap();
+    assert_eq!(ledger.root_at_height(1000), Some(old_root));
+    assert_eq!(ledger.root_at_height(1001), Some(ledger.root()));
+    assert_eq!(ledger.root_at_height(899), None);
+
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&old_proof, &challenges).unwrap());
+
+    // A proof against the newer root is valid at 1001 but not for height-1000 challenges
+    let new_proof = system.prove(prepared.clone(), &challenges).unwrap();
+    assert!(ledger.is_valid_root(new_proof.ledger_root));
+    let result = system.verify(&new_proof, &challenges);
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::RootNotValidAtHeight { height: 1000, .. })
+        ),
+        "Expected RootNotValidAtHeight, got {:?}",
+        result
+    );
+
+    let later = challenges_at(1001);
+    let later_proof = system.prove(prepared, &later).unwrap();
+    assert!(system.verify(&later_proof, &later).unwrap());
+
+    println!("✓ Roots checked against challenge heights");
+}
+
+#[test]
+fn test_root_heights_persist() {
+    let (_, metadatas) = create_test_files(2, 300, 53);
+    let mut ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    ledger.record_root_at_height(42).unwrap();
+
+    let path = std::env::temp_dir().join(format!(
+        "kontor_root_heights_ledger_{}.bin",
+        std::process::id()
+    ));
+    ledger.save(&path).unwrap();
+    let loaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert!(loaded.tracks_root_heights());
+    assert_eq!(loaded.root_at_height(100), Some(ledger.root()));
+    assert_eq!(loaded.root_at_height(41), None);
+}
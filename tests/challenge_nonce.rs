@@ -0,0 +1,129 @@
+//! Tests for challenge nonces (replay protection)
+//!
+//! This module tests that:
+//! 1. Nonces are bound into the initial chain state, so proofs for different nonces
+//!    do not cross-verify
+//! 2. Replayed (proof, challenge) pairs are detectable via the nonce commitment
+//! 3. Nonce-free challenges keep their IDs, state and proofs
+
+use ff::{Field, PrimeField};
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, Proof},
+    poseidon::calculate_nonce_commitment,
+    FileLedger,
+};
+use std::collections::HashSet;
+
+fn copy(proof: &Proof) -> Proof {
+    Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap()
+}
+
+#[test]
+fn test_different_nonces_do_not_cross_verify() {
+    println!("Testing that proofs for different nonces do not cross-verify");
+
+    let (prepared, metadata) = api::prepare_file(&[7u8; 1500], "nonce.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(55u64);
+    let challenge1 = Challenge::new_test(metadata.clone(), 1000, 3, seed).with_nonce(1);
+    let challenge2 = Challenge::new_test(metadata, 1000, 3, seed).with_nonce(2);
+    assert_ne!(challenge1.id(), challenge2.id());
+
+    let proof1 = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge1))
+        .unwrap();
+    let proof2 = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge2))
+        .unwrap();
+    assert_eq!(
+        proof1.challenge_nonce_commitment,
+        calculate_nonce_commitment(&[1])
+    );
+    assert_ne!(
+        proof1.challenge_nonce_commitment,
+        proof2.challenge_nonce_commitment
+    );
+
+    assert!(system
+        .verify(&proof1, std::slice::from_ref(&challenge1))
+        .unwrap());
+    assert!(system
+        .verify(&proof2, std::slice::from_ref(&challenge2))
+        .unwrap());
+
+    // Bypassing the challenge-ID check, the nonce commitment still rejects the swap
+    assert!(!api::verify_raw(std::slice::from_ref(&challenge2), &proof1, &ledger).unwrap());
+
+    // Relabeling the commitment does not help: the SNARK is bound to the original state
+    let mut relabeled = copy(&proof1);
+    relabeled.challenge_nonce_commitment = proof2.challenge_nonce_commitment;
+    assert!(!matches!(
+        api::verify_raw(std::slice::from_ref(&challenge2), &relabeled, &ledger),
+        Ok(true)
+    ));
+
+    println!("✓ Nonces separate otherwise identical challenges");
+}
+
+#[test]
+fn test_replays_detectable_via_nonce_commitment() {
+    println!("Testing replay detection with a set of accepted nonce commitments");
+
+    let (prepared1, metadata1) = api::prepare_file(b"first replay file", "a.dat").unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"second replay file", "b.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(8u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 2, seed).with_nonce(41),
+        Challenge::new_test(metadata2, 1000, 2, seed).with_nonce(42),
+    ];
+    let proof = system
+        .prove(vec![&prepared1, &prepared2], &challenges)
+        .unwrap();
+    assert_ne!(proof.challenge_nonce_commitment, FieldElement::ZERO);
+
+    let mut seen = HashSet::new();
+    let mut accept = |proof: &Proof| {
+        system.verify(proof, &challenges).unwrap()
+            && seen.insert(proof.challenge_nonce_commitment.to_repr().as_ref().to_vec())
+    };
+
+    assert!(accept(&proof), "First submission should be accepted");
+    assert!(
+        !accept(&copy(&proof)),
+        "Replayed submission must be detected"
+    );
+
+    println!("✓ Replayed (proof, challenge) pairs detected");
+}
+
+#[test]
+fn test_zero_nonce_is_backward_compatible() {
+    println!("Testing that nonce 0 keeps legacy challenge IDs and state");
+
+    let (prepared, metadata) = api::prepare_file(b"legacy challenge", "legacy.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let legacy = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(3u64));
+    assert_eq!(legacy.nonce, 0);
+    assert_eq!(legacy.clone().with_nonce(0).id(), legacy.id());
+    assert_ne!(legacy.clone().with_nonce(1).id(), legacy.id());
+    assert_eq!(calculate_nonce_commitment(&[0, 0]), FieldElement::ZERO);
+
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&legacy))
+        .unwrap();
+    assert_eq!(proof.challenge_nonce_commitment, FieldElement::ZERO);
+    assert!(system.verify(&proof, &[legacy]).unwrap());
+
+    println!("✓ Nonce-free challenges prove and verify as before");
+}
@@ -0,0 +1,270 @@
+//! Tests for the JSON transport encoding of challenges and signed challenges
+//!
+//! This module tests that:
+//! 1. `Challenge::to_json` and `from_json` round-trip challenges using every optional
+//!    setting, deterministically, with hex field elements and an explicit metadata object
+//! 2. Decoding rejects unknown schema versions, unknown or missing fields, malformed and
+//!    non-canonical hex, and challenges that fail `Challenge::validate`
+//! 3. `SignedChallenge` round-trips through JSON and verifies under the issuer's key only;
+//!    any change to the challenge or signature fails with `InvalidChallengeSignature`
+//! 4. A prover that accepts a verified signed challenge produces a proof the issuer verifies
+
+use kontor_crypto::{
+    api::{
+        prepare_file_raw, Challenge, ChallengeKind, ChallengeMode, FieldElement, FileMetadata,
+        PorSystem, ProofBinding, SeedSchedule, SignedChallenge, SigningKey,
+    },
+    CircuitMode, KontorPoRError,
+};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_data, create_test_files};
+
+fn metadata() -> FileMetadata {
+    let (_, metadatas) = create_test_files(1, 300, 11);
+    metadatas[0].with_annotation("rack", "b7")
+}
+
+fn full_challenge(metadata: FileMetadata) -> Challenge {
+    let seeds = (0..3).map(|i| FieldElement::from(100 + i as u64)).collect();
+    Challenge::new(
+        metadata,
+        812_345,
+        3,
+        FieldElement::from(42u64),
+        "node-7".to_string(),
+    )
+    .with_nonce(99)
+    .with_ledger_id("tenant-a")
+    .with_mode(ChallengeMode::Private)
+    .with_circuit_mode(CircuitMode::Strict)
+    .with_seed_schedule(SeedSchedule::PerStep(seeds))
+    .with_max_agg_depth(4)
+    .with_binding(ProofBinding::LedgerRoot)
+}
+
+/// An edit applied to an encoded challenge.
+type Edit = Box<dyn FnOnce(&mut Value)>;
+
+/// Encodes `challenge`, applies `edit` to the JSON and decodes it again.
+fn decode_edited(
+    challenge: &Challenge,
+    edit: impl FnOnce(&mut Value),
+) -> kontor_crypto::Result<Challenge> {
+    let mut value: Value = serde_json::from_str(&challenge.to_json().unwrap()).unwrap();
+    edit(&mut value);
+    Challenge::from_json(&value.to_string())
+}
+
+#[test]
+fn test_challenge_json_round_trip() {
+    let challenge = full_challenge(metadata());
+    let json = challenge.to_json().unwrap();
+    assert_eq!(Challenge::from_json(&json).unwrap(), challenge);
+    assert_eq!(challenge.clone().to_json().unwrap(), json);
+
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"], json!(1));
+    assert_eq!(
+        value["seed"],
+        json!(format!("2a{}", "0".repeat(62))),
+        "field elements are little-endian hex"
+    );
+    assert_eq!(
+        value["file_metadata"]["file_id"],
+        json!(challenge.file_metadata.file_id)
+    );
+    assert_eq!(value["file_metadata"]["annotations"]["rack"], json!("b7"));
+    assert_eq!(value["per_step_seeds"].as_array().unwrap().len(), 3);
+
+    // Defaults are omitted, and raw files keep an explicit null erasure code
+    let (_, raw) = prepare_file_raw(&create_test_data(500, Some(3)), "raw.dat").unwrap();
+    let explicit = Challenge::new_test(raw, 10, 2, FieldElement::from(1u64))
+        .with_kind(ChallengeKind::ExplicitIndices(vec![0, 1]));
+    let json = explicit.to_json().unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["file_metadata"]["erasure"], Value::Null);
+    assert_eq!(value["explicit_indices"], json!([0, 1]));
+    for omitted in ["per_step_seeds", "ledger_id", "max_agg_depth", "binding"] {
+        assert!(
+            value.get(omitted).is_none(),
+            "{} should be omitted",
+            omitted
+        );
+    }
+    assert_eq!(Challenge::from_json(&json).unwrap(), explicit);
+}
+
+#[test]
+fn test_challenge_json_rejects_invalid_input() {
+    let challenge = full_challenge(metadata());
+
+    let result = decode_edited(&challenge, |v| v["schema_version"] = json!(2));
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::UnsupportedChallengeVersion {
+                found: 2,
+                supported: 1
+            })
+        ),
+        "got {:?}",
+        result
+    );
+
+    let serialization: [(&str, Edit); 6] = [
+        ("unknown field", Box::new(|v| v["priority"] = json!(1))),
+        (
+            "unknown metadata field",
+            Box::new(|v| v["file_metadata"]["chunk_size"] = json!(31)),
+        ),
+        (
+            "missing erasure code",
+            Box::new(|v| {
+                v["file_metadata"]
+                    .as_object_mut()
+                    .unwrap()
+                    .remove("erasure");
+            }),
+        ),
+        (
+            "missing version",
+            Box::new(|v| {
+                v.as_object_mut().unwrap().remove("schema_version");
+            }),
+        ),
+        (
+            "uppercase hex",
+            Box::new(|v| v["seed"] = json!(format!("2A{}", "0".repeat(62)))),
+        ),
+        ("short hex", Box::new(|v| v["seed"] = json!("2a"))),
+    ];
+    for (case, edit) in serialization {
+        let result = decode_edited(&challenge, edit);
+        assert!(
+            matches!(result, Err(KontorPoRError::Serialization(_))),
+            "{}: got {:?}",
+            case,
+            result
+        );
+    }
+
+    let result = decode_edited(&challenge, |v| {
+        v["file_metadata"]["root"] = json!("f".repeat(64))
+    });
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::NonCanonicalFieldElement { .. })
+    ));
+
+    // Decoded challenges are validated
+    let result = decode_edited(&challenge, |v| v["num_challenges"] = json!(0));
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidChallengeCount { count: 0 })
+    ));
+    let result = decode_edited(&challenge, |v| v["num_challenges"] = json!(2));
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidSeedSchedule {
+            expected: 2,
+            got: 3
+        })
+    ));
+    let result = decode_edited(&challenge, |v| v["file_metadata"]["padded_len"] = json!(24));
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidMetadata { field, .. }) if field == "padded_len"
+    ));
+    let result = decode_edited(&challenge, |v| v["max_agg_depth"] = json!(0));
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+}
+
+#[test]
+fn test_signed_challenge_verification() {
+    let issuer = SigningKey::from_bytes(&[7u8; 32]);
+    let other = SigningKey::from_bytes(&[8u8; 32]);
+    let challenge = full_challenge(metadata());
+
+    let signed = SignedChallenge::sign(challenge.clone(), &issuer).unwrap();
+    let json = signed.to_json().unwrap();
+    let received = SignedChallenge::from_json(&json).unwrap();
+    assert_eq!(received, signed);
+    assert_eq!(
+        received.verify(&issuer.verifying_key()).unwrap(),
+        &challenge
+    );
+
+    assert!(matches!(
+        received.verify(&other.verifying_key()),
+        Err(KontorPoRError::InvalidChallengeSignature)
+    ));
+
+    // Changing any part of the challenge, even an annotation, breaks the signature
+    let tamper: [Edit; 3] = [
+        Box::new(|v| v["challenge"]["block_height"] = json!(812_346)),
+        Box::new(|v| v["challenge"]["file_metadata"]["annotations"]["rack"] = json!("c1")),
+        Box::new(|v| {
+            let signature = v["signature"].as_str().unwrap();
+            let flipped = if signature.starts_with('0') { "1" } else { "0" };
+            v["signature"] = json!(format!("{}{}", flipped, &signature[1..]));
+        }),
+    ];
+    for edit in tamper {
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        edit(&mut value);
+        let tampered = SignedChallenge::from_json(&value.to_string()).unwrap();
+        assert!(matches!(
+            tampered.verify(&issuer.verifying_key()),
+            Err(KontorPoRError::InvalidChallengeSignature)
+        ));
+    }
+
+    // The embedded challenge is decoded as strictly as a bare one
+    let mut value: Value = serde_json::from_str(&json).unwrap();
+    value["challenge"]["schema_version"] = json!(7);
+    assert!(matches!(
+        SignedChallenge::from_json(&value.to_string()),
+        Err(KontorPoRError::UnsupportedChallengeVersion { found: 7, .. })
+    ));
+    let mut value: Value = serde_json::from_str(&json).unwrap();
+    value["signature"] = json!("00");
+    assert!(matches!(
+        SignedChallenge::from_json(&value.to_string()),
+        Err(KontorPoRError::Serialization(_))
+    ));
+}
+
+#[test]
+fn test_signed_challenge_end_to_end() {
+    println!("Testing a signed challenge sent from verifier to prover");
+
+    let issuer = SigningKey::from_bytes(&[21u8; 32]);
+    let (files, metadatas) = create_test_files(1, 400, 12);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+
+    // Verifier service
+    let challenge = Challenge::new(
+        metadatas[0].redact(),
+        77,
+        2,
+        FieldElement::from(5u64),
+        "node-1".to_string(),
+    );
+    let wire = SignedChallenge::sign(challenge.clone(), &issuer)
+        .unwrap()
+        .to_json()
+        .unwrap();
+
+    // Prover service: check the signature before proving
+    let received = SignedChallenge::from_json(&wire).unwrap();
+    let accepted = received.verify(&issuer.verifying_key()).unwrap().clone();
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&files[&metadatas[0].file_id]], &[accepted])
+        .unwrap();
+
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+    println!("✓ Proof for the transported challenge verified");
+}
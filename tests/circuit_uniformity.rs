@@ -8,6 +8,7 @@ use ff::Field;
 use kontor_crypto::{
     api::FieldElement,
     circuit::{FileProofWitness, PorCircuit},
+    config,
 };
 
 mod common;
@@ -49,9 +50,7 @@ fn test_mixed_depth_proofs_have_identical_fingerprints() {
     );
 
     let circuit1 = PorCircuit::<FieldElement>::new(
-        FILES_PER_STEP,
-        TEST_DEPTH,
-        AGG_DEPTH,
+        config::Shape::new(FILES_PER_STEP, TEST_DEPTH, AGG_DEPTH),
         Some(vec![witness1_file1, witness1_file2]),
     );
 
@@ -77,9 +76,7 @@ fn test_mixed_depth_proofs_have_identical_fingerprints() {
     );
 
     let circuit2 = PorCircuit::<FieldElement>::new(
-        FILES_PER_STEP,
-        TEST_DEPTH,
-        AGG_DEPTH,
+        config::Shape::new(FILES_PER_STEP, TEST_DEPTH, AGG_DEPTH),
         Some(vec![witness2_file1, witness2_file2]),
     );
 
@@ -109,9 +106,7 @@ fn test_mixed_depth_proofs_have_identical_fingerprints() {
     );
 
     let circuit3 = PorCircuit::<FieldElement>::new(
-        FILES_PER_STEP,
-        TEST_DEPTH,
-        AGG_DEPTH,
+        config::Shape::new(FILES_PER_STEP, TEST_DEPTH, AGG_DEPTH),
         Some(vec![witness3_file1, witness3_file2]),
     );
     let fp3 = fingerprint_shape(&circuit3);
@@ -128,9 +123,10 @@ fn test_mixed_depth_proofs_have_identical_fingerprints() {
     );
 
     let single_circuit1 = PorCircuit::<FieldElement>::new(
-        1, // Single file
-        TEST_DEPTH,
-        0, // No aggregation
+        config::Shape::new(
+            1, // Single file
+            TEST_DEPTH, 0, // No aggregation
+        ),
         Some(vec![single_witness1]),
     );
     let single_fp1 = fingerprint_shape(&single_circuit1);
@@ -147,9 +143,10 @@ fn test_mixed_depth_proofs_have_identical_fingerprints() {
     );
 
     let single_circuit2 = PorCircuit::<FieldElement>::new(
-        1, // Single file
-        TEST_DEPTH,
-        0, // No aggregation
+        config::Shape::new(
+            1, // Single file
+            TEST_DEPTH, 0, // No aggregation
+        ),
         Some(vec![single_witness2]),
     );
     let single_fp2 = fingerprint_shape(&single_circuit2);
@@ -180,7 +177,8 @@ fn test_setup_circuit_has_correct_structure() {
 
     // Single-file setup circuit
     let single_circuit = PorCircuit::<FieldElement>::new(
-        1, TEST_DEPTH, 0, None, // No witnesses for setup
+        config::Shape::new(1, TEST_DEPTH, 0),
+        None, // No witnesses for setup
     );
 
     assert_eq!(single_circuit.file_tree_depth, TEST_DEPTH);
@@ -189,8 +187,10 @@ fn test_setup_circuit_has_correct_structure() {
 
     // Multi-file setup circuit with aggregation
     let setup_circuit = PorCircuit::<FieldElement>::new(
-        4, // 4 files per step
-        TEST_DEPTH, 2,    // aggregation depth
+        config::Shape::new(
+            4, // 4 files per step
+            TEST_DEPTH, 2, // aggregation depth
+        ),
         None, // No witnesses for setup
     );
 
@@ -220,7 +220,10 @@ fn test_circuit_witness_structure() {
         });
     }
 
-    let circuit = PorCircuit::<FieldElement>::new(FILES, TEST_DEPTH, AGG_DEPTH, Some(witnesses));
+    let circuit = PorCircuit::<FieldElement>::new(
+        config::Shape::new(FILES, TEST_DEPTH, AGG_DEPTH),
+        Some(witnesses),
+    );
 
     // Basic validation
     assert_eq!(circuit.file_tree_depth, TEST_DEPTH);
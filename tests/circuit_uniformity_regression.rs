@@ -27,9 +27,7 @@ fn test_param_gen_vs_proving_uniformity() {
     // 1. Generate parameters using dummy witnesses (same as params.rs does)
     println!("Step 1: Generate parameters with dummy witnesses");
     let _params = kontor_crypto::params::load_or_generate_params(
-        files_per_step,
-        file_tree_depth,
-        aggregated_tree_depth,
+        kontor_crypto::config::Shape::new(files_per_step, file_tree_depth, aggregated_tree_depth),
     )
     .expect("Should generate parameters");
 
@@ -1,10 +1,13 @@
 use kontor_crypto::circuit::{FileProofWitness, PorCircuit};
+use kontor_crypto::circuit_safety::{diagnose_unsatisfied, ConstraintClass};
 use kontor_crypto::config;
 use kontor_crypto::merkle::{build_tree, get_padded_proof_for_leaf};
 use kontor_crypto::poseidon::{domain_tags, poseidon_hash_tagged};
 
 mod common;
-use common::fixtures::{create_circuit_public_inputs, E1, E2, F1, S1, S2};
+use common::fixtures::{
+    circuit_public_input_values, create_circuit_public_inputs, E1, E2, F1, S1, S2,
+};
 use ff::Field;
 use nova_snark::frontend::{
     gadgets::{
@@ -47,7 +50,10 @@ fn test_por_circuit_basic() {
     };
     // Phase 3: Use proper shape derivation to ensure minimum depth 1
     let (files_per_step, file_tree_depth) = config::derive_shape(1, depth);
-    let circuit = PorCircuit::new(files_per_step, file_tree_depth, 0, Some(vec![witness]));
+    let circuit = PorCircuit::new(
+        config::Shape::new(files_per_step, file_tree_depth, 0),
+        Some(vec![witness]),
+    );
     let pp =
         PublicParams::<E1, E2, PorCircuit<F1>>::setup(&circuit, &*S1::ck_floor(), &*S2::ck_floor())
             .expect("Failed to setup public params");
@@ -115,7 +121,7 @@ fn test_multi_file_constructor() {
             ledger_index: 1,
         },
     ];
-    let circuit = PorCircuit::<F1>::new(2, 1, 1, Some(witnesses)); // 2 files, depth 1, agg depth 1
+    let circuit = PorCircuit::<F1>::new(config::Shape::new(2, 1, 1), Some(witnesses)); // 2 files, depth 1, agg depth 1
 
     // Check that the circuit was created successfully
     assert_eq!(circuit.files_per_step, 2);
@@ -127,7 +133,6 @@ fn test_multi_file_constructor() {
 
 #[test]
 fn test_por_circuit_invalid_sibling() {
-    use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
     let data = vec![vec![1u8], vec![2u8], vec![3u8], vec![4u8]];
     let (tree, root) = build_tree(&data).expect("Failed to build tree for test");
     let depth = 2;
@@ -152,10 +157,8 @@ fn test_por_circuit_invalid_sibling() {
         agg_siblings: vec![],
         ledger_index: 0,
     };
-    let invalid_circuit = PorCircuit::new(1, depth, 0, Some(vec![witness])); // Single file
-    let mut cs = TestConstraintSystem::<F1>::new();
-    let z = create_circuit_public_inputs(
-        &mut cs,
+    let invalid_circuit = PorCircuit::new(config::Shape::new(1, depth, 0), Some(vec![witness])); // Single file
+    let z = circuit_public_input_values(
         root,
         acc_in,
         random_seed,
@@ -163,12 +166,17 @@ fn test_por_circuit_invalid_sibling() {
         &[depth], // depths
         &[F1::ZERO],
     );
-    let result = invalid_circuit.synthesize(&mut cs, &z);
-    assert!(result.is_ok(), "Synthesis should succeed");
-    assert!(
-        !cs.is_satisfied(),
-        "Circuit with invalid Merkle sibling should not satisfy constraints"
-    );
+    let report = diagnose_unsatisfied(&invalid_circuit, &z).expect("Synthesis should succeed");
+    // The bad sibling yields a different file root, caught where it meets the public root
+    let unsatisfied = report
+        .unsatisfied
+        .expect("Circuit with invalid Merkle sibling should not satisfy constraints");
+    assert_eq!(unsatisfied.class, ConstraintClass::RootEquality);
+    assert_eq!(unsatisfied.file_slot, Some(0));
+    assert!(unsatisfied
+        .assignments
+        .iter()
+        .any(|(path, value)| path.starts_with("z0/") && *value == root));
 }
 
 #[test]
@@ -191,7 +199,10 @@ fn test_por_circuit_mismatched_depth() {
         agg_siblings: vec![],
         ledger_index: 0,
     };
-    let circuit = PorCircuit::new(1, declared_depth, 0, Some(vec![witness])); // Single file
+    let circuit = PorCircuit::new(
+        config::Shape::new(1, declared_depth, 0),
+        Some(vec![witness]),
+    ); // Single file
 
     // Check that circuit was created with correct depth
     assert_eq!(circuit.file_tree_depth, declared_depth);
@@ -231,7 +242,10 @@ fn test_por_circuit_zero_depth() {
     };
     // Phase 3: Use proper shape derivation to ensure minimum depth 1
     let (files_per_step, file_tree_depth) = config::derive_shape(1, depth);
-    let circuit = PorCircuit::new(files_per_step, file_tree_depth, 0, Some(vec![witness]));
+    let circuit = PorCircuit::new(
+        config::Shape::new(files_per_step, file_tree_depth, 0),
+        Some(vec![witness]),
+    );
     let pp =
         PublicParams::<E1, E2, PorCircuit<F1>>::setup(&circuit, &*S1::ck_floor(), &*S2::ck_floor())
             .expect("Failed to setup public params");
@@ -288,7 +302,10 @@ fn test_por_circuit_accumulator_update() {
     };
     // Phase 3: Use proper shape derivation to ensure minimum depth 1
     let (files_per_step, file_tree_depth) = config::derive_shape(1, depth);
-    let circuit = PorCircuit::new(files_per_step, file_tree_depth, 0, Some(vec![witness]));
+    let circuit = PorCircuit::new(
+        config::Shape::new(files_per_step, file_tree_depth, 0),
+        Some(vec![witness]),
+    );
 
     let pp =
         PublicParams::<E1, E2, PorCircuit<F1>>::setup(&circuit, &*S1::ck_floor(), &*S2::ck_floor())
@@ -346,7 +363,10 @@ fn test_por_circuit_wrong_root() {
     };
     // Phase 3: Use proper shape derivation to ensure minimum depth 1
     let (files_per_step, file_tree_depth) = config::derive_shape(1, depth);
-    let circuit = PorCircuit::new(files_per_step, file_tree_depth, 0, Some(vec![witness]));
+    let circuit = PorCircuit::new(
+        config::Shape::new(files_per_step, file_tree_depth, 0),
+        Some(vec![witness]),
+    );
 
     let pp =
         PublicParams::<E1, E2, PorCircuit<F1>>::setup(&circuit, &*S1::ck_floor(), &*S2::ck_floor())
@@ -441,7 +461,10 @@ fn test_constraint_count() {
         };
         // Phase 3: Use proper shape derivation to ensure minimum depth 1
         let (files_per_step, file_tree_depth) = config::derive_shape(1, depth);
-        let circuit = PorCircuit::new(files_per_step, file_tree_depth, 0, Some(vec![witness]));
+        let circuit = PorCircuit::new(
+            config::Shape::new(files_per_step, file_tree_depth, 0),
+            Some(vec![witness]),
+        );
         let mut cs = TestConstraintSystem::<F1>::new();
         let z = create_circuit_public_inputs(
             &mut cs,
@@ -457,3 +480,56 @@ fn test_constraint_count() {
         println!("Depth {}: {} constraints", depth, cs.num_constraints());
     }
 }
+
+#[test]
+fn test_shared_tag_gadget_matches_tagged_gadget() {
+    use kontor_crypto::circuit::gadgets::poseidon::{
+        poseidon_hash_shared_tag_gadget, poseidon_hash_tagged_gadget, TagConstants,
+    };
+    let x_val = F1::from(7u64);
+    let y_val = F1::from(11u64);
+    let expected = poseidon_hash_tagged(domain_tags::node(), x_val, y_val);
+
+    // Each tagged hash allocates and constrains its own tag
+    let mut tagged_cs = TestConstraintSystem::<F1>::new();
+    let x = AllocatedNum::alloc(tagged_cs.namespace(|| "x"), || Ok(x_val)).unwrap();
+    let y = AllocatedNum::alloc(tagged_cs.namespace(|| "y"), || Ok(y_val)).unwrap();
+    for i in 0..3 {
+        let hash = poseidon_hash_tagged_gadget(
+            tagged_cs.namespace(|| format!("hash_{}", i)),
+            domain_tags::node(),
+            &x,
+            &y,
+        )
+        .unwrap();
+        assert_eq!(hash.get_value(), Some(expected));
+    }
+    assert!(tagged_cs.is_satisfied());
+
+    // Shared tags are constrained once however many hashes use them
+    let mut shared_cs = TestConstraintSystem::<F1>::new();
+    let x = AllocatedNum::alloc(shared_cs.namespace(|| "x"), || Ok(x_val)).unwrap();
+    let y = AllocatedNum::alloc(shared_cs.namespace(|| "y"), || Ok(y_val)).unwrap();
+    let tags = TagConstants::alloc(
+        shared_cs.namespace(|| "tags"),
+        &[domain_tags::node(), domain_tags::node()],
+    )
+    .unwrap();
+    for i in 0..3 {
+        let hash = poseidon_hash_shared_tag_gadget(
+            shared_cs.namespace(|| format!("hash_{}", i)),
+            tags.get(domain_tags::node()),
+            &x,
+            &y,
+        )
+        .unwrap();
+        assert_eq!(hash.get_value(), Some(expected));
+    }
+    assert!(shared_cs.is_satisfied());
+
+    assert_eq!(
+        tagged_cs.num_constraints() - shared_cs.num_constraints(),
+        2,
+        "three hashes under one shared tag should save two tag constraints"
+    );
+}
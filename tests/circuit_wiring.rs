@@ -7,6 +7,7 @@ use ff::{Field, PrimeField};
 use kontor_crypto::{
     api::FieldElement,
     circuit::{FileProofWitness, PorCircuit},
+    config,
     poseidon::{domain_tags, poseidon_hash_tagged},
     utils::derive_index_from_bits,
 };
@@ -45,9 +46,11 @@ fn test_poseidon_hash_tagged_gadget_matches_off_circuit() {
 
     // Test with a circuit that uses this witness
     let circuit = PorCircuit::<FieldElement>::new(
-        1,          // files_per_step
-        tree_depth, // file_tree_depth
-        0,          // aggregated_tree_depth (single file)
+        config::Shape::new(
+            1,          // files_per_step
+            tree_depth, // file_tree_depth
+            0,          // aggregated_tree_depth (single file)
+        ),
         Some(vec![witness.clone()]),
     );
 
@@ -139,9 +142,11 @@ fn test_state_chaining_gadget_correctness() {
     }
 
     let circuit = PorCircuit::<FieldElement>::new(
-        4, // files_per_step (padded to power of 2)
-        1, // file_tree_depth
-        0, // aggregated_tree_depth
+        config::Shape::new(
+            4, // files_per_step (padded to power of 2)
+            1, // file_tree_depth
+            0, // aggregated_tree_depth
+        ),
         Some(witnesses),
     );
 
@@ -270,9 +275,11 @@ fn test_challenge_index_derivation_consistency() {
 
     // Create circuit with the witness
     let circuit = PorCircuit::<FieldElement>::new(
-        1,     // files_per_step
-        depth, // file_tree_depth
-        0,     // aggregated_tree_depth
+        config::Shape::new(
+            1,     // files_per_step
+            depth, // file_tree_depth
+            0,     // aggregated_tree_depth
+        ),
         Some(vec![witness]),
     );
 
@@ -17,7 +17,7 @@ fn get_expected_aggregated_root(
 ) -> FieldElement {
     if let Some(ledger) = ledger {
         // Multi-file case: use ledger root
-        ledger.tree.root()
+        ledger.root()
     } else {
         // Single-file case: use the file root from the first (and only) challenge
         challenges[0].file_metadata.root
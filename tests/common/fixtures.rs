@@ -227,11 +227,15 @@ pub fn setup_test_scenario(
     // Generate parameters after we know the number of challenges
     let agg_depth = ledger
         .as_ref()
-        .map(|l| l.tree.layers.len() - 1)
+        .map(|l| l.tree_layers_len() - 1)
         .unwrap_or(0);
     let (files_per_step, file_tree_depth) = config::derive_shape(challenges.len(), max_file_depth);
-    let params = params::load_or_generate_params(files_per_step, file_tree_depth, agg_depth)
-        .expect("Failed to load or generate parameters");
+    let params = params::load_or_generate_params(config::Shape::new(
+        files_per_step,
+        file_tree_depth,
+        agg_depth,
+    ))
+    .expect("Failed to load or generate parameters");
 
     Ok(TestSetup {
         params,
@@ -254,6 +258,21 @@ pub fn create_test_data(size: usize, seed: Option<u64>) -> Vec<u8> {
     data
 }
 
+/// Returns a copy of `file` whose leaves at `indices` have a nonzero 32nd byte.
+///
+/// With bincode's default encoding the tree's layer count and leaf count are each an
+/// 8-byte prefix, followed by the leaves as 32-byte little-endian representations. The
+/// stored root is left as is, so the file still matches its metadata.
+pub fn widen_leaves(file: &PreparedFile, indices: impl IntoIterator<Item = usize>) -> PreparedFile {
+    let mut bytes = bincode::serialize(file).unwrap();
+    for index in indices {
+        let top = 16 + 32 * index + 31;
+        assert_eq!(bytes[top], 0, "prepared leaves encode 31-byte symbols");
+        bytes[top] = 1;
+    }
+    bincode::deserialize(&bytes).unwrap()
+}
+
 /// Creates allocated public inputs (z0) for circuit testing.
 /// This centralizes the repetitive pattern of allocating z0 vectors.
 /// Phase 3: Updated for new public I/O schema [agg_root, state_in, seed, ledger_indices..., depths..., leaves...]
@@ -307,6 +326,31 @@ pub fn create_circuit_public_inputs<F: PrimeField, CS: ConstraintSystem<F>>(
     z
 }
 
+/// The values [`create_circuit_public_inputs`] would allocate, for
+/// `circuit_safety::diagnose_unsatisfied`.
+pub fn circuit_public_input_values<F: PrimeField>(
+    agg_root: F,
+    state_in: F,
+    seed: F,
+    ledger_indices: &[usize],
+    depths: &[usize],
+    leaf_values: &[F],
+) -> Vec<F> {
+    let mut cs = nova_snark::frontend::util_cs::test_cs::TestConstraintSystem::<F>::new();
+    create_circuit_public_inputs(
+        &mut cs,
+        agg_root,
+        state_in,
+        seed,
+        ledger_indices,
+        depths,
+        leaf_values,
+    )
+    .iter()
+    .map(|z| z.get_value().expect("public inputs are assigned"))
+    .collect()
+}
+
 /// DEPRECATED: Meta commitments are no longer used in Phase 3 circuit.
 /// Security now comes from public depth binding per slot.
 /// This function is kept only for legacy tests that haven't been updated yet.
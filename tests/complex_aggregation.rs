@@ -71,7 +71,7 @@ fn test_awkward_file_count_padding() {
         file_tree_depth,
         file_tree_depth,
         FieldElement::ZERO,
-        ledger.tree.layers.len() - 1,
+        ledger.tree_layers_len() - 1,
         0,
         &dummy_ledger_indices,
     )
@@ -245,7 +245,7 @@ fn test_maximum_file_aggregation() {
     assert!(is_valid, "Proof with 7 files should verify");
 
     // Check aggregation tree depth
-    let agg_depth = ledger.tree.layers.len() - 1;
+    let agg_depth = ledger.tree_layers_len() - 1;
     let expected_agg_depth = (num_files as f64).log2().ceil() as usize;
 
     println!("✓ 7 files aggregated successfully");
@@ -0,0 +1,110 @@
+//! Tests for the constraint pre-check before folding (`ProveOptions::precheck_constraints`)
+//!
+//! This module tests that:
+//! 1. Honest single-file and multi-file proofs prove and verify with the pre-check on
+//! 2. A prepared file with a corrupted Merkle sibling fails with
+//!    `ConstraintPrecheckFailed`, naming the step, the failing namespace and the
+//!    corrupted file's slot, instead of a generic SNARK failure
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, PreparedFile, ProveOptions},
+    circuit_safety::ConstraintClass,
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_files;
+
+/// Copy of `file` whose first interior tree layer holds the first leaf in every node, so
+/// every leaf's sibling at that level is wrong while the leaves and the root are unchanged.
+fn with_corrupted_sibling(file: &PreparedFile) -> PreparedFile {
+    let mut json = serde_json::to_value(file).unwrap();
+    let leaf = json["tree"]["layers"][0][0].clone();
+    for node in json["tree"]["layers"][1].as_array_mut().unwrap() {
+        *node = leaf.clone();
+    }
+    serde_json::from_value(json).unwrap()
+}
+
+fn precheck() -> ProveOptions {
+    ProveOptions::default().with_precheck_constraints(true)
+}
+
+#[test]
+fn test_precheck_accepts_honest_witnesses() {
+    println!("Testing the constraint pre-check on honest proofs");
+
+    let (files, metadatas) = create_test_files(2, 400, 1650);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(165u64);
+
+    let single = vec![Challenge::new_test(metadatas[0].clone(), 1000, 3, seed)];
+    let proof = system
+        .prove_with_options(vec![&files[&metadatas[0].file_id]], &single, &precheck())
+        .unwrap();
+    assert!(system.verify(&proof, &single).unwrap());
+
+    let multi: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 3, seed))
+        .collect();
+    let proof = system
+        .prove_with_options(files.values().collect(), &multi, &precheck())
+        .unwrap();
+    assert!(system.verify(&proof, &multi).unwrap());
+
+    println!("✓ Honest witnesses pass the pre-check");
+}
+
+#[test]
+fn test_precheck_names_the_broken_constraint() {
+    println!("Testing the constraint pre-check on a corrupted Merkle sibling");
+
+    let (files, metadatas) = create_test_files(1, 400, 1660);
+    let metadata = &metadatas[0];
+    let corrupted = with_corrupted_sibling(&files[&metadata.file_id]);
+    assert_eq!(corrupted.root, metadata.root);
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+    let challenges = vec![Challenge::new_test(
+        metadata.clone(),
+        1000,
+        2,
+        FieldElement::from(166u64),
+    )];
+
+    let result = system.prove_with_options(vec![&corrupted], &challenges, &precheck());
+    match result {
+        Err(KontorPoRError::ConstraintPrecheckFailed {
+            step,
+            ref path,
+            class,
+            file_slot,
+        }) => {
+            // A wrong sibling hashes to a wrong file root, which the root check rejects
+            assert_eq!(step, 0);
+            assert_eq!(class, ConstraintClass::RootEquality);
+            assert_eq!(file_slot, Some(0));
+            assert!(path.starts_with("file_0/"), "got {path}");
+            println!("  pre-check reported {path}");
+        }
+        Err(e) => panic!("expected ConstraintPrecheckFailed, got {e:?}"),
+        Ok(_) => panic!("expected ConstraintPrecheckFailed, got a proof"),
+    }
+
+    // Without the pre-check the broken witness is folded and only fails later
+    let result = system.prove(vec![&corrupted], &challenges);
+    assert!(
+        !matches!(result, Err(KontorPoRError::ConstraintPrecheckFailed { .. })),
+        "the pre-check is off by default"
+    );
+    if let Ok(proof) = result {
+        assert!(!matches!(system.verify(&proof, &challenges), Ok(true)));
+    }
+
+    println!("✓ The pre-check names the unsatisfied constraint");
+}
@@ -0,0 +1,191 @@
+//! Tests for preparing several files as one dataset
+//!
+//! This module tests that:
+//! 1. A three-file dataset reconstructs to its files, whatever order they were given in
+//! 2. A single file is extracted from only the codewords it occupies
+//! 3. A dataset is challenged, proven and verified as one file
+//! 4. Duplicate paths, non-canonical manifests and mismatched metadata are rejected
+
+use kontor_crypto::{
+    api::{
+        self, Challenge, DatasetManifest, DatasetMetadata, FieldElement, PorSystem,
+        PreparedDataset, SymbolStore,
+    },
+    config, FileLedger, KontorPoRError,
+};
+
+/// Every stored symbol of a prepared dataset.
+fn all_symbols(prepared: &PreparedDataset, metadata: &DatasetMetadata) -> Vec<Option<Vec<u8>>> {
+    (0..metadata.file.total_symbols())
+        .map(|index| prepared.file.symbol(index))
+        .collect()
+}
+
+/// Three files; the large one spans a codeword boundary so the dataset has three codewords.
+fn three_files() -> Vec<(String, Vec<u8>)> {
+    vec![
+        ("notes/readme.txt".to_string(), b"dataset readme".to_vec()),
+        (
+            "data/big.bin".to_string(),
+            (0..20_000u32).map(|i| (i % 251) as u8).collect(),
+        ),
+        ("config.json".to_string(), br#"{"replicas":3}"#.to_vec()),
+    ]
+}
+
+fn as_entries(files: &[(String, Vec<u8>)]) -> Vec<(String, &[u8])> {
+    files
+        .iter()
+        .map(|(path, data)| (path.clone(), data.as_slice()))
+        .collect()
+}
+
+#[test]
+fn test_three_file_round_trip() {
+    println!("Testing dataset preparation and full reconstruction");
+
+    let files = three_files();
+    let (prepared, metadata) = api::prepare_dataset(&as_entries(&files), "bundle").unwrap();
+    assert_eq!(metadata.name(), "bundle");
+    assert_eq!(metadata.file.num_codewords(), 3);
+    assert_eq!(
+        metadata.file.original_size as u64,
+        metadata.manifest.total_size()
+    );
+    assert_eq!(prepared.manifest, metadata.manifest);
+
+    let paths: Vec<&str> = metadata
+        .manifest
+        .entries
+        .iter()
+        .map(|e| e.path.as_str())
+        .collect();
+    assert_eq!(paths, ["config.json", "data/big.bin", "notes/readme.txt"]);
+
+    let mut symbols = all_symbols(&prepared, &metadata);
+    symbols[0] = None;
+    symbols[300] = None;
+    let mut expected = files.clone();
+    expected.sort();
+    assert_eq!(
+        api::reconstruct_dataset(&symbols, &metadata).unwrap(),
+        expected
+    );
+
+    // Entry order does not change the committed dataset
+    let mut reversed = files.clone();
+    reversed.reverse();
+    let (_, reordered) = api::prepare_dataset(&as_entries(&reversed), "bundle").unwrap();
+    assert_eq!(reordered, metadata);
+
+    println!("✓ Three files reconstructed from one dataset");
+}
+
+#[test]
+fn test_extract_single_file_from_its_codewords() {
+    println!("Testing single-file extraction with other codewords missing");
+
+    let files = three_files();
+    let (prepared, metadata) = api::prepare_dataset(&as_entries(&files), "bundle").unwrap();
+    let readme = metadata.manifest.entry("notes/readme.txt").unwrap().clone();
+    let codeword_bytes = config::DATA_SYMBOLS_PER_CODEWORD * config::CHUNK_SIZE_BYTES;
+    assert_eq!(readme.offset as usize / codeword_bytes, 2);
+
+    // Lose all of codeword 1: the manifest (codeword 0) and the readme (codeword 2) survive
+    let mut symbols = all_symbols(&prepared, &metadata);
+    let codeword_1 = config::TOTAL_SYMBOLS_PER_CODEWORD..2 * config::TOTAL_SYMBOLS_PER_CODEWORD;
+    for symbol in &mut symbols[codeword_1] {
+        *symbol = None;
+    }
+
+    assert_eq!(
+        metadata.extract_file(&symbols, "notes/readme.txt").unwrap(),
+        b"dataset readme"
+    );
+    assert_eq!(
+        metadata.extract_file(&symbols, "config.json").unwrap(),
+        br#"{"replicas":3}"#
+    );
+    assert!(matches!(
+        metadata.extract_file(&symbols, "data/big.bin"),
+        Err(KontorPoRError::ReconstructionFailed { unrecoverable_codewords, .. })
+            if unrecoverable_codewords == [1]
+    ));
+    assert!(api::reconstruct_dataset(&symbols, &metadata).is_err());
+    assert!(matches!(
+        metadata.extract_file(&symbols, "missing.txt"),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    println!("✓ Files extracted from their own codewords");
+}
+
+#[test]
+fn test_dataset_proves_and_verifies() {
+    println!("Testing proving a dataset as a single file");
+
+    let files = [
+        ("a.txt".to_string(), b"first file".as_slice()),
+        ("b.txt".to_string(), b"second file".as_slice()),
+        ("c/d.txt".to_string(), b"third file".as_slice()),
+    ];
+    let (prepared, metadata) = api::prepare_dataset(&files, "small-bundle").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata.file).unwrap();
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata.file.clone(), 1000, 3, FieldElement::from(8u64));
+    let proof = system
+        .prove(vec![&prepared.file], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+
+    println!("✓ Dataset proven and verified under one root");
+}
+
+#[test]
+fn test_invalid_datasets_rejected() {
+    let data = b"contents".as_slice();
+    let duplicate = [("x".to_string(), data), ("x".to_string(), data)];
+    assert!(matches!(
+        api::prepare_dataset(&duplicate, "dup"),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    let empty_path = [(String::new(), data)];
+    assert!(matches!(
+        api::prepare_dataset(&empty_path, "empty"),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // The manifest round-trips, and only its canonical form decodes
+    let manifest = DatasetManifest::new([("b", 3), ("a", 5)]).unwrap();
+    let bytes = manifest.to_bytes();
+    assert_eq!(bytes.len(), manifest.encoded_len());
+    assert_eq!(DatasetManifest::from_bytes(&bytes).unwrap(), manifest);
+    let mut shifted = bytes.clone();
+    let offset_pos = 4 + 2 + 4 + 4 + 1;
+    shifted[offset_pos] += 1;
+    assert!(matches!(
+        DatasetManifest::from_bytes(&shifted),
+        Err(KontorPoRError::Serialization(_))
+    ));
+    assert!(matches!(
+        DatasetManifest::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(KontorPoRError::Serialization(_))
+    ));
+
+    // Metadata whose manifest disagrees with the committed one is refused
+    let files = [("a".to_string(), data), ("b".to_string(), data)];
+    let (prepared, metadata) = api::prepare_dataset(&files, "pair").unwrap();
+    let symbols = all_symbols(&prepared, &metadata);
+    let mut swapped = metadata.clone();
+    swapped.manifest.entries[1].path = "c".to_string();
+    assert!(matches!(
+        swapped.extract_file(&symbols, "c"),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        api::reconstruct_dataset(&symbols, &swapped),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+}
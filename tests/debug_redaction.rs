@@ -0,0 +1,85 @@
+//! Tests for log-safe Debug and Display output
+//!
+//! This module tests that:
+//! 1. `Debug` of a prepared file prints no tree node other than the root
+//! 2. `Display` of metadata, challenges and proofs is a one-line summary
+
+use kontor_crypto::api::{self, Challenge, FieldElement, PorSystem};
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_data};
+
+/// Every `0x`-prefixed 64-digit hex field element in `text`.
+fn field_hexes(text: &str) -> Vec<&str> {
+    text.match_indices("0x")
+        .filter_map(|(i, _)| text.get(i..i + 66))
+        .filter(|hex| hex[2..].chars().all(|c| c.is_ascii_hexdigit()))
+        .collect()
+}
+
+#[test]
+fn test_prepared_file_debug_omits_tree_nodes() {
+    println!("Testing that Debug of a prepared file prints only the root");
+
+    let data = create_test_data(4096, Some(5));
+    let (prepared, metadata) = api::prepare_file(&data, "secret.dat").unwrap();
+    let root_hex = format!("{:?}", metadata.root);
+
+    let full = prepared.debug_full();
+    let nodes: Vec<&str> = field_hexes(&full)
+        .into_iter()
+        .filter(|hex| *hex != root_hex)
+        .collect();
+    assert!(
+        nodes.len() > 100,
+        "debug_full must expose the tree for tests"
+    );
+
+    let debug = format!("{:?}", prepared);
+    assert!(debug.contains(&root_hex));
+    assert!(debug.contains(&metadata.file_id));
+    for hex in field_hexes(&debug) {
+        assert_eq!(hex, root_hex, "Debug output leaked a tree node");
+    }
+    assert!(
+        debug.len() < 1000,
+        "Debug output should be a summary, got {} bytes",
+        debug.len()
+    );
+
+    println!("✓ Prepared file Debug output: {}", debug);
+}
+
+#[test]
+fn test_display_summaries_are_single_lines() {
+    println!("Testing Display summaries for metadata, challenges and proofs");
+
+    let data = create_test_data(1024, Some(6));
+    let (prepared, metadata) = api::prepare_file(&data, "secret.dat").unwrap();
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(3u64));
+
+    let metadata_line = metadata.to_string();
+    assert!(metadata_line.contains(&metadata.file_id));
+    assert!(
+        !metadata_line.contains("secret.dat"),
+        "Annotations stay out of log summaries"
+    );
+
+    let challenge_line = challenge.to_string();
+    assert!(challenge_line.contains(&challenge.id().to_hex()));
+    assert!(challenge_line.contains(&metadata.file_id));
+
+    let ledger = create_single_file_ledger(&metadata);
+    let proof = PorSystem::new(&ledger)
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    let proof_line = proof.to_string();
+    assert!(proof_line.contains(&format!("{:?}", proof.ledger_root)));
+
+    for line in [&metadata_line, &challenge_line, &proof_line] {
+        assert!(!line.contains('\n'), "Summary must be one line: {}", line);
+        println!("  {}", line);
+    }
+
+    println!("✓ Display summaries are concise");
+}
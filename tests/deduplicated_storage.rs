@@ -0,0 +1,105 @@
+//! Tests for proving deduplicated content registered under several file IDs
+//!
+//! This module tests that:
+//! 1. The same data prepared under two salts has one root and two file IDs, and one
+//!    `PreparedFile` answers challenges for both in a single proof that verifies
+//! 2. Each registration keeps its own ledger index and slot
+//! 3. A prepared file only serves challenges for content with the same root
+//! 4. A prepared file serving only another registration of its content is not unused
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem, PreparedFile},
+    FileLedger, KontorPoRError,
+};
+
+const DATA: &[u8] = b"one deal's worth of data, registered twice under different salts";
+
+/// The same content prepared under two salts, plus an unrelated file.
+fn registrations() -> (PreparedFile, FileMetadata, FileMetadata, FileMetadata) {
+    let (prepared, first) = api::prepare_file_salted(DATA, "deal-a.dat", &[1u8; 32]).unwrap();
+    let (_, second) = api::prepare_file_salted(DATA, "deal-b.dat", &[2u8; 32]).unwrap();
+    let (_, other) = api::prepare_file(b"unrelated content", "other.dat").unwrap();
+    (prepared, first, second, other)
+}
+
+fn challenge(metadata: &FileMetadata) -> Challenge {
+    Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(21u64))
+}
+
+#[test]
+fn test_one_copy_answers_both_registrations() {
+    println!("Testing one prepared file answering two registrations of its content");
+
+    let (prepared, first, second, other) = registrations();
+    assert_eq!(first.root, second.root);
+    assert_eq!(first.commitment(), second.commitment());
+    assert_ne!(first.file_id, second.file_id);
+
+    let mut ledger = FileLedger::new();
+    ledger
+        .add_files(&[first.clone(), second.clone(), other])
+        .unwrap();
+    let first_index = ledger.lookup(&first.file_id).unwrap().0;
+    let second_index = ledger.lookup(&second.file_id).unwrap().0;
+    assert_ne!(first_index, second_index);
+
+    let challenges = vec![challenge(&first), challenge(&second)];
+    let system = PorSystem::new(&ledger).with_reject_unused_files(true);
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+
+    // Each registration fills its own slot at its own ledger index
+    let mut indices = proof.ledger_indices.clone();
+    indices.sort_unstable();
+    let mut expected = vec![first_index, second_index];
+    expected.sort_unstable();
+    assert_eq!(indices, expected);
+    let mut slots = proof.challenge_slots.clone();
+    slots.sort_unstable();
+    assert_eq!(slots, vec![0, 1]);
+
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ One stored copy proved both registrations");
+}
+
+#[test]
+fn test_second_registration_alone() {
+    let (prepared, first, second, other) = registrations();
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&[first, second.clone(), other]).unwrap();
+
+    // The copy is prepared under the first registration's ID but challenged only as the
+    // second, so it is referenced rather than unused
+    let challenges = vec![challenge(&second)];
+    let system = PorSystem::new(&ledger).with_reject_unused_files(true);
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+}
+
+#[test]
+fn test_different_content_cannot_share_a_prepared_file() {
+    let (prepared, first, _, other) = registrations();
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&[first.clone(), other.clone()]).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let result = system.prove(vec![&prepared], &[challenge(&first), challenge(&other)]);
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::MissingPreparedFile { ref file_ids }) if *file_ids == [other.file_id.clone()]
+        ),
+        "got {:?}",
+        result.map(|_| ())
+    );
+
+    // Synthetic metadata under a new ID with the content's depth but another root
+    let mut forged = first.clone();
+    forged.file_id = "forged".to_string();
+    forged.root += FieldElement::from(1u64);
+    let result = system.prove(vec![&prepared], &[challenge(&forged)]);
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::MissingPreparedFile { .. })
+    ));
+}
@@ -0,0 +1,132 @@
+//! Tests for shape-compatibility checks on file tree depth
+//!
+//! This module tests that:
+//! 1. A prepared file deeper than its (synthetic) metadata claims is rejected with
+//!    `DepthExceedsShape` before any parameters are loaded, for single- and multi-file batches
+//! 2. Witness generation refuses a file_tree_depth shallower than the file instead of
+//!    truncating its Merkle path
+
+use ff::Field;
+use kontor_crypto::{
+    api::{
+        self, generate_circuit_witness, prepare_file, Challenge, FieldElement, FileMetadata,
+        PorSystem,
+    },
+    metrics::{set_metrics_sink, MetricsSink, Phase, TimingEvent},
+    KontorPoRError,
+};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_data};
+
+#[derive(Default)]
+struct CollectingSink {
+    events: Mutex<Vec<TimingEvent>>,
+}
+
+impl MetricsSink for CollectingSink {
+    fn record(&self, event: TimingEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+/// Metadata for `metadata`'s file that understates its tree as depth 1.
+fn shallow_metadata(metadata: &FileMetadata) -> FileMetadata {
+    FileMetadata {
+        padded_len: 2,
+        ..metadata.clone()
+    }
+}
+
+// The only test in this file that calls prove, so the process-wide metrics sink sees no
+// parameter loads from other tests.
+#[test]
+fn test_oversized_file_rejected_before_param_load() {
+    println!("Testing a prepared file deeper than the derived shape");
+
+    let (big, big_metadata) = prepare_file(&create_test_data(20_000, Some(7)), "big.dat").unwrap();
+    let (small, small_metadata) =
+        prepare_file(&create_test_data(200, Some(8)), "small.dat").unwrap();
+    let actual_depth = api::tree_depth_from_metadata(&big_metadata);
+    let small_depth = api::tree_depth_from_metadata(&small_metadata);
+    assert!(actual_depth > small_depth && small_depth > 1);
+
+    let synthetic = shallow_metadata(&big_metadata);
+    assert_eq!(synthetic.root, big.root);
+    let ledger = create_multi_file_ledger(&[&synthetic, &small_metadata]);
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(9u64);
+
+    let sink = Arc::new(CollectingSink::default());
+    set_metrics_sink(Some(sink.clone()));
+
+    let single = vec![Challenge::new_test(synthetic.clone(), 1000, 2, seed)];
+    let single_result = system.prove(vec![&big], &single);
+
+    let both = vec![
+        Challenge::new_test(synthetic.clone(), 1000, 2, seed),
+        Challenge::new_test(small_metadata.clone(), 1000, 2, seed),
+    ];
+    let multi_result = system.prove(vec![&big, &small], &both);
+
+    set_metrics_sink(None);
+
+    match single_result {
+        Err(KontorPoRError::DepthExceedsShape {
+            file_id,
+            depth,
+            max,
+        }) => {
+            assert_eq!(file_id, synthetic.file_id);
+            assert_eq!(depth, actual_depth);
+            assert_eq!(max, 1);
+        }
+        other => panic!("expected DepthExceedsShape, got {:?}", other.map(|_| ())),
+    }
+    // In a batch the shape follows the deepest metadata, here the honest small file
+    assert!(matches!(
+        multi_result,
+        Err(KontorPoRError::DepthExceedsShape { depth, max, .. })
+            if depth == actual_depth && max == small_depth
+    ));
+
+    let events = sink.events.lock().unwrap();
+    assert!(
+        events.iter().all(|e| e.phase != Phase::ParamLoad),
+        "depth check must run before parameter loading"
+    );
+
+    println!("✓ DepthExceedsShape reported before parameters are loaded");
+}
+
+#[test]
+fn test_witness_generation_rejects_shallow_shape() {
+    let (prepared, metadata) = prepare_file(&create_test_data(1000, Some(3)), "w.dat").unwrap();
+    let depth = api::tree_depth_from_metadata(&metadata);
+    let ledger = create_multi_file_ledger(&[&metadata]);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 1, FieldElement::from(4u64));
+    let files: BTreeMap<String, &_> = [(metadata.file_id.clone(), &prepared)].into();
+
+    let generate = |file_tree_depth: usize| {
+        generate_circuit_witness(
+            &[&challenge],
+            Some(&files),
+            &ledger,
+            file_tree_depth,
+            file_tree_depth,
+            FieldElement::ZERO,
+            0,
+            0,
+            &[0],
+        )
+    };
+
+    assert!(generate(depth).is_ok());
+    assert!(matches!(
+        generate(depth - 1),
+        Err(KontorPoRError::DepthExceedsShape { depth: d, max, .. })
+            if d == depth && max == depth - 1
+    ));
+}
@@ -35,7 +35,9 @@ fn test_depth_zero_cheat_is_prevented() {
 
     // Generate parameters for the correct depth
     let (files_per_step, file_tree_depth) = config::derive_shape(1, actual_depth);
-    let _params = params::load_or_generate_params(files_per_step, file_tree_depth, 0).unwrap();
+    let _params =
+        params::load_or_generate_params(config::Shape::new(files_per_step, file_tree_depth, 0))
+            .unwrap();
 
     // Create a challenge
     let challenge = api::Challenge::new_test(metadata.clone(), 1000, 1, FieldElement::from(42u64));
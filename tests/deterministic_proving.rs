@@ -0,0 +1,200 @@
+//! Tests for reproducible proving sessions
+//!
+//! This module tests that:
+//! 1. Two sessions from the same seed build identical files, ledgers and challenges,
+//!    generate identical circuit witnesses, and prove identical public statements
+//! 2. A different seed changes the public statement
+//! 3. Proving with the same `ProveOptions::rng_seed` samples the same leaves of a large
+//!    file for validation
+//!
+//! The sampled leaf validation is the only randomness the library draws while proving.
+//! The compressed SNARK bytes are not compared: Nova blinds witness commitments and
+//! randomizes the compressed SNARK with the OS RNG internally, so they differ between
+//! runs even with identical inputs.
+
+use kontor_crypto::{
+    api::{
+        self, generate_circuit_witness, Challenge, FieldElement, FileMetadata, LeafValidation,
+        PorSystem, PreparedFile, Proof, ProveOptions,
+    },
+    circuit::FileProofWitness,
+    config, FileLedger, KontorPoRError,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_data, widen_leaves};
+
+/// Everything one seeded session produces.
+struct Session {
+    metadatas: Vec<FileMetadata>,
+    ledger: FileLedger,
+    challenges: Vec<Challenge>,
+    witnesses: Vec<FileProofWitness<FieldElement>>,
+    proof: Proof,
+}
+
+/// Builds three files, a ledger and two challenges from `seed`, then witnesses and proves.
+fn run_session(seed: u64) -> Session {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let files: Vec<(PreparedFile, FileMetadata)> = [400, 900, 1500]
+        .iter()
+        .enumerate()
+        .map(|(i, &size)| {
+            let mut data = vec![0u8; size];
+            rng.fill_bytes(&mut data);
+            api::prepare_file(&data, &format!("file_{}.dat", i)).unwrap()
+        })
+        .collect();
+    let metadatas: Vec<FileMetadata> = files.iter().map(|(_, m)| m.clone()).collect();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+
+    let challenge_seed = FieldElement::from(rng.next_u64());
+    let challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, challenge_seed))
+        .collect();
+
+    let file_refs: BTreeMap<String, &PreparedFile> = files
+        .iter()
+        .map(|(prepared, metadata)| (metadata.file_id.clone(), prepared))
+        .collect();
+    let plan = api::plan::build_plan(&challenges, &ledger).unwrap();
+    let ledger_indices: Vec<usize> = challenges
+        .iter()
+        .map(|c| ledger.lookup(&c.file_metadata.file_id).unwrap().0)
+        .collect();
+    let (witness, _) = generate_circuit_witness(
+        &challenges.iter().collect::<Vec<_>>(),
+        Some(&file_refs),
+        &ledger,
+        plan.file_tree_depth,
+        plan.file_tree_depth,
+        FieldElement::from(0u64),
+        plan.aggregated_tree_depth,
+        0,
+        &ledger_indices,
+    )
+    .unwrap();
+
+    let proof = PorSystem::new(&ledger)
+        .prove(files[..2].iter().map(|(p, _)| p).collect(), &challenges)
+        .unwrap();
+    assert!(PorSystem::new(&ledger).verify(&proof, &challenges).unwrap());
+
+    Session {
+        metadatas,
+        ledger,
+        challenges,
+        witnesses: witness.witnesses().to_vec(),
+        proof,
+    }
+}
+
+fn assert_same_witness(a: &FileProofWitness<FieldElement>, b: &FileProofWitness<FieldElement>) {
+    assert_eq!(a.leaf, b.leaf);
+    assert_eq!(a.file_siblings, b.file_siblings);
+    assert_eq!(a.file_root, b.file_root);
+    assert_eq!(a.actual_depth, b.actual_depth);
+    assert_eq!(a.agg_siblings, b.agg_siblings);
+    assert_eq!(a.ledger_index, b.ledger_index);
+}
+
+#[test]
+fn test_seeded_sessions_are_reproducible() {
+    println!("Testing two proving sessions from one seed");
+
+    let first = run_session(11);
+    let second = run_session(11);
+
+    assert_eq!(first.metadatas, second.metadatas);
+    assert_eq!(first.ledger.root(), second.ledger.root());
+    let ids = |s: &Session| s.challenges.iter().map(Challenge::id).collect::<Vec<_>>();
+    assert_eq!(ids(&first), ids(&second));
+
+    assert_eq!(first.witnesses.len(), second.witnesses.len());
+    for (a, b) in first.witnesses.iter().zip(&second.witnesses) {
+        assert_same_witness(a, b);
+    }
+
+    assert!(first.proof.same_statement(&second.proof));
+    assert_eq!(first.proof.public_inputs, second.proof.public_inputs);
+    assert_eq!(first.proof.public_outputs, second.proof.public_outputs);
+    assert_eq!(first.proof.ledger_indices, second.proof.ledger_indices);
+
+    // Each session's proof verifies against the other's challenges and ledger
+    assert!(PorSystem::new(&second.ledger)
+        .verify(&first.proof, &second.challenges)
+        .unwrap());
+
+    println!("✓ Witnesses and public values identical across seeded sessions");
+}
+
+#[test]
+fn test_different_seed_changes_statement() {
+    let first = run_session(11);
+    let other = run_session(12);
+
+    assert_ne!(first.ledger.root(), other.ledger.root());
+    assert_ne!(first.proof.public_inputs, other.proof.public_inputs);
+    assert!(!first.proof.same_statement(&other.proof));
+}
+
+/// The first `count` leaves, in ascending order, that proving with `rng_seed` validates.
+///
+/// Every leaf after the last one found is widened, so each failing run reports the next
+/// sampled leaf as `InvalidLeafEncoding`.
+fn sampled_leaves(
+    prepared: &PreparedFile,
+    system: &PorSystem,
+    challenge: &Challenge,
+    rng_seed: u64,
+    count: usize,
+) -> Vec<usize> {
+    let options = ProveOptions::default().with_rng_seed(rng_seed);
+    let padded_len = challenge.file_metadata.padded_len;
+    let mut sampled = Vec::new();
+    let mut start = 0;
+    while sampled.len() < count {
+        let hostile = widen_leaves(prepared, start..padded_len);
+        match system.prove_with_options(vec![&hostile], std::slice::from_ref(challenge), &options) {
+            Err(KontorPoRError::InvalidLeafEncoding { index }) => {
+                sampled.push(index);
+                start = index + 1;
+            }
+            other => panic!("expected InvalidLeafEncoding, got {:?}", other.map(|_| ())),
+        }
+    }
+    sampled
+}
+
+#[test]
+fn test_seeded_proving_samples_the_same_leaves() {
+    println!("Testing that a seeded prove samples the same leaves for validation");
+
+    let (prepared, metadata) =
+        api::prepare_file(&create_test_data(150_000, Some(7)), "large.dat").unwrap();
+    assert!(metadata.padded_len > config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD);
+    let ledger = create_single_file_ledger(&metadata);
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata, 1000, 2, FieldElement::from(3u64));
+
+    let first = sampled_leaves(&prepared, &system, &challenge, 11, 8);
+    let second = sampled_leaves(&prepared, &system, &challenge, 11, 8);
+    assert_eq!(first, second);
+
+    // The seeded sample is the one `PreparedFile::validate_with_seed` draws
+    let hostile = widen_leaves(&prepared, [first[3]]);
+    assert!(matches!(
+        hostile.validate_with_seed(LeafValidation::Sampled, Some(11)),
+        Err(KontorPoRError::InvalidLeafEncoding { index }) if index == first[3]
+    ));
+
+    let other = sampled_leaves(&prepared, &system, &challenge, 12, 8);
+    assert_ne!(first, other);
+
+    println!("✓ Same seed, same sampled leaves: {:?}", first);
+}
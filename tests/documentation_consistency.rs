@@ -45,12 +45,13 @@ fn test_circuit_arity_matches_public_inputs() {
 
     // Generate circuit witness to inspect z vector structure
     let dummy_ledger_indices = vec![0]; // Single file at index 0
+    let depth = metadata.depth();
     let (witness, _) = api::generate_circuit_witness(
         &[&challenge],
         Some(&files),
         &dummy_ledger,
-        1,
-        1,
+        depth,
+        depth,
         FieldElement::ZERO,
         0,
         0,
@@ -63,7 +64,10 @@ fn test_circuit_arity_matches_public_inputs() {
     use nova_snark::traits::circuit::StepCircuit;
 
     let files_per_step = 1;
-    let circuit = PorCircuit::<FieldElement>::new(files_per_step, 1, 0, Some(witness.witnesses));
+    let circuit = PorCircuit::<FieldElement>::new(
+        config::Shape::new(files_per_step, depth, 0),
+        Some(witness.witnesses),
+    );
 
     let circuit_arity = circuit.arity();
     // Phase 3: arity = fixed_fields + ledger_indices + depths + leaves
@@ -286,6 +290,41 @@ fn test_domain_tags_are_unique() {
             "challenge_per_file",
             domain_tags::challenge_per_file::<FieldElement>(),
         ),
+        ("challenge_id", domain_tags::challenge_id::<FieldElement>()),
+        (
+            "leaf_blinding",
+            domain_tags::leaf_blinding::<FieldElement>(),
+        ),
+        (
+            "leaf_commitment",
+            domain_tags::leaf_commitment::<FieldElement>(),
+        ),
+        (
+            "seed_schedule",
+            domain_tags::seed_schedule::<FieldElement>(),
+        ),
+        (
+            "ledger_attestation",
+            domain_tags::ledger_attestation::<FieldElement>(),
+        ),
+        ("node_arity4", domain_tags::node_arity4::<FieldElement>()),
+        (
+            "root_commitment_arity4",
+            domain_tags::root_commitment_arity4::<FieldElement>(),
+        ),
+        (
+            "challenge_nonce",
+            domain_tags::challenge_nonce::<FieldElement>(),
+        ),
+        (
+            "empty_ledger_slot",
+            domain_tags::empty_ledger_slot::<FieldElement>(),
+        ),
+        (
+            "explicit_index",
+            domain_tags::explicit_index::<FieldElement>(),
+        ),
+        ("heartbeat", domain_tags::heartbeat::<FieldElement>()),
     ];
 
     // Check all pairs for uniqueness
@@ -349,7 +388,7 @@ fn test_option1_proof_format_version_awareness() {
 
     // Test with single file (should have arity = 2 + 1 (ledger) + 1 (depth) + 1 (seed) + 1 (leaf) = 6)
     use kontor_crypto::circuit::PorCircuit;
-    let circuit = PorCircuit::<FieldElement>::new(1, 3, 0, None);
+    let circuit = PorCircuit::<FieldElement>::new(config::Shape::new(1, 3, 0), None);
     assert_eq!(
         circuit.arity(),
         6,
@@ -357,7 +396,7 @@ fn test_option1_proof_format_version_awareness() {
     );
 
     // Test with multi-file (should have arity = 2 + 4 (ledger) + 4 (depths) + 4 (seeds) + 4 (leaves) = 18)
-    let circuit_multi = PorCircuit::<FieldElement>::new(4, 3, 2, None);
+    let circuit_multi = PorCircuit::<FieldElement>::new(config::Shape::new(4, 3, 2), None);
     assert_eq!(
         circuit_multi.arity(),
         18,
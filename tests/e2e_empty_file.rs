@@ -0,0 +1,85 @@
+//! End-to-end tests for zero-length files
+//!
+//! An empty file is a depth-0 tree whose single leaf is the all-zero padding symbol.
+//! This module tests that such a file flows through prepare → ledger → prove → verify →
+//! reconstruct, alone and alongside non-empty files.
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    erasure, merkle, FileLedger,
+};
+
+#[test]
+fn test_empty_file_end_to_end() {
+    println!("Testing an empty file through the full pipeline");
+
+    let (prepared, metadata) = api::prepare_file(&[], "placeholder.dat").unwrap();
+    assert_eq!(metadata.original_size, 0);
+    assert_eq!(metadata.padded_len, 1);
+    assert_eq!(metadata.num_codewords(), 0);
+    assert_eq!(metadata.total_symbols(), 0);
+    assert_eq!(api::tree_depth_from_metadata(&metadata), 0);
+
+    // The tree is the well-defined single padding leaf
+    let (_, padding_root) = merkle::build_tree(&[vec![0u8; 31]]).unwrap();
+    let (_, empty_root) = merkle::build_tree(&[]).unwrap();
+    assert_eq!(metadata.root, padding_root);
+    assert_eq!(metadata.root, empty_root);
+    assert_eq!(metadata.root, FieldElement::ZERO);
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    assert!(ledger.lookup(&metadata.file_id).is_some());
+
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(11u64));
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+
+    let symbols = erasure::encode_file_symbols(&[]).unwrap();
+    assert!(symbols.is_empty());
+    let reconstructed = api::reconstruct_file(&[], &metadata).unwrap();
+    assert!(reconstructed.is_empty());
+
+    println!("✓ Empty file prepared, proven, verified and reconstructed");
+}
+
+#[test]
+fn test_empty_file_in_multi_file_batch() {
+    println!("Testing an empty file challenged alongside a non-empty file");
+
+    let (empty_prepared, empty_metadata) = api::prepare_file(&[], "empty.dat").unwrap();
+    let data = b"non-empty neighbour of a placeholder file";
+    let (prepared, metadata) = api::prepare_file(data, "full.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&empty_metadata).unwrap();
+    ledger.add_file(&metadata).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(empty_metadata.clone(), 1000, 2, FieldElement::from(3u64)),
+        Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(3u64)),
+    ];
+
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&empty_prepared, &prepared], &challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // The non-empty file still reconstructs normally
+    let symbols: Vec<Option<Vec<u8>>> = erasure::encode_file_symbols(data)
+        .unwrap()
+        .into_iter()
+        .map(Some)
+        .collect();
+    assert_eq!(api::reconstruct_file(&symbols, &metadata).unwrap(), data);
+    assert!(api::reconstruct_file(&[], &empty_metadata)
+        .unwrap()
+        .is_empty());
+
+    println!("✓ Empty file coexists with non-empty files in a batch");
+}
@@ -0,0 +1,287 @@
+//! Tests for erasure-coding fault injection and recoverability prediction
+//!
+//! The recoverability report must agree with the actual outcome of
+//! `decode_file_symbols` for every simulated loss pattern, and a failed decode must name
+//! exactly the codewords the report predicts as unrecoverable.
+
+use kontor_crypto::{
+    api::{self, FileMetadata},
+    erasure::{decode_file_symbols, encode_file_symbols, recoverability, simulate_loss, LossModel},
+    KontorPoRError,
+};
+
+fn setup(size: usize) -> (Vec<u8>, Vec<Vec<u8>>, FileMetadata) {
+    let data: Vec<u8> = (0..size).map(|i| (i * 7 % 256) as u8).collect();
+    let (_prepared, metadata) = api::prepare_file(&data, "fault.dat").unwrap();
+    let symbols = encode_file_symbols(&data).unwrap();
+    (data, symbols, metadata)
+}
+
+/// Decode the damaged symbols and check the result against the report's prediction.
+fn assert_prediction_matches(
+    data: &[u8],
+    damaged: &[Option<Vec<u8>>],
+    metadata: &FileMetadata,
+    context: &str,
+) -> bool {
+    let report = recoverability(damaged, metadata);
+    let mut to_decode = damaged.to_vec();
+    let decoded = decode_file_symbols(
+        &mut to_decode,
+        metadata.num_codewords(),
+        metadata.original_size,
+    );
+
+    assert_eq!(
+        report.is_recoverable(),
+        decoded.is_ok(),
+        "Prediction disagrees with decode for {}: {:?}",
+        context,
+        report.unrecoverable_codewords()
+    );
+    match decoded {
+        Ok(reconstructed) => {
+            assert_eq!(reconstructed, data, "Decoded data differs for {}", context)
+        }
+        Err(KontorPoRError::ReconstructionFailed {
+            unrecoverable_codewords,
+            missing_per_codeword,
+            ..
+        }) => {
+            assert_eq!(
+                unrecoverable_codewords,
+                report.unrecoverable_codewords(),
+                "{}",
+                context
+            );
+            let predicted: Vec<usize> = report.codewords.iter().map(|cw| cw.missing).collect();
+            assert_eq!(missing_per_codeword, predicted, "{}", context);
+        }
+        Err(other) => panic!(
+            "Expected ReconstructionFailed for {}, got {:?}",
+            context, other
+        ),
+    }
+    report.is_recoverable()
+}
+
+/// Drops `count` symbols of codeword `cw_idx`, spaced `stride` apart from its first symbol.
+fn drop_in_codeword(damaged: &mut [Option<Vec<u8>>], cw_idx: usize, count: usize, stride: usize) {
+    let start = cw_idx * 255;
+    for k in 0..count {
+        damaged[start + k * stride] = None;
+    }
+}
+
+#[test]
+fn test_random_loss_prediction_matches_decode() {
+    println!("Testing recoverability prediction against decode for random loss");
+
+    let (data, symbols, metadata) = setup(15_000);
+    assert_eq!(metadata.num_codewords(), 3);
+
+    let mut outcomes = (0, 0);
+    for fraction in [0.0, 0.05, 0.09, 0.1, 0.12, 0.3] {
+        for seed in 0..40u64 {
+            let damaged = simulate_loss(&symbols, &metadata, LossModel::Random(fraction), seed);
+            let expected_lost = (fraction * symbols.len() as f64).round() as usize;
+            assert_eq!(
+                damaged.iter().filter(|s| s.is_none()).count(),
+                expected_lost
+            );
+
+            let context = format!("Random({}) seed {}", fraction, seed);
+            if assert_prediction_matches(&data, &damaged, &metadata, &context) {
+                outcomes.0 += 1;
+            } else {
+                outcomes.1 += 1;
+            }
+        }
+    }
+
+    // Fractions near the ~9.4% threshold must exercise both outcomes
+    assert!(outcomes.0 > 0 && outcomes.1 > 0, "{:?}", outcomes);
+    println!(
+        "✓ {} recoverable / {} unrecoverable predictions all matched",
+        outcomes.0, outcomes.1
+    );
+}
+
+#[test]
+fn test_per_codeword_loss_prediction_matches_decode() {
+    println!("Testing recoverability prediction against decode for per-codeword loss");
+
+    let (data, symbols, metadata) = setup(15_000);
+
+    for seed in 0..40u64 {
+        // Within tolerance: always recoverable
+        let damaged = simulate_loss(&symbols, &metadata, LossModel::PerCodeword(24), seed);
+        let report = recoverability(&damaged, &metadata);
+        assert!(report.codewords.iter().all(|cw| cw.missing <= 24));
+        assert!(assert_prediction_matches(
+            &data,
+            &damaged,
+            &metadata,
+            &format!("PerCodeword(24) seed {}", seed)
+        ));
+
+        // Beyond tolerance: may go either way, but prediction must agree with decode
+        let damaged = simulate_loss(&symbols, &metadata, LossModel::PerCodeword(40), seed);
+        assert_prediction_matches(
+            &data,
+            &damaged,
+            &metadata,
+            &format!("PerCodeword(40) seed {}", seed),
+        );
+    }
+
+    println!("✓ Per-codeword predictions matched decode outcomes");
+}
+
+#[test]
+fn test_burst_loss_prediction_matches_decode() {
+    println!("Testing recoverability prediction against decode for burst loss");
+
+    let (data, symbols, metadata) = setup(15_000);
+
+    // A 24-symbol burst inside one codeword is recoverable; 25 is not
+    let damaged = simulate_loss(
+        &symbols,
+        &metadata,
+        LossModel::Burst { start: 10, len: 24 },
+        0,
+    );
+    assert!(assert_prediction_matches(
+        &data, &damaged, &metadata, "burst 24"
+    ));
+
+    let damaged = simulate_loss(
+        &symbols,
+        &metadata,
+        LossModel::Burst { start: 10, len: 25 },
+        0,
+    );
+    assert!(!assert_prediction_matches(
+        &data, &damaged, &metadata, "burst 25"
+    ));
+    assert_eq!(
+        recoverability(&damaged, &metadata).unrecoverable_codewords(),
+        vec![0]
+    );
+
+    // A 48-symbol burst straddling a codeword boundary splits 24/24 and is recoverable
+    let damaged = simulate_loss(
+        &symbols,
+        &metadata,
+        LossModel::Burst {
+            start: 255 - 24,
+            len: 48,
+        },
+        0,
+    );
+    assert!(assert_prediction_matches(
+        &data,
+        &damaged,
+        &metadata,
+        "straddling burst"
+    ));
+
+    // Bursts past the end are clamped
+    let damaged = simulate_loss(
+        &symbols,
+        &metadata,
+        LossModel::Burst {
+            start: symbols.len() - 5,
+            len: 1000,
+        },
+        0,
+    );
+    assert_eq!(damaged.len(), symbols.len());
+    assert_eq!(recoverability(&damaged, &metadata).total_missing(), 5);
+
+    // Sweep burst positions
+    for start in (0..symbols.len()).step_by(17) {
+        for len in [20, 30, 60] {
+            let damaged = simulate_loss(&symbols, &metadata, LossModel::Burst { start, len }, 0);
+            assert_prediction_matches(
+                &data,
+                &damaged,
+                &metadata,
+                &format!("burst {}+{}", start, len),
+            );
+        }
+    }
+
+    println!("✓ Burst predictions matched decode outcomes");
+}
+
+#[test]
+fn test_simulate_loss_is_deterministic() {
+    let (_data, symbols, metadata) = setup(5_000);
+
+    let a = simulate_loss(&symbols, &metadata, LossModel::Random(0.2), 99);
+    let b = simulate_loss(&symbols, &metadata, LossModel::Random(0.2), 99);
+    let c = simulate_loss(&symbols, &metadata, LossModel::Random(0.2), 100);
+    assert_eq!(a, b, "Same seed must produce the same loss pattern");
+    assert_ne!(
+        a, c,
+        "Different seeds should produce different loss patterns"
+    );
+}
+
+#[test]
+fn test_failed_decode_names_concentrated_codeword() {
+    println!("Testing that a failed decode names exactly the overloaded codeword");
+
+    let (data, symbols, metadata) = setup(15_000);
+    let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+    drop_in_codeword(&mut damaged, 1, 30, 3);
+
+    match api::reconstruct_file(&damaged, &metadata) {
+        Err(KontorPoRError::ReconstructionFailed {
+            unrecoverable_codewords,
+            missing_per_codeword,
+            needed,
+        }) => {
+            assert_eq!(unrecoverable_codewords, vec![1]);
+            assert_eq!(missing_per_codeword, vec![0, 30, 0]);
+            assert_eq!(needed, 231);
+        }
+        other => panic!("Expected ReconstructionFailed, got {:?}", other),
+    }
+
+    // A second overloaded codeword is reported alongside the first
+    drop_in_codeword(&mut damaged, 2, 25, 1);
+    assert!(!assert_prediction_matches(
+        &data,
+        &damaged,
+        &metadata,
+        "two overloaded codewords"
+    ));
+    match api::reconstruct_file(&damaged, &metadata) {
+        Err(KontorPoRError::ReconstructionFailed {
+            unrecoverable_codewords,
+            missing_per_codeword,
+            ..
+        }) => {
+            assert_eq!(unrecoverable_codewords, vec![1, 2]);
+            assert_eq!(missing_per_codeword, vec![0, 30, 25]);
+        }
+        other => panic!("Expected ReconstructionFailed, got {:?}", other),
+    }
+
+    println!("✓ Unrecoverable codewords named with per-codeword missing counts");
+}
+
+#[test]
+fn test_spread_loss_recovers() {
+    let (data, symbols, metadata) = setup(15_000);
+    let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+
+    // 72 symbols lost in total, but no codeword loses more than 24
+    for cw_idx in 0..metadata.num_codewords() {
+        drop_in_codeword(&mut damaged, cw_idx, 24, 10);
+    }
+    assert_eq!(damaged.iter().filter(|s| s.is_none()).count(), 72);
+    assert_eq!(api::reconstruct_file(&damaged, &metadata).unwrap(), data);
+}
@@ -0,0 +1,208 @@
+//! Tests for the GF(2^16) Reed-Solomon backend (`ErasureConfig::field(ErasureField::Gf16)`)
+//!
+//! This module tests that:
+//! 1. Files prepared over GF(2^16) record the code in metadata, use codewords of 2040
+//!    31-byte symbols, round-trip through serialization, and prove and verify unchanged
+//! 2. A burst of 192 lost symbols within one codeword, far beyond a GF(2^8) codeword's
+//!    24, reconstructs in full and by byte range
+//! 3. One missing symbol in each of 97 shards makes a codeword unrecoverable, and
+//!    `recoverability` predicts this
+//! 4. A config never decodes metadata of the other field: `ErasureConfig::check` reports
+//!    `ErasureFieldMismatch`
+//! 5. (ignored by default) A 100 MiB file survives 192-symbol bursts across its codewords
+
+use kontor_crypto::{
+    api::{self, Challenge, ErasureCode, ErasureField, FieldElement, FileMetadata, PorSystem},
+    erasure::{
+        decode_file_symbols_with, encode_file_symbols_with, recoverability, simulate_loss,
+        ErasureConfig, LossModel,
+    },
+    FileLedger, KontorPoRError,
+};
+
+const GF16: ErasureConfig = ErasureConfig::field(ErasureField::Gf16);
+
+/// Symbols per GF(2^16) codeword, and data bytes each codeword holds
+const CODEWORD_SYMBOLS: usize = 2040;
+const CODEWORD_BYTES: usize = 1848 * 31;
+
+/// Deterministic, incompressible-looking test data.
+fn data(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u32).wrapping_mul(2_654_435_761).to_le_bytes()[3])
+        .collect()
+}
+
+fn encoded(data: &[u8]) -> (Vec<Vec<u8>>, FileMetadata) {
+    let (_, metadata) = api::prepare_file_with_erasure(data, "gf16.dat", GF16).unwrap();
+    (encode_file_symbols_with(data, GF16).unwrap(), metadata)
+}
+
+#[test]
+fn test_gf16_metadata_and_proving() {
+    println!("Testing GF(2^16) preparation, metadata and proving");
+
+    let data = data(CODEWORD_BYTES + 1000);
+    let (prepared, metadata) = api::prepare_file_with_erasure(&data, "gf16.dat", GF16).unwrap();
+    assert_eq!(metadata.erasure, Some(ErasureCode::ReedSolomonGf16));
+    assert_eq!(metadata.erasure.unwrap().field(), ErasureField::Gf16);
+    assert_eq!(metadata.num_codewords(), 2);
+    assert_eq!(metadata.total_symbols(), 2 * CODEWORD_SYMBOLS);
+    assert_eq!(metadata.padded_len, 4096);
+
+    let symbols = encode_file_symbols_with(&data, GF16).unwrap();
+    assert_eq!(symbols.len(), metadata.total_symbols());
+    assert!(symbols.iter().all(|symbol| symbol.len() == 31));
+    assert_eq!(
+        api::rebuild_prepared(&symbols, &metadata).unwrap().root,
+        prepared.root
+    );
+
+    // The same data coded over GF(2^8) commits to different symbols
+    let (_, gf8) = api::prepare_file(&data, "gf8.dat").unwrap();
+    assert_ne!(gf8.root, metadata.root);
+
+    let json = serde_json::to_string(&metadata).unwrap();
+    assert!(json.contains("\"ReedSolomonGf16\""));
+    assert_eq!(
+        serde_json::from_str::<FileMetadata>(&json).unwrap(),
+        metadata
+    );
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let challenges = vec![Challenge::new_test(
+        metadata.clone(),
+        1000,
+        2,
+        FieldElement::from(17u64),
+    )];
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ GF(2^16) file proved and verified");
+}
+
+#[test]
+fn test_gf16_survives_large_burst() {
+    println!("Testing a 192-symbol burst within one GF(2^16) codeword");
+
+    let data = data(2 * CODEWORD_BYTES + 5000);
+    let (symbols, metadata) = encoded(&data);
+    assert_eq!(metadata.num_codewords(), 3);
+
+    // Starting on a shard boundary, the burst covers exactly 96 shards
+    let burst = LossModel::Burst {
+        start: CODEWORD_SYMBOLS + 1000,
+        len: 192,
+    };
+    let damaged = simulate_loss(&symbols, &metadata, burst, 1);
+    let report = recoverability(&damaged, &metadata);
+    assert_eq!(report.total_missing(), 192);
+    assert!(report.is_recoverable());
+
+    assert_eq!(api::reconstruct_file(&damaged, &metadata).unwrap(), data);
+
+    // Only the damaged codeword needs decoding for a range inside it
+    let range = CODEWORD_BYTES + 20_000..CODEWORD_BYTES + 50_000;
+    let mut sparse = damaged.clone();
+    sparse[..CODEWORD_SYMBOLS].fill(None);
+    assert_eq!(
+        api::reconstruct_range(&sparse, &metadata, range.clone()).unwrap(),
+        data[range]
+    );
+
+    println!("✓ Burst of 192 symbols recovered");
+}
+
+#[test]
+fn test_gf16_shard_budget() {
+    let data = data(10_000);
+    let (symbols, metadata) = encoded(&data);
+    let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+
+    // One symbol of each of 96 shards is still recoverable...
+    for shard in 0..96 {
+        damaged[2 * shard + 1] = None;
+    }
+    assert!(recoverability(&damaged, &metadata).is_recoverable());
+    assert_eq!(api::reconstruct_file(&damaged, &metadata).unwrap(), data);
+
+    // ...but a 97th shard is not, although only 97 symbols are missing
+    damaged[193] = None;
+    let report = recoverability(&damaged, &metadata);
+    assert_eq!(report.unrecoverable_codewords(), vec![0]);
+    let result = decode_file_symbols_with(&mut damaged, 1, data.len(), GF16);
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::ReconstructionFailed { ref unrecoverable_codewords, ref missing_per_codeword, needed })
+                if *unrecoverable_codewords == [0] && *missing_per_codeword == [97] && needed == 1848
+        ),
+        "got {:?}",
+        result.map(|data| data.len())
+    );
+}
+
+#[test]
+fn test_fields_never_cross_decode() {
+    let data = data(5000);
+    let (_, gf16) = encoded(&data);
+    let (_, gf8) = api::prepare_file(&data, "gf8.dat").unwrap();
+
+    let gf8_config = ErasureConfig::default();
+    assert_eq!(gf8_config.code(), ErasureCode::ReedSolomon);
+    gf8_config.check(&gf8).unwrap();
+    GF16.check(&gf16).unwrap();
+    assert_eq!(ErasureConfig::for_metadata(&gf16).unwrap(), GF16);
+
+    let mismatch = |result: kontor_crypto::Result<()>, expected, found| {
+        assert!(
+            matches!(
+                result,
+                Err(KontorPoRError::ErasureFieldMismatch { expected: e, found: f })
+                    if e == expected && f == found
+            ),
+            "got {:?}",
+            result
+        );
+    };
+    mismatch(
+        gf8_config.check(&gf16),
+        ErasureField::Gf8,
+        ErasureField::Gf16,
+    );
+    mismatch(GF16.check(&gf8), ErasureField::Gf16, ErasureField::Gf8);
+
+    let (_, raw) = api::prepare_file_raw(&data, "raw.dat").unwrap();
+    assert!(matches!(
+        GF16.check(&raw),
+        Err(KontorPoRError::ErasureCoding { .. })
+    ));
+}
+
+/// A 100 MiB file spans 1831 codewords. Each loses a 192-symbol burst at the same offset,
+/// as when one stripe of every codeword sits on a failed disk; the decoder reuses one
+/// inverted matrix for that loss pattern.
+#[test]
+#[ignore = "encodes 100 MiB over GF(2^16); run with --ignored"]
+fn test_gf16_100mb_recovery() {
+    println!("Testing GF(2^16) recovery of a 100 MiB file");
+
+    let data = data(100 << 20);
+    let symbols = encode_file_symbols_with(&data, GF16).unwrap();
+    let num_codewords = symbols.len() / CODEWORD_SYMBOLS;
+    assert_eq!(num_codewords, 1831);
+
+    let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+    for codeword in damaged.chunks_mut(CODEWORD_SYMBOLS) {
+        codeword[600..792].fill(None);
+    }
+
+    let reconstructed =
+        decode_file_symbols_with(&mut damaged, num_codewords, data.len(), GF16).unwrap();
+    assert!(reconstructed == data);
+
+    println!("✓ {} codewords recovered", num_codewords);
+}
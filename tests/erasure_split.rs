@@ -0,0 +1,70 @@
+//! Tests for split data/parity erasure encoding
+//!
+//! Splitting a file into data and parity tiers and merging them back must reproduce the
+//! exact flat symbol order of `encode_file_symbols`, and therefore the same Merkle root
+//! that `prepare_file` commits to.
+
+use kontor_crypto::{
+    api,
+    erasure::{
+        decode_split_symbols, encode_file_symbols, encode_file_symbols_split, merge_for_tree,
+        CodewordLayout,
+    },
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+#[test]
+fn test_split_merge_matches_flat_encoding() {
+    // Empty, tiny, exactly one full codeword, one symbol over, and several codewords
+    for size in [0, 1, 31 * 231, 31 * 231 + 1, 20_000] {
+        let data = create_test_data(size, Some(size as u64));
+        let flat = encode_file_symbols(&data).unwrap();
+        let encoded = encode_file_symbols_split(&data).unwrap();
+
+        assert_eq!(encoded.layout, CodewordLayout::for_size(size));
+        assert_eq!(encoded.data_symbols.len(), encoded.layout.num_data_symbols);
+        assert_eq!(
+            encoded.parity_symbols.len(),
+            encoded.layout.num_parity_symbols()
+        );
+        assert_eq!(encoded.layout.total_symbols(), flat.len());
+
+        let merged = merge_for_tree(&encoded);
+        assert_eq!(merged, flat, "merged symbols differ for size {}", size);
+
+        // The tree over the merged symbols is the one prepare_file commits to
+        let (prepared, metadata) = api::prepare_file(&data, "split.dat").unwrap();
+        assert_eq!(encoded.layout.num_codewords, metadata.num_codewords());
+        let rebuilt = api::rebuild_prepared(&merged, &metadata).unwrap();
+        assert_eq!(rebuilt.root, metadata.root);
+        assert_eq!(rebuilt.root, prepared.root);
+    }
+}
+
+#[test]
+fn test_decode_from_split_tiers() {
+    let data = create_test_data(20_000, Some(5));
+    let encoded = encode_file_symbols_split(&data).unwrap();
+
+    let data_tier: Vec<Option<Vec<u8>>> = encoded.data_symbols.iter().cloned().map(Some).collect();
+    let mut parity_tier: Vec<Option<Vec<u8>>> =
+        encoded.parity_symbols.iter().cloned().map(Some).collect();
+
+    // The hot tier alone is enough when nothing is missing
+    let no_parity = vec![None; parity_tier.len()];
+    let decoded =
+        decode_split_symbols(&data_tier, &no_parity, &encoded.layout, data.len()).unwrap();
+    assert_eq!(decoded, data);
+
+    // Parity from the cold tier repairs losses in the hot tier
+    let mut damaged = data_tier.clone();
+    for index in (0..damaged.len()).step_by(40) {
+        damaged[index] = None;
+    }
+    parity_tier[0] = None;
+    let decoded =
+        decode_split_symbols(&damaged, &parity_tier, &encoded.layout, data.len()).unwrap();
+    assert_eq!(decoded, data);
+}
@@ -0,0 +1,146 @@
+//! Tests for pinning final leaf outputs with `VerifyOptions::expected_leaves`
+//!
+//! This module tests that:
+//! 1. Leaves pinned from one proof verify a later proof of the same challenges
+//! 2. A pinned value that disagrees fails with `LeafValueMismatch` naming the file and step
+//! 3. Expectations for unchallenged files or non-final steps are rejected
+//! 4. Cached verification outcomes do not bypass the leaf check
+
+use ff::Field;
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, VerificationCache, VerifyOptions},
+    KontorPoRError,
+};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+const STEPS: usize = 3;
+
+fn assert_leaf_mismatch(result: kontor_crypto::Result<bool>, file_id: &str) {
+    match result {
+        Err(KontorPoRError::LeafValueMismatch { file_id: got, step }) => {
+            assert_eq!(got, file_id);
+            assert_eq!(step, STEPS - 1);
+        }
+        Err(other) => panic!("expected LeafValueMismatch, got {:?}", other),
+        Ok(valid) => panic!(
+            "expected LeafValueMismatch, verification returned {}",
+            valid
+        ),
+    }
+}
+
+#[test]
+fn test_pinned_leaves_across_epochs() {
+    println!("Testing pinned leaf outputs across two proofs of the same challenges");
+
+    let (files, metadatas) = create_test_files(2, 400, 61);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(77u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, STEPS, seed))
+        .collect();
+
+    // First epoch: record the leaf each file answered with
+    let first = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(system.verify(&first, &challenges).unwrap());
+    let pinned: BTreeMap<(String, usize), FieldElement> = challenges
+        .iter()
+        .map(|c| {
+            let leaf = first.public_slice(c).unwrap().leaf_output;
+            ((c.file_metadata.file_id.clone(), STEPS - 1), leaf)
+        })
+        .collect();
+
+    // Second epoch: the same sectors must yield the same leaves
+    let second = system.prove(files.values().collect(), &challenges).unwrap();
+    let options = VerifyOptions::default().with_expected_leaves(pinned.clone());
+    assert!(system
+        .verify_with_options(&second, &challenges, &options)
+        .unwrap());
+
+    // A disagreeing expectation names the file and step
+    let file_id = metadatas[1].file_id.clone();
+    let mut corrupted = pinned.clone();
+    *corrupted.get_mut(&(file_id.clone(), STEPS - 1)).unwrap() += FieldElement::ONE;
+    let options = VerifyOptions::default().with_expected_leaves(corrupted);
+    assert_leaf_mismatch(
+        system.verify_with_options(&second, &challenges, &options),
+        &file_id,
+    );
+
+    println!("✓ Pinned leaves match across epochs; corrupted pins rejected");
+}
+
+#[test]
+fn test_expectations_must_address_final_step_of_challenged_file() {
+    let (files, metadatas) = create_test_files(1, 300, 62);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let challenges = vec![Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        STEPS,
+        FieldElement::from(5u64),
+    )];
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    let leaf = proof.public_slice(&challenges[0]).unwrap().leaf_output;
+    let file_id = metadatas[0].file_id.clone();
+
+    let earlier_step = BTreeMap::from([((file_id.clone(), 0), leaf)]);
+    let options = VerifyOptions::default().with_expected_leaves(earlier_step);
+    assert!(matches!(
+        system.verify_with_options(&proof, &challenges, &options),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    let unknown_file = BTreeMap::from([(("not-challenged".to_string(), STEPS - 1), leaf)]);
+    let options = VerifyOptions::default().with_expected_leaves(unknown_file);
+    assert!(matches!(
+        system.verify_with_options(&proof, &challenges, &options),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // An empty expectation set checks nothing
+    let options = VerifyOptions::default().with_expected_leaves(BTreeMap::new());
+    assert!(system
+        .verify_with_options(&proof, &challenges, &options)
+        .unwrap());
+}
+
+#[test]
+fn test_cached_outcome_still_checks_leaves() {
+    let (files, metadatas) = create_test_files(1, 300, 63);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let cache = VerificationCache::new(8);
+    let system = PorSystem::new(&ledger).with_verification_cache(&cache);
+    let challenges = vec![Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        STEPS,
+        FieldElement::from(6u64),
+    )];
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+    assert_eq!(cache.misses(), 1);
+
+    let file_id = metadatas[0].file_id.clone();
+    let leaf = proof.public_slice(&challenges[0]).unwrap().leaf_output;
+    let wrong = BTreeMap::from([((file_id.clone(), STEPS - 1), leaf + FieldElement::ONE)]);
+    let options = VerifyOptions::default().with_expected_leaves(wrong);
+    assert_leaf_mismatch(
+        system.verify_with_options(&proof, &challenges, &options),
+        &file_id,
+    );
+    assert_eq!(cache.hits(), 1, "the SNARK outcome came from the cache");
+
+    let right = BTreeMap::from([((file_id, STEPS - 1), leaf)]);
+    let options = VerifyOptions::default().with_expected_leaves(right);
+    assert!(system
+        .verify_with_options(&proof, &challenges, &options)
+        .unwrap());
+}
@@ -0,0 +1,147 @@
+//! Tests for explicit (auditor-selected) challenge indices
+//!
+//! This module tests that:
+//! 1. A proof opening leaves {0, 5, last} verifies, and verifying with a different claimed
+//!    index fails both the challenge-ID check and the SNARK's index accumulator
+//! 2. Multi-file batches of explicit-index challenges verify
+//! 3. Out-of-range indices, index lists of the wrong length, per-step seed schedules and
+//!    mixed batches are rejected at prove and verify time
+
+use kontor_crypto::{
+    api::{
+        self, plan::build_plan, spot_check, Challenge, ChallengeKind, FieldElement, PorSystem,
+        SeedSchedule,
+    },
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_data, create_test_files};
+
+fn explicit(metadata: &api::FileMetadata, indices: Vec<usize>) -> Challenge {
+    Challenge::new_test(
+        metadata.clone(),
+        1000,
+        indices.len(),
+        FieldElement::from(3u64),
+    )
+    .with_kind(ChallengeKind::ExplicitIndices(indices))
+}
+
+#[test]
+fn test_explicit_index_proof_verifies_and_other_index_fails() {
+    println!("Testing a proof for explicitly chosen leaves");
+
+    let (prepared, metadata) =
+        api::prepare_file(&create_test_data(2000, Some(42)), "audit.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let last = metadata.padded_len - 1;
+    let challenge = explicit(&metadata, vec![0, 5, last]);
+
+    let plan = build_plan(std::slice::from_ref(&challenge), &ledger).unwrap();
+    let opened: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| step.slots[0].explicit_index)
+        .collect();
+    assert_eq!(opened, vec![Some(0), Some(5), Some(last)]);
+
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system
+        .verify(&proof, std::slice::from_ref(&challenge))
+        .unwrap());
+
+    // Spot checks open the same leaves
+    let spot = spot_check(&prepared, &challenge).unwrap();
+    assert!(spot.all_passed());
+    let spot_indices: Vec<usize> = spot.indices.iter().map(|i| i.leaf_index).collect();
+    assert_eq!(spot_indices, vec![0, 5, last]);
+
+    // Claiming a different index changes the challenge ID...
+    let other = explicit(&metadata, vec![0, 6, last]);
+    assert_ne!(other.id(), challenge.id());
+    assert!(system.verify(&proof, std::slice::from_ref(&other)).is_err());
+
+    // ...and the SNARK's index accumulator rejects it on its own
+    assert!(!api::verify_raw(std::slice::from_ref(&other), &proof, &ledger).unwrap());
+
+    // A seeded challenge cannot verify the explicit-index proof either
+    let seeded = Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(3u64));
+    assert!(!api::verify_raw(&[seeded], &proof, &ledger).unwrap_or(false));
+
+    // Out-of-range claims are rejected before any SNARK work
+    let out_of_range = explicit(&metadata, vec![0, 5, metadata.padded_len]);
+    assert!(matches!(
+        api::verify_raw(&[out_of_range], &proof, &ledger),
+        Err(KontorPoRError::ChallengeIndexOutOfRange { index, leaves, .. })
+            if index == metadata.padded_len && leaves == metadata.padded_len
+    ));
+
+    println!("✓ Explicit indices verify and other indices are rejected");
+}
+
+#[test]
+fn test_multi_file_explicit_indices() {
+    let (files, metadatas) = create_test_files(2, 300, 90);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+
+    let challenges = vec![
+        explicit(&metadatas[0], vec![1, 0]),
+        explicit(&metadatas[1], vec![metadatas[1].padded_len - 1, 2]),
+    ];
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let swapped = vec![explicit(&metadatas[0], vec![0, 1]), challenges[1].clone()];
+    assert!(!api::verify_raw(&swapped, &proof, &ledger).unwrap());
+}
+
+#[test]
+fn test_invalid_explicit_indices_rejected() {
+    let (files, metadatas) = create_test_files(2, 200, 91);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let first = &files[&metadatas[0].file_id];
+    let leaves = metadatas[0].padded_len;
+
+    assert!(matches!(
+        system.prove(vec![first], &[explicit(&metadatas[0], vec![0, leaves])]),
+        Err(KontorPoRError::ChallengeIndexOutOfRange { ref file_id, index, .. })
+            if *file_id == metadatas[0].file_id && index == leaves
+    ));
+
+    let short = Challenge::new_test(metadatas[0].clone(), 1000, 3, FieldElement::from(3u64))
+        .with_kind(ChallengeKind::ExplicitIndices(vec![0, 1]));
+    assert!(matches!(
+        system.prove(vec![first], &[short]),
+        Err(KontorPoRError::InvalidExplicitIndices {
+            expected: 3,
+            got: 2
+        })
+    ));
+
+    let with_schedule =
+        explicit(&metadatas[0], vec![0, 1]).with_seed_schedule(SeedSchedule::PerStep(vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+        ]));
+    assert!(matches!(
+        build_plan(&[with_schedule], &ledger),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("per-step seed schedule")
+    ));
+
+    let mixed = vec![
+        explicit(&metadatas[0], vec![0, 1]),
+        Challenge::new_test(metadatas[1].clone(), 1000, 2, FieldElement::from(3u64)),
+    ];
+    assert!(matches!(
+        system.prove(files.values().collect(), &mixed),
+        Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "kind"
+    ));
+}
@@ -0,0 +1,251 @@
+//! Tests for failure injection points (requires the `failpoints` feature)
+//!
+//! This module tests that:
+//! 1. Errors injected while proving surface as `InjectedFailure` and a later proof succeeds
+//! 2. A proof failing or panicking after a checkpointed step resumes from its checkpoint
+//! 3. Failures around parameter generation leave no cache file, temporary file or lock behind
+//! 4. A failed ledger update leaves the saved ledger loadable and unchanged
+
+#![cfg(feature = "failpoints")]
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    failpoints::{self, points, FailAction},
+    params::{self, Shape},
+    FileLedger, KontorPoRError,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+/// The failpoint registry and parameter cache directory are process-wide, so tests here
+/// run one at a time.
+static FAILPOINT_GUARD: Mutex<()> = Mutex::new(());
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("kontor_failpoints_{}_{}", name, std::process::id()))
+}
+
+fn assert_injected<T>(result: kontor_crypto::Result<T>, point: &str) {
+    match result {
+        Err(KontorPoRError::InjectedFailure { point: got }) => assert_eq!(got, point),
+        Err(other) => panic!("Expected InjectedFailure at {}, got {:?}", point, other),
+        Ok(_) => panic!("Expected InjectedFailure at {}, operation succeeded", point),
+    }
+}
+
+fn dir_entries(dir: &Path) -> Vec<String> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[test]
+fn test_prove_points_return_clean_errors() {
+    println!("Testing injected errors at each proving point");
+
+    let _guard = FAILPOINT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    failpoints::reset();
+
+    let (files, metadatas) = create_test_files(2, 300, 81);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(5u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 3, seed))
+        .collect();
+
+    for point in [
+        points::PROVE_AFTER_WITNESS_GEN,
+        points::PROVE_AFTER_STEP,
+        points::PROVE_BEFORE_SERIALIZATION,
+    ] {
+        failpoints::configure(point, FailAction::Error);
+        assert_injected(system.prove(files.values().collect(), &challenges), point);
+        failpoints::remove(point);
+        println!("  {}: clean error", point);
+    }
+
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Proving recovers once the points are disarmed");
+}
+
+#[test]
+fn test_checkpoint_survives_failure_after_step() {
+    println!("Testing resume after failures following checkpointed steps");
+
+    let _guard = FAILPOINT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    failpoints::reset();
+
+    let (files, metadatas) = create_test_files(1, 300, 82);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let challenges = vec![Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        4,
+        FieldElement::from(9u64),
+    )];
+    let path = temp_path("checkpoint.ckpt");
+    let _ = std::fs::remove_file(&path);
+
+    // Fail after step 3, once its checkpoint has been written
+    failpoints::configure_after(points::PROVE_AFTER_STEP, 2, FailAction::Error);
+    assert_injected(
+        system.prove_with_checkpoint(files.values().collect(), &challenges, &path, 1),
+        points::PROVE_AFTER_STEP,
+    );
+    failpoints::reset();
+    assert!(path.is_file(), "checkpoint written before the failure");
+
+    let proof = system
+        .resume_prove(&path, files.values().collect(), &challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // A panic in the resumed run leaves the checkpoint readable as well
+    failpoints::configure(points::PROVE_AFTER_STEP, FailAction::Panic);
+    let panicked = catch_unwind(AssertUnwindSafe(|| {
+        system.resume_prove(&path, files.values().collect(), &challenges)
+    }));
+    failpoints::reset();
+    assert!(panicked.is_err(), "armed panic point must panic");
+
+    let proof = system
+        .resume_prove(&path, files.values().collect(), &challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let _ = std::fs::remove_file(&path);
+    println!("✓ Checkpoints stay usable after injected errors and panics");
+}
+
+#[test]
+fn test_params_failures_leave_cache_clean() {
+    println!("Testing injected failures around parameter generation");
+
+    let _guard = FAILPOINT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    failpoints::reset();
+
+    let dir = temp_path("params");
+    let _ = std::fs::remove_dir_all(&dir);
+    params::set_cache_dir(Some(dir.clone()));
+    params::clear_memory_cache();
+    let shape = Shape::new(1, 2, 0);
+
+    failpoints::configure(points::PARAMS_BEFORE_CACHE_WRITE, FailAction::Error);
+    assert_injected(
+        params::load_or_generate_params_for_shape(&shape),
+        points::PARAMS_BEFORE_CACHE_WRITE,
+    );
+    failpoints::reset();
+    assert!(!params::is_cached(&shape));
+    assert!(dir_entries(&dir).is_empty(), "{:?}", dir_entries(&dir));
+
+    failpoints::configure(points::PARAMS_AFTER_GENERATION, FailAction::Panic);
+    let panicked = catch_unwind(|| params::load_or_generate_params_for_shape(&shape));
+    failpoints::reset();
+    assert!(panicked.is_err(), "armed panic point must panic");
+    assert!(!params::is_cached(&shape));
+    assert!(
+        dir_entries(&dir).is_empty(),
+        "a panic releases the shape lock: {:?}",
+        dir_entries(&dir)
+    );
+    assert_eq!(params::memory_cache_size(), 0);
+
+    // Disarmed, the shape is generated and cached normally
+    params::load_or_generate_params_for_shape(&shape).unwrap();
+    assert!(params::is_cached(&shape));
+
+    // A cached shape is loaded without generating or writing again
+    params::clear_memory_cache();
+    failpoints::configure(points::PARAMS_AFTER_GENERATION, FailAction::Error);
+    failpoints::configure(points::PARAMS_BEFORE_CACHE_WRITE, FailAction::Error);
+    let loaded = params::load_or_generate_params_for_shape(&shape);
+    failpoints::reset();
+    assert_eq!(loaded.unwrap().file_tree_depth, 2);
+
+    params::set_cache_dir(None);
+    params::clear_memory_cache();
+    let _ = std::fs::remove_dir_all(&dir);
+    println!("✓ Parameter cache left clean by injected failures");
+}
+
+#[test]
+fn test_ledger_failure_leaves_saved_ledger_intact() {
+    println!("Testing injected failures between tree rebuild and root append");
+
+    let _guard = FAILPOINT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    failpoints::reset();
+
+    let (_, first) = api::prepare_file(b"first ledger file", "first.dat").unwrap();
+    let (_, second) = api::prepare_file(b"second ledger file", "second.dat").unwrap();
+    let (_, third) = api::prepare_file(b"third ledger file", "third.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&first).unwrap();
+    ledger.add_file(&second).unwrap();
+    let path = temp_path("ledger.bin");
+    ledger.save(&path).unwrap();
+    let saved_root = ledger.root();
+    let saved_history = ledger.historical_roots().to_vec();
+
+    failpoints::configure(points::LEDGER_BEFORE_HISTORICAL_ROOT, FailAction::Error);
+    assert_injected(
+        ledger.add_file(&third),
+        points::LEDGER_BEFORE_HISTORICAL_ROOT,
+    );
+    failpoints::reset();
+    assert_eq!(
+        ledger.historical_roots(),
+        saved_history,
+        "the new root is not appended after the failure"
+    );
+
+    failpoints::configure(points::LEDGER_BEFORE_HISTORICAL_ROOT, FailAction::Panic);
+    let panicked = catch_unwind(AssertUnwindSafe(|| ledger.remove_file(&first.file_id)));
+    failpoints::reset();
+    assert!(panicked.is_err(), "armed panic point must panic");
+
+    let loaded = FileLedger::load(&path).unwrap();
+    assert_eq!(loaded.root(), saved_root);
+    assert_eq!(loaded.file_count(), 2);
+    assert_eq!(loaded.historical_roots(), saved_history);
+
+    let _ = std::fs::remove_file(&path);
+    println!("✓ Saved ledger unaffected by injected failures");
+}
+
+#[test]
+fn test_unarmed_and_skipped_points_pass() {
+    let _guard = FAILPOINT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    failpoints::reset();
+
+    let (_, metadata) = api::prepare_file(b"skipped point", "skip.dat").unwrap();
+    let (_, other) = api::prepare_file(b"second skipped point", "skip2.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    // The first hit passes, the second fires
+    failpoints::configure_after(points::LEDGER_BEFORE_HISTORICAL_ROOT, 1, FailAction::Error);
+    ledger.remove_file(&metadata.file_id).unwrap();
+    assert_injected(
+        ledger.add_file(&other),
+        points::LEDGER_BEFORE_HISTORICAL_ROOT,
+    );
+    failpoints::reset();
+
+    assert_eq!(points::ALL.len(), 6);
+    ledger.add_file(&metadata).unwrap();
+}
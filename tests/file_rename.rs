@@ -0,0 +1,101 @@
+//! Tests for renaming files without re-encoding
+//!
+//! This module tests that:
+//! 1. `FileMetadata::with_filename` changes only the filename
+//! 2. `FileLedger::rename_file` leaves the ledger root, rc values and history unchanged
+//! 3. Proofs made before a rename verify against renamed metadata and the renamed ledger
+//! 4. Renaming a file that is not in the ledger fails
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    FileLedger, KontorPoRError,
+};
+
+#[test]
+fn test_with_filename_keeps_commitments() {
+    let (_, metadata) = api::prepare_file(b"rename me", "old.dat").unwrap();
+    let renamed = metadata.with_filename("new.dat");
+
+    assert_eq!(renamed.filename(), "new.dat");
+    assert_eq!(metadata.filename(), "old.dat");
+    assert_eq!(renamed.with_filename("old.dat"), metadata);
+    assert_eq!(renamed.file_id, metadata.file_id);
+    assert_eq!(renamed.root, metadata.root);
+    assert_eq!(renamed.depth(), metadata.depth());
+}
+
+#[test]
+fn test_rename_keeps_root_and_proofs_valid() {
+    println!("Testing that renaming a ledger file keeps proofs valid");
+
+    let (first, first_metadata) = api::prepare_file(b"first file to rename", "a.dat").unwrap();
+    let (second, second_metadata) = api::prepare_file(b"second file, unchanged", "b.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&first_metadata).unwrap();
+    ledger.add_file(&second_metadata).unwrap();
+
+    let seed = FieldElement::from(9u64);
+    let challenges = vec![
+        Challenge::new_test(first_metadata.clone(), 1000, 2, seed),
+        Challenge::new_test(second_metadata.clone(), 1000, 2, seed),
+    ];
+    let proof = PorSystem::new(&ledger)
+        .prove(vec![&first, &second], &challenges)
+        .unwrap();
+
+    let root_before = ledger.root();
+    let history_before = ledger.historical_roots().to_vec();
+    let entry_before = ledger.entry(&first_metadata.file_id).unwrap().clone();
+
+    ledger
+        .rename_file(&first_metadata.file_id, "renamed.dat")
+        .unwrap();
+
+    let entry = &ledger.entry(&first_metadata.file_id).unwrap();
+    assert_eq!(entry.filename, "renamed.dat");
+    assert_eq!(entry.root, entry_before.root);
+    assert_eq!(entry.depth, entry_before.depth);
+    assert_eq!(entry.rc, entry_before.rc);
+    assert_eq!(ledger.root(), root_before);
+    assert_eq!(ledger.historical_roots(), history_before);
+    ledger.check_consistency().unwrap();
+
+    // The earlier proof verifies against the renamed ledger and renamed metadata
+    let renamed_challenges = vec![
+        Challenge::new_test(first_metadata.with_filename("renamed.dat"), 1000, 2, seed),
+        challenges[1].clone(),
+    ];
+    assert_eq!(renamed_challenges[0].id(), challenges[0].id());
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&proof, &challenges).unwrap());
+    assert!(system.verify(&proof, &renamed_challenges).unwrap());
+
+    // The new filename survives save/load
+    let path = std::env::temp_dir().join(format!("kontor_rename_{}.bin", std::process::id()));
+    ledger.save(&path).unwrap();
+    let loaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        loaded.entry(&first_metadata.file_id).unwrap().filename,
+        "renamed.dat"
+    );
+    assert_eq!(loaded.root(), root_before);
+
+    println!("✓ Rename left the root unchanged and the proof valid");
+}
+
+#[test]
+fn test_rename_unknown_file_fails() {
+    let (_, metadata) = api::prepare_file(b"only file", "only.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    assert!(matches!(
+        ledger.rename_file("not-a-file-id", "x.dat"),
+        Err(KontorPoRError::FileNotFound { file_id }) if file_id == "not-a-file-id"
+    ));
+    assert_eq!(
+        ledger.entry(&metadata.file_id).unwrap().filename,
+        "only.dat"
+    );
+}
@@ -0,0 +1,291 @@
+//! Tests for Poseidon hash profiles
+//!
+//! This module tests that:
+//! 1. Native hashes and circuit gadgets agree under each profile, and the profiles differ
+//! 2. Tree builders and paths hash under the given profile; file roots and root
+//!    commitments depend on it, and the ledger recovers it from rc
+//! 3. Conservative files prove and verify end to end, and batches cannot mix profiles
+//! 4. A Standard proof presented against Conservative metadata fails with
+//!    `HashProfileMismatch`
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, HashProfile, PorSystem, TreeArity},
+    circuit::gadgets::{poseidon_hash_tagged4_gadget_with, poseidon_hash_tagged_gadget_with},
+    merkle,
+    poseidon::{domain_tags, poseidon_hash_tagged4_with, poseidon_hash_tagged_with},
+    FileLedger, KontorPoRError,
+};
+use nova_snark::frontend::{
+    gadgets::num::AllocatedNum, util_cs::test_cs::TestConstraintSystem, ConstraintSystem,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+const PROFILES: [HashProfile; 2] = [HashProfile::Standard, HashProfile::Conservative];
+
+#[test]
+fn test_gadgets_match_native_hashes_per_profile() {
+    println!("Testing circuit gadgets against native hashes for each profile");
+
+    let x = FieldElement::from(42u64);
+    let y = FieldElement::from(123u64);
+    let tag = domain_tags::node();
+
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let x_alloc = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(x)).unwrap();
+    let y_alloc = AllocatedNum::alloc(cs.namespace(|| "y"), || Ok(y)).unwrap();
+
+    for profile in PROFILES {
+        let mut ns = cs.namespace(|| format!("{}", profile));
+        let hash = poseidon_hash_tagged_gadget_with(
+            ns.namespace(|| "hash2"),
+            profile,
+            tag,
+            &x_alloc,
+            &y_alloc,
+        )
+        .unwrap();
+        assert_eq!(
+            hash.get_value(),
+            Some(poseidon_hash_tagged_with(profile, tag, x, y))
+        );
+
+        let hash4 = poseidon_hash_tagged4_gadget_with(
+            ns.namespace(|| "hash4"),
+            profile,
+            tag,
+            [&x_alloc, &y_alloc, &y_alloc, &x_alloc],
+        )
+        .unwrap();
+        assert_eq!(
+            hash4.get_value(),
+            Some(poseidon_hash_tagged4_with(profile, tag, [x, y, y, x]))
+        );
+    }
+    assert!(cs.is_satisfied());
+
+    assert_ne!(
+        poseidon_hash_tagged_with(HashProfile::Standard, tag, x, y),
+        poseidon_hash_tagged_with(HashProfile::Conservative, tag, x, y),
+        "Profiles must use different constants"
+    );
+
+    println!("✓ Gadget and native hashes agree under both profiles");
+}
+
+#[test]
+fn test_tree_builders_hash_with_profile() {
+    let leaves: Vec<FieldElement> = (0..16u64).map(|i| FieldElement::from(i * 5 + 1)).collect();
+
+    for profile in PROFILES {
+        let binary = merkle::build_tree_from_leaves_with_profile(&leaves, profile).unwrap();
+        assert_eq!(
+            binary.layers[1][0],
+            merkle::hash_node_with_profile(profile, leaves[0], leaves[1])
+        );
+        let proof = merkle::get_padded_proof_for_leaf_with_profile(&binary, 9, 4, profile).unwrap();
+        assert!(merkle::verify_merkle_proof_in_place_with_profile(
+            binary.root(),
+            &proof,
+            profile
+        ));
+
+        let arity4 = merkle::build_tree_from_leaves_arity4_with_profile(&leaves, profile).unwrap();
+        assert_eq!(
+            arity4.layers[1][0],
+            merkle::hash_node4_with_profile(profile, [leaves[0], leaves[1], leaves[2], leaves[3]])
+        );
+        let proof4 =
+            merkle::get_padded_proof4_for_leaf_with_profile(&arity4, 9, 2, profile).unwrap();
+        assert!(merkle::verify_merkle_proof4_in_place_with_profile(
+            arity4.root(),
+            &proof4,
+            profile
+        ));
+    }
+
+    // A path built under one profile does not verify under the other
+    let standard = merkle::build_tree_from_leaves(&leaves).unwrap();
+    let proof = merkle::get_padded_proof_for_leaf(&standard, 3, 4).unwrap();
+    assert!(!merkle::verify_merkle_proof_in_place_with_profile(
+        standard.root(),
+        &proof,
+        HashProfile::Conservative
+    ));
+}
+
+#[test]
+fn test_profile_changes_roots_and_commitments() {
+    let data = create_test_data(3000, Some(5));
+    let (_, standard) = api::prepare_file(&data, "profile.dat").unwrap();
+
+    for tree_arity in [TreeArity::Binary, TreeArity::Arity4] {
+        let (_, same_arity) =
+            api::prepare_file_with_arity(&data, "profile.dat", tree_arity).unwrap();
+        let (_, conservative) = api::prepare_file_with_profile(
+            &data,
+            "profile.dat",
+            tree_arity,
+            HashProfile::Conservative,
+        )
+        .unwrap();
+        assert_eq!(conservative.hash_profile, HashProfile::Conservative);
+        assert_eq!(conservative.file_id, same_arity.file_id);
+        assert_ne!(conservative.root, same_arity.root);
+
+        // The ledger recovers both parameters from rc
+        let mut ledger = FileLedger::new();
+        ledger.add_file(&conservative).unwrap();
+        let entry = ledger.entry(&conservative.file_id).unwrap();
+        assert_eq!(entry.tree_arity(), Some(tree_arity));
+        assert_eq!(entry.hash_profile(), Some(HashProfile::Conservative));
+        assert_ne!(
+            entry.rc,
+            tree_arity.root_commitment(conservative.root, conservative.depth())
+        );
+    }
+
+    // Relabeling the profile changes the challenge identity
+    let seed = FieldElement::from(3u64);
+    let mut relabeled = standard.clone();
+    relabeled.hash_profile = HashProfile::Conservative;
+    assert_ne!(
+        Challenge::new_test(relabeled, 1000, 2, seed).id(),
+        Challenge::new_test(standard, 1000, 2, seed).id()
+    );
+}
+
+#[test]
+fn test_conservative_files_prove_and_verify() {
+    println!("Testing Conservative-profile prove/verify for a two-file batch");
+
+    let (prepared1, metadata1) = api::prepare_file_with_profile(
+        &create_test_data(2500, Some(1)),
+        "hc_first.dat",
+        TreeArity::Binary,
+        HashProfile::Conservative,
+    )
+    .unwrap();
+    let (prepared2, metadata2) = api::prepare_file_with_profile(
+        &create_test_data(700, Some(2)),
+        "hc_second.dat",
+        TreeArity::Binary,
+        HashProfile::Conservative,
+    )
+    .unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let seed = FieldElement::from(91u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 2, seed),
+        Challenge::new_test(metadata2, 1000, 2, seed),
+    ];
+
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&prepared1, &prepared2], &challenges)
+        .unwrap();
+    assert_eq!(proof.hash_profile, HashProfile::Conservative);
+    assert!(
+        system.verify(&proof, &challenges).unwrap(),
+        "Conservative proof should verify"
+    );
+
+    println!("✓ Conservative files prove and verify end to end");
+}
+
+#[test]
+fn test_mixed_hash_profiles_rejected() {
+    let (prepared1, metadata1) = api::prepare_file_with_profile(
+        b"conservative file",
+        "a.dat",
+        TreeArity::Binary,
+        HashProfile::Conservative,
+    )
+    .unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"standard file", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 1, FieldElement::from(1u64)),
+        Challenge::new_test(metadata2, 1000, 1, FieldElement::from(1u64)),
+    ];
+
+    let result = PorSystem::new(&ledger).prove(vec![&prepared1, &prepared2], &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "hash_profile"),
+        "Mixed hash profiles must be rejected"
+    );
+}
+
+#[test]
+fn test_standard_proof_against_conservative_metadata_fails() {
+    println!("Testing a Standard proof verified against Conservative metadata");
+
+    let data = create_test_data(1200, Some(9));
+    let (prepared, metadata) = api::prepare_file(&data, "mismatch.dat").unwrap();
+    let (_, conservative) = api::prepare_file_with_profile(
+        &data,
+        "mismatch.dat",
+        TreeArity::Binary,
+        HashProfile::Conservative,
+    )
+    .unwrap();
+
+    let seed = FieldElement::from(17u64);
+    let challenges = [Challenge::new_test(metadata.clone(), 1000, 2, seed)];
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    assert_eq!(proof.hash_profile, HashProfile::Standard);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // A verifier whose ledger records the file under the Conservative profile
+    let mut conservative_ledger = FileLedger::new();
+    conservative_ledger.add_file(&conservative).unwrap();
+    let result = PorSystem::new(&conservative_ledger)
+        .verify(&proof, &[Challenge::new_test(conservative, 1000, 2, seed)]);
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::HashProfileMismatch {
+                proof: HashProfile::Standard,
+                metadata: HashProfile::Conservative,
+            })
+        ),
+        "Expected HashProfileMismatch, got {:?}",
+        result
+    );
+
+    // The prover also refuses a file prepared under another profile
+    let relabeled = {
+        let mut m = metadata;
+        m.hash_profile = HashProfile::Conservative;
+        m
+    };
+    let result = system.prove(
+        vec![&prepared],
+        &[Challenge::new_test(relabeled, 1000, 2, seed)],
+    );
+    assert!(result.is_err(), "Prover must reject a profile mismatch");
+
+    println!("✓ Profile mismatch reported as HashProfileMismatch");
+}
+
+#[test]
+fn test_hash_profile_display_and_default() {
+    assert_eq!(HashProfile::default(), HashProfile::Standard);
+    assert_eq!(HashProfile::Standard.to_string(), "standard");
+    assert_eq!(HashProfile::Conservative.to_string(), "conservative");
+    assert!(HashProfile::Conservative.is_conservative());
+    assert!(!HashProfile::Standard.is_conservative());
+}
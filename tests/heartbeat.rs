@@ -0,0 +1,107 @@
+//! Tests for heartbeat proofs (epochs without challenged files)
+//!
+//! This module tests that:
+//! 1. A heartbeat proves and verifies against the ledger's current root
+//! 2. A heartbeat is bound to its seed and prover
+//! 3. A heartbeat against a stale root fails once that root is pruned
+//! 4. Proofs that answer challenges are not accepted as heartbeats
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem},
+    poseidon::calculate_heartbeat_commitment,
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_single_file_ledger, create_test_files};
+
+#[test]
+fn test_heartbeat_roundtrip() {
+    println!("Testing heartbeat proof generation and verification");
+
+    let (_, metadatas) = create_test_files(2, 300, 41);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(7u64);
+
+    let proof = system.prove_heartbeat(seed, "node_1").unwrap();
+    assert!(proof.challenge_ids.is_empty());
+    assert_eq!(proof.ledger_root, ledger.root());
+    assert_eq!(proof.shape.aggregated_tree_depth, 0);
+
+    assert!(system.verify_heartbeat(&proof, seed, "node_1").unwrap());
+
+    println!("✓ Heartbeat verified");
+}
+
+#[test]
+fn test_heartbeat_is_bound_to_seed_and_prover() {
+    println!("Testing that a heartbeat only verifies for its seed and prover");
+
+    let (_, metadatas) = create_test_files(1, 300, 42);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(8u64);
+
+    let mut proof = system.prove_heartbeat(seed, "node_1").unwrap();
+    assert!(!system
+        .verify_heartbeat(&proof, FieldElement::from(9u64), "node_1")
+        .unwrap());
+    assert!(!system.verify_heartbeat(&proof, seed, "node_2").unwrap());
+
+    // Rewriting the commitment does not transfer the SNARK to another prover
+    proof.challenge_nonce_commitment = calculate_heartbeat_commitment(seed, "node_2");
+    assert!(!system.verify_heartbeat(&proof, seed, "node_2").unwrap());
+
+    println!("✓ Heartbeat bound to seed and prover");
+}
+
+#[test]
+fn test_heartbeat_against_pruned_root_fails() {
+    println!("Testing heartbeats against historical and pruned roots");
+
+    let (_, metadatas) = create_test_files(3, 300, 43);
+    let mut ledger = create_multi_file_ledger(&metadatas[..2].iter().collect::<Vec<_>>());
+    let seed = FieldElement::from(10u64);
+    let proof = PorSystem::new(&ledger)
+        .prove_heartbeat(seed, "node_1")
+        .unwrap();
+    let stale_root = proof.ledger_root;
+
+    // Still accepted while the old root is retained as a historical root
+    ledger.add_file(&metadatas[2]).unwrap();
+    assert_ne!(ledger.root(), stale_root);
+    assert!(PorSystem::new(&ledger)
+        .verify_heartbeat(&proof, seed, "node_1")
+        .unwrap());
+
+    ledger.set_historical_roots(Vec::new()).unwrap();
+    let result = PorSystem::new(&ledger).verify_heartbeat(&proof, seed, "node_1");
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidLedgerRoot { .. })),
+        "Heartbeat against a pruned root must fail, got {:?}",
+        result
+    );
+
+    println!("✓ Pruned root rejected");
+}
+
+#[test]
+fn test_challenge_proof_is_not_a_heartbeat() {
+    println!("Testing that a regular proof is rejected as a heartbeat");
+
+    let (files, metadatas) = create_test_files(1, 300, 44);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(11u64);
+
+    let challenge = Challenge::new_test(metadatas[0].clone(), 1000, 1, seed);
+    let proof = system
+        .prove(vec![&files[&metadatas[0].file_id]], &[challenge])
+        .unwrap();
+
+    let result = system.verify_heartbeat(&proof, seed, "node_1");
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+
+    println!("✓ Regular proof rejected");
+}
@@ -0,0 +1,162 @@
+//! Tests for validation of prepared-file leaf widths before proving
+//!
+//! This module tests that:
+//! 1. `PreparedFile::validate` accepts prepared files and reports the first leaf that does
+//!    not round-trip through `leaf_to_bytes31` as `InvalidLeafEncoding`
+//! 2. Files above the exhaustive threshold are sampled by default and checked in full
+//!    under `LeafValidation::Exhaustive`; a seeded sample, or one seeded from the file
+//!    root, checks the same leaves every time
+//! 3. `PorSystem::prove` and `prove_with_options` reject a hostile tree with an over-wide
+//!    leaf, and witness generation rejects an over-wide challenged leaf
+
+use ff::Field;
+use kontor_crypto::{
+    api::{
+        generate_circuit_witness, prepare_file, tree_depth_from_metadata, Challenge, FieldElement,
+        LeafValidation, PorSystem, PreparedFile, ProveOptions,
+    },
+    config, KontorPoRError,
+};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_data, widen_leaves};
+
+#[test]
+fn test_validate_reports_first_wide_leaf() {
+    let (prepared, metadata) = prepare_file(&create_test_data(2000, Some(1)), "a.dat").unwrap();
+    assert!(metadata.padded_len <= config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD);
+    prepared.validate(LeafValidation::Sampled).unwrap();
+    prepared.validate(LeafValidation::Exhaustive).unwrap();
+
+    // Small files are checked in full even when sampled
+    let hostile = widen_leaves(&prepared, [metadata.padded_len - 1, 5]);
+    assert_eq!(hostile.root, prepared.root);
+    for mode in [LeafValidation::Sampled, LeafValidation::Exhaustive] {
+        let result = hostile.validate(mode);
+        assert!(
+            matches!(
+                result,
+                Err(KontorPoRError::InvalidLeafEncoding { index: 5 })
+            ),
+            "{:?}: got {:?}",
+            mode,
+            result
+        );
+    }
+}
+
+#[test]
+fn test_large_files_are_sampled() {
+    let data = create_test_data(150_000, Some(2));
+    let (prepared, metadata) = prepare_file(&data, "large.dat").unwrap();
+    assert!(metadata.padded_len > config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD);
+    prepared.validate(LeafValidation::Sampled).unwrap();
+
+    let one_wide = widen_leaves(&prepared, [4321]);
+    assert!(matches!(
+        one_wide.validate(LeafValidation::Exhaustive),
+        Err(KontorPoRError::InvalidLeafEncoding { index: 4321 })
+    ));
+
+    // Any sample finds a leaf when every leaf is wide
+    let all_wide = widen_leaves(&prepared, 0..metadata.padded_len);
+    assert!(matches!(
+        all_wide.validate(LeafValidation::Sampled),
+        Err(KontorPoRError::InvalidLeafEncoding { .. })
+    ));
+
+    // A seeded sample hits the same wide leaf every time: the first leaf it checks
+    let sampled = wide_leaf(all_wide.validate_with_seed(LeafValidation::Sampled, Some(5)));
+    let hit = widen_leaves(&prepared, [sampled]);
+    let ledger = create_single_file_ledger(&metadata);
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(6u64));
+    let seeded = ProveOptions::default().with_rng_seed(5);
+    for _ in 0..3 {
+        assert_eq!(
+            wide_leaf(hit.validate_with_seed(LeafValidation::Sampled, Some(5))),
+            sampled
+        );
+        let result =
+            system.prove_with_options(vec![&hit], std::slice::from_ref(&challenge), &seeded);
+        assert_eq!(wide_leaf(result.map(|_| ())), sampled);
+    }
+
+    // Without a seed the sample comes from the file root, so repeated checks agree
+    let unseeded = wide_leaf(all_wide.validate(LeafValidation::Sampled));
+    for _ in 0..3 {
+        assert_eq!(
+            wide_leaf(all_wide.validate(LeafValidation::Sampled)),
+            unseeded
+        );
+    }
+}
+
+/// The index of an `InvalidLeafEncoding` failure.
+fn wide_leaf(result: Result<(), KontorPoRError>) -> usize {
+    match result {
+        Err(KontorPoRError::InvalidLeafEncoding { index }) => index,
+        other => panic!("expected InvalidLeafEncoding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_prove_rejects_wide_leaves() {
+    println!("Testing that proving over a tree with an over-wide leaf is rejected");
+
+    let (prepared, metadata) = prepare_file(&create_test_data(3000, Some(3)), "p.dat").unwrap();
+    let ledger = create_single_file_ledger(&metadata);
+    let system = PorSystem::new(&ledger);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(8u64));
+    let hostile = widen_leaves(&prepared, [17]);
+
+    let result = system.prove(vec![&hostile], std::slice::from_ref(&challenge));
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::InvalidLeafEncoding { index: 17 })
+        ),
+        "got {:?}",
+        result.map(|_| ())
+    );
+    let exhaustive = ProveOptions::default().with_leaf_validation(LeafValidation::Exhaustive);
+    let result = system.prove_with_options(vec![&hostile], &[challenge], &exhaustive);
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidLeafEncoding { index: 17 })
+    ));
+
+    println!("✓ Hostile tree rejected before proving");
+}
+
+#[test]
+fn test_witness_rejects_wide_challenged_leaf() {
+    let (prepared, metadata) = prepare_file(&create_test_data(1000, Some(4)), "w.dat").unwrap();
+    let hostile = widen_leaves(&prepared, 0..metadata.padded_len);
+    let depth = tree_depth_from_metadata(&metadata);
+    let ledger = create_single_file_ledger(&metadata);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 1, FieldElement::from(4u64));
+
+    let generate = |file: &PreparedFile| {
+        let files: BTreeMap<String, &PreparedFile> = [(metadata.file_id.clone(), file)].into();
+        generate_circuit_witness(
+            &[&challenge],
+            Some(&files),
+            &ledger,
+            depth,
+            depth,
+            FieldElement::ZERO,
+            0,
+            0,
+            &[0],
+        )
+        .map(|_| ())
+    };
+
+    assert!(generate(&prepared).is_ok());
+    assert!(matches!(
+        generate(&hostile),
+        Err(KontorPoRError::InvalidLeafEncoding { .. })
+    ));
+}
@@ -0,0 +1,155 @@
+//! Tests for preparing files from precomputed leaf hashes
+//!
+//! This module tests that:
+//! 1. `prepare_file_with_leaf_hashes` ingests correct leaf hashes into a file that proves
+//!    and verifies
+//! 2. A single wrong leaf hash is reported as `LeafHashMismatch` by exhaustive validation,
+//!    and by sampled validation of small files
+//! 3. The resulting metadata and tree equal `prepare_file_raw` on the same bytes, and
+//!    `merkle::build_tree_from_prehashed` equals `build_tree`
+//! 4. Mismatched counts and mis-sized symbols are rejected
+
+use kontor_crypto::{
+    api::{
+        prepare_file_raw, prepare_file_with_leaf_hashes, prepare_file_with_leaf_hashes_validated,
+        Challenge, FieldElement, LeafValidation, PorSystem,
+    },
+    config, merkle, FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+/// Splits `data` into raw symbols and their leaves, as a storage system would hold them.
+fn symbols_and_leaves(data: &[u8]) -> (Vec<Vec<u8>>, Vec<FieldElement>) {
+    let symbols: Vec<Vec<u8>> = data
+        .chunks(config::CHUNK_SIZE_BYTES)
+        .map(<[u8]>::to_vec)
+        .collect();
+    let leaves = symbols
+        .iter()
+        .map(|symbol| merkle::get_leaf_hash(symbol).unwrap())
+        .collect();
+    (symbols, leaves)
+}
+
+#[test]
+fn test_ingested_leaf_hashes_prove_and_verify() {
+    println!("Testing ingestion of precomputed leaf hashes");
+
+    let data = create_test_data(2_000, Some(710));
+    let (symbols, leaves) = symbols_and_leaves(&data);
+    let (prepared, metadata) =
+        prepare_file_with_leaf_hashes(&symbols, &leaves, "ingested.dat").unwrap();
+    assert_eq!(metadata.original_size, data.len());
+    assert_eq!(metadata.erasure, None);
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+    let challenges = vec![Challenge::new_test(
+        metadata,
+        1000,
+        2,
+        FieldElement::from(71u64),
+    )];
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Ingested leaf hashes build a provable file");
+}
+
+#[test]
+fn test_wrong_leaf_hash_is_caught() {
+    println!("Testing detection of a wrong leaf hash");
+
+    // Above the exhaustive threshold, so only exhaustive validation must see every leaf
+    let data = create_test_data(
+        (config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD + 100) * config::CHUNK_SIZE_BYTES,
+        Some(720),
+    );
+    let (symbols, mut leaves) = symbols_and_leaves(&data);
+    assert!(symbols.len() > config::LEAF_VALIDATION_EXHAUSTIVE_THRESHOLD);
+    leaves[4150] = merkle::get_leaf_hash(&[0xAB; 31]).unwrap();
+
+    let result = prepare_file_with_leaf_hashes_validated(
+        &symbols,
+        &leaves,
+        "wrong.dat",
+        LeafValidation::Exhaustive,
+    );
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::LeafHashMismatch { index: 4150 })
+        ),
+        "expected LeafHashMismatch at 4150, got {result:?}"
+    );
+
+    // Small files are checked in full even when sampled
+    let (small_symbols, mut small_leaves) = symbols_and_leaves(&data[..3_100]);
+    small_leaves[17] = FieldElement::from(17u64);
+    let result = prepare_file_with_leaf_hashes(&small_symbols, &small_leaves, "wrong.dat");
+    assert!(
+        matches!(result, Err(KontorPoRError::LeafHashMismatch { index: 17 })),
+        "expected LeafHashMismatch at 17, got {result:?}"
+    );
+
+    println!("✓ A single wrong leaf hash is rejected");
+}
+
+#[test]
+fn test_metadata_matches_standard_path() {
+    println!("Testing equivalence with prepare_file_raw");
+
+    for len in [0, 1, 31, 32, 31 * 64, 5_000] {
+        let data = create_test_data(len, Some(730 + len as u64));
+        let (symbols, leaves) = symbols_and_leaves(&data);
+        let (prepared, metadata) =
+            prepare_file_with_leaf_hashes(&symbols, &leaves, "same.dat").unwrap();
+        let (expected_prepared, expected_metadata) = prepare_file_raw(&data, "same.dat").unwrap();
+        assert_eq!(
+            metadata, expected_metadata,
+            "metadata differs for {len} bytes"
+        );
+        assert_eq!(prepared.debug_full(), expected_prepared.debug_full());
+    }
+
+    let (symbols, leaves) = symbols_and_leaves(&create_test_data(1_000, Some(740)));
+    let (tree, root) = merkle::build_tree_from_prehashed(&leaves).unwrap();
+    let (expected_tree, expected_root) = merkle::build_tree(&symbols).unwrap();
+    assert_eq!(root, expected_root);
+    assert_eq!(tree.layers, expected_tree.layers);
+    let (empty_tree, empty_root) = merkle::build_tree_from_prehashed(&[]).unwrap();
+    assert_eq!(empty_root, merkle::build_tree(&[]).unwrap().1);
+    assert_eq!(empty_tree.layers, vec![vec![empty_root]]);
+
+    // A leaf wider than 31 bytes cannot be a symbol's leaf
+    let mut wide = leaves.clone();
+    wide[3] = -FieldElement::from(1u64);
+    assert!(matches!(
+        merkle::build_tree_from_prehashed(&wide),
+        Err(KontorPoRError::InvalidLeafEncoding { index: 3 })
+    ));
+
+    println!("✓ Ingested files match the standard path");
+}
+
+#[test]
+fn test_malformed_input_is_rejected() {
+    println!("Testing malformed symbol input");
+
+    let (mut symbols, leaves) = symbols_and_leaves(&create_test_data(200, Some(750)));
+
+    let result = prepare_file_with_leaf_hashes(&symbols, &leaves[1..], "bad.dat");
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+
+    symbols[2].pop();
+    let result = prepare_file_with_leaf_hashes(&symbols, &leaves, "bad.dat");
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidInput(ref msg)) if msg.contains("symbol 2")),
+        "expected a short symbol to be rejected, got {result:?}"
+    );
+
+    println!("✓ Malformed input is rejected");
+}
@@ -0,0 +1,181 @@
+//! Tests for ledger root attestations used for on-chain anchoring
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    ledger::{verify_ledger_root_with_attestations, FileLedger, LedgerAttestation},
+    KontorPoRError,
+};
+
+fn ledger_with_files(count: u8) -> (FileLedger, Vec<(api::PreparedFile, api::FileMetadata)>) {
+    let files: Vec<_> = (0..count)
+        .map(|i| api::prepare_file(&[i + 1; 120], &format!("attest_{}.dat", i)).unwrap())
+        .collect();
+    let mut ledger = FileLedger::new();
+    for (_, metadata) in &files {
+        ledger.add_file(metadata).unwrap();
+    }
+    (ledger, files)
+}
+
+#[test]
+fn test_attestation_roundtrip() {
+    println!("Testing attestation serialization round-trips");
+
+    let (ledger, _) = ledger_with_files(3);
+    let attestation = ledger.attestation(840_000);
+
+    assert_eq!(attestation.file_count, 3);
+    assert_eq!(attestation.aggregated_depth, ledger.depth() as u64);
+    assert_eq!(attestation.block_height, 840_000);
+    assert_eq!(attestation.root_field(), Some(ledger.root()));
+    assert!(attestation.verify_against(&ledger));
+
+    let bytes = attestation.to_bytes();
+    assert_eq!(LedgerAttestation::from_bytes(&bytes).unwrap(), attestation);
+
+    let hex = attestation.to_hex();
+    assert_eq!(hex.len(), bytes.len() * 2);
+    assert_eq!(LedgerAttestation::from_hex(&hex).unwrap(), attestation);
+
+    let encoded = bincode::serialize(&attestation).unwrap();
+    let decoded: LedgerAttestation = bincode::deserialize(&encoded).unwrap();
+    assert_eq!(decoded, attestation);
+
+    // The same ledger state attested at another height yields a different commitment
+    assert_ne!(
+        ledger.attestation(840_001).commitment,
+        attestation.commitment
+    );
+
+    println!("✓ Attestation survives bytes, hex and serde round-trips");
+}
+
+#[test]
+fn test_tampered_file_count_detected() {
+    println!("Testing that a tampered file count is detected");
+
+    let (ledger, _) = ledger_with_files(3);
+    let mut tampered = ledger.attestation(100);
+    tampered.file_count += 1;
+
+    assert!(!tampered.is_consistent());
+    assert!(!tampered.verify_against(&ledger));
+
+    let result = LedgerAttestation::from_hex(&tampered.to_hex());
+    assert!(
+        matches!(result, Err(KontorPoRError::LedgerValidation { .. })),
+        "Decoding a tampered attestation must fail, got {:?}",
+        result
+    );
+
+    // A stale attestation is consistent but no longer matches the grown ledger
+    let (mut grown, _) = ledger_with_files(3);
+    let stale = grown.attestation(100);
+    let (_, extra) = api::prepare_file(b"late arrival", "late.dat").unwrap();
+    grown.add_file(&extra).unwrap();
+    assert!(stale.is_consistent());
+    assert!(!stale.verify_against(&grown));
+
+    println!("✓ Tampered and stale attestations rejected");
+}
+
+#[test]
+fn test_tampered_aggregated_depth_detected() {
+    println!("Testing that a tampered aggregated depth is detected");
+
+    let (ledger, files) = ledger_with_files(3);
+    let honest = ledger.attestation(100);
+    for depth in [0, honest.aggregated_depth + 1, u64::MAX] {
+        let mut tampered = honest.clone();
+        tampered.aggregated_depth = depth;
+
+        assert!(!tampered.is_consistent());
+        assert!(!tampered.verify_against(&ledger));
+        let result = LedgerAttestation::from_hex(&tampered.to_hex());
+        assert!(
+            matches!(result, Err(KontorPoRError::LedgerValidation { .. })),
+            "Decoding an attestation with depth {} must fail, got {:?}",
+            depth,
+            result
+        );
+    }
+
+    // A relayed attestation with an edited depth no longer selects a proof's root
+    let challenges: Vec<_> = files
+        .iter()
+        .map(|(_, metadata)| {
+            Challenge::new_test(metadata.clone(), 1000, 1, FieldElement::from(9u64))
+        })
+        .collect();
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(
+            files.iter().map(|(prepared, _)| prepared).collect(),
+            &challenges,
+        )
+        .unwrap();
+    let posted = ledger.attestation(100);
+    assert!(verify_ledger_root_with_attestations(&proof, std::slice::from_ref(&posted)).is_ok());
+    for depth in [0, proof.shape.aggregated_tree_depth as u64 + 1] {
+        let mut tampered = posted.clone();
+        tampered.aggregated_depth = depth;
+        assert!(matches!(
+            verify_ledger_root_with_attestations(&proof, &[tampered]),
+            Err(KontorPoRError::InvalidLedgerRoot { .. })
+        ));
+    }
+
+    // An attestation encoded in format version 1 is rejected
+    let mut old = ledger.attestation(100).to_bytes();
+    old[4..6].copy_from_slice(&1u16.to_le_bytes());
+    assert!(matches!(
+        LedgerAttestation::from_bytes(&old),
+        Err(KontorPoRError::Serialization(_))
+    ));
+
+    println!("✓ Tampered depths rejected");
+}
+
+#[test]
+fn test_proof_root_verified_against_attestations_only() {
+    println!("Testing proof ledger_root validation against posted attestations");
+
+    let (mut ledger, files) = ledger_with_files(2);
+    let challenges: Vec<_> = files
+        .iter()
+        .map(|(_, metadata)| {
+            Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(5u64))
+        })
+        .collect();
+    let prepared: Vec<_> = files.iter().map(|(prepared, _)| prepared).collect();
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(prepared, &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // Post the attestation, then move the ledger on and forget its history
+    let posted = LedgerAttestation::from_hex(&ledger.attestation(10).to_hex()).unwrap();
+    let (_, extra) = api::prepare_file(b"next epoch file", "next.dat").unwrap();
+    ledger.add_file(&extra).unwrap();
+    ledger.set_historical_roots(Vec::new()).unwrap();
+    assert!(!ledger.is_valid_root(proof.ledger_root));
+    let later = ledger.attestation(20);
+
+    let attestations = vec![later.clone(), posted.clone()];
+    let matched = verify_ledger_root_with_attestations(&proof, &attestations).unwrap();
+    assert_eq!(matched, &posted);
+
+    // Without the matching attestation the root is rejected
+    let result = verify_ledger_root_with_attestations(&proof, std::slice::from_ref(&later));
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+
+    // A tampered copy of the right attestation is not accepted either
+    let mut tampered = posted;
+    tampered.file_count = 7;
+    assert!(verify_ledger_root_with_attestations(&proof, &[tampered]).is_err());
+
+    println!("✓ Proof root validated from attestations alone");
+}
@@ -4,7 +4,7 @@
 //! cryptographic results to individual adds, with the benefit of rebuilding
 //! the Merkle tree only once.
 
-use kontor_crypto::api::{self, FieldElement, FileMetadata};
+use kontor_crypto::api::{self, ErasureCode, FieldElement, FileMetadata, TreeArity};
 use kontor_crypto::ledger::FileLedger;
 
 /// Helper to create a dummy FileMetadata for testing.
@@ -15,12 +15,16 @@ fn dummy_metadata(file_id: &str, root_val: u64, depth: usize) -> FileMetadata {
         file_id: file_id.to_string(),
         padded_len: 1 << depth, // 2^depth
         original_size: 100,
-        filename: format!("{}.dat", file_id),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     }
 }
 
 fn historical_root_total(ledger: &FileLedger) -> usize {
-    ledger.historical_roots.len()
+    ledger.historical_roots().len()
 }
 
 // ===========================================
@@ -39,10 +43,10 @@ fn test_batch_add_basic() {
 
     ledger.add_files(&files).expect("Batch add should succeed");
 
-    assert_eq!(ledger.files.len(), 3, "Should have 3 files");
-    assert!(ledger.files.contains_key("file_a"));
-    assert!(ledger.files.contains_key("file_b"));
-    assert!(ledger.files.contains_key("file_c"));
+    assert_eq!(ledger.file_count(), 3, "Should have 3 files");
+    assert!(ledger.entry("file_a").is_some());
+    assert!(ledger.entry("file_b").is_some());
+    assert!(ledger.entry("file_c").is_some());
 }
 
 #[test]
@@ -52,7 +56,7 @@ fn test_batch_add_empty() {
     // Add a file first
     let existing = dummy_metadata("existing", 1, 3);
     ledger.add_file(&existing).unwrap();
-    let root_before = ledger.tree.root();
+    let root_before = ledger.root();
 
     // Empty batch should succeed and not change anything
     let empty: Vec<FileMetadata> = vec![];
@@ -60,8 +64,8 @@ fn test_batch_add_empty() {
         .add_files(&empty)
         .expect("Empty batch should succeed");
 
-    assert_eq!(ledger.files.len(), 1, "Should still have 1 file");
-    assert_eq!(ledger.tree.root(), root_before, "Root should not change");
+    assert_eq!(ledger.file_count(), 1, "Should still have 1 file");
+    assert_eq!(ledger.root(), root_before, "Root should not change");
 }
 
 #[test]
@@ -73,13 +77,13 @@ fn test_batch_add_single_file() {
         .add_files(&files)
         .expect("Single file batch should succeed");
 
-    assert_eq!(ledger.files.len(), 1);
-    assert!(ledger.files.contains_key("only_file"));
+    assert_eq!(ledger.file_count(), 1);
+    assert!(ledger.entry("only_file").is_some());
     assert_eq!(
-        ledger.files.get("only_file").unwrap().root,
+        ledger.entry("only_file").unwrap().root,
         FieldElement::from(999u64)
     );
-    assert_eq!(ledger.files.get("only_file").unwrap().depth, 5);
+    assert_eq!(ledger.entry("only_file").unwrap().depth, 5);
 }
 
 // ===========================================
@@ -107,21 +111,20 @@ fn test_batch_add_equivalent_to_individual_adds() {
 
     // Both should produce identical cryptographic results
     assert_eq!(
-        ledger_individual.tree.root(),
-        ledger_batch.tree.root(),
+        ledger_individual.root(),
+        ledger_batch.root(),
         "Aggregated roots must be identical"
     );
     assert_eq!(
-        ledger_individual.files.len(),
-        ledger_batch.files.len(),
+        ledger_individual.file_count(),
+        ledger_batch.file_count(),
         "File counts must be identical"
     );
 
     // Verify all file entries match exactly
-    for (key, entry_individual) in &ledger_individual.files {
+    for (key, entry_individual) in ledger_individual.entries() {
         let entry_batch = ledger_batch
-            .files
-            .get(key)
+            .entry(key)
             .expect("File should exist in batch ledger");
         assert_eq!(entry_individual.root, entry_batch.root);
         assert_eq!(entry_individual.depth, entry_batch.depth);
@@ -155,8 +158,8 @@ fn test_batch_add_order_independence() {
     ledger2.add_files(&files_order2).unwrap();
 
     assert_eq!(
-        ledger1.tree.root(),
-        ledger2.tree.root(),
+        ledger1.root(),
+        ledger2.root(),
         "Different insertion orders must produce identical root (BTreeMap sorts by key)"
     );
 
@@ -183,10 +186,10 @@ fn test_batch_add_with_duplicates_in_batch() {
 
     ledger.add_files(&files).unwrap();
 
-    assert_eq!(ledger.files.len(), 2, "Should have 2 unique files");
+    assert_eq!(ledger.file_count(), 2, "Should have 2 unique files");
 
     // The last duplicate should win
-    let dup_entry = ledger.files.get("dup_file").unwrap();
+    let dup_entry = ledger.entry("dup_file").unwrap();
     assert_eq!(
         dup_entry.root,
         FieldElement::from(999u64),
@@ -202,7 +205,7 @@ fn test_batch_add_overwrites_existing_files() {
     // Add initial file
     let existing = dummy_metadata("existing", 100, 3);
     ledger.add_file(&existing).unwrap();
-    let original_root = ledger.tree.root();
+    let original_root = ledger.root();
 
     // Batch add with same file_id but different values
     let files = vec![
@@ -211,14 +214,14 @@ fn test_batch_add_overwrites_existing_files() {
     ];
     ledger.add_files(&files).unwrap();
 
-    assert_eq!(ledger.files.len(), 2);
+    assert_eq!(ledger.file_count(), 2);
     assert_eq!(
-        ledger.files.get("existing").unwrap().root,
+        ledger.entry("existing").unwrap().root,
         FieldElement::from(999u64),
         "Existing file should be overwritten"
     );
     assert_ne!(
-        ledger.tree.root(),
+        ledger.root(),
         original_root,
         "Root should change after overwrite"
     );
@@ -247,11 +250,11 @@ fn test_batch_add_after_individual_adds() {
     ];
     ledger.add_files(&batch_files).unwrap();
 
-    assert_eq!(ledger.files.len(), 4, "Should have 4 files total");
-    assert!(ledger.files.contains_key("individual_1"));
-    assert!(ledger.files.contains_key("individual_2"));
-    assert!(ledger.files.contains_key("batch_1"));
-    assert!(ledger.files.contains_key("batch_2"));
+    assert_eq!(ledger.file_count(), 4, "Should have 4 files total");
+    assert!(ledger.entry("individual_1").is_some());
+    assert!(ledger.entry("individual_2").is_some());
+    assert!(ledger.entry("batch_1").is_some());
+    assert!(ledger.entry("batch_2").is_some());
 }
 
 #[test]
@@ -270,7 +273,7 @@ fn test_individual_add_after_batch_add() {
         .add_file(&dummy_metadata("individual_1", 300, 5))
         .unwrap();
 
-    assert_eq!(ledger.files.len(), 3, "Should have 3 files total");
+    assert_eq!(ledger.file_count(), 3, "Should have 3 files total");
 }
 
 // ===========================================
@@ -388,13 +391,13 @@ fn test_batch_add_large_batch() {
         .add_files(&files)
         .expect("Large batch should succeed");
 
-    assert_eq!(ledger.files.len(), 100);
+    assert_eq!(ledger.file_count(), 100);
 
-    // Verify ordering (BTreeMap sorts lexicographically)
-    let keys: Vec<_> = ledger.files.keys().collect();
-    assert_eq!(*keys[0], "file_000");
-    assert_eq!(*keys[50], "file_050");
-    assert_eq!(*keys[99], "file_099");
+    // Verify ordering (file IDs sort lexicographically)
+    let keys: Vec<_> = ledger.file_ids().collect();
+    assert_eq!(keys[0], "file_000");
+    assert_eq!(keys[50], "file_050");
+    assert_eq!(keys[99], "file_099");
 }
 
 #[test]
@@ -416,8 +419,8 @@ fn test_batch_vs_individual_large_dataset() {
     ledger_batch.add_files(&files).unwrap();
 
     // Verify identical results
-    assert_eq!(ledger_individual.tree.root(), ledger_batch.tree.root());
-    assert_eq!(ledger_individual.files.len(), ledger_batch.files.len());
+    assert_eq!(ledger_individual.root(), ledger_batch.root());
+    assert_eq!(ledger_individual.file_count(), ledger_batch.file_count());
     assert_eq!(ledger_individual.depth(), ledger_batch.depth());
 
     // Verify each file's lookup returns same values
@@ -447,7 +450,7 @@ fn test_batch_add_with_iter() {
     let mut ledger = FileLedger::new();
     ledger.add_files(files.iter()).unwrap();
 
-    assert_eq!(ledger.files.len(), 3);
+    assert_eq!(ledger.file_count(), 3);
 }
 
 #[test]
@@ -466,10 +469,10 @@ fn test_batch_add_with_filter() {
         .add_files(files.iter().filter(|m| m.depth() > 3))
         .unwrap();
 
-    assert_eq!(ledger.files.len(), 2);
-    assert!(ledger.files.contains_key("large_1"));
-    assert!(ledger.files.contains_key("large_2"));
-    assert!(!ledger.files.contains_key("small_1"));
+    assert_eq!(ledger.file_count(), 2);
+    assert!(ledger.entry("large_1").is_some());
+    assert!(ledger.entry("large_2").is_some());
+    assert!(ledger.entry("small_1").is_none());
 }
 
 // ===========================================
@@ -500,12 +503,12 @@ fn test_batch_add_with_real_files() {
     ledger_individual.add_file(&metadata3).unwrap();
 
     // Must produce identical results
-    assert_eq!(ledger_batch.tree.root(), ledger_individual.tree.root());
-    assert_eq!(ledger_batch.files.len(), 3);
+    assert_eq!(ledger_batch.root(), ledger_individual.root());
+    assert_eq!(ledger_batch.file_count(), 3);
 
     // Verify each file is correctly stored
     for metadata in &metadatas {
-        let entry = ledger_batch.files.get(&metadata.file_id).unwrap();
+        let entry = ledger_batch.entry(&metadata.file_id).unwrap();
         assert_eq!(entry.root, metadata.root);
         assert_eq!(entry.depth, metadata.depth());
     }
@@ -527,7 +530,7 @@ fn test_add_file_always_records_historical_root() {
         1,
         "First file should record one historical root"
     );
-    let root_after_first = ledger.tree.root();
+    let root_after_first = ledger.root();
 
     // Second file - records current root
     ledger.add_file(&dummy_metadata("file_2", 200, 3)).unwrap();
@@ -563,7 +566,7 @@ fn test_add_files_does_not_record_historical_roots() {
         0,
         "add_files should NOT record historical roots"
     );
-    assert_eq!(ledger.files.len(), 3);
+    assert_eq!(ledger.file_count(), 3);
 
     // Case 2: Batch add to non-empty ledger (still no historical root)
     let more_files = vec![
@@ -577,7 +580,7 @@ fn test_add_files_does_not_record_historical_roots() {
         0,
         "add_files should still NOT record historical roots even on non-empty ledger"
     );
-    assert_eq!(ledger.files.len(), 5);
+    assert_eq!(ledger.file_count(), 5);
 }
 
 #[test]
@@ -630,7 +633,7 @@ fn test_historical_roots_recorded_correctly() {
     assert_eq!(historical_root_total(&ledger), 3);
 
     // Test set_historical_roots to clear
-    ledger.set_historical_roots(vec![]);
+    ledger.set_historical_roots(vec![]).unwrap();
 
     assert_eq!(
         historical_root_total(&ledger),
@@ -652,8 +655,8 @@ fn test_add_file_atomicity_state_consistency() {
     // Add first file - records current root
     ledger.add_file(&dummy_metadata("file_1", 100, 3)).unwrap();
 
-    let files_before = ledger.files.len();
-    let root_before = ledger.tree.root();
+    let files_before = ledger.file_count();
+    let root_before = ledger.root();
     let historical_count_before = historical_root_total(&ledger);
 
     // Add second file - records current root
@@ -661,11 +664,11 @@ fn test_add_file_atomicity_state_consistency() {
 
     // State should be fully updated
     assert_eq!(
-        ledger.files.len(),
+        ledger.file_count(),
         files_before + 1,
         "File count should increase by 1"
     );
-    assert_ne!(ledger.tree.root(), root_before, "Tree root should change");
+    assert_ne!(ledger.root(), root_before, "Tree root should change");
     assert_eq!(
         historical_root_total(&ledger),
         historical_count_before + 1,
@@ -687,8 +690,8 @@ fn test_add_files_atomicity_state_consistency() {
     // Setup initial state
     ledger.add_file(&dummy_metadata("initial", 100, 3)).unwrap();
 
-    let files_before = ledger.files.len();
-    let root_before = ledger.tree.root();
+    let files_before = ledger.file_count();
+    let root_before = ledger.root();
     let historical_count_before = historical_root_total(&ledger);
 
     // Batch add
@@ -701,11 +704,11 @@ fn test_add_files_atomicity_state_consistency() {
 
     // State should be fully updated atomically
     assert_eq!(
-        ledger.files.len(),
+        ledger.file_count(),
         files_before + 3,
         "File count should increase by batch size"
     );
-    assert_ne!(ledger.tree.root(), root_before, "Tree root should change");
+    assert_ne!(ledger.root(), root_before, "Tree root should change");
     // add_files does NOT record historical roots
     assert_eq!(
         historical_root_total(&ledger),
@@ -722,7 +725,7 @@ fn test_empty_batch_does_not_record_historical_root() {
 
     ledger.add_file(&dummy_metadata("initial", 100, 3)).unwrap();
 
-    let root_before = ledger.tree.root();
+    let root_before = ledger.root();
     let historical_count_before = historical_root_total(&ledger);
 
     // Add empty batch - should be a complete no-op
@@ -731,7 +734,7 @@ fn test_empty_batch_does_not_record_historical_root() {
 
     // Root should not change
     assert_eq!(
-        ledger.tree.root(),
+        ledger.root(),
         root_before,
         "Root should not change for empty batch"
     );
@@ -753,21 +756,17 @@ fn test_empty_batch_is_true_noop() {
 
     // Case 1: Empty batch on empty ledger
     let mut ledger_empty = FileLedger::new();
-    let root_before_empty = ledger_empty.tree.root();
+    let root_before_empty = ledger_empty.root();
 
     let empty: Vec<api::FileMetadata> = vec![];
     ledger_empty.add_files(&empty).unwrap();
 
     assert_eq!(
-        ledger_empty.files.len(),
+        ledger_empty.file_count(),
         0,
         "Empty ledger should stay empty"
     );
-    assert_eq!(
-        ledger_empty.tree.root(),
-        root_before_empty,
-        "Root unchanged"
-    );
+    assert_eq!(ledger_empty.root(), root_before_empty, "Root unchanged");
     assert_eq!(
         historical_root_total(&ledger_empty),
         0,
@@ -779,8 +778,8 @@ fn test_empty_batch_is_true_noop() {
     ledger.add_file(&dummy_metadata("file_1", 100, 3)).unwrap();
     ledger.add_file(&dummy_metadata("file_2", 200, 3)).unwrap();
 
-    let files_before = ledger.files.len();
-    let root_before = ledger.tree.root();
+    let files_before = ledger.file_count();
+    let root_before = ledger.root();
     let historical_before = historical_root_total(&ledger);
 
     // Multiple empty batches should all be no-ops
@@ -788,8 +787,8 @@ fn test_empty_batch_is_true_noop() {
         ledger.add_files(&empty).unwrap();
     }
 
-    assert_eq!(ledger.files.len(), files_before, "File count unchanged");
-    assert_eq!(ledger.tree.root(), root_before, "Root unchanged");
+    assert_eq!(ledger.file_count(), files_before, "File count unchanged");
+    assert_eq!(ledger.root(), root_before, "Root unchanged");
     assert_eq!(
         historical_root_total(&ledger),
         historical_before,
@@ -805,7 +804,7 @@ fn test_add_file_and_batch_interleaved() {
 
     // Individual add - records historical root
     ledger.add_file(&dummy_metadata("ind_1", 100, 3)).unwrap();
-    assert_eq!(ledger.files.len(), 1);
+    assert_eq!(ledger.file_count(), 1);
     assert_eq!(historical_root_total(&ledger), 1);
 
     // Batch add - does NOT record historical root
@@ -815,23 +814,23 @@ fn test_add_file_and_batch_interleaved() {
             dummy_metadata("batch_2", 300, 3),
         ])
         .unwrap();
-    assert_eq!(ledger.files.len(), 3);
+    assert_eq!(ledger.file_count(), 3);
     assert_eq!(historical_root_total(&ledger), 1); // unchanged
 
     // Individual add - records historical root
     ledger.add_file(&dummy_metadata("ind_2", 400, 3)).unwrap();
-    assert_eq!(ledger.files.len(), 4);
+    assert_eq!(ledger.file_count(), 4);
     assert_eq!(historical_root_total(&ledger), 2);
 
     // Another batch - does NOT record historical root
     ledger
         .add_files(&[dummy_metadata("batch_3", 500, 3)])
         .unwrap();
-    assert_eq!(ledger.files.len(), 5);
+    assert_eq!(ledger.file_count(), 5);
     assert_eq!(historical_root_total(&ledger), 2); // unchanged
 
     // Current root is always valid
-    assert!(ledger.is_valid_root(ledger.tree.root()));
+    assert!(ledger.is_valid_root(ledger.root()));
 }
 
 #[test]
@@ -840,15 +839,15 @@ fn test_add_file_appends_historical_roots() {
     let mut ledger = FileLedger::new();
 
     ledger.add_file(&dummy_metadata("file_1", 100, 3)).unwrap();
-    let root_after_1 = ledger.tree.root();
+    let root_after_1 = ledger.root();
     assert_eq!(historical_root_total(&ledger), 1);
 
     ledger.add_file(&dummy_metadata("file_2", 200, 3)).unwrap();
-    let root_after_2 = ledger.tree.root();
+    let root_after_2 = ledger.root();
     assert_eq!(historical_root_total(&ledger), 2);
 
     ledger.add_file(&dummy_metadata("file_3", 300, 3)).unwrap();
-    let root_after_3 = ledger.tree.root();
+    let root_after_3 = ledger.root();
     assert_eq!(historical_root_total(&ledger), 3);
 
     // ALL roots should be valid
@@ -879,7 +878,7 @@ fn test_all_states_preserved_after_each_add() {
             .add_file(&dummy_metadata(&format!("file_{}", i), i as u64 * 100, 3))
             .unwrap();
 
-        captured_roots.push(ledger.tree.root());
+        captured_roots.push(ledger.root());
     }
 
     // We should have captured 5 roots (one after each add)
@@ -910,16 +909,16 @@ fn test_save_load_preserves_historical_roots_vec_format() {
 
     // Add files - each add_file records the current root
     ledger.add_file(&dummy_metadata("file_1", 100, 3)).unwrap();
-    let root_1 = ledger.tree.root();
+    let root_1 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_2", 200, 3)).unwrap();
-    let root_2 = ledger.tree.root();
+    let root_2 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_3", 300, 3)).unwrap();
-    let root_3 = ledger.tree.root();
+    let root_3 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_4", 400, 3)).unwrap();
-    let root_4 = ledger.tree.root();
+    let root_4 = ledger.root();
 
     // Verify initial state - 4 historical roots (one per add_file)
     assert_eq!(historical_root_total(&ledger), 4);
@@ -964,7 +963,7 @@ fn test_save_load_preserves_historical_roots_vec_format() {
     );
 
     // Current root should match
-    assert_eq!(loaded.tree.root(), ledger.tree.root());
+    assert_eq!(loaded.root(), ledger.root());
 }
 
 #[test]
@@ -973,23 +972,23 @@ fn test_historical_roots_accumulate() {
     let mut ledger = FileLedger::new();
 
     ledger.add_file(&dummy_metadata("file_1", 100, 3)).unwrap();
-    let root_1 = ledger.tree.root();
+    let root_1 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_2", 200, 3)).unwrap();
-    let root_2 = ledger.tree.root();
+    let root_2 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_3", 300, 3)).unwrap();
-    let root_3 = ledger.tree.root();
+    let root_3 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_4", 400, 3)).unwrap();
-    let root_4 = ledger.tree.root();
+    let root_4 = ledger.root();
 
     ledger.add_file(&dummy_metadata("file_5", 500, 3)).unwrap();
-    let root_5 = ledger.tree.root();
+    let root_5 = ledger.root();
 
     // We should have 5 historical roots (one per add_file)
     assert_eq!(historical_root_total(&ledger), 5);
-    assert_eq!(ledger.historical_roots.len(), 5);
+    assert_eq!(ledger.historical_roots().len(), 5);
 
     // All roots should be valid
     assert!(ledger.is_valid_root(root_1), "root_1 should be valid");
@@ -999,7 +998,7 @@ fn test_historical_roots_accumulate() {
     assert!(ledger.is_valid_root(root_5), "root_5 should be valid");
 
     // Test clearing with set_historical_roots
-    ledger.set_historical_roots(vec![]);
+    ledger.set_historical_roots(vec![]).unwrap();
 
     assert_eq!(
         historical_root_total(&ledger),
@@ -0,0 +1,262 @@
+//! Tests for fixed-capacity ledgers and aggregated-depth stability
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    ledger::FileLedger,
+    params, poseidon, KontorPoRError,
+};
+use std::sync::Mutex;
+
+/// Serializes tests that count parameter generation, which is process-wide.
+static PARAMS_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_fixed_capacity_depth_is_constant() {
+    println!("Testing that a fixed-capacity ledger keeps a constant aggregated depth");
+
+    let mut ledger = FileLedger::with_capacity(8);
+    assert_eq!(ledger.capacity(), Some(8));
+    assert_eq!(ledger.depth(), 3, "Empty ledger should already be padded");
+
+    for i in 0..8u8 {
+        let data = [i + 1; 64];
+        let (_prepared, metadata) = api::prepare_file(&data, "cap.dat").unwrap();
+        ledger.add_file(&metadata).unwrap();
+        assert_eq!(
+            ledger.depth(),
+            3,
+            "Depth must not change as files are added"
+        );
+        assert_eq!(ledger.remaining_capacity(), Some(7 - i as usize));
+    }
+
+    println!("✓ Aggregated depth stays fixed at capacity");
+}
+
+#[test]
+fn test_fixed_capacity_rejects_overflow() {
+    println!("Testing that add_file returns LedgerFull once capacity is reached");
+
+    let (_p1, metadata1) = api::prepare_file(b"first file", "a.dat").unwrap();
+    let (_p2, metadata2) = api::prepare_file(b"second file", "b.dat").unwrap();
+    let (_p3, metadata3) = api::prepare_file(b"third file", "c.dat").unwrap();
+
+    let mut ledger = FileLedger::with_capacity(2);
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+    let root_before = ledger.root();
+
+    let result = ledger.add_file(&metadata3);
+    assert!(
+        matches!(result, Err(KontorPoRError::LedgerFull { capacity: 2 })),
+        "Expected LedgerFull, got {:?}",
+        result
+    );
+    assert_eq!(ledger.root(), root_before, "Rejected add must not mutate");
+
+    // Re-adding an existing file does not consume capacity
+    ledger
+        .add_file(&metadata1)
+        .expect("Re-adding an existing file should be allowed at capacity");
+
+    // Batch adds are rejected atomically
+    let mut batch_ledger = FileLedger::with_capacity(2);
+    let result = batch_ledger.add_files(&[metadata1, metadata2, metadata3]);
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::LedgerFull { capacity: 2 })
+    ));
+    assert!(
+        batch_ledger.file_count() == 0,
+        "Batch must be all-or-nothing"
+    );
+
+    println!("✓ Capacity overflow rejected without mutating the ledger");
+}
+
+#[test]
+fn test_fixed_capacity_root_is_order_independent() {
+    println!("Testing that insertion order does not affect a fixed-capacity root");
+
+    let metadatas: Vec<_> = (0..3u8)
+        .map(|i| api::prepare_file(&[i + 10; 80], "order.dat").unwrap().1)
+        .collect();
+
+    let mut forward = FileLedger::with_capacity(16);
+    for metadata in &metadatas {
+        forward.add_file(metadata).unwrap();
+    }
+
+    let mut backward = FileLedger::with_capacity(16);
+    for metadata in metadatas.iter().rev() {
+        backward.add_file(metadata).unwrap();
+    }
+
+    assert_eq!(forward.root(), backward.root());
+    assert_eq!(forward.depth(), 4);
+
+    println!("✓ Root is canonical under different insertion orders");
+}
+
+#[test]
+fn test_fixed_capacity_save_load_roundtrip() {
+    let (_p1, metadata1) = api::prepare_file(b"persisted", "p.dat").unwrap();
+
+    let mut ledger = FileLedger::with_capacity(4);
+    ledger.add_file(&metadata1).unwrap();
+
+    let path = std::env::temp_dir().join("kontor_ledger_capacity_roundtrip.bin");
+    ledger.save(&path).unwrap();
+    let loaded = FileLedger::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(loaded.capacity(), Some(4));
+    assert_eq!(loaded.remaining_capacity(), Some(3));
+    assert_eq!(loaded.root(), ledger.root());
+    assert_eq!(loaded.depth(), 2);
+}
+
+#[test]
+fn test_params_survive_ledger_growth_with_fixed_capacity() {
+    println!("Testing that params generated once keep working as files are added");
+    let _guard = PARAMS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let prepared: Vec<_> = (0..4u8)
+        .map(|i| api::prepare_file(&[i + 1; 100], "grow.dat").unwrap())
+        .collect();
+
+    let mut ledger = FileLedger::with_capacity(8);
+    ledger.add_file(&prepared[0].1).unwrap();
+    ledger.add_file(&prepared[1].1).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(prepared[0].1.clone(), 1000, 2, FieldElement::from(7u64)),
+        Challenge::new_test(prepared[1].1.clone(), 1000, 2, FieldElement::from(7u64)),
+    ];
+    let files = vec![&prepared[0].0, &prepared[1].0];
+
+    let proof_before = PorSystem::new(&ledger)
+        .prove(files.clone(), &challenges)
+        .unwrap();
+    let depth_before = proof_before.shape.aggregated_tree_depth;
+    let cache_size_before = params::memory_cache_size();
+
+    // Grow the ledger past the point where a growing ledger would deepen its tree
+    ledger.add_file(&prepared[2].1).unwrap();
+    ledger.add_file(&prepared[3].1).unwrap();
+    assert_eq!(ledger.depth(), depth_before);
+
+    let system = PorSystem::new(&ledger);
+    let proof_after = system.prove(files, &challenges).unwrap();
+    assert_eq!(
+        proof_after.shape.aggregated_tree_depth, depth_before,
+        "Shape must be unchanged after growth"
+    );
+    assert!(
+        params::memory_cache_size() <= cache_size_before,
+        "No new parameter set should be generated for the grown ledger"
+    );
+
+    assert!(system.verify(&proof_after, &challenges).unwrap());
+    assert!(
+        system.verify(&proof_before, &challenges).unwrap(),
+        "Proof against an earlier root must still verify"
+    );
+
+    // Contrast: a growing ledger changes depth (and therefore shape) as files are added
+    let mut growing = FileLedger::new();
+    growing.add_file(&prepared[0].1).unwrap();
+    growing.add_file(&prepared[1].1).unwrap();
+    let growing_depth_before = growing.depth();
+    growing.add_file(&prepared[2].1).unwrap();
+    assert_ne!(growing.depth(), growing_depth_before);
+
+    println!("✓ Fixed-capacity ledger keeps parameters valid across growth");
+}
+
+#[test]
+fn test_proof_survives_growth_in_constant_depth_ledger() {
+    println!("Testing that a proof made with 3 files verifies after 50 more are added");
+    let _guard = PARAMS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let prepared: Vec<_> = (0..53u8)
+        .map(|i| api::prepare_file(&[i + 1; 40], &format!("epoch_{}.dat", i)).unwrap())
+        .collect();
+
+    let mut ledger = FileLedger::new_with_depth(6);
+    assert_eq!(ledger.capacity(), Some(64));
+    assert_eq!(ledger.depth(), 6);
+    for (_, metadata) in &prepared[..3] {
+        ledger.add_file(metadata).unwrap();
+    }
+
+    let seed = FieldElement::from(13u64);
+    let challenges: Vec<_> = prepared[..3]
+        .iter()
+        .map(|(_, metadata)| Challenge::new_test(metadata.clone(), 1000, 2, seed))
+        .collect();
+    let files: Vec<_> = prepared[..3].iter().map(|(p, _)| p).collect();
+    let proof = PorSystem::new(&ledger)
+        .prove(files.clone(), &challenges)
+        .unwrap();
+    assert_eq!(proof.shape.aggregated_tree_depth, 6);
+    let generated_before = params::generated_count();
+
+    for (_, metadata) in &prepared[3..] {
+        ledger.add_file(metadata).unwrap();
+    }
+    assert_eq!(ledger.depth(), 6);
+    assert_ne!(ledger.root(), proof.ledger_root);
+
+    let system = PorSystem::new(&ledger);
+    assert!(
+        system.verify(&proof, &challenges).unwrap(),
+        "Proof from the 3-file ledger must verify after growth"
+    );
+    let fresh = system.prove(files, &challenges).unwrap();
+    assert!(system.verify(&fresh, &challenges).unwrap());
+    assert_eq!(
+        params::generated_count(),
+        generated_before,
+        "No parameters may be regenerated as the ledger grows"
+    );
+
+    println!("✓ Constant-depth ledger keeps proofs and parameters valid");
+}
+
+#[test]
+fn test_empty_slot_leaf_is_domain_separated() {
+    let empty = poseidon::empty_ledger_slot();
+    assert_ne!(empty, FieldElement::ZERO);
+    assert_ne!(
+        empty,
+        poseidon::calculate_root_commitment(FieldElement::ZERO, FieldElement::ZERO)
+    );
+    assert_ne!(
+        empty,
+        poseidon::calculate_root_commitment_arity4(FieldElement::ZERO, FieldElement::ZERO)
+    );
+
+    // The empty file's commitment (zero root, depth 0) differs from an empty slot
+    let (_, empty_file) = api::prepare_file(b"", "empty.dat").unwrap();
+    let mut ledger = FileLedger::new_with_depth(2);
+    ledger.add_file(&empty_file).unwrap();
+    let (index, rc) = ledger.lookup(&empty_file.file_id).unwrap();
+    assert_ne!(rc, empty);
+
+    // Unused slots hold the empty leaf and never resolve to a file
+    let leaves = &ledger.leaves();
+    assert_eq!(leaves.len(), 4);
+    assert_eq!(leaves[index], rc);
+    assert!(leaves
+        .iter()
+        .enumerate()
+        .all(|(i, leaf)| i == index || *leaf == empty));
+    assert_eq!(ledger.get_canonical_index_for_rc(empty), None);
+
+    // An empty constant-depth ledger is all empty slots, not zeros
+    let fresh = FileLedger::new_with_depth(3);
+    assert!(fresh.leaves().iter().all(|leaf| *leaf == empty));
+    assert_ne!(fresh.root(), FileLedger::new().root());
+}
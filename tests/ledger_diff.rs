@@ -0,0 +1,186 @@
+//! Tests for syncing ledgers with `FileLedger::diff` and `apply_diff`
+//!
+//! This module tests that:
+//! 1. A follower sharing a base with a diverged leader converges on the leader's root
+//!    after applying the diff, and a proof made against the leader verifies against it
+//! 2. Diffs list removed, added and changed files, survive JSON, and are empty for equal ledgers
+//! 3. Applying a diff rebuilds once and records one historical root
+//! 4. Diffs that do not match the ledger are rejected and leave it unchanged
+
+use kontor_crypto::api::{
+    self, Challenge, ErasureCode, FieldElement, FileMetadata, PorSystem, TreeArity,
+};
+use kontor_crypto::{FileLedger, KontorPoRError, LedgerDiff};
+
+fn dummy_metadata(file_id: &str, root_val: u64, depth: usize) -> FileMetadata {
+    FileMetadata {
+        root: FieldElement::from(root_val),
+        file_id: file_id.to_string(),
+        padded_len: 1 << depth,
+        original_size: 100,
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
+    }
+}
+
+/// A leader and follower that share `base_a`, `base_b` and `base_c`, after which the
+/// leader drops `base_a`, replaces `base_b` and adds `extra`.
+fn diverged_pair() -> (FileLedger, FileLedger) {
+    let base = [
+        dummy_metadata("base_a", 1, 3),
+        dummy_metadata("base_b", 2, 4),
+        dummy_metadata("base_c", 3, 5),
+    ];
+    let mut leader = FileLedger::new();
+    leader.add_files(&base).unwrap();
+    let follower = leader.clone();
+
+    leader.remove_file("base_a").unwrap();
+    leader.add_file(&dummy_metadata("base_b", 20, 6)).unwrap();
+    leader.add_file(&dummy_metadata("extra", 4, 3)).unwrap();
+    (leader, follower)
+}
+
+#[test]
+fn test_follower_converges_and_verifies_leader_proof() {
+    println!("Testing follower sync from a diverged leader");
+
+    let (prepared_a, meta_a) = api::prepare_file(b"shared base file", "a.dat").unwrap();
+    let (prepared_b, meta_b) = api::prepare_file(b"file only the leader has", "b.dat").unwrap();
+    let (_, meta_c) = api::prepare_file(b"file the leader removes", "c.dat").unwrap();
+
+    let mut leader = FileLedger::new();
+    leader.add_file(&meta_a).unwrap();
+    leader.add_file(&meta_c).unwrap();
+    let mut follower = leader.clone();
+
+    // Both sides diverge; the follower's own additions are undone by the sync
+    leader.remove_file(&meta_c.file_id).unwrap();
+    leader.add_file(&meta_b).unwrap();
+    follower
+        .add_file(&dummy_metadata("follower_only", 9, 4))
+        .unwrap();
+    assert_ne!(follower.root(), leader.root());
+
+    let diff = follower.diff(&leader);
+    follower.apply_diff(&diff).unwrap();
+    assert_eq!(follower.root(), leader.root());
+    assert_eq!(follower.depth(), leader.depth());
+    for file_id in [&meta_a.file_id, &meta_b.file_id] {
+        assert_eq!(follower.lookup(file_id), leader.lookup(file_id));
+    }
+    assert!(follower.diff(&leader).is_empty());
+
+    let challenges = vec![
+        Challenge::new_test(meta_a.clone(), 1000, 2, FieldElement::from(11u64)),
+        Challenge::new_test(meta_b.clone(), 1000, 2, FieldElement::from(11u64)),
+    ];
+    let proof = PorSystem::new(&leader)
+        .prove(vec![&prepared_a, &prepared_b], &challenges)
+        .unwrap();
+    assert!(PorSystem::new(&follower)
+        .verify(&proof, &challenges)
+        .unwrap());
+
+    println!("✓ Follower root matches the leader and accepts its proof");
+}
+
+#[test]
+fn test_diff_contents_and_serialization() {
+    let (leader, follower) = diverged_pair();
+
+    let diff = follower.diff(&leader);
+    assert_eq!(diff.removed, ["base_a"]);
+    assert_eq!(diff.added.keys().collect::<Vec<_>>(), ["extra"]);
+    assert_eq!(diff.changed.keys().collect::<Vec<_>>(), ["base_b"]);
+    assert_eq!(&diff.changed["base_b"], leader.entry("base_b").unwrap());
+
+    // The reverse diff undoes the changes
+    let reverse = leader.diff(&follower);
+    assert_eq!(reverse.removed, ["extra"]);
+    assert_eq!(reverse.added.keys().collect::<Vec<_>>(), ["base_a"]);
+    assert_eq!(
+        &reverse.changed["base_b"],
+        follower.entry("base_b").unwrap()
+    );
+
+    let json = serde_json::to_string(&diff).unwrap();
+    let decoded: LedgerDiff = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, diff);
+    let mut synced = follower.clone();
+    synced.apply_diff(&decoded).unwrap();
+    assert_eq!(synced.root(), leader.root());
+
+    // Renames alone do not show up in a diff
+    let mut renamed = leader.clone();
+    renamed.rename_file("extra", "new-name.dat").unwrap();
+    assert!(leader.diff(&renamed).is_empty());
+    assert!(leader.diff(&leader).is_empty());
+}
+
+#[test]
+fn test_apply_records_one_historical_root() {
+    let (leader, mut follower) = diverged_pair();
+    let recorded_before = follower.historical_roots().len();
+
+    follower.apply_diff(&follower.diff(&leader)).unwrap();
+    assert_eq!(follower.historical_roots().len(), recorded_before + 1);
+    assert_eq!(
+        follower.historical_roots().last(),
+        leader.historical_roots().last()
+    );
+}
+
+#[test]
+fn test_mismatched_diffs_rejected() {
+    let (leader, follower) = diverged_pair();
+    let diff = follower.diff(&leader);
+
+    // Already applied: the added file is present and the removed one is gone
+    let mut synced = follower.clone();
+    synced.apply_diff(&diff).unwrap();
+    let root = synced.root();
+    let recorded = synced.historical_roots().len();
+    assert!(matches!(
+        synced.apply_diff(&diff),
+        Err(KontorPoRError::FileNotFound { file_id }) if file_id == "base_a"
+    ));
+    let mut re_add = diff.clone();
+    re_add.removed.clear();
+    re_add.changed.clear();
+    assert!(matches!(
+        synced.apply_diff(&re_add),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert_eq!(synced.root(), root);
+    assert_eq!(synced.historical_roots().len(), recorded);
+
+    let mut duplicated = diff.clone();
+    duplicated.removed.push("extra".to_string());
+    let mut target = follower.clone();
+    assert!(matches!(
+        target.apply_diff(&duplicated),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert_eq!(target.root(), follower.root());
+
+    // At capacity, a removal makes room for an addition but an addition alone does not fit
+    let mut full = FileLedger::with_capacity(3);
+    full.add_files(&[
+        dummy_metadata("base_a", 1, 3),
+        dummy_metadata("base_b", 2, 4),
+        dummy_metadata("base_c", 3, 5),
+    ])
+    .unwrap();
+    let mut add_only = diff.clone();
+    add_only.removed.clear();
+    assert!(matches!(
+        full.apply_diff(&add_only),
+        Err(KontorPoRError::LedgerFull { capacity: 3 })
+    ));
+    full.apply_diff(&diff).unwrap();
+    assert_eq!(full.file_count(), 3);
+}
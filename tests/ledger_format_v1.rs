@@ -0,0 +1,116 @@
+//! Tests for loading ledgers saved in format version 1 (the 0.1 releases)
+//!
+//! This module tests that:
+//! 1. The committed version 1 ledger loads with its entries, root and historical roots,
+//!    as a growing, canonically indexed ledger with empty filenames
+//! 2. The loaded ledger saves in the current format and reloads unchanged
+//! 3. A truncated version 1 ledger is rejected
+//!
+//! `tests/data/ledger_v1.bin` was saved by `FileLedger::save` at format version 1 with
+//! three files, `alpha.dat`, `beta.dat` and `gamma.dat`, each holding
+//! `format!("ledger format v1 fixture: {name}").repeat(20)`. It must not be regenerated.
+
+use ff::PrimeField;
+use kontor_crypto::{config, FileLedger, IndexPolicy, KontorPoRError};
+use std::path::PathBuf;
+
+const FIXTURE: &[u8] = include_bytes!("data/ledger_v1.bin");
+
+/// (file ID, file root) of each fixture file
+const FILES: [(&str, &str); 3] = [
+    (
+        "ffceabf3ea7d6ab9bf86612e681750c8075d7798de368b93f1fd98798cec780d",
+        "7cc0bbc3620493a75d38372df85d73e49761779fd2475f6de228abeea31e8a18",
+    ),
+    (
+        "a6738a2d1e9d78aad0009c81f29b005677fa5425558523d3ec79d6ab80892b32",
+        "c602e857537514a6de2de7fc52efc75d32fadc7855330c369010f441560c7a2d",
+    ),
+    (
+        "68a3bfbb194e3ecb119f16b9b9db5297095f6624e8b7e34cd641a1c8193c11b9",
+        "e40e99c1c6ae3ef85eaf17a6bbcf7c63d34765195e8bf25a80d0f273e4f46f05",
+    ),
+];
+
+const ROOT: &str = "e44892623b51ca702a5ca5780aff500465b2ab944ee601928308f2c161252939";
+
+const HISTORICAL_ROOTS: [&str; 3] = [
+    "a0530f8f869e6170ae279ea93b0952fbd2846cc5ca9173d04d886be269af4b39",
+    "8a830cf923c6845bac79283eb49f52d5e50db8c16051ed9e6c377d662ad7f221",
+    ROOT,
+];
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("kontor_{}_{}.bin", name, std::process::id()))
+}
+
+fn load_bytes(name: &str, bytes: &[u8]) -> Result<FileLedger, KontorPoRError> {
+    let path = temp_path(name);
+    std::fs::write(&path, bytes).unwrap();
+    let result = FileLedger::load(&path);
+    std::fs::remove_file(&path).ok();
+    result
+}
+
+#[test]
+fn test_v1_ledger_loads() {
+    println!("Testing that a format version 1 ledger loads");
+
+    assert_eq!(u16::from_le_bytes([FIXTURE[0], FIXTURE[1]]), 1);
+    let ledger = load_bytes("ledger_v1_load", FIXTURE).unwrap();
+
+    assert_eq!(ledger.file_count(), FILES.len());
+    for (file_id, root) in FILES {
+        let entry = ledger.entry(file_id).unwrap();
+        assert_eq!(to_hex(entry.root.to_repr().as_ref()), root);
+        assert!(entry.filename.is_empty());
+    }
+    assert_eq!(to_hex(ledger.root().to_repr().as_ref()), ROOT);
+
+    let historical: Vec<String> = ledger
+        .historical_roots()
+        .iter()
+        .map(|r| to_hex(r))
+        .collect();
+    assert_eq!(historical, HISTORICAL_ROOTS);
+
+    assert_eq!(ledger.capacity(), None);
+    assert_eq!(ledger.index_policy(), IndexPolicy::Canonical);
+    assert!(ledger.migrated_roots().is_empty());
+    assert!(!ledger.tracks_root_heights());
+    assert!(ledger.check_consistency().unwrap().is_consistent());
+}
+
+#[test]
+fn test_v1_ledger_resaves_in_current_format() {
+    println!("Testing that a loaded version 1 ledger saves in the current format");
+
+    let ledger = load_bytes("ledger_v1_resave_src", FIXTURE).unwrap();
+    let path = temp_path("ledger_v1_resave");
+    ledger.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    let reloaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(
+        u16::from_le_bytes([saved[0], saved[1]]),
+        config::LEDGER_FORMAT_VERSION
+    );
+    assert_eq!(reloaded.root(), ledger.root());
+    assert_eq!(reloaded.historical_roots(), ledger.historical_roots());
+    for (file_id, _) in FILES {
+        assert_eq!(reloaded.entry(file_id), ledger.entry(file_id));
+    }
+}
+
+#[test]
+fn test_truncated_v1_ledger_is_rejected() {
+    println!("Testing that a truncated version 1 ledger is rejected");
+
+    let truncated = &FIXTURE[..FIXTURE.len() - 1];
+    assert!(load_bytes("ledger_v1_truncated", truncated).is_err());
+}
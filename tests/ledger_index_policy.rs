@@ -0,0 +1,199 @@
+//! Tests for ledger index policies
+//!
+//! This module tests that:
+//! 1. Insertion-order ledgers assign indices at add time, independent of file ID order, and
+//!    keep them as more files are added
+//! 2. Removed files leave tombstoned slots that are not reused, and the slots survive a
+//!    save/load round trip
+//! 3. Proofs against an insertion-order ledger use the stable indices and verify across
+//!    additions
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, FileMetadata, PorSystem},
+    verify_aggregation_proof, verify_membership_certificate, FileLedger, IndexPolicy,
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_files;
+
+/// Metadatas sorted by file ID, descending, so adding them in order is never alphabetical.
+fn reverse_sorted(mut metadatas: Vec<FileMetadata>) -> Vec<FileMetadata> {
+    metadatas.sort_by(|a, b| b.file_id.cmp(&a.file_id));
+    metadatas
+}
+
+fn assert_index(ledger: &FileLedger, metadata: &FileMetadata, index: usize) {
+    let (found, rc) = ledger.lookup(&metadata.file_id).unwrap();
+    assert_eq!(found, index, "index of {}", metadata.file_id);
+    assert_eq!(ledger.get_canonical_index_for_rc(rc), Some(index));
+
+    let proof = ledger.get_aggregation_proof(&metadata.file_id).unwrap();
+    assert!(verify_aggregation_proof(ledger.root(), &proof, rc));
+    let certificate = ledger.membership_certificate(&metadata.file_id).unwrap();
+    assert_eq!(certificate.index, index);
+    assert!(verify_membership_certificate(&certificate));
+}
+
+#[test]
+fn test_insertion_order_indices_are_stable() {
+    println!("Testing insertion-order index assignment");
+
+    let (_, metadatas) = create_test_files(6, 100, 400);
+    let metadatas = reverse_sorted(metadatas);
+
+    let mut ledger = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    let mut canonical = FileLedger::new();
+    assert_eq!(ledger.index_policy(), IndexPolicy::InsertionOrder);
+    assert_eq!(canonical.index_policy(), IndexPolicy::Canonical);
+    for metadata in &metadatas[..3] {
+        ledger.add_file(metadata).unwrap();
+        canonical.add_file(metadata).unwrap();
+    }
+    for (index, metadata) in metadatas[..3].iter().enumerate() {
+        assert_index(&ledger, metadata, index);
+        // The canonical ledger orders the same files by ID, i.e. in reverse
+        assert_eq!(canonical.lookup(&metadata.file_id).unwrap().0, 2 - index);
+    }
+    assert_ne!(ledger.root(), canonical.root());
+
+    // Re-adding an existing file keeps its slot
+    ledger.add_file(&metadatas[1]).unwrap();
+    assert_index(&ledger, &metadatas[1], 1);
+
+    // Later additions (single and batched) take the next slots in order
+    ledger.add_file(&metadatas[3]).unwrap();
+    ledger.add_files(&metadatas[4..]).unwrap();
+    for (index, metadata) in metadatas.iter().enumerate() {
+        assert_index(&ledger, metadata, index);
+    }
+
+    let description = ledger.describe();
+    assert_eq!(description.index_policy, IndexPolicy::InsertionOrder);
+    let listed: Vec<(usize, &str)> = description
+        .files
+        .iter()
+        .map(|f| (f.index, f.file_id.as_str()))
+        .collect();
+    let expected: Vec<(usize, &str)> = metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (i, m.file_id.as_str()))
+        .collect();
+    assert_eq!(listed, expected);
+    assert!(ledger.check_consistency().unwrap().is_consistent());
+
+    println!("✓ Indices follow insertion order and never shift");
+}
+
+#[test]
+fn test_removed_slots_are_tombstoned() {
+    println!("Testing tombstoned slots");
+
+    let (_, metadatas) = create_test_files(4, 100, 410);
+    let metadatas = reverse_sorted(metadatas);
+    let mut ledger = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    ledger.add_files(&metadatas[..3]).unwrap();
+
+    let removed = ledger.remove_file(&metadatas[1].file_id).unwrap();
+    assert_eq!(removed.root, metadatas[1].root);
+    assert!(ledger.lookup(&metadatas[1].file_id).is_none());
+    assert_eq!(ledger.leaves()[1], FieldElement::from(0u64));
+    assert_index(&ledger, &metadatas[0], 0);
+    assert_index(&ledger, &metadatas[2], 2);
+    assert_eq!(
+        ledger.historical_roots().len(),
+        1,
+        "removal records the new root"
+    );
+
+    // The tombstone is not reused, not even by the removed file
+    ledger.add_file(&metadatas[3]).unwrap();
+    ledger.add_file(&metadatas[1]).unwrap();
+    assert_index(&ledger, &metadatas[3], 3);
+    assert_index(&ledger, &metadatas[1], 4);
+    assert_eq!(ledger.describe().files.len(), 4);
+
+    assert!(matches!(
+        ledger.remove_file("missing"),
+        Err(KontorPoRError::FileNotFound { .. })
+    ));
+
+    // Canonical ledgers shift later files down instead
+    let mut canonical = FileLedger::new();
+    canonical.add_files(&metadatas[..3]).unwrap();
+    canonical.remove_file(&metadatas[1].file_id).unwrap();
+    assert_eq!(canonical.lookup(&metadatas[0].file_id).unwrap().0, 1);
+
+    let path = std::env::temp_dir().join(format!(
+        "kontor_ledger_index_policy_{}.bin",
+        std::process::id()
+    ));
+    ledger.save(&path).unwrap();
+    let loaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.index_policy(), IndexPolicy::InsertionOrder);
+    assert_eq!(loaded.root(), ledger.root());
+    for metadata in &metadatas {
+        assert_eq!(
+            loaded.lookup(&metadata.file_id),
+            ledger.lookup(&metadata.file_id)
+        );
+    }
+
+    println!("✓ Tombstones keep other indices fixed and survive save/load");
+}
+
+#[test]
+fn test_proofs_verify_across_additions() {
+    println!("Testing proofs against an insertion-order ledger");
+
+    let (files, metadatas) = create_test_files(3, 200, 420);
+    let metadatas = reverse_sorted(metadatas);
+    let mut ledger = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    ledger.add_file(&metadatas[0]).unwrap();
+    ledger.add_file(&metadatas[2]).unwrap();
+
+    let seed = FieldElement::from(21u64);
+    let challenge = |m: &FileMetadata| Challenge::new_test(m.clone(), 1000, 2, seed);
+    let single = vec![challenge(&metadatas[2])];
+    let pair = vec![challenge(&metadatas[0]), challenge(&metadatas[2])];
+    let (single_proof, pair_proof) = {
+        let system = PorSystem::new(&ledger);
+        let single_proof = system
+            .prove(vec![&files[&metadatas[2].file_id]], &single)
+            .unwrap();
+        let pair_files = vec![&files[&metadatas[0].file_id], &files[&metadatas[2].file_id]];
+        let pair_proof = system.prove(pair_files, &pair).unwrap();
+        assert!(system.verify(&single_proof, &single).unwrap());
+        assert!(system.verify(&pair_proof, &pair).unwrap());
+        (single_proof, pair_proof)
+    };
+
+    // Add a file whose ID sorts between the two. Under the canonical policy it would take
+    // index 1 and push the first file to index 2; here both keep their slots
+    ledger.add_file(&metadatas[1]).unwrap();
+    assert_eq!(ledger.lookup(&metadatas[0].file_id).unwrap().0, 0);
+    assert_eq!(ledger.lookup(&metadatas[2].file_id).unwrap().0, 1);
+    assert_eq!(ledger.lookup(&metadatas[1].file_id).unwrap().0, 2);
+
+    // The earlier multi-file proof is pinned to the old root through the historical roots
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&pair_proof, &pair).unwrap());
+
+    // Single-file proofs do not involve the ledger root at all
+    ledger.set_historical_roots(Vec::new()).unwrap();
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&single_proof, &single).unwrap());
+
+    let all: Vec<Challenge> = metadatas.iter().map(challenge).collect();
+    let proof = system.prove(files.values().collect(), &all).unwrap();
+    assert!(system.verify(&proof, &all).unwrap());
+    for metadata in &metadatas {
+        let (index, _) = ledger.lookup(&metadata.file_id).unwrap();
+        assert!(proof.ledger_indices.contains(&index));
+    }
+    assert!(proof.ledger_indices.contains(&2));
+
+    println!("✓ Proofs use the stable indices and verify after additions");
+}
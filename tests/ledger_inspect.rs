@@ -0,0 +1,119 @@
+//! Tests for ledger inspection (`FileLedger::describe` and `check_consistency`)
+//!
+//! This module tests that:
+//! 1. The description lists every file in canonical order with hex roots, rc values and filenames
+//! 2. Filenames survive save/load and the description round-trips through JSON
+//! 3. Consistency checking accepts a loaded ledger and flags entries whose rc was altered
+
+use kontor_crypto::{
+    api::{self, TreeArity},
+    merkle::canonical::field_to_hex,
+    FileLedger, KontorPoRError, LedgerDescription,
+};
+
+fn mixed_arity_ledger() -> (FileLedger, Vec<api::FileMetadata>) {
+    let (_, binary) = api::prepare_file(b"inspect binary file", "binary.dat").unwrap();
+    let (_, arity4) =
+        api::prepare_file_with_arity(b"inspect arity-4 file", "quad.dat", TreeArity::Arity4)
+            .unwrap();
+    let (_, third) = api::prepare_file(b"inspect third file", "third.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    for metadata in [&binary, &arity4, &third] {
+        ledger.add_file(metadata).unwrap();
+    }
+    (ledger, vec![binary, arity4, third])
+}
+
+#[test]
+fn test_describe_lists_files_in_canonical_order() {
+    println!("Testing FileLedger::describe contents");
+
+    let (ledger, metadatas) = mixed_arity_ledger();
+    let description = ledger.describe();
+
+    assert_eq!(description.root, field_to_hex(&ledger.root()));
+    assert_eq!(description.depth, ledger.depth());
+    assert_eq!(description.file_count, 3);
+    assert_eq!(description.files.len(), 3);
+    assert_eq!(description.capacity, None);
+    assert_eq!(
+        description.historical_roots.len(),
+        ledger.historical_roots().len()
+    );
+    assert_eq!(
+        description.historical_roots.last().unwrap(),
+        &description.root
+    );
+
+    for file in &description.files {
+        let metadata = metadatas
+            .iter()
+            .find(|m| m.file_id == file.file_id)
+            .unwrap();
+        let (index, rc) = ledger.lookup(&file.file_id).unwrap();
+        assert_eq!(file.index, index);
+        assert_eq!(file.rc, field_to_hex(&rc));
+        assert_eq!(file.root, field_to_hex(&metadata.root));
+        assert_eq!(file.depth, metadata.depth());
+        assert_eq!(file.filename, metadata.filename());
+        assert_eq!(file.tree_arity, Some(metadata.tree_arity));
+        assert_eq!(file.root.len(), 64);
+    }
+    let indices: Vec<usize> = description.files.iter().map(|f| f.index).collect();
+    assert_eq!(indices, vec![0, 1, 2]);
+
+    println!("✓ Description matches ledger contents");
+}
+
+#[test]
+fn test_description_survives_save_load_and_json() {
+    let (ledger, _) = mixed_arity_ledger();
+    let path =
+        std::env::temp_dir().join(format!("kontor_ledger_inspect_{}.bin", std::process::id()));
+    ledger.save(&path).unwrap();
+    let loaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    let description = loaded.describe();
+    assert_eq!(description, ledger.describe());
+
+    let json = serde_json::to_string_pretty(&description).unwrap();
+    assert!(json.contains("quad.dat"));
+    let parsed: LedgerDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, description);
+}
+
+#[test]
+fn test_check_consistency_detects_altered_entries() {
+    println!("Testing ledger consistency checking");
+
+    let (mut ledger, _) = mixed_arity_ledger();
+    assert!(ledger.check_consistency().unwrap().is_consistent());
+
+    // An rc that no longer commits to its root and depth
+    let file_id = ledger.file_ids().next().unwrap().to_owned();
+    let original = ledger.entry(&file_id).unwrap().clone();
+    ledger.entry_mut_unchecked(&file_id).unwrap().rc = original.root;
+    assert!(matches!(
+        ledger.check_consistency().unwrap().into_result(),
+        Err(KontorPoRError::LedgerValidation { reason }) if reason.contains(&file_id)
+    ));
+    assert_eq!(ledger.describe().files[0].tree_arity, None);
+
+    // A consistent entry whose rc differs from the one the tree was built from
+    let (_, other) = api::prepare_file(b"some other file", "other.dat").unwrap();
+    let entry = ledger.entry_mut_unchecked(&file_id).unwrap();
+    entry.root = other.root;
+    entry.depth = other.depth();
+    entry.rc = TreeArity::Binary.root_commitment(other.root, other.depth());
+    assert!(matches!(
+        ledger.check_consistency().unwrap().into_result(),
+        Err(KontorPoRError::LedgerValidation { .. })
+    ));
+
+    *ledger.entry_mut_unchecked(&file_id).unwrap() = original;
+    assert!(ledger.check_consistency().unwrap().is_consistent());
+
+    println!("✓ Altered entries flagged, restored ledger consistent");
+}
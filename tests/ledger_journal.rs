@@ -0,0 +1,378 @@
+//! Tests for journaled ledger persistence (`FileLedger::open_journaled`)
+//!
+//! This module tests that:
+//! 1. Reopening a journaled ledger replays its adds, batch adds and removals, matching an
+//!    in-memory reference ledger's root and historical roots
+//! 2. A journal truncated at any byte offset recovers to the last complete update, and
+//!    the recovered ledger keeps journaling
+//! 3. A corrupt final frame is dropped, while a corrupt earlier frame fails the open
+//! 4. `compact` folds the journal into the snapshot, and a journal left over from an
+//!    interrupted compaction is discarded
+//! 5. Failed updates are not journaled, and misuse is reported
+//! 6. Diffs, renames, historical root changes and root heights are journaled too: the
+//!    ledger reopened after each of them matches the reference ledger
+
+use kontor_crypto::{api, api::FileMetadata, FileLedger, KontorPoRError, LedgerDiff};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A fresh directory for one test, with the snapshot path inside it.
+fn scratch(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("kontor_journal_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let snapshot = dir.join("ledger.bin");
+    (dir, snapshot)
+}
+
+fn journal_of(snapshot: &Path) -> PathBuf {
+    snapshot.with_file_name("ledger.bin.journal")
+}
+
+fn metadata(i: usize) -> FileMetadata {
+    let data = format!("journaled ledger file {}", i);
+    api::prepare_file(data.as_bytes(), &format!("f{}.dat", i))
+        .unwrap()
+        .1
+}
+
+/// One ledger update, applied the same way to the journaled and reference ledgers.
+enum Update {
+    Add(usize),
+    AddBatch(Vec<usize>),
+    Remove(usize),
+}
+
+fn updates() -> Vec<Update> {
+    vec![
+        Update::Add(0),
+        Update::Add(1),
+        Update::AddBatch(vec![2, 3, 4]),
+        Update::Remove(1),
+        Update::Add(5),
+        Update::Remove(3),
+    ]
+}
+
+fn apply(ledger: &mut FileLedger, update: &Update) {
+    match update {
+        Update::Add(i) => ledger.add_file(&metadata(*i)).unwrap(),
+        Update::AddBatch(batch) => {
+            let files: Vec<FileMetadata> = batch.iter().map(|&i| metadata(i)).collect();
+            ledger.add_files(&files).unwrap();
+        }
+        Update::Remove(i) => {
+            ledger.remove_file(&metadata(*i).file_id).unwrap();
+        }
+    }
+}
+
+fn assert_same(recovered: &FileLedger, reference: &FileLedger, context: &str) {
+    assert_eq!(recovered.root(), reference.root(), "{}", context);
+    assert_eq!(
+        recovered.historical_roots(),
+        reference.historical_roots(),
+        "{}",
+        context
+    );
+    assert!(recovered.file_ids().eq(reference.file_ids()), "{}", context);
+}
+
+#[test]
+fn test_reopen_replays_updates() {
+    println!("Testing that a reopened journaled ledger replays its updates");
+
+    let (dir, snapshot) = scratch("replay");
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    assert!(ledger.is_journaled());
+    assert!(snapshot.exists());
+    let mut reference = FileLedger::new();
+    for update in updates() {
+        apply(&mut ledger, &update);
+        apply(&mut reference, &update);
+    }
+    assert_same(&ledger, &reference, "live ledger");
+
+    drop(ledger);
+    let reopened = FileLedger::open_journaled(&snapshot).unwrap();
+    assert_same(&reopened, &reference, "reopened ledger");
+    assert_eq!(reopened.file_count(), 4);
+
+    // The snapshot alone is still the empty ledger
+    assert_eq!(
+        FileLedger::load(&snapshot).unwrap().root(),
+        FileLedger::new().root()
+    );
+    // Clones are in-memory copies
+    assert!(!reopened.clone().is_journaled());
+
+    fs::remove_dir_all(&dir).ok();
+    println!("✓ Replayed {} updates", updates().len());
+}
+
+#[test]
+fn test_truncated_journal_recovers_last_complete_update() {
+    println!("Testing recovery from a journal truncated at every byte offset");
+
+    let (dir, snapshot) = scratch("truncate");
+    let journal = journal_of(&snapshot);
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    let mut reference = FileLedger::new();
+    // Journal length and reference state after each complete update
+    let mut boundaries = vec![fs::metadata(&journal).unwrap().len() as usize];
+    let mut states = vec![reference.clone()];
+    for update in updates() {
+        apply(&mut ledger, &update);
+        apply(&mut reference, &update);
+        boundaries.push(fs::metadata(&journal).unwrap().len() as usize);
+        states.push(reference.clone());
+    }
+    drop(ledger);
+    let full = fs::read(&journal).unwrap();
+    assert_eq!(full.len(), *boundaries.last().unwrap());
+
+    for offset in 0..=full.len() {
+        fs::write(&journal, &full[..offset]).unwrap();
+        let recovered = FileLedger::open_journaled(&snapshot).unwrap();
+        let complete = boundaries.iter().filter(|&&end| end <= offset).count();
+        let expected = &states[complete.saturating_sub(1)];
+        assert_same(
+            &recovered,
+            expected,
+            &format!("journal truncated to {} bytes", offset),
+        );
+
+        // The torn tail is gone from disk
+        let kept = fs::metadata(&journal).unwrap().len() as usize;
+        let expected_len = boundaries[complete.saturating_sub(1)];
+        assert_eq!(kept, expected_len, "offset {}", offset);
+    }
+
+    // A recovered ledger appends after its last complete frame
+    let torn = (boundaries[3] + boundaries[4]) / 2;
+    fs::write(&journal, &full[..torn]).unwrap();
+    let mut recovered = FileLedger::open_journaled(&snapshot).unwrap();
+    let mut expected = states[3].clone();
+    recovered.add_file(&metadata(9)).unwrap();
+    expected.add_file(&metadata(9)).unwrap();
+    drop(recovered);
+    assert_same(
+        &FileLedger::open_journaled(&snapshot).unwrap(),
+        &expected,
+        "update after recovery",
+    );
+
+    fs::remove_dir_all(&dir).ok();
+    println!("✓ Recovered at all {} offsets", full.len() + 1);
+}
+
+#[test]
+fn test_corrupt_frames() {
+    let (dir, snapshot) = scratch("corrupt");
+    let journal = journal_of(&snapshot);
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    let mut reference = FileLedger::new();
+    ledger.add_file(&metadata(0)).unwrap();
+    reference.add_file(&metadata(0)).unwrap();
+    let first_end = fs::metadata(&journal).unwrap().len() as usize;
+    ledger.add_file(&metadata(1)).unwrap();
+    drop(ledger);
+    let full = fs::read(&journal).unwrap();
+
+    // A flipped byte in the final frame reads as a torn write
+    let mut bad_tail = full.clone();
+    *bad_tail.last_mut().unwrap() ^= 1;
+    fs::write(&journal, &bad_tail).unwrap();
+    let recovered = FileLedger::open_journaled(&snapshot).unwrap();
+    assert_same(&recovered, &reference, "corrupt final frame");
+    drop(recovered);
+
+    // A flipped byte in an earlier frame cannot be recovered from
+    let mut bad_middle = full.clone();
+    bad_middle[first_end - 1] ^= 1;
+    fs::write(&journal, &bad_middle).unwrap();
+    let result = FileLedger::open_journaled(&snapshot);
+    assert!(
+        matches!(result, Err(KontorPoRError::LedgerValidation { .. })),
+        "got {:?}",
+        result.map(|l| l.root())
+    );
+
+    // A file that is not a journal is refused
+    fs::write(&journal, vec![0u8; 64]).unwrap();
+    assert!(matches!(
+        FileLedger::open_journaled(&snapshot),
+        Err(KontorPoRError::LedgerValidation { .. })
+    ));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_compact() {
+    println!("Testing journal compaction");
+
+    let (dir, snapshot) = scratch("compact");
+    let journal = journal_of(&snapshot);
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    let empty_len = fs::metadata(&journal).unwrap().len();
+    let mut reference = FileLedger::new();
+    for update in updates() {
+        apply(&mut ledger, &update);
+        apply(&mut reference, &update);
+    }
+    let stale = fs::read(&journal).unwrap();
+
+    ledger.compact().unwrap();
+    assert_eq!(fs::metadata(&journal).unwrap().len(), empty_len);
+    assert_same(
+        &FileLedger::load(&snapshot).unwrap(),
+        &reference,
+        "snapshot",
+    );
+
+    // Updates after compaction go to the new journal
+    ledger.add_file(&metadata(7)).unwrap();
+    reference.add_file(&metadata(7)).unwrap();
+    drop(ledger);
+    assert_same(
+        &FileLedger::open_journaled(&snapshot).unwrap(),
+        &reference,
+        "after compaction",
+    );
+
+    // A crash between writing the snapshot and resetting the journal leaves the old
+    // journal behind; it describes updates the snapshot already holds
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    ledger.compact().unwrap();
+    drop(ledger);
+    fs::write(&journal, &stale).unwrap();
+    let reopened = FileLedger::open_journaled(&snapshot).unwrap();
+    assert_same(&reopened, &reference, "stale journal");
+    assert_eq!(fs::metadata(&journal).unwrap().len(), empty_len);
+
+    fs::remove_dir_all(&dir).ok();
+    println!("✓ Compaction folded the journal into the snapshot");
+}
+
+#[test]
+fn test_failed_updates_and_misuse() {
+    let (dir, snapshot) = scratch("failures");
+    let journal = journal_of(&snapshot);
+
+    // Configure the ledger by saving it before the first open
+    FileLedger::with_capacity(1).save(&snapshot).unwrap();
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    assert_eq!(ledger.capacity(), Some(1));
+    ledger.add_file(&metadata(0)).unwrap();
+    let root = ledger.root();
+    let len = fs::metadata(&journal).unwrap().len();
+
+    assert!(matches!(
+        ledger.add_file(&metadata(1)),
+        Err(KontorPoRError::LedgerFull { capacity: 1 })
+    ));
+    assert!(matches!(
+        ledger.remove_file("not-a-file-id"),
+        Err(KontorPoRError::FileNotFound { .. })
+    ));
+    assert!(matches!(
+        ledger.rename_file("not-a-file-id", "renamed.dat"),
+        Err(KontorPoRError::FileNotFound { .. })
+    ));
+    let missing = LedgerDiff {
+        removed: vec!["not-a-file-id".to_string()],
+        ..LedgerDiff::default()
+    };
+    assert!(matches!(
+        ledger.apply_diff(&missing),
+        Err(KontorPoRError::FileNotFound { .. })
+    ));
+    assert_eq!(ledger.root(), root);
+    assert_eq!(fs::metadata(&journal).unwrap().len(), len);
+    drop(ledger);
+    let reopened = FileLedger::open_journaled(&snapshot).unwrap();
+    assert_eq!(reopened.root(), root);
+    assert_eq!(reopened.file_count(), 1);
+
+    // Only journaled ledgers compact
+    assert!(matches!(
+        FileLedger::new().compact(),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // A journal without its snapshot is not silently dropped
+    fs::remove_file(&snapshot).unwrap();
+    assert!(matches!(
+        FileLedger::open_journaled(&snapshot),
+        Err(KontorPoRError::LedgerValidation { .. })
+    ));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+/// An update other than adding or removing files.
+type Step = (&'static str, fn(&mut FileLedger));
+
+#[test]
+fn test_reopen_replays_every_update_kind() {
+    println!("Testing that diffs, renames and root changes survive a reopen");
+
+    let steps: Vec<Step> = vec![
+        ("apply_diff", |ledger| {
+            let mut leader = FileLedger::new();
+            leader
+                .add_files(&[metadata(0), metadata(1), metadata(2)])
+                .unwrap();
+            let diff = ledger.diff(&leader);
+            ledger.apply_diff(&diff).unwrap();
+        }),
+        ("rename_file", |ledger| {
+            ledger
+                .rename_file(&metadata(1).file_id, "renamed.dat")
+                .unwrap();
+        }),
+        ("record_current_root", |ledger| {
+            ledger.record_current_root().unwrap();
+        }),
+        ("record_root_at_height", |ledger| {
+            ledger.record_root_at_height(100).unwrap();
+        }),
+        ("set_historical_roots", |ledger| {
+            let roots = ledger.historical_roots()[1..].to_vec();
+            ledger.set_historical_roots(roots).unwrap();
+        }),
+        ("prune_unreferenced_roots", |ledger| {
+            ledger
+                .prune_unreferenced_roots(&[], Duration::ZERO)
+                .unwrap();
+        }),
+        ("clear_historical_roots", |ledger| {
+            ledger.clear_historical_roots().unwrap();
+        }),
+    ];
+
+    let (dir, snapshot) = scratch("kinds");
+    let mut ledger = FileLedger::open_journaled(&snapshot).unwrap();
+    ledger.add_file(&metadata(0)).unwrap();
+    let mut reference = ledger.clone();
+    for (name, step) in &steps {
+        step(&mut ledger);
+        step(&mut reference);
+        drop(ledger);
+        ledger = FileLedger::open_journaled(&snapshot).unwrap();
+        assert_same(&ledger, &reference, name);
+        assert!(ledger.entries().eq(reference.entries()), "{}", name);
+        assert_eq!(ledger.root_at_height(100), reference.root_at_height(100));
+    }
+    assert_eq!(
+        ledger.entry(&metadata(1).file_id).unwrap().filename,
+        "renamed.dat"
+    );
+    assert!(ledger.tracks_root_heights());
+    assert!(ledger.historical_roots().is_empty());
+
+    fs::remove_dir_all(&dir).ok();
+    println!("✓ Every update kind is journaled");
+}
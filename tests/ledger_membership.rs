@@ -0,0 +1,149 @@
+//! Tests for standalone ledger membership certificates
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, FieldElement, TreeArity},
+    ledger::{verify_membership_certificate, FileLedger, MembershipCertificate},
+};
+
+fn ledger_with_files(count: u8) -> (FileLedger, Vec<api::FileMetadata>) {
+    let metadatas: Vec<_> = (0..count)
+        .map(|i| {
+            api::prepare_file(&[i + 1; 90], &format!("member_{}.dat", i))
+                .unwrap()
+                .1
+        })
+        .collect();
+    let mut ledger = FileLedger::new();
+    for metadata in &metadatas {
+        ledger.add_file(metadata).unwrap();
+    }
+    (ledger, metadatas)
+}
+
+#[test]
+fn test_membership_certificate_verifies() {
+    println!("Testing membership certificates for every file in a ledger");
+
+    let (ledger, metadatas) = ledger_with_files(5);
+    for metadata in &metadatas {
+        let certificate = ledger.membership_certificate(&metadata.file_id).unwrap();
+        let (index, rc) = ledger.lookup(&metadata.file_id).unwrap();
+
+        assert_eq!(certificate.file_id, metadata.file_id);
+        assert_eq!(certificate.root, metadata.root);
+        assert_eq!(certificate.depth, metadata.depth());
+        assert_eq!(certificate.tree_arity, TreeArity::Binary);
+        assert_eq!(certificate.index, index);
+        assert_eq!(certificate.rc, rc);
+        assert_eq!(certificate.siblings.len(), ledger.depth());
+        assert_eq!(certificate.ledger_root, ledger.root());
+        assert!(verify_membership_certificate(&certificate));
+
+        let encoded = bincode::serialize(&certificate).unwrap();
+        let decoded: MembershipCertificate = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded, certificate);
+        assert!(verify_membership_certificate(&decoded));
+    }
+
+    assert!(ledger.membership_certificate("not-a-file").is_none());
+
+    // Single-file ledger: empty path, the rc is the ledger root
+    let (single, metadatas) = ledger_with_files(1);
+    let certificate = single
+        .membership_certificate(&metadatas[0].file_id)
+        .unwrap();
+    assert!(certificate.siblings.is_empty());
+    assert!(verify_membership_certificate(&certificate));
+
+    // Arity-4 files are certified under their own rc tag
+    let (_, metadata4) =
+        api::prepare_file_with_arity(&[9; 500], "member_a4.dat", TreeArity::Arity4).unwrap();
+    let mut mixed = FileLedger::new();
+    mixed.add_file(&metadata4).unwrap();
+    mixed.add_file(&metadatas[0]).unwrap();
+    let certificate = mixed.membership_certificate(&metadata4.file_id).unwrap();
+    assert_eq!(certificate.tree_arity, TreeArity::Arity4);
+    assert!(verify_membership_certificate(&certificate));
+
+    println!("✓ Certificates verify without a ledger instance");
+}
+
+#[test]
+fn test_tampered_membership_certificate_fails() {
+    println!("Testing that tampered certificates are rejected");
+
+    let (ledger, metadatas) = ledger_with_files(4);
+    let certificate = ledger
+        .membership_certificate(&metadatas[1].file_id)
+        .unwrap();
+    assert!(verify_membership_certificate(&certificate));
+
+    let mut tampered = certificate.clone();
+    tampered.depth += 1;
+    assert!(!verify_membership_certificate(&tampered), "depth");
+
+    let mut tampered = certificate.clone();
+    tampered.rc += FieldElement::ONE;
+    assert!(!verify_membership_certificate(&tampered), "rc");
+
+    let mut tampered = certificate.clone();
+    tampered.root += FieldElement::ONE;
+    assert!(!verify_membership_certificate(&tampered), "root");
+
+    let mut tampered = certificate.clone();
+    tampered.index ^= 1;
+    assert!(!verify_membership_certificate(&tampered), "index");
+
+    // Indices that only differ above the path length are rejected too
+    let mut tampered = certificate.clone();
+    tampered.index += 1 << certificate.siblings.len();
+    assert!(
+        !verify_membership_certificate(&tampered),
+        "out-of-range index"
+    );
+
+    let mut tampered = certificate.clone();
+    tampered.siblings[0] += FieldElement::ONE;
+    assert!(!verify_membership_certificate(&tampered), "sibling");
+
+    let mut tampered = certificate.clone();
+    tampered.tree_arity = TreeArity::Arity4;
+    assert!(!verify_membership_certificate(&tampered), "arity");
+
+    let mut tampered = certificate;
+    tampered.ledger_root += FieldElement::ONE;
+    assert!(!verify_membership_certificate(&tampered), "ledger root");
+
+    println!("✓ Tampered depth, rc, index, path and root all rejected");
+}
+
+#[test]
+fn test_certificate_survives_later_adds_and_pruning() {
+    println!("Testing certificates against an older, pruned ledger root");
+
+    let (mut ledger, metadatas) = ledger_with_files(2);
+    let certificate = ledger
+        .membership_certificate(&metadatas[0].file_id)
+        .unwrap();
+    let old_root = ledger.root();
+
+    // Later adds change the root and the file's path
+    for i in 10..15u8 {
+        let (_, metadata) = api::prepare_file(&[i; 70], "later.dat").unwrap();
+        ledger.add_file(&metadata).unwrap();
+    }
+    assert_ne!(ledger.root(), old_root);
+    let fresh = ledger
+        .membership_certificate(&metadatas[0].file_id)
+        .unwrap();
+    assert_ne!(fresh.ledger_root, certificate.ledger_root);
+    assert!(verify_membership_certificate(&fresh));
+
+    // Pruning the historical roots does not affect the cryptographic statement
+    ledger.set_historical_roots(vec![]).unwrap();
+    assert!(!ledger.is_valid_root(old_root));
+    assert!(verify_membership_certificate(&certificate));
+
+    println!("✓ Old certificates remain checkable against their embedded root");
+}
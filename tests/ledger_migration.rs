@@ -0,0 +1,226 @@
+//! Tests for moving files to a new ledger (`FileLedger::migrate_from`)
+//!
+//! This module tests that:
+//! 1. A proof made against the old ledger verifies against the new ledger after migration,
+//!    and `Proof::resolve_migrated_files` names its files through the receipt even though
+//!    their indices moved
+//! 2. Migrated roots survive save/load, are listed by `describe`, and are kept by
+//!    `clear_historical_roots`; `prune_migrated_roots` makes the old proof fail
+//! 3. Migration fails with `FileIdConflict` and leaves the ledger unchanged when a file ID
+//!    is already registered with a different rc
+//! 4. On a journaled ledger, the migrated files and roots and their pruning survive a
+//!    reopen
+
+use ff::PrimeField;
+use kontor_crypto::{
+    api::{Challenge, FieldElement, FileMetadata, PorSystem, PreparedFile, Proof},
+    FileLedger, IndexPolicy, KontorPoRError,
+};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::create_test_files;
+
+fn challenges_for(metadatas: &[&FileMetadata]) -> Vec<Challenge> {
+    let seed = FieldElement::from(1668u64);
+    metadatas
+        .iter()
+        .map(|m| Challenge::new_test((*m).clone(), 1000, 2, seed))
+        .collect()
+}
+
+fn prove(
+    ledger: &FileLedger,
+    files: &BTreeMap<String, PreparedFile>,
+    challenges: &[Challenge],
+) -> Proof {
+    let refs = challenges
+        .iter()
+        .map(|c| &files[&c.file_metadata.file_id])
+        .collect();
+    PorSystem::new(ledger).prove(refs, challenges).unwrap()
+}
+
+#[test]
+fn test_old_proofs_verify_after_migration() {
+    println!("Testing proofs across a ledger migration");
+
+    let (files, metadatas) = create_test_files(4, 300, 1690);
+    let mut old = FileLedger::new();
+    old.add_files(&metadatas[..3]).unwrap();
+    old.record_current_root().unwrap();
+    let challenges = challenges_for(&[&metadatas[0], &metadatas[2]]);
+    let proof = prove(&old, &files, &challenges);
+
+    // The new ledger already holds another file, so every migrated file gets a new index
+    let mut new = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    new.add_file(&metadatas[3]).unwrap();
+    let receipt = new.migrate_from(&old).unwrap();
+    assert_eq!(new.file_count(), 4);
+    assert_ne!(new.root(), old.root());
+
+    let old_root: [u8; 32] = old.root().to_repr().into();
+    assert_eq!(receipt.old_root, old_root);
+    assert_eq!(receipt.old_index_policy, IndexPolicy::Canonical);
+    assert_eq!(receipt.migrated_roots.last(), Some(&old_root));
+    assert_eq!(receipt.files.len(), 3);
+    for (old_index, file) in &receipt.files {
+        assert_eq!(old.file_id_at(*old_index), Some(file.file_id.as_str()));
+        assert_eq!(new.lookup(&file.file_id).unwrap().0, file.new_index);
+        assert_eq!(receipt.new_index(*old_index), Some(file.new_index));
+        assert_ne!(*old_index, file.new_index);
+    }
+    assert!(new.migrated_roots().contains(&old_root));
+
+    let system = PorSystem::new(&new);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let mut expected: Vec<String> = challenges
+        .iter()
+        .map(|c| c.file_metadata.file_id.clone())
+        .collect();
+    expected.sort();
+    assert_eq!(
+        proof.resolve_migrated_files(&new, &receipt).unwrap(),
+        expected
+    );
+
+    // Proofs against the new ledger resolve as usual
+    let new_challenges = challenges_for(&[&metadatas[3], &metadatas[1]]);
+    let new_proof = prove(&new, &files, &new_challenges);
+    assert!(system.verify(&new_proof, &new_challenges).unwrap());
+    assert_eq!(
+        new_proof.resolve_migrated_files(&new, &receipt).unwrap(),
+        new_proof.resolve_files(&new).unwrap()
+    );
+
+    println!("✓ Old proofs verify and resolve after migration");
+}
+
+#[test]
+fn test_pruning_migrated_roots_invalidates_old_proofs() {
+    println!("Testing pruning of migrated roots");
+
+    let (files, metadatas) = create_test_files(2, 300, 1691);
+    let mut old = FileLedger::new();
+    old.add_files(&metadatas).unwrap();
+    let challenges = challenges_for(&[&metadatas[0], &metadatas[1]]);
+    let proof = prove(&old, &files, &challenges);
+
+    let mut new = FileLedger::new();
+    let receipt = new.migrate_from(&old).unwrap();
+    // Same files under the same policy give the same root, so move the new ledger on
+    let (_, extra) = create_test_files(1, 300, 1692);
+    new.add_file(&extra[0]).unwrap();
+    assert_ne!(new.root(), proof.ledger_root);
+
+    // Migrated roots are persisted, described and kept apart from historical roots
+    let path = std::env::temp_dir().join(format!(
+        "kontor_ledger_migration_{}.bin",
+        std::process::id()
+    ));
+    new.save(&path).unwrap();
+    let mut loaded = FileLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.migrated_roots(), new.migrated_roots());
+    assert_eq!(
+        loaded.describe().migrated_roots.len(),
+        new.migrated_roots().len()
+    );
+
+    loaded.clear_historical_roots().unwrap();
+    assert!(loaded.is_valid_root(proof.ledger_root));
+    assert!(PorSystem::new(&loaded).verify(&proof, &challenges).unwrap());
+
+    let pruned = loaded.prune_migrated_roots().unwrap();
+    assert_eq!(pruned, receipt.migrated_roots);
+    assert!(loaded.migrated_roots().is_empty());
+    assert!(!loaded.is_valid_root(proof.ledger_root));
+
+    let result = PorSystem::new(&loaded).verify(&proof, &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidLedgerRoot { .. })),
+        "expected InvalidLedgerRoot, got {result:?}"
+    );
+    assert!(matches!(
+        proof.resolve_migrated_files(&loaded, &receipt),
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+
+    println!("✓ Pruned migrated roots no longer verify");
+}
+
+#[test]
+fn test_conflicting_migration_is_rejected() {
+    println!("Testing migration onto a conflicting file ID");
+
+    let (_, metadatas) = create_test_files(2, 300, 1693);
+    let mut old = FileLedger::new();
+    old.add_files(&metadatas).unwrap();
+
+    let mut clash = metadatas[1].clone();
+    clash.file_id = metadatas[0].file_id.clone();
+    let mut new = FileLedger::new();
+    new.add_file(&clash).unwrap();
+    let root_before = new.root();
+
+    let result = new.migrate_from(&old);
+    assert!(
+        matches!(result, Err(KontorPoRError::FileIdConflict { ref file_id }) if *file_id == metadatas[0].file_id),
+        "expected FileIdConflict, got {result:?}"
+    );
+    assert_eq!(new.root(), root_before);
+    assert_eq!(new.file_count(), 1);
+    assert!(new.migrated_roots().is_empty());
+
+    // A file registered with the same rc is kept rather than rejected
+    let mut shared = FileLedger::new();
+    shared.add_file(&metadatas[0]).unwrap();
+    let receipt = shared.migrate_from(&old).unwrap();
+    assert_eq!(shared.file_count(), 2);
+    assert_eq!(receipt.files.len(), 2);
+    assert_eq!(shared.root(), old.root());
+
+    println!("✓ Conflicting migrations leave the ledger unchanged");
+}
+
+#[test]
+fn test_migration_is_journaled() {
+    println!("Testing migration of a journaled ledger");
+
+    let (_, metadatas) = create_test_files(3, 300, 1693);
+    let mut old = FileLedger::new();
+    old.add_files(&metadatas[..2]).unwrap();
+    old.record_current_root().unwrap();
+
+    let dir = std::env::temp_dir().join(format!(
+        "kontor_ledger_migration_journal_{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("ledger.bin");
+
+    let mut new = FileLedger::open_journaled(&path).unwrap();
+    new.add_file(&metadatas[2]).unwrap();
+    let receipt = new.migrate_from(&old).unwrap();
+    drop(new);
+
+    let mut reopened = FileLedger::open_journaled(&path).unwrap();
+    assert_eq!(reopened.file_count(), 3);
+    assert_eq!(reopened.migrated_roots(), receipt.migrated_roots);
+    assert_eq!(
+        reopened.historical_roots().last(),
+        Some(&reopened.root().to_repr().into())
+    );
+    assert!(reopened.is_valid_root(old.root()));
+
+    reopened.prune_migrated_roots().unwrap();
+    drop(reopened);
+    let reopened = FileLedger::open_journaled(&path).unwrap();
+    assert!(reopened.migrated_roots().is_empty());
+    assert!(!reopened.is_valid_root(old.root()));
+
+    std::fs::remove_dir_all(&dir).ok();
+    println!("✓ Migrated roots and their pruning survive a reopen");
+}
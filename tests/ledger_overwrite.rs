@@ -0,0 +1,105 @@
+//! Tests for verifying proofs after ledger entries are overwritten
+//!
+//! This module tests that:
+//! 1. A multi-file proof made before `add_file` replaced a challenged file's root still
+//!    verifies against the retained historical root, while forged metadata does not
+//! 2. Planning against the current root still requires the challenged rc in the ledger
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem},
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_test_data, create_test_files};
+
+/// Metadata claiming `original`'s file ID for different content.
+fn replacement(original: &FileMetadata, data_seed: u64) -> FileMetadata {
+    let data = create_test_data(original.original_size, Some(data_seed));
+    let (_, mut metadata) = api::prepare_file(&data, "replacement.dat").unwrap();
+    metadata.file_id = original.file_id.clone();
+    metadata
+}
+
+#[test]
+fn test_proof_verifies_after_entry_overwrite() {
+    println!("Testing verification against a historical root after an overwrite");
+
+    let (files, metadatas) = create_test_files(2, 200, 510);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    // Batched additions do not record a historical root on their own
+    ledger.record_current_root().unwrap();
+    let proved_root = ledger.root();
+
+    let seed = FieldElement::from(51u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let proof = {
+        let system = PorSystem::new(&ledger);
+        let proof = system.prove(files.values().collect(), &challenges).unwrap();
+        assert!(system.verify(&proof, &challenges).unwrap());
+        proof
+    };
+    assert_eq!(proof.ledger_root, proved_root);
+
+    // Overwrite the first file with new content under the same ID (v2)
+    let v2 = replacement(&metadatas[0], 9_001);
+    assert_ne!(v2.root, metadatas[0].root);
+    ledger.add_file(&v2).unwrap();
+    assert_ne!(ledger.root(), proved_root);
+    assert!(ledger.is_valid_root(proved_root));
+    assert_eq!(ledger.entry(&metadatas[0].file_id).unwrap().root, v2.root);
+
+    // The v1 rc is gone from the ledger, but the proof verifies via the historical root
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&proof, &challenges).unwrap());
+    assert!(api::verify_raw(&challenges, &proof, &ledger).unwrap());
+
+    // Metadata never committed under the historical root (v3) does not match the proof's
+    // challenge IDs
+    let forged: Vec<Challenge> = std::iter::once(replacement(&metadatas[0], 9_002))
+        .chain(metadatas[1..].iter().cloned())
+        .map(|m| Challenge::new_test(m, 1000, 2, seed))
+        .collect();
+    let result = system.verify(&proof, &forged);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidInput(ref msg)) if msg.contains("Challenge ID mismatch")),
+        "expected a challenge ID mismatch, got {result:?}"
+    );
+
+    // Once the historical root is dropped, the v1 rc must be in the current ledger again
+    ledger.clear_historical_roots().unwrap();
+    let result = PorSystem::new(&ledger).verify(&proof, &challenges);
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::FileNotInLedger { .. })
+    ));
+
+    println!("✓ Overwritten entries verify against their historical root");
+}
+
+#[test]
+fn test_current_root_requires_current_entry() {
+    println!("Testing planning against the current root after an overwrite");
+
+    let (_, metadatas) = create_test_files(2, 200, 520);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    ledger.add_file(&replacement(&metadatas[0], 9_003)).unwrap();
+
+    let seed = FieldElement::from(52u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let result = api::plan::build_plan(&challenges, &ledger);
+    assert!(
+        matches!(result, Err(KontorPoRError::FileNotInLedger { ref file_id }) if *file_id == metadatas[0].file_id),
+        "expected FileNotInLedger, got {result:?}"
+    );
+
+    println!("✓ Stale metadata cannot be planned against the current root");
+}
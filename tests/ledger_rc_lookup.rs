@@ -0,0 +1,159 @@
+//! Tests for `FileLedger::lookup_by_rc` and its reverse index
+//!
+//! This module tests that:
+//! 1. Overwriting a file drops its old rc and maps the new rc to the same file and index
+//! 2. Removals shift canonical indices in the reverse index as they do in the tree
+//! 3. Files sharing an rc resolve to the lowest index, before and after removals
+//! 4. Insertion-order ledgers map rcs to their permanent slots
+//! 5. The reverse index is rebuilt on load and after applying a diff
+
+use kontor_crypto::api::{ErasureCode, FieldElement, FileMetadata, TreeArity};
+use kontor_crypto::{FileLedger, IndexPolicy};
+
+fn dummy_metadata(file_id: &str, root_val: u64, depth: usize) -> FileMetadata {
+    FileMetadata {
+        root: FieldElement::from(root_val),
+        file_id: file_id.to_string(),
+        padded_len: 1 << depth,
+        original_size: 100,
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
+    }
+}
+
+fn rc_of(ledger: &FileLedger, file_id: &str) -> FieldElement {
+    ledger.entry(file_id).unwrap().rc
+}
+
+/// Every file's rc resolves to that file at the index `lookup` reports.
+fn assert_agrees_with_lookup(ledger: &FileLedger) {
+    for (file_id, entry) in ledger.entries() {
+        let (index, rc) = ledger.lookup(file_id).unwrap();
+        assert_eq!(rc, entry.rc);
+        assert_eq!(ledger.lookup_by_rc(rc), Some((file_id, index)));
+        assert_eq!(ledger.get_canonical_index_for_rc(rc), Some(index));
+    }
+}
+
+fn three_file_ledger(policy: IndexPolicy) -> FileLedger {
+    let mut ledger = FileLedger::new_with_policy(policy);
+    ledger
+        .add_files(&[
+            dummy_metadata("c", 3, 5),
+            dummy_metadata("a", 1, 3),
+            dummy_metadata("b", 2, 4),
+        ])
+        .unwrap();
+    ledger
+}
+
+#[test]
+fn test_overwrite_replaces_rc() {
+    println!("Testing rc lookup across overwrites");
+
+    let mut ledger = three_file_ledger(IndexPolicy::Canonical);
+    assert_agrees_with_lookup(&ledger);
+    let old_rc = rc_of(&ledger, "b");
+    assert_eq!(ledger.lookup_by_rc(old_rc), Some(("b", 1)));
+
+    ledger.add_file(&dummy_metadata("b", 20, 6)).unwrap();
+    let new_rc = rc_of(&ledger, "b");
+    assert_ne!(new_rc, old_rc);
+    assert_eq!(ledger.lookup_by_rc(old_rc), None);
+    assert_eq!(ledger.lookup_by_rc(new_rc), Some(("b", 1)));
+    assert_agrees_with_lookup(&ledger);
+
+    // Overwriting with the same root and depth keeps the entry
+    ledger.add_file(&dummy_metadata("b", 20, 6)).unwrap();
+    assert_eq!(ledger.lookup_by_rc(new_rc), Some(("b", 1)));
+
+    // The rc's leaf is where the index says it is
+    let (_, index) = ledger.lookup_by_rc(new_rc).unwrap();
+    assert_eq!(ledger.leaves()[index], new_rc);
+
+    println!("✓ Old rc dropped and new rc found at the same index");
+}
+
+#[test]
+fn test_removal_shifts_canonical_indices() {
+    let mut ledger = three_file_ledger(IndexPolicy::Canonical);
+    let rc_a = rc_of(&ledger, "a");
+    let rc_c = rc_of(&ledger, "c");
+    assert_eq!(ledger.lookup_by_rc(rc_c), Some(("c", 2)));
+
+    ledger.remove_file("a").unwrap();
+    assert_eq!(ledger.lookup_by_rc(rc_a), None);
+    assert_eq!(ledger.lookup_by_rc(rc_c), Some(("c", 1)));
+    assert_agrees_with_lookup(&ledger);
+
+    assert_eq!(FileLedger::new().lookup_by_rc(rc_a), None);
+}
+
+#[test]
+fn test_shared_rc_resolves_to_lowest_index() {
+    let mut ledger = three_file_ledger(IndexPolicy::Canonical);
+    let rc_a = rc_of(&ledger, "a");
+
+    // Overwrite "c" with the same content as "a"
+    ledger.add_file(&dummy_metadata("c", 1, 3)).unwrap();
+    assert_eq!(rc_of(&ledger, "c"), rc_a);
+    assert_eq!(ledger.lookup_by_rc(rc_a), Some(("a", 0)));
+
+    // Once "a" is gone the copy takes over
+    ledger.remove_file("a").unwrap();
+    assert_eq!(ledger.lookup_by_rc(rc_a), Some(("c", 1)));
+}
+
+#[test]
+fn test_insertion_order_slots() {
+    let mut ledger = three_file_ledger(IndexPolicy::InsertionOrder);
+    assert_eq!(ledger.lookup_by_rc(rc_of(&ledger, "c")), Some(("c", 0)));
+    assert_eq!(ledger.lookup_by_rc(rc_of(&ledger, "b")), Some(("b", 2)));
+
+    let old_rc = rc_of(&ledger, "a");
+    ledger.add_file(&dummy_metadata("a", 10, 4)).unwrap();
+    assert_eq!(ledger.lookup_by_rc(old_rc), None);
+    assert_eq!(ledger.lookup_by_rc(rc_of(&ledger, "a")), Some(("a", 1)));
+
+    // Tombstoning a slot leaves the other slots in place
+    let rc_c = rc_of(&ledger, "c");
+    ledger.remove_file("c").unwrap();
+    assert_eq!(ledger.lookup_by_rc(rc_c), None);
+    assert_eq!(ledger.lookup_by_rc(rc_of(&ledger, "b")), Some(("b", 2)));
+    ledger.add_file(&dummy_metadata("d", 4, 3)).unwrap();
+    assert_eq!(ledger.lookup_by_rc(rc_of(&ledger, "d")), Some(("d", 3)));
+    assert_agrees_with_lookup(&ledger);
+}
+
+#[test]
+fn test_index_rebuilt_on_load_and_diff() {
+    for policy in [IndexPolicy::Canonical, IndexPolicy::InsertionOrder] {
+        let mut ledger = three_file_ledger(policy);
+        ledger.remove_file("a").unwrap();
+        ledger.add_file(&dummy_metadata("b", 20, 6)).unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "kontor_ledger_rc_lookup_{:?}_{}.bin",
+            policy,
+            std::process::id()
+        ));
+        ledger.save(&path).unwrap();
+        let loaded = FileLedger::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for file_id in ledger.file_ids() {
+            let rc = rc_of(&ledger, file_id);
+            assert_eq!(loaded.lookup_by_rc(rc), ledger.lookup_by_rc(rc));
+        }
+        assert_agrees_with_lookup(&loaded);
+
+        let mut follower = three_file_ledger(policy);
+        let stale_rc = rc_of(&follower, "a");
+        follower.apply_diff(&follower.diff(&ledger)).unwrap();
+        assert_eq!(follower.lookup_by_rc(stale_rc), None);
+        assert_agrees_with_lookup(&follower);
+    }
+}
@@ -2,7 +2,7 @@
 
 use ff::PrimeField;
 use kontor_crypto::{
-    api::{self, Challenge, FieldElement, FileMetadata},
+    api::{self, Challenge, ErasureCode, FieldElement, FileMetadata, TreeArity},
     ledger::FileLedger,
 };
 use std::collections::BTreeMap;
@@ -33,7 +33,7 @@ fn test_file_removal_invalidates_proof() {
             .add_file(metadata)
             .expect("Failed to add file to ledger");
     }
-    let original_root = ledger_full.tree.root();
+    let original_root = ledger_full.root();
 
     // Generate proof for file at index 1 with full ledger
     let challenge = Challenge::new_test(metadatas[1].clone(), 1000, 1, FieldElement::from(42u64));
@@ -61,7 +61,7 @@ fn test_file_removal_invalidates_proof() {
     ledger_reduced.add_file(&metadatas[1]).unwrap();
     // File 2 is NOT added (removed)
 
-    let new_root = ledger_reduced.tree.root();
+    let new_root = ledger_reduced.root();
     assert_ne!(
         original_root, new_root,
         "Ledger root should change after file removal"
@@ -114,14 +114,14 @@ fn test_ledger_reorg_changes_aggregated_root() {
     ledger1.add_file(&prepared_files[0].0).unwrap();
     ledger1.add_file(&prepared_files[1].0).unwrap();
     ledger1.add_file(&prepared_files[2].0).unwrap();
-    let root1 = ledger1.tree.root();
+    let root1 = ledger1.root();
 
     // Create another ledger with same files (BTreeMap ensures same order)
     let mut ledger2 = FileLedger::new();
     ledger2.add_file(&prepared_files[2].0).unwrap();
     ledger2.add_file(&prepared_files[0].0).unwrap();
     ledger2.add_file(&prepared_files[1].0).unwrap();
-    let root2 = ledger2.tree.root();
+    let root2 = ledger2.root();
 
     // Due to BTreeMap sorting, both ledgers should have the same order and root
     assert_eq!(
@@ -137,12 +137,16 @@ fn test_ledger_reorg_changes_aggregated_root() {
         file_id: "file_a".to_string(),
         padded_len: 8, // depth 3
         original_size: 100,
-        filename: "fake.dat".to_string(),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     };
     ledger3.add_file(&fake_metadata_a).unwrap(); // Different root
     ledger3.add_file(&prepared_files[1].0).unwrap();
     ledger3.add_file(&prepared_files[2].0).unwrap();
-    let root3 = ledger3.tree.root();
+    let root3 = ledger3.root();
 
     assert_ne!(
         root1, root3,
@@ -198,7 +202,11 @@ fn test_proof_invalidation_with_file_update() {
         file_id: metadata_v1.file_id.clone(),
         padded_len: metadata_v2.padded_len,
         original_size: metadata_v2.original_size,
-        filename: metadata_v1.filename.clone(),
+        annotations: metadata_v1.annotations.clone(),
+        tree_arity: metadata_v1.tree_arity,
+        erasure: metadata_v1.erasure,
+        salt_commitment: metadata_v1.salt_commitment,
+        hash_profile: metadata_v1.hash_profile,
     };
     ledger_v2.add_file(&updated_metadata).unwrap();
 
@@ -236,46 +244,49 @@ fn test_historical_roots_recorded_on_every_add() {
 
     let mut ledger = FileLedger::new();
     assert!(
-        ledger.historical_roots.is_empty(),
+        ledger.historical_roots().is_empty(),
         "New ledger should have no historical roots"
     );
 
     // First file - records current root
     ledger.add_file(&metadata1).unwrap();
-    let root_after_first: [u8; 32] = { ledger.tree.root().to_repr().into() };
+    let root_after_first: [u8; 32] = { ledger.root().to_repr().into() };
     assert_eq!(
-        ledger.historical_roots.len(),
+        ledger.historical_roots().len(),
         1,
         "First file should record one historical root"
     );
     assert_eq!(
-        ledger.historical_roots[0], root_after_first,
+        ledger.historical_roots()[0],
+        root_after_first,
         "Historical root should be the current root after first file"
     );
 
     // Second file - records current root
     ledger.add_file(&metadata2).unwrap();
-    let root_after_second: [u8; 32] = { ledger.tree.root().to_repr().into() };
+    let root_after_second: [u8; 32] = { ledger.root().to_repr().into() };
     assert_eq!(
-        ledger.historical_roots.len(),
+        ledger.historical_roots().len(),
         2,
         "Second file should record another historical root"
     );
     assert_eq!(
-        ledger.historical_roots[1], root_after_second,
+        ledger.historical_roots()[1],
+        root_after_second,
         "Second historical root should be the current root after second file"
     );
 
     // Third file - records current root
     ledger.add_file(&metadata3).unwrap();
-    let root_after_third: [u8; 32] = { ledger.tree.root().to_repr().into() };
+    let root_after_third: [u8; 32] = { ledger.root().to_repr().into() };
     assert_eq!(
-        ledger.historical_roots.len(),
+        ledger.historical_roots().len(),
         3,
         "Third file should record another historical root"
     );
     assert_eq!(
-        ledger.historical_roots[2], root_after_third,
+        ledger.historical_roots()[2],
+        root_after_third,
         "Third historical root should be the current root after third file"
     );
 
@@ -301,21 +312,24 @@ fn test_add_files_bulk_initialization() {
         .expect("Bulk add should succeed");
 
     assert!(
-        ledger.historical_roots.is_empty(),
+        ledger.historical_roots().is_empty(),
         "Bulk init should have no historical roots"
     );
 
     // Client would then set historical roots from external storage
     let fake_historical_root: [u8; 32] = [0xAB; 32];
-    ledger.set_historical_roots(vec![fake_historical_root]);
+    ledger
+        .set_historical_roots(vec![fake_historical_root])
+        .unwrap();
 
     assert_eq!(
-        ledger.historical_roots.len(),
+        ledger.historical_roots().len(),
         1,
         "Should have one historical root after set_historical_roots"
     );
     assert_eq!(
-        ledger.historical_roots[0], fake_historical_root,
+        ledger.historical_roots()[0],
+        fake_historical_root,
         "Historical root should match what was set"
     );
 
@@ -0,0 +1,123 @@
+//! Tests for heap memory accounting (`heap_size` and `metrics::MemoryReport`)
+//!
+//! This module tests that:
+//! 1. A prepared file's heap size grows linearly with its leaf count, is within a small
+//!    factor of its serialized size, and shrinks when pruned
+//! 2. A ledger's heap size grows linearly with its file count
+//! 3. A proof's heap size is within a small factor of its serialized size
+//! 4. `MemoryReport` sums the sizes of everything added to it
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem},
+    metrics::MemoryReport,
+    FileLedger,
+};
+
+/// `actual` is within `factor` of `expected` in either direction.
+fn assert_within(actual: usize, expected: usize, factor: f64, what: &str) {
+    let ratio = actual as f64 / expected as f64;
+    assert!(
+        (1.0 / factor..=factor).contains(&ratio),
+        "{}: {} bytes vs {} expected (ratio {:.2})",
+        what,
+        actual,
+        expected,
+        ratio
+    );
+}
+
+fn ledger_of(count: usize) -> FileLedger {
+    let files: Vec<FileMetadata> = (0..count)
+        .map(|i| {
+            api::prepare_file(format!("ledger file {}", i).as_bytes(), "f.dat")
+                .unwrap()
+                .1
+        })
+        .collect();
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&files).unwrap();
+    ledger
+}
+
+#[test]
+fn test_prepared_file_scales_with_leaves() {
+    println!("Testing prepared file heap sizes across leaf counts");
+
+    let mut per_leaf = Vec::new();
+    for size in [10_000usize, 40_000, 160_000] {
+        let data = vec![7u8; size];
+        let (mut prepared, metadata) = api::prepare_file(&data, "scaled.dat").unwrap();
+        let heap = prepared.heap_size();
+
+        // A full binary tree holds about two nodes of 32 bytes per leaf
+        assert!(heap >= prepared.stored_nodes() * 32);
+        per_leaf.push(heap as f64 / metadata.padded_len as f64);
+
+        let serialized = bincode::serialize(&prepared).unwrap().len();
+        assert_within(heap, serialized, 2.0, "prepared file vs serialized");
+
+        prepared.prune(0);
+        assert!(prepared.heap_size() < heap);
+        println!(
+            "  {} leaves: {} bytes, {} after pruning",
+            metadata.padded_len,
+            heap,
+            prepared.heap_size()
+        );
+    }
+
+    let (min, max) = per_leaf
+        .iter()
+        .fold((f64::MAX, 0f64), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+    assert!(max / min < 1.25, "bytes per leaf {:?}", per_leaf);
+
+    println!("✓ Prepared file heap size is linear in its leaves");
+}
+
+#[test]
+fn test_ledger_scales_with_files() {
+    let small = ledger_of(16);
+    let large = ledger_of(64);
+    let ratio = large.heap_size() as f64 / small.heap_size() as f64;
+    assert!((3.0..=5.0).contains(&ratio), "ratio {:.2}", ratio);
+}
+
+#[test]
+fn test_proof_and_report() {
+    println!("Testing proof heap size and the memory report");
+
+    let ledger = ledger_of(3);
+    let (prepared, metadata) = api::prepare_file(&vec![3u8; 5_000], "proved.dat").unwrap();
+    let mut ledger_with_file = ledger.clone();
+    ledger_with_file.add_file(&metadata).unwrap();
+    let challenges = vec![Challenge::new_test(
+        metadata,
+        1000,
+        2,
+        FieldElement::from(9u64),
+    )];
+    let proof = PorSystem::new(&ledger_with_file)
+        .prove(vec![&prepared], &challenges)
+        .unwrap();
+
+    let serialized = proof.to_bytes().unwrap().len();
+    assert_within(proof.heap_size(), serialized, 2.0, "proof vs serialized");
+
+    let mut report = MemoryReport::default();
+    report
+        .add_prepared_file(&prepared)
+        .add_prepared_file(&prepared)
+        .add_proof(&proof)
+        .add_ledger(&ledger_with_file);
+    assert_eq!(report.prepared_files, 2);
+    assert_eq!(report.prepared_file_bytes, 2 * prepared.heap_size());
+    assert_eq!(report.proofs, 1);
+    assert_eq!(report.ledger_bytes, ledger_with_file.heap_size());
+    assert_eq!(
+        report.total_bytes(),
+        2 * prepared.heap_size() + proof.heap_size() + ledger_with_file.heap_size()
+    );
+    println!("{}", report.format_table());
+
+    println!("✓ Report sums its parts");
+}
@@ -0,0 +1,114 @@
+//! Tests for metadata annotations and redaction
+//!
+//! This module tests that:
+//! 1. `prepare_file` records the filename as an annotation that `redact` removes
+//! 2. Redacted and full metadata have identical commitments, challenge IDs and ledger roots
+//! 3. Proofs made from full metadata verify against redacted challenges, and vice versa
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem},
+    FileLedger,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+#[test]
+fn test_redaction_removes_annotations_only() {
+    let (_, metadata) = api::prepare_file(&create_test_data(500, Some(1)), "customer.csv").unwrap();
+    assert_eq!(metadata.filename(), "customer.csv");
+    assert_eq!(
+        metadata.annotations.get(FileMetadata::FILENAME_ANNOTATION),
+        Some(&"customer.csv".to_string())
+    );
+
+    let annotated = metadata.with_annotation("owner", "acme");
+    let redacted = annotated.redact();
+    assert!(redacted.annotations.is_empty());
+    assert_eq!(redacted.filename(), "");
+    assert_eq!(
+        redacted,
+        FileMetadata {
+            annotations: Default::default(),
+            ..metadata.clone()
+        }
+    );
+
+    // The commitment is the ledger's rc for the file, whatever the annotations
+    let expected_rc = metadata
+        .tree_arity
+        .root_commitment(metadata.root, metadata.depth());
+    for copy in [&metadata, &annotated, &redacted] {
+        assert_eq!(copy.commitment(), expected_rc);
+    }
+
+    // Redacted metadata serializes without the filename
+    let json = serde_json::to_string(&redacted).unwrap();
+    assert!(!json.contains("customer.csv"));
+    assert!(serde_json::to_string(&metadata)
+        .unwrap()
+        .contains("customer.csv"));
+    let parsed: FileMetadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, redacted);
+}
+
+#[test]
+fn test_redacted_metadata_proves_and_verifies_identically() {
+    println!("Testing proofs across full and redacted metadata");
+
+    let (first, first_metadata) =
+        api::prepare_file(&create_test_data(800, Some(2)), "invoices.db").unwrap();
+    let (second, second_metadata) =
+        api::prepare_file(&create_test_data(600, Some(3)), "payroll.xlsx").unwrap();
+    let full = [first_metadata, second_metadata];
+    let redacted: Vec<FileMetadata> = full.iter().map(FileMetadata::redact).collect();
+
+    let mut full_ledger = FileLedger::new();
+    let mut redacted_ledger = FileLedger::new();
+    for (full, redacted) in full.iter().zip(&redacted) {
+        full_ledger.add_file(full).unwrap();
+        redacted_ledger.add_file(redacted).unwrap();
+    }
+    assert_eq!(full_ledger.root(), redacted_ledger.root());
+    for metadata in &full {
+        assert_eq!(
+            full_ledger.entry(&metadata.file_id).unwrap().rc,
+            metadata.commitment()
+        );
+        assert_eq!(
+            redacted_ledger.entry(&metadata.file_id).unwrap().filename,
+            ""
+        );
+    }
+
+    let seed = FieldElement::from(11u64);
+    let full_challenges: Vec<Challenge> = full
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let redacted_challenges: Vec<Challenge> = redacted
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    for (full, redacted) in full_challenges.iter().zip(&redacted_challenges) {
+        assert_eq!(full.id(), redacted.id());
+    }
+
+    // Prove with the operator's full metadata, verify with what verifiers see
+    let system = PorSystem::new(&full_ledger);
+    let proof = system
+        .prove(vec![&first, &second], &full_challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &full_challenges).unwrap());
+    assert!(PorSystem::new(&redacted_ledger)
+        .verify(&proof, &redacted_challenges)
+        .unwrap());
+
+    // And the other way round
+    let proof = PorSystem::new(&redacted_ledger)
+        .prove(vec![&first, &second], &redacted_challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &full_challenges).unwrap());
+
+    println!("✓ Redacted metadata yields identical commitments and verification results");
+}
@@ -0,0 +1,142 @@
+//! Tests for multi-ledger `PorSystem`s
+//!
+//! This module tests that:
+//! 1. Tenants with overlapping file IDs prove and verify against their own named ledger
+//! 2. A proof checked against another tenant's ledger does not verify
+//! 3. Unknown, missing or mixed ledger IDs fail with routing errors before any SNARK work
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, PreparedFile},
+    FileLedger, KontorPoRError,
+};
+use std::collections::BTreeMap;
+
+struct Tenant {
+    ledger: FileLedger,
+    files: Vec<PreparedFile>,
+    challenges: Vec<Challenge>,
+}
+
+/// Both tenants store `shared` (same file ID) plus one file of their own.
+fn tenant(name: &str, shared: &[u8], own: &[u8]) -> Tenant {
+    let mut ledger = FileLedger::new();
+    let mut files = Vec::new();
+    let mut challenges = Vec::new();
+    for data in [shared, own] {
+        let (prepared, metadata) = api::prepare_file(data, "tenant.dat").unwrap();
+        ledger.add_file(&metadata).unwrap();
+        files.push(prepared);
+        challenges.push(
+            Challenge::new_test(metadata, 1000, 2, FieldElement::from(77u64)).with_ledger_id(name),
+        );
+    }
+    Tenant {
+        ledger,
+        files,
+        challenges,
+    }
+}
+
+fn tenants() -> (Tenant, Tenant) {
+    let shared = b"file stored by both tenants";
+    (
+        tenant("tenant-a", shared, b"tenant a only"),
+        tenant("tenant-b", shared, b"tenant b only, and longer"),
+    )
+}
+
+#[test]
+fn test_tenants_verify_against_their_own_ledger() {
+    println!("Testing proofs for two tenants with an overlapping file ID");
+
+    let (a, b) = tenants();
+    assert_eq!(
+        a.challenges[0].file_metadata.file_id,
+        b.challenges[0].file_metadata.file_id
+    );
+    assert_ne!(a.ledger.root(), b.ledger.root());
+
+    let system = PorSystem::new_multi(BTreeMap::from([
+        ("tenant-a".to_string(), &a.ledger),
+        ("tenant-b".to_string(), &b.ledger),
+    ]));
+    let proof_a = system
+        .prove(a.files.iter().collect(), &a.challenges)
+        .unwrap();
+    let proof_b = system
+        .prove(b.files.iter().collect(), &b.challenges)
+        .unwrap();
+
+    assert!(system.verify(&proof_a, &a.challenges).unwrap());
+    assert!(system.verify(&proof_b, &b.challenges).unwrap());
+    assert_eq!(proof_a.ledger_root, a.ledger.root());
+    assert_eq!(proof_b.ledger_root, b.ledger.root());
+
+    // Routing tenant-a's name to tenant-b's ledger makes its proof fail
+    let swapped = PorSystem::new_multi(BTreeMap::from([
+        ("tenant-a".to_string(), &b.ledger),
+        ("tenant-b".to_string(), &a.ledger),
+    ]));
+    assert!(!matches!(swapped.verify(&proof_a, &a.challenges), Ok(true)));
+
+    // The ledger ID is bound into the challenge IDs, so relabelling cannot move a proof
+    let relabelled: Vec<Challenge> = a
+        .challenges
+        .iter()
+        .map(|c| c.clone().with_ledger_id("tenant-b"))
+        .collect();
+    assert_ne!(relabelled[0].id(), a.challenges[0].id());
+    assert!(system.verify(&proof_a, &relabelled).is_err());
+
+    println!("✓ Each tenant's proof verifies only against its own ledger");
+}
+
+#[test]
+fn test_unknown_ledger_id_is_a_routing_error() {
+    let (a, b) = tenants();
+    let system = PorSystem::new_multi(BTreeMap::from([("tenant-a".to_string(), &a.ledger)]));
+
+    // Proving and verifying for a tenant the system does not hold
+    assert!(matches!(
+        system.prove(b.files.iter().collect(), &b.challenges),
+        Err(KontorPoRError::UnknownLedger { id }) if id == "tenant-b"
+    ));
+
+    let proof_b = PorSystem::new_multi(BTreeMap::from([("tenant-b".to_string(), &b.ledger)]))
+        .prove(b.files.iter().collect(), &b.challenges)
+        .unwrap();
+    assert!(matches!(
+        system.verify(&proof_b, &b.challenges),
+        Err(KontorPoRError::UnknownLedger { id }) if id == "tenant-b"
+    ));
+}
+
+#[test]
+fn test_missing_or_mixed_ledger_ids_rejected() {
+    let (a, b) = tenants();
+    let system = PorSystem::new_multi(BTreeMap::from([
+        ("tenant-a".to_string(), &a.ledger),
+        ("tenant-b".to_string(), &b.ledger),
+    ]));
+
+    let mixed = vec![a.challenges[0].clone(), b.challenges[1].clone()];
+    assert!(matches!(
+        system.prove(vec![&a.files[0], &b.files[1]], &mixed),
+        Err(KontorPoRError::ChallengeMismatch { field }) if field == "ledger_id"
+    ));
+
+    let mut unnamed = a.challenges.clone();
+    for challenge in &mut unnamed {
+        challenge.ledger_id = None;
+    }
+    assert!(matches!(
+        system.prove(a.files.iter().collect(), &unnamed),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // A single-ledger system only serves unnamed challenges
+    assert!(matches!(
+        PorSystem::new(&a.ledger).prove(a.files.iter().collect(), &a.challenges),
+        Err(KontorPoRError::UnknownLedger { .. })
+    ));
+}
@@ -13,6 +13,7 @@ fn test_gating_logic_correctness() {
     use kontor_crypto::api::FieldElement;
     use kontor_crypto::circuit::CircuitWitness;
     use kontor_crypto::circuit::{FileProofWitness, PorCircuit};
+    use kontor_crypto::config;
     use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
     use nova_snark::traits::circuit::StepCircuit;
 
@@ -61,9 +62,11 @@ fn test_gating_logic_correctness() {
     // Circuit should know that only first 2 slots are real
     let circuit_witness = CircuitWitness::new(witnesses, 2);
     let circuit = PorCircuit::new(
-        4, // files_per_step
-        3, // file_tree_depth
-        2, // aggregated_tree_depth
+        config::Shape::new(
+            4, // files_per_step
+            3, // file_tree_depth
+            2, // aggregated_tree_depth
+        ),
         Some(circuit_witness.witnesses().to_vec()),
     );
 
@@ -103,6 +106,7 @@ fn test_padding_slot_zero_state_divergence() {
     use kontor_crypto::api::FieldElement;
     use kontor_crypto::circuit::CircuitWitness;
     use kontor_crypto::circuit::{FileProofWitness, PorCircuit};
+    use kontor_crypto::config;
     use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
     use nova_snark::traits::circuit::StepCircuit;
 
@@ -135,9 +139,11 @@ fn test_padding_slot_zero_state_divergence() {
     // But wrong gating logic would process slot 0 due to "OR (file_idx == 0)"
     let circuit_witness = CircuitWitness::new(witnesses, 1);
     let circuit = PorCircuit::new(
-        2, // files_per_step
-        2, // file_tree_depth
-        1, // aggregated_tree_depth
+        config::Shape::new(
+            2, // files_per_step
+            2, // file_tree_depth
+            1, // aggregated_tree_depth
+        ),
         Some(circuit_witness.witnesses().to_vec()),
     );
 
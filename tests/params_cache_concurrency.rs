@@ -0,0 +1,145 @@
+//! Tests for concurrent access to the on-disk parameter cache
+//!
+//! This module tests that:
+//! 1. Threads racing on the same uncached shape generate it once and all load the result
+//! 2. Corrupted or truncated cache files are deleted and regenerated
+//! 3. Waiting on another writer's lock gives up with `ParamsLockTimeout`
+
+use kontor_crypto::{
+    params::{self, Shape},
+    KontorPoRError,
+};
+use std::path::{Path, PathBuf};
+use std::sync::{Barrier, Mutex};
+use std::time::Duration;
+
+/// The cache directory and lock timeout are process-wide, so tests here run one at a time.
+static CACHE_GUARD: Mutex<()> = Mutex::new(());
+
+fn fresh_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kontor_params_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    params::set_cache_dir(Some(dir.clone()));
+    params::clear_memory_cache();
+    dir
+}
+
+fn stray_files(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.ends_with(".params"))
+        .collect()
+}
+
+#[test]
+fn test_racing_threads_generate_shape_once() {
+    println!("Testing concurrent cache misses on one shape");
+
+    let _guard = CACHE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = fresh_cache_dir("race");
+    let shape = Shape::new(1, 2, 0);
+
+    let threads = 4;
+    let barrier = Barrier::new(threads);
+    let generated_before = params::generated_count();
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    params::load_or_generate_params_for_shape(&shape)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    for result in results {
+        let loaded = result.unwrap();
+        assert_eq!(loaded.file_tree_depth, 2);
+    }
+    assert_eq!(
+        params::generated_count() - generated_before,
+        1,
+        "Only the lock winner should generate"
+    );
+    assert!(params::cache_file_path(&shape).unwrap().is_file());
+    assert!(stray_files(&dir).is_empty(), "{:?}", stray_files(&dir));
+
+    params::set_cache_dir(None);
+    let _ = std::fs::remove_dir_all(&dir);
+    println!("✓ {} racing threads shared one generation", threads);
+}
+
+#[test]
+fn test_corrupt_cache_file_is_regenerated() {
+    println!("Testing recovery from corrupted parameter cache files");
+
+    let _guard = CACHE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = fresh_cache_dir("corrupt");
+    let shape = Shape::new(1, 1, 0);
+
+    params::load_or_generate_params_for_shape(&shape).unwrap();
+    let path = params::cache_file_path(&shape).unwrap();
+    let valid = std::fs::read(&path).unwrap();
+
+    // A flipped payload byte fails the checksum; a truncated file fails to decode
+    let mut flipped = valid.clone();
+    let position = flipped.len() - 100;
+    flipped[position] ^= 0x01;
+    let truncated = valid[..valid.len() / 2].to_vec();
+
+    for corrupted in [flipped, truncated] {
+        std::fs::write(&path, &corrupted).unwrap();
+        params::clear_memory_cache();
+
+        let generated_before = params::generated_count();
+        let loaded = params::load_or_generate_params_for_shape(&shape).unwrap();
+        assert_eq!(loaded.file_tree_depth, 1);
+        assert_eq!(params::generated_count() - generated_before, 1);
+        assert_ne!(std::fs::read(&path).unwrap(), corrupted);
+
+        // The rewritten file loads without another generation
+        params::clear_memory_cache();
+        params::load_or_generate_params_for_shape(&shape).unwrap();
+        assert_eq!(params::generated_count() - generated_before, 1);
+    }
+    assert!(stray_files(&dir).is_empty(), "{:?}", stray_files(&dir));
+
+    params::set_cache_dir(None);
+    let _ = std::fs::remove_dir_all(&dir);
+    println!("✓ Corrupted cache files deleted and regenerated");
+}
+
+#[test]
+fn test_held_lock_times_out() {
+    let _guard = CACHE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = fresh_cache_dir("lock_timeout");
+    let shape = Shape::new(2, 1, 1);
+
+    // Simulate another process generating this shape
+    std::fs::create_dir_all(&dir).unwrap();
+    let lock_path = params::cache_file_path(&shape)
+        .unwrap()
+        .with_extension("lock");
+    std::fs::write(&lock_path, b"12345\n").unwrap();
+
+    let previous = params::lock_timeout();
+    params::set_lock_timeout(Duration::from_millis(300));
+    let result = params::load_or_generate_params_for_shape(&shape);
+    params::set_lock_timeout(previous);
+
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::ParamsLockTimeout { timeout, .. }) if timeout == Duration::from_millis(300)
+    ));
+    assert!(
+        lock_path.is_file(),
+        "Another writer's lock must be left alone"
+    );
+
+    params::set_cache_dir(None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,252 @@
+//! Tests for parameter cache statistics and garbage collection
+//!
+//! This module tests that:
+//! 1. `cache_stats` lists parameter files with their shapes and ignores foreign files
+//! 2. Each `GcPolicy` deletes the expected files and a dry run deletes nothing
+//! 3. Foreign files and shapes being written (lock file present) are never deleted
+
+use kontor_crypto::params::{self, CacheEntryInfo, GcPolicy, Shape};
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn shapes() -> [Shape; 2] {
+    [Shape::new(1, 1, 0), Shape::new(1, 2, 0)]
+}
+
+/// Parameters for [`shapes`], generated once per test binary; each test copies the files
+/// into its own directory.
+fn generated_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("kontor_params_gc_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        params::set_cache_dir(Some(dir.clone()));
+        params::pregenerate(&shapes(), false).unwrap();
+        params::set_cache_dir(None);
+        dir
+    })
+}
+
+/// A cache directory holding both generated shapes, a file of an older format version and
+/// foreign files.
+struct Fixture {
+    dir: PathBuf,
+    valid: Vec<PathBuf>,
+    old_format: PathBuf,
+    foreign: Vec<PathBuf>,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("kontor_params_gc_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let valid: Vec<PathBuf> = fs::read_dir(generated_dir())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .map(|source| {
+                let target = dir.join(source.file_name().unwrap());
+                fs::copy(&source, &target).unwrap();
+                target
+            })
+            .collect();
+        assert_eq!(valid.len(), 2);
+
+        let old_format = dir.join("por_public_1x3_agg0_v7.params");
+        let mut bytes = b"KPAR".to_vec();
+        bytes.extend_from_slice(&7u16.to_le_bytes());
+        bytes.extend_from_slice(&[0xAB; 1000]);
+        fs::write(&old_format, bytes).unwrap();
+
+        let foreign = vec![dir.join("notes.params"), dir.join("readme.txt")];
+        fs::write(&foreign[0], b"not a parameter file").unwrap();
+        fs::write(&foreign[1], b"KPAR but not a params extension").unwrap();
+
+        Self {
+            dir,
+            valid,
+            old_format,
+            foreign,
+        }
+    }
+
+    /// Sets a file's access and modification times to `days` ago.
+    fn age(&self, path: &Path, days: u32) {
+        let time = SystemTime::now() - DAY * days;
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_times(FileTimes::new().set_accessed(time).set_modified(time))
+            .unwrap();
+    }
+
+    fn path_of(&self, shape: &Shape) -> PathBuf {
+        let stats = params::cache_stats(&self.dir).unwrap();
+        stats
+            .into_iter()
+            .find(|e| e.shape.as_ref() == Some(shape))
+            .unwrap()
+            .path
+    }
+
+    fn assert_foreign_untouched(&self) {
+        for path in &self.foreign {
+            assert!(path.is_file(), "{} was deleted", path.display());
+        }
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn sorted_paths(entries: &[CacheEntryInfo]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_cache_stats_lists_parameter_files_only() {
+    println!("Testing cache_stats over a directory with foreign files");
+
+    let fixture = Fixture::new("stats");
+    let stats = params::cache_stats(&fixture.dir).unwrap();
+    assert_eq!(stats.len(), 3, "{:?}", stats);
+
+    let mut expected = fixture.valid.clone();
+    expected.push(fixture.old_format.clone());
+    expected.sort();
+    assert_eq!(sorted_paths(&stats), expected);
+
+    for shape in shapes() {
+        let entry = stats
+            .iter()
+            .find(|e| e.shape == Some(shape))
+            .expect("generated shape listed");
+        assert!(entry.is_current_format());
+        assert_eq!(entry.size, fs::metadata(&entry.path).unwrap().len());
+        assert!(entry.last_accessed.is_some());
+    }
+    let old = stats.iter().find(|e| e.path == fixture.old_format).unwrap();
+    assert_eq!(old.shape, None);
+    assert_eq!(old.format_version, 7);
+    assert!(!old.is_current_format());
+
+    // Least recently accessed first
+    fixture.age(&fixture.old_format, 10);
+    fixture.age(&fixture.path_of(&shapes()[1]), 5);
+    let stats = params::cache_stats(&fixture.dir).unwrap();
+    assert_eq!(stats[0].path, fixture.old_format);
+    assert_eq!(stats[1].shape, Some(shapes()[1]));
+
+    assert!(params::cache_stats(&fixture.dir.join("missing"))
+        .unwrap()
+        .is_empty());
+
+    println!("✓ Only parameter files listed, with shapes and sizes");
+}
+
+#[test]
+fn test_gc_older_than_and_dry_run() {
+    println!("Testing GcPolicy::OlderThan with and without a dry run");
+
+    let fixture = Fixture::new("older");
+    let stale = fixture.path_of(&shapes()[0]);
+    fixture.age(&stale, 40);
+    fixture.age(&fixture.old_format, 60);
+    for path in &fixture.foreign {
+        fixture.age(path, 90);
+    }
+    let policy = GcPolicy::OlderThan(30 * DAY);
+
+    let planned = params::gc_dry_run(&fixture.dir, policy).unwrap();
+    assert_eq!(sorted_paths(&planned.removed), {
+        let mut expected = vec![stale.clone(), fixture.old_format.clone()];
+        expected.sort();
+        expected
+    });
+    assert_eq!(planned.kept.len(), 1);
+    assert!(stale.is_file() && fixture.old_format.is_file());
+
+    let report = params::gc(&fixture.dir, policy).unwrap();
+    assert_eq!(
+        sorted_paths(&report.removed),
+        sorted_paths(&planned.removed)
+    );
+    assert_eq!(report.freed_bytes(), planned.freed_bytes());
+    assert!(!stale.exists() && !fixture.old_format.exists());
+    assert!(fixture.path_of(&shapes()[1]).is_file());
+    fixture.assert_foreign_untouched();
+
+    println!("✓ Old parameter files deleted, foreign files kept");
+}
+
+#[test]
+fn test_gc_keep_shapes() {
+    let fixture = Fixture::new("keep");
+    let kept = fixture.path_of(&shapes()[0]);
+    let dropped = fixture.path_of(&shapes()[1]);
+
+    let report = params::gc(&fixture.dir, GcPolicy::KeepShapes(&shapes()[..1])).unwrap();
+    assert_eq!(report.removed.len(), 2);
+    assert!(kept.is_file());
+    assert!(!dropped.exists());
+    assert!(!fixture.old_format.exists(), "older formats are never kept");
+    fixture.assert_foreign_untouched();
+
+    // Keeping every remaining shape deletes nothing
+    let report = params::gc(&fixture.dir, GcPolicy::KeepShapes(&shapes())).unwrap();
+    assert!(report.removed.is_empty());
+    assert_eq!(report.kept.len(), 1);
+}
+
+#[test]
+fn test_gc_max_total_bytes_evicts_least_recently_used() {
+    println!("Testing GcPolicy::MaxTotalBytes eviction order");
+
+    let fixture = Fixture::new("maxbytes");
+    let recent = fixture.path_of(&shapes()[0]);
+    let older = fixture.path_of(&shapes()[1]);
+    fixture.age(&recent, 1);
+    fixture.age(&older, 2);
+    fixture.age(&fixture.old_format, 3);
+
+    let recent_size = fs::metadata(&recent).unwrap().len();
+    let report = params::gc(&fixture.dir, GcPolicy::MaxTotalBytes(recent_size)).unwrap();
+    assert_eq!(report.removed.len(), 2);
+    assert_eq!(report.removed[0].path, fixture.old_format);
+    assert_eq!(report.removed[1].path, older);
+    assert!(recent.is_file());
+    fixture.assert_foreign_untouched();
+
+    // Already under the limit
+    let report = params::gc(&fixture.dir, GcPolicy::MaxTotalBytes(recent_size)).unwrap();
+    assert!(report.removed.is_empty());
+
+    let report = params::gc(&fixture.dir, GcPolicy::MaxTotalBytes(0)).unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert!(params::cache_stats(&fixture.dir).unwrap().is_empty());
+    fixture.assert_foreign_untouched();
+
+    println!("✓ Least recently used files evicted until under the limit");
+}
+
+#[test]
+fn test_gc_skips_locked_shapes() {
+    let fixture = Fixture::new("locked");
+    let locked = fixture.path_of(&shapes()[0]);
+    fs::write(locked.with_extension("lock"), b"12345").unwrap();
+
+    let report = params::gc(&fixture.dir, GcPolicy::MaxTotalBytes(0)).unwrap();
+    assert_eq!(report.removed.len(), 2);
+    assert!(locked.is_file(), "a shape being written is kept");
+    assert_eq!(report.kept.len(), 1);
+    assert_eq!(report.kept[0].path, locked);
+}
@@ -0,0 +1,130 @@
+//! Tests for reading parameter file headers and streaming loads
+//!
+//! This module tests that:
+//! 1. `peek_header` reports the format, schema version and shape of a cached file
+//! 2. Peeking reads only the header, so multi-GB files return immediately
+//! 3. Foreign, older-format and missing files are rejected
+//! 4. A cached file of another shape is regenerated rather than loaded
+
+use kontor_crypto::{
+    config::PUBLIC_IO_SCHEMA_VERSION,
+    params::{self, Shape},
+    KontorPoRError,
+};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The cache directory is process-wide, so tests here run one at a time.
+static CACHE_GUARD: Mutex<()> = Mutex::new(());
+
+fn fresh_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "kontor_params_header_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    params::set_cache_dir(Some(dir.clone()));
+    params::clear_memory_cache();
+    dir
+}
+
+#[test]
+fn test_peek_header_reads_shape() {
+    println!("Testing header peeks on cached and oversized parameter files");
+
+    let _guard = CACHE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = fresh_cache_dir("peek");
+    let shape = Shape::new(1, 1, 0);
+    params::load_or_generate_params_for_shape(&shape).unwrap();
+    let path = params::cache_file_path(&shape).unwrap();
+    params::set_cache_dir(None);
+
+    let header = params::peek_header(&path).unwrap();
+    assert_eq!(header.shape, shape);
+    assert_eq!(header.schema_version, PUBLIC_IO_SCHEMA_VERSION);
+    let listed = params::cache_stats(&dir).unwrap();
+    assert_eq!(listed[0].format_version, header.format_version);
+
+    // A sparse multi-GB file with the same leading bytes peeks just as fast
+    let large = dir.join("large.params");
+    let mut file = File::create(&large).unwrap();
+    file.write_all(&fs::read(&path).unwrap()[..4096]).unwrap();
+    file.set_len(4 << 30).unwrap();
+    drop(file);
+    let start = Instant::now();
+    let large_header = params::peek_header(&large).unwrap();
+    let elapsed = start.elapsed();
+    assert_eq!(large_header, header);
+    assert!(
+        elapsed < Duration::from_secs(1),
+        "peeking a 4 GiB file took {:?}",
+        elapsed
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+    println!("✓ Header peeked in {:?} regardless of file size", elapsed);
+}
+
+#[test]
+fn test_peek_header_rejects_other_files() {
+    let dir = std::env::temp_dir().join(format!(
+        "kontor_params_header_foreign_{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let foreign = dir.join("notes.params");
+    fs::write(&foreign, b"not a parameter file").unwrap();
+    let old_format = dir.join("por_public_1x3_agg0_v7.params");
+    let mut bytes = b"KPAR".to_vec();
+    bytes.extend_from_slice(&7u16.to_le_bytes());
+    bytes.extend_from_slice(&[0xAB; 1000]);
+    fs::write(&old_format, bytes).unwrap();
+    let empty = dir.join("empty.params");
+    fs::write(&empty, b"").unwrap();
+
+    for path in [&foreign, &old_format, &empty] {
+        let result = params::peek_header(path);
+        assert!(
+            matches!(result, Err(KontorPoRError::Serialization(_))),
+            "{}: {:?}",
+            path.display(),
+            result
+        );
+    }
+    assert!(matches!(
+        params::peek_header(&dir.join("missing.params")),
+        Err(KontorPoRError::IO(_))
+    ));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cached_file_of_other_shape_is_regenerated() {
+    println!("Testing that a cached file of another shape is not loaded");
+
+    let _guard = CACHE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = fresh_cache_dir("other_shape");
+    let shape = Shape::new(1, 1, 0);
+    let other = Shape::new(1, 2, 0);
+    params::load_or_generate_params_for_shape(&other).unwrap();
+    let path = params::cache_file_path(&shape).unwrap();
+    fs::copy(params::cache_file_path(&other).unwrap(), &path).unwrap();
+    params::clear_memory_cache();
+
+    let generated_before = params::generated_count();
+    let loaded = params::load_or_generate_params_for_shape(&shape).unwrap();
+    assert_eq!(loaded.file_tree_depth, 1);
+    assert_eq!(params::generated_count() - generated_before, 1);
+    assert_eq!(params::peek_header(&path).unwrap().shape, shape);
+
+    params::set_cache_dir(None);
+    let _ = fs::remove_dir_all(&dir);
+    println!("✓ Shape mismatch detected from the header and regenerated");
+}
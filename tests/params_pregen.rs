@@ -0,0 +1,72 @@
+//! Tests for parameter pregeneration and the on-disk parameter cache
+
+use kontor_crypto::{
+    config,
+    params::{self, PregenStatus, Shape},
+};
+
+#[test]
+fn test_pregenerate_populates_disk_cache_for_fast_path() {
+    println!("Testing that pregenerated shapes are loaded from the disk cache");
+
+    let cache_dir =
+        std::env::temp_dir().join(format!("kontor_params_pregen_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    params::set_cache_dir(Some(cache_dir.clone()));
+
+    let shapes = [Shape::new(1, 1, 0), Shape::new(2, 1, 1)];
+    let report = params::pregenerate(&shapes, true).unwrap();
+
+    assert_eq!(report.entries.len(), 2);
+    assert_eq!(report.generated(), 2);
+    for (entry, shape) in report.entries.iter().zip(&shapes) {
+        assert_eq!(entry.shape, *shape, "Report must preserve input order");
+        assert_eq!(entry.status, PregenStatus::Generated);
+        assert!(entry.cache_file_size.unwrap() > 0);
+        assert!(params::cache_file_path(shape).unwrap().is_file());
+    }
+    let leftovers: Vec<_> = std::fs::read_dir(&cache_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.ends_with(".params"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "Stray lock/tmp files: {:?}",
+        leftovers
+    );
+
+    // Re-running skips everything
+    let rerun = params::pregenerate(&shapes, false).unwrap();
+    assert_eq!(rerun.skipped(), 2);
+    assert_eq!(rerun.generated(), 0);
+
+    // With the memory cache cleared, loads must come from disk rather than regeneration
+    params::clear_memory_cache();
+    let generated_before = params::generated_count();
+    let single = params::load_or_generate_params(config::Shape::new(1, 1, 0)).unwrap();
+    let multi = params::load_or_generate_params(config::Shape::new(2, 1, 1)).unwrap();
+    assert_eq!(
+        params::generated_count(),
+        generated_before,
+        "Cached shapes must not be regenerated"
+    );
+    assert_eq!(single.file_tree_depth, 1);
+    assert_eq!(multi.aggregated_tree_depth, 1);
+
+    params::set_cache_dir(None);
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    println!("✓ Pregenerated parameters take the disk-cache fast path");
+}
+
+#[test]
+fn test_pregenerate_rejects_invalid_shapes() {
+    let result = params::pregenerate(&[Shape::new(3, 1, 0)], false);
+    assert!(result.is_err(), "files_per_step must be a power of two");
+
+    let matrix = Shape::matrix(&[1, 2], &[4, 5], &[0], Default::default());
+    assert_eq!(matrix.len(), 4);
+    assert_eq!(matrix[1], Shape::new(1, 5, 0));
+}
@@ -0,0 +1,113 @@
+//! Tests for per-step challenge seed schedules
+//!
+//! This module tests that:
+//! 1. A proof with an explicit seed for every step verifies
+//! 2. Reordering step seeds at verify time is rejected
+//! 3. Schedules that do not cover every step are rejected up front
+//! 4. Fixed and per-step schedules cannot be mixed in a batch
+
+use kontor_crypto::{
+    api::{self, spot_check, Challenge, FieldElement, PorSystem, SeedSchedule},
+    FileLedger, KontorPoRError,
+};
+
+fn step_seeds(n: u64) -> Vec<FieldElement> {
+    (0..n).map(|i| FieldElement::from(1_000 + i * 17)).collect()
+}
+
+#[test]
+fn test_per_step_seeded_proof_verifies_and_swaps_fail() {
+    println!("Testing a 5-step proof with per-step seeds");
+
+    let (prepared, metadata) =
+        api::prepare_file(b"per-step seeded file data", "steps.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seeds = step_seeds(5);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 5, FieldElement::from(9u64))
+        .with_seed_schedule(SeedSchedule::PerStep(seeds.clone()));
+
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(
+        system
+            .verify(&proof, std::slice::from_ref(&challenge))
+            .unwrap(),
+        "Per-step seeded proof should verify"
+    );
+
+    // Spot checks replay the same schedule
+    assert!(spot_check(&prepared, &challenge).unwrap().all_passed());
+
+    // Swap two step seeds at verify time
+    let mut swapped_seeds = seeds.clone();
+    swapped_seeds.swap(1, 3);
+    let swapped = Challenge::new_test(metadata.clone(), 1000, 5, FieldElement::from(9u64))
+        .with_seed_schedule(SeedSchedule::PerStep(swapped_seeds));
+    assert_ne!(
+        swapped.id(),
+        challenge.id(),
+        "Schedule order must be bound in the ID"
+    );
+
+    // The high-level API rejects the mismatched challenge ID...
+    assert!(system
+        .verify(&proof, std::slice::from_ref(&swapped))
+        .is_err());
+
+    // ...and the SNARK check itself rejects the reordered schedule
+    let raw = api::verify_raw(&[swapped], &proof, &ledger).unwrap();
+    assert!(!raw, "Swapped step seeds must fail verification");
+
+    // A fixed-seed challenge cannot verify the per-step proof either
+    let fixed = Challenge::new_test(metadata, 1000, 5, FieldElement::from(9u64));
+    assert!(!api::verify_raw(&[fixed], &proof, &ledger).unwrap_or(false));
+
+    println!("✓ Per-step seeds verify and reordering is rejected");
+}
+
+#[test]
+fn test_seed_schedule_length_mismatch_rejected() {
+    let (prepared, metadata) = api::prepare_file(b"schedule length", "len.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    for len in [3u64, 6] {
+        let challenge = Challenge::new_test(metadata.clone(), 1000, 5, FieldElement::from(1u64))
+            .with_seed_schedule(SeedSchedule::PerStep(step_seeds(len)));
+        let result = PorSystem::new(&ledger).prove(vec![&prepared], &[challenge]);
+        assert!(
+            matches!(
+                result,
+                Err(KontorPoRError::InvalidSeedSchedule { expected: 5, got }) if got == len as usize
+            ),
+            "Schedule of length {} must be rejected",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_mixed_seed_schedules_rejected() {
+    let (prepared1, metadata1) = api::prepare_file(b"first scheduled file", "a.dat").unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"second fixed file", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 2, FieldElement::from(1u64))
+            .with_seed_schedule(SeedSchedule::PerStep(step_seeds(2))),
+        Challenge::new_test(metadata2, 1000, 2, FieldElement::from(1u64)),
+    ];
+
+    let result = PorSystem::new(&ledger).prove(vec![&prepared1, &prepared2], &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "seed_schedule"),
+        "Mixed seed schedules must be rejected"
+    );
+}
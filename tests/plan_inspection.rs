@@ -0,0 +1,209 @@
+//! Tests for the challenge batching plan inspection API
+//!
+//! This module tests that:
+//! 1. `build_plan` matches the proof `prove` produces (step count, slot order) for 1, 2, 3 and 5 files
+//! 2. Padding slots are reported empty, and per-step seeds appear in their steps
+//! 3. Invalid batches are rejected by `build_plan` with the same errors as `prove`
+
+use kontor_crypto::{
+    api::{
+        self,
+        plan::{build_plan, estimate_step_constraints},
+        Challenge, FieldElement, PorSystem, SeedSchedule,
+    },
+    config,
+    metrics::{set_metrics_sink, MetricsSink, Phase, TimingEvent},
+    KontorPoRError,
+};
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+#[derive(Default)]
+struct CollectingSink {
+    events: Mutex<Vec<TimingEvent>>,
+}
+
+impl MetricsSink for CollectingSink {
+    fn record(&self, event: TimingEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn test_plan_matches_proof() {
+    println!("Testing that build_plan matches the generated proof");
+
+    // Only this test proves, so the process-wide sink sees only its own steps
+    let sink = Arc::new(CollectingSink::default());
+    set_metrics_sink(Some(sink.clone()));
+
+    for num_files in [1, 2, 3, 5] {
+        let (files, metadatas) = create_test_files(num_files, 200, 40 + num_files as u64);
+        let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+        // Reverse caller order so the plan has to sort
+        let challenges: Vec<Challenge> = metadatas
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, m)| Challenge::new_test(m.clone(), 1000, 3, FieldElement::from(i as u64)))
+            .collect();
+
+        let plan = build_plan(&challenges, &ledger).unwrap();
+        assert_eq!(plan.files_per_step, num_files.next_power_of_two());
+        assert_eq!(plan.total_steps, 3);
+        assert_eq!(plan.steps.len(), plan.total_steps);
+        assert_eq!(
+            plan.estimated_constraints(),
+            plan.total_steps * plan.estimated_constraints_per_step
+        );
+
+        let mut sorted_ids: Vec<&str> = metadatas.iter().map(|m| m.file_id.as_str()).collect();
+        sorted_ids.sort();
+        assert_eq!(plan.slot_file_ids(), sorted_ids);
+
+        sink.events.lock().unwrap().clear();
+        let system = PorSystem::new(&ledger);
+        let proof = system.prove(files.values().collect(), &challenges).unwrap();
+        assert!(system.verify(&proof, &challenges).unwrap());
+
+        // Step count: one witness and one fold per planned step
+        let events = sink.events.lock().unwrap();
+        for phase in [Phase::WitnessGen, Phase::Fold] {
+            let mut steps: Vec<usize> = events
+                .iter()
+                .filter(|e| e.phase == phase)
+                .filter_map(|e| e.step)
+                .collect();
+            steps.sort();
+            assert_eq!(
+                steps,
+                (0..plan.total_steps).collect::<Vec<_>>(),
+                "{:?} steps for {} files",
+                phase,
+                num_files
+            );
+        }
+
+        // Slot order: the proof's public ledger indices follow the planned slots
+        assert_eq!(
+            proof.shape.aggregated_tree_depth,
+            plan.aggregated_tree_depth
+        );
+        for step in &plan.steps {
+            let indices: Vec<usize> = step.slots.iter().map(|s| s.ledger_index).collect();
+            assert_eq!(indices, proof.ledger_indices, "{} files", num_files);
+        }
+
+        println!(
+            "  {} files: {} slots, {} steps, ~{} constraints",
+            num_files,
+            plan.files_per_step,
+            plan.total_steps,
+            plan.estimated_constraints()
+        );
+    }
+
+    set_metrics_sink(None);
+    println!("✓ Plan step count and slot order match the proof");
+}
+
+#[test]
+fn test_plan_padding_and_per_step_seeds() {
+    let (_, metadatas) = create_test_files(3, 150, 7);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let schedule = (0..2)
+                .map(|step| FieldElement::from((10 * i + step) as u64))
+                .collect();
+            Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(i as u64))
+                .with_seed_schedule(SeedSchedule::PerStep(schedule))
+        })
+        .collect();
+
+    let plan = build_plan(&challenges, &ledger).unwrap();
+    assert_eq!(plan.files_per_step, 4);
+    assert_eq!(
+        plan.estimated_constraints_per_step,
+        estimate_step_constraints(4, plan.file_tree_depth, plan.aggregated_tree_depth)
+    );
+
+    for step in &plan.steps {
+        assert_eq!(step.slots.len(), 4);
+        for slot in &step.slots[..3] {
+            let challenge = challenges
+                .iter()
+                .find(|c| Some(&c.file_metadata.file_id) == slot.file_id.as_ref())
+                .unwrap();
+            assert_eq!(slot.challenge_id, Some(challenge.id()));
+            let SeedSchedule::PerStep(schedule) = &challenge.seed_schedule else {
+                unreachable!()
+            };
+            assert_eq!(slot.seed, schedule[step.step]);
+            assert_eq!(
+                slot.depth,
+                api::tree_depth_from_metadata(&challenge.file_metadata)
+            );
+        }
+
+        let padding = &step.slots[3];
+        assert_eq!(padding.file_id, None);
+        assert_eq!(padding.challenge_id, None);
+        assert_eq!(padding.seed, FieldElement::from(0u64));
+    }
+}
+
+#[test]
+fn test_plan_rejects_invalid_batches() {
+    let (_, metadatas) = create_test_files(2, 100, 3);
+    let ledger = create_multi_file_ledger(&[&metadatas[0]]);
+    let seed = FieldElement::from(1u64);
+
+    assert!(matches!(
+        build_plan(&[], &ledger),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        build_plan(
+            &[Challenge::new_test(metadatas[0].clone(), 1000, 0, seed)],
+            &ledger
+        ),
+        Err(KontorPoRError::InvalidChallengeCount { count: 0 })
+    ));
+    assert!(matches!(
+        build_plan(
+            &[Challenge::new_test(
+                metadatas[0].clone(),
+                1000,
+                config::MAX_NUM_CHALLENGES + 1,
+                seed
+            )],
+            &ledger
+        ),
+        Err(KontorPoRError::InvalidChallengeCount { .. })
+    ));
+
+    let full_ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let mismatched = [
+        Challenge::new_test(metadatas[0].clone(), 1000, 2, seed),
+        Challenge::new_test(metadatas[1].clone(), 1000, 3, seed),
+    ];
+    assert!(matches!(
+        build_plan(&mismatched, &full_ledger),
+        Err(KontorPoRError::ChallengeMismatch { field }) if field == "num_challenges"
+    ));
+
+    // The second file is not in the single-file ledger
+    let unknown = [
+        Challenge::new_test(metadatas[0].clone(), 1000, 2, seed),
+        Challenge::new_test(metadatas[1].clone(), 1000, 2, seed),
+    ];
+    assert!(matches!(
+        build_plan(&unknown, &ledger),
+        Err(KontorPoRError::FileNotInLedger { .. })
+    ));
+}
@@ -0,0 +1,143 @@
+//! Tests for cached Poseidon constants
+//!
+//! This module tests that:
+//! 1. Hashes computed with the cached constants are bit-identical to hashes computed with
+//!    freshly generated constants, for every tag and both sponge widths
+//! 2. The circuit gadgets, which use their own per-field constant cache, agree with the
+//!    native hashes
+//! 3. `warm_up` is idempotent and does not change any output
+
+use ff::Field;
+use generic_array::typenum::U2;
+use kontor_crypto::{
+    api::FieldElement,
+    circuit::gadgets::{
+        poseidon::circuit_constants, poseidon_hash_tagged4_gadget, poseidon_hash_tagged_gadget,
+    },
+    merkle::{canonical::field_to_bytes, hash_node, hash_node4},
+    poseidon::{self, domain_tags, poseidon_hash_tagged, poseidon_hash_tagged4},
+};
+use nova_snark::frontend::{
+    gadgets::{
+        num::AllocatedNum,
+        poseidon::{IOPattern, Simplex, Sponge, SpongeAPI, SpongeOp, SpongeTrait, Strength},
+    },
+    util_cs::test_cs::TestConstraintSystem,
+    ConstraintSystem,
+};
+
+/// Reference hash that regenerates the constants on every call, as the gadgets used to
+fn uncached_hash(inputs: &[FieldElement]) -> FieldElement {
+    let constants = Sponge::<FieldElement, U2>::api_constants(Strength::Standard);
+    let mut sponge = Sponge::<FieldElement, U2>::new_with_constants(&constants, Simplex);
+    let mut acc = ();
+    let pattern = IOPattern(vec![
+        SpongeOp::Absorb(inputs.len() as u32),
+        SpongeOp::Squeeze(1),
+    ]);
+    sponge.start(pattern, None, &mut acc);
+    SpongeAPI::absorb(&mut sponge, inputs.len() as u32, inputs, &mut acc);
+    let output = SpongeAPI::squeeze(&mut sponge, 1, &mut acc);
+    sponge.finish(&mut acc).unwrap();
+    output[0]
+}
+
+fn tags() -> Vec<FieldElement> {
+    vec![
+        domain_tags::leaf(),
+        domain_tags::node(),
+        domain_tags::challenge(),
+        domain_tags::root_commitment(),
+        domain_tags::node_arity4(),
+        domain_tags::explicit_index(),
+    ]
+}
+
+fn vectors() -> Vec<(FieldElement, FieldElement)> {
+    vec![
+        (FieldElement::ZERO, FieldElement::ZERO),
+        (FieldElement::from(42u64), FieldElement::from(123u64)),
+        (-FieldElement::ONE, FieldElement::from(u64::MAX)),
+    ]
+}
+
+#[test]
+fn test_cached_constants_match_fresh_constants() {
+    println!("Testing cached Poseidon constants against freshly generated ones");
+
+    for tag in tags() {
+        for (x, y) in vectors() {
+            let expected = uncached_hash(&[tag, x, y]);
+            assert_eq!(
+                field_to_bytes(&poseidon_hash_tagged(tag, x, y)),
+                field_to_bytes(&expected)
+            );
+
+            let children = [x, y, x + y, FieldElement::from(7u64)];
+            let expected4 =
+                uncached_hash(&[tag, children[0], children[1], children[2], children[3]]);
+            assert_eq!(poseidon_hash_tagged4(tag, children), expected4);
+        }
+    }
+
+    let (x, y) = vectors()[1];
+    assert_eq!(hash_node(x, y), uncached_hash(&[domain_tags::node(), x, y]));
+    let children = [x, y, x, y];
+    assert_eq!(
+        hash_node4(children),
+        uncached_hash(&[domain_tags::node_arity4(), x, y, x, y])
+    );
+
+    println!("✓ Cached and fresh constants produce identical hashes");
+}
+
+#[test]
+fn test_gadgets_match_native_hashes_across_warm_up() {
+    println!("Testing circuit gadgets against native hashes");
+
+    let before: Vec<FieldElement> = tags()
+        .into_iter()
+        .flat_map(|tag| {
+            vectors()
+                .into_iter()
+                .map(move |(x, y)| poseidon_hash_tagged(tag, x, y))
+        })
+        .collect();
+
+    poseidon::warm_up();
+    poseidon::warm_up();
+    assert!(std::ptr::eq(
+        circuit_constants::<FieldElement>(),
+        circuit_constants::<FieldElement>()
+    ));
+
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let mut after = Vec::new();
+    for (t, tag) in tags().into_iter().enumerate() {
+        for (v, (x, y)) in vectors().into_iter().enumerate() {
+            let mut ns = cs.namespace(|| format!("tag_{}_vector_{}", t, v));
+            let x_alloc = AllocatedNum::alloc(ns.namespace(|| "x"), || Ok(x)).unwrap();
+            let y_alloc = AllocatedNum::alloc(ns.namespace(|| "y"), || Ok(y)).unwrap();
+            let hash =
+                poseidon_hash_tagged_gadget(ns.namespace(|| "hash2"), tag, &x_alloc, &y_alloc)
+                    .unwrap();
+            assert_eq!(hash.get_value(), Some(poseidon_hash_tagged(tag, x, y)));
+            after.push(hash.get_value().unwrap());
+
+            let hash4 = poseidon_hash_tagged4_gadget(
+                ns.namespace(|| "hash4"),
+                tag,
+                [&x_alloc, &y_alloc, &y_alloc, &x_alloc],
+            )
+            .unwrap();
+            assert_eq!(
+                hash4.get_value(),
+                Some(poseidon_hash_tagged4(tag, [x, y, y, x]))
+            );
+        }
+    }
+    assert!(cs.is_satisfied());
+    assert_eq!(before, after, "warm_up must not change any output");
+
+    println!("✓ Gadget and native hashes agree before and after warm-up");
+}
@@ -0,0 +1,76 @@
+//! Tests for cancellable file preparation (`api::prepare_file_cancellable`)
+//!
+//! This module tests that:
+//! 1. A flag set before the call returns `Cancelled` without preparing anything
+//! 2. A flag set from another thread stops a multi-megabyte preparation promptly
+//! 3. An unset flag prepares exactly what `prepare_file` does
+
+use kontor_crypto::{api, KontorPoRError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Deterministic, incompressible-looking test data.
+fn data(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u32).wrapping_mul(2_654_435_761).to_le_bytes()[3])
+        .collect()
+}
+
+#[test]
+fn test_cancelled_before_start() {
+    let cancel = AtomicBool::new(true);
+    let result = api::prepare_file_cancellable(&data(4096), "early.dat", &cancel);
+    assert!(
+        matches!(result, Err(KontorPoRError::Cancelled)),
+        "got {:?}",
+        result.map(|(_, metadata)| metadata.file_id)
+    );
+}
+
+#[test]
+fn test_cancelled_from_another_thread() {
+    println!("Testing cancellation of a preparation in progress");
+
+    let data = data(8 << 20);
+    let cancel = AtomicBool::new(false);
+    let (result, cancelled_at) = std::thread::scope(|scope| {
+        let worker = scope.spawn(|| {
+            let result = api::prepare_file_cancellable(&data, "large.dat", &cancel);
+            (result, Instant::now())
+        });
+        std::thread::sleep(Duration::from_millis(200));
+        let cancelled_at = Instant::now();
+        cancel.store(true, Ordering::Relaxed);
+        let (result, finished_at) = worker.join().unwrap();
+        (result, finished_at.saturating_duration_since(cancelled_at))
+    });
+
+    assert!(
+        matches!(result, Err(KontorPoRError::Cancelled)),
+        "8 MiB preparation finished before the flag was set: {:?}",
+        result.map(|(_, metadata)| metadata.file_id)
+    );
+    assert!(
+        cancelled_at < Duration::from_secs(5),
+        "preparation ran {:?} past cancellation",
+        cancelled_at
+    );
+
+    println!(
+        "✓ Preparation stopped {:?} after cancellation",
+        cancelled_at
+    );
+}
+
+#[test]
+fn test_uncancelled_matches_prepare_file() {
+    let data = data(100_000);
+    let cancel = AtomicBool::new(false);
+    let (prepared, metadata) = api::prepare_file_cancellable(&data, "same.dat", &cancel).unwrap();
+    let (expected_prepared, expected) = api::prepare_file(&data, "same.dat").unwrap();
+
+    assert_eq!(metadata, expected);
+    assert_eq!(prepared.root, expected_prepared.root);
+    assert_eq!(prepared.file_id, expected_prepared.file_id);
+    assert!(!cancel.load(Ordering::Relaxed));
+}
@@ -0,0 +1,157 @@
+//! Tests for preparing files from memory maps (`prepare_file_mmap`)
+//!
+//! This module tests that:
+//! 1. `prepare_file_mmap` produces exactly the metadata and tree of `prepare_file` on the
+//!    same bytes, for a few MB of data and for sizes at symbol and codeword boundaries
+//! 2. Streaming symbols into leaves gives the trees built from fully materialized symbols,
+//!    for GF(2^16) and raw (unencoded) files too
+//! 3. A missing input file fails with `IO`, and a set flag cancels
+//!    `prepare_file_mmap_cancellable`
+//! 4. (ignored) A large file is prepared with peak heap bounded by its tree, not its data
+
+use kontor_crypto::{
+    api::{self, ErasureField, HashProfile, PreparedFile, TreeArity},
+    erasure::{self, ErasureConfig},
+    merkle::build_file_tree,
+    KontorPoRError,
+};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+
+mod common;
+use common::fixtures::create_test_data;
+
+/// Writes `data` to a fresh file in the temp directory.
+fn write_temp(name: &str, data: &[u8]) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("kontor_mmap_{}_{}.bin", name, std::process::id()));
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+fn serialized(file: &PreparedFile) -> Vec<u8> {
+    bincode::serialize(file).unwrap()
+}
+
+#[test]
+fn test_mmap_matches_in_memory_preparation() {
+    println!("Testing prepare_file_mmap against prepare_file");
+
+    let codeword_bytes = 231 * 31;
+    let sizes = [
+        3 << 20,
+        0,
+        1,
+        31,
+        32,
+        codeword_bytes,
+        codeword_bytes + 1,
+        5 * codeword_bytes - 7,
+    ];
+    for size in sizes {
+        let data = create_test_data(size, Some(size as u64 + 1));
+        let path = write_temp(&format!("eq_{}", size), &data);
+
+        let (mapped, mapped_metadata) = api::prepare_file_mmap(&path, "input.dat").unwrap();
+        let (prepared, metadata) = api::prepare_file(&data, "input.dat").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mapped_metadata, metadata, "size {}", size);
+        assert_eq!(mapped.root, prepared.root, "size {}", size);
+        assert_eq!(serialized(&mapped), serialized(&prepared), "size {}", size);
+    }
+
+    println!("✓ Memory-mapped preparation is identical");
+}
+
+#[test]
+fn test_streamed_leaves_match_materialized_symbols() {
+    let data = create_test_data(200_000, Some(77));
+
+    // Default GF(2^8) code, binary and arity-4 trees
+    let symbols = erasure::encode_file_symbols(&data).unwrap();
+    for arity in [TreeArity::Binary, TreeArity::Arity4] {
+        let mut padded = symbols.clone();
+        padded.resize(arity.padded_len(symbols.len()), vec![0; 31]);
+        let (_, root) = build_file_tree(&padded, arity, HashProfile::Standard).unwrap();
+        let (prepared, _) = api::prepare_file_with_arity(&data, "a.dat", arity).unwrap();
+        assert_eq!(prepared.root, root, "{:?}", arity);
+    }
+
+    // GF(2^16) code
+    let config = ErasureConfig::field(ErasureField::Gf16);
+    let mut symbols = erasure::encode_file_symbols_with(&data, config).unwrap();
+    symbols.resize(symbols.len().next_power_of_two(), vec![0; 31]);
+    let (_, root) = build_file_tree(&symbols, TreeArity::Binary, HashProfile::Standard).unwrap();
+    let (prepared, _) = api::prepare_file_with_erasure(&data, "b.dat", config).unwrap();
+    assert_eq!(prepared.root, root);
+
+    // Raw files chunk the data directly, zero-padding the last symbol
+    let raw = &data[..1000];
+    let mut symbols: Vec<Vec<u8>> = raw.chunks(31).map(<[u8]>::to_vec).collect();
+    for symbol in &mut symbols {
+        symbol.resize(31, 0);
+    }
+    symbols.resize(symbols.len().next_power_of_two(), vec![0; 31]);
+    let (_, root) = build_file_tree(&symbols, TreeArity::Binary, HashProfile::Standard).unwrap();
+    let (prepared, _) = api::prepare_file_raw(raw, "c.dat").unwrap();
+    assert_eq!(prepared.root, root);
+
+    // Encoding accepts any byte container
+    assert_eq!(
+        erasure::encode_file_symbols(&data).unwrap(),
+        erasure::encode_file_symbols(data.as_slice()).unwrap()
+    );
+}
+
+#[test]
+fn test_mmap_errors() {
+    let path = std::env::temp_dir().join("kontor_mmap_does_not_exist.bin");
+    let result = api::prepare_file_mmap(&path, "missing.dat");
+    assert!(matches!(result, Err(KontorPoRError::IO(_))));
+
+    let path = write_temp("cancel", &create_test_data(100_000, Some(9)));
+    let cancel = AtomicBool::new(true);
+    let result = api::prepare_file_mmap_cancellable(&path, "cancel.dat", &cancel);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(KontorPoRError::Cancelled)));
+}
+
+/// Run with `cargo test --release --features memory-profiling --test prepare_mmap -- --ignored`
+/// to also check the peak heap.
+#[test]
+#[ignore = "writes and prepares a 128 MiB file"]
+fn test_mmap_large_file_smoke() {
+    let size = 128 << 20;
+    let path = std::env::temp_dir().join(format!("kontor_mmap_large_{}.bin", std::process::id()));
+    {
+        use std::io::Write;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+        let block = create_test_data(1 << 20, Some(5));
+        for _ in 0..(size >> 20) {
+            file.write_all(&block).unwrap();
+        }
+    }
+
+    kontor_crypto::metrics::reset_peak_memory();
+    let (prepared, metadata) = api::prepare_file_mmap(&path, "large.dat").unwrap();
+    let peak_mb = kontor_crypto::metrics::get_peak_memory_mb();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(metadata.original_size, size);
+    assert_eq!(
+        metadata.padded_len,
+        metadata.total_symbols().next_power_of_two()
+    );
+    let tree_mb = prepared.heap_size() >> 20;
+    println!(
+        "Prepared {} MiB: tree {} MiB, peak heap {} MiB",
+        size >> 20,
+        tree_mb,
+        peak_mb
+    );
+    // The leaves and the tree are held, never a copy of the data or its symbols
+    if cfg!(feature = "memory-profiling") {
+        assert!(peak_mb <= 2 * tree_mb + 16, "peak heap {} MiB", peak_mb);
+    }
+}
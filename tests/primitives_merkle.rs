@@ -6,8 +6,8 @@
 use ff::Field;
 use kontor_crypto::merkle::F;
 use kontor_crypto::merkle::{
-    build_tree, get_leaf_hash, get_padded_proof_for_leaf, hash_node, poseidon_hash_pair,
-    verify_merkle_proof_in_place,
+    build_tree, build_tree_from_leaves, get_leaf_hash, get_padded_proof_for_leaf, hash_node,
+    poseidon_hash_pair, verify_merkle_proof_in_place, CircuitMerkleProof, MerkleTree,
 };
 
 #[test]
@@ -742,3 +742,72 @@ fn test_adversarial_path_bit_flip() {
 
     println!("✓ Path bit tampering correctly detected");
 }
+
+fn depth_ten_tree() -> MerkleTree {
+    let leaves: Vec<F> = (0..1024u64).map(|i| F::from(i * 13 + 1)).collect();
+    build_tree_from_leaves(&leaves).unwrap()
+}
+
+#[test]
+fn test_tree_and_proof_serde_roundtrip() {
+    let tree = depth_ten_tree();
+    let bytes = bincode::serialize(&tree).unwrap();
+    let decoded: MerkleTree = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded.layers, tree.layers);
+    assert_eq!(decoded.root(), tree.root());
+
+    for index in [0, 1, 511, 1023] {
+        let proof = get_padded_proof_for_leaf(&tree, index, 10).unwrap();
+        let bytes = bincode::serialize(&proof).unwrap();
+        let decoded: CircuitMerkleProof = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.leaf, proof.leaf);
+        assert_eq!(decoded.siblings, proof.siblings);
+        assert_eq!(decoded.path_indices, proof.path_indices);
+        assert!(verify_merkle_proof_in_place(tree.root(), &decoded));
+    }
+}
+
+#[test]
+fn test_tree_and_proof_encoded_sizes() {
+    let tree = depth_ten_tree();
+    assert_eq!(tree.layers.len(), 11);
+
+    // 8-byte outer length + 11 × 8-byte layer lengths + 2047 nodes × 32 bytes
+    let tree_bytes = bincode::serialize(&tree).unwrap();
+    assert_eq!(tree_bytes.len(), 8 + 11 * 8 + 2047 * 32);
+    assert_eq!(tree_bytes.len(), 65_600);
+
+    // leaf (32) + siblings (8 + 10 × 32) + path_len (4) + packed path bits (8 + 2)
+    let proof = get_padded_proof_for_leaf(&tree, 700, 10).unwrap();
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    assert_eq!(proof_bytes.len(), 32 + 8 + 10 * 32 + 4 + 8 + 2);
+    assert_eq!(proof_bytes.len(), 374);
+}
+
+#[test]
+fn test_tree_and_proof_reject_non_canonical_encodings() {
+    let tree = build_tree_from_leaves(&[F::from(1u64), F::from(2u64)]).unwrap();
+    let mut bytes = bincode::serialize(&tree).unwrap();
+
+    // Overwrite the first leaf (after the outer and first layer lengths) with 2^256 - 1
+    bytes[16..48].copy_from_slice(&[0xff; 32]);
+    assert!(bincode::deserialize::<MerkleTree>(&bytes).is_err());
+
+    // The root layer must contain exactly one node
+    let malformed = MerkleTree {
+        layers: vec![vec![F::ONE, F::ONE]],
+    };
+    let bytes = bincode::serialize(&malformed).unwrap();
+    assert!(bincode::deserialize::<MerkleTree>(&bytes).is_err());
+
+    let proof = get_padded_proof_for_leaf(&tree, 1, 1).unwrap();
+    let mut bytes = bincode::serialize(&proof).unwrap();
+    bytes[..32].copy_from_slice(&[0xff; 32]);
+    assert!(bincode::deserialize::<CircuitMerkleProof>(&bytes).is_err());
+
+    // Path bits beyond path_len must be zero: bytes end with [path_len u32][len u64][bits]
+    let mut bytes = bincode::serialize(&proof).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] |= 0b10;
+    assert!(bincode::deserialize::<CircuitMerkleProof>(&bytes).is_err());
+}
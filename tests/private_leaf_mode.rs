@@ -0,0 +1,175 @@
+//! Tests for private challenge mode (blinded leaf commitments in public outputs)
+//!
+//! This module tests that:
+//! 1. Private-mode circuits output H(TAG_LEAF_COMMITMENT, leaf, blinding) instead of the leaf
+//! 2. Private-mode proofs verify end to end
+//! 3. Raw leaf values do not appear in a private-mode proof
+//! 4. Challenge batches cannot mix modes
+//! 5. Single-leaf files, whose blinding would have no private input, are rejected
+
+use ff::{Field, PrimeField};
+use kontor_crypto::{
+    api::{self, Challenge, ChallengeMode, FieldElement, PorSystem},
+    circuit::PorCircuit,
+    config, erasure, merkle,
+    poseidon::{calculate_leaf_commitment, derive_leaf_blinding},
+    utils::derive_leaf_index_for_file,
+    FileLedger, KontorPoRError,
+};
+use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
+use nova_snark::traits::circuit::StepCircuit;
+
+mod common;
+use common::fixtures::{create_circuit_public_inputs, create_witness_with_siblings};
+
+#[test]
+fn test_private_mode_outputs_leaf_commitment() {
+    println!("Testing that private mode replaces public leaves with commitments");
+
+    let leaf = FieldElement::from(42u64);
+    let sibling = FieldElement::from(7u64);
+    let seed = FieldElement::from(1234u64);
+
+    // Place the leaf on whichever side the in-circuit challenge derivation selects
+    let leaf_index = derive_leaf_index_for_file(0, 1, seed, FieldElement::ZERO, false);
+    let root = if leaf_index == 1 {
+        merkle::hash_node(sibling, leaf)
+    } else {
+        merkle::hash_node(leaf, sibling)
+    };
+
+    let witness = create_witness_with_siblings(leaf, vec![sibling], root, 1, vec![], 0, true);
+    let circuit = PorCircuit::<FieldElement>::new(config::Shape::new(1, 1, 0), Some(vec![witness]))
+        .with_private_leaves(true);
+
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let z = create_circuit_public_inputs(
+        &mut cs,
+        root,
+        FieldElement::ZERO,
+        seed,
+        &[0],
+        &[1],
+        &[FieldElement::ZERO],
+    );
+
+    let outputs = circuit.synthesize(&mut cs, &z).unwrap();
+    assert!(
+        cs.is_satisfied(),
+        "Private-mode circuit should be satisfied"
+    );
+
+    // Single-slot layout: [2 fixed] + [1 ledger] + [1 depth] + [1 seed] + [1 leaf]
+    let leaf_idx = 2 + 1 + 1 + 1;
+    let expected = calculate_leaf_commitment(leaf, derive_leaf_blinding(seed, sibling));
+    assert_eq!(
+        outputs[leaf_idx].get_value(),
+        Some(expected),
+        "Public output should be the blinded commitment"
+    );
+    assert_ne!(
+        outputs[leaf_idx].get_value(),
+        Some(leaf),
+        "Raw leaf must not be exposed in private mode"
+    );
+
+    println!("✓ Private mode exposes only the leaf commitment");
+}
+
+#[test]
+fn test_private_mode_proof_verifies_and_hides_leaves() {
+    println!("Testing private-mode prove/verify and absence of raw leaves in the proof");
+
+    let data: Vec<u8> = (0..2000u32).map(|i| (i * 31 % 251) as u8 + 1).collect();
+    let (prepared, metadata) = api::prepare_file(&data, "secret.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(99u64))
+        .with_mode(ChallengeMode::Private);
+
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(
+        system
+            .verify(&proof, std::slice::from_ref(&challenge))
+            .unwrap(),
+        "Private-mode proof should verify"
+    );
+
+    // No non-zero leaf of the file may appear anywhere in the serialized proof
+    let proof_bytes = proof.to_bytes().unwrap();
+    let leaves: Vec<FieldElement> = erasure::encode_file_symbols(&data)
+        .unwrap()
+        .iter()
+        .map(|symbol| merkle::get_leaf_hash(symbol).unwrap())
+        .filter(|leaf| *leaf != FieldElement::ZERO)
+        .collect();
+    assert!(!leaves.is_empty());
+    for leaf in &leaves {
+        let repr = leaf.to_repr();
+        assert!(
+            !proof_bytes
+                .windows(repr.as_ref().len())
+                .any(|window| window == repr.as_ref()),
+            "Raw leaf value found in private-mode proof bytes"
+        );
+    }
+
+    // A public-mode challenge cannot be used to verify the private-mode proof
+    let public_challenge = Challenge::new_test(metadata, 1000, 3, FieldElement::from(99u64));
+    assert_ne!(public_challenge.id(), challenge.id());
+    assert!(system.verify(&proof, &[public_challenge]).is_err());
+
+    println!("✓ Private-mode proof verifies without leaking leaf values");
+}
+
+#[test]
+fn test_mixed_challenge_modes_rejected() {
+    let (prepared1, metadata1) = api::prepare_file(b"first private file", "a.dat").unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"second public file", "b.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 1, FieldElement::from(1u64))
+            .with_mode(ChallengeMode::Private),
+        Challenge::new_test(metadata2, 1000, 1, FieldElement::from(1u64)),
+    ];
+
+    let result = PorSystem::new(&ledger).prove(vec![&prepared1, &prepared2], &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "mode"),
+        "Mixed modes must be rejected"
+    );
+}
+
+#[test]
+fn test_private_mode_rejects_single_leaf_files() {
+    let (prepared, metadata) = api::prepare_file_raw(b"x", "tiny.dat").unwrap();
+    assert_eq!(metadata.padded_len, 1, "fixture must be a one-leaf file");
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let challenge = Challenge::new_test(metadata, 1000, 1, FieldElement::from(1u64));
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system
+        .verify(&proof, std::slice::from_ref(&challenge))
+        .unwrap());
+
+    let private = challenge.with_mode(ChallengeMode::Private);
+    let result = system.prove(vec![&prepared], std::slice::from_ref(&private));
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidInput(_))),
+        "A one-leaf file has no private sibling to blind its leaf with"
+    );
+}
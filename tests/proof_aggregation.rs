@@ -0,0 +1,139 @@
+//! Tests for aggregating independently generated proofs
+//!
+//! This module tests that:
+//! 1. Proofs from different provers against one ledger root aggregate and verify
+//! 2. Tampering with either inner proof breaks the aggregate
+//! 3. Ledger-root and challenge-set mismatches are rejected eagerly
+
+use kontor_crypto::{
+    api::{self, aggregate_proofs, AggregatedProof, Challenge, FieldElement, PorSystem, Proof},
+    FileLedger, KontorPoRError,
+};
+
+struct Node {
+    files: Vec<(api::PreparedFile, api::FileMetadata)>,
+    seed: u64,
+}
+
+impl Node {
+    fn challenges(&self) -> Vec<Challenge> {
+        self.files
+            .iter()
+            .map(|(_, metadata)| {
+                Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(self.seed))
+            })
+            .collect()
+    }
+
+    fn prove(&self, ledger: &FileLedger) -> Proof {
+        let files: Vec<_> = self.files.iter().map(|(prepared, _)| prepared).collect();
+        PorSystem::new(ledger)
+            .prove(files, &self.challenges())
+            .unwrap()
+    }
+}
+
+fn two_nodes() -> (FileLedger, Node, Node) {
+    let mut files: Vec<_> = (0..4u8)
+        .map(|i| api::prepare_file(&[i + 1; 150], &format!("node_file_{}.dat", i)).unwrap())
+        .collect();
+
+    let mut ledger = FileLedger::new();
+    for (_, metadata) in &files {
+        ledger.add_file(metadata).unwrap();
+    }
+
+    let node_b = Node {
+        files: files.split_off(2),
+        seed: 202,
+    };
+    let node_a = Node { files, seed: 101 };
+    (ledger, node_a, node_b)
+}
+
+fn copy(aggregated: &AggregatedProof) -> AggregatedProof {
+    AggregatedProof::from_bytes(&aggregated.to_bytes().unwrap()).unwrap()
+}
+
+#[test]
+fn test_aggregate_two_provers_and_detect_tampering() {
+    println!("Testing aggregation of proofs from two independent provers");
+
+    let (ledger, node_a, node_b) = two_nodes();
+    let proofs = vec![node_a.prove(&ledger), node_b.prove(&ledger)];
+    let challenge_sets = vec![node_a.challenges(), node_b.challenges()];
+
+    let aggregated = aggregate_proofs(&proofs, &challenge_sets).unwrap();
+    assert_eq!(aggregated.ledger_root(), Some(ledger.root()));
+
+    let system = PorSystem::new(&ledger);
+    assert!(system
+        .verify_aggregated(&aggregated, &challenge_sets)
+        .unwrap());
+
+    // Transport round-trip preserves the aggregate
+    let decoded = copy(&aggregated);
+    assert_eq!(decoded.commitment, aggregated.commitment);
+    assert!(system.verify_aggregated(&decoded, &challenge_sets).unwrap());
+
+    for i in 0..2 {
+        // Tampering with an inner proof breaks the commitment...
+        let mut tampered = copy(&aggregated);
+        tampered.proofs[i].ledger_indices.swap(0, 1);
+        assert!(
+            !system
+                .verify_aggregated(&tampered, &challenge_sets)
+                .unwrap(),
+            "Tampered inner proof {} must break the commitment",
+            i
+        );
+
+        // ...and re-committing to the tampered proof still fails inner verification
+        let recommitted = aggregate_proofs(&tampered.proofs, &challenge_sets).unwrap();
+        assert!(
+            !matches!(
+                system.verify_aggregated(&recommitted, &challenge_sets),
+                Ok(true)
+            ),
+            "Tampered inner proof {} must fail verification",
+            i
+        );
+    }
+
+    println!("✓ Aggregate verifies and tampering with either inner proof breaks it");
+}
+
+#[test]
+fn test_aggregate_rejects_mismatches_eagerly() {
+    println!("Testing eager rejection of ledger-root and challenge-set mismatches");
+
+    let (mut ledger, node_a, node_b) = two_nodes();
+    let proof_a = node_a.prove(&ledger);
+
+    // Node B proves after the ledger has moved on
+    let (_, late) = api::prepare_file(b"late file", "late.dat").unwrap();
+    ledger.add_file(&late).unwrap();
+    let proof_b = node_b.prove(&ledger);
+
+    let challenge_sets = vec![node_a.challenges(), node_b.challenges()];
+    let result = aggregate_proofs(&[proof_a, proof_b], &challenge_sets);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidLedgerRoot { .. })),
+        "Different ledger roots must be rejected"
+    );
+
+    // A proof paired with another prover's challenge set
+    let proof_b = node_b.prove(&ledger);
+    let result = aggregate_proofs(std::slice::from_ref(&proof_b), &[node_a.challenges()]);
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+
+    // The same challenges answered twice
+    let duplicate = vec![node_b.challenges(), node_b.challenges()];
+    let result = aggregate_proofs(&[node_b.prove(&ledger), proof_b], &duplicate);
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+
+    // Count mismatch
+    assert!(aggregate_proofs(&[], &[]).is_err());
+
+    println!("✓ Mismatches rejected before any commitment is computed");
+}
@@ -0,0 +1,165 @@
+//! Tests for proof bindings on a ledger holding a single file (`ProofBinding`)
+//!
+//! This module tests that:
+//! 1. A single challenge binds to the file root by default and keeps verifying as the
+//!    ledger grows, without historical roots
+//! 2. A single challenge asking for `ProofBinding::LedgerRoot` binds to the one-file
+//!    ledger's root and needs that root retained once the ledger grows
+//! 3. Several challenges against a one-file ledger bind to the ledger root and verify
+//! 4. Bindings are part of challenge IDs, must agree within a batch, and file-root
+//!    binding is refused for several challenges
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, FileMetadata, PorSystem, PreparedFile, ProofBinding},
+    FileLedger, KontorPoRError,
+};
+
+fn one_file_ledger() -> (PreparedFile, FileMetadata, FileLedger) {
+    let (prepared, metadata) =
+        api::prepare_file(b"the only file in this ledger", "only.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    assert_eq!(ledger.depth(), 0);
+    assert_eq!(ledger.root(), metadata.commitment());
+    (prepared, metadata, ledger)
+}
+
+/// Adds a second file to the ledger, then forgets the earlier roots unless `keep_history`.
+fn grow(ledger: &mut FileLedger, keep_history: bool) {
+    let (_, other) = api::prepare_file(b"a file added later", "later.dat").unwrap();
+    ledger.add_file(&other).unwrap();
+    assert_eq!(ledger.depth(), 1);
+    if !keep_history {
+        ledger.set_historical_roots(vec![]).unwrap();
+    }
+}
+
+#[test]
+fn test_file_root_binding_survives_growth() {
+    println!("Testing the default file-root binding on a one-file ledger");
+
+    let (prepared, metadata, mut ledger) = one_file_ledger();
+    let challenges = vec![Challenge::new_test(
+        metadata.clone(),
+        1000,
+        2,
+        FieldElement::from(3u64),
+    )];
+    assert_eq!(
+        ProofBinding::for_challenges(&challenges),
+        ProofBinding::FileRoot
+    );
+
+    let proof = PorSystem::new(&ledger)
+        .prove(vec![&prepared], &challenges)
+        .unwrap();
+    assert_eq!(proof.binding(), ProofBinding::FileRoot);
+    assert_eq!(proof.shape.aggregated_tree_depth, 0);
+    assert_eq!(proof.ledger_root, metadata.root);
+    assert!(PorSystem::new(&ledger).verify(&proof, &challenges).unwrap());
+
+    // The proof never referenced the ledger root, so growth needs no historical roots
+    grow(&mut ledger, false);
+    assert!(PorSystem::new(&ledger).verify(&proof, &challenges).unwrap());
+
+    println!("✓ File-root proof verified before and after growth");
+}
+
+#[test]
+fn test_ledger_root_binding_needs_historical_root() {
+    println!("Testing a ledger-bound single challenge on a one-file ledger");
+
+    let (prepared, metadata, ledger) = one_file_ledger();
+    let challenges = vec![
+        Challenge::new_test(metadata, 1000, 2, FieldElement::from(3u64))
+            .with_binding(ProofBinding::LedgerRoot),
+    ];
+
+    let proof = PorSystem::new(&ledger)
+        .prove(vec![&prepared], &challenges)
+        .unwrap();
+    assert_eq!(proof.binding(), ProofBinding::LedgerRoot);
+    assert_eq!(proof.shape.aggregated_tree_depth, 1);
+    assert_eq!(proof.ledger_root, ledger.root());
+    assert_eq!(proof.ledger_indices, vec![0]);
+    assert!(PorSystem::new(&ledger).verify(&proof, &challenges).unwrap());
+
+    // Once the ledger grows the proof's root must be retained
+    let mut forgetful = ledger.clone();
+    grow(&mut forgetful, false);
+    let result = PorSystem::new(&forgetful).verify(&proof, &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidLedgerRoot { .. })),
+        "expected InvalidLedgerRoot, got {:?}",
+        result
+    );
+
+    let mut retaining = ledger.clone();
+    grow(&mut retaining, true);
+    assert!(PorSystem::new(&retaining)
+        .verify(&proof, &challenges)
+        .unwrap());
+
+    println!("✓ Ledger-bound proof needs its root retained after growth");
+}
+
+#[test]
+fn test_multi_challenge_request_on_one_file_ledger() {
+    println!("Testing several challenges against a one-file ledger");
+
+    let (prepared, metadata, ledger) = one_file_ledger();
+    let challenges: Vec<Challenge> = [5u64, 6]
+        .iter()
+        .map(|&seed| Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(seed)))
+        .collect();
+    assert_eq!(
+        ProofBinding::for_challenges(&challenges),
+        ProofBinding::LedgerRoot
+    );
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    assert_eq!(proof.binding(), ProofBinding::LedgerRoot);
+    assert_eq!(proof.ledger_root, ledger.root());
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let plan = api::plan::build_plan(&challenges, &ledger).unwrap();
+    assert_eq!(plan.binding, ProofBinding::LedgerRoot);
+    assert_eq!(plan.aggregated_tree_depth, 1);
+
+    println!("✓ Multi-challenge proof bound to the one-file ledger root");
+}
+
+#[test]
+fn test_binding_requests_are_validated() {
+    let (_, metadata, ledger) = one_file_ledger();
+    let base = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(7u64));
+    let other = Challenge::new_test(metadata, 1000, 2, FieldElement::from(8u64));
+
+    // Requested bindings change the challenge ID; the default leaves it unchanged
+    let file_bound = base.clone().with_binding(ProofBinding::FileRoot);
+    let ledger_bound = base.clone().with_binding(ProofBinding::LedgerRoot);
+    assert_ne!(file_bound.id(), base.id());
+    assert_ne!(ledger_bound.id(), base.id());
+    assert_ne!(file_bound.id(), ledger_bound.id());
+
+    // File-root binding cannot cover several challenges
+    let result = api::plan::build_plan(
+        &[
+            file_bound.clone(),
+            other.clone().with_binding(ProofBinding::FileRoot),
+        ],
+        &ledger,
+    );
+    assert!(matches!(result, Err(KontorPoRError::InvalidInput(_))));
+
+    // A batch must agree on its binding
+    let result = api::plan::build_plan(&[ledger_bound, other], &ledger);
+    assert!(
+        matches!(result, Err(KontorPoRError::ChallengeMismatch { ref field }) if field == "binding")
+    );
+
+    let plan = api::plan::build_plan(&[file_bound], &ledger).unwrap();
+    assert_eq!(plan.binding, ProofBinding::FileRoot);
+    assert_eq!(plan.aggregated_tree_depth, 0);
+}
@@ -0,0 +1,154 @@
+//! Tests for enumerating the ledger files a proof covers
+//!
+//! This module tests that:
+//! 1. `Proof::covered_indices` and `file_depths` survive serialization, and
+//!    `Proof::resolve_files` maps the indices back to the challenged files at the current
+//!    root; verification rejects altered file depths
+//! 2. After the ledger changes, canonical ledgers cannot resolve the now-historical root
+//!    (`CannotResolveHistorical`), while insertion-order ledgers resolve it until a covered
+//!    slot is tombstoned
+//! 3. File-root-bound proofs cover no ledger index and resolve by root and depth
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, FileMetadata, PorSystem, PreparedFile, Proof},
+    FileLedger, IndexPolicy, KontorPoRError,
+};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_single_file_ledger, create_test_files};
+
+fn challenges_for(metadatas: &[&FileMetadata]) -> Vec<Challenge> {
+    metadatas
+        .iter()
+        .map(|m| Challenge::new_test((*m).clone(), 1000, 2, FieldElement::from(6u64)))
+        .collect()
+}
+
+fn prove(
+    ledger: &FileLedger,
+    files: &BTreeMap<String, PreparedFile>,
+    challenges: &[Challenge],
+) -> Proof {
+    let refs = challenges
+        .iter()
+        .map(|c| &files[&c.file_metadata.file_id])
+        .collect();
+    let proof = PorSystem::new(ledger).prove(refs, challenges).unwrap();
+    Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap()
+}
+
+#[test]
+fn test_resolve_files_at_current_root() {
+    println!("Testing resolution of a proof's ledger indices to file IDs");
+
+    let (files, metadatas) = create_test_files(4, 300, 40);
+    let mut ledger = FileLedger::new();
+    for metadata in &metadatas {
+        ledger.add_file(metadata).unwrap();
+    }
+    let challenges = challenges_for(&[&metadatas[3], &metadatas[1], &metadatas[2]]);
+    let proof = prove(&ledger, &files, &challenges);
+
+    // Slots follow the file order, and the fourth slot is padding
+    let mut covered: Vec<&FileMetadata> = challenges.iter().map(|c| &c.file_metadata).collect();
+    covered.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+    let expected_indices: Vec<usize> = covered
+        .iter()
+        .map(|m| ledger.lookup(&m.file_id).unwrap().0)
+        .collect();
+    assert_eq!(proof.covered_indices(), expected_indices);
+    assert_eq!(proof.ledger_indices.len(), 4);
+    assert_eq!(proof.file_depths.len(), 4);
+    assert_eq!(proof.file_depths[3], 0);
+    for (slot, metadata) in covered.iter().enumerate() {
+        assert_eq!(proof.file_depths[slot], metadata.depth());
+        assert_eq!(
+            ledger.file_id_at(proof.covered_indices()[slot]),
+            Some(metadata.file_id.as_str())
+        );
+    }
+
+    let resolved = proof.resolve_files(&ledger).unwrap();
+    let expected: Vec<String> = covered.iter().map(|m| m.file_id.clone()).collect();
+    assert_eq!(resolved, expected);
+
+    // The depths are part of the checked statement
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&proof, &challenges).unwrap());
+    let mut tampered = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    tampered.file_depths[0] += 1;
+    assert!(!system.verify(&tampered, &challenges).unwrap());
+    assert!(matches!(
+        tampered.resolve_files(&ledger),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // Unknown roots are rejected
+    let mut unknown = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    unknown.ledger_root += FieldElement::from(1u64);
+    assert!(matches!(
+        unknown.resolve_files(&ledger),
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+
+    println!("✓ Proof resolved to its challenged files");
+}
+
+#[test]
+fn test_resolve_files_at_historical_root() {
+    println!("Testing resolution against a historical ledger root");
+
+    let (files, metadatas) = create_test_files(3, 300, 50);
+    let challenges = challenges_for(&[&metadatas[0], &metadatas[1]]);
+
+    // Canonical ordering: adding a file may shift indices, so the old root is unresolvable
+    let mut canonical = FileLedger::new();
+    canonical.add_file(&metadatas[0]).unwrap();
+    canonical.add_file(&metadatas[1]).unwrap();
+    let proof = prove(&canonical, &files, &challenges);
+    canonical.add_file(&metadatas[2]).unwrap();
+    assert!(canonical.is_valid_root(proof.ledger_root));
+    assert!(matches!(
+        proof.resolve_files(&canonical),
+        Err(KontorPoRError::CannotResolveHistorical { index, .. })
+            if index == proof.covered_indices()[0]
+    ));
+
+    // Insertion order: slots are stable until a covered file is removed
+    let mut stable = FileLedger::new_with_policy(IndexPolicy::InsertionOrder);
+    stable.add_file(&metadatas[0]).unwrap();
+    stable.add_file(&metadatas[1]).unwrap();
+    let proof = prove(&stable, &files, &challenges);
+    let expected = proof.resolve_files(&stable).unwrap();
+    stable.add_file(&metadatas[2]).unwrap();
+    assert_ne!(stable.root(), proof.ledger_root);
+    assert_eq!(proof.resolve_files(&stable).unwrap(), expected);
+
+    let removed = &expected[1];
+    let removed_index = stable.lookup(removed).unwrap().0;
+    stable.remove_file(removed).unwrap();
+    assert!(stable.is_valid_root(proof.ledger_root));
+    assert!(matches!(
+        proof.resolve_files(&stable),
+        Err(KontorPoRError::CannotResolveHistorical { index, .. }) if index == removed_index
+    ));
+
+    println!("✓ Historical roots resolve only while their slots are unchanged");
+}
+
+#[test]
+fn test_file_root_bound_proof_coverage() {
+    let (files, metadatas) = create_test_files(1, 500, 60);
+    let ledger = create_single_file_ledger(&metadatas[0]);
+    let challenges = challenges_for(&[&metadatas[0]]);
+    let proof = prove(&ledger, &files, &challenges);
+
+    assert!(proof.covered_indices().is_empty());
+    assert_eq!(proof.file_depths[0], metadatas[0].depth());
+    assert_eq!(
+        proof.resolve_files(&ledger).unwrap(),
+        vec![metadatas[0].file_id.clone()]
+    );
+    assert!(proof.resolve_files(&FileLedger::new()).unwrap().is_empty());
+}
@@ -0,0 +1,207 @@
+//! Tests for per-challenge slices of multi-file proofs
+//!
+//! This module tests that:
+//! 1. A slice extracted for each file of a 3-file proof verifies on its own
+//! 2. Tampering any slice field, or pairing it with another challenge, is rejected
+//! 3. A proof whose recorded public outputs were rewritten fails both slice and full
+//!    verification
+//! 4. Slices of proofs against roots the verifier does not accept fail with `InvalidLedgerRoot`
+
+use kontor_crypto::{
+    api::{verify_slice, Challenge, FieldElement, PorSystem, Proof, ProofSlice},
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+/// A 3-file proof with its challenges and the ledger it was generated against.
+fn three_file_proof() -> (Proof, Vec<Challenge>, FileLedger) {
+    let (files, metadatas) = create_test_files(3, 400, 61);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let seed = FieldElement::from(13u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let prepared = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(prepared, &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+    (proof, challenges, ledger)
+}
+
+fn assert_rejected(result: kontor_crypto::Result<bool>, what: &str) {
+    assert!(
+        !matches!(result, Ok(true)),
+        "Slice with {} must be rejected",
+        what
+    );
+}
+
+#[test]
+fn test_slices_of_each_file_verify() {
+    println!("Testing slice extraction for every file of a 3-file proof");
+
+    let (proof, challenges, ledger) = three_file_proof();
+    let root = ledger.root();
+    let mut slots = Vec::new();
+    for challenge in &challenges {
+        let slice = proof.public_slice(challenge).unwrap();
+        assert_eq!(slice.challenge_id, challenge.id());
+        assert_eq!(slice.ledger_root, root);
+        assert_eq!(slice.seed, challenge.seed);
+        assert!(
+            verify_slice(&proof, &slice, challenge, &[root]).unwrap(),
+            "Slice for slot {} must verify",
+            slice.slot
+        );
+        slots.push(slice.slot);
+        println!(
+            "  slot {}: ledger index {}, depth {}",
+            slice.slot, slice.ledger_index, slice.depth
+        );
+    }
+    slots.sort_unstable();
+    slots.dedup();
+    assert_eq!(slots.len(), 3, "Each file has its own slot");
+
+    println!("✓ All slices verified");
+}
+
+#[test]
+fn test_tampered_slices_rejected() {
+    println!("Testing rejection of tampered slices");
+
+    let (proof, challenges, ledger) = three_file_proof();
+    let root = ledger.root();
+    let challenge = &challenges[0];
+    let slice = proof.public_slice(challenge).unwrap();
+    let other = proof.public_slice(&challenges[1]).unwrap();
+
+    let tampered: Vec<(&str, ProofSlice)> = vec![
+        (
+            "another file's slot",
+            ProofSlice {
+                slot: other.slot,
+                ..slice.clone()
+            },
+        ),
+        (
+            "an out-of-range slot",
+            ProofSlice {
+                slot: 4,
+                ..slice.clone()
+            },
+        ),
+        (
+            "a tampered leaf value",
+            ProofSlice {
+                leaf_output: slice.leaf_output + FieldElement::from(1u64),
+                ..slice.clone()
+            },
+        ),
+        (
+            "another file's ledger index",
+            ProofSlice {
+                ledger_index: other.ledger_index,
+                ..slice.clone()
+            },
+        ),
+        (
+            "a wrong depth",
+            ProofSlice {
+                depth: slice.depth + 1,
+                ..slice.clone()
+            },
+        ),
+        (
+            "a wrong seed",
+            ProofSlice {
+                seed: slice.seed + FieldElement::from(1u64),
+                ..slice.clone()
+            },
+        ),
+        (
+            "a wrong ledger root",
+            ProofSlice {
+                ledger_root: slice.ledger_root + FieldElement::from(1u64),
+                ..slice.clone()
+            },
+        ),
+        (
+            "another challenge's ID",
+            ProofSlice {
+                challenge_id: challenges[1].id(),
+                ..slice.clone()
+            },
+        ),
+        (
+            "another proof's digest",
+            ProofSlice {
+                proof_digest: [0u8; 32],
+                ..slice.clone()
+            },
+        ),
+    ];
+
+    for (what, bad) in &tampered {
+        assert_rejected(verify_slice(&proof, bad, challenge, &[root]), what);
+    }
+
+    // A valid slice presented for another challenge
+    assert_rejected(
+        verify_slice(&proof, &slice, &challenges[1], &[root]),
+        "another challenge",
+    );
+
+    // An uncovered challenge has no slice
+    let uncovered = Challenge::new_test(
+        challenge.file_metadata.clone(),
+        1001,
+        2,
+        FieldElement::from(13u64),
+    );
+    assert!(matches!(
+        proof.public_slice(&uncovered),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    println!("✓ {} tampered slices rejected", tampered.len() + 1);
+}
+
+#[test]
+fn test_rewritten_public_outputs_rejected() {
+    println!("Testing proofs with rewritten public outputs");
+
+    let (proof, challenges, ledger) = three_file_proof();
+    let root = ledger.root();
+    let challenge = &challenges[2];
+    let slot = proof.public_slice(challenge).unwrap().slot;
+
+    // Rewrite the slot's leaf output and extract a consistent slice from the forgery
+    let mut forged = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    let leaf_index = forged.public_outputs.len() - forged.ledger_indices.len() + slot;
+    forged.public_outputs[leaf_index] += FieldElement::from(1u64);
+    let forged_slice = forged.public_slice(challenge).unwrap();
+
+    assert!(!verify_slice(&forged, &forged_slice, challenge, &[root]).unwrap());
+    assert!(!PorSystem::new(&ledger)
+        .verify(&forged, &challenges)
+        .unwrap());
+
+    println!("✓ Rewritten outputs rejected by slice and full verification");
+}
+
+#[test]
+fn test_slice_against_unaccepted_root_fails() {
+    let (proof, challenges, _) = three_file_proof();
+    let slice = proof.public_slice(&challenges[0]).unwrap();
+
+    let result = verify_slice(&proof, &slice, &challenges[0], &[FieldElement::from(1u64)]);
+    assert!(matches!(
+        result,
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+}
@@ -0,0 +1,166 @@
+//! Tests for the public statements of proofs (`Proof::statement`)
+//!
+//! This module tests that:
+//! 1. The prove-side statement and the one returned by `verify_statement` have equal digests
+//! 2. Statements list the challenged files in slot order with their ledger rc and index
+//! 3. Tampering with any statement field, or challenging with another seed, changes the digest
+//! 4. Statements need exactly the proof's challenges, and invalid proofs return none
+//! 5. Single-file statements are rooted at the file
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, Proof, ProofStatement},
+    FileLedger,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+/// An edit to one field of a statement.
+type Tamper = fn(&mut ProofStatement);
+
+/// A 3-file proof for `seed` with its challenges and the ledger it was generated against.
+fn three_file_proof(seed: u64) -> (Proof, Vec<Challenge>, FileLedger) {
+    let (files, metadatas) = create_test_files(3, 400, 71);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(seed)))
+        .collect();
+    let prepared = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+
+    let proof = PorSystem::new(&ledger)
+        .prove(prepared, &challenges)
+        .unwrap();
+    (proof, challenges, ledger)
+}
+
+#[test]
+fn test_prove_and_verify_statements_agree() {
+    println!("Testing prove-side and verify-side statements");
+
+    let (proof, challenges, ledger) = three_file_proof(17);
+    let statement = proof.statement(&challenges).unwrap();
+    let verified = PorSystem::new(&ledger)
+        .verify_statement(&proof, &challenges)
+        .unwrap()
+        .expect("valid proof must return its statement");
+    assert_eq!(verified, statement);
+    assert_eq!(verified.digest(), statement.digest());
+
+    assert_eq!(statement.ledger_root, ledger.root());
+    assert_eq!(statement.num_steps, 2);
+    assert_eq!(statement.final_state, proof.public_outputs[1]);
+    assert_eq!(statement.entries.len(), 3);
+    let mut slots = proof.challenge_slots.clone();
+    slots.sort_unstable();
+    for (entry, slot) in statement.entries.iter().zip(slots) {
+        let (index, rc) = ledger.lookup(&entry.file_id).unwrap();
+        assert_eq!(entry.rc, rc);
+        assert_eq!(entry.ledger_index, index);
+        assert_eq!(entry.ledger_index, proof.ledger_indices[slot]);
+        assert_eq!(entry.seed, FieldElement::from(17u64));
+    }
+
+    // The statement survives serialization
+    let json = serde_json::to_string(&statement).unwrap();
+    let decoded: ProofStatement = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.digest(), statement.digest());
+
+    println!("✓ Statements agree: digest {:?}", statement.digest());
+}
+
+#[test]
+fn test_tampering_changes_digest() {
+    println!("Testing that tampered statements and challenges change the digest");
+
+    let (proof, challenges, _ledger) = three_file_proof(17);
+    let statement = proof.statement(&challenges).unwrap();
+    let digest = statement.digest();
+
+    let tampers: Vec<(&str, Tamper)> = vec![
+        ("ledger root", |s| s.ledger_root += FieldElement::from(1u64)),
+        ("final state", |s| s.final_state += FieldElement::from(1u64)),
+        ("step count", |s| s.num_steps += 1),
+        ("file ID", |s| s.entries[0].file_id.push('x')),
+        ("rc", |s| s.entries[1].rc += FieldElement::from(1u64)),
+        ("ledger index", |s| s.entries[2].ledger_index += 1),
+        ("depth", |s| s.entries[0].depth += 1),
+        ("seed", |s| s.entries[1].seed += FieldElement::from(1u64)),
+        ("entry order", |s| s.entries.swap(0, 1)),
+        ("dropped entry", |s| {
+            s.entries.pop();
+        }),
+    ];
+    for (what, tamper) in tampers {
+        let mut tampered = statement.clone();
+        tamper(&mut tampered);
+        assert_ne!(
+            tampered.digest(),
+            digest,
+            "Tampered {} must change the digest",
+            what
+        );
+    }
+
+    // Challenges with another seed make another statement
+    let (reseeded_proof, reseeded, _) = three_file_proof(18);
+    assert_ne!(
+        reseeded_proof.statement(&reseeded).unwrap().digest(),
+        digest
+    );
+
+    // A statement needs the proof's own challenges, in order
+    assert!(proof.statement(&reseeded).is_err());
+    assert!(proof.statement(&challenges[..2]).is_err());
+    let mut reordered = challenges.clone();
+    reordered.swap(0, 2);
+    assert!(proof.statement(&reordered).is_err());
+    assert!(proof.statement(&[]).is_err());
+
+    println!("✓ Every tamper changed the digest");
+}
+
+#[test]
+fn test_invalid_proof_has_no_statement() {
+    let (mut proof, challenges, ledger) = three_file_proof(17);
+    let honest = proof.statement(&challenges).unwrap();
+
+    // Rewriting the final state changes the prove-side digest, but full verification
+    // refuses to vouch for it
+    proof.public_outputs[1] += FieldElement::from(1u64);
+    assert_ne!(
+        proof.statement(&challenges).unwrap().digest(),
+        honest.digest()
+    );
+    let result = PorSystem::new(&ledger).verify_statement(&proof, &challenges);
+    assert!(
+        matches!(result, Ok(None)),
+        "got {:?}",
+        result.map(|s| s.is_some())
+    );
+}
+
+#[test]
+fn test_single_file_statement() {
+    let data = b"single-file statement test data";
+    let (prepared, metadata) = api::prepare_file(data, "statement.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(5u64));
+
+    let system = PorSystem::new(&ledger);
+    let challenges = [challenge];
+    let proof = system.prove(vec![&prepared], &challenges).unwrap();
+    let statement = system
+        .verify_statement(&proof, &challenges)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(statement, proof.statement(&challenges).unwrap());
+    assert_eq!(statement.ledger_root, metadata.root);
+    assert_eq!(statement.num_steps, 3);
+    assert_eq!(statement.entries.len(), 1);
+    assert_eq!(statement.entries[0].file_id, metadata.file_id);
+    assert_eq!(statement.entries[0].rc, metadata.commitment());
+    assert_eq!(statement.entries[0].ledger_index, 0);
+}
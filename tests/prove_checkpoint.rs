@@ -0,0 +1,135 @@
+//! Tests for checkpointed proving
+//!
+//! This module tests that:
+//! 1. A proof checkpointed every 2 of 6 steps can be resumed from disk, folds only the
+//!    remaining steps and verifies normally
+//! 2. Resuming with different challenges or a different shape fails with `CheckpointMismatch`
+//! 3. Corrupted or missing checkpoints and a zero interval are rejected
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem},
+    metrics::{set_metrics_sink, MetricsSink, Phase, TimingEvent},
+    KontorPoRError,
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+#[derive(Default)]
+struct CollectingSink {
+    events: Mutex<Vec<TimingEvent>>,
+}
+
+impl MetricsSink for CollectingSink {
+    fn record(&self, event: TimingEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "kontor_checkpoint_{}_{}.ckpt",
+        name,
+        std::process::id()
+    ))
+}
+
+// The only test in this file that folds steps, so the process-wide metrics sink sees
+// no events from other tests.
+#[test]
+fn test_resume_from_checkpoint() {
+    println!("Testing a 6-step proof checkpointed every 2 steps");
+
+    let (files, metadatas) = create_test_files(2, 300, 70);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(11u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 6, seed))
+        .collect();
+    let path = checkpoint_path("resume");
+    let _ = std::fs::remove_file(&path);
+
+    let proof = system
+        .prove_with_checkpoint(files.values().collect(), &challenges, &path, 2)
+        .unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+    assert!(
+        path.exists(),
+        "checkpoint is kept after the proof completes"
+    );
+    drop(proof);
+
+    // The last checkpoint was taken after 4 of 6 steps; resuming folds only steps 4 and 5
+    let sink = Arc::new(CollectingSink::default());
+    set_metrics_sink(Some(sink.clone()));
+    let resumed = system.resume_prove(&path, files.values().collect(), &challenges);
+    set_metrics_sink(None);
+    let resumed = resumed.unwrap();
+
+    let folded: Vec<usize> = sink
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| e.phase == Phase::Fold)
+        .filter_map(|e| e.step)
+        .collect();
+    assert_eq!(folded, vec![4, 5]);
+    assert!(system.verify(&resumed, &challenges).unwrap());
+
+    // Different challenges over the same files and shape
+    let reseeded: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 6, FieldElement::from(12u64)))
+        .collect();
+    assert!(matches!(
+        system.resume_prove(&path, files.values().collect(), &reseeded),
+        Err(KontorPoRError::CheckpointMismatch { reason }) if reason.contains("challenges")
+    ));
+
+    // Challenging one file changes the circuit shape, and with it the parameters
+    let first = &files[&metadatas[0].file_id];
+    assert!(matches!(
+        system.resume_prove(&path, vec![first], &challenges[..1]),
+        Err(KontorPoRError::CheckpointMismatch { reason }) if reason.contains("parameters")
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+    println!("✓ Resumed proof folds only the remaining steps and verifies");
+}
+
+#[test]
+fn test_invalid_checkpoints_rejected() {
+    let (files, metadatas) = create_test_files(1, 200, 71);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let challenges = vec![Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        4,
+        FieldElement::from(3u64),
+    )];
+    let path = checkpoint_path("invalid");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(
+        system.prove_with_checkpoint(files.values().collect(), &challenges, &path, 0),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("at least one step")
+    ));
+    assert!(matches!(
+        system.resume_prove(&path, files.values().collect(), &challenges),
+        Err(KontorPoRError::IO(msg)) if msg.contains("does not exist")
+    ));
+
+    std::fs::write(&path, b"not a checkpoint").unwrap();
+    assert!(matches!(
+        system.resume_prove(&path, files.values().collect(), &challenges),
+        Err(KontorPoRError::Serialization(msg)) if msg.contains("bad magic")
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
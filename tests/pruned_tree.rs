@@ -0,0 +1,142 @@
+//! Tests for pruned (low-memory) Merkle trees
+//!
+//! This module tests that:
+//! 1. Proofs from a pruned tree match the unpruned tree at every prune level
+//! 2. Pruning more layers stores fewer nodes
+//! 3. Pruned prepared files prove, verify, spot-check and serialize
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, TreeArity},
+    merkle::{self, MerkleTree},
+    FileLedger,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+fn leaves(count: usize) -> Vec<FieldElement> {
+    (0..count as u64)
+        .map(|i| FieldElement::from(1000 + i * 7))
+        .collect()
+}
+
+fn tree_depth(tree: &MerkleTree) -> usize {
+    tree.layers.len() - 1
+}
+
+fn assert_binary_proofs_match(full: &MerkleTree, pruned: &MerkleTree, num_leaves: usize) {
+    let depth = tree_depth(full);
+    for index in 0..num_leaves {
+        let expected = merkle::get_padded_proof_for_leaf(full, index, depth).unwrap();
+        let actual = merkle::get_padded_proof_for_leaf(pruned, index, depth).unwrap();
+        assert_eq!(actual.leaf, expected.leaf, "leaf {}", index);
+        assert_eq!(actual.siblings, expected.siblings, "siblings {}", index);
+        assert_eq!(actual.path_indices, expected.path_indices, "path {}", index);
+    }
+}
+
+fn assert_arity4_proofs_match(full: &MerkleTree, pruned: &MerkleTree, num_leaves: usize) {
+    let depth = tree_depth(full);
+    for index in 0..num_leaves {
+        let expected = merkle::get_padded_proof4_for_leaf(full, index, depth).unwrap();
+        let actual = merkle::get_padded_proof4_for_leaf(pruned, index, depth).unwrap();
+        assert_eq!(actual.leaf, expected.leaf, "leaf {}", index);
+        assert_eq!(actual.siblings, expected.siblings, "siblings {}", index);
+        assert_eq!(actual.path_indices, expected.path_indices, "path {}", index);
+    }
+}
+
+#[test]
+fn test_pruned_binary_proofs_match() {
+    println!("Testing binary proof equivalence across prune levels");
+
+    // 13 leaves give odd-length layers, which duplicate their last node
+    for num_leaves in [13, 16, 64] {
+        let full = merkle::build_tree_from_leaves(&leaves(num_leaves)).unwrap();
+        let depth = tree_depth(&full);
+
+        let mut previous_nodes = full.stored_nodes();
+        for keep_top_levels in (0..=depth).rev() {
+            let mut pruned = merkle::build_tree_from_leaves(&leaves(num_leaves)).unwrap();
+            pruned.prune(keep_top_levels);
+
+            assert_eq!(pruned.root(), full.root());
+            assert!(pruned.stored_nodes() <= previous_nodes);
+            previous_nodes = pruned.stored_nodes();
+            assert_binary_proofs_match(&full, &pruned, num_leaves);
+        }
+        // Fully pruned keeps only the leaves and the root
+        assert_eq!(previous_nodes, num_leaves + 1);
+    }
+
+    println!("✓ Binary proofs identical at every prune level");
+}
+
+#[test]
+fn test_pruned_arity4_proofs_match() {
+    println!("Testing arity-4 proof equivalence across prune levels");
+
+    for num_leaves in [13, 64] {
+        let full = merkle::build_tree_from_leaves_arity4(&leaves(num_leaves)).unwrap();
+        let depth = tree_depth(&full);
+
+        for keep_top_levels in 0..=depth {
+            let mut pruned = merkle::build_tree_from_leaves_arity4(&leaves(num_leaves)).unwrap();
+            pruned.prune(keep_top_levels);
+
+            assert_eq!(pruned.root(), full.root());
+            assert_arity4_proofs_match(&full, &pruned, num_leaves);
+        }
+    }
+
+    println!("✓ Arity-4 proofs identical at every prune level");
+}
+
+#[test]
+fn test_pruned_prepared_file_proves_and_verifies() {
+    println!("Testing prove/verify and spot checks with pruned prepared files");
+
+    for tree_arity in [TreeArity::Binary, TreeArity::Arity4] {
+        let data = create_test_data(4000, Some(11));
+        let (mut prepared, metadata) =
+            api::prepare_file_with_arity(&data, "pruned.dat", tree_arity).unwrap();
+        let full_nodes = prepared.stored_nodes();
+        prepared.prune(0);
+        assert!(prepared.stored_nodes() < full_nodes);
+
+        let mut ledger = FileLedger::new();
+        ledger.add_file(&metadata).unwrap();
+        let system = PorSystem::new(&ledger);
+        let challenge = Challenge::new_test(metadata, 1000, 4, FieldElement::from(5u64));
+        let proof = system
+            .prove(vec![&prepared], std::slice::from_ref(&challenge))
+            .unwrap();
+        assert!(
+            system
+                .verify(&proof, std::slice::from_ref(&challenge))
+                .unwrap(),
+            "{:?} proof from a pruned file should verify",
+            tree_arity
+        );
+
+        if tree_arity == TreeArity::Binary {
+            assert!(api::spot_check(&prepared, &challenge).unwrap().all_passed());
+        }
+    }
+
+    println!("✓ Pruned prepared files prove, verify and spot-check");
+}
+
+#[test]
+fn test_pruned_tree_serialization_roundtrip() {
+    let full = merkle::build_tree_from_leaves(&leaves(40)).unwrap();
+    let mut pruned = merkle::build_tree_from_leaves(&leaves(40)).unwrap();
+    pruned.prune(1);
+
+    let bytes = bincode::serialize(&pruned).unwrap();
+    assert!(bytes.len() < bincode::serialize(&full).unwrap().len());
+
+    let restored: MerkleTree = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(restored.stored_nodes(), pruned.stored_nodes());
+    assert_binary_proofs_match(&full, &restored, 40);
+}
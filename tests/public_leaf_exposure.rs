@@ -10,7 +10,7 @@ use ff::Field;
 use kontor_crypto::{
     api::{self, Challenge, FieldElement},
     circuit::PorCircuit,
-    leaf_to_bytes31, merkle, FileLedger,
+    config, leaf_to_bytes31, merkle, FileLedger,
 };
 use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
 use nova_snark::traits::circuit::StepCircuit;
@@ -53,9 +53,11 @@ fn test_public_leaf_binding() {
     ];
 
     let circuit = PorCircuit::<FieldElement>::new(
-        4, // files_per_step
-        2, // file_tree_depth
-        2, // aggregated_tree_depth
+        config::Shape::new(
+            4, // files_per_step
+            2, // file_tree_depth
+            2, // aggregated_tree_depth
+        ),
         Some(witnesses.clone()),
     );
 
@@ -0,0 +1,145 @@
+//! Tests for equality, hashing and ordering of public types
+//!
+//! This module tests that:
+//! 1. Duplicate challenges collapse in a `HashSet`, distinct ones do not
+//! 2. `FileMetadata`, `Challenge` and `CircuitMerkleProof` compare and hash equal after
+//!    bincode and JSON round-trips
+//! 3. `Proof::same_statement` matches proofs of the same challenges and no others
+//! 4. `ChallengeID` orders by its bytes
+
+use kontor_crypto::{
+    api::{self, Challenge, ChallengeID, FieldElement, PorSystem, Proof},
+    merkle::{build_tree, get_padded_proof_for_leaf, CircuitMerkleProof},
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_challenge_set_dedup() {
+    let (_, metadata) = api::prepare_file(b"dedup challenge file", "dedup.dat").unwrap();
+    let seed = FieldElement::from(11u64);
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 3, seed);
+    let other_seed = Challenge::new_test(metadata.clone(), 1000, 3, FieldElement::from(12u64));
+    let other_height = Challenge::new_test(metadata, 1001, 3, seed);
+
+    let set: HashSet<Challenge> = [
+        challenge.clone(),
+        other_seed.clone(),
+        challenge.clone(),
+        other_height,
+        other_seed,
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&challenge));
+}
+
+#[test]
+fn test_metadata_and_challenge_equal_after_round_trip() {
+    let (_, metadata) = api::prepare_file(b"round trip metadata", "trip.dat").unwrap();
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(7u64));
+
+    let from_bincode = bincode::deserialize(&bincode::serialize(&metadata).unwrap()).unwrap();
+    let from_json = serde_json::from_str(&serde_json::to_string(&metadata).unwrap()).unwrap();
+    assert_eq!(metadata, from_bincode);
+    assert_eq!(metadata, from_json);
+    assert_eq!(hash_of(&metadata), hash_of(&from_bincode));
+    assert_eq!(hash_of(&metadata), hash_of(&from_json));
+
+    let from_bincode: Challenge =
+        bincode::deserialize(&bincode::serialize(&challenge).unwrap()).unwrap();
+    let from_json: Challenge =
+        serde_json::from_str(&serde_json::to_string(&challenge).unwrap()).unwrap();
+    assert_eq!(challenge, from_bincode);
+    assert_eq!(challenge, from_json);
+    assert_eq!(hash_of(&challenge), hash_of(&from_bincode));
+    assert_eq!(hash_of(&challenge), hash_of(&from_json));
+
+    // Metadata differing only in its annotations is a different value
+    let mut annotated = metadata.clone();
+    annotated
+        .annotations
+        .insert("operator".to_string(), "note".to_string());
+    assert_ne!(metadata, annotated);
+    assert_eq!(metadata.redact(), annotated.redact());
+}
+
+#[test]
+fn test_merkle_proof_equal_after_round_trip() {
+    let chunks: Vec<Vec<u8>> = (0u8..5).map(|i| vec![i; 31]).collect();
+    let (tree, _) = build_tree(&chunks).unwrap();
+    let proof = get_padded_proof_for_leaf(&tree, 3, 4).unwrap();
+
+    let from_bincode: CircuitMerkleProof =
+        bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+    let from_json: CircuitMerkleProof =
+        serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert_eq!(proof, from_bincode);
+    assert_eq!(proof, from_json);
+    assert_eq!(hash_of(&proof), hash_of(&from_bincode));
+    assert_eq!(hash_of(&proof), hash_of(&from_json));
+
+    assert_ne!(proof, get_padded_proof_for_leaf(&tree, 2, 4).unwrap());
+}
+
+#[test]
+fn test_proof_same_statement() {
+    println!("Testing Proof::same_statement across independent proving runs");
+
+    let (files, metadatas) = create_test_files(2, 300, 91);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(21u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+
+    let first = system.prove(files.values().collect(), &challenges).unwrap();
+    let second = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(first.same_statement(&second));
+
+    let decoded = Proof::from_bytes(&first.to_bytes().unwrap()).unwrap();
+    assert!(first.same_statement(&decoded));
+
+    let other_challenges = [Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        2,
+        FieldElement::from(22u64),
+    )];
+    let other = system
+        .prove(vec![&files[&metadatas[0].file_id]], &other_challenges)
+        .unwrap();
+    assert!(!first.same_statement(&other));
+
+    println!("✓ Same challenges give the same statement, different ones do not");
+}
+
+#[test]
+fn test_challenge_id_ordering() {
+    let low = ChallengeID([0u8; 32]);
+    let mut high_bytes = [0u8; 32];
+    high_bytes[0] = 1;
+    let high = ChallengeID(high_bytes);
+    assert!(low < high);
+
+    let ordered: Vec<ChallengeID> = [high.clone(), low.clone(), high.clone()]
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    assert_eq!(ordered, vec![low, high]);
+}
@@ -0,0 +1,134 @@
+//! Tests for files prepared without erasure coding
+//!
+//! This module tests that:
+//! 1. Raw files chunk their data directly into symbols and record `erasure: None`
+//! 2. Raw files prove and verify like erasure-coded files
+//! 3. Reconstruction from partial symbols is refused and `assemble_raw` needs every symbol
+
+use kontor_crypto::{
+    api::{self, Challenge, ErasureCode, FieldElement, FileMetadata, PorSystem},
+    merkle, FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+/// Splits `data` into zero-padded 31-byte symbols, as `prepare_file_raw` does.
+fn raw_symbols(data: &[u8]) -> Vec<Option<Vec<u8>>> {
+    data.chunks(31)
+        .map(|chunk| {
+            let mut symbol = chunk.to_vec();
+            symbol.resize(31, 0);
+            Some(symbol)
+        })
+        .collect()
+}
+
+#[test]
+fn test_raw_file_metadata_and_tree() {
+    let data = create_test_data(1000, Some(5));
+    let (_prepared, metadata) = api::prepare_file_raw(&data, "raw.dat").unwrap();
+
+    assert_eq!(metadata.erasure, None);
+    assert_eq!(metadata.num_codewords(), 0);
+    assert_eq!(
+        metadata.total_symbols(),
+        33,
+        "1000 bytes chunk into 33 symbols"
+    );
+    assert_eq!(metadata.padded_len, 64);
+
+    // The tree is built over the raw chunks, not RS-encoded symbols, zero-padded to 64
+    let mut symbols: Vec<Vec<u8>> = raw_symbols(&data).into_iter().flatten().collect();
+    symbols.resize(metadata.padded_len, vec![0; 31]);
+    let (_, root) = merkle::build_tree(&symbols).unwrap();
+    assert_eq!(metadata.root, root);
+
+    let (_, coded_metadata) = api::prepare_file(&data, "raw.dat").unwrap();
+    assert_eq!(coded_metadata.erasure, Some(ErasureCode::ReedSolomon));
+    assert_ne!(coded_metadata.root, metadata.root);
+    assert!(coded_metadata.padded_len > metadata.padded_len);
+
+    // The missing erasure code survives serialization
+    let bytes = bincode::serialize(&metadata).unwrap();
+    let decoded: FileMetadata = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, metadata);
+}
+
+#[test]
+fn test_raw_files_prove_and_verify() {
+    println!("Testing prove/verify for raw and erasure-coded files in one batch");
+
+    let raw_data = create_test_data(3000, Some(1));
+    let coded_data = create_test_data(800, Some(2));
+    let (raw_prepared, raw_metadata) = api::prepare_file_raw(&raw_data, "raw.dat").unwrap();
+    let (coded_prepared, coded_metadata) = api::prepare_file(&coded_data, "coded.dat").unwrap();
+
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&raw_metadata).unwrap();
+    ledger.add_file(&coded_metadata).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(31u64);
+    let single = Challenge::new_test(raw_metadata.clone(), 1000, 3, seed);
+    let proof = system
+        .prove(vec![&raw_prepared], std::slice::from_ref(&single))
+        .unwrap();
+    assert!(system.verify(&proof, &[single]).unwrap());
+
+    let challenges = vec![
+        Challenge::new_test(raw_metadata, 1000, 3, seed),
+        Challenge::new_test(coded_metadata, 1000, 3, seed),
+    ];
+    let proof = system
+        .prove(vec![&raw_prepared, &coded_prepared], &challenges)
+        .unwrap();
+    assert!(
+        system.verify(&proof, &challenges).unwrap(),
+        "Mixed raw/coded proof should verify"
+    );
+
+    println!("✓ Raw files prove and verify unchanged");
+}
+
+#[test]
+fn test_raw_file_reconstruction_refused() {
+    println!("Testing that raw files must be assembled from every symbol");
+
+    let data = create_test_data(500, Some(9));
+    let (_prepared, metadata) = api::prepare_file_raw(&data, "raw.dat").unwrap();
+    let mut symbols = raw_symbols(&data);
+
+    // All symbols present: reconstruct_file still refuses, assemble_raw succeeds
+    let result = api::reconstruct_file(&symbols, &metadata);
+    assert!(
+        matches!(result, Err(KontorPoRError::ErasureCoding { .. })),
+        "reconstruct_file must refuse raw files, got {:?}",
+        result
+    );
+    assert_eq!(api::assemble_raw(&symbols, &metadata).unwrap(), data);
+
+    // Trailing tree padding is ignored
+    let mut padded = symbols.clone();
+    padded.resize(metadata.padded_len, Some(vec![0u8; 31]));
+    assert_eq!(api::assemble_raw(&padded, &metadata).unwrap(), data);
+
+    // A single missing symbol cannot be recovered
+    symbols[3] = None;
+    let result = api::assemble_raw(&symbols, &metadata);
+    assert!(
+        matches!(result, Err(KontorPoRError::ErasureCoding { ref details }) if details.contains("1 missing")),
+        "Missing symbol must be reported, got {:?}",
+        result
+    );
+    assert!(api::assemble_raw(&symbols[..5], &metadata).is_err());
+
+    // assemble_raw is only for raw files
+    let (_, coded_metadata) = api::prepare_file(&data, "coded.dat").unwrap();
+    assert!(matches!(
+        api::assemble_raw(&raw_symbols(&data), &coded_metadata),
+        Err(KontorPoRError::ErasureCoding { .. })
+    ));
+
+    println!("✓ Raw files refuse partial reconstruction");
+}
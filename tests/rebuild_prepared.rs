@@ -0,0 +1,106 @@
+//! Tests for rebuilding prepared files from stored symbols
+//!
+//! This module tests that:
+//! 1. Symbols from storage rebuild a `PreparedFile` that proves and verifies
+//! 2. Both unpadded (`total_symbols()`) and padded (`padded_len`) symbol sets are accepted
+//! 3. Corrupted symbols and wrong symbol counts are rejected
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, TreeArity},
+    erasure::encode_file_symbols,
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+#[test]
+fn test_rebuild_prepared_proves_and_verifies() {
+    println!("Testing prepare -> store symbols -> rebuild -> prove/verify");
+
+    for tree_arity in [TreeArity::Binary, TreeArity::Arity4] {
+        let data = create_test_data(2500, Some(3));
+        let (prepared, metadata) =
+            api::prepare_file_with_arity(&data, "stored.dat", tree_arity).unwrap();
+
+        // Only the encoded symbols survive a restart
+        let symbols = encode_file_symbols(&data).unwrap();
+        assert_eq!(symbols.len(), metadata.total_symbols());
+
+        let rebuilt = api::rebuild_prepared(&symbols, &metadata).unwrap();
+        assert_eq!(rebuilt.file_id, prepared.file_id);
+        assert_eq!(rebuilt.root, prepared.root);
+
+        let mut padded = symbols.clone();
+        padded.resize(metadata.padded_len, vec![0u8; 31]);
+        let rebuilt_padded = api::rebuild_prepared(&padded, &metadata).unwrap();
+        assert_eq!(rebuilt_padded.root, metadata.root);
+
+        let mut ledger = FileLedger::new();
+        ledger.add_file(&metadata).unwrap();
+        let system = PorSystem::new(&ledger);
+        let challenge = Challenge::new_test(metadata, 1000, 3, FieldElement::from(21u64));
+        let proof = system
+            .prove(vec![&rebuilt], std::slice::from_ref(&challenge))
+            .unwrap();
+        assert!(
+            system.verify(&proof, &[challenge]).unwrap(),
+            "{:?} proof from a rebuilt file should verify",
+            tree_arity
+        );
+    }
+
+    // Raw files rebuild from their chunked symbols too
+    let data = create_test_data(400, Some(4));
+    let (_, metadata) = api::prepare_file_raw(&data, "raw.dat").unwrap();
+    let symbols: Vec<Vec<u8>> = data
+        .chunks(31)
+        .map(|chunk| {
+            let mut symbol = chunk.to_vec();
+            symbol.resize(31, 0);
+            symbol
+        })
+        .collect();
+    assert_eq!(
+        api::rebuild_prepared(&symbols, &metadata).unwrap().root,
+        metadata.root
+    );
+
+    println!("✓ Rebuilt files prove and verify");
+}
+
+#[test]
+fn test_rebuild_prepared_rejects_bad_symbols() {
+    let data = create_test_data(1200, Some(8));
+    let (_, metadata) = api::prepare_file(&data, "stored.dat").unwrap();
+    let symbols = encode_file_symbols(&data).unwrap();
+
+    let mut corrupted = symbols.clone();
+    corrupted[5][0] ^= 1;
+    let result = api::rebuild_prepared(&corrupted, &metadata);
+    assert!(
+        matches!(result, Err(KontorPoRError::RootMismatch { ref file_id }) if *file_id == metadata.file_id),
+        "Corrupted symbol must be reported as a root mismatch"
+    );
+
+    // Non-zero padding changes the root
+    let mut padded = symbols.clone();
+    padded.resize(metadata.padded_len, vec![1u8; 31]);
+    assert!(matches!(
+        api::rebuild_prepared(&padded, &metadata),
+        Err(KontorPoRError::RootMismatch { .. })
+    ));
+
+    // Counts other than total_symbols() or padded_len are refused
+    assert!(matches!(
+        api::rebuild_prepared(&symbols[1..], &metadata),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    let mut short_symbol = symbols;
+    short_symbol[2].pop();
+    assert!(matches!(
+        api::rebuild_prepared(&short_symbol, &metadata),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+}
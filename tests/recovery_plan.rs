@@ -0,0 +1,166 @@
+//! Tests for planning which symbols to fetch before reconstruction (`erasure::recovery_plan`)
+//!
+//! This module tests that:
+//! 1. Fetching exactly the planned symbols makes a damaged file decode, and leaving out
+//!    any one planned symbol makes decoding fail
+//! 2. Plans prefer data symbols over parity symbols and are empty for recoverable files
+//! 3. Over GF(2^16) plans complete the shards with the fewest missing symbols
+
+use kontor_crypto::{
+    api::{self, ErasureField, FileMetadata},
+    erasure::{
+        decode_file_symbols_with, encode_file_symbols, encode_file_symbols_with, recoverability,
+        recovery_plan, simulate_loss, ErasureConfig, LossModel, RecoveryPlan,
+    },
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+/// Symbols held after loss plus the planned fetches, leaving out `skip` if given.
+fn fetched(
+    symbols: &[Vec<u8>],
+    available: &[bool],
+    plan: &RecoveryPlan,
+    skip: Option<usize>,
+) -> Vec<Option<Vec<u8>>> {
+    let mut held: Vec<Option<Vec<u8>>> = symbols
+        .iter()
+        .zip(available)
+        .map(|(symbol, &present)| present.then(|| symbol.clone()))
+        .collect();
+    for index in plan.fetch_indices() {
+        if Some(index) != skip {
+            held[index] = Some(symbols[index].clone());
+        }
+    }
+    held
+}
+
+fn availability(symbols: &[Vec<u8>], metadata: &FileMetadata, loss: LossModel) -> Vec<bool> {
+    simulate_loss(symbols, metadata, loss, 7)
+        .iter()
+        .map(Option::is_some)
+        .collect()
+}
+
+#[test]
+fn test_planned_fetches_are_sufficient_and_minimal() {
+    println!("Testing that planned fetches exactly restore recoverability");
+
+    let data = create_test_data(231 * 31 * 4, Some(1640));
+    let (_, metadata) = api::prepare_file(&data, "plan.dat").unwrap();
+    let config = ErasureConfig::for_metadata(&metadata).unwrap();
+    let symbols = encode_file_symbols_with(&data, config).unwrap();
+    let available = availability(&symbols, &metadata, LossModel::Random(0.2));
+
+    let plan = recovery_plan(&available, &metadata);
+    let unrecoverable = recoverability(
+        &fetched(&symbols, &available, &RecoveryPlan::default(), None),
+        &metadata,
+    )
+    .unrecoverable_codewords();
+    assert!(!unrecoverable.is_empty());
+    assert_eq!(
+        plan.codewords.iter().map(|cw| cw.index).collect::<Vec<_>>(),
+        unrecoverable
+    );
+    assert_eq!(plan.total_needed, plan.fetch_indices().len());
+    for cw in &plan.codewords {
+        assert_eq!(cw.needed, cw.fetch.len());
+        assert!(cw.fetch.iter().all(|&i| !available[i]));
+    }
+
+    let mut held = fetched(&symbols, &available, &plan, None);
+    let decoded =
+        decode_file_symbols_with(&mut held, metadata.num_codewords(), data.len(), config).unwrap();
+    assert_eq!(decoded, data);
+
+    for skip in plan.fetch_indices() {
+        let mut held = fetched(&symbols, &available, &plan, Some(skip));
+        let result =
+            decode_file_symbols_with(&mut held, metadata.num_codewords(), data.len(), config);
+        assert!(
+            matches!(result, Err(KontorPoRError::ReconstructionFailed { .. })),
+            "decoding without planned symbol {skip} must fail"
+        );
+    }
+
+    println!(
+        "✓ {} planned symbols suffice and none is redundant",
+        plan.total_needed
+    );
+}
+
+#[test]
+fn test_plan_prefers_data_symbols() {
+    println!("Testing data symbol preference and empty plans");
+
+    let data = create_test_data(231 * 31 * 2, Some(1641));
+    let (_, metadata) = api::prepare_file(&data, "prefer.dat").unwrap();
+    let symbols = encode_file_symbols(&data).unwrap();
+
+    // 30 symbols lost across the data/parity boundary of codeword 1: 11 data, 19 parity
+    let mut available = vec![true; symbols.len()];
+    available[255 + 220..255 + 250].fill(false);
+    let plan = recovery_plan(&available, &metadata);
+    assert_eq!(plan.codewords.len(), 1);
+    assert_eq!(plan.codewords[0].index, 1);
+    assert_eq!(plan.codewords[0].needed, 6);
+    assert_eq!(
+        plan.fetch_indices(),
+        (255 + 220..255 + 226).collect::<Vec<_>>()
+    );
+
+    // A wholly lost codeword is restored from its data symbols alone
+    let mut available = vec![true; symbols.len()];
+    available[..255].fill(false);
+    let plan = recovery_plan(&available, &metadata);
+    assert_eq!(plan.fetch_indices(), (0..231).collect::<Vec<_>>());
+
+    // Recoverable damage needs nothing; symbols past a truncated list count as missing
+    let plan = recovery_plan(
+        &availability(&symbols, &metadata, LossModel::PerCodeword(24)),
+        &metadata,
+    );
+    assert!(plan.is_empty());
+    assert_eq!(plan.total_needed, 0);
+    let plan = recovery_plan(&vec![true; 255], &metadata);
+    assert_eq!(plan.fetch_indices(), (255..255 + 231).collect::<Vec<_>>());
+
+    println!("✓ Plans fetch data symbols before parity");
+}
+
+#[test]
+fn test_gf16_plan_completes_cheapest_shards() {
+    println!("Testing recovery plans over GF(2^16)");
+
+    const GF16: ErasureConfig = ErasureConfig::field(ErasureField::Gf16);
+    let data = create_test_data(5_000, Some(1642));
+    let (_, metadata) = api::prepare_file_with_erasure(&data, "gf16.dat", GF16).unwrap();
+    let symbols = encode_file_symbols_with(&data, GF16).unwrap();
+
+    // 96 data shards missing one symbol, one data shard missing both and one parity shard
+    // missing one: 98 incomplete shards against 96 parity shards
+    let mut available = vec![true; symbols.len()];
+    for shard in 0..96 {
+        available[2 * shard + 1] = false;
+    }
+    available[2 * 96..2 * 96 + 2].fill(false);
+    available[1900] = false;
+
+    let plan = recovery_plan(&available, &metadata);
+    assert_eq!(plan.total_needed, 2);
+    assert_eq!(plan.fetch_indices(), vec![1, 3]);
+
+    let mut held = fetched(&symbols, &available, &plan, None);
+    let decoded = decode_file_symbols_with(&mut held, 1, data.len(), GF16).unwrap();
+    assert_eq!(decoded, data);
+    for skip in plan.fetch_indices() {
+        let held = fetched(&symbols, &available, &plan, Some(skip));
+        assert!(!recoverability(&held, &metadata).is_recoverable());
+    }
+
+    println!("✓ GF(2^16) plans complete the cheapest shards");
+}
@@ -0,0 +1,182 @@
+//! Tests for the public root commitment helper
+//!
+//! This module tests that:
+//! 1. `ledger::compute_rc` matches the `compute_rc` circuit gadget, the rc the ledger
+//!    stores from `add_file` and `add_files`, and `FileMetadata::commitment` for depths
+//!    0 through 20
+//! 2. `compute_rc_with` agrees with the gadget and the ledger for every arity and hash
+//!    profile, and the commitments of different arities and profiles differ
+//! 3. A verifier holding only `(root, depth)` checks ledger membership with the helper
+
+use ff::Field;
+use kontor_crypto::{
+    api::{prepare_file, FieldElement, HashProfile, TreeArity},
+    circuit::gadgets::poseidon_hash_tagged_gadget_with,
+    compute_rc, compute_rc_with,
+    poseidon::domain_tags,
+    verify_aggregation_proof, FileDescriptor, FileLedger,
+};
+use nova_snark::frontend::{
+    gadgets::num::AllocatedNum, util_cs::test_cs::TestConstraintSystem, ConstraintSystem,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+/// A ledger entry with an arbitrary root and depth.
+struct Synthetic {
+    file_id: String,
+    root: FieldElement,
+    depth: usize,
+    arity: TreeArity,
+    profile: HashProfile,
+}
+
+impl Synthetic {
+    fn new(depth: usize, arity: TreeArity, profile: HashProfile) -> Self {
+        Synthetic {
+            file_id: format!("{:?}-{}-{}", arity, profile, depth),
+            root: FieldElement::from(1000 + depth as u64),
+            depth,
+            arity,
+            profile,
+        }
+    }
+}
+
+impl FileDescriptor for Synthetic {
+    fn file_id(&self) -> &str {
+        &self.file_id
+    }
+    fn root(&self) -> FieldElement {
+        self.root
+    }
+    fn depth(&self) -> usize {
+        self.depth
+    }
+    fn tree_arity(&self) -> TreeArity {
+        self.arity
+    }
+    fn hash_profile(&self) -> HashProfile {
+        self.profile
+    }
+}
+
+/// The rc the circuit computes for `(root, depth)`.
+fn gadget_rc(entry: &Synthetic) -> FieldElement {
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let root = AllocatedNum::alloc(cs.namespace(|| "root"), || Ok(entry.root)).unwrap();
+    let depth = AllocatedNum::alloc(cs.namespace(|| "depth"), || {
+        Ok(FieldElement::from(entry.depth as u64))
+    })
+    .unwrap();
+    let tag = match entry.arity {
+        TreeArity::Binary => domain_tags::root_commitment(),
+        TreeArity::Arity4 => domain_tags::root_commitment_arity4(),
+    };
+    let rc = poseidon_hash_tagged_gadget_with(
+        cs.namespace(|| "compute_rc"),
+        entry.profile,
+        tag,
+        &root,
+        &depth,
+    )
+    .unwrap();
+    assert!(cs.is_satisfied());
+    rc.get_value().unwrap()
+}
+
+#[test]
+fn test_compute_rc_matches_gadget_and_ledger() {
+    println!("Testing compute_rc against the circuit gadget and the ledger");
+
+    let entries: Vec<Synthetic> = (0..=20)
+        .map(|depth| Synthetic::new(depth, TreeArity::Binary, HashProfile::Standard))
+        .collect();
+    let mut ledger = FileLedger::new();
+    for entry in &entries[..10] {
+        ledger.add_file(entry).unwrap();
+    }
+    ledger.add_files(&entries[10..]).unwrap();
+
+    for entry in &entries {
+        let rc = compute_rc(entry.root, entry.depth);
+        assert_eq!(rc, gadget_rc(entry), "depth {}", entry.depth);
+        assert_eq!(ledger.entry(&entry.file_id).unwrap().rc, rc);
+        assert_eq!(
+            rc,
+            compute_rc_with(
+                entry.root,
+                entry.depth,
+                TreeArity::Binary,
+                HashProfile::Standard
+            )
+        );
+    }
+
+    // Depth is part of the commitment
+    assert_ne!(
+        compute_rc(entries[0].root, 0),
+        compute_rc(entries[0].root, 1)
+    );
+
+    let (_, metadata) = prepare_file(&create_test_data(5000, Some(3)), "m.dat").unwrap();
+    assert_eq!(
+        metadata.commitment(),
+        compute_rc(metadata.root, metadata.depth())
+    );
+
+    println!("✓ Helper, gadget and ledger agree for depths 0 through 20");
+}
+
+#[test]
+fn test_compute_rc_with_arities_and_profiles() {
+    let mut ledger = FileLedger::new();
+    let mut seen = Vec::new();
+    for arity in [TreeArity::Binary, TreeArity::Arity4] {
+        for profile in [HashProfile::Standard, HashProfile::Conservative] {
+            for depth in [0, 1, 5, 10] {
+                let entry = Synthetic::new(depth, arity, profile);
+                ledger.add_file(&entry).unwrap();
+                let rc = compute_rc_with(entry.root, depth, arity, profile);
+                assert_eq!(rc, gadget_rc(&entry));
+                assert_eq!(ledger.entry(&entry.file_id).unwrap().rc, rc);
+                assert_eq!(
+                    arity.root_commitment_with_profile(profile, entry.root, depth),
+                    rc
+                );
+                if depth == 5 {
+                    seen.push(rc);
+                }
+            }
+        }
+    }
+    seen.sort_unstable_by_key(|rc| format!("{:?}", rc));
+    seen.dedup();
+    assert_eq!(seen.len(), 4, "arities and profiles never share an rc");
+}
+
+#[test]
+fn test_external_membership_check() {
+    let (_, metadata) = prepare_file(&create_test_data(800, Some(9)), "x.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    ledger
+        .add_file(&Synthetic::new(3, TreeArity::Binary, HashProfile::Standard))
+        .unwrap();
+
+    // The verifier knows the ledger root, the file's (root, depth) and its path only
+    let proof = ledger.get_aggregation_proof(&metadata.file_id).unwrap();
+    let rc = compute_rc(metadata.root, metadata.depth());
+    assert!(verify_aggregation_proof(ledger.root(), &proof, rc));
+    assert!(!verify_aggregation_proof(
+        ledger.root(),
+        &proof,
+        compute_rc(metadata.root + FieldElement::ONE, metadata.depth())
+    ));
+    assert!(!verify_aggregation_proof(
+        ledger.root(),
+        &proof,
+        compute_rc(metadata.root, metadata.depth() + 1)
+    ));
+}
@@ -0,0 +1,144 @@
+//! Tests for per-root verification counters and pruning of unreferenced roots
+//!
+//! This module tests that:
+//! 1. `PorSystem::with_root_usage` counts successful and failed verifications of
+//!    ledger-bound proofs under their historical root; unknown roots and file-root-bound
+//!    proofs are not counted, and the counters can be reset
+//! 2. `FileLedger::prune_unreferenced_roots` drops only the historical roots that were not
+//!    seen within `min_age`, keeping the current root; proofs against a dropped root then
+//!    fail with `InvalidLedgerRoot` while the others still verify
+
+use ff::PrimeField;
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, PreparedFile, Proof, RootUsageTracker},
+    FileLedger, KontorPoRError,
+};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+/// Proves both challenges against the ledger in its current state.
+fn prove_pair(
+    ledger: &FileLedger,
+    files: &BTreeMap<String, PreparedFile>,
+    challenges: &[Challenge],
+) -> Proof {
+    let refs = challenges
+        .iter()
+        .map(|c| &files[&c.file_metadata.file_id])
+        .collect();
+    PorSystem::new(ledger).prove(refs, challenges).unwrap()
+}
+
+fn repr(root: FieldElement) -> [u8; 32] {
+    root.to_repr().into()
+}
+
+#[test]
+fn test_root_usage_counts_and_prunes() {
+    println!("Testing per-root counters and pruning of unreferenced roots");
+
+    let (files, metadatas) = create_test_files(4, 200, 90);
+    let challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(9u64)))
+        .collect();
+
+    // Two historical roots, each with a proof generated against it
+    let mut ledger = create_multi_file_ledger(&[&metadatas[0], &metadatas[1]]);
+    let old_root = ledger.root();
+    let old_proof = prove_pair(&ledger, &files, &challenges);
+    ledger.add_file(&metadatas[2]).unwrap();
+    let idle_root = ledger.root();
+    let idle_proof = prove_pair(&ledger, &files, &challenges);
+    ledger.add_file(&metadatas[3]).unwrap();
+    assert!(ledger.is_valid_root(old_root) && ledger.is_valid_root(idle_root));
+
+    let tracker = RootUsageTracker::new();
+    {
+        let system = PorSystem::new(&ledger).with_root_usage(&tracker);
+        assert!(system.verify(&old_proof, &challenges).unwrap());
+        assert!(system.verify(&old_proof, &challenges).unwrap());
+        assert!(system.verify(&idle_proof, &challenges).unwrap());
+
+        // A proof that fails verification still references its root
+        let mut tampered = Proof::from_bytes(&old_proof.to_bytes().unwrap()).unwrap();
+        tampered.challenge_nonce_commitment += FieldElement::from(1u64);
+        assert!(!system.verify(&tampered, &challenges).unwrap());
+
+        // Unknown roots are rejected before they are counted
+        let mut unknown = Proof::from_bytes(&old_proof.to_bytes().unwrap()).unwrap();
+        unknown.ledger_root += FieldElement::from(1u64);
+        assert!(matches!(
+            system.verify(&unknown, &challenges),
+            Err(KontorPoRError::InvalidLedgerRoot { .. })
+        ));
+
+        // File-root-bound proofs do not depend on the ledger root
+        let single = vec![challenges[0].clone()];
+        let single_proof = system
+            .prove(vec![&files[&metadatas[0].file_id]], &single)
+            .unwrap();
+        assert!(system.verify(&single_proof, &single).unwrap());
+    }
+
+    let system = PorSystem::new(&ledger).with_root_usage(&tracker);
+    let mut stats = system.root_usage_stats();
+    assert_eq!(stats.len(), 2);
+    let old = stats.iter().find(|u| u.root == old_root).unwrap();
+    assert_eq!((old.successes, old.failures, old.references()), (2, 1, 3));
+    let idle = stats.iter().find(|u| u.root == idle_root).unwrap();
+    assert_eq!((idle.successes, idle.failures), (1, 0));
+    assert!(PorSystem::new(&ledger).root_usage_stats().is_empty());
+
+    // The idle root was last referenced two hours ago
+    let idle = stats.iter_mut().find(|u| u.root == idle_root).unwrap();
+    idle.last_seen -= Duration::from_secs(2 * 3600);
+
+    let current = ledger.root();
+    let before = ledger.historical_roots().len();
+    let pruned = ledger
+        .prune_unreferenced_roots(&stats, Duration::from_secs(3600))
+        .unwrap();
+    assert!(pruned.contains(&repr(idle_root)));
+    assert!(!pruned.contains(&repr(old_root)));
+    assert!(!pruned.contains(&repr(current)));
+    assert_eq!(ledger.historical_roots().len(), before - pruned.len());
+    assert!(ledger.is_valid_root(old_root) && ledger.is_valid_root(current));
+    assert!(!ledger.is_valid_root(idle_root));
+
+    let system = PorSystem::new(&ledger).with_root_usage(&tracker);
+    system.reset_root_usage();
+    assert!(tracker.stats().is_empty());
+    assert!(system.verify(&old_proof, &challenges).unwrap());
+    assert!(matches!(
+        system.verify(&idle_proof, &challenges),
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+    let stats = tracker.stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!((stats[0].root, stats[0].successes), (old_root, 1));
+
+    println!("✓ Only the unreferenced historical root was pruned");
+}
+
+#[test]
+fn test_prune_without_references() {
+    let (_, metadatas) = create_test_files(3, 200, 95);
+    let mut ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let current = ledger.root();
+    assert_eq!(ledger.historical_roots().len(), 3);
+
+    // With no counters every historical root but the current one is unreferenced
+    let pruned = ledger
+        .prune_unreferenced_roots(&[], Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(pruned.len(), 2);
+    assert_eq!(ledger.historical_roots(), [repr(current)]);
+    assert!(ledger
+        .prune_unreferenced_roots(&[], Duration::ZERO)
+        .unwrap()
+        .is_empty());
+}
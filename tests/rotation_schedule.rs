@@ -0,0 +1,207 @@
+//! Tests for the deterministic challenge rotation (`FileLedger::rotation_schedule`)
+//!
+//! This module tests that:
+//! 1. Every cycle selects every file exactly once, for several ledger sizes and rates
+//! 2. The schedule is deterministic and differs between cycles and ledgers
+//! 3. Over many cycles each file lands in each epoch of its cycle about equally often
+//! 4. Files added mid-cycle join at the next boundary when cycles are chained
+//! 5. Empty ledgers and a zero rate are handled
+
+use kontor_crypto::api::{ErasureCode, FieldElement, FileMetadata, TreeArity};
+use kontor_crypto::{FileLedger, RotationCycle};
+use std::collections::BTreeMap;
+
+fn dummy_metadata(file_id: &str, root_val: u64, depth: usize) -> FileMetadata {
+    FileMetadata {
+        root: FieldElement::from(root_val),
+        file_id: file_id.to_string(),
+        padded_len: 1 << depth,
+        original_size: 100,
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
+    }
+}
+
+fn ledger_with_files(num_files: usize) -> FileLedger {
+    let metadatas: Vec<FileMetadata> = (0..num_files)
+        .map(|i| dummy_metadata(&format!("file_{:04}", i), i as u64 + 1, 4))
+        .collect();
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    ledger
+}
+
+/// Asserts that the epochs of the cycle starting at `start` select each file exactly once.
+fn assert_cycle_covers(ledger: &FileLedger, start: u64, files_per_epoch: usize) {
+    let n = ledger.file_count();
+    let cycle_len = n.div_ceil(files_per_epoch) as u64;
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for epoch in start..start + cycle_len {
+        let selected = ledger.rotation_schedule(epoch, files_per_epoch);
+        assert!(!selected.is_empty() && selected.len() <= files_per_epoch);
+        for file_id in selected {
+            *counts.entry(file_id).or_default() += 1;
+        }
+    }
+    assert_eq!(counts.len(), n, "cycle at {} missed files", start);
+    assert!(counts.values().all(|&count| count == 1));
+    assert!(counts.keys().eq(ledger.file_ids()));
+}
+
+#[test]
+fn test_every_cycle_covers_every_file() {
+    println!("Testing rotation coverage across ledger sizes");
+
+    for n in [1, 2, 7, 10, 64, 100, 257] {
+        let ledger = ledger_with_files(n);
+        for files_per_epoch in [1, 3, 10, n, n + 5] {
+            let cycle_len = n.div_ceil(files_per_epoch) as u64;
+            for cycle in 0..20 {
+                assert_cycle_covers(&ledger, cycle * cycle_len, files_per_epoch);
+            }
+            let cycle = ledger.rotation_cycle(3 * cycle_len, files_per_epoch);
+            assert_eq!(cycle.epochs(), 3 * cycle_len..4 * cycle_len);
+        }
+    }
+
+    println!("✓ Each cycle selected every file exactly once");
+}
+
+#[test]
+fn test_schedule_is_deterministic_and_shuffled() {
+    let ledger = ledger_with_files(50);
+    let copy = ledger_with_files(50);
+    for epoch in 0..30 {
+        assert_eq!(
+            ledger.rotation_schedule(epoch, 5),
+            copy.rotation_schedule(epoch, 5)
+        );
+    }
+
+    // Cycles are 10 epochs long and each gets its own order
+    let first = ledger.rotation_cycle(0, 5);
+    let second = ledger.rotation_cycle(10, 5);
+    assert_ne!(first.order, second.order);
+    assert_eq!(first.files_at(3).unwrap(), ledger.rotation_schedule(3, 5));
+    assert_eq!(
+        second.files_at(13).unwrap(),
+        ledger.rotation_schedule(13, 5)
+    );
+    assert_eq!(first.files_at(10), None);
+
+    // Orders are not the canonical order and depend on the root
+    let canonical: Vec<String> = ledger.file_ids().map(str::to_owned).collect();
+    assert_ne!(first.order, canonical);
+    let mut other = ledger.clone();
+    other
+        .add_file(&dummy_metadata("file_0000", 999, 4))
+        .unwrap();
+    assert_ne!(other.rotation_cycle(0, 5).order, first.order);
+
+    let json = serde_json::to_string(&first).unwrap();
+    assert_eq!(serde_json::from_str::<RotationCycle>(&json).unwrap(), first);
+}
+
+#[test]
+fn test_epoch_positions_are_uniform() {
+    println!("Testing the distribution of each file's epoch within its cycle");
+
+    // 12 files, 3 per epoch: 4 epochs per cycle, so each file should land in each epoch
+    // position about a quarter of the time
+    let ledger = ledger_with_files(12);
+    let cycles = 2000u64;
+    let mut positions = vec![[0u64; 4]; 12];
+    let index_of: BTreeMap<&str, usize> = ledger.file_ids().zip(0..).collect();
+    for cycle in 0..cycles {
+        let rotation = ledger.rotation_cycle(cycle * 4, 3);
+        for position in 0..4 {
+            for file_id in rotation.files_at(cycle * 4 + position).unwrap() {
+                positions[index_of[file_id.as_str()]][position as usize] += 1;
+            }
+        }
+    }
+
+    // Chi-square with 3 degrees of freedom; 16.27 is the 0.1% critical value
+    let expected = cycles as f64 / 4.0;
+    for (file, counts) in positions.iter().enumerate() {
+        let chi_square: f64 = counts
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(
+            chi_square < 16.27,
+            "file {} positions {:?} are skewed (chi-square {:.2})",
+            file,
+            counts,
+            chi_square
+        );
+    }
+
+    println!("✓ Epoch positions pass a chi-square uniformity check");
+}
+
+#[test]
+fn test_mid_cycle_additions_join_next_cycle() {
+    println!("Testing files added partway through a cycle");
+
+    let mut ledger = ledger_with_files(10);
+    let files_per_epoch = 3;
+    let cycle = ledger.rotation_cycle(0, files_per_epoch);
+    assert_eq!(cycle.epochs(), 0..4);
+
+    // Two epochs in, two files are added
+    let mut selected: Vec<String> = Vec::new();
+    for epoch in 0..2 {
+        selected.extend_from_slice(cycle.files_at(epoch).unwrap());
+    }
+    ledger.add_file(&dummy_metadata("late_a", 100, 4)).unwrap();
+    ledger.add_file(&dummy_metadata("late_b", 101, 4)).unwrap();
+
+    // The stateless schedule now follows the new root, so the live cycle is reshuffled
+    let reshuffled = ledger.rotation_cycle(0, files_per_epoch);
+    assert_ne!(reshuffled.order, cycle.order);
+
+    // Finishing the held cycle covers the original files; the late files wait
+    for epoch in 2..4 {
+        selected.extend_from_slice(cycle.files_at(epoch).unwrap());
+    }
+    selected.sort();
+    let original: Vec<String> = (0..10).map(|i| format!("file_{:04}", i)).collect();
+    assert_eq!(selected, original);
+
+    // The next cycle starts at the boundary and includes them
+    let next = ledger.rotation_cycle(cycle.epochs().end, files_per_epoch);
+    assert_eq!(next.epochs(), 4..8);
+    let mut covered: Vec<String> = next
+        .epochs()
+        .flat_map(|epoch| next.files_at(epoch).unwrap().to_vec())
+        .collect();
+    covered.sort();
+    assert_eq!(
+        covered,
+        ledger.file_ids().map(str::to_owned).collect::<Vec<_>>()
+    );
+    assert!(covered.contains(&"late_a".to_string()));
+
+    println!("✓ Late files joined at the next cycle boundary");
+}
+
+#[test]
+fn test_degenerate_inputs() {
+    let empty = FileLedger::new();
+    assert!(empty.rotation_schedule(0, 3).is_empty());
+    assert!(empty.rotation_schedule(7, 3).is_empty());
+    assert_eq!(empty.rotation_cycle(5, 3).epochs(), 5..6);
+
+    // A zero rate selects one file per epoch
+    let ledger = ledger_with_files(4);
+    assert_eq!(ledger.rotation_schedule(0, 0).len(), 1);
+    assert_cycle_covers(&ledger, 4, 1);
+    assert_eq!(
+        ledger.rotation_schedule(5, 0),
+        ledger.rotation_schedule(5, 1)
+    );
+}
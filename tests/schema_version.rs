@@ -0,0 +1,144 @@
+//! Tests for the public IO schema version (`config::PUBLIC_IO_SCHEMA_VERSION`)
+//!
+//! This module tests that:
+//! 1. `PublicIOLayout` reports the schema version and names every public field at its index
+//! 2. Proofs record the schema version; a serialized proof with the embedded version bumped
+//!    fails `verify` and `preverify` with `SchemaVersionMismatch`, not a SNARK failure
+//! 3. Parameter cache files record the schema version; loading a file of another version
+//!    fails with `SchemaVersionMismatch`, and `gc` clears it
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, Proof},
+    config::{PublicIOLayout, PUBLIC_IO_SCHEMA_VERSION},
+    params::{self, GcPolicy, Shape},
+    KontorPoRError,
+};
+use std::fs;
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+#[test]
+fn test_layout_describes_every_field() {
+    let layout = PublicIOLayout::new(2);
+    assert_eq!(layout.schema_version(), PUBLIC_IO_SCHEMA_VERSION);
+
+    let names = layout.describe();
+    assert_eq!(names.len(), layout.arity());
+    assert_eq!(names[layout.idx_agg_root()], "aggregated_root");
+    assert_eq!(names[layout.idx_state_in()], "state_in");
+    for slot in 0..2 {
+        assert_eq!(
+            names[layout.idx_ledger(slot)],
+            format!("ledger_index_{}", slot)
+        );
+        assert_eq!(
+            names[layout.idx_depth(slot)],
+            format!("actual_depth_{}", slot)
+        );
+        assert_eq!(names[layout.idx_seed(slot)], format!("seed_{}", slot));
+        assert_eq!(names[layout.idx_leaf(slot)], format!("leaf_{}", slot));
+    }
+}
+
+#[test]
+fn test_proof_from_another_schema_is_rejected_up_front() {
+    println!("Testing a proof whose embedded schema version was bumped");
+
+    let (files, metadatas) = create_test_files(2, 200, 31);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(5u64)))
+        .collect();
+    let prepared = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(prepared, &challenges).unwrap();
+    assert_eq!(proof.schema_version, PUBLIC_IO_SCHEMA_VERSION);
+
+    // The schema version is the last field of the encoding, a little-endian u32
+    let mut bytes = proof.to_bytes().unwrap();
+    let at = bytes.len() - 4;
+    assert_eq!(bytes[at..], PUBLIC_IO_SCHEMA_VERSION.to_le_bytes());
+    bytes[at] += 1;
+    let bumped = Proof::from_bytes(&bytes).unwrap();
+    assert_eq!(bumped.schema_version, PUBLIC_IO_SCHEMA_VERSION + 1);
+
+    let expected = |result: &Result<(), KontorPoRError>| {
+        matches!(
+            result,
+            Err(KontorPoRError::SchemaVersionMismatch { expected, found })
+                if *expected == PUBLIC_IO_SCHEMA_VERSION && *found == PUBLIC_IO_SCHEMA_VERSION + 1
+        )
+    };
+    let verified = system.verify(&bumped, &challenges).map(|_| ());
+    assert!(expected(&verified), "got {:?}", verified);
+    let preverified = system.preverify(&bumped, &challenges).map(|_| ());
+    assert!(expected(&preverified), "got {:?}", preverified);
+    assert!(!bumped.same_statement(&proof));
+
+    // The untouched proof still verifies
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Bumped schema version reported as SchemaVersionMismatch");
+}
+
+#[test]
+fn test_params_from_another_schema_are_rejected() {
+    println!("Testing a parameter cache file of another schema version");
+
+    let dir = std::env::temp_dir().join(format!("kontor_schema_params_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    params::set_cache_dir(Some(dir.clone()));
+    let shape = Shape::new(1, 1, 0);
+
+    let params = params::load_or_generate_params_for_shape(&shape).unwrap();
+    assert_eq!(params.schema_version, PUBLIC_IO_SCHEMA_VERSION);
+    let path = params::cache_file_path(&shape).unwrap();
+    // Other tests in this binary may cache their shapes here too
+    let entry = |path: &std::path::Path| {
+        params::cache_stats(&dir)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.path == path)
+            .unwrap()
+    };
+    assert_eq!(entry(&path).schema_version, Some(PUBLIC_IO_SCHEMA_VERSION));
+    assert!(entry(&path).is_loadable());
+
+    // Header: magic (4), format version (2), schema version (4)
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[6..10].copy_from_slice(&(PUBLIC_IO_SCHEMA_VERSION + 1).to_le_bytes());
+    fs::write(&path, &bytes).unwrap();
+    params::clear_memory_cache();
+
+    let result = params::load_or_generate_params_for_shape(&shape).map(|_| ());
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::SchemaVersionMismatch { expected, found })
+                if expected == PUBLIC_IO_SCHEMA_VERSION && found == PUBLIC_IO_SCHEMA_VERSION + 1
+        ),
+        "got {:?}",
+        result
+    );
+    // The file is left for the operator rather than replaced
+    assert!(path.exists());
+
+    let stale = entry(&path);
+    assert_eq!(stale.shape, Some(shape));
+    assert_eq!(stale.schema_version, Some(PUBLIC_IO_SCHEMA_VERSION + 1));
+    assert!(stale.is_current_format());
+    assert!(!stale.is_loadable());
+    let report = params::gc(&dir, GcPolicy::KeepShapes(&[shape])).unwrap();
+    assert!(report.removed.iter().any(|e| e.path == path));
+    assert!(!path.exists());
+
+    // Once cleared, the shape regenerates
+    params::load_or_generate_params_for_shape(&shape).unwrap();
+    assert!(path.exists());
+
+    params::set_cache_dir(None);
+    fs::remove_dir_all(&dir).ok();
+    println!("✓ Stale parameters reported and cleared by gc");
+}
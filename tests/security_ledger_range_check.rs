@@ -5,6 +5,7 @@
 
 use ff::Field;
 use kontor_crypto::circuit::{FileProofWitness, PorCircuit};
+use kontor_crypto::config;
 use kontor_crypto::merkle::F as FieldElement;
 use nova_snark::frontend::{
     gadgets::num::AllocatedNum, util_cs::test_cs::TestConstraintSystem, ConstraintSystem,
@@ -39,9 +40,11 @@ fn test_verifier_index_responsibility() {
         ];
 
         let circuit = PorCircuit::<FieldElement>::new(
-            2, // files_per_step
-            2, // file_tree_depth
-            aggregated_depth,
+            config::Shape::new(
+                2, // files_per_step
+                2, // file_tree_depth
+                aggregated_depth,
+            ),
             Some(witnesses),
         );
 
@@ -93,9 +96,11 @@ fn test_verifier_index_responsibility() {
         ];
 
         let circuit = PorCircuit::<FieldElement>::new(
-            2, // files_per_step
-            2, // file_tree_depth
-            aggregated_depth,
+            config::Shape::new(
+                2, // files_per_step
+                2, // file_tree_depth
+                aggregated_depth,
+            ),
             Some(witnesses),
         );
 
@@ -189,9 +194,11 @@ fn test_verifier_prevents_bit_masking_attacks() {
         ];
 
         let circuit = PorCircuit::<FieldElement>::new(
-            2, // files_per_step
-            2, // file_tree_depth
-            aggregated_depth,
+            config::Shape::new(
+                2, // files_per_step
+                2, // file_tree_depth
+                aggregated_depth,
+            ),
             Some(witnesses),
         );
 
@@ -54,7 +54,7 @@ fn test_wrong_aggregated_root_fails_verification() {
         .expect("Should generate proof with legitimate ledger");
 
     // SECURITY TEST 1: Verify with correct aggregated root (should succeed)
-    let correct_root = legitimate_ledger.tree.root();
+    let correct_root = legitimate_ledger.root();
     let valid_result = system
         .verify(&proof, &challenges)
         .expect("Verification with correct root should complete");
@@ -72,7 +72,7 @@ fn test_wrong_aggregated_root_fails_verification() {
         .expect("Should add file 1 to malicious ledger");
     // Note: deliberately omitting file 2, so malicious ledger has different root
 
-    let malicious_root = malicious_ledger.tree.root();
+    let malicious_root = malicious_ledger.root();
 
     // Verify that the roots are actually different
     assert_ne!(
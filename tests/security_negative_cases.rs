@@ -164,15 +164,3 @@ fn test_zero_challenges_rejected() {
 
 // Note: test_too_many_files_rejected removed - too complex and slow for the benefit
 // The PRACTICAL_MAX_FILES limit is validated, but creating 1000+ test files is impractical
-
-#[test]
-fn test_empty_data_rejected() {
-    println!("Testing that empty data is rejected");
-
-    let empty_data = b""; // Empty data
-
-    // Should fail during prepare_file
-    let result = api::prepare_file(empty_data, "test_file.dat");
-    assert_error_contains(result, "Empty");
-    println!("✓ Empty data correctly rejected");
-}
@@ -0,0 +1,153 @@
+//! Tests for proofs that embed their challenges (`ProveOptions::embed_challenges`)
+//!
+//! This module tests that:
+//! 1. A proof with embedded challenges verifies with `PorSystem::verify_self_contained`
+//!    and no external challenges, after serialization and against a historical root
+//! 2. `Proof::size_breakdown` reports the embedded challenges' share of the proof size
+//! 3. Tampered embedded metadata is rejected by the ledger rc check, and other tampered
+//!    challenge fields by the challenge ID check; proofs without embedded challenges are
+//!    rejected
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, ProveOptions},
+    FileLedger, KontorPoRError, Proof,
+};
+
+mod common;
+use common::fixtures::create_test_files;
+
+fn embedding() -> ProveOptions {
+    ProveOptions::default().with_embed_challenges(true)
+}
+
+/// Copy of `proof` with `tamper` applied to its embedded challenges.
+fn tampered(proof: &Proof, tamper: impl FnOnce(&mut Vec<Challenge>)) -> Proof {
+    let mut copy = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    tamper(copy.embedded_challenges.as_mut().unwrap());
+    copy
+}
+
+#[test]
+fn test_self_contained_proof_verifies() {
+    println!("Testing verification from embedded challenges");
+
+    let (files, metadatas) = create_test_files(3, 300, 1670);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas[..2]).unwrap();
+    ledger.record_current_root().unwrap();
+
+    let seed = FieldElement::from(167u64);
+    let challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let prepared = vec![&files[&metadatas[0].file_id], &files[&metadatas[1].file_id]];
+
+    let (proof, plain) = {
+        let system = PorSystem::new(&ledger);
+        let proof = system
+            .prove_with_options(prepared.clone(), &challenges, &embedding())
+            .unwrap();
+        let plain = system.prove(prepared, &challenges).unwrap();
+        assert_eq!(proof.embedded_challenges.as_deref(), Some(&challenges[..]));
+        assert!(plain.embedded_challenges.is_none());
+
+        let restored = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert!(system.verify_self_contained(&restored).unwrap());
+        assert!(system.verify(&proof, &challenges).unwrap());
+        (proof, plain)
+    };
+
+    // Embedding adds the challenges and nothing else
+    let embedded_size = proof.size_breakdown().unwrap();
+    let plain_size = plain.size_breakdown().unwrap();
+    assert_eq!(embedded_size.total, proof.to_bytes().unwrap().len());
+    assert_eq!(
+        embedded_size.total,
+        embedded_size.snark + embedded_size.embedded_challenges + embedded_size.statement
+    );
+    assert_eq!(embedded_size.snark, plain_size.snark);
+    assert_eq!(embedded_size.statement, plain_size.statement);
+    assert!(embedded_size.embedded_challenges > plain_size.embedded_challenges);
+    assert_eq!(
+        embedded_size.total - plain_size.total,
+        embedded_size.embedded_challenges - plain_size.embedded_challenges
+    );
+    println!(
+        "  embedding 2 challenges adds {} bytes",
+        embedded_size.total - plain_size.total
+    );
+
+    // Still verifies once the ledger has moved on
+    ledger.add_file(&metadatas[2]).unwrap();
+    assert_ne!(ledger.root(), proof.ledger_root);
+    assert!(PorSystem::new(&ledger)
+        .verify_self_contained(&proof)
+        .unwrap());
+
+    println!("✓ Self-contained proofs verify without external challenges");
+}
+
+#[test]
+fn test_tampered_embedded_challenges_are_rejected() {
+    println!("Testing rejection of tampered embedded challenges");
+
+    let (files, metadatas) = create_test_files(2, 300, 1680);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    let system = PorSystem::new(&ledger);
+
+    let seed = FieldElement::from(168u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let proof = system
+        .prove_with_options(files.values().collect(), &challenges, &embedding())
+        .unwrap();
+    assert!(system.verify_self_contained(&proof).unwrap());
+
+    // Metadata committing to another root fails the rc check against the ledger
+    let other_root = tampered(&proof, |challenges| {
+        challenges[0].file_metadata.root = FieldElement::from(12345u64);
+    });
+    let result = system.verify_self_contained(&other_root);
+    assert!(
+        matches!(result, Err(KontorPoRError::EmbeddedMetadataMismatch { ref file_id }) if *file_id == metadatas[0].file_id),
+        "expected EmbeddedMetadataMismatch, got {result:?}"
+    );
+
+    let unknown_file = tampered(&proof, |challenges| {
+        challenges[1].file_metadata.file_id = "not_in_ledger".to_string();
+    });
+    let result = system.verify_self_contained(&unknown_file);
+    assert!(
+        matches!(result, Err(KontorPoRError::FileNotInLedger { ref file_id }) if file_id == "not_in_ledger"),
+        "expected FileNotInLedger, got {result:?}"
+    );
+
+    // Fields outside the metadata change the challenge IDs the proof records
+    let other_seed = tampered(&proof, |challenges| {
+        challenges[0].seed = FieldElement::from(169u64);
+    });
+    let result = system.verify_self_contained(&other_seed);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidInput(ref msg)) if msg.contains("Challenge ID mismatch")),
+        "expected a challenge ID mismatch, got {result:?}"
+    );
+
+    let dropped = tampered(&proof, |challenges| {
+        challenges.pop();
+    });
+    assert!(system.verify_self_contained(&dropped).is_err());
+
+    // Proofs made without embedding need their challenges
+    let plain = system.prove(files.values().collect(), &challenges).unwrap();
+    let result = system.verify_self_contained(&plain);
+    assert!(
+        matches!(result, Err(KontorPoRError::InvalidInput(ref msg)) if msg.contains("embed")),
+        "expected InvalidInput, got {result:?}"
+    );
+
+    println!("✓ Tampered embedded challenges are rejected");
+}
@@ -2,7 +2,7 @@
 
 use ff::Field;
 use kontor_crypto::{
-    api::{Challenge, FieldElement, FileMetadata},
+    api::{Challenge, ErasureCode, FieldElement, FileMetadata, TreeArity},
     config, params,
 };
 
@@ -37,14 +37,20 @@ fn test_param_cache_consistency() {
     params::clear_memory_cache();
 
     // First load
-    let params1 =
-        params::load_or_generate_params(files_per_step, file_tree_depth, aggregated_tree_depth)
-            .expect("Failed to load params");
+    let params1 = params::load_or_generate_params(config::Shape::new(
+        files_per_step,
+        file_tree_depth,
+        aggregated_tree_depth,
+    ))
+    .expect("Failed to load params");
 
     // Second load should come from cache
-    let params2 =
-        params::load_or_generate_params(files_per_step, file_tree_depth, aggregated_tree_depth)
-            .expect("Failed to load params");
+    let params2 = params::load_or_generate_params(config::Shape::new(
+        files_per_step,
+        file_tree_depth,
+        aggregated_tree_depth,
+    ))
+    .expect("Failed to load params");
 
     // They should have the same shape parameters
     assert_eq!(params1.file_tree_depth, params2.file_tree_depth);
@@ -61,10 +67,12 @@ fn test_param_cache_different_shapes() {
     params::clear_memory_cache();
 
     // Load params for shape 1
-    let _params1 = params::load_or_generate_params(2, 5, 0).expect("Failed to load params 1");
+    let _params1 = params::load_or_generate_params(config::Shape::new(2, 5, 0))
+        .expect("Failed to load params 1");
 
     // Load params for shape 2
-    let _params2 = params::load_or_generate_params(4, 8, 0).expect("Failed to load params 2");
+    let _params2 = params::load_or_generate_params(config::Shape::new(4, 8, 0))
+        .expect("Failed to load params 2");
 
     // Cache should have at least 2 entries (might have more if other tests ran in parallel)
     assert!(params::memory_cache_size() >= 2);
@@ -78,7 +86,11 @@ fn test_shape_from_challenges() {
         file_id: "file1".to_string(),
         padded_len: 16, // depth 4 (2^4 = 16)
         original_size: 100,
-        filename: "file1.dat".to_string(),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     };
 
     let metadata2 = FileMetadata {
@@ -86,7 +98,11 @@ fn test_shape_from_challenges() {
         file_id: "file2".to_string(),
         padded_len: 64, // depth 6 (2^6 = 64)
         original_size: 200,
-        filename: "file2.dat".to_string(),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     };
 
     let metadata3 = FileMetadata {
@@ -94,7 +110,11 @@ fn test_shape_from_challenges() {
         file_id: "file3".to_string(),
         padded_len: 8, // depth 3 (2^3 = 8)
         original_size: 50,
-        filename: "file3.dat".to_string(),
+        annotations: Default::default(),
+        tree_arity: TreeArity::Binary,
+        erasure: Some(ErasureCode::ReedSolomon),
+        salt_commitment: None,
+        hash_profile: Default::default(),
     };
 
     let challenges = [
@@ -0,0 +1,163 @@
+//! Tests for the typed circuit shape (`config::Shape`) shared by prover and verifier
+//!
+//! This module tests that:
+//! 1. `Shape::for_challenges` derives the shape the plan and the proof record
+//! 2. Shapes validate, format as parameter cache names and round-trip through serde
+//! 3. A proof whose recorded shape differs from the challenge-derived one is rejected
+//!    with `ShapeMismatch`, before any parameters are loaded for it
+//! 4. Against a historical root, a recorded aggregated depth outside the allowed range is
+//!    rejected before a shape is built from it
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    config, params, FileLedger, KontorPoRError, Proof,
+};
+
+mod common;
+use common::fixtures::create_test_files;
+
+#[test]
+fn test_shape_for_challenges_matches_proof() {
+    println!("Testing shape derivation from challenges");
+
+    let (files, metadatas) = create_test_files(3, 200, 810);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+
+    let seed = FieldElement::from(81u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+
+    let shape = config::Shape::for_challenges(&challenges, &ledger).unwrap();
+    assert_eq!(
+        shape,
+        api::plan::build_plan(&challenges, &ledger).unwrap().shape()
+    );
+    assert_eq!(shape.files_per_step, 4);
+    assert_eq!(shape.aggregated_tree_depth, ledger.depth());
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert_eq!(proof.shape, shape);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // Duplicates are collapsed as proving collapses them
+    let duplicated = [challenges.clone(), challenges[..1].to_vec()].concat();
+    assert_eq!(
+        config::Shape::for_challenges(&duplicated, &ledger).unwrap(),
+        shape
+    );
+
+    println!("✓ Prover, plan and proof agree on {shape}");
+}
+
+#[test]
+fn test_shape_validation_and_encoding() {
+    println!("Testing shape validation, Display and serde");
+
+    let shape = config::Shape::try_new(2, 5, 1).unwrap();
+    assert_eq!(shape, config::Shape::new(2, 5, 1));
+    assert_eq!(shape, config::Shape::derive(2, 5, 1));
+    assert_eq!(shape.to_string(), "2x5_agg1");
+
+    let json = serde_json::to_string(&shape).unwrap();
+    assert_eq!(serde_json::from_str::<config::Shape>(&json).unwrap(), shape);
+
+    // Parameter cache files keep their names
+    let cache_dir = std::env::temp_dir().join("kontor_shape_negotiation");
+    params::set_cache_dir(Some(cache_dir.clone()));
+    let path = params::cache_file_path(&params::Shape::from(shape)).unwrap();
+    params::set_cache_dir(None);
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("por_public_2x5_agg1_v"), "got {name}");
+
+    for (files_per_step, file_tree_depth) in [(0, 5), (3, 5), (2, 0)] {
+        let result = config::Shape::try_new(files_per_step, file_tree_depth, 1);
+        assert!(
+            matches!(result, Err(KontorPoRError::InvalidInput(_))),
+            "{files_per_step}x{file_tree_depth} must be rejected, got {result:?}"
+        );
+    }
+    let too_wide = config::Shape::new(config::PRACTICAL_MAX_FILES * 2, 5, 1);
+    assert!(matches!(
+        too_wide.validate(),
+        Err(KontorPoRError::TooManyFiles { .. })
+    ));
+
+    println!("✓ Shapes validate and encode as expected");
+}
+
+#[test]
+fn test_tampered_shape_is_rejected() {
+    println!("Testing rejection of a proof recording the wrong shape");
+
+    let (files, metadatas) = create_test_files(2, 200, 820);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+
+    let seed = FieldElement::from(82u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+
+    for tamper in [
+        |shape: &mut config::Shape| shape.file_tree_depth += 1,
+        |shape: &mut config::Shape| shape.files_per_step *= 2,
+        |shape: &mut config::Shape| shape.aggregated_tree_depth += 1,
+    ] {
+        let mut tampered = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        tamper(&mut tampered.shape);
+        let result = system.verify(&tampered, &challenges);
+        assert!(
+            matches!(
+                result,
+                Err(KontorPoRError::ShapeMismatch { proof: recorded, expected })
+                    if recorded == tampered.shape && expected == proof.shape
+            ),
+            "expected ShapeMismatch, got {result:?}"
+        );
+    }
+
+    println!("✓ Mismatched shapes are rejected");
+}
+
+#[test]
+fn test_historical_root_bounds_aggregated_depth() {
+    println!("Testing the aggregated depth bound of proofs against historical roots");
+
+    let (files, metadatas) = create_test_files(3, 200, 830);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas[..2]).unwrap();
+
+    let seed = FieldElement::from(83u64);
+    let challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 1, seed))
+        .collect();
+    let proof = PorSystem::new(&ledger)
+        .prove(files.values().take(2).collect(), &challenges)
+        .unwrap();
+
+    // The proof's root becomes historical once the ledger moves on
+    ledger.record_current_root().unwrap();
+    ledger.add_file(&metadatas[2]).unwrap();
+    let system = PorSystem::new(&ledger);
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    for depth in [0, config::MAX_AGG_DEPTH + 1, usize::MAX] {
+        let mut tampered = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        tampered.shape.aggregated_tree_depth = depth;
+        let result = system.verify(&tampered, &challenges);
+        assert!(
+            matches!(result, Err(KontorPoRError::InvalidInput(_))),
+            "aggregated depth {depth} must be rejected, got {result:?}"
+        );
+    }
+
+    println!("✓ Historical-root proofs cannot claim an unbounded aggregated depth");
+}
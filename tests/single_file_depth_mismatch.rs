@@ -8,12 +8,14 @@ use kontor_crypto::{
     api::FieldElement,
     circuit::CircuitWitness,
     circuit::{FileProofWitness, PorCircuit},
+    circuit_safety::{diagnose_unsatisfied, ConstraintClass},
+    config,
 };
 use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
 use nova_snark::traits::circuit::StepCircuit;
 
 mod common;
-use common::fixtures::create_circuit_public_inputs;
+use common::fixtures::{circuit_public_input_values, create_circuit_public_inputs};
 
 #[test]
 fn test_single_file_depth_mismatch_rejected() {
@@ -32,17 +34,16 @@ fn test_single_file_depth_mismatch_rejected() {
 
     let circuit_witness = CircuitWitness::new(vec![witness], 1);
     let circuit = PorCircuit::new(
-        1, // files_per_step (single-file)
-        3, // file_tree_depth
-        0, // aggregated_tree_depth (single-file)
+        config::Shape::new(
+            1, // files_per_step (single-file)
+            3, // file_tree_depth
+            0, // aggregated_tree_depth (single-file)
+        ),
         Some(circuit_witness.witnesses().to_vec()),
     );
 
-    let mut cs = TestConstraintSystem::<FieldElement>::new();
-
     // Create public inputs with WRONG depth (claim depth=1 when actual is 2)
-    let z_in = create_circuit_public_inputs(
-        &mut cs,
+    let z_in = circuit_public_input_values(
         FieldElement::from(999u64), // aggregated_root
         FieldElement::ZERO,         // state_in
         FieldElement::from(42u64),  // seed
@@ -51,15 +52,16 @@ fn test_single_file_depth_mismatch_rejected() {
         &[FieldElement::ZERO],      // leaves
     );
 
-    // Synthesize the circuit
-    let result = circuit.synthesize(&mut cs, &z_in);
-    assert!(result.is_ok(), "Circuit synthesis should succeed");
+    let report = diagnose_unsatisfied(&circuit, &z_in).expect("Circuit synthesis should succeed");
 
     // The circuit should NOT be satisfied due to depth mismatch
-    // sum(active_flags) = 2 but public_depth = 1
-    assert!(
-        !cs.is_satisfied(),
-        "Circuit should reject depth mismatch: witness depth=2 vs public depth=1"
+    // sum(active_flags) = 2 but public_depth = 1 (the root is also wrong, but the depth
+    // binding is checked first)
+    assert_eq!(
+        report.class(),
+        Some(ConstraintClass::DepthBinding),
+        "Circuit should reject depth mismatch: witness depth=2 vs public depth=1, got {:?}",
+        report.unsatisfied
     );
 
     println!("✓ Single-file depth mismatch correctly rejected");
@@ -82,9 +84,11 @@ fn test_single_file_zero_depth_accepted() {
 
     let circuit_witness = CircuitWitness::new(vec![witness], 1);
     let circuit = PorCircuit::new(
-        1, // files_per_step (single-file)
-        3, // file_tree_depth
-        0, // aggregated_tree_depth (single-file)
+        config::Shape::new(
+            1, // files_per_step (single-file)
+            3, // file_tree_depth
+            0, // aggregated_tree_depth (single-file)
+        ),
         Some(circuit_witness.witnesses().to_vec()),
     );
 
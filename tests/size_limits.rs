@@ -0,0 +1,95 @@
+//! Tests for file size limits and overflow-safe sizing
+//!
+//! This module tests that:
+//! 1. Padded leaf counts near `usize::MAX` are reported as overflow instead of wrapping
+//! 2. Metadata claiming more than `MAX_FILE_SIZE` bytes, or a padded length no such file
+//!    can have, is rejected by reconstruction, rebuilding, planning and witness generation
+//! 3. Real files stay within the limits
+//!
+//! All oversized inputs are synthetic metadata; no large buffers are allocated.
+
+use kontor_crypto::{
+    api::{self, plan::build_plan, prepare_file, Challenge, FieldElement, FileMetadata, TreeArity},
+    config::MAX_FILE_SIZE,
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::create_test_data;
+
+fn hostile(metadata: &FileMetadata, original_size: usize, padded_len: usize) -> FileMetadata {
+    FileMetadata {
+        original_size,
+        padded_len,
+        ..metadata.clone()
+    }
+}
+
+#[test]
+fn test_checked_padded_len_near_overflow() {
+    let top = 1usize << (usize::BITS - 1);
+
+    assert_eq!(TreeArity::Binary.checked_padded_len(top), Some(top));
+    assert_eq!(TreeArity::Binary.checked_padded_len(top + 1), None);
+    assert_eq!(TreeArity::Binary.checked_padded_len(usize::MAX), None);
+
+    // 2^63 is not a power of four, and doubling it overflows
+    assert_eq!(TreeArity::Arity4.checked_padded_len(top), None);
+    assert_eq!(TreeArity::Arity4.checked_padded_len(top / 2), Some(top / 2));
+    assert_eq!(
+        TreeArity::Arity4.checked_padded_len(top / 4 + 1),
+        Some(top / 2)
+    );
+    assert_eq!(TreeArity::Binary.checked_padded_len(0), Some(1));
+}
+
+#[test]
+fn test_hostile_metadata_rejected() {
+    println!("Testing size checks on hostile metadata");
+
+    let data = create_test_data(1000, Some(3));
+    let (prepared, metadata) = prepare_file(&data, "sized.dat").unwrap();
+    metadata.check_size_limits().unwrap();
+    assert!(metadata.padded_len <= metadata.max_padded_len());
+
+    // Sizes derived from the largest accepted file fit comfortably
+    let largest = hostile(&metadata, MAX_FILE_SIZE as usize, metadata.padded_len);
+    largest.check_size_limits().unwrap();
+    assert!(largest.total_symbols() <= largest.max_padded_len());
+    assert!(largest
+        .max_padded_len()
+        .checked_mul(kontor_crypto::config::CHUNK_SIZE_BYTES)
+        .is_some());
+
+    let too_large = hostile(&metadata, usize::MAX, metadata.padded_len);
+    assert_eq!(too_large.total_symbols(), too_large.num_codewords() * 255);
+    let symbols: Vec<Option<Vec<u8>>> = vec![None; metadata.total_symbols()];
+    assert!(matches!(
+        api::reconstruct_file(&symbols, &too_large),
+        Err(KontorPoRError::FileTooLarge { size, max })
+            if size == usize::MAX as u64 && max == MAX_FILE_SIZE
+    ));
+
+    let too_many_leaves = hostile(&metadata, metadata.original_size, 1 << 60);
+    assert!(matches!(
+        too_many_leaves.check_size_limits(),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("padded_len")
+    ));
+    assert!(matches!(
+        api::rebuild_prepared(&[], &too_many_leaves),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("padded_len")
+    ));
+
+    // The plan (shared by prover and verifier) rejects the claim before deriving a shape
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+    for bad in [too_large, too_many_leaves] {
+        let challenge = Challenge::new_test(bad, 1000, 1, FieldElement::from(1u64));
+        assert!(build_plan(std::slice::from_ref(&challenge), &ledger).is_err());
+        assert!(api::PorSystem::new(&ledger)
+            .prove(vec![&prepared], &[challenge])
+            .is_err());
+    }
+
+    println!("✓ Oversized metadata is rejected everywhere it is consumed");
+}
@@ -0,0 +1,131 @@
+//! Tests for native spot checks against stored symbols
+//!
+//! This module tests that:
+//! 1. Spot checks open exactly the leaves a real proof would open
+//! 2. A corrupted symbol fails only its own index
+//! 3. Missing symbols fail their index
+
+use ff::Field;
+use kontor_crypto::{
+    api::{
+        self, generate_circuit_witness, spot_check, Challenge, FieldElement, PorSystem,
+        PreparedFile, SymbolStore,
+    },
+    get_leaf_hash, CircuitMerkleProof, FileLedger, Result,
+};
+use std::collections::BTreeMap;
+
+/// Wraps a prepared file, serving a damaged (or missing) copy of one symbol.
+struct DamagedStore<'a> {
+    inner: &'a PreparedFile,
+    damaged_index: usize,
+    missing: bool,
+}
+
+impl SymbolStore for DamagedStore<'_> {
+    fn symbol(&self, index: usize) -> Option<Vec<u8>> {
+        let mut symbol = self.inner.symbol(index)?;
+        if index == self.damaged_index {
+            if self.missing {
+                return None;
+            }
+            symbol[0] ^= 0x01;
+        }
+        Some(symbol)
+    }
+
+    fn merkle_proof(&self, index: usize) -> Result<CircuitMerkleProof> {
+        self.inner.merkle_proof(index)
+    }
+}
+
+#[test]
+fn test_spot_check_matches_real_proof() {
+    println!("Testing that spot_check opens the same leaves as a real proof");
+
+    let data: Vec<u8> = (0..5000u32).map(|i| (i % 253) as u8).collect();
+    let (prepared, metadata) = api::prepare_file(&data, "spot.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 6, FieldElement::from(4242u64));
+    let result = spot_check(&prepared, &challenge).unwrap();
+    assert_eq!(result.indices.len(), 6);
+    assert!(result.all_passed(), "Honest store must pass every index");
+
+    // Replay witness generation step by step and compare the opened leaves
+    let files: BTreeMap<String, &PreparedFile> =
+        BTreeMap::from([(metadata.file_id.clone(), &prepared)]);
+    let depth = metadata.depth();
+    let mut state = FieldElement::ZERO;
+    for entry in &result.indices {
+        let (witness, next_state) = generate_circuit_witness(
+            &[&challenge],
+            Some(&files),
+            &ledger,
+            depth,
+            depth,
+            state,
+            0,
+            entry.step,
+            &[0],
+        )
+        .unwrap();
+
+        let expected_leaf = get_leaf_hash(&prepared.symbol(entry.leaf_index).unwrap()).unwrap();
+        assert_eq!(
+            witness.witnesses()[0].leaf,
+            expected_leaf,
+            "Step {} opened a different leaf than spot_check",
+            entry.step
+        );
+        state = next_state;
+    }
+
+    // The real proof for the same seed verifies
+    let system = PorSystem::new(&ledger);
+    let proof = system
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    assert!(system.verify(&proof, &[challenge]).unwrap());
+
+    println!("✓ spot_check and proof generation agree on challenged leaves");
+}
+
+#[test]
+fn test_spot_check_corrupted_symbol_fails_only_its_index() {
+    println!("Testing that corrupting one symbol flips exactly its index");
+
+    let data: Vec<u8> = (0..8000u32).map(|i| (i * 13 % 251) as u8).collect();
+    let (prepared, metadata) = api::prepare_file(&data, "corrupt.dat").unwrap();
+    let challenge = Challenge::new_test(metadata, 1000, 8, FieldElement::from(77u64));
+
+    let honest = spot_check(&prepared, &challenge).unwrap();
+    assert!(honest.all_passed());
+
+    for missing in [false, true] {
+        let target = honest.indices[3].leaf_index;
+        let store = DamagedStore {
+            inner: &prepared,
+            damaged_index: target,
+            missing,
+        };
+        let damaged = spot_check(&store, &challenge).unwrap();
+
+        // Same indices are challenged; only the damaged one fails
+        for (before, after) in honest.indices.iter().zip(&damaged.indices) {
+            assert_eq!(before.leaf_index, after.leaf_index);
+            assert_eq!(
+                after.passed,
+                after.leaf_index != target,
+                "Unexpected result at step {} (missing={})",
+                after.step,
+                missing
+            );
+        }
+        assert!(damaged.failed_indices().iter().all(|&i| i == target));
+        assert!(!damaged.failed_indices().is_empty());
+    }
+
+    println!("✓ Only the corrupted index fails");
+}
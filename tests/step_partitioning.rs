@@ -0,0 +1,277 @@
+//! Tests for batches partitioned into passes over a capped slot count
+//!
+//! This module tests that:
+//! 1. `derive_partition` and `build_plan` split 9 and 17 files into passes of 8 slots in
+//!    canonical order, with padding only in the last pass
+//! 2. Proofs of 9 and 17 files with `max_files_per_step` 8 verify with one proof
+//! 3. Removing any one file's challenge makes verification fail
+//! 4. A prover that answers the first pass again instead of the second cannot make a
+//!    proof that verifies
+//! 5. Partitioned batches reject per-step seeds, mismatched caps and caps that are not
+//!    powers of two
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, SeedSchedule, VerifyOptions},
+    circuit::PorCircuit,
+    config::{self, PublicIOLayout},
+    FileLedger, KontorPoRError,
+};
+use nova_snark::{
+    nova::{CompressedSNARK, PublicParams, RecursiveSNARK},
+    traits::snark::RelaxedR1CSSNARKTrait,
+};
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files, E1, E2, F1, S1, S2};
+
+const MAX_FILES_PER_STEP: usize = 8;
+/// Shared by both ledgers so the 9- and 17-file proofs use one parameter set
+const MAX_AGG_DEPTH: usize = 5;
+
+fn capped_challenges(metadatas: &[api::FileMetadata], num_challenges: usize) -> Vec<Challenge> {
+    metadatas
+        .iter()
+        .map(|m| {
+            Challenge::new_test(m.clone(), 1000, num_challenges, FieldElement::from(31u64))
+                .with_max_files_per_step(MAX_FILES_PER_STEP)
+                .with_max_agg_depth(MAX_AGG_DEPTH)
+        })
+        .collect()
+}
+
+/// Proves `num_files` capped challenges, checks the proof verifies and that dropping any
+/// one challenge changes the outcome.
+fn prove_partitioned(num_files: usize, expected_passes: usize) {
+    let (files, metadatas) = create_test_files(num_files, 300, 91);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges = capped_challenges(&metadatas, 2);
+
+    let plan = api::plan::build_plan(&challenges, &ledger).unwrap();
+    assert_eq!(plan.files_per_step, MAX_FILES_PER_STEP);
+    assert_eq!(plan.num_passes, expected_passes);
+    assert_eq!(plan.total_steps, 2 * expected_passes);
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert_eq!(
+        proof.ledger_indices.len(),
+        MAX_FILES_PER_STEP * expected_passes
+    );
+    assert_eq!(proof.covered_indices().len(), num_files);
+    assert!(
+        system.verify(&proof, &challenges).unwrap(),
+        "{}-file partitioned proof must verify",
+        num_files
+    );
+
+    for removed in 0..challenges.len() {
+        let mut subset = challenges.clone();
+        subset.remove(removed);
+        assert!(
+            !matches!(system.verify(&proof, &subset), Ok(true)),
+            "Proof must not verify without challenge {}",
+            removed
+        );
+    }
+    check_last_pass_leaves(&system, &proof, &challenges, &ledger);
+}
+
+/// Only files in the last pass have public leaf outputs.
+fn check_last_pass_leaves(
+    system: &PorSystem,
+    proof: &api::Proof,
+    challenges: &[Challenge],
+    ledger: &FileLedger,
+) {
+    let plan = api::plan::build_plan(challenges, ledger).unwrap();
+    let first_file = plan.slot_file_ids()[0].to_string();
+    let expected = BTreeMap::from([((first_file, 1), FieldElement::from(0u64))]);
+    let options = VerifyOptions::default().with_expected_leaves(expected);
+    assert!(matches!(
+        system.verify_with_options(proof, challenges, &options),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_partition_plans() {
+    println!("Testing partitions of 9 and 17 files into passes of 8 slots");
+
+    assert_eq!(config::derive_partition(5, None), (8, 1));
+    assert_eq!(config::derive_partition(5, Some(8)), (8, 1));
+    assert_eq!(config::derive_partition(9, Some(8)), (8, 2));
+    assert_eq!(config::derive_partition(17, Some(8)), (8, 3));
+    assert_eq!(config::derive_partition(17, None), (32, 1));
+
+    for (num_files, passes) in [(9, 2), (17, 3)] {
+        let (_, metadatas) = create_test_files(num_files, 300, 91);
+        let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+        let challenges = capped_challenges(&metadatas, 3);
+        let plan = api::plan::build_plan(&challenges, &ledger).unwrap();
+
+        assert_eq!(plan.num_passes, passes);
+        assert_eq!(plan.total_steps, 3 * passes);
+        assert_eq!(plan.slot_file_ids().len(), num_files);
+        let mut sorted: Vec<&str> = metadatas.iter().map(|m| m.file_id.as_str()).collect();
+        sorted.sort();
+        assert_eq!(plan.slot_file_ids(), sorted);
+
+        for step in &plan.steps {
+            assert_eq!(step.pass, step.step % passes);
+            assert_eq!(step.slots.len(), MAX_FILES_PER_STEP);
+            let active = step.slots.iter().filter(|s| s.file_id.is_some()).count();
+            let expected = if step.pass + 1 < passes {
+                MAX_FILES_PER_STEP
+            } else {
+                num_files - (passes - 1) * MAX_FILES_PER_STEP
+            };
+            assert_eq!(active, expected, "step {}", step.step);
+            assert!(step
+                .slots
+                .iter()
+                .filter(|s| s.file_id.is_none())
+                .all(|s| s.depth == 0));
+        }
+        println!("  {} files: {} passes of 8 slots", num_files, passes);
+    }
+
+    println!("✓ Files are partitioned in canonical order");
+}
+
+#[test]
+fn test_nine_files_prove_in_two_passes() {
+    println!("Testing a 9-file proof with files_per_step capped at 8");
+    prove_partitioned(9, 2);
+    println!("✓ 9-file proof verifies and needs every challenge");
+}
+
+#[test]
+fn test_seventeen_files_prove_in_three_passes() {
+    println!("Testing a 17-file proof with files_per_step capped at 8");
+    prove_partitioned(17, 3);
+    println!("✓ 17-file proof verifies and needs every challenge");
+}
+
+#[test]
+fn test_repeated_pass_fails_verification() {
+    println!("Testing a 9-file proof whose second step answers the first pass again");
+
+    let (files, metadatas) = create_test_files(9, 300, 91);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges = capped_challenges(&metadatas, 1);
+    let system = PorSystem::new(&ledger);
+    let mut proof = system.prove(files.values().collect(), &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    // The first pass's challenges, in slot order
+    let plan = api::plan::build_plan(&challenges, &ledger).unwrap();
+    let by_file: BTreeMap<&str, &Challenge> = challenges
+        .iter()
+        .map(|c| (c.file_metadata.file_id.as_str(), c))
+        .collect();
+    let first_pass: Vec<&Challenge> = plan.steps[0]
+        .slots
+        .iter()
+        .map(|slot| by_file[slot.file_id.as_deref().unwrap()])
+        .collect();
+    let file_refs: BTreeMap<String, &api::PreparedFile> =
+        files.iter().map(|(id, f)| (id.clone(), f)).collect();
+
+    // Both steps open the first pass's files, with the first pass's inputs
+    let layout = PublicIOLayout::new(MAX_FILES_PER_STEP);
+    let z0 = proof.public_inputs.clone();
+    let first_pass_inputs =
+        z0[layout.ledger_indices_range().start..layout.seeds_range().end].to_vec();
+    let shape = proof.shape;
+    let mut state = z0[layout.idx_state_in()];
+    let circuits: Vec<PorCircuit<F1>> = (0..2)
+        .map(|_| {
+            let (witness, new_state) = api::generate_circuit_witness(
+                &first_pass,
+                Some(&file_refs),
+                &ledger,
+                shape.file_tree_depth,
+                shape.file_tree_depth,
+                state,
+                shape.aggregated_tree_depth,
+                0,
+                &proof.ledger_indices[..MAX_FILES_PER_STEP],
+            )
+            .unwrap();
+            state = new_state;
+            PorCircuit::new(shape, Some(witness.witnesses().to_vec()))
+                .with_ledger_depth(witness.ledger_depth())
+                .with_pass_inputs(Some(first_pass_inputs.clone()))
+        })
+        .collect();
+
+    let pp = PublicParams::<E1, E2, PorCircuit<F1>>::setup(
+        &circuits[0],
+        &*S1::ck_floor(),
+        &*S2::ck_floor(),
+    )
+    .unwrap();
+    let (pk, vk) = CompressedSNARK::<E1, E2, PorCircuit<F1>, S1, S2>::setup(&pp).unwrap();
+    // These are the parameters the verifier loads: the honest proof checks out under them
+    assert!(proof.compressed_snark.verify(&vk, 2, &z0).is_ok());
+
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuits[0], &z0).unwrap();
+    for circuit in [&circuits[0], &circuits[1]] {
+        recursive_snark.prove_step(&pp, circuit).unwrap();
+    }
+    let forged = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    proof.public_outputs = forged
+        .verify(&vk, 2, &z0)
+        .expect("the forged steps are individually satisfiable");
+    proof.compressed_snark = forged;
+
+    assert!(
+        !system.verify(&proof, &challenges).unwrap(),
+        "A proof that skips the second pass must not verify"
+    );
+
+    println!("✓ Every step is bound to the files of its pass");
+}
+
+#[test]
+fn test_partition_rejections() {
+    println!("Testing partitioned batch validation");
+
+    let (_, metadatas) = create_test_files(9, 300, 91);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+
+    let per_step: Vec<Challenge> = capped_challenges(&metadatas, 2)
+        .into_iter()
+        .map(|c| {
+            c.with_seed_schedule(SeedSchedule::PerStep(vec![
+                FieldElement::from(1u64),
+                FieldElement::from(2u64),
+            ]))
+        })
+        .collect();
+    assert!(matches!(
+        api::plan::build_plan(&per_step, &ledger),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // Per-step seeds remain fine while the batch fits one pass
+    assert!(api::plan::build_plan(&per_step[..8], &ledger).is_ok());
+
+    let mut mismatched = capped_challenges(&metadatas, 2);
+    mismatched[3] = mismatched[3].clone().with_max_files_per_step(4);
+    assert!(matches!(
+        api::plan::build_plan(&mismatched, &ledger),
+        Err(KontorPoRError::ChallengeMismatch { field }) if field == "max_files_per_step"
+    ));
+
+    let not_power_of_two = capped_challenges(&metadatas[..1], 2)[0]
+        .clone()
+        .with_max_files_per_step(6);
+    assert!(matches!(
+        not_power_of_two.validate(),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    println!("✓ Invalid partitioned batches are rejected");
+}
@@ -0,0 +1,88 @@
+//! Tests for streaming Merkle tree construction
+//!
+//! This module tests that:
+//! 1. `build_tree_streaming` builds the same layers and root as `build_tree_from_leaves` for 1..=1025 leaves
+//! 2. Leaves-and-root retention matches a pruned tree and still yields identical proofs
+//! 3. Empty iterators and wrong `expected_count` values are handled
+
+use kontor_crypto::merkle::{
+    build_tree_from_leaves, build_tree_streaming, build_tree_streaming_with_retention,
+    get_padded_proof_for_leaf, LayerRetention, F,
+};
+use kontor_crypto::KontorPoRError;
+
+fn leaves(count: usize) -> Vec<F> {
+    (0..count as u64).map(|i| F::from(i * 7 + 3)).collect()
+}
+
+#[test]
+fn test_streaming_matches_materialized_build() {
+    println!("Testing streaming tree construction for 1..=1025 leaves");
+
+    for count in 1..=1025 {
+        let leaves = leaves(count);
+        let expected = build_tree_from_leaves(&leaves).unwrap();
+
+        let (streamed, root) = build_tree_streaming(leaves.iter().copied(), None).unwrap();
+        assert_eq!(streamed.layers, expected.layers, "{} leaves", count);
+        assert_eq!(root, expected.root(), "{} leaves", count);
+
+        let (preallocated, root) =
+            build_tree_streaming(leaves.iter().copied(), Some(count)).unwrap();
+        assert_eq!(preallocated.layers, expected.layers, "{} leaves", count);
+        assert_eq!(root, expected.root(), "{} leaves", count);
+    }
+
+    println!("✓ Streaming and materialized builders agree");
+}
+
+#[test]
+fn test_leaves_and_root_retention() {
+    for count in (1..=1025).step_by(17).chain([2, 4, 512, 1024]) {
+        let leaves = leaves(count);
+        let mut pruned = build_tree_from_leaves(&leaves).unwrap();
+        let full = pruned.clone();
+        pruned.prune(0);
+
+        let (tree, root) = build_tree_streaming_with_retention(
+            leaves.iter().copied(),
+            Some(count),
+            LayerRetention::LeavesAndRoot,
+        )
+        .unwrap();
+        assert_eq!(tree.layers, pruned.layers, "{} leaves", count);
+        assert_eq!(root, full.root());
+        assert_eq!(tree.stored_nodes(), count + usize::from(count > 1));
+
+        let depth = full.layers.len() - 1;
+        for index in [0, count / 2, count - 1] {
+            let proof = get_padded_proof_for_leaf(&tree, index, depth).unwrap();
+            let expected = get_padded_proof_for_leaf(&full, index, depth).unwrap();
+            assert_eq!(proof.siblings, expected.siblings, "{} leaves", count);
+        }
+    }
+}
+
+#[test]
+fn test_empty_and_miscounted_iterators() {
+    let empty = build_tree_from_leaves(&[]).unwrap();
+    for retention in [LayerRetention::Full, LayerRetention::LeavesAndRoot] {
+        let (tree, root) =
+            build_tree_streaming_with_retention(std::iter::empty(), None, retention).unwrap();
+        assert_eq!(tree.layers, empty.layers);
+        assert_eq!(root, empty.root());
+    }
+
+    assert!(matches!(
+        build_tree_streaming(leaves(10).into_iter(), Some(11)),
+        Err(KontorPoRError::MerkleTree(_))
+    ));
+    assert!(matches!(
+        build_tree_streaming(leaves(10).into_iter(), Some(9)),
+        Err(KontorPoRError::MerkleTree(_))
+    ));
+    assert!(matches!(
+        build_tree_streaming(std::iter::empty(), Some(1)),
+        Err(KontorPoRError::MerkleTree(_))
+    ));
+}
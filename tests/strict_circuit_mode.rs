@@ -0,0 +1,303 @@
+//! Tests for strict circuit mode (in-circuit ledger index checks)
+//!
+//! This module tests that:
+//! 1. Strict mode adds exactly `CircuitMode::extra_constraints` constraints per step
+//! 2. An out-of-range ledger index aliasing a valid one satisfies the lenient circuit but
+//!    fails the strict one's range constraint
+//! 3. Duplicate ledger indices across active slots fail the strict distinctness constraint
+//! 4. Strict challenges prove and verify end to end, while lenient proofs rely on the
+//!    verifier's range check
+
+use ff::Field;
+use kontor_crypto::{
+    api::{self, generate_circuit_witness, Challenge, CircuitMode, FieldElement, PorSystem},
+    circuit::{FileProofWitness, PorCircuit},
+    circuit_safety::{diagnose_unsatisfied, ConstraintClass, DiagnosisReport},
+    config, KontorPoRError,
+};
+use nova_snark::frontend::util_cs::test_cs::TestConstraintSystem;
+use nova_snark::traits::circuit::StepCircuit;
+use std::collections::BTreeMap;
+
+mod common;
+use common::fixtures::{
+    circuit_public_input_values, create_circuit_public_inputs, create_multi_file_ledger,
+    create_test_files,
+};
+
+/// Synthesizes a circuit over the given public ledger indices and depths.
+fn synthesize(
+    circuit: &PorCircuit<FieldElement>,
+    root: FieldElement,
+    seed: FieldElement,
+    ledger_indices: &[usize],
+    depths: &[usize],
+) -> TestConstraintSystem<FieldElement> {
+    let mut cs = TestConstraintSystem::<FieldElement>::new();
+    let leaves = vec![FieldElement::ZERO; ledger_indices.len()];
+    let z = create_circuit_public_inputs(
+        &mut cs,
+        root,
+        FieldElement::ZERO,
+        seed,
+        ledger_indices,
+        depths,
+        &leaves,
+    );
+    circuit.synthesize(&mut cs, &z).expect("synthesis succeeds");
+    cs
+}
+
+/// One honestly generated multi-file step: witnesses, public ledger indices and depths in
+/// slot order (padding slots have index and depth zero).
+struct Step {
+    witnesses: Vec<FileProofWitness<FieldElement>>,
+    ledger_indices: Vec<usize>,
+    depths: Vec<usize>,
+    files_per_step: usize,
+    file_tree_depth: usize,
+    aggregated_tree_depth: usize,
+    root: FieldElement,
+    seed: FieldElement,
+}
+
+impl Step {
+    /// Challenges `num_files` files once each, plus the first file a second time if
+    /// `repeat_first` is set.
+    fn new(num_files: usize, repeat_first: bool) -> Self {
+        let (files, metadatas) = create_test_files(num_files, 300, 61);
+        let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+        let seed = FieldElement::from(5u64);
+
+        let mut challenges: Vec<Challenge> = metadatas
+            .iter()
+            .map(|m| Challenge::new_test(m.clone(), 1000, 1, seed))
+            .collect();
+        if repeat_first {
+            challenges.push(challenges[0].clone().with_nonce(1));
+        }
+        challenges.sort_by(|a, b| a.file_metadata.file_id.cmp(&b.file_metadata.file_id));
+
+        let max_depth = challenges
+            .iter()
+            .map(|c| api::tree_depth_from_metadata(&c.file_metadata))
+            .max()
+            .unwrap();
+        let (files_per_step, file_tree_depth) = config::derive_shape(challenges.len(), max_depth);
+        let mut ledger_indices = vec![0; files_per_step];
+        let mut depths = vec![0; files_per_step];
+        for (slot, challenge) in challenges.iter().enumerate() {
+            ledger_indices[slot] = ledger.lookup(&challenge.file_metadata.file_id).unwrap().0;
+            depths[slot] = api::tree_depth_from_metadata(&challenge.file_metadata);
+        }
+        let aggregated_tree_depth = ledger.tree_layers_len() - 1;
+
+        let file_refs: BTreeMap<String, &_> = files.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let (witness, _) = generate_circuit_witness(
+            &challenges.iter().collect::<Vec<_>>(),
+            Some(&file_refs),
+            &ledger,
+            file_tree_depth,
+            file_tree_depth,
+            FieldElement::ZERO,
+            aggregated_tree_depth,
+            0,
+            &ledger_indices,
+        )
+        .unwrap();
+
+        Self {
+            witnesses: witness.witnesses().to_vec(),
+            ledger_indices,
+            depths,
+            files_per_step,
+            file_tree_depth,
+            aggregated_tree_depth,
+            root: ledger.root(),
+            seed,
+        }
+    }
+
+    fn diagnose(
+        &self,
+        mode: CircuitMode,
+        ledger_indices: &[usize],
+    ) -> DiagnosisReport<FieldElement> {
+        let circuit = PorCircuit::new(
+            config::Shape::new(
+                self.files_per_step,
+                self.file_tree_depth,
+                self.aggregated_tree_depth,
+            ),
+            Some(self.witnesses.clone()),
+        )
+        .with_circuit_mode(mode);
+        let leaves = vec![FieldElement::ZERO; ledger_indices.len()];
+        let z = circuit_public_input_values(
+            self.root,
+            FieldElement::ZERO,
+            self.seed,
+            ledger_indices,
+            &self.depths,
+            &leaves,
+        );
+        diagnose_unsatisfied(&circuit, &z).expect("synthesis succeeds")
+    }
+
+    fn is_satisfied(&self, mode: CircuitMode, ledger_indices: &[usize]) -> bool {
+        self.diagnose(mode, ledger_indices).is_satisfied()
+    }
+}
+
+#[test]
+fn test_strict_mode_constraint_delta() {
+    println!("Testing strict-mode constraint counts");
+
+    for (files_per_step, aggregated_tree_depth) in [(1, 0), (2, 1), (4, 2), (8, 3)] {
+        let count = |mode: CircuitMode| {
+            let circuit = PorCircuit::<FieldElement>::new(
+                config::Shape::new(files_per_step, 2, aggregated_tree_depth),
+                None,
+            )
+            .with_circuit_mode(mode);
+            let indices = vec![0; files_per_step];
+            let depths = vec![0; files_per_step];
+            synthesize(
+                &circuit,
+                FieldElement::ZERO,
+                FieldElement::ZERO,
+                &indices,
+                &depths,
+            )
+            .num_constraints()
+        };
+
+        let lenient = count(CircuitMode::Lenient);
+        let strict = count(CircuitMode::Strict);
+        assert_eq!(
+            strict - lenient,
+            CircuitMode::Strict.extra_constraints(files_per_step, aggregated_tree_depth),
+            "{} slots, aggregated depth {}",
+            files_per_step,
+            aggregated_tree_depth
+        );
+        assert_eq!(
+            CircuitMode::Lenient.extra_constraints(files_per_step, aggregated_tree_depth),
+            0
+        );
+        println!(
+            "  {} slots, agg depth {}: {} lenient, {} strict (+{})",
+            files_per_step,
+            aggregated_tree_depth,
+            lenient,
+            strict,
+            strict - lenient
+        );
+    }
+
+    println!("✓ Strict mode adds files_per_step * (aggregated_tree_depth + files_per_step) constraints to multi-file circuits");
+}
+
+#[test]
+fn test_out_of_range_index_rejected_only_by_strict_circuit() {
+    println!("Testing an out-of-range ledger index aliasing a valid one");
+
+    let step = Step::new(2, false);
+    for mode in [CircuitMode::Lenient, CircuitMode::Strict] {
+        assert!(
+            step.is_satisfied(mode, &step.ledger_indices),
+            "honest indices must satisfy the {:?} circuit",
+            mode
+        );
+    }
+
+    // Same low bits, so the aggregation path and membership proof are unchanged
+    let mut aliased = step.ledger_indices.clone();
+    aliased[0] += 1 << step.aggregated_tree_depth;
+    assert!(
+        step.is_satisfied(CircuitMode::Lenient, &aliased),
+        "lenient circuits leave the range check to the verifier"
+    );
+    let report = step.diagnose(CircuitMode::Strict, &aliased);
+    assert_eq!(
+        report.class(),
+        Some(ConstraintClass::LedgerIndex),
+        "strict circuits must reject an out-of-range ledger index: {:?}",
+        report.unsatisfied
+    );
+    assert!(report
+        .unsatisfied
+        .unwrap()
+        .path
+        .ends_with("ledger_index_in_range"));
+
+    println!("✓ Aliased index accepted by the lenient circuit, rejected by the strict one");
+}
+
+#[test]
+fn test_duplicate_indices_rejected_by_strict_circuit() {
+    // Two active slots proving the same file share its ledger index
+    let repeated = Step::new(2, true);
+    assert_eq!(repeated.files_per_step, 4);
+    assert!(repeated.is_satisfied(CircuitMode::Lenient, &repeated.ledger_indices));
+    let report = repeated.diagnose(CircuitMode::Strict, &repeated.ledger_indices);
+    assert_eq!(report.class(), Some(ConstraintClass::LedgerIndex));
+    assert!(report
+        .unsatisfied
+        .unwrap()
+        .path
+        .starts_with("distinct_ledger_index_"));
+
+    // A padding slot (index 0) may coincide with the active slot at ledger index 0
+    let padded = Step::new(3, false);
+    assert_eq!(padded.depths[3], 0);
+    assert!(padded.ledger_indices[..3].contains(&0));
+    assert!(padded.is_satisfied(CircuitMode::Strict, &padded.ledger_indices));
+}
+
+#[test]
+fn test_strict_challenges_prove_and_verify() {
+    println!("Testing strict-mode proofs end to end");
+
+    let (files, metadatas) = create_test_files(2, 250, 90);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(3u64);
+    let lenient: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+    let strict: Vec<Challenge> = lenient
+        .iter()
+        .map(|c| c.clone().with_circuit_mode(CircuitMode::Strict))
+        .collect();
+    assert_ne!(strict[0].id(), lenient[0].id());
+
+    let proof = system.prove(files.values().collect(), &strict).unwrap();
+    assert!(system.verify(&proof, &strict).unwrap());
+    assert!(system.verify(&proof, &lenient).is_err());
+
+    // Lenient proofs: an aliased index is caught by the verifier, not the circuit
+    let mut lenient_proof = system.prove(files.values().collect(), &lenient).unwrap();
+    assert!(system.verify(&lenient_proof, &lenient).unwrap());
+    lenient_proof.ledger_indices[0] += 1 << lenient_proof.shape.aggregated_tree_depth;
+    assert!(matches!(
+        system.verify(&lenient_proof, &lenient),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("out of range")
+    ));
+
+    // A strict batch must agree on the mode and challenge distinct files
+    let mixed = vec![strict[0].clone(), lenient[1].clone()];
+    assert!(matches!(
+        system.prove(files.values().collect(), &mixed),
+        Err(KontorPoRError::ChallengeMismatch { field }) if field == "circuit_mode"
+    ));
+    let repeated = vec![strict[0].clone(), strict[0].clone().with_nonce(1)];
+    let first = &files[&metadatas[0].file_id];
+    assert!(matches!(
+        system.prove(vec![first], &repeated),
+        Err(KontorPoRError::InvalidInput(msg)) if msg.contains("distinct files")
+    ));
+
+    println!("✓ Strict proofs verify; lenient proofs rely on the verifier's range check");
+}
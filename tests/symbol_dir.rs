@@ -0,0 +1,125 @@
+//! Tests for the filesystem-backed symbol store
+//!
+//! This module tests that:
+//! 1. Symbols written to a directory reload intact and reconstruct the file after a
+//!    recoverable subset is deleted
+//! 2. Files of the wrong length are treated as missing and reported as warnings
+//! 3. Deleting more than 24 symbols of a codeword fails with `ErasureCoding`
+//! 4. Writes with a bad index or length are rejected
+
+use kontor_crypto::{
+    api::{self, prepare_file, prepare_file_raw, SymbolStore},
+    erasure::{encode_file_symbols, DirSymbolStore},
+    KontorPoRError,
+};
+use std::path::PathBuf;
+
+mod common;
+use common::fixtures::create_test_data;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kontor_symbols_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_reconstruct_from_dir_with_losses() {
+    println!("Testing reconstruction from a symbol directory");
+
+    let dir = temp_dir("recoverable");
+    let data = create_test_data(20_000, Some(7));
+    let (_, metadata) = prepare_file(&data, "stored.dat").unwrap();
+    let store = DirSymbolStore::open(&dir, &metadata).unwrap();
+    let symbols = encode_file_symbols(&data).unwrap();
+    assert_eq!(symbols.len(), store.total_symbols());
+    for (index, symbol) in symbols.iter().enumerate() {
+        store.write_symbol(index, symbol).unwrap();
+    }
+    assert!(store.missing_indices().unwrap().is_empty());
+    assert_eq!(
+        store.load_all().unwrap(),
+        symbols.iter().cloned().map(Some).collect::<Vec<_>>()
+    );
+
+    // Lose 20 symbols from the first codeword and one from each of the others
+    let mut lost: Vec<usize> = (0..20).collect();
+    lost.extend((1..metadata.num_codewords()).map(|cw| cw * 255 + 100));
+    for index in &lost {
+        std::fs::remove_file(store.symbol_path(*index)).unwrap();
+    }
+    // Truncate one more symbol in the first codeword and leave stray files around
+    std::fs::write(store.symbol_path(30), [0u8; 12]).unwrap();
+    std::fs::write(dir.join("notes.txt"), b"not a symbol").unwrap();
+    std::fs::write(dir.join(symbols.len().to_string()), [0u8; 31]).unwrap();
+    lost.push(30);
+    lost.sort_unstable();
+
+    assert_eq!(store.missing_indices().unwrap(), lost);
+
+    let result = api::reconstruct_from_dir(&dir, &metadata).unwrap();
+    assert_eq!(result.data, data);
+    assert_eq!(result.missing, lost);
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].contains("symbol 30 has 12 bytes"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    println!("✓ Recoverable losses reconstruct and corrupt files are reported");
+}
+
+#[test]
+fn test_reconstruct_from_dir_too_many_missing() {
+    let dir = temp_dir("unrecoverable");
+    let data = create_test_data(10_000, Some(8));
+    let (_, metadata) = prepare_file(&data, "lost.dat").unwrap();
+    let store = DirSymbolStore::open(&dir, &metadata).unwrap();
+    for (index, symbol) in encode_file_symbols(&data).unwrap().iter().enumerate() {
+        // Skip 25 symbols of the first codeword, one more than parity can cover
+        if index >= 25 {
+            store.write_symbol(index, symbol).unwrap();
+        }
+    }
+
+    assert!(matches!(
+        api::reconstruct_from_dir(&dir, &metadata),
+        Err(KontorPoRError::ErasureCoding { ref details }) if details.contains("[0]")
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_symbol_store_rejects_bad_writes_and_raw_files() {
+    let dir = temp_dir("raw");
+    let data = create_test_data(500, Some(9));
+    let (prepared, metadata) = prepare_file_raw(&data, "raw.dat").unwrap();
+    let store = DirSymbolStore::open(&dir, &metadata).unwrap();
+
+    assert!(matches!(
+        store.write_symbol(0, &[0u8; 30]),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        store.write_symbol(metadata.total_symbols(), &[0u8; 31]),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    // Raw files have no redundancy, so every symbol must be present
+    for index in 0..metadata.total_symbols() {
+        store
+            .write_symbol(index, &prepared.symbol(index).unwrap())
+            .unwrap();
+    }
+    assert_eq!(
+        api::reconstruct_from_dir(&dir, &metadata).unwrap().data,
+        data
+    );
+
+    std::fs::remove_file(store.symbol_path(3)).unwrap();
+    assert!(matches!(
+        api::reconstruct_from_dir(&dir, &metadata),
+        Err(KontorPoRError::ErasureCoding { .. })
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,172 @@
+//! Tests for tracing spans and metrics hooks inside prove and verify
+//!
+//! This module tests that:
+//! 1. The stable span names (with their structured fields) fire for a small proof
+//! 2. A registered `MetricsSink` receives timing events for every phase
+//!
+//! Spans only exist with the `instrumentation` feature (on by default).
+
+#![cfg(feature = "instrumentation")]
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem},
+    metrics::{set_metrics_sink, MetricsSink, Phase, TimingEvent},
+    FileLedger,
+};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer, Registry,
+};
+
+/// Records the name and initial fields of every new span.
+#[derive(Clone, Default)]
+struct SpanCollector {
+    spans: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+struct FieldsVisitor(String);
+
+impl Visit for FieldsVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        write!(self.0, "{}={:?} ", field.name(), value).unwrap();
+    }
+}
+
+impl<S> Layer<S> for SpanCollector
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = FieldsVisitor(String::new());
+        attrs.record(&mut fields);
+        self.spans
+            .lock()
+            .unwrap()
+            .push((attrs.metadata().name().to_string(), fields.0));
+    }
+}
+
+impl SpanCollector {
+    fn has_span(&self, name: &str, field: &str) -> bool {
+        self.spans
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(span, fields)| span == name && fields.contains(field))
+    }
+}
+
+fn two_file_setup() -> (
+    FileLedger,
+    Vec<api::PreparedFile>,
+    Vec<Challenge>,
+    Vec<String>,
+) {
+    let (prepared1, metadata1) = api::prepare_file(b"traced file one", "one.dat").unwrap();
+    let (prepared2, metadata2) = api::prepare_file(b"traced file two", "two.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata1).unwrap();
+    ledger.add_file(&metadata2).unwrap();
+
+    let file_ids = vec![metadata1.file_id.clone(), metadata2.file_id.clone()];
+    let seed = FieldElement::from(17u64);
+    let challenges = vec![
+        Challenge::new_test(metadata1, 1000, 3, seed),
+        Challenge::new_test(metadata2, 1000, 3, seed),
+    ];
+    (ledger, vec![prepared1, prepared2], challenges, file_ids)
+}
+
+#[test]
+fn test_prove_and_verify_spans_fire() {
+    println!("Testing span names and fields for a two-file proof");
+
+    let (ledger, files, challenges, file_ids) = two_file_setup();
+    let collector = SpanCollector::default();
+    let subscriber = Registry::default().with(collector.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let system = PorSystem::new(&ledger);
+        let proof = system.prove(files.iter().collect(), &challenges).unwrap();
+        assert!(system.verify(&proof, &challenges).unwrap());
+    });
+
+    for (name, field) in [
+        ("prove", "num_challenges=2"),
+        ("make_plan", "num_challenges=2"),
+        ("load_params", "files_per_step=2"),
+        ("generate_circuit_witness", "step_num=2"),
+        ("RecursiveSNARK::new", ""),
+        ("prove_step", "challenge_num=2"),
+        ("CompressedSNARK::prove", ""),
+        ("verify", "num_challenges=2"),
+        ("CompressedSNARK::verify", "num_iterations=3"),
+    ] {
+        assert!(
+            collector.has_span(name, field),
+            "Missing span {} with {}",
+            name,
+            field
+        );
+    }
+    for file_id in &file_ids {
+        assert!(
+            collector.has_span("file_witness", &format!("file_id={}", file_id)),
+            "Missing file_witness span for {}",
+            file_id
+        );
+    }
+
+    println!("✓ Expected spans fire with structured fields");
+}
+
+#[derive(Default)]
+struct CollectingSink {
+    events: Mutex<Vec<TimingEvent>>,
+}
+
+impl MetricsSink for CollectingSink {
+    fn record(&self, event: TimingEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn test_metrics_sink_receives_timing_events() {
+    println!("Testing MetricsSink timing events for prove and verify");
+
+    let (ledger, files, challenges, _) = two_file_setup();
+    let sink = Arc::new(CollectingSink::default());
+    set_metrics_sink(Some(sink.clone()));
+
+    let system = PorSystem::new(&ledger);
+    let proof = system.prove(files.iter().collect(), &challenges).unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+    set_metrics_sink(None);
+
+    let events = sink.events.lock().unwrap();
+    let has = |phase: Phase, step: Option<usize>| {
+        events.iter().any(|e| e.phase == phase && e.step == step)
+    };
+    assert!(has(Phase::Plan, None));
+    assert!(has(Phase::ParamLoad, None));
+    for step in 0..3 {
+        assert!(has(Phase::WitnessGen, Some(step)), "witness step {}", step);
+        assert!(has(Phase::Fold, Some(step)), "fold step {}", step);
+    }
+    assert!(has(Phase::Compress, None));
+    assert!(has(Phase::SnarkVerify, None));
+
+    // Events stop once the sink is removed
+    let count = events.len();
+    drop(events);
+    system.verify(&proof, &challenges).unwrap();
+    assert_eq!(sink.events.lock().unwrap().len(), count);
+
+    println!("✓ Sink receives every phase without a tracing subscriber");
+}
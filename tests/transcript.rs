@@ -0,0 +1,107 @@
+//! Tests for proof transcripts (`transcript` feature)
+//!
+//! This module tests that:
+//! 1. The prover's and verifier's transcripts of a valid proof agree on every value both
+//!    record, the prover's covering each slot's leaf index and state at every step, and
+//!    transcripts round-trip through JSON
+//! 2. After one challenge's seed is tampered with, `Transcript::diff` reports the `z0`
+//!    seed of that challenge's slot as the first divergence
+#![cfg(feature = "transcript")]
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, Transcript, TranscriptLabel},
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+#[test]
+fn test_prover_and_verifier_transcripts_agree() {
+    println!("Testing transcripts of a valid proof");
+
+    let (files, metadatas) = create_test_files(2, 300, 31);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 3, FieldElement::from(17u64)))
+        .collect();
+    let system = PorSystem::new(&ledger);
+
+    let mut prover = Transcript::new();
+    let refs = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+    let proof = system
+        .prove_with_transcript(refs, &challenges, &mut prover)
+        .unwrap();
+    let mut verifier = Transcript::new();
+    assert!(system
+        .verify_with_transcript(&proof, &challenges, &mut verifier)
+        .unwrap());
+
+    assert!(prover.diff(&verifier).is_empty());
+    assert!(verifier.diff(&prover).is_empty());
+    let count = |t: &Transcript, label| t.entries().iter().filter(|e| e.label == label).count();
+    assert_eq!(count(&prover, TranscriptLabel::LeafIndex), 3 * 2);
+    assert_eq!(count(&prover, TranscriptLabel::StateAfter), 3 * 2);
+    assert_eq!(count(&prover, TranscriptLabel::FinalState), 1);
+    assert_eq!(count(&verifier, TranscriptLabel::Seed), 2);
+    assert_eq!(count(&verifier, TranscriptLabel::LeafIndex), 0);
+
+    let json = prover.to_json().unwrap();
+    assert_eq!(Transcript::from_json(&json).unwrap(), prover);
+    assert!(matches!(
+        Transcript::from_json("{\"entries\": 1}"),
+        Err(KontorPoRError::Serialization(_))
+    ));
+
+    println!("✓ Transcripts agree");
+}
+
+#[test]
+fn test_diff_pinpoints_tampered_seed() {
+    println!("Testing that a tampered challenge seed is pinpointed");
+
+    let (files, metadatas) = create_test_files(3, 300, 37);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, m)| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(50 + i as u64)))
+        .collect();
+    let system = PorSystem::new(&ledger);
+
+    let mut prover = Transcript::new();
+    let refs = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+    let proof = system
+        .prove_with_transcript(refs, &challenges, &mut prover)
+        .unwrap();
+
+    // The verifier receives a challenge whose seed differs from the one proven
+    let tampered_idx = 1;
+    let mut tampered = challenges.clone();
+    tampered[tampered_idx].seed += FieldElement::from(1u64);
+    let mut verifier = Transcript::new();
+    assert!(matches!(
+        system.verify_with_transcript(&proof, &tampered, &mut verifier),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+
+    let divergences = prover.diff(&verifier);
+    assert_eq!(divergences.len(), 1, "{:#?}", divergences);
+    let first = &divergences[0];
+    println!("First divergence: {}", first);
+    let ours = first.ours.as_ref().unwrap();
+    let theirs = first.theirs.as_ref().unwrap();
+    assert_eq!(ours.label, TranscriptLabel::Seed);
+    assert_eq!(ours.step, None);
+    assert_eq!(ours.slot, Some(proof.challenge_slots[tampered_idx]));
+    assert_eq!(
+        ours.file_id.as_deref(),
+        Some(metadatas[tampered_idx].file_id.as_str())
+    );
+    assert_eq!((theirs.label, theirs.slot), (ours.label, ours.slot));
+    assert_ne!(theirs.value, ours.value);
+    assert!(!first.context.is_empty());
+
+    println!("✓ Divergence found at the tampered slot's seed");
+}
@@ -0,0 +1,257 @@
+//! Tests for two-phase verification (`PorSystem::preverify` and `PorSystem::verify_snark`)
+//!
+//! This module tests that:
+//! 1. A valid proof preverifies, its statement survives JSON and bincode round trips, and
+//!    the decoded statement verifies against a system without the proof's ledger
+//! 2. Request-level failures (challenge IDs, limits, hash profile, binding, indices,
+//!    unknown roots) are errors from `preverify`, the same errors `verify` returns
+//! 3. Public inputs that do not describe the challenges make the statement inadmissible,
+//!    and `verify` and `verify_snark` both answer `Ok(false)`
+//! 4. Tampering only the SNARK can detect (outputs, consistent lies about indices) passes
+//!    `preverify` and fails `verify_snark`
+//! 5. A statement cannot be used for another proof
+
+use kontor_crypto::{
+    api::{
+        Challenge, FieldElement, HashProfile, PorSystem, PreverifiedStatement, Proof, VerifyOptions,
+    },
+    config::PublicIOLayout,
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+/// A valid 2-file proof with its challenges and ledger.
+fn two_file_proof(seed: u64) -> (Proof, Vec<Challenge>, FileLedger) {
+    let (files, metadatas) = create_test_files(2, 300, 97);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(seed)))
+        .collect();
+    let prepared = metadatas.iter().map(|m| &files[&m.file_id]).collect();
+    let proof = PorSystem::new(&ledger)
+        .prove(prepared, &challenges)
+        .unwrap();
+    (proof, challenges, ledger)
+}
+
+/// A copy of `proof` to tamper with.
+fn copy(proof: &Proof) -> Proof {
+    Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap()
+}
+
+fn error_name(error: &KontorPoRError) -> String {
+    format!("{:?}", error)
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_valid_proof_statement_crosses_processes() {
+    println!("Testing a serialized preverified statement");
+
+    let (proof, challenges, ledger) = two_file_proof(11);
+    let system = PorSystem::new(&ledger);
+    let statement = system.preverify(&proof, &challenges).unwrap();
+    assert!(statement.is_admissible());
+    assert_eq!(statement.num_steps, 2);
+    assert_eq!(statement.shape.files_per_step, 2);
+    assert_eq!(statement.z0_primary, proof.public_inputs);
+
+    let json = serde_json::to_string(&statement).unwrap();
+    let from_json: PreverifiedStatement = serde_json::from_str(&json).unwrap();
+    let bytes = bincode::serialize(&statement).unwrap();
+    let from_bincode: PreverifiedStatement = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(from_json, statement);
+    assert_eq!(from_bincode, statement);
+
+    // The SNARK phase needs no ledger
+    let empty = FileLedger::new();
+    let worker = PorSystem::new(&empty);
+    assert!(worker.verify_snark(&from_json, &proof).unwrap());
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    println!("✓ Statement verified after a JSON round trip");
+}
+
+#[test]
+fn test_failures_land_in_the_right_phase() {
+    println!("Testing which phase rejects each negative case");
+
+    let (proof, challenges, ledger) = two_file_proof(11);
+    let system = PorSystem::new(&ledger);
+    let layout = PublicIOLayout::new(proof.ledger_indices.len());
+
+    // --- Errors from preverify, identical to verify's ---
+    let reseeded: Vec<Challenge> = challenges
+        .iter()
+        .map(|c| {
+            let mut c = c.clone();
+            c.seed = FieldElement::from(12u64);
+            c
+        })
+        .collect();
+    let mut wrong_root = copy(&proof);
+    wrong_root.ledger_root += FieldElement::from(1u64);
+    let mut out_of_range = copy(&proof);
+    out_of_range.ledger_indices[0] = 1 << 20;
+    let mut short_indices = copy(&proof);
+    short_indices.ledger_indices.pop();
+    let mut other_profile = copy(&proof);
+    other_profile.hash_profile = HashProfile::Conservative;
+    let mut file_bound = copy(&proof);
+    file_bound.shape.aggregated_tree_depth = 0;
+
+    let default = VerifyOptions::default();
+    let cases: Vec<(&str, &Proof, &[Challenge], VerifyOptions, &str)> = vec![
+        (
+            "other challenges",
+            &proof,
+            &reseeded,
+            default.clone(),
+            "InvalidInput",
+        ),
+        (
+            "missing challenge",
+            &proof,
+            &challenges[..1],
+            default.clone(),
+            "InvalidInput",
+        ),
+        (
+            "unknown root",
+            &wrong_root,
+            &challenges,
+            default.clone(),
+            "InvalidLedgerRoot",
+        ),
+        (
+            "index out of range",
+            &out_of_range,
+            &challenges,
+            default.clone(),
+            "InvalidInput",
+        ),
+        (
+            "short indices",
+            &short_indices,
+            &challenges,
+            default.clone(),
+            "InvalidInput",
+        ),
+        (
+            "hash profile",
+            &other_profile,
+            &challenges,
+            default.clone(),
+            "HashProfileMismatch",
+        ),
+        (
+            "binding",
+            &file_bound,
+            &challenges,
+            default.clone(),
+            "InvalidInput",
+        ),
+        (
+            "step limit",
+            &proof,
+            &challenges,
+            VerifyOptions::default().with_max_steps(1),
+            "TooManySteps",
+        ),
+        (
+            "future challenge",
+            &proof,
+            &challenges,
+            VerifyOptions::default().with_current_block_height(999),
+            "ChallengeFromFuture",
+        ),
+    ];
+    for (what, proof, challenges, options, expected) in cases {
+        let preverified = system.preverify_with_options(proof, challenges, &options);
+        let verified = system.verify_with_options(proof, challenges, &options);
+        let (Err(pre), Err(full)) = (&preverified, &verified) else {
+            panic!(
+                "{}: expected errors, got {:?} and {:?}",
+                what,
+                preverified.map(|_| ()),
+                verified
+            );
+        };
+        assert_eq!(error_name(pre), expected, "{}: {:?}", what, pre);
+        assert_eq!(pre.to_string(), full.to_string(), "{}", what);
+    }
+
+    // --- Inadmissible statements: verify answers Ok(false) without the SNARK ---
+    let mut wrong_nonce = copy(&proof);
+    wrong_nonce.challenge_nonce_commitment += FieldElement::from(1u64);
+    let mut wrong_inputs = copy(&proof);
+    wrong_inputs.public_inputs[layout.idx_seed(0)] += FieldElement::from(1u64);
+    let mut swapped_slots = copy(&proof);
+    swapped_slots.challenge_slots.swap(0, 1);
+    for (what, tampered) in [
+        ("nonce commitment", &wrong_nonce),
+        ("public inputs", &wrong_inputs),
+        ("challenge slots", &swapped_slots),
+    ] {
+        let statement = system.preverify(tampered, &challenges).unwrap();
+        assert!(!statement.is_admissible(), "{}", what);
+        assert!(statement.rejection.is_some(), "{}", what);
+        assert!(
+            !system.verify_snark(&statement, tampered).unwrap(),
+            "{}",
+            what
+        );
+        assert!(!system.verify(tampered, &challenges).unwrap(), "{}", what);
+    }
+
+    // --- SNARK failures: preverify admits the proof, verify_snark rejects it ---
+    let mut wrong_outputs = copy(&proof);
+    wrong_outputs.public_outputs[layout.idx_leaf(0)] += FieldElement::from(1u64);
+    // Swapped indices, recorded consistently in the public inputs, are only caught by
+    // the circuit's Merkle path check
+    let mut swapped_indices = copy(&proof);
+    swapped_indices.ledger_indices.swap(0, 1);
+    for (slot, index) in swapped_indices
+        .ledger_indices
+        .clone()
+        .into_iter()
+        .enumerate()
+    {
+        swapped_indices.public_inputs[layout.idx_ledger(slot)] = FieldElement::from(index as u64);
+    }
+    for (what, tampered) in [
+        ("public outputs", &wrong_outputs),
+        ("swapped indices", &swapped_indices),
+    ] {
+        let statement = system.preverify(tampered, &challenges).unwrap();
+        assert!(statement.is_admissible(), "{}", what);
+        assert!(
+            !system.verify_snark(&statement, tampered).unwrap(),
+            "{}",
+            what
+        );
+        assert!(!system.verify(tampered, &challenges).unwrap(), "{}", what);
+    }
+
+    println!("✓ Every negative case failed in its phase");
+}
+
+#[test]
+fn test_statement_is_bound_to_its_proof() {
+    let (proof, challenges, ledger) = two_file_proof(11);
+    let system = PorSystem::new(&ledger);
+    let statement = system.preverify(&proof, &challenges).unwrap();
+
+    let mut other = copy(&proof);
+    other.public_outputs[0] += FieldElement::from(1u64);
+    assert!(matches!(
+        system.verify_snark(&statement, &other),
+        Err(KontorPoRError::InvalidInput(_))
+    ));
+}
@@ -192,27 +192,19 @@ fn test_empty_file_specs_succeeds_with_empty_setup() {
 }
 
 #[test]
-fn test_zero_size_file_rejected() {
-    // Test that zero-size files are explicitly rejected.
-    // The prepare_file function returns EmptyData error for empty data.
+fn test_zero_size_file_supported() {
+    // Zero-size files are supported end to end as a depth-0 tree with one padding leaf.
     let config = TestConfig {
         file_specs: vec![FileSpec::from_size(0)],
         ..Default::default()
     };
 
-    let result = setup_test_scenario(&config);
-    let error = match result {
-        Err(e) => e,
-        Ok(_) => panic!("Zero-size files MUST be rejected - prepare_file returns EmptyData error"),
-    };
-    let error_msg = format!("{}", error);
-    assert!(
-        error_msg.contains("empty") || error_msg.contains("Empty"),
-        "Error should mention empty data: {}",
-        error_msg
-    );
+    let setup = setup_test_scenario(&config).expect("Zero-size file spec should set up");
+    assert_eq!(setup.metadatas[0].original_size, 0);
+    assert_eq!(api::tree_depth_from_metadata(&setup.metadatas[0]), 0);
+    assert_prove_and_verify_succeeds(setup);
 
-    println!("✓ Zero-size file correctly rejected with EmptyData error");
+    println!("✓ Zero-size file accepted as a single padding leaf");
 }
 
 #[test]
@@ -371,7 +363,7 @@ fn test_prove_fails_with_missing_file() {
     setup.files.remove(&first_key);
 
     // This should fail during prove() with a missing file error
-    assert_prove_fails(setup, "not found");
+    assert_prove_fails(setup, "Missing prepared file");
 
     println!("✓ Missing file correctly rejected");
 }
@@ -0,0 +1,122 @@
+//! Tests for the verification outcome cache
+//!
+//! This module tests that:
+//! 1. Repeat verifications of the same proof and challenges are answered from the cache
+//! 2. Entries survive ledger additions while their root stays historical, and are dropped
+//!    once `set_historical_roots(vec![])` prunes it
+//! 3. A tampered proof of the same serialized size does not hit the original's entry
+//! 4. The least recently used entry is evicted when the cache is full
+
+use kontor_crypto::{
+    api::{Challenge, FieldElement, PorSystem, Proof, VerificationCache, VerifyOptions},
+    KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_multi_file_ledger, create_test_files};
+
+#[test]
+fn test_cache_hits_and_root_invalidation() {
+    println!("Testing cached verification across ledger changes");
+
+    let (files, metadatas) = create_test_files(3, 200, 80);
+    let mut ledger = create_multi_file_ledger(&[&metadatas[0], &metadatas[1]]);
+    let challenges: Vec<Challenge> = metadatas[..2]
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, FieldElement::from(5u64)))
+        .collect();
+    let cache = VerificationCache::new(8);
+
+    let proof = {
+        let system = PorSystem::new(&ledger).with_verification_cache(&cache);
+        let proof = system
+            .prove(
+                vec![&files[&metadatas[0].file_id], &files[&metadatas[1].file_id]],
+                &challenges,
+            )
+            .unwrap();
+
+        assert!(system.verify(&proof, &challenges).unwrap());
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+        assert!(system.verify(&proof, &challenges).unwrap());
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(cache.len(), 1);
+
+        // A step limit is enforced before the cache is consulted, never answered from it
+        let strict = VerifyOptions::default().with_max_steps(1);
+        assert!(matches!(
+            system.verify_with_options(&proof, &challenges, &strict),
+            Err(KontorPoRError::TooManySteps { .. })
+        ));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        proof
+    };
+
+    // The old root stays valid as a historical root, so the entry still applies
+    ledger.add_file(&metadatas[2]).unwrap();
+    {
+        let system = PorSystem::new(&ledger).with_verification_cache(&cache);
+        assert!(system.verify(&proof, &challenges).unwrap());
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+    }
+
+    // Once the root is pruned the entry is dropped and the proof is verified again
+    ledger.set_historical_roots(vec![]).unwrap();
+    let system = PorSystem::new(&ledger).with_verification_cache(&cache);
+    assert!(matches!(
+        system.verify(&proof, &challenges),
+        Err(KontorPoRError::InvalidLedgerRoot { .. })
+    ));
+    assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    assert!(
+        system.verify(&proof, &challenges).is_err(),
+        "unknown-root errors are not cached"
+    );
+    assert_eq!((cache.hits(), cache.misses()), (2, 3));
+    assert!(cache.is_empty());
+
+    println!("✓ Cached outcomes follow the ledger's accepted roots");
+}
+
+#[test]
+fn test_tampered_proof_misses_and_eviction() {
+    println!("Testing cache keys for tampered proofs");
+
+    let (files, metadatas) = create_test_files(2, 200, 81);
+    let ledger = create_multi_file_ledger(&metadatas.iter().collect::<Vec<_>>());
+    let cache = VerificationCache::new(1);
+    let system = PorSystem::new(&ledger).with_verification_cache(&cache);
+    let challenges = vec![Challenge::new_test(
+        metadatas[0].clone(),
+        1000,
+        2,
+        FieldElement::from(6u64),
+    )];
+    let proof = system
+        .prove(vec![&files[&metadatas[0].file_id]], &challenges)
+        .unwrap();
+    assert!(system.verify(&proof, &challenges).unwrap());
+
+    let mut tampered = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    tampered.challenge_nonce_commitment += FieldElement::from(1u64);
+    assert_eq!(
+        tampered.to_bytes().unwrap().len(),
+        proof.to_bytes().unwrap().len()
+    );
+
+    assert!(!system.verify(&tampered, &challenges).unwrap());
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+    assert!(!system.verify(&tampered, &challenges).unwrap());
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    // With capacity one, caching the tampered outcome evicted the original
+    assert_eq!(cache.len(), 1);
+    assert!(system.verify(&proof, &challenges).unwrap());
+    assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.capacity(), 1);
+
+    println!("✓ Tampered proofs are verified separately and the cache stays bounded");
+}
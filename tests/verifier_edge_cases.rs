@@ -27,10 +27,13 @@ fn test_verifier_rejects_out_of_range_ledger_index() {
         .prove(vec![&prepared_a, &prepared_b], &challenges)
         .expect("Should generate a valid multi-file proof");
 
-    assert!(proof.aggregated_tree_depth > 0, "Must be multi-file proof");
+    assert!(
+        proof.shape.aggregated_tree_depth > 0,
+        "Must be multi-file proof"
+    );
 
     // Make ledger_index out of range: max is (1<<depth)-1, so choose 1<<depth.
-    let out_of_range = 1usize << proof.aggregated_tree_depth;
+    let out_of_range = 1usize << proof.shape.aggregated_tree_depth;
     proof.ledger_indices[0] = out_of_range;
 
     let res = system.verify(&proof, &challenges);
@@ -73,10 +76,9 @@ fn test_verifier_rejects_ledger_indices_length_mismatch() {
 }
 
 #[test]
-fn test_duplicate_file_challenges_fail_verification() {
-    // VERIF-01: Duplicate challenges (same file, same params) produce invalid proofs.
-    // This is expected - duplicate challenges are not a supported use case.
-    // The verifier correctly rejects these proofs.
+fn test_duplicate_file_challenges_are_collapsed() {
+    // VERIF-01: Duplicate challenges (same file, same params) are proven once.
+    // The proof still lists every challenge and verifies against the duplicated list.
     println!("Testing duplicate file challenges in multi-file proof");
 
     let data = vec![1u8; 100];
@@ -97,15 +99,16 @@ fn test_duplicate_file_challenges_fail_verification() {
     let proof = system
         .prove(vec![&prepared], &challenges)
         .expect("Prove accepts duplicate challenges");
+    assert_eq!(proof.challenge_ids.len(), 3);
+    assert_eq!(proof.challenge_slots, vec![0, 0, 0]);
 
-    // Verification should fail - duplicate challenges produce invalid proofs
     let verify_result = system.verify(&proof, &challenges);
     assert!(
-        matches!(verify_result, Ok(false) | Err(_)),
-        "Duplicate challenges MUST fail verification"
+        matches!(verify_result, Ok(true)),
+        "Collapsed duplicate challenges must verify, got {verify_result:?}"
     );
 
-    println!("✓ Duplicate challenges correctly rejected by verifier");
+    println!("✓ Duplicate challenges proven once and verified");
 }
 
 #[test]
@@ -151,26 +154,21 @@ fn test_malformed_metadata_non_power_of_two_padded_len() {
         FieldElement::from(999u64),
     );
 
-    // Create ledger with malformed metadata for verification
+    // The ledger refuses the malformed metadata outright
     let mut malformed_ledger = kontor_crypto::FileLedger::new();
-    malformed_ledger.add_file(&malformed_metadata).unwrap();
-
-    // Try to verify with malformed metadata
-    let malformed_system = kontor_crypto::api::PorSystem::new(&malformed_ledger);
-    let malformed_result = malformed_system.verify(&proof, &[malformed_challenge]);
-
-    match malformed_result {
-        Ok(is_valid) => {
-            assert!(
-                !is_valid,
-                "Proof with non-power-of-two padded_len should not verify as valid"
-            );
-            println!("✓ Malformed metadata (padded_len=7) correctly rejected (returned false)");
-        }
-        Err(e) => {
-            println!("✓ Malformed metadata correctly caused error: {}", e);
-        }
-    }
+    assert!(matches!(
+        malformed_ledger.add_file(&malformed_metadata),
+        Err(KontorPoRError::InvalidMetadata { field, .. }) if field == "padded_len"
+    ));
+    assert_eq!(malformed_ledger.file_count(), 0);
+
+    // Verifying against the valid ledger rejects it before trailing_zeros reads a depth
+    let malformed_result = system.verify(&proof, &[malformed_challenge]);
+    assert!(matches!(
+        malformed_result,
+        Err(KontorPoRError::InvalidMetadata { field, .. }) if field == "padded_len"
+    ));
+    println!("✓ Malformed metadata (padded_len=7) rejected with InvalidMetadata");
 }
 
 #[test]
@@ -241,20 +239,50 @@ fn test_inconsistent_metadata_fields() {
         FieldElement::from(123u64),
     );
 
-    // Create ledger with zero padded_len metadata
+    // Zero is not a power of two, so the ledger and the verifier both reject it
     let mut bad_ledger2 = kontor_crypto::FileLedger::new();
-    bad_ledger2.add_file(&inconsistent_meta2).unwrap();
-
-    let bad_system2 = kontor_crypto::api::PorSystem::new(&bad_ledger2);
-    let result2 = bad_system2.verify(&proof, &[bad_challenge2]);
+    assert!(matches!(
+        bad_ledger2.add_file(&inconsistent_meta2),
+        Err(KontorPoRError::InvalidMetadata { field, .. }) if field == "padded_len"
+    ));
+    assert!(matches!(
+        system.verify(&proof, &[bad_challenge2]),
+        Err(KontorPoRError::InvalidMetadata { field, .. }) if field == "padded_len"
+    ));
+    println!("✓ Metadata with padded_len=0 rejected with InvalidMetadata");
+}
 
-    match result2 {
-        Ok(is_valid) => {
-            assert!(!is_valid, "Metadata with padded_len=0 should fail");
-            println!("✓ Metadata with padded_len=0 rejected");
-        }
-        Err(_) => {
-            println!("✓ Metadata with padded_len=0 caused error");
-        }
+#[test]
+fn test_file_metadata_validate() {
+    println!("Testing FileMetadata::validate on each checked field");
+
+    let (_, metadata) = api::prepare_file(&[7u8; 500], "checked.dat").unwrap();
+    metadata.validate().unwrap();
+
+    let mut empty_id = metadata.clone();
+    empty_id.file_id.clear();
+    let mut wide = metadata.clone();
+    wide.padded_len = 1000;
+    let mut oversized = metadata.clone();
+    oversized.original_size = usize::MAX;
+    for (bad, expected) in [
+        (&empty_id, "file_id"),
+        (&wide, "padded_len"),
+        (&oversized, "original_size"),
+    ] {
+        assert!(matches!(
+            bad.validate(),
+            Err(KontorPoRError::InvalidMetadata { field, .. }) if field == expected
+        ));
     }
+
+    // A batch with one invalid entry leaves the ledger untouched
+    let mut ledger = kontor_crypto::FileLedger::new();
+    assert!(matches!(
+        ledger.add_files([&metadata, &wide]),
+        Err(KontorPoRError::InvalidMetadata { .. })
+    ));
+    assert_eq!(ledger.file_count(), 0);
+
+    println!("✓ Inconsistent metadata is rejected field by field");
 }
@@ -0,0 +1,60 @@
+//! Tests for verifier-only builds against a committed proof fixture (`test-vectors` feature)
+//!
+//! This module tests that:
+//! 1. The committed fixture's proof verifies from its JSON alone, with or without the
+//!    `prover` feature
+//! 2. The proof is rejected for other challenges and once its ledger root is unknown
+//!
+//! Run in the verifier-only configuration with
+//! `cargo test --no-default-features --features verifier,test-vectors --test verifier_only`.
+//! Circuit or encoding changes invalidate the fixture; regenerate it with
+//! `cargo run --release --features test-vectors -- fixtures --file-sizes 100,1000
+//! --num-challenges 2 --out tests/data/verifier_fixture.json`.
+
+#![cfg(feature = "test-vectors")]
+
+use kontor_crypto::{
+    api::{FieldElement, PorSystem},
+    fixtures::Fixture,
+    FileLedger,
+};
+
+fn committed_fixture() -> Fixture {
+    Fixture::from_json(include_str!("data/verifier_fixture.json")).unwrap()
+}
+
+#[test]
+fn test_committed_proof_verifies() {
+    println!("Testing the committed fixture proof in this build configuration");
+
+    let fixture = committed_fixture();
+    let ledger = fixture.ledger().unwrap();
+    let challenges = fixture.challenges().unwrap();
+    let proof = fixture.proof().unwrap();
+
+    assert_eq!(proof.challenge_ids.len(), fixture.files.len());
+    assert!(PorSystem::new(&ledger).verify(&proof, &challenges).unwrap());
+
+    println!("✓ Committed proof verified");
+}
+
+#[test]
+fn test_committed_proof_rejected_for_other_statements() {
+    let fixture = committed_fixture();
+    let ledger = fixture.ledger().unwrap();
+    let proof = fixture.proof().unwrap();
+
+    // Challenges with another seed have other IDs
+    let mut reseeded = fixture.challenges().unwrap();
+    reseeded[0].seed += FieldElement::from(1u64);
+    assert!(PorSystem::new(&ledger).verify(&proof, &reseeded).is_err());
+
+    // A ledger that never held the proof's root
+    let mut other = FileLedger::new();
+    other
+        .add_files(&fixture.file_metadata().unwrap()[..1])
+        .unwrap();
+    assert!(PorSystem::new(&other)
+        .verify(&proof, &fixture.challenges().unwrap())
+        .is_err());
+}
@@ -0,0 +1,90 @@
+//! Tests for verification resource limits
+//!
+//! This module tests that:
+//! 1. Challenge sets implying too many steps are rejected before any SNARK work
+//! 2. An exhausted time budget stops verification with `VerificationTimedOut`
+//! 3. Default options and generous limits verify like `PorSystem::verify`
+
+use kontor_crypto::{
+    api::{self, Challenge, FieldElement, PorSystem, VerifyOptions},
+    FileLedger, KontorPoRError,
+};
+use std::time::{Duration, Instant};
+
+fn small_proof_setup() -> (FileLedger, api::Proof, Vec<Challenge>, api::FileMetadata) {
+    let (prepared, metadata) = api::prepare_file(b"verify options test file", "v.dat").unwrap();
+    let mut ledger = FileLedger::new();
+    ledger.add_file(&metadata).unwrap();
+
+    let challenge = Challenge::new_test(metadata.clone(), 1000, 2, FieldElement::from(9u64));
+    let proof = PorSystem::new(&ledger)
+        .prove(vec![&prepared], std::slice::from_ref(&challenge))
+        .unwrap();
+    (ledger, proof, vec![challenge], metadata)
+}
+
+#[test]
+fn test_max_steps_rejects_early() {
+    println!("Testing early rejection of a 10k-step challenge set");
+
+    let (ledger, proof, _, metadata) = small_proof_setup();
+    let system = PorSystem::new(&ledger);
+
+    let oversized = vec![Challenge::new_test(
+        metadata,
+        1000,
+        10_000,
+        FieldElement::from(9u64),
+    )];
+    let options = VerifyOptions::default().with_max_steps(100);
+
+    let start = Instant::now();
+    let result = system.verify_with_options(&proof, &oversized, &options);
+    let elapsed = start.elapsed();
+
+    assert!(
+        matches!(
+            result,
+            Err(KontorPoRError::TooManySteps {
+                steps: 10_000,
+                max: 100
+            })
+        ),
+        "10k-step challenges must be rejected with max_steps = 100"
+    );
+    // Rejection happens before parameter loading or SNARK verification
+    assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+
+    println!("✓ Oversized step count rejected in {:?}", elapsed);
+}
+
+#[test]
+fn test_timeout_stops_verification() {
+    let (ledger, proof, challenges, _) = small_proof_setup();
+    let system = PorSystem::new(&ledger);
+
+    let options = VerifyOptions::default().with_timeout(Duration::ZERO);
+    assert!(matches!(
+        system.verify_with_options(&proof, &challenges, &options),
+        Err(KontorPoRError::VerificationTimedOut { timeout, .. }) if timeout == Duration::ZERO
+    ));
+}
+
+#[test]
+fn test_default_and_generous_options_verify() {
+    let (ledger, proof, challenges, _) = small_proof_setup();
+    let system = PorSystem::new(&ledger);
+
+    assert_eq!(VerifyOptions::default().max_steps, None);
+    assert_eq!(VerifyOptions::default().timeout, None);
+    assert!(system
+        .verify_with_options(&proof, &challenges, &VerifyOptions::default())
+        .unwrap());
+
+    let options = VerifyOptions::default()
+        .with_max_steps(2)
+        .with_timeout(Duration::from_secs(600));
+    assert!(system
+        .verify_with_options(&proof, &challenges, &options)
+        .unwrap());
+}
@@ -0,0 +1,119 @@
+//! Tests for scrubbing plaintext buffers (requires the `zeroize` feature)
+//!
+//! This module tests that:
+//! 1. Preparing, encoding and reconstructing with scrubbing enabled still round-trips the
+//!    data, over GF(2^8) and GF(2^16), and `reconstruct_file_zeroizing` returns the plaintext
+//! 2. `PreparedFile::zeroize` drops the tree but keeps the public file ID and root
+//! 3. Proving with a zeroized file fails with `PreparedFileZeroized` instead of producing a
+//!    proof, alone or next to intact files, while the intact files still prove
+
+#![cfg(feature = "zeroize")]
+
+use kontor_crypto::{
+    api::{self, Challenge, ErasureField, FieldElement, PorSystem, Zeroizing},
+    erasure::{decode_byte_range_with, encode_file_symbols_with, ErasureConfig},
+    FileLedger, KontorPoRError,
+};
+
+mod common;
+use common::fixtures::{create_test_data, create_test_files};
+
+#[test]
+fn test_scrubbed_buffers_round_trip() {
+    println!("Testing round trips with buffer scrubbing enabled");
+
+    let data = create_test_data(231 * 31 + 500, Some(1666));
+    for field in [ErasureField::Gf8, ErasureField::Gf16] {
+        let config = ErasureConfig::field(field);
+        let (_, metadata) = api::prepare_file_with_erasure(&data, "secret.dat", config).unwrap();
+        let symbols = encode_file_symbols_with(&data, config).unwrap();
+        assert_eq!(symbols.len(), metadata.total_symbols());
+
+        let mut damaged: Vec<Option<Vec<u8>>> = symbols.into_iter().map(Some).collect();
+        for index in [0, 3, 30] {
+            damaged[index] = None;
+        }
+
+        let plaintext: Zeroizing<Vec<u8>> =
+            api::reconstruct_file_zeroizing(&damaged, &metadata).unwrap();
+        assert_eq!(plaintext.as_slice(), data.as_slice(), "{field:?}");
+        assert_eq!(api::reconstruct_file(&damaged, &metadata).unwrap(), data);
+
+        let range = decode_byte_range_with(
+            &damaged,
+            metadata.num_codewords(),
+            metadata.original_size,
+            40..7_200,
+            config,
+        )
+        .unwrap();
+        assert_eq!(range, data[40..7_200]);
+    }
+
+    let (_, raw) = api::prepare_file_raw(&data, "secret.dat").unwrap();
+    let (_, expected) = api::prepare_file_raw(&data, "secret.dat").unwrap();
+    assert_eq!(raw, expected);
+
+    println!("✓ Scrubbing leaves results unchanged");
+}
+
+#[test]
+fn test_zeroize_keeps_public_fields() {
+    println!("Testing PreparedFile::zeroize");
+
+    let data = create_test_data(2_000, Some(1667));
+    let (mut prepared, metadata) = api::prepare_file(&data, "secret.dat").unwrap();
+    assert!(!prepared.is_zeroized());
+    assert!(prepared.stored_nodes() > 0);
+
+    prepared.zeroize();
+    assert!(prepared.is_zeroized());
+    assert_eq!(prepared.stored_nodes(), 0);
+    assert_eq!(prepared.file_id, metadata.file_id);
+    assert_eq!(prepared.root, metadata.root);
+
+    // Zeroizing twice is harmless
+    prepared.zeroize();
+    assert!(prepared.is_zeroized());
+
+    println!("✓ Zeroized files keep only their public fields");
+}
+
+#[test]
+fn test_zeroized_file_cannot_prove() {
+    println!("Testing proving with a zeroized file");
+
+    let (mut files, metadatas) = create_test_files(2, 400, 1668);
+    let mut ledger = FileLedger::new();
+    ledger.add_files(&metadatas).unwrap();
+    let system = PorSystem::new(&ledger);
+    let seed = FieldElement::from(1668u64);
+    let challenges: Vec<Challenge> = metadatas
+        .iter()
+        .map(|m| Challenge::new_test(m.clone(), 1000, 2, seed))
+        .collect();
+
+    let zeroized_id = metadatas[0].file_id.clone();
+    files.get_mut(&zeroized_id).unwrap().zeroize();
+
+    let result = system.prove(vec![&files[&zeroized_id]], &challenges[..1]);
+    assert!(
+        matches!(result, Err(KontorPoRError::PreparedFileZeroized { ref file_id }) if *file_id == zeroized_id),
+        "expected PreparedFileZeroized, got {:?}",
+        result.err()
+    );
+
+    let result = system.prove(files.values().collect(), &challenges);
+    assert!(
+        matches!(result, Err(KontorPoRError::PreparedFileZeroized { ref file_id }) if *file_id == zeroized_id),
+        "expected PreparedFileZeroized, got {:?}",
+        result.err()
+    );
+
+    // The untouched file still proves on its own
+    let intact = &files[&metadatas[1].file_id];
+    let proof = system.prove(vec![intact], &challenges[1..]).unwrap();
+    assert!(system.verify(&proof, &challenges[1..]).unwrap());
+
+    println!("✓ Zeroized files fail cleanly");
+}